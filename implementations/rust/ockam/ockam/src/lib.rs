@@ -75,7 +75,10 @@ mod unique;
 
 pub use error::OckamError;
 pub use metadata::OckamMessage;
-pub use relay_service::{RelayService, RelayServiceOptions};
+pub use relay_service::{
+    RelayRegistry, RelayRegistryEntry, RelayService, RelayServiceOptions,
+    DEFAULT_RELAY_OWNERSHIP_TTL,
+};
 pub use system::{SystemBuilder, SystemHandler, WorkerSystem};
 pub use unique::unique_with_prefix;
 
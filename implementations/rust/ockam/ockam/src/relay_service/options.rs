@@ -1,14 +1,24 @@
+use crate::relay_service::RelayRegistry;
+use core::time::Duration;
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::flow_control::{FlowControlId, FlowControls};
 use ockam_core::{Address, AllowAll, IncomingAccessControl};
 
+/// Time during which the owner of a relay name keeps it once its relay is not registered anymore
+pub const DEFAULT_RELAY_OWNERSHIP_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// Trust Options for a Forwarding Service
 pub struct RelayServiceOptions {
     pub(super) service_incoming_access_control: Arc<dyn IncomingAccessControl>,
     pub(super) relays_incoming_access_control: Arc<dyn IncomingAccessControl>,
     pub(super) consumer_service: Vec<FlowControlId>,
     pub(super) consumer_relay: Vec<FlowControlId>,
+    pub(super) reservations: Vec<(String, Arc<dyn IncomingAccessControl>)>,
+    pub(super) registry: RelayRegistry,
+    pub(super) relay_ttl: Option<Duration>,
+    pub(super) ownership_ttl: Duration,
 }
 
 impl RelayServiceOptions {
@@ -19,6 +29,10 @@ impl RelayServiceOptions {
             relays_incoming_access_control: Arc::new(AllowAll),
             consumer_service: vec![],
             consumer_relay: vec![],
+            reservations: vec![],
+            registry: RelayRegistry::default(),
+            relay_ttl: None,
+            ownership_ttl: DEFAULT_RELAY_OWNERSHIP_TTL,
         }
     }
 
@@ -72,6 +86,47 @@ impl RelayServiceOptions {
         self
     }
 
+    /// Only allow the registration of relays whose name starts with the given prefix
+    /// when the registration message is authorized by the given access control,
+    /// for instance an ABAC policy or an identifier check.
+    /// Several reservations can apply to the same name, they must all be satisfied
+    pub fn with_reserved_prefix(
+        mut self,
+        prefix: impl Into<String>,
+        access_control: Arc<dyn IncomingAccessControl>,
+    ) -> Self {
+        self.reservations.push((prefix.into(), access_control));
+        self
+    }
+
+    /// Keep track of the created relays in the given registry
+    pub fn with_registry(mut self, registry: RelayRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Registry of the created relays
+    pub fn registry(&self) -> RelayRegistry {
+        self.registry.clone()
+    }
+
+    /// Stop relays which were not registered again for longer than the given duration.
+    /// Clients must then send heartbeats more often than that, which is the case for
+    /// [`RemoteRelay::create_static`](crate::remote::RemoteRelay::create_static).
+    /// By default, relays never expire
+    pub fn with_relay_ttl(mut self, ttl: Duration) -> Self {
+        self.relay_ttl = Some(ttl);
+        self
+    }
+
+    /// Release the ownership of a relay name when its relay is not registered anymore since
+    /// the given duration, so that other identities can register it.
+    /// By default, see [`DEFAULT_RELAY_OWNERSHIP_TTL`]
+    pub fn with_ownership_ttl(mut self, ttl: Duration) -> Self {
+        self.ownership_ttl = ttl;
+        self
+    }
+
     pub(super) fn setup_flow_control_for_relay_service(
         &self,
         flow_controls: &FlowControls,
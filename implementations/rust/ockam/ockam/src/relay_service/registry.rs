@@ -0,0 +1,214 @@
+use core::time::Duration;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Address, Route};
+use ockam_identity::utils::now;
+use ockam_identity::{Identifier, TimestampInSeconds};
+
+/// Information about a relay registered at a [`RelayService`](crate::RelayService)
+#[derive(Clone, Debug)]
+pub struct RelayRegistryEntry {
+    address: Address,
+    owner: Option<Identifier>,
+    forward_route: Route,
+    renewable: bool,
+    created_at: TimestampInSeconds,
+    last_seen: TimestampInSeconds,
+    messages_forwarded: u64,
+    bytes_forwarded: u64,
+}
+
+impl RelayRegistryEntry {
+    pub(super) fn new(
+        address: Address,
+        owner: Option<Identifier>,
+        forward_route: Route,
+        renewable: bool,
+    ) -> Self {
+        let now = current_time();
+        Self {
+            address,
+            owner,
+            forward_route,
+            renewable,
+            created_at: now,
+            last_seen: now,
+            messages_forwarded: 0,
+            bytes_forwarded: 0,
+        }
+    }
+
+    /// Name of the relay
+    pub fn name(&self) -> String {
+        self.address.address().to_string()
+    }
+    /// Address of the relay worker
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+    /// Identity which registered the relay through a secure channel, if any
+    pub fn owner(&self) -> Option<&Identifier> {
+        self.owner.as_ref()
+    }
+    /// Route used to reach the node which registered the relay
+    pub fn forward_route(&self) -> &Route {
+        &self.forward_route
+    }
+    /// True if the relay has a static name and is kept alive by heartbeats
+    pub fn is_renewable(&self) -> bool {
+        self.renewable
+    }
+    /// Time of the first registration
+    pub fn created_at(&self) -> TimestampInSeconds {
+        self.created_at
+    }
+    /// Time of the last registration, or heartbeat
+    pub fn last_seen(&self) -> TimestampInSeconds {
+        self.last_seen
+    }
+    /// Number of messages forwarded by the relay
+    pub fn messages_forwarded(&self) -> u64 {
+        self.messages_forwarded
+    }
+    /// Number of payload bytes forwarded by the relay
+    pub fn bytes_forwarded(&self) -> u64 {
+        self.bytes_forwarded
+    }
+}
+
+/// Ownership of a relay name
+#[derive(Clone, Debug)]
+struct RelayOwnership {
+    owner: Identifier,
+    /// Time at which the relay was stopped, or expired, if it is not registered anymore
+    unregistered_at: Option<TimestampInSeconds>,
+}
+
+/// Registry of the relays created by a [`RelayService`](crate::RelayService)
+#[derive(Clone, Default)]
+pub struct RelayRegistry {
+    entries: Arc<RwLock<BTreeMap<String, RelayRegistryEntry>>>,
+    /// Identities owning relay names. A name stays owned for some time once its relay is
+    /// stopped or expired, so that it can't be registered by anyone else in the meantime.
+    /// The ownership is then released, see [`RelayRegistry::release_expired_owners`],
+    /// which keeps this map bounded by the names registered during that time
+    owners: Arc<RwLock<BTreeMap<String, RelayOwnership>>>,
+}
+
+impl RelayRegistry {
+    /// Return all the registered relays
+    pub fn get_all(&self) -> Vec<RelayRegistryEntry> {
+        self.entries.read().unwrap().values().cloned().collect()
+    }
+
+    /// Return the relay registered with the given name
+    pub fn get(&self, name: &str) -> Option<RelayRegistryEntry> {
+        self.entries.read().unwrap().get(name).cloned()
+    }
+
+    /// Return the identity owning the given relay name, if any, even if
+    /// the relay is not registered anymore
+    pub fn owner(&self, name: &str) -> Option<Identifier> {
+        self.owners
+            .read()
+            .unwrap()
+            .get(name)
+            .map(|ownership| ownership.owner.clone())
+    }
+
+    /// Release the ownership of a relay name, so that it can be registered by any
+    /// identity, for instance after the relay was evicted by the administrator of the node
+    pub fn release(&self, name: &str) {
+        self.owners.write().unwrap().remove(name);
+    }
+
+    /// Release the ownership of the names whose relay is not registered anymore since more
+    /// than `ttl`
+    pub fn release_expired_owners(&self, ttl: Duration) {
+        let now = current_time();
+        self.owners.write().unwrap().retain(|_, ownership| {
+            ownership
+                .unregistered_at
+                .map(|at| Duration::from_secs(now.saturating_sub(*at)) <= ttl)
+                .unwrap_or(true)
+        });
+    }
+
+    pub(super) fn insert(&self, entry: RelayRegistryEntry) {
+        if let Some(owner) = &entry.owner {
+            self.owners
+                .write()
+                .unwrap()
+                .entry(entry.name())
+                .and_modify(|ownership| ownership.unregistered_at = None)
+                .or_insert_with(|| RelayOwnership {
+                    owner: owner.clone(),
+                    unregistered_at: None,
+                });
+        }
+        self.entries.write().unwrap().insert(entry.name(), entry);
+    }
+
+    /// Remove the relay only if it was registered with the given route, since the
+    /// name may have been registered again in the meantime
+    pub(super) fn remove(&self, name: &str, forward_route: &Route) {
+        let mut entries = self.entries.write().unwrap();
+        if entries
+            .get(name)
+            .map(|entry| &entry.forward_route == forward_route)
+            .unwrap_or(false)
+        {
+            entries.remove(name);
+            self.set_unregistered(name);
+        }
+    }
+
+    /// Start the ownership ttl of a name whose relay is not registered anymore
+    fn set_unregistered(&self, name: &str) {
+        if let Some(ownership) = self.owners.write().unwrap().get_mut(name) {
+            ownership.unregistered_at = Some(current_time());
+        }
+    }
+
+    pub(super) fn touch(&self, name: &str) {
+        if let Some(entry) = self.entries.write().unwrap().get_mut(name) {
+            entry.last_seen = current_time();
+        }
+    }
+
+    pub(super) fn record_message(&self, name: &str, bytes: usize) {
+        if let Some(entry) = self.entries.write().unwrap().get_mut(name) {
+            entry.messages_forwarded += 1;
+            entry.bytes_forwarded += bytes as u64;
+        }
+    }
+
+    /// Remove and return the renewable relays which were not seen for more than `ttl`.
+    /// Timestamps have a resolution of one second, so a relay is expired once the number of
+    /// whole seconds since it was last seen exceeds the `ttl`
+    pub(super) fn remove_expired(&self, ttl: Duration) -> Vec<RelayRegistryEntry> {
+        let now = current_time();
+        let mut entries = self.entries.write().unwrap();
+        let expired: Vec<String> = entries
+            .iter()
+            .filter(|(_, entry)| {
+                entry.renewable && Duration::from_secs(now.saturating_sub(*entry.last_seen)) > ttl
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        expired
+            .iter()
+            .filter_map(|name| {
+                self.set_unregistered(name);
+                entries.remove(name)
+            })
+            .collect()
+    }
+}
+
+/// Without a clock, all the timestamps are 0 and relays never expire
+fn current_time() -> TimestampInSeconds {
+    now().unwrap_or(TimestampInSeconds(0))
+}
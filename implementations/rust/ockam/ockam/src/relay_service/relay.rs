@@ -1,6 +1,7 @@
+use crate::relay_service::RelayRegistry;
 use crate::Context;
 use ockam_core::compat::sync::Arc;
-use ockam_core::compat::{boxed::Box, vec::Vec};
+use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
 use ockam_core::{
     Address, AllowAll, AllowOnwardAddress, Any, IncomingAccessControl, LocalMessage,
     OutgoingAccessControl, Result, Route, Routed, TransportMessage, Worker,
@@ -9,6 +10,9 @@ use ockam_node::WorkerBuilder;
 use tracing::info;
 
 pub(super) struct Relay {
+    registry: RelayRegistry,
+    name: String,
+    registered_route: Route,
     forward_route: Route,
     // this option will be `None` after this worker is initialized, because
     // while initializing, the worker will send the payload contained in this
@@ -19,6 +23,7 @@ pub(super) struct Relay {
 impl Relay {
     pub(super) async fn create(
         ctx: &Context,
+        registry: RelayRegistry,
         address: Address,
         forward_route: Route,
         registration_payload: Vec<u8>,
@@ -36,6 +41,9 @@ impl Relay {
         };
 
         let relay = Self {
+            registry,
+            name: address.address().into(),
+            registered_route: forward_route.clone(),
             forward_route,
             payload: Some(registration_payload.clone()),
         };
@@ -71,6 +79,11 @@ impl Worker for Relay {
         Ok(())
     }
 
+    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        self.registry.remove(&self.name, &self.registered_route);
+        Ok(())
+    }
+
     async fn handle_message(
         &mut self,
         ctx: &mut Self::Context,
@@ -78,6 +91,8 @@ impl Worker for Relay {
     ) -> Result<()> {
         let mut message = msg.into_local_message();
         let transport_message = message.transport_mut();
+        self.registry
+            .record_message(&self.name, transport_message.payload.len());
 
         // Remove my address from the onward_route
         transport_message.onward_route.step()?;
@@ -1,18 +1,52 @@
 use crate::relay_service::relay::Relay;
+use crate::relay_service::RelayRegistryEntry;
+use crate::remote::REGISTRATION_REJECTED_PREFIX;
 use crate::{Context, RelayServiceOptions};
+use core::cmp::max;
 use core::str::from_utf8;
+use core::time::Duration;
 use ockam_core::compat::boxed::Box;
-use ockam_core::{Address, Any, DenyAll, Result, Routed, Worker};
-use ockam_node::WorkerBuilder;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{
+    Address, AllowAll, AllowOnwardAddress, Any, DenyAll, Encodable, Error, LocalMessage, Mailbox,
+    Mailboxes, OutgoingAccessControl, RelayMessage, Result, Route, Routed, TransportMessage,
+    Worker,
+};
+use ockam_identity::{Identifier, IdentitySecureChannelLocalInfo};
+use ockam_node::{DelayedEvent, WorkerBuilder};
+use tracing::{debug, info, warn};
+
+/// Number of checks, and delay between them, made when waiting for a relay to stop
+const STOP_RELAY_TRIES: usize = 100;
+const STOP_RELAY_INTERVAL: Duration = Duration::from_millis(10);
 
 /// Alias worker to register remote workers under local names.
 ///
 /// To talk with this worker, you can use the
 /// [`RemoteRelay`](crate::remote::RemoteRelay) which is a
 /// compatible client for this server.
+///
+/// A relay name belongs to the identity which first registered it through a secure
+/// channel. Only its owner can register that name again, for instance to update the
+/// route of the relay, even after the relay expired. The ownership is released once the
+/// relay is not registered anymore for longer than
+/// [`RelayServiceOptions::with_ownership_ttl`], or when it is released explicitly with
+/// [`RelayRegistry::release`](crate::RelayRegistry::release). Names registered without a secure
+/// channel have no owner: they can be claimed by an identity at any time, and can't be
+/// registered without a secure channel anymore once claimed. Names can also be reserved
+/// with [`RelayServiceOptions::with_reserved_prefix`].
+///
+/// Created relays are tracked in a [`RelayRegistry`](crate::RelayRegistry). When
+/// [`RelayServiceOptions::with_relay_ttl`] is set, named relays which stop sending
+/// heartbeats are evicted.
 #[non_exhaustive]
 pub struct RelayService {
     options: RelayServiceOptions,
+    sweep_address: Address,
+    sweep: Option<DelayedEvent<()>>,
 }
 
 impl RelayService {
@@ -28,12 +62,21 @@ impl RelayService {
 
         let service_incoming_access_control = options.service_incoming_access_control.clone();
 
-        let s = Self { options };
+        let sweep_address = Address::random_tagged("RelayService.sweep");
+        let s = Self {
+            options,
+            sweep_address: sweep_address.clone(),
+            sweep: None,
+        };
+
+        // Only messages coming from the sweep timer are processed on that address,
+        // see handle_message
+        let main_mailbox =
+            Mailbox::new(address, service_incoming_access_control, Arc::new(DenyAll));
+        let sweep_mailbox = Mailbox::new(sweep_address, Arc::new(AllowAll), Arc::new(DenyAll));
 
         WorkerBuilder::new(s)
-            .with_address(address)
-            .with_incoming_access_control_arc(service_incoming_access_control)
-            .with_outgoing_access_control(DenyAll)
+            .with_mailboxes(Mailboxes::new(main_mailbox, vec![sweep_mailbox]))
             .start(ctx)
             .await?;
 
@@ -41,29 +84,217 @@ impl RelayService {
     }
 }
 
+impl RelayService {
+    /// Return an error message if the sender of the registration is not allowed to
+    /// register a relay with the given name
+    async fn check_registration(
+        &self,
+        name: &str,
+        identifier: &Option<Identifier>,
+        msg: &Routed<Any>,
+    ) -> Result<Option<String>> {
+        self.options
+            .registry
+            .release_expired_owners(self.options.ownership_ttl);
+
+        for (prefix, access_control) in &self.options.reservations {
+            if name.starts_with(prefix.as_str()) {
+                let relay_msg =
+                    RelayMessage::new(msg.src_addr(), msg.msg_addr(), msg.local_message().clone());
+                if !access_control.is_authorized(&relay_msg).await? {
+                    return Ok(Some(format!("the relay name '{name}' is reserved")));
+                }
+            }
+        }
+
+        // A relay without owner doesn't prevent an identity from claiming its name
+        match self.options.registry.owner(name) {
+            Some(owner) if Some(&owner) != identifier.as_ref() => Ok(Some(format!(
+                "the relay name '{name}' is already registered by another identity"
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Stop a relay and wait until its address is released, so that a relay can be
+    /// created again with the same name
+    async fn stop_relay(ctx: &Context, address: &Address) -> Result<()> {
+        // The relay may have stopped already
+        let _ = ctx.stop_worker(address.clone()).await;
+        Self::wait_for_relay_to_stop(ctx, address).await
+    }
+
+    /// Wait until a relay which is stopping, for instance after being evicted,
+    /// releases its address
+    async fn wait_for_relay_to_stop(ctx: &Context, address: &Address) -> Result<()> {
+        for _ in 0..STOP_RELAY_TRIES {
+            if !ctx.list_workers().await?.contains(address) {
+                return Ok(());
+            }
+            ctx.sleep(STOP_RELAY_INTERVAL).await;
+        }
+        Err(Error::new(
+            Origin::Ockam,
+            Kind::Timeout,
+            format!("the relay {address} was not stopped"),
+        ))
+    }
+
+    /// Interval between two checks for expired relays
+    fn sweep_interval(ttl: Duration) -> Duration {
+        max(ttl / 2, Duration::from_secs(1))
+    }
+
+    /// Stop the relays which didn't send a heartbeat in time
+    async fn evict_expired_relays(&mut self, ctx: &Context) -> Result<()> {
+        let ttl = match self.options.relay_ttl {
+            Some(ttl) => ttl,
+            None => return Ok(()),
+        };
+
+        for entry in self.options.registry.remove_expired(ttl) {
+            info!(
+                "Relay {} expired, last seen at {}",
+                entry.address(),
+                *entry.last_seen()
+            );
+            if let Err(err) = Self::stop_relay(ctx, entry.address()).await {
+                warn!("Failed to stop an expired relay: {}", err);
+            }
+        }
+
+        if let Some(sweep) = &mut self.sweep {
+            sweep.schedule(Self::sweep_interval(ttl)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reply to a registration without going through a relay
+    async fn reply(
+        ctx: &Context,
+        forward_route: Route,
+        return_address: Address,
+        payload: Vec<u8>,
+    ) -> Result<()> {
+        // The service itself can't send messages, use the same outgoing access
+        // control as the relay would
+        let outgoing_access_control: Arc<dyn OutgoingAccessControl> = if forward_route.len() == 1 {
+            Arc::new(AllowAll)
+        } else {
+            Arc::new(AllowOnwardAddress(forward_route.next()?.clone()))
+        };
+        let child_ctx = ctx
+            .new_detached_with_mailboxes(Mailboxes::main(
+                Address::random_tagged("RelayService.reply"),
+                Arc::new(DenyAll),
+                outgoing_access_control,
+            ))
+            .await?;
+
+        let msg = TransportMessage::v1(forward_route, return_address, payload);
+        child_ctx.forward(LocalMessage::new(msg, Vec::new())).await
+    }
+}
+
 #[crate::worker]
 impl Worker for RelayService {
     type Context = Context;
     type Message = Any;
 
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        if let Some(ttl) = self.options.relay_ttl {
+            let mut sweep = DelayedEvent::create(ctx, self.sweep_address.clone(), ()).await?;
+            sweep.schedule(Self::sweep_interval(ttl)).await?;
+            self.sweep = Some(sweep);
+        }
+
+        Ok(())
+    }
+
+    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        self.sweep = None;
+        Ok(())
+    }
+
     async fn handle_message(
         &mut self,
         ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
-        let forward_route = msg.return_route();
-        let payload = msg.into_transport_message().payload;
+        if msg.msg_addr() == self.sweep_address {
+            let from_sweep_timer = self
+                .sweep
+                .as_ref()
+                .map(|sweep| msg.src_addr() == sweep.address())
+                .unwrap_or(false);
+            if from_sweep_timer {
+                self.evict_expired_relays(ctx).await?;
+            } else {
+                debug!(
+                    "Ignoring a message from {} for the sweep timer",
+                    msg.src_addr()
+                );
+            }
+            return Ok(());
+        }
 
-        let random_address = Address::random_tagged("Relay.service");
+        let forward_route = msg.return_route();
+        let identifier = IdentitySecureChannelLocalInfo::find_info(msg.local_message())
+            .ok()
+            .map(|info| info.their_identity_id());
 
         // TODO: assume that the first byte is length, ignore it.
         // We have to improve this actually parse the payload.
-        let address = match payload.get(1..) {
+        let name = match msg.payload().get(1..) {
             Some(address) => match from_utf8(address) {
-                Ok(v) if v != "register" => Address::from_string(v),
-                _ => random_address,
+                Ok(v) if v != "register" => Some(v.to_string()),
+                _ => None,
             },
-            None => random_address,
+            None => None,
+        };
+
+        let address = match &name {
+            Some(name) => {
+                if let Some(reason) = self.check_registration(name, &identifier, &msg).await? {
+                    warn!("Rejected the registration of a relay: {}", reason);
+                    let payload = format!("{REGISTRATION_REJECTED_PREFIX}{reason}").encode()?;
+                    return Self::reply(ctx, forward_route, msg.msg_addr(), payload).await;
+                }
+
+                let address = Address::from_string(name);
+                match self.options.registry.get(name) {
+                    // The owner registers the relay again, usually as a heartbeat
+                    Some(entry) if entry.forward_route() == &forward_route => {
+                        self.options.registry.touch(name);
+                        return Self::reply(ctx, forward_route, address, msg.take_payload()).await;
+                    }
+                    // The route to the owner changed, replace the relay
+                    Some(_) => {
+                        info!("Replacing the relay {}", address);
+                        Self::stop_relay(ctx, &address).await?;
+                    }
+                    // A relay which was evicted may still be stopping
+                    None => Self::wait_for_relay_to_stop(ctx, &address).await?,
+                }
+                self.options.registry.insert(RelayRegistryEntry::new(
+                    address.clone(),
+                    identifier,
+                    forward_route.clone(),
+                    true,
+                ));
+                address
+            }
+            None => {
+                let address = Address::random_tagged("Relay.service");
+                self.options.registry.insert(RelayRegistryEntry::new(
+                    address.clone(),
+                    identifier,
+                    forward_route.clone(),
+                    false,
+                ));
+                address
+            }
         };
 
         self.options
@@ -71,9 +302,10 @@ impl Worker for RelayService {
 
         Relay::create(
             ctx,
+            self.options.registry.clone(),
             address,
             forward_route,
-            payload,
+            msg.take_payload(),
             self.options.relays_incoming_access_control.clone(),
         )
         .await?;
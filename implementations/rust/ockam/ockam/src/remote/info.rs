@@ -1,7 +1,8 @@
 use crate::Message;
 use ockam_core::compat::string::String;
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
-use ockam_core::{Address, Route};
+use ockam_core::{Address, Error, Result, Route};
 use serde::{Deserialize, Serialize};
 
 /// Information about a remotely forwarded worker.
@@ -45,3 +46,20 @@ impl RemoteRelayInfo {
         &self.flow_control_id
     }
 }
+
+/// Outcome of a registration, sent by the [`RemoteRelay`](crate::remote::RemoteRelay)
+/// worker to the caller waiting for its creation
+#[derive(Serialize, Deserialize, Message)]
+pub(crate) enum RemoteRelayRegistration {
+    Registered(RemoteRelayInfo),
+    Rejected(String),
+}
+
+impl RemoteRelayRegistration {
+    pub(crate) fn into_result(self) -> Result<RemoteRelayInfo> {
+        match self {
+            Self::Registered(info) => Ok(info),
+            Self::Rejected(reason) => Err(Error::new(Origin::Ockam, Kind::Conflict, reason)),
+        }
+    }
+}
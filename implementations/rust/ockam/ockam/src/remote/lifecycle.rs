@@ -1,4 +1,6 @@
-use crate::remote::{Addresses, RemoteRelay, RemoteRelayInfo, RemoteRelayOptions};
+use crate::remote::{
+    Addresses, RemoteRelay, RemoteRelayInfo, RemoteRelayOptions, RemoteRelayRegistration,
+};
 use crate::Context;
 use core::time::Duration;
 use ockam_core::compat::sync::Arc;
@@ -131,7 +133,11 @@ impl RemoteRelay {
             .start(ctx)
             .await?;
 
-        let resp = child_ctx.receive::<RemoteRelayInfo>().await?.body();
+        let resp = child_ctx
+            .receive::<RemoteRelayRegistration>()
+            .await?
+            .body()
+            .into_result()?;
 
         Ok(resp)
     }
@@ -178,7 +184,11 @@ impl RemoteRelay {
             .start(ctx)
             .await?;
 
-        let resp = callback_ctx.receive::<RemoteRelayInfo>().await?.body();
+        let resp = callback_ctx
+            .receive::<RemoteRelayRegistration>()
+            .await?
+            .body()
+            .into_result()?;
 
         Ok(resp)
     }
@@ -229,7 +239,11 @@ impl RemoteRelay {
             .start(ctx)
             .await?;
 
-        let resp = callback_ctx.receive::<RemoteRelayInfo>().await?.body();
+        let resp = callback_ctx
+            .receive::<RemoteRelayRegistration>()
+            .await?
+            .body()
+            .into_result()?;
 
         Ok(resp)
     }
@@ -17,6 +17,9 @@ use ockam_core::flow_control::FlowControlId;
 use ockam_core::Route;
 use ockam_node::DelayedEvent;
 
+/// Prefix of the response sent by the relay service when it rejects a registration
+pub(crate) const REGISTRATION_REJECTED_PREFIX: &str = "ockam.relay.rejected: ";
+
 /// This Worker is responsible for registering on Ockam Orchestrator and forwarding messages to local Worker
 pub struct RemoteRelay {
     /// Address used from other node
@@ -1,4 +1,6 @@
-use crate::remote::{RemoteRelay, RemoteRelayInfo};
+use crate::remote::{
+    RemoteRelay, RemoteRelayInfo, RemoteRelayRegistration, REGISTRATION_REJECTED_PREFIX,
+};
 use crate::{Context, OckamError};
 use ockam_core::compat::{
     boxed::Box,
@@ -6,7 +8,7 @@ use ockam_core::compat::{
     vec::Vec,
 };
 use ockam_core::{Any, Decodable, Result, Routed, Worker};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 #[crate::worker]
 impl Worker for RemoteRelay {
@@ -61,6 +63,22 @@ impl Worker for RemoteRelay {
                         .map_err(|_| OckamError::InvalidHubResponse)?;
                     let payload =
                         String::from_utf8(payload).map_err(|_| OckamError::InvalidHubResponse)?;
+
+                    if let Some(reason) = payload.strip_prefix(REGISTRATION_REJECTED_PREFIX) {
+                        warn!("RemoteRelay registration was rejected: {}", reason);
+                        if !self.completion_msg_sent {
+                            ctx.send_from_address(
+                                self.addresses.completion_callback.clone(),
+                                RemoteRelayRegistration::Rejected(reason.to_string()),
+                                self.addresses.main_remote.clone(),
+                            )
+                            .await?;
+                            self.completion_msg_sent = true;
+                        }
+                        // The name belongs to someone else, stop trying to register it
+                        return ctx.stop_worker(self.addresses.main_internal.clone()).await;
+                    }
+
                     // using ends_with() instead of == to allow for prefixes
                     if !payload.ends_with(&self.registration_payload) {
                         return Err(OckamError::InvalidHubResponse.into());
@@ -76,12 +94,12 @@ impl Worker for RemoteRelay {
 
                         ctx.send_from_address(
                             self.addresses.completion_callback.clone(),
-                            RemoteRelayInfo::new(
+                            RemoteRelayRegistration::Registered(RemoteRelayInfo::new(
                                 return_route,
                                 address,
                                 self.addresses.main_remote.clone(),
                                 self.flow_control_id.clone(),
-                            ),
+                            )),
                             self.addresses.main_remote.clone(),
                         )
                         .await?;
@@ -0,0 +1,76 @@
+use ockam::identity::{secure_channels, SecureChannelListenerOptions, SecureChannelOptions};
+use ockam_core::{route, Result};
+use ockam_node::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use ockam_node::tokio::net::{TcpListener, TcpStream};
+use ockam_node::tokio::time::timeout;
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpInletOptions, TcpOutletOptions, TcpTransport};
+use std::time::Duration;
+
+// A portal going through a secure channel is closed on both sides when the channel is closed
+#[ockam_macros::test(timeout = 10000)]
+async fn test_portal_is_closed_when_its_secure_channel_is_closed(ctx: &mut Context) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+
+    let server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    tcp.create_outlet(
+        "outlet",
+        server.local_addr().unwrap().to_string(),
+        TcpOutletOptions::new().as_consumer(bob_listener.flow_control_id()),
+    )
+    .await?;
+    let (inlet_address, _) = tcp
+        .create_inlet(
+            "127.0.0.1:0",
+            route![alice_channel.clone(), "outlet"],
+            TcpInletOptions::new(),
+        )
+        .await?;
+
+    let mut client = TcpStream::connect(inlet_address).await.unwrap();
+    let (mut server_stream, _) = server.accept().await.unwrap();
+
+    client.write_all(b"hello").await.unwrap();
+    let mut buffer = [0u8; 5];
+    server_stream.read_exact(&mut buffer).await.unwrap();
+    assert_eq!(&buffer, b"hello");
+
+    secure_channels
+        .stop_secure_channel(ctx, alice_channel.encryptor_address())
+        .await?;
+
+    // Both TCP connections are closed by the portal workers
+    let read = timeout(Duration::from_secs(5), client.read(&mut buffer))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(read, 0);
+    let read = timeout(Duration::from_secs(5), server_stream.read(&mut buffer))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(read, 0);
+
+    ctx.stop().await
+}
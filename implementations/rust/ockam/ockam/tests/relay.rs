@@ -1,8 +1,9 @@
 use ockam::identity::{secure_channels, SecureChannelListenerOptions, SecureChannelOptions};
 use ockam::remote::{RemoteRelay, RemoteRelayOptions};
 use ockam::workers::Echoer;
-use ockam::{RelayService, RelayServiceOptions};
-use ockam_core::{route, AllowAll, Result};
+use ockam::{RelayRegistry, RelayService, RelayServiceOptions};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, AllowAll, DenyAll, Result};
 use ockam_node::{Context, MessageReceiveOptions};
 use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
 use std::time::Duration;
@@ -253,3 +254,307 @@ async fn test4(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+// A relay name belongs to the identity which registered it first
+#[ockam_macros::test]
+async fn test_relay_name_is_owned_by_its_first_identity(ctx: &mut Context) -> Result<()> {
+    let secure_channel_listener_options = SecureChannelListenerOptions::new();
+    let options = RelayServiceOptions::new()
+        .service_as_consumer(&secure_channel_listener_options.spawner_flow_control_id())
+        .relay_as_consumer(&secure_channel_listener_options.spawner_flow_control_id());
+    RelayService::create(ctx, "forwarding_service", options).await?;
+
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+    let cloud_identity = identities_creation.create_identity().await?;
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            cloud_identity.identifier(),
+            "cloud_listener",
+            secure_channel_listener_options,
+        )
+        .await?;
+
+    let owner = identities_creation.create_identity().await?;
+    let owner_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            owner.identifier(),
+            "cloud_listener",
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        owner_channel.clone(),
+        "payments",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+
+    // The owner can register the name again
+    RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        owner_channel,
+        "payments",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+
+    let other = identities_creation.create_identity().await?;
+    let other_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            other.identifier(),
+            "cloud_listener",
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    let res = RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        other_channel,
+        "payments",
+        RemoteRelayOptions::new(),
+    )
+    .await;
+    assert!(
+        res.is_err(),
+        "Another identity should not take over the name"
+    );
+
+    ctx.stop().await
+}
+
+// A name registered without a secure channel can be claimed by an identity, and can't be
+// registered without a secure channel anymore once claimed
+#[ockam_macros::test]
+async fn test_relay_name_without_owner_can_be_claimed(ctx: &mut Context) -> Result<()> {
+    let secure_channel_listener_options = SecureChannelListenerOptions::new();
+    let options = RelayServiceOptions::new()
+        .service_as_consumer(&secure_channel_listener_options.spawner_flow_control_id())
+        .relay_as_consumer(&secure_channel_listener_options.spawner_flow_control_id());
+    RelayService::create(ctx, "forwarding_service", options).await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+    let cloud_identity = identities_creation.create_identity().await?;
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            cloud_identity.identifier(),
+            "cloud_listener",
+            secure_channel_listener_options,
+        )
+        .await?;
+
+    RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        route![],
+        "payments",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+
+    // The relay without owner is replaced by the relay of the identity
+    let owner = identities_creation.create_identity().await?;
+    let owner_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            owner.identifier(),
+            "cloud_listener",
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        owner_channel,
+        "payments",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+
+    let res = RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        route![],
+        "payments",
+        RemoteRelayOptions::new(),
+    )
+    .await;
+    assert!(
+        res.is_err(),
+        "The name should not be registered without a secure channel once claimed"
+    );
+
+    // The owner can replace its relay with a new route, and the new relay is reachable
+    let new_owner_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            owner.identifier(),
+            "cloud_listener",
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    let remote_info = RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        new_owner_channel,
+        "payments",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+    let resp = ctx
+        .send_and_receive::<String>(
+            route![remote_info.remote_address(), "echoer"],
+            "Hello".to_string(),
+        )
+        .await?;
+    assert_eq!(resp, "Hello");
+
+    ctx.stop().await
+}
+
+// Reserved relay names can only be registered when the access control allows it
+#[ockam_macros::test]
+async fn test_reserved_relay_name(ctx: &mut Context) -> Result<()> {
+    let options = RelayServiceOptions::new().with_reserved_prefix("payments", Arc::new(DenyAll));
+    RelayService::create(ctx, "forwarding_service", options).await?;
+
+    let res = RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        route![],
+        "payments_eu",
+        RemoteRelayOptions::new(),
+    )
+    .await;
+    assert!(res.is_err(), "The name should be reserved");
+
+    RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        route![],
+        "orders",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+
+    ctx.stop().await
+}
+
+// Relays are listed in the registry of the service and evicted when they stop sending heartbeats
+#[ockam_macros::test]
+async fn test_relay_registry_and_expiry(ctx: &mut Context) -> Result<()> {
+    let registry = RelayRegistry::default();
+    let options = RelayServiceOptions::new()
+        .with_registry(registry.clone())
+        .with_relay_ttl(Duration::from_secs(1));
+    RelayService::create(ctx, "forwarding_service", options).await?;
+
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let remote_info = RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        route![],
+        "payments",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+
+    let resp = ctx
+        .send_and_receive::<String>(
+            route![remote_info.remote_address(), "echoer"],
+            "Hello".to_string(),
+        )
+        .await?;
+    assert_eq!(resp, "Hello");
+
+    let relays = registry.get_all();
+    assert_eq!(relays.len(), 1);
+    let relay = &relays[0];
+    assert_eq!(relay.name(), remote_info.remote_address());
+    assert!(relay.owner().is_none());
+    assert!(relay.is_renewable());
+    assert_eq!(relay.messages_forwarded(), 1);
+    assert!(relay.bytes_forwarded() > 0);
+
+    // No heartbeats are sent, so the relay expires
+    ctx.sleep(Duration::from_secs(4)).await;
+    assert!(registry.get_all().is_empty());
+    assert!(!ctx
+        .list_workers()
+        .await?
+        .contains(&remote_info.remote_address().into()));
+
+    ctx.stop().await
+}
+
+// The ownership of a relay name is released some time after its relay expired
+#[ockam_macros::test]
+async fn test_relay_name_ownership_is_released(ctx: &mut Context) -> Result<()> {
+    let secure_channel_listener_options = SecureChannelListenerOptions::new();
+    let registry = RelayRegistry::default();
+    let options = RelayServiceOptions::new()
+        .service_as_consumer(&secure_channel_listener_options.spawner_flow_control_id())
+        .relay_as_consumer(&secure_channel_listener_options.spawner_flow_control_id())
+        .with_registry(registry.clone())
+        .with_relay_ttl(Duration::from_secs(1))
+        .with_ownership_ttl(Duration::ZERO);
+    RelayService::create(ctx, "forwarding_service", options).await?;
+
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+    let cloud_identity = identities_creation.create_identity().await?;
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            cloud_identity.identifier(),
+            "cloud_listener",
+            secure_channel_listener_options,
+        )
+        .await?;
+
+    let owner = identities_creation.create_identity().await?;
+    let owner_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            owner.identifier(),
+            "cloud_listener",
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        owner_channel,
+        "payments",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+    assert_eq!(registry.owner("payments"), Some(owner.identifier().clone()));
+
+    // No heartbeats are sent, so the relay expires, then its ownership
+    ctx.sleep(Duration::from_secs(5)).await;
+    assert!(registry.get_all().is_empty());
+
+    let other = identities_creation.create_identity().await?;
+    let other_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            other.identifier(),
+            "cloud_listener",
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    RemoteRelay::create_static_without_heartbeats(
+        ctx,
+        other_channel,
+        "payments",
+        RemoteRelayOptions::new(),
+    )
+    .await?;
+    assert_eq!(registry.owner("payments"), Some(other.identifier().clone()));
+
+    // The ownership can also be released explicitly
+    registry.release("payments");
+    assert!(registry.owner("payments").is_none());
+
+    ctx.stop().await
+}
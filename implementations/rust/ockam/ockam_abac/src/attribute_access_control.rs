@@ -16,7 +16,9 @@ use crate::{eval, Env, Expr};
 use ockam_core::compat::format;
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
-use ockam_identity::{Identifier, IdentitiesRepository, IdentitySecureChannelLocalInfo};
+use ockam_identity::{
+    CredentialSchemas, Identifier, IdentitiesRepository, IdentitySecureChannelLocalInfo,
+};
 
 /// This AccessControl uses a storage for authenticated attributes in order
 /// to verify if a policy expression is valid
@@ -26,6 +28,7 @@ pub struct AbacAccessControl {
     repository: Arc<dyn IdentitiesRepository>,
     expression: Expr,
     environment: Env,
+    credential_schemas: Option<Arc<CredentialSchemas>>,
 }
 
 /// Debug implementation printing out the policy expression only
@@ -47,9 +50,17 @@ impl AbacAccessControl {
             repository,
             expression,
             environment,
+            credential_schemas: None,
         }
     }
 
+    /// Use credential schemas to give their declared type to attribute values, instead of strings.
+    /// For example a list attribute can then be checked with `member?`
+    pub fn with_credential_schemas(mut self, credential_schemas: Arc<CredentialSchemas>) -> Self {
+        self.credential_schemas = Some(credential_schemas);
+        self
+    }
+
     /// Create an AccessControl which will verify that the sender of
     /// a message has an authenticated attribute with the correct name and value
     pub fn create(
@@ -74,6 +85,7 @@ impl AbacAccessControl {
 
         // Get identity attributes and populate the environment:
         if let Some(attrs) = self.repository.get_attributes(&id).await? {
+            let schema = attrs.schema();
             for (key, value) in attrs.attrs() {
                 let key = match from_utf8(key) {
                     Ok(key) => key,
@@ -94,6 +106,29 @@ impl AbacAccessControl {
                         "attribute key with whitespace ignored"
                     }
                 }
+                let typed_value = match (&self.credential_schemas, &schema) {
+                    (Some(schemas), Some(schema)) => {
+                        schemas.parse_attribute(schema, key.as_bytes(), value)
+                    }
+                    _ => None,
+                };
+                match typed_value {
+                    Some(Ok(v)) => {
+                        environment.put(format!("subject.{key}"), Expr::from(v));
+                        continue;
+                    }
+                    Some(Err(e)) => {
+                        log::warn! {
+                            policy = %self.expression,
+                            id     = %id,
+                            key    = %key,
+                            err    = %e,
+                            "attribute does not match its schema; access denied"
+                        }
+                        return Ok(false);
+                    }
+                    None => (),
+                }
                 match str::from_utf8(value) {
                     Ok(s) => {
                         if environment.contains(key) {
@@ -9,6 +9,7 @@ use core::fmt;
 use minicbor::{Decode, Encode};
 use ockam_core::compat::string::String;
 use ockam_core::compat::vec::{vec, Vec};
+use ockam_identity::AttributeValue;
 
 #[derive(Debug, Clone, Encode, Decode)]
 #[rustfmt::skip]
@@ -165,6 +166,17 @@ impl From<f64> for Expr {
     }
 }
 
+impl From<AttributeValue> for Expr {
+    fn from(v: AttributeValue) -> Self {
+        match v {
+            AttributeValue::String(s) => Self::Str(s),
+            AttributeValue::Integer(i) => Self::Int(i),
+            AttributeValue::Boolean(b) => Self::Bool(b),
+            AttributeValue::List(xs) => Self::Seq(xs.into_iter().map(Expr::from).collect()),
+        }
+    }
+}
+
 pub fn t() -> Expr {
     Expr::Bool(true)
 }
@@ -551,6 +563,30 @@ mod tests {
         assert_eq!(Some(Ordering::Equal), x.compare(&z).unwrap());
     }
 
+    #[test]
+    fn typed_attributes() {
+        use ockam_identity::AttributeValue;
+
+        let mut env = Env::new();
+        env.put("subject.max", Expr::from(AttributeValue::Integer(10)));
+        env.put("subject.admin", Expr::from(AttributeValue::Boolean(true)));
+        env.put(
+            "subject.ports",
+            Expr::from(AttributeValue::List(vec![
+                AttributeValue::Integer(22),
+                AttributeValue::Integer(443),
+            ])),
+        );
+
+        let x = parse("(and subject.admin (< 5 subject.max) (member? 443 subject.ports))")
+            .unwrap()
+            .unwrap();
+        assert!(eval(&x, &env).unwrap().equals(&Expr::Bool(true)).unwrap());
+
+        let y = parse("(member? 80 subject.ports)").unwrap().unwrap();
+        assert!(eval(&y, &env).unwrap().equals(&Expr::Bool(false)).unwrap());
+    }
+
     #[derive(Debug, Clone)]
     struct S(String);
 
@@ -9,7 +9,7 @@ use ockam_core::compat::format;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, RelayMessage};
 use ockam_core::{IncomingAccessControl, Result};
-use ockam_identity::IdentitiesRepository;
+use ockam_identity::{CredentialSchemas, IdentitiesRepository};
 use tracing as log;
 
 /// Evaluates a policy expression against an environment of attributes.
@@ -22,6 +22,7 @@ pub struct PolicyAccessControl {
     policies: Arc<dyn PolicyStorage>,
     repository: Arc<dyn IdentitiesRepository>,
     environment: Env,
+    credential_schemas: Option<Arc<CredentialSchemas>>,
 }
 
 /// Debug implementation writing out the resource, action and initial environment
@@ -55,8 +56,15 @@ impl PolicyAccessControl {
             policies,
             repository,
             environment: env,
+            credential_schemas: None,
         }
     }
+
+    /// Use credential schemas to give their declared type to subject attributes
+    pub fn with_credential_schemas(mut self, credential_schemas: Arc<CredentialSchemas>) -> Self {
+        self.credential_schemas = Some(credential_schemas);
+        self
+    }
 }
 
 #[async_trait]
@@ -85,8 +93,11 @@ impl IncomingAccessControl for PolicyAccessControl {
             return Ok(false);
         };
 
-        AbacAccessControl::new(self.repository.clone(), expr, self.environment.clone())
-            .is_authorized(msg)
-            .await
+        let mut abac =
+            AbacAccessControl::new(self.repository.clone(), expr, self.environment.clone());
+        if let Some(credential_schemas) = &self.credential_schemas {
+            abac = abac.with_credential_schemas(credential_schemas.clone());
+        }
+        abac.is_authorized(msg).await
     }
 }
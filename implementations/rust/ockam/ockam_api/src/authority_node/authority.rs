@@ -61,6 +61,7 @@ impl Authority {
         let secure_channels = SecureChannels::builder()
             .with_vault(vault)
             .with_identities_repository(repository)
+            .with_credential_schemas(configuration.credential_schemas())
             .build();
 
         let identifier = configuration.identifier();
@@ -191,12 +192,19 @@ impl Authority {
         configuration: &Configuration,
     ) -> Result<()> {
         // create and start a credential issuer worker
-        let issuer = CredentialsIssuer::new(
+        let mut issuer = CredentialsIssuer::new(
             self.secure_channels.identities().repository(),
             self.secure_channels.identities().credentials(),
             &self.identifier,
             configuration.project_identifier(),
         );
+        if let Some(delegation) = configuration.delegation()? {
+            issuer = issuer.with_delegation(delegation);
+            info!("the credential issuer is a sub-authority");
+        }
+        if configuration.selective_disclosure {
+            issuer = issuer.with_selective_disclosure();
+        }
 
         let address = DefaultAddress::CREDENTIAL_ISSUER.to_string();
         ctx.flow_controls()
@@ -368,11 +376,10 @@ impl Authority {
             "resource.trust_context_id",
             str(configuration.project_identifier.clone()),
         );
-        let abac = Arc::new(AbacAccessControl::new(
-            self.identities_repository(),
-            rule,
-            env,
-        ));
+        let abac = Arc::new(
+            AbacAccessControl::new(self.identities_repository(), rule, env)
+                .with_credential_schemas(self.identities().credential_schemas()),
+        );
         abac
     }
 }
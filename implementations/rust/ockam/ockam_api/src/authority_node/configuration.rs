@@ -1,11 +1,16 @@
 use crate::bootstrapped_identities_store::PreTrustedIdentities;
 use crate::DefaultAddress;
 
+use ockam::identity::models::CredentialAndPurposeKey;
 use ockam::identity::utils::now;
-use ockam::identity::{AttributesEntry, Identifier, TRUST_CONTEXT_ID};
+use ockam::identity::{
+    AttributesEntry, CredentialSchema, CredentialSchemas, Identifier, TRUST_CONTEXT_ID,
+};
 use ockam_core::compat::collections::HashMap;
 use ockam_core::compat::fmt;
 use ockam_core::compat::fmt::{Display, Formatter};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::path::PathBuf;
@@ -47,6 +52,20 @@ pub struct Configuration {
 
     /// optional configuration for the okta service
     pub okta: Option<OktaConfiguration>,
+
+    /// schemas used to validate the attributes of the issued credentials
+    #[serde(default)]
+    pub credential_schemas: Vec<CredentialSchema>,
+
+    /// hex encoded delegation credential, when the authority issues credentials as a
+    /// sub-authority of another authority
+    #[serde(default)]
+    pub delegation: Option<String>,
+
+    /// If true, issue credentials where each attribute is committed individually, so that
+    /// project members can present only the attributes needed by each node
+    #[serde(default)]
+    pub selective_disclosure: bool,
 }
 
 /// Local and private functions for the authority configuration
@@ -73,6 +92,27 @@ impl Configuration {
             .unwrap_or(DefaultAddress::SECURE_CHANNEL_LISTENER.into())
     }
 
+    /// Return the registry of credential schemas
+    pub(crate) fn credential_schemas(&self) -> CredentialSchemas {
+        self.credential_schemas
+            .iter()
+            .fold(CredentialSchemas::new(), |schemas, schema| {
+                schemas.with_schema(schema.clone())
+            })
+    }
+
+    /// Return the decoded delegation credential, if any
+    pub(crate) fn delegation(&self) -> Result<Option<CredentialAndPurposeKey>> {
+        match &self.delegation {
+            Some(delegation) => {
+                let delegation = hex::decode(delegation)
+                    .map_err(|e| Error::new(Origin::Node, Kind::Invalid, e.to_string()))?;
+                Ok(Some(minicbor::decode(&delegation)?))
+            }
+            None => Ok(None),
+        }
+    }
+
     /// Return the service name for the direct authenticator
     pub(crate) fn authenticator_name(&self) -> String {
         self.authenticator_name
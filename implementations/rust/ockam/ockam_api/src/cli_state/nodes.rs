@@ -4,18 +4,20 @@ use crate::cli_state::{
     StateDirTrait, StateItemTrait, VaultState,
 };
 use crate::config::lookup::ProjectLookup;
+use crate::nodes::models::relay::RelayReservation;
 use crate::nodes::models::transport::CreateTransportJson;
 use backwards_compatibility::*;
 use miette::{IntoDiagnostic, WrapErr};
 use nix::errno::Errno;
-use ockam::identity::Identifier;
 use ockam::identity::Vault;
+use ockam::identity::{CredentialSchema, Identifier};
 use ockam::LmdbStorage;
 use ockam_core::compat::collections::HashSet;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 use sysinfo::{Pid, ProcessExt, ProcessStatus, System, SystemExt};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -115,6 +117,60 @@ impl NodeState {
         Ok(())
     }
 
+    /// Return the resources which must be recreated when the node starts
+    pub fn resources(&self) -> Result<Vec<NodeResource>> {
+        let path = self.paths.resources();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn set_resources(&self, resources: &[NodeResource]) -> Result<()> {
+        let contents = serde_json::to_string(resources)?;
+        std::fs::write(self.paths.resources(), contents)?;
+        Ok(())
+    }
+
+    /// Record a resource, replacing any resource of the same kind with the same name
+    pub fn add_resource(&self, resource: NodeResource) -> Result<()> {
+        let mut resources = self.resources()?;
+        resources.retain(|r| !(r.kind == resource.kind && r.name == resource.name));
+        debug!(name = %self.name(), kind = ?resource.kind, resource = %resource.name, "resource persisted");
+        resources.push(resource);
+        self.set_resources(&resources)
+    }
+
+    pub fn remove_resource(&self, kind: NodeResourceKind, name: &str) -> Result<()> {
+        let mut resources = self.resources()?;
+        let count = resources.len();
+        resources.retain(|r| !(r.kind == kind && r.name == name));
+        if resources.len() != count {
+            debug!(name = %self.name(), ?kind, resource = %name, "persisted resource removed");
+            self.set_resources(&resources)?;
+        }
+        Ok(())
+    }
+
+    /// Identifiers (`kind/name`) of the resources created by applying a configuration
+    /// file to the node. Only these resources are updated or deleted when the
+    /// configuration changes, the other resources of the node are left untouched.
+    pub fn config_resources(&self) -> Result<Vec<String>> {
+        let path = self.paths.config_resources();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn set_config_resources(&self, ids: &[String]) -> Result<()> {
+        let contents = serde_json::to_string(ids)?;
+        std::fs::write(self.paths.config_resources(), contents)?;
+        Ok(())
+    }
+
     pub fn pid(&self) -> Result<Option<i32>> {
         let path = self.paths.pid();
         if path.exists() {
@@ -149,6 +205,12 @@ impl NodeState {
         }
     }
 
+    /// Path of the file listing the identities trusted by a node created with a
+    /// configuration file. The node reads it again each time it checks an identity
+    pub fn trusted_identities_file(&self) -> PathBuf {
+        self.paths.trusted_identities()
+    }
+
     pub fn stdout_log(&self) -> PathBuf {
         self.paths.stdout()
     }
@@ -309,6 +371,31 @@ pub struct NodeSetupConfig {
     pub authority_node: Option<bool>,
     pub project: Option<ProjectLookup>,
     pub api_transport: Option<CreateTransportJson>,
+
+    /// Resources created at runtime are not recreated when an ephemeral node starts.
+    /// The field might be missing in previous configuration files
+    #[serde(default)]
+    pub ephemeral: bool,
+
+    /// Configuration file watched by the node, if it was created with one.
+    /// The field might be missing in previous configuration files
+    #[serde(default)]
+    pub config_file: Option<PathBuf>,
+
+    /// Schemas used to validate and type the attributes of the credentials verified by the node.
+    /// The field might be missing in previous configuration files
+    #[serde(default)]
+    pub credential_schemas: Vec<CredentialSchema>,
+
+    /// Time after which the relays registered at the node expire if they stop sending heartbeats.
+    /// The field might be missing in previous configuration files
+    #[serde(default)]
+    pub relay_ttl: Option<Duration>,
+
+    /// Relay names which can only be registered at the node by some identities.
+    /// The field might be missing in previous configuration files
+    #[serde(default)]
+    pub relay_reservations: Vec<RelayReservation>,
 }
 
 impl NodeSetupConfig {
@@ -327,6 +414,31 @@ impl NodeSetupConfig {
         self
     }
 
+    pub fn set_ephemeral(mut self, ephemeral: bool) -> Self {
+        self.ephemeral = ephemeral;
+        self
+    }
+
+    pub fn set_config_file(mut self, config_file: Option<PathBuf>) -> Self {
+        self.config_file = config_file;
+        self
+    }
+
+    pub fn set_credential_schemas(mut self, credential_schemas: Vec<CredentialSchema>) -> Self {
+        self.credential_schemas = credential_schemas;
+        self
+    }
+
+    pub fn set_relay_ttl(mut self, relay_ttl: Option<Duration>) -> Self {
+        self.relay_ttl = relay_ttl;
+        self
+    }
+
+    pub fn set_relay_reservations(mut self, relay_reservations: Vec<RelayReservation>) -> Self {
+        self.relay_reservations = relay_reservations;
+        self
+    }
+
     pub fn set_api_transport(mut self, transport: CreateTransportJson) -> Self {
         self.api_transport = Some(transport);
         self
@@ -341,6 +453,48 @@ impl NodeSetupConfig {
     }
 }
 
+/// Kind of a resource created through the node API.
+/// Resources are recreated in the order of this enumeration when a node starts,
+/// so that a resource is created after the resources it may depend on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeResourceKind {
+    SecureChannelListener,
+    TcpOutlet,
+    Relay,
+    TcpInlet,
+    KafkaService,
+}
+
+/// A resource created through the node API, along with the request which created it
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct NodeResource {
+    pub kind: NodeResourceKind,
+    /// Alias or address identifying the resource
+    pub name: String,
+    /// Path of the creation request
+    pub path: String,
+    /// CBOR body of the creation request
+    #[serde(with = "hex")]
+    pub body: Vec<u8>,
+}
+
+impl NodeResource {
+    pub fn new(
+        kind: NodeResourceKind,
+        name: impl Into<String>,
+        path: impl Into<String>,
+        body: Vec<u8>,
+    ) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            path: path.into(),
+            body,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 struct NodePaths {
     path: PathBuf,
@@ -384,6 +538,18 @@ impl NodePaths {
     fn policies_storage(&self) -> PathBuf {
         self.path.join("policies_storage.lmdb")
     }
+
+    fn resources(&self) -> PathBuf {
+        self.path.join("resources.json")
+    }
+
+    fn config_resources(&self) -> PathBuf {
+        self.path.join("config_resources.json")
+    }
+
+    fn trusted_identities(&self) -> PathBuf {
+        self.path.join("trusted_identities.json")
+    }
 }
 
 mod backwards_compatibility {
@@ -492,6 +658,11 @@ mod traits {
                         authority_node: setup.authority_node,
                         project: setup.project,
                         api_transport: None,
+                        ephemeral: false,
+                        config_file: None,
+                        credential_schemas: vec![],
+                        relay_ttl: None,
+                        relay_reservations: vec![],
                     };
                     if let Some(t) = setup
                         .transports
@@ -736,4 +907,39 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn node_resources_are_replaced_and_removed() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let node_state = NodeState {
+            name: "n".to_string(),
+            path: tmp_dir.path().to_path_buf(),
+            paths: NodePaths::new(tmp_dir.path()),
+            config: NodeConfig {
+                setup: NodeSetupConfig::default(),
+                version: ConfigVersion::latest(),
+                default_vault: PathBuf::new(),
+                default_identity: PathBuf::new(),
+            },
+        };
+        assert!(node_state.resources().unwrap().is_empty());
+
+        let inlet = NodeResource::new(NodeResourceKind::TcpInlet, "i", "/node/inlet", vec![1]);
+        let outlet = NodeResource::new(NodeResourceKind::TcpOutlet, "i", "/node/outlet", vec![2]);
+        node_state.add_resource(inlet.clone()).unwrap();
+        node_state.add_resource(outlet.clone()).unwrap();
+
+        // A resource with the same kind and name replaces the previous one
+        let new_inlet = NodeResource::new(NodeResourceKind::TcpInlet, "i", "/node/inlet", vec![3]);
+        node_state.add_resource(new_inlet.clone()).unwrap();
+        assert_eq!(
+            node_state.resources().unwrap(),
+            vec![outlet.clone(), new_inlet]
+        );
+
+        node_state
+            .remove_resource(NodeResourceKind::TcpInlet, "i")
+            .unwrap();
+        assert_eq!(node_state.resources().unwrap(), vec![outlet]);
+    }
 }
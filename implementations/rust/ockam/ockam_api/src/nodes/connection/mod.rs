@@ -1,4 +1,7 @@
 mod plain_tcp;
+#[cfg(unix)]
+mod plain_uds;
+mod plain_ws;
 mod project;
 mod secure;
 
@@ -15,6 +18,9 @@ use crate::error::ApiError;
 use crate::nodes::NodeManager;
 use crate::{multiaddr_to_route, DefaultAddress};
 pub(crate) use plain_tcp::PlainTcpInstantiator;
+#[cfg(unix)]
+pub(crate) use plain_uds::PlainUdsInstantiator;
+pub(crate) use plain_ws::PlainWebSocketInstantiator;
 pub(crate) use project::ProjectInstantiator;
 pub(crate) use secure::SecureChannelInstantiator;
 use std::fmt::{Debug, Formatter};
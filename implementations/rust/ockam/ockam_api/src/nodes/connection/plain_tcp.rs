@@ -1,6 +1,6 @@
 use crate::error::ApiError;
 use crate::nodes::connection::{Changes, ConnectionBuilder, Instantiator};
-use crate::{multiaddr_to_route, route_to_multiaddr};
+use crate::{multiaddr_to_reconnecting_route, route_to_multiaddr};
 use std::sync::Arc;
 
 use crate::nodes::NodeManager;
@@ -8,13 +8,22 @@ use ockam_core::{async_trait, Error, Route};
 use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Tcp};
 use ockam_multiaddr::{Match, MultiAddr, Protocol};
 use ockam_node::Context;
+use ockam_transport_tcp::TcpReconnectOptions;
 
 /// Creates the tcp connection.
-pub(crate) struct PlainTcpInstantiator {}
+pub(crate) struct PlainTcpInstantiator {
+    reconnect: Option<TcpReconnectOptions>,
+}
 
 impl PlainTcpInstantiator {
     pub(crate) fn new() -> Self {
-        Self {}
+        Self { reconnect: None }
+    }
+
+    /// Re-establish the tcp connection when it drops
+    pub(crate) fn with_reconnect(mut self, reconnect: Option<TcpReconnectOptions>) -> Self {
+        self.reconnect = reconnect;
+        self
     }
 }
 
@@ -37,13 +46,17 @@ impl Instantiator for PlainTcpInstantiator {
     ) -> Result<Changes, Error> {
         let (before, tcp_piece, after) = extracted;
 
-        let mut tcp = multiaddr_to_route(&tcp_piece, &node_manager.tcp_transport)
-            .await
-            .ok_or_else(|| {
-                ApiError::core(format!(
-                    "Couldn't convert MultiAddr to route: tcp_piece={tcp_piece}"
-                ))
-            })?;
+        let mut tcp = multiaddr_to_reconnecting_route(
+            &tcp_piece,
+            &node_manager.tcp_transport,
+            self.reconnect.clone(),
+        )
+        .await
+        .ok_or_else(|| {
+            ApiError::core(format!(
+                "Couldn't convert MultiAddr to route: tcp_piece={tcp_piece}"
+            ))
+        })?;
 
         let multiaddr = route_to_multiaddr(&tcp.route).ok_or_else(|| {
             ApiError::core(format!(
@@ -0,0 +1,59 @@
+use crate::error::ApiError;
+use crate::nodes::connection::{Changes, ConnectionBuilder, Instantiator};
+use crate::try_address_to_multiaddr;
+use std::sync::Arc;
+
+use crate::nodes::NodeManager;
+use ockam_core::{async_trait, Error, Route};
+use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Tcp, Ws};
+use ockam_multiaddr::{Match, MultiAddr, Protocol};
+use ockam_node::Context;
+
+/// Creates the WebSocket connection.
+///
+/// WebSocket connections don't support flow control, so no flow control id is set.
+pub(crate) struct PlainWebSocketInstantiator {}
+
+impl PlainWebSocketInstantiator {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait]
+impl Instantiator for PlainWebSocketInstantiator {
+    fn matches(&self) -> Vec<Match> {
+        vec![
+            // matches any tcp address and port followed by a ws protocol
+            Match::any([DnsAddr::CODE, Ip4::CODE, Ip6::CODE]),
+            Tcp::CODE.into(),
+            Ws::CODE.into(),
+        ]
+    }
+
+    async fn instantiate(
+        &self,
+        _ctx: Arc<Context>,
+        node_manager: &NodeManager,
+        _transport_route: Route,
+        extracted: (MultiAddr, MultiAddr, MultiAddr),
+    ) -> Result<Changes, Error> {
+        let (before, ws_piece, after) = extracted;
+
+        let ws = node_manager
+            .web_socket_transport()
+            .ok_or_else(|| ApiError::core("The WebSocket transport is not enabled"))?;
+
+        let peer = ws_piece.to_socket_addr()?;
+        let sender_address = ws.connect(&peer).await?;
+        let multiaddr = try_address_to_multiaddr(&sender_address)?;
+        let current_multiaddr = ConnectionBuilder::combine(before, multiaddr, after)?;
+
+        Ok(Changes {
+            current_multiaddr,
+            flow_control_id: None,
+            secure_channel_encryptors: vec![],
+            tcp_connection: None,
+        })
+    }
+}
@@ -1,13 +1,14 @@
 use crate::error::ApiError;
 use crate::nodes::connection::{Changes, Instantiator};
 use crate::nodes::NodeManager;
-use crate::{multiaddr_to_route, try_address_to_multiaddr};
+use crate::{multiaddr_to_reconnecting_route, try_address_to_multiaddr};
 use std::sync::Arc;
 
 use ockam_core::{async_trait, Error, Route};
 use ockam_multiaddr::proto::Project;
 use ockam_multiaddr::{Match, MultiAddr, Protocol};
 use ockam_node::Context;
+use ockam_transport_tcp::TcpReconnectOptions;
 
 use ockam::identity::models::CredentialAndPurposeKey;
 use ockam::identity::Identifier;
@@ -18,6 +19,7 @@ pub(crate) struct ProjectInstantiator {
     identifier: Identifier,
     timeout: Option<Duration>,
     credential: Option<CredentialAndPurposeKey>,
+    reconnect: Option<TcpReconnectOptions>,
 }
 
 impl ProjectInstantiator {
@@ -30,8 +32,15 @@ impl ProjectInstantiator {
             identifier,
             timeout,
             credential,
+            reconnect: None,
         }
     }
+
+    /// Re-establish the tcp connection to the project when it drops
+    pub fn with_reconnect(mut self, reconnect: Option<TcpReconnectOptions>) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
 }
 
 #[async_trait]
@@ -61,13 +70,17 @@ impl Instantiator for ProjectInstantiator {
             node_manager.resolve_project(&project).await?;
 
         debug!(addr = %project_multiaddr, "creating secure channel");
-        let tcp = multiaddr_to_route(&project_multiaddr, &node_manager.tcp_transport)
-            .await
-            .ok_or_else(|| {
-                ApiError::core(format!(
-                    "Couldn't convert MultiAddr to route: project_multiaddr={project_multiaddr}"
-                ))
-            })?;
+        let tcp = multiaddr_to_reconnecting_route(
+            &project_multiaddr,
+            &node_manager.tcp_transport,
+            self.reconnect.clone(),
+        )
+        .await
+        .ok_or_else(|| {
+            ApiError::core(format!(
+                "Couldn't convert MultiAddr to route: project_multiaddr={project_multiaddr}"
+            ))
+        })?;
 
         debug!("create a secure channel to the project {project_identifier}");
         let sc = node_manager
@@ -78,6 +91,8 @@ impl Instantiator for ProjectInstantiator {
                 Some(vec![project_identifier]),
                 self.timeout,
                 self.credential.clone(),
+                None,
+                Some(project_piece.to_string()),
             )
             .await?;
 
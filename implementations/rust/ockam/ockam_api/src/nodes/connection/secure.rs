@@ -18,6 +18,8 @@ pub(crate) struct SecureChannelInstantiator {
     credential: Option<CredentialAndPurposeKey>,
     authorized_identities: Option<Vec<Identifier>>,
     timeout: Option<Duration>,
+    /// Address of the whole connection, identifying the secure channel to resume
+    resumption_key: Option<MultiAddr>,
 }
 
 impl SecureChannelInstantiator {
@@ -32,8 +34,16 @@ impl SecureChannelInstantiator {
             credential,
             authorized_identities,
             timeout,
+            resumption_key: None,
         }
     }
+
+    /// Resume the secure channels of a previous connection to the same address,
+    /// when that connection is re-created
+    pub(crate) fn with_resumption(mut self, addr: &MultiAddr) -> Self {
+        self.resumption_key = Some(addr.clone());
+        self
+    }
 }
 
 #[async_trait]
@@ -64,6 +74,10 @@ impl Instantiator for SecureChannelInstantiator {
                 self.authorized_identities.clone(),
                 self.timeout,
                 self.credential.clone(),
+                None,
+                self.resumption_key
+                    .as_ref()
+                    .map(|addr| format!("{addr}#{secure_piece}")),
             )
             .await?;
 
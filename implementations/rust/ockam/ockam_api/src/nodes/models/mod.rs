@@ -10,5 +10,6 @@ pub mod portal;
 pub mod relay;
 pub mod secure_channel;
 pub mod services;
+pub mod session;
 pub mod transport;
 pub mod workers;
@@ -145,6 +145,91 @@ impl CreateOutlet {
     }
 }
 
+/// Request body to change the outlet of an inlet, while keeping its alias and listen address
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UpdateInlet {
+    /// The new peer address
+    #[n(1)] pub(crate) outlet_addr: MultiAddr,
+    /// An authorised identity for secure channels
+    #[n(2)] pub(crate) authorized: Option<Identifier>,
+    /// The maximum duration to wait for the new outlet to be available
+    #[n(3)] pub(crate) wait_for_outlet_duration: Option<Duration>,
+    /// Remove the authorised identity. When false, a missing `authorized` keeps the current one
+    #[n(4)] pub(crate) clear_authorized: bool,
+}
+
+impl UpdateInlet {
+    pub fn new(
+        to: MultiAddr,
+        auth: Option<Identifier>,
+        wait_for_outlet_duration: Option<Duration>,
+    ) -> Self {
+        Self {
+            outlet_addr: to,
+            authorized: auth,
+            wait_for_outlet_duration,
+            clear_authorized: false,
+        }
+    }
+
+    /// Remove the authorised identity of the inlet
+    pub fn clear_authorized(mut self) -> Self {
+        self.authorized = None;
+        self.clear_authorized = true;
+        self
+    }
+
+    pub fn outlet_addr(&self) -> &MultiAddr {
+        &self.outlet_addr
+    }
+
+    pub fn authorized(&self) -> Option<Identifier> {
+        self.authorized.clone()
+    }
+}
+
+/// Request body to change the target of an outlet, while keeping its alias and worker address
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UpdateOutlet {
+    /// The new address the portal should connect to
+    #[n(1)] pub socket_addr: SocketAddr,
+}
+
+impl UpdateOutlet {
+    pub fn new(socket_addr: SocketAddr) -> Self {
+        Self { socket_addr }
+    }
+}
+
+/// Request body to delete the inlet and the outlet having a given alias
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct DeletePortal {
+    #[n(1)] pub alias: String,
+}
+
+impl DeletePortal {
+    pub fn new(alias: impl Into<String>) -> Self {
+        Self {
+            alias: alias.into(),
+        }
+    }
+}
+
+/// Response body when deleting a portal
+#[derive(Clone, Debug, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct DeletedPortal {
+    #[n(1)] pub inlet: Option<InletStatus>,
+    #[n(2)] pub outlet: Option<OutletStatus>,
+}
+
 /// Response body when interacting with a portal endpoint
 #[derive(Clone, Debug, Decode, Encode, Serialize, Deserialize)]
 #[rustfmt::skip]
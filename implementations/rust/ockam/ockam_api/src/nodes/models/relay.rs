@@ -2,7 +2,7 @@ use minicbor::{Decode, Encode};
 
 use ockam::identity::Identifier;
 use ockam::remote::RemoteRelayInfo;
-use ockam::route;
+use ockam::{route, RelayRegistryEntry};
 use ockam_core::flow_control::FlowControlId;
 use ockam_multiaddr::MultiAddr;
 
@@ -103,3 +103,80 @@ impl From<RemoteRelayInfo> for RelayInfo {
         }
     }
 }
+
+/// Response body when listing the relays registered at the relay service of a node
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize, serde::Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RegisteredRelay {
+    #[n(1)] name: String,
+    #[n(2)] owner: Option<Identifier>,
+    #[n(3)] forward_route: String,
+    #[n(4)] created_at: u64,
+    #[n(5)] last_seen: u64,
+    #[n(6)] messages_forwarded: u64,
+    #[n(7)] bytes_forwarded: u64,
+}
+
+impl RegisteredRelay {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn owner(&self) -> Option<&Identifier> {
+        self.owner.as_ref()
+    }
+
+    pub fn forward_route(&self) -> &str {
+        &self.forward_route
+    }
+
+    /// Unix timestamp of the first registration, in seconds
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    /// Unix timestamp of the last registration or heartbeat, in seconds
+    pub fn last_seen(&self) -> u64 {
+        self.last_seen
+    }
+
+    pub fn messages_forwarded(&self) -> u64 {
+        self.messages_forwarded
+    }
+
+    pub fn bytes_forwarded(&self) -> u64 {
+        self.bytes_forwarded
+    }
+}
+
+impl From<&RelayRegistryEntry> for RegisteredRelay {
+    fn from(entry: &RelayRegistryEntry) -> Self {
+        Self {
+            name: entry.name(),
+            owner: entry.owner().cloned(),
+            forward_route: entry.forward_route().to_string(),
+            created_at: *entry.created_at(),
+            last_seen: *entry.last_seen(),
+            messages_forwarded: entry.messages_forwarded(),
+            bytes_forwarded: entry.bytes_forwarded(),
+        }
+    }
+}
+
+/// Relay names starting with `prefix` which can only be registered at the relay
+/// service of a node by one of the given identities
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RelayReservation {
+    pub prefix: String,
+    pub identifiers: Vec<Identifier>,
+}
+
+impl RelayReservation {
+    pub fn new(prefix: impl Into<String>, identifiers: Vec<Identifier>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            identifiers,
+        }
+    }
+}
@@ -22,6 +22,8 @@ pub struct CreateSecureChannelRequest {
     #[n(4)] pub timeout: Option<Duration>,
     #[n(5)] pub identity_name: Option<String>,
     #[n(6)] pub credential_name: Option<String>,
+    /// Attributes of the credential presented to the listener, all by default
+    #[n(7)] pub disclosed_attributes: Option<Vec<String>>,
 }
 
 impl CreateSecureChannelRequest {
@@ -38,8 +40,15 @@ impl CreateSecureChannelRequest {
             timeout: Some(DEFAULT_TIMEOUT),
             identity_name,
             credential_name,
+            disclosed_attributes: None,
         }
     }
+
+    /// Only present the given attributes of a credential issued with selective disclosure
+    pub fn with_disclosed_attributes(mut self, disclosed_attributes: Vec<String>) -> Self {
+        self.disclosed_attributes = Some(disclosed_attributes);
+        self
+    }
 }
 
 /// Response body when instructing a node to create a Secure Channel
@@ -0,0 +1,88 @@
+//! Monitored sessions request/response types
+
+use std::time::Duration;
+
+use minicbor::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// Status of a session monitored by a node
+#[derive(Debug, Clone, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionStatus {
+    #[n(1)] pub key: String,
+    /// Kind of the resource using the session: `tcp-inlet` or `relay`
+    #[n(2)] pub kind: String,
+    /// Name of the resource using the session, for example the alias of an inlet
+    #[n(3)] pub name: String,
+    #[n(4)] pub ping_route: String,
+    /// `up`, `degraded` when the session is being replaced, or `down`
+    #[n(5)] pub status: String,
+    /// Unix timestamp of the last status change, in seconds
+    #[n(6)] pub status_since: u64,
+    /// Number of pings sent without a response
+    #[n(7)] pub pending_pings: u64,
+    /// Time it took to be up again, the last time the session was replaced
+    #[n(8)] pub last_recovery_time_ms: Option<u64>,
+}
+
+/// Response body when listing the sessions monitored by a node
+#[derive(Debug, Clone, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionList {
+    #[n(1)] pub list: Vec<SessionStatus>,
+}
+
+impl SessionList {
+    pub fn new(list: Vec<SessionStatus>) -> Self {
+        Self { list }
+    }
+}
+
+/// Status change of a monitored session
+#[derive(Debug, Clone, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionEvent {
+    /// Sequence number of the event, increasing for each event of a node
+    #[n(1)] pub seq: u64,
+    #[n(2)] pub key: String,
+    #[n(3)] pub kind: String,
+    #[n(4)] pub name: String,
+    #[n(5)] pub status: String,
+    #[n(6)] pub previous_status: String,
+    /// Unix timestamp of the event, in seconds
+    #[n(7)] pub at: u64,
+    /// When the session is up again, the time elapsed since it stopped being up
+    #[n(8)] pub recovery_time_ms: Option<u64>,
+}
+
+/// Request body to receive the status changes of the sessions monitored by a node.
+///
+/// The response is sent as soon as there are events after the given sequence number,
+/// or when the wait duration is elapsed. Subscribers repeat the request with the
+/// sequence number of the last event they received.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct GetSessionEvents {
+    #[n(1)] pub after: Option<u64>,
+    #[n(2)] pub wait: Option<Duration>,
+}
+
+impl GetSessionEvents {
+    pub fn new(after: Option<u64>, wait: Option<Duration>) -> Self {
+        Self { after, wait }
+    }
+}
+
+/// Response body when receiving the status changes of the sessions monitored by a node
+#[derive(Debug, Clone, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionEventList {
+    #[n(1)] pub events: Vec<SessionEvent>,
+    /// Sequence number of the last event of the node, to use in the next request
+    #[n(2)] pub last: u64,
+}
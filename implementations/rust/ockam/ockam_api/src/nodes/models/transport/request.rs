@@ -1,4 +1,7 @@
 use minicbor::{Decode, Encode};
+use std::time::Duration;
+
+use ockam_transport_tcp::{TcpConnectionOptions, TcpKeepaliveOptions, TcpListenerOptions};
 
 /// Request body when instructing a node to create a transport
 #[derive(Debug, Clone, Decode, Encode, PartialEq, Eq)]
@@ -7,11 +10,21 @@ use minicbor::{Decode, Encode};
 pub struct CreateTcpConnection {
     /// The address payload for the transport
     #[n(1)] pub addr: String,
+    /// Keepalive, heartbeat and idle timeout settings
+    #[n(2)] pub liveness: Option<TcpLiveness>,
 }
 
 impl CreateTcpConnection {
     pub fn new(addr: String) -> Self {
-        Self { addr }
+        Self {
+            addr,
+            liveness: None,
+        }
+    }
+
+    pub fn with_liveness(mut self, liveness: TcpLiveness) -> Self {
+        self.liveness = Some(liveness);
+        self
     }
 }
 
@@ -22,9 +35,107 @@ impl CreateTcpConnection {
 pub struct CreateTcpListener {
     /// The address payload for the transport
     #[n(1)] pub addr: String,
+    /// Keepalive, heartbeat and idle timeout settings of accepted connections
+    #[n(2)] pub liveness: Option<TcpLiveness>,
 }
 
 impl CreateTcpListener {
+    pub fn new(addr: String) -> Self {
+        Self {
+            addr,
+            liveness: None,
+        }
+    }
+
+    pub fn with_liveness(mut self, liveness: TcpLiveness) -> Self {
+        self.liveness = Some(liveness);
+        self
+    }
+}
+
+/// Settings used to detect dead peers on TCP connections. Durations are in milliseconds,
+/// unset values keep the transport defaults
+#[derive(Debug, Clone, Default, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpLiveness {
+    /// Inactivity before TCP keepalive probes are sent
+    #[n(1)] pub keepalive_time: Option<u64>,
+    /// Disable TCP keepalive probes
+    #[n(2)] pub no_keepalive: bool,
+    /// Interval of the heartbeats sent when nothing else is sent
+    #[n(3)] pub heartbeat_interval: Option<u64>,
+    /// Close the connection when nothing is received for that long
+    #[n(4)] pub idle_timeout: Option<u64>,
+}
+
+impl TcpLiveness {
+    fn keepalive(&self) -> Option<TcpKeepaliveOptions> {
+        if self.no_keepalive {
+            None
+        } else {
+            let keepalive = TcpKeepaliveOptions::new();
+            Some(match self.keepalive_time {
+                Some(time) => keepalive.with_time(Duration::from_millis(time)),
+                None => keepalive,
+            })
+        }
+    }
+
+    pub fn connection_options(&self, mut options: TcpConnectionOptions) -> TcpConnectionOptions {
+        options = match self.keepalive() {
+            Some(keepalive) => options.with_keepalive(keepalive),
+            None => options.without_keepalive(),
+        };
+        if let Some(interval) = self.heartbeat_interval {
+            options = options.with_heartbeat_interval(Duration::from_millis(interval));
+        }
+        if let Some(timeout) = self.idle_timeout {
+            options = options.with_idle_timeout(Duration::from_millis(timeout));
+        }
+        options
+    }
+
+    pub fn listener_options(&self, mut options: TcpListenerOptions) -> TcpListenerOptions {
+        options = match self.keepalive() {
+            Some(keepalive) => options.with_keepalive(keepalive),
+            None => options.without_keepalive(),
+        };
+        if let Some(interval) = self.heartbeat_interval {
+            options = options.with_heartbeat_interval(Duration::from_millis(interval));
+        }
+        if let Some(timeout) = self.idle_timeout {
+            options = options.with_idle_timeout(Duration::from_millis(timeout));
+        }
+        options
+    }
+}
+
+/// Request body when instructing a node to create a WebSocket or UDS listener
+#[derive(Debug, Clone, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateTransportListener {
+    /// The socket address or path to listen on
+    #[n(1)] pub addr: String,
+}
+
+impl CreateTransportListener {
+    pub fn new(addr: String) -> Self {
+        Self { addr }
+    }
+}
+
+/// Request body when instructing a node to connect to a WebSocket or UDS peer
+#[derive(Debug, Clone, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateTransportConnection {
+    /// The socket address or path of the peer
+    #[n(1)] pub addr: String,
+}
+
+impl CreateTransportConnection {
     pub fn new(addr: String) -> Self {
         Self { addr }
     }
@@ -23,8 +23,8 @@ pub struct TransportStatus {
     #[n(4)] pub worker_addr: String,
     /// Corresponding worker address
     #[n(5)] pub processor_address: String,
-    /// Corresponding flow control id
-    #[n(6)] pub flow_control_id: FlowControlId,
+    /// Corresponding flow control id, if the transport supports flow control
+    #[n(6)] pub flow_control_id: Option<FlowControlId>,
 }
 
 impl TransportStatus {
@@ -35,7 +35,26 @@ impl TransportStatus {
             socket_addr: api_transport.socket_address.to_string(),
             worker_addr: api_transport.worker_address.clone(),
             processor_address: api_transport.processor_address.clone(),
-            flow_control_id: api_transport.flow_control_id,
+            flow_control_id: Some(api_transport.flow_control_id),
+        }
+    }
+
+    /// Status of a transport which doesn't support flow control, such as
+    /// the WebSocket and UDS transports
+    pub fn without_flow_control(
+        tt: TransportType,
+        tm: TransportMode,
+        socket_addr: impl Into<String>,
+        worker_addr: impl Into<String>,
+        processor_address: impl Into<String>,
+    ) -> Self {
+        Self {
+            tt,
+            tm,
+            socket_addr: socket_addr.into(),
+            worker_addr: worker_addr.into(),
+            processor_address: processor_address.into(),
+            flow_control_id: None,
         }
     }
 
@@ -1,9 +1,13 @@
+use crate::nodes::connection::Connection;
 use crate::nodes::service::Alias;
+use crate::session::sessions::Key;
 use ockam::identity::Identifier;
 use ockam::identity::{SecureChannel, SecureChannelListener};
 use ockam::remote::RemoteRelayInfo;
 use ockam_core::compat::collections::BTreeMap;
-use ockam_core::{Address, Route};
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::{Address, IncomingAccessControl, Route};
+use ockam_multiaddr::MultiAddr;
 use ockam_node::compat::asynchronous::RwLock;
 use std::borrow::Borrow;
 use std::fmt::Display;
@@ -156,6 +160,16 @@ pub(crate) struct InletInfo {
     pub(crate) bind_addr: String,
     pub(crate) worker_addr: Address,
     pub(crate) outlet_route: Route,
+    /// Address of the outlet, as requested when the inlet was created
+    pub(crate) outlet_addr: MultiAddr,
+    pub(crate) prefix_route: Route,
+    pub(crate) suffix_route: Route,
+    pub(crate) authorized: Option<Identifier>,
+    pub(crate) access_control: Arc<dyn IncomingAccessControl>,
+    /// Session monitoring the connection to the outlet, if any
+    pub(crate) session: Option<Key>,
+    /// Current connection to the outlet, replaced when the session recreates the inlet
+    pub(crate) connection: Option<Arc<Mutex<Connection>>>,
 }
 
 impl InletInfo {
@@ -163,6 +177,10 @@ impl InletInfo {
         bind_addr: &str,
         worker_addr: Option<&Address>,
         outlet_route: &Route,
+        outlet_addr: &MultiAddr,
+        prefix_route: &Route,
+        suffix_route: &Route,
+        access_control: Arc<dyn IncomingAccessControl>,
     ) -> Self {
         let worker_addr = match worker_addr {
             Some(addr) => addr.clone(),
@@ -172,6 +190,13 @@ impl InletInfo {
             bind_addr: bind_addr.to_owned(),
             worker_addr,
             outlet_route: outlet_route.to_owned(),
+            outlet_addr: outlet_addr.to_owned(),
+            prefix_route: prefix_route.to_owned(),
+            suffix_route: suffix_route.to_owned(),
+            authorized: None,
+            access_control,
+            session: None,
+            connection: None,
         }
     }
 }
@@ -180,10 +205,17 @@ impl InletInfo {
 pub struct OutletInfo {
     pub(crate) socket_addr: SocketAddr,
     pub(crate) worker_addr: Address,
+    pub(crate) access_control: Arc<dyn IncomingAccessControl>,
+    pub(crate) reachable_from_default_secure_channel: bool,
 }
 
 impl OutletInfo {
-    pub(crate) fn new(socket_addr: &SocketAddr, worker_addr: Option<&Address>) -> Self {
+    pub(crate) fn new(
+        socket_addr: &SocketAddr,
+        worker_addr: Option<&Address>,
+        access_control: Arc<dyn IncomingAccessControl>,
+        reachable_from_default_secure_channel: bool,
+    ) -> Self {
         let worker_addr = match worker_addr {
             Some(addr) => addr.clone(),
             None => Address::from_string(""),
@@ -191,10 +223,40 @@ impl OutletInfo {
         Self {
             socket_addr: *socket_addr,
             worker_addr,
+            access_control,
+            reachable_from_default_secure_channel,
+        }
+    }
+}
+
+/// A WebSocket or UDS listener started through the node manager
+#[derive(Clone)]
+pub(crate) struct TransportListenerInfo {
+    /// Socket address or path the listener is bound to
+    pub(crate) socket_addr: String,
+}
+
+impl TransportListenerInfo {
+    pub(crate) fn new(socket_addr: impl Into<String>) -> Self {
+        Self {
+            socket_addr: socket_addr.into(),
         }
     }
 }
 
+/// A WebSocket or UDS connection established through the node manager
+#[derive(Clone)]
+pub(crate) struct TransportConnectionInfo {
+    /// Address of the sender worker
+    pub(crate) worker_addr: Address,
+}
+
+impl TransportConnectionInfo {
+    pub(crate) fn new(worker_addr: Address) -> Self {
+        Self { worker_addr }
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct Registry {
     pub(crate) secure_channels: SecureChannelRegistry,
@@ -208,6 +270,10 @@ pub(crate) struct Registry {
     pub(crate) relays: RegistryOf<String, RemoteRelayInfo>,
     pub(crate) inlets: RegistryOf<Alias, InletInfo>,
     pub(crate) outlets: RegistryOf<Alias, OutletInfo>,
+    pub(crate) ws_listeners: RegistryOf<Address, TransportListenerInfo>,
+    pub(crate) ws_connections: RegistryOf<String, TransportConnectionInfo>,
+    pub(crate) uds_listeners: RegistryOf<Address, TransportListenerInfo>,
+    pub(crate) uds_connections: RegistryOf<String, TransportConnectionInfo>,
 }
 
 pub(crate) struct RegistryOf<K, V> {
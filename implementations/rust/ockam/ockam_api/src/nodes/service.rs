@@ -9,17 +9,20 @@ use std::time::Duration;
 
 use minicbor::{Decoder, Encode};
 
+pub use authorization::*;
 pub use node_identities::*;
 use ockam::identity::models::CredentialAndPurposeKey;
 use ockam::identity::CredentialsServerModule;
 use ockam::identity::TrustContext;
 use ockam::identity::Vault;
 use ockam::identity::{
-    Credentials, CredentialsServer, Identities, IdentitiesRepository, IdentityAttributesReader,
+    CredentialSchemas, Credentials, CredentialsServer, Identities, IdentitiesRepository,
+    IdentityAttributesReader,
 };
-use ockam::identity::{Identifier, SecureChannels};
+use ockam::identity::{Identifier, IdentityIdAccessControl, SecureChannels};
 use ockam::{
-    Address, Context, RelayService, RelayServiceOptions, Result, Routed, TcpTransport, Worker,
+    Address, Context, RelayRegistry, RelayService, RelayServiceOptions, Result, Routed,
+    TcpTransport, Worker,
 };
 use ockam_abac::expr::{eq, ident, str};
 use ockam_abac::{Action, Env, Expr, PolicyAccessControl, PolicyStorage, Resource};
@@ -29,6 +32,10 @@ use ockam_core::flow_control::FlowControlId;
 use ockam_core::IncomingAccessControl;
 use ockam_core::{AllowAll, AsyncTryClone};
 use ockam_multiaddr::MultiAddr;
+use ockam_transport_tcp::TcpReconnectOptions;
+#[cfg(unix)]
+use ockam_transport_uds::UdsTransport;
+use ockam_transport_websocket::WebSocketTransport;
 
 use crate::bootstrapped_identities_store::BootstrapedIdentityStore;
 use crate::bootstrapped_identities_store::PreTrustedIdentities;
@@ -37,12 +44,15 @@ use crate::cloud::{AuthorityNode, ProjectNode};
 use crate::config::cli::TrustContextConfig;
 use crate::config::lookup::ProjectLookup;
 use crate::error::ApiError;
+#[cfg(unix)]
+use crate::nodes::connection::PlainUdsInstantiator;
 use crate::nodes::connection::{
-    Connection, ConnectionBuilder, PlainTcpInstantiator, ProjectInstantiator,
-    SecureChannelInstantiator,
+    Connection, ConnectionBuilder, PlainTcpInstantiator, PlainWebSocketInstantiator,
+    ProjectInstantiator, SecureChannelInstantiator,
 };
 use crate::nodes::models::base::NodeStatus;
 use crate::nodes::models::portal::{OutletList, OutletStatus};
+use crate::nodes::models::relay::RelayReservation;
 use crate::nodes::models::transport::{TransportMode, TransportType};
 use crate::nodes::models::workers::{WorkerList, WorkerStatus};
 use crate::nodes::registry::KafkaServiceKind;
@@ -51,6 +61,7 @@ use crate::DefaultAddress;
 
 use super::registry::Registry;
 
+mod authorization;
 pub(crate) mod background_node;
 pub(crate) mod credentials;
 mod flow_controls;
@@ -61,8 +72,13 @@ mod node_services;
 mod policy;
 mod portals;
 pub mod relay;
+pub mod resources;
 mod secure_channel;
+mod sessions;
 mod transport;
+#[cfg(unix)]
+mod uds;
+mod web_socket;
 
 const TARGET: &str = "ockam_api::nodemanager::service";
 
@@ -95,12 +111,22 @@ pub struct NodeManager {
     node_name: String,
     api_transport_flow_control_id: FlowControlId,
     pub(crate) tcp_transport: TcpTransport,
+    pub(crate) web_socket_transport: Option<Arc<WebSocketTransport>>,
+    #[cfg(unix)]
+    pub(crate) uds_transport: Option<Arc<UdsTransport>>,
     enable_credential_checks: bool,
     identifier: Identifier,
     pub(crate) secure_channels: Arc<SecureChannels>,
     trust_context: Option<TrustContext>,
     pub(crate) registry: Registry,
+    pub(crate) relay_registry: RelayRegistry,
+    relay_ttl: Option<Duration>,
+    relay_reservations: Vec<RelayReservation>,
     policies: Arc<dyn PolicyStorage>,
+    /// Record the resources created through the API to recreate them on restart
+    persist_resources: bool,
+    /// Addresses used to send messages on behalf of remote callers
+    remote_callers: RemoteCallers,
 }
 
 impl NodeManager {
@@ -140,6 +166,15 @@ impl NodeManager {
         &self.tcp_transport
     }
 
+    pub fn web_socket_transport(&self) -> Option<&WebSocketTransport> {
+        self.web_socket_transport.as_deref()
+    }
+
+    #[cfg(unix)]
+    pub fn uds_transport(&self) -> Option<&UdsTransport> {
+        self.uds_transport.as_deref()
+    }
+
     pub async fn list_outlets(&self) -> OutletList {
         OutletList::new(
             self.registry
@@ -251,13 +286,16 @@ impl NodeManager {
                 self.policies.set_policy(r, a, &fallback).await?
             }
             let policies = self.policies.clone();
-            Ok(Arc::new(PolicyAccessControl::new(
-                policies,
-                self.identities_repository(),
-                r.clone(),
-                a.clone(),
-                env,
-            )))
+            Ok(Arc::new(
+                PolicyAccessControl::new(
+                    policies,
+                    self.identities_repository(),
+                    r.clone(),
+                    a.clone(),
+                    env,
+                )
+                .with_credential_schemas(self.identities().credential_schemas()),
+            ))
         } else {
             Ok(Arc::new(AllowAll))
         }
@@ -276,6 +314,8 @@ pub struct NodeManagerGeneralOptions {
     pre_trusted_identities: Option<PreTrustedIdentities>,
     start_default_services: bool,
     persistent: bool,
+    relay_ttl: Option<Duration>,
+    relay_reservations: Vec<RelayReservation>,
 }
 
 impl NodeManagerGeneralOptions {
@@ -292,8 +332,24 @@ impl NodeManagerGeneralOptions {
             pre_trusted_identities,
             start_default_services,
             persistent,
+            relay_ttl: None,
+            relay_reservations: vec![],
         }
     }
+
+    /// Evict the relays registered at the relay service of the node when they stop
+    /// sending heartbeats for longer than the given duration
+    pub fn with_relay_ttl(mut self, relay_ttl: Option<Duration>) -> Self {
+        self.relay_ttl = relay_ttl;
+        self
+    }
+
+    /// Only allow the given identities to register relays whose name starts with
+    /// the prefix of a reservation at the relay service of the node
+    pub fn with_relay_reservations(mut self, relay_reservations: Vec<RelayReservation>) -> Self {
+        self.relay_reservations = relay_reservations;
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -316,6 +372,9 @@ pub struct ApiTransport {
 pub struct NodeManagerTransportOptions {
     api_transport_flow_control_id: FlowControlId,
     tcp_transport: TcpTransport,
+    web_socket_transport: Option<WebSocketTransport>,
+    #[cfg(unix)]
+    uds_transport: Option<UdsTransport>,
 }
 
 impl NodeManagerTransportOptions {
@@ -323,20 +382,45 @@ impl NodeManagerTransportOptions {
         Self {
             api_transport_flow_control_id,
             tcp_transport,
+            web_socket_transport: None,
+            #[cfg(unix)]
+            uds_transport: None,
         }
     }
+
+    /// Make WebSocket listeners and connections manageable through the node manager
+    pub fn with_web_socket_transport(mut self, web_socket_transport: WebSocketTransport) -> Self {
+        self.web_socket_transport = Some(web_socket_transport);
+        self
+    }
+
+    /// Make Unix domain socket listeners and connections manageable through the node manager
+    #[cfg(unix)]
+    pub fn with_uds_transport(mut self, uds_transport: UdsTransport) -> Self {
+        self.uds_transport = Some(uds_transport);
+        self
+    }
 }
 
 pub struct NodeManagerTrustOptions {
     trust_context_config: Option<TrustContextConfig>,
+    credential_schemas: CredentialSchemas,
 }
 
 impl NodeManagerTrustOptions {
     pub fn new(trust_context_config: Option<TrustContextConfig>) -> Self {
         Self {
             trust_context_config,
+            credential_schemas: CredentialSchemas::new(),
         }
     }
+
+    /// Validate the attributes of the credentials verified by the node with these schemas,
+    /// and give their declared type to the attributes used in policies
+    pub fn with_credential_schemas(mut self, credential_schemas: CredentialSchemas) -> Self {
+        self.credential_schemas = credential_schemas;
+        self
+    }
 }
 
 impl NodeManager {
@@ -378,15 +462,21 @@ impl NodeManager {
         let secure_channels = SecureChannels::builder()
             .with_vault(vault)
             .with_identities_repository(identities_repository.clone())
+            .with_credential_schemas(trust_options.credential_schemas)
             .build();
 
         let policies: Arc<dyn PolicyStorage> = Arc::new(node_state.policies_storage().await?);
+        let persist_resources =
+            general_options.persistent && !node_state.config().setup().ephemeral;
 
         let mut s = Self {
             cli_state,
             node_name: general_options.node_name,
             api_transport_flow_control_id: transport_options.api_transport_flow_control_id,
             tcp_transport: transport_options.tcp_transport,
+            web_socket_transport: transport_options.web_socket_transport.map(Arc::new),
+            #[cfg(unix)]
+            uds_transport: transport_options.uds_transport.map(Arc::new),
             enable_credential_checks: trust_options.trust_context_config.is_some()
                 && trust_options
                     .trust_context_config
@@ -398,7 +488,12 @@ impl NodeManager {
             secure_channels,
             trust_context: None,
             registry: Default::default(),
+            relay_registry: Default::default(),
+            relay_ttl: general_options.relay_ttl,
+            relay_reservations: general_options.relay_reservations,
             policies,
+            persist_resources,
+            remote_callers: Default::default(),
         };
 
         if let Some(tc) = trust_options.trust_context_config {
@@ -438,14 +533,22 @@ impl NodeManager {
         self.start_uppercase_service_impl(ctx, DefaultAddress::UPPERCASE_SERVICE.into())
             .await?;
 
-        RelayService::create(
-            ctx,
-            DefaultAddress::RELAY_SERVICE,
-            RelayServiceOptions::new()
-                .service_as_consumer(api_flow_control_id)
-                .relay_as_consumer(api_flow_control_id),
-        )
-        .await?;
+        let mut relay_service_options = RelayServiceOptions::new()
+            .service_as_consumer(api_flow_control_id)
+            .relay_as_consumer(api_flow_control_id)
+            .with_registry(self.relay_registry.clone());
+        if let Some(relay_ttl) = self.relay_ttl {
+            relay_service_options = relay_service_options.with_relay_ttl(relay_ttl);
+        }
+        for reservation in &self.relay_reservations {
+            relay_service_options = relay_service_options.with_reserved_prefix(
+                reservation.prefix.clone(),
+                Arc::new(IdentityIdAccessControl::new(
+                    reservation.identifiers.clone(),
+                )),
+            );
+        }
+        RelayService::create(ctx, DefaultAddress::RELAY_SERVICE, relay_service_options).await?;
 
         self.create_secure_channel_listener(
             DefaultAddress::SECURE_CHANNEL_LISTENER.into(),
@@ -456,6 +559,16 @@ impl NodeManager {
         )
         .await?;
 
+        // The node manager API can be used by remote identities, through the default
+        // secure channel listener, if the policies of the node-manager resource allow it
+        if let Some(flow_control_id) = ctx
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+        {
+            ctx.flow_controls()
+                .add_consumer(NODEMANAGER_ADDR, &flow_control_id);
+        }
+
         // If we've been configured with a trust context, we can start Credential Exchange service
         if let Ok(tc) = self.trust_context() {
             self.start_credentials_service_impl(
@@ -506,12 +619,40 @@ impl NodeManager {
             None => self.get_identifier(None).await?,
         };
         let authorized = authorized.map(|authorized| vec![authorized]);
-        self.connect(ctx, addr, identifier, authorized, credential, timeout)
+        self.connect(ctx, addr, identifier, authorized, credential, timeout, None)
             .await
     }
 
+    /// Create a connection like [`make_connection`](Self::make_connection), where the TCP
+    /// connection, if any, is re-established when it drops.
+    ///
+    /// This is used for the connections monitored by sessions: the TCP connection keeps
+    /// its address across reconnections and the session is replaced as soon as the
+    /// connection is re-established.
+    pub async fn make_reconnecting_connection(
+        &self,
+        ctx: Arc<Context>,
+        addr: &MultiAddr,
+        authorized: Option<Identifier>,
+        timeout: Option<Duration>,
+    ) -> Result<Connection> {
+        let identifier = self.get_identifier(None).await?;
+        let authorized = authorized.map(|authorized| vec![authorized]);
+        self.connect(
+            ctx,
+            addr,
+            identifier,
+            authorized,
+            None,
+            timeout,
+            Some(TcpReconnectOptions::new()),
+        )
+        .await
+    }
+
     /// Resolve project ID (if any), create secure channel (if needed) and create a tcp connection
     /// Returns [`Connection`]
+    #[allow(clippy::too_many_arguments)]
     async fn connect(
         &self,
         ctx: Arc<Context>,
@@ -520,21 +661,36 @@ impl NodeManager {
         authorized: Option<Vec<Identifier>>,
         credential: Option<CredentialAndPurposeKey>,
         timeout: Option<Duration>,
+        reconnect: Option<TcpReconnectOptions>,
     ) -> Result<Connection> {
         debug!(?timeout, "connecting to {}", &addr);
-        let connection = ConnectionBuilder::new(addr.clone())
+        let builder = ConnectionBuilder::new(addr.clone())
             .instantiate(
                 ctx.clone(),
                 self,
-                ProjectInstantiator::new(identifier.clone(), timeout, credential.clone()),
+                ProjectInstantiator::new(identifier.clone(), timeout, credential.clone())
+                    .with_reconnect(reconnect.clone()),
             )
             .await?
-            .instantiate(ctx.clone(), self, PlainTcpInstantiator::new())
+            // WebSocket addresses contain a tcp port, so they must be instantiated first
+            .instantiate(ctx.clone(), self, PlainWebSocketInstantiator::new())
             .await?
             .instantiate(
                 ctx.clone(),
                 self,
-                SecureChannelInstantiator::new(&identifier, credential, timeout, authorized),
+                PlainTcpInstantiator::new().with_reconnect(reconnect),
+            )
+            .await?;
+        #[cfg(unix)]
+        let builder = builder
+            .instantiate(ctx.clone(), self, PlainUdsInstantiator::new())
+            .await?;
+        let connection = builder
+            .instantiate(
+                ctx.clone(),
+                self,
+                SecureChannelInstantiator::new(&identifier, credential, timeout, authorized)
+                    .with_resumption(addr),
             )
             .await?
             .build();
@@ -544,6 +700,25 @@ impl NodeManager {
         Ok(connection)
     }
 
+    /// Stop the secure channels and the tcp connection created for a connection.
+    /// Errors are only logged since the connection may already be closed
+    pub(crate) async fn close_connection(&self, ctx: &Context, connection: &Connection) {
+        for encryptor in &connection.secure_channel_encryptors {
+            if let Err(error) = self.delete_secure_channel(ctx, encryptor).await {
+                debug!("cannot delete secure channel `{encryptor}`: {error}");
+            }
+        }
+        if let Some(tcp_connection) = connection.tcp_connection.as_ref() {
+            if let Err(error) = self
+                .tcp_transport
+                .disconnect(tcp_connection.sender_address().clone())
+                .await
+            {
+                debug!("cannot stop tcp worker `{tcp_connection}`: {error}");
+            }
+        }
+    }
+
     pub(crate) async fn resolve_project(&self, name: &str) -> Result<(MultiAddr, Identifier)> {
         let projects = ProjectLookup::from_state(self.cli_state.projects.list()?)
             .await
@@ -574,6 +749,7 @@ impl NodeManagerWorker {
         ctx: &mut Context,
         req: &RequestHeader,
         dec: &mut Decoder<'_>,
+        caller: &Caller,
     ) -> Result<Vec<u8>> {
         debug! {
             target: TARGET,
@@ -631,6 +807,44 @@ impl NodeManagerWorker {
                 encode_response(self.delete_tcp_listener(req, dec).await)?
             }
 
+            // ==*== WebSocket ==*==
+            (Get, ["node", "ws", "connection"]) => self.get_ws_connections(req).await.to_vec()?,
+            (Post, ["node", "ws", "connection"]) => {
+                encode_response(self.create_ws_connection(req, dec).await)?
+            }
+            (Delete, ["node", "ws", "connection"]) => {
+                encode_response(self.delete_ws_connection(req, dec).await)?
+            }
+            (Get, ["node", "ws", "listener"]) => self.get_ws_listeners(req).await.to_vec()?,
+            (Post, ["node", "ws", "listener"]) => {
+                encode_response(self.create_ws_listener(req, dec).await)?
+            }
+            (Delete, ["node", "ws", "listener"]) => {
+                encode_response(self.delete_ws_listener(req, dec).await)?
+            }
+
+            // ==*== Unix domain sockets ==*==
+            #[cfg(unix)]
+            (Get, ["node", "uds", "connection"]) => self.get_uds_connections(req).await.to_vec()?,
+            #[cfg(unix)]
+            (Post, ["node", "uds", "connection"]) => {
+                encode_response(self.create_uds_connection(req, dec).await)?
+            }
+            #[cfg(unix)]
+            (Delete, ["node", "uds", "connection"]) => {
+                encode_response(self.delete_uds_connection(req, dec).await)?
+            }
+            #[cfg(unix)]
+            (Get, ["node", "uds", "listener"]) => self.get_uds_listeners(req).await.to_vec()?,
+            #[cfg(unix)]
+            (Post, ["node", "uds", "listener"]) => {
+                encode_response(self.create_uds_listener(req, dec).await)?
+            }
+            #[cfg(unix)]
+            (Delete, ["node", "uds", "listener"]) => {
+                encode_response(self.delete_uds_listener(req, dec).await)?
+            }
+
             // ==*== Credential ==*==
             (Post, ["node", "credentials", "actions", "get"]) => self
                 .get_credential(req, dec, ctx)
@@ -726,6 +940,10 @@ impl NodeManagerWorker {
             (Post, ["node", "forwarder"]) => {
                 encode_response(self.create_relay(ctx, req, dec.decode()?).await)?
             }
+            (Get, ["relays"]) => encode_response(self.get_registered_relays(req).await)?,
+            (Delete, ["relays", name]) => {
+                encode_response(self.evict_registered_relay(ctx, req, name).await)?
+            }
 
             // ==*== Inlets & Outlets ==*==
             (Get, ["node", "inlet"]) => self.get_inlets(req).await.to_vec()?,
@@ -742,15 +960,26 @@ impl NodeManagerWorker {
                 encode_response(self.delete_outlet(req, alias).await)?
             }
             (Delete, ["node", "inlet", alias]) => {
-                encode_response(self.delete_inlet(req, alias).await)?
+                encode_response(self.delete_inlet(ctx, req, alias).await)?
+            }
+            (Patch, ["node", "inlet", alias]) => {
+                encode_response(self.update_inlet(ctx, req, alias, dec.decode()?).await)?
+            }
+            (Patch, ["node", "outlet", alias]) => {
+                encode_response(self.update_outlet(ctx, req, alias, dec.decode()?).await)?
+            }
+            (Delete, ["node", "portal"]) => {
+                encode_response(self.delete_portal(ctx, req, dec.decode()?).await)?
             }
-            (Delete, ["node", "portal"]) => todo!(),
 
             // ==*== Flow Controls ==*==
             (Post, ["node", "flow_controls", "add_consumer"]) => {
                 encode_response(self.add_consumer(ctx, req, dec))?
             }
 
+            // ==*== Sessions ==*==
+            (Get, ["node", "sessions"]) => self.get_sessions(req).to_vec()?,
+
             // ==*== Workers ==*==
             (Get, ["node", "workers"]) => {
                 let workers = ctx.list_workers().await?;
@@ -780,7 +1009,7 @@ impl NodeManagerWorker {
             }
 
             // ==*== Messages ==*==
-            (Post, ["v0", "message"]) => self.send_message(ctx, req, dec).await?,
+            (Post, ["v0", "message"]) => self.send_message(ctx, req, dec, caller).await?,
 
             // ==*== Catch-all for Unimplemented APIs ==*==
             _ => {
@@ -811,22 +1040,49 @@ impl Worker for NodeManagerWorker {
                 return Ok(());
             }
         };
-
-        let r = match self.handle_request(ctx, &req, &mut dec).await {
-            Ok(r) => r,
-            Err(err) => {
-                error! {
-                    target: TARGET,
-                    re     = %req.id(),
-                    method = ?req.method(),
-                    path   = %req.path(),
-                    code   = %err.code(),
-                    cause  = ?err.source(),
-                    "failed to handle request"
-                }
-                Response::internal_error(&req, &format!("failed to handle request: {err} {req:?}"))
+        let body = &msg.as_body()[dec.position()..];
+
+        // Requests of remote identities must be authorized by a policy,
+        // and requests of unknown callers are denied
+        let caller = match self.node_manager.request_caller(ctx, &msg) {
+            Some(caller)
+                if self
+                    .node_manager
+                    .is_request_authorized(&msg, &req, &caller)
+                    .await
+                    .unwrap_or(false) =>
+            {
+                Some(caller)
+            }
+            _ => None,
+        };
+        let r = if let Some(caller) = caller {
+            if matches!(req.method(), Some(Method::Get)) && req.path() == "/node/sessions/events" {
+                // This request can wait for new events, and is responded to separately
+                return self.get_session_events(ctx, &msg, &req, &mut dec).await;
+            }
+            match self.handle_request(ctx, &req, &mut dec, &caller).await {
+                Ok(r) => r,
+                Err(err) => {
+                    error! {
+                        target: TARGET,
+                        re     = %req.id(),
+                        method = ?req.method(),
+                        path   = %req.path(),
+                        code   = %err.code(),
+                        cause  = ?err.source(),
+                        "failed to handle request"
+                    }
+                    Response::internal_error(
+                        &req,
+                        &format!("failed to handle request: {err} {req:?}"),
+                    )
                     .to_vec()?
+                }
             }
+        } else {
+            Response::forbidden(&req, "The identity is not authorized to make this request")
+                .to_vec()?
         };
         debug! {
             target: TARGET,
@@ -835,6 +1091,7 @@ impl Worker for NodeManagerWorker {
             path   = %req.path(),
             "responding"
         }
+        self.persist_resource(&req, body, &r);
         ctx.send(msg.return_route(), r).await
     }
 }
@@ -0,0 +1,522 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use ockam::identity::{Identifier, IdentitySecureChannelLocalInfo};
+use ockam::{Result, Routed};
+use ockam_abac::expr::str;
+use ockam_abac::{Action, Env, PolicyAccessControl, Resource};
+use ockam_core::api::{Method, RequestHeader};
+use ockam_core::{Address, IncomingAccessControl, LocalMessage, RelayMessage, Route};
+use ockam_node::Context;
+
+use crate::nodes::NodeManager;
+
+/// Resource of the policies authorizing the requests sent to the node manager
+/// through a secure channel, when there is no policy for the route of a request
+pub const NODE_MANAGER_RESOURCE: &str = "node-manager";
+
+/// Action of the requests which only read the state of the node
+pub const READ_ACTION: &str = "read";
+
+/// Action of the requests which modify the node
+pub const WRITE_ACTION: &str = "write";
+
+/// Resources of the policies authorizing the requests for each part of the node manager API
+pub const NODE_RESOURCE: &str = "node-manager.node";
+pub const TRANSPORTS_RESOURCE: &str = "node-manager.transports";
+pub const CREDENTIALS_RESOURCE: &str = "node-manager.credentials";
+pub const SECURE_CHANNELS_RESOURCE: &str = "node-manager.secure-channels";
+pub const SERVICES_RESOURCE: &str = "node-manager.services";
+pub const RELAYS_RESOURCE: &str = "node-manager.relays";
+pub const PORTALS_RESOURCE: &str = "node-manager.portals";
+pub const FLOW_CONTROLS_RESOURCE: &str = "node-manager.flow-controls";
+pub const WORKERS_RESOURCE: &str = "node-manager.workers";
+pub const POLICIES_RESOURCE: &str = "node-manager.policies";
+pub const MESSAGES_RESOURCE: &str = "node-manager.messages";
+
+/// Actions of the requests for each part of the node manager API
+pub const CREATE_ACTION: &str = "create";
+pub const UPDATE_ACTION: &str = "update";
+pub const DELETE_ACTION: &str = "delete";
+pub const EXECUTE_ACTION: &str = "execute";
+
+/// Caller of a request received by the node manager
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Caller {
+    /// The request was sent by a worker of this node, or through the TCP listener
+    /// of the node API, which is only reachable from the local host
+    Local,
+    /// The request was sent by a remote identity, through a secure channel,
+    /// or by this node on behalf of that identity
+    Remote(Identifier),
+}
+
+/// Addresses used by the node manager to send messages on behalf of remote callers.
+///
+/// A message sent back to the node manager from one of these addresses, directly or through
+/// any transport or secure channel, is authorized like a request of the remote caller
+#[derive(Default)]
+pub(crate) struct RemoteCallers {
+    callers: Mutex<BTreeMap<Address, Identifier>>,
+}
+
+impl RemoteCallers {
+    pub(crate) fn insert(&self, address: Address, identifier: Identifier) {
+        self.callers.lock().unwrap().insert(address, identifier);
+    }
+
+    pub(crate) fn remove(&self, address: &Address) {
+        self.callers.lock().unwrap().remove(address);
+    }
+
+    /// Return the remote caller on behalf of which a message with this return route was sent
+    fn find(&self, return_route: &Route) -> Option<Identifier> {
+        let callers = self.callers.lock().unwrap();
+        return_route
+            .iter()
+            .find_map(|address| callers.get(address).cloned())
+    }
+}
+
+impl NodeManager {
+    /// Return the caller of a request received by the node manager, or `None` if the request
+    /// was neither sent by an identity, nor provably sent from the local host.
+    ///
+    /// Messages sent by this node on behalf of a remote caller keep that caller, even if they
+    /// are routed back to the node manager through a local connection or a secure channel.
+    pub(super) fn request_caller(&self, ctx: &Context, msg: &Routed<Vec<u8>>) -> Option<Caller> {
+        if let Some(identifier) = self.remote_callers.find(&msg.return_route()) {
+            return Some(Caller::Remote(identifier));
+        }
+        if let Ok(info) = IdentitySecureChannelLocalInfo::find_info(msg.local_message()) {
+            return Some(Caller::Remote(info.their_identity_id()));
+        }
+
+        let source = msg.src_addr();
+        let is_local = match ctx
+            .flow_controls()
+            .find_flow_control_with_producer_address(&source)
+        {
+            // The message was received by a connection accepted by the node API listener
+            Some(producer) => {
+                producer.spawner_flow_control_id().as_ref()
+                    == Some(&self.api_transport_flow_control_id)
+            }
+            // The message was sent by a worker of this node
+            None => msg.return_route().len() == 1,
+        };
+        if is_local {
+            Some(Caller::Local)
+        } else {
+            warn!(%source, "the caller of a request to the node manager is unknown");
+            None
+        }
+    }
+
+    /// Return true if a request received by the node manager can be handled.
+    ///
+    /// Requests received from the local host are always authorized. Requests of remote
+    /// identities are authorized by the policy of the resource and action of their route,
+    /// for example `node-manager.portals` / `delete` for `DELETE /node/inlet/<alias>`.
+    /// If there is no such policy, the policy of the `node-manager` resource is used instead,
+    /// with the `read` action for GET requests and the `write` action otherwise.
+    /// Without any policy, the requests are denied.
+    pub(super) async fn is_request_authorized(
+        &self,
+        msg: &Routed<Vec<u8>>,
+        req: &RequestHeader,
+        caller: &Caller,
+    ) -> Result<bool> {
+        let identifier = match caller {
+            Caller::Local => return Ok(true),
+            Caller::Remote(identifier) => identifier,
+        };
+
+        let method = match req.method() {
+            Some(method) => method,
+            None => return Ok(false),
+        };
+        let (resource, action) = route_resource_action(method, req.path());
+        let (resource, action) = if self
+            .policies
+            .get_policy(&resource, &action)
+            .await?
+            .is_some()
+        {
+            (resource, action)
+        } else {
+            (
+                Resource::new(NODE_MANAGER_RESOURCE),
+                Action::new(request_action(method)),
+            )
+        };
+
+        let mut env = Env::new();
+        env.put("resource.id", str(resource.as_str()));
+        env.put("action.id", str(action.as_str()));
+        if let Some(trust_context) = &self.trust_context {
+            env.put("resource.trust_context_id", str(trust_context.id()));
+        }
+
+        let access_control = PolicyAccessControl::new(
+            self.policies.clone(),
+            self.identities_repository(),
+            resource.clone(),
+            action.clone(),
+            env,
+        )
+        .with_credential_schemas(self.identities().credential_schemas());
+
+        // The policy is evaluated for the caller, even if the message was sent by this node
+        let local_info = IdentitySecureChannelLocalInfo::mark(
+            msg.local_message().local_info().to_vec(),
+            identifier.clone(),
+        )?;
+        let local_msg = LocalMessage::new(msg.local_message().transport().clone(), local_info);
+        let relay_msg = RelayMessage::new(msg.src_addr(), msg.msg_addr(), local_msg);
+        let authorized = access_control.is_authorized(&relay_msg).await?;
+        if !authorized {
+            warn! {
+                %identifier,
+                %resource,
+                %action,
+                method = ?req.method(),
+                path   = %req.path(),
+                "unauthorized request to the node manager"
+            }
+        }
+        Ok(authorized)
+    }
+}
+
+/// Requests which only read the state of the node are GET requests
+fn request_action(method: Method) -> &'static str {
+    match method {
+        Method::Get => READ_ACTION,
+        _ => WRITE_ACTION,
+    }
+}
+
+/// Return the resource and action of the policy authorizing a request to the node manager
+fn route_resource_action(method: Method, path: &str) -> (Resource, Action) {
+    let segments = path.trim_start_matches('/').split('/').collect::<Vec<_>>();
+    let resource = match segments.as_slice() {
+        ["node"] | ["node", "sessions", ..] => NODE_RESOURCE,
+        ["node", "tcp" | "ws" | "uds", ..] => TRANSPORTS_RESOURCE,
+        ["node", "credentials", ..] => CREDENTIALS_RESOURCE,
+        ["node", "secure_channel" | "show_secure_channel"] => SECURE_CHANNELS_RESOURCE,
+        ["node", "secure_channel_listener" | "show_secure_channel_listener"] => {
+            SECURE_CHANNELS_RESOURCE
+        }
+        ["node", "services", ..] => SERVICES_RESOURCE,
+        ["node", "forwarder", ..] | ["relays", ..] => RELAYS_RESOURCE,
+        ["node", "inlet" | "outlet" | "portal", ..] => PORTALS_RESOURCE,
+        ["node", "flow_controls", ..] => FLOW_CONTROLS_RESOURCE,
+        ["node", "workers"] => WORKERS_RESOURCE,
+        ["policy", ..] => POLICIES_RESOURCE,
+        ["v0", "message"] => MESSAGES_RESOURCE,
+        _ => NODE_MANAGER_RESOURCE,
+    };
+    let action = match (method, segments.as_slice()) {
+        // These requests make the node act on behalf of the caller
+        (Method::Post, ["node", "credentials", "actions", ..] | ["v0", "message"]) => {
+            EXECUTE_ACTION
+        }
+        (Method::Get, _) => READ_ACTION,
+        (Method::Post, _) => CREATE_ACTION,
+        (Method::Put | Method::Patch, _) => UPDATE_ACTION,
+        (Method::Delete, _) => DELETE_ACTION,
+    };
+    (Resource::new(resource), Action::new(action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::service::message::SendMessage;
+    use crate::nodes::NODEMANAGER_ADDR;
+    use crate::test_utils::{start_manager_for_tests, NodeManagerHandle, NodeManagerTestOptions};
+    use crate::DefaultAddress;
+    use ockam::identity::models::CredentialSchemaIdentifier;
+    use ockam::identity::utils::now;
+    use ockam::identity::{
+        AttributeSchema, AttributeType, AttributesEntry, CredentialSchema, CredentialSchemas,
+        IdentityAttributesWriter, SecureChannelOptions,
+    };
+    use ockam_abac::expr::{eq, ident, int};
+    use ockam_abac::Expr;
+    use ockam_core::api::{Request, Response, Status};
+    use ockam_core::route;
+    use ockam_multiaddr::MultiAddr;
+
+    /// Send a request to the node manager through a secure channel
+    /// created by a new remote identity
+    async fn send_remote_request(
+        ctx: &Context,
+        handle: &NodeManagerHandle,
+        client: &Identifier,
+        request: Vec<u8>,
+    ) -> Result<Vec<u8>> {
+        let channel = handle
+            .secure_channels
+            .create_secure_channel(
+                ctx,
+                client,
+                route![DefaultAddress::SECURE_CHANNEL_LISTENER],
+                SecureChannelOptions::new(),
+            )
+            .await?;
+        let response: Vec<u8> = ctx
+            .send_and_receive(
+                route![channel.encryptor_address().clone(), NODEMANAGER_ADDR],
+                request,
+            )
+            .await?;
+        Ok(response)
+    }
+
+    async fn create_client(handle: &NodeManagerHandle) -> Result<Identifier> {
+        Ok(handle
+            .secure_channels
+            .identities()
+            .identities_creation()
+            .create_identity()
+            .await?
+            .identifier()
+            .clone())
+    }
+
+    #[ockam_macros::test]
+    async fn remote_request_without_policy_is_denied(ctx: &mut Context) -> Result<()> {
+        let handle = start_manager_for_tests(ctx).await?;
+        let client = create_client(&handle).await?;
+
+        let response =
+            send_remote_request(ctx, &handle, &client, Request::get("/node").to_vec()?).await?;
+        let (header, _) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Forbidden));
+
+        // The same request is authorized for a local caller
+        let local_response: Vec<u8> = ctx
+            .send_and_receive(route![NODEMANAGER_ADDR], Request::get("/node").to_vec()?)
+            .await?;
+        let (header, _) = Response::parse_response_header(&local_response)?;
+        assert_eq!(header.status(), Some(Status::Ok));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn message_sent_back_to_the_node_manager_is_authorized_for_the_caller(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let handle = start_manager_for_tests(ctx).await?;
+        let client = create_client(&handle).await?;
+
+        // The client can only send messages through the node
+        handle
+            .node_manager
+            .policies
+            .set_policy(
+                &Resource::new(MESSAGES_RESOURCE),
+                &Action::new(EXECUTE_ACTION),
+                &eq([ident("subject.identifier"), str(client.to_string())]),
+            )
+            .await?;
+
+        let to_node_manager: MultiAddr = format!("/service/{NODEMANAGER_ADDR}").parse().unwrap();
+        let send_message = Request::post("/v0/message").body(SendMessage::new(
+            &to_node_manager,
+            Request::get("/node").to_vec()?,
+        ));
+        let response = send_remote_request(ctx, &handle, &client, send_message.to_vec()?).await?;
+
+        // The message is sent, but the request it contains is denied
+        let (header, mut decoder) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Ok));
+        let inner_response: Vec<u8> = decoder.decode()?;
+        let (header, _) = Response::parse_response_header(&inner_response)?;
+        assert_eq!(header.status(), Some(Status::Forbidden));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn policies_use_the_credential_schemas_of_the_node(ctx: &mut Context) -> Result<()> {
+        let schema = CredentialSchemaIdentifier(7);
+        let credential_schemas = CredentialSchemas::new().with_schema(
+            CredentialSchema::new(schema.clone()).with_attribute(
+                "max_connections",
+                AttributeSchema::new(AttributeType::Integer),
+            ),
+        );
+        let handle = NodeManagerTestOptions::new()
+            .with_credential_schemas(credential_schemas)
+            .start(ctx)
+            .await?;
+        let client = create_client(&handle).await?;
+        let max_connections = |value: &str| {
+            AttributesEntry::new(
+                [(b"max_connections".to_vec(), value.as_bytes().to_vec())].into(),
+                now().unwrap(),
+                None,
+                None,
+            )
+            .with_schema(schema.clone())
+        };
+
+        // The attribute is compared as an integer
+        handle
+            .node_manager
+            .policies
+            .set_policy(
+                &Resource::new(NODE_RESOURCE),
+                &Action::new(READ_ACTION),
+                &Expr::List(vec![ident("<"), ident("subject.max_connections"), int(20)]),
+            )
+            .await?;
+        let repository = handle.node_manager.identities_repository();
+        repository
+            .put_attributes(&client, max_connections("10"))
+            .await?;
+        let response =
+            send_remote_request(ctx, &handle, &client, Request::get("/node").to_vec()?).await?;
+        let (header, _) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Ok));
+
+        // An attribute which doesn't match its schema denies the request
+        handle
+            .node_manager
+            .policies
+            .set_policy(
+                &Resource::new(NODE_RESOURCE),
+                &Action::new(READ_ACTION),
+                &eq([ident("subject.max_connections"), str("ten")]),
+            )
+            .await?;
+        repository
+            .put_attributes(&client, max_connections("ten"))
+            .await?;
+        let response =
+            send_remote_request(ctx, &handle, &client, Request::get("/node").to_vec()?).await?;
+        let (header, _) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Forbidden));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn read_only_identity_is_denied_other_actions(ctx: &mut Context) -> Result<()> {
+        let handle = start_manager_for_tests(ctx).await?;
+        let client = create_client(&handle).await?;
+        for resource in [TRANSPORTS_RESOURCE, PORTALS_RESOURCE] {
+            handle
+                .node_manager
+                .policies
+                .set_policy(
+                    &Resource::new(resource),
+                    &Action::new(READ_ACTION),
+                    &eq([ident("subject.identifier"), str(client.to_string())]),
+                )
+                .await?;
+        }
+
+        let status = |response: Vec<u8>| -> Result<Option<Status>> {
+            Ok(Response::parse_response_header(&response)?.0.status())
+        };
+        let response = send_remote_request(
+            ctx,
+            &handle,
+            &client,
+            Request::get("/node/tcp/listener").to_vec()?,
+        )
+        .await?;
+        assert_eq!(status(response)?, Some(Status::Ok));
+
+        let response = send_remote_request(
+            ctx,
+            &handle,
+            &client,
+            Request::post("/node/tcp/listener").to_vec()?,
+        )
+        .await?;
+        assert_eq!(status(response)?, Some(Status::Forbidden));
+
+        // The name of a resource doesn't change the action of a request
+        let response = send_remote_request(
+            ctx,
+            &handle,
+            &client,
+            Request::delete("/node/inlet/list-x").to_vec()?,
+        )
+        .await?;
+        assert_eq!(status(response)?, Some(Status::Forbidden));
+
+        ctx.stop().await
+    }
+
+    #[test]
+    fn test_route_resource_action() {
+        let check = |method, path, resource: &str, action: &str| {
+            assert_eq!(
+                route_resource_action(method, path),
+                (Resource::new(resource), Action::new(action)),
+                "{method} {path}"
+            );
+        };
+        check(Method::Get, "/node", NODE_RESOURCE, READ_ACTION);
+        check(
+            Method::Get,
+            "/node/sessions/events",
+            NODE_RESOURCE,
+            READ_ACTION,
+        );
+        check(Method::Get, "/node/inlet/i1", PORTALS_RESOURCE, READ_ACTION);
+        check(
+            Method::Post,
+            "/node/outlet",
+            PORTALS_RESOURCE,
+            CREATE_ACTION,
+        );
+        check(
+            Method::Delete,
+            "/node/portal",
+            PORTALS_RESOURCE,
+            DELETE_ACTION,
+        );
+        check(
+            Method::Post,
+            "/policy/tcp-outlet/handle_message",
+            POLICIES_RESOURCE,
+            CREATE_ACTION,
+        );
+        check(
+            Method::Post,
+            "/v0/message",
+            MESSAGES_RESOURCE,
+            EXECUTE_ACTION,
+        );
+        check(
+            Method::Get,
+            "/node/tcp/listener",
+            TRANSPORTS_RESOURCE,
+            READ_ACTION,
+        );
+        check(
+            Method::Get,
+            "/node/show_secure_channel",
+            SECURE_CHANNELS_RESOURCE,
+            READ_ACTION,
+        );
+        check(
+            Method::Delete,
+            "/node/inlet/list-x",
+            PORTALS_RESOURCE,
+            DELETE_ACTION,
+        );
+        check(Method::Get, "/relays", RELAYS_RESOURCE, READ_ACTION);
+        check(Method::Get, "/relays/r1", RELAYS_RESOURCE, READ_ACTION);
+        check(Method::Delete, "/relays/r1", RELAYS_RESOURCE, DELETE_ACTION);
+        check(Method::Get, "/unknown", NODE_MANAGER_RESOURCE, READ_ACTION);
+    }
+}
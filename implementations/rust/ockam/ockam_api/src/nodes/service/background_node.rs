@@ -1,9 +1,12 @@
 use crate::cli_state::{CliState, StateDirTrait, StateItemTrait};
+use crate::nodes::service::message::SendMessage;
 use crate::nodes::NODEMANAGER_ADDR;
 use miette::IntoDiagnostic;
 use minicbor::{Decode, Encode};
-use ockam_core::api::{Reply, Request};
+use ockam_core::api::Reply::Successful;
+use ockam_core::api::{Error, Reply, Request, Response};
 use ockam_core::{AsyncTryClone, Route};
+use ockam_multiaddr::MultiAddr;
 use ockam_node::api::Client;
 use ockam_node::Context;
 use ockam_transport_tcp::{TcpConnectionOptions, TcpTransport};
@@ -20,6 +23,9 @@ pub struct BackgroundNode {
     cli_state: CliState,
     node_name: String,
     to: Route,
+    /// Route to the secure channel listener of a remote node which must receive
+    /// the requests instead of this node
+    remote: Option<MultiAddr>,
     timeout: Option<Duration>,
     tcp_transport: Arc<TcpTransport>,
 }
@@ -47,6 +53,7 @@ impl BackgroundNode {
             cli_state: cli_state.clone(),
             node_name: node_name.to_string(),
             to: NODEMANAGER_ADDR.into(),
+            remote: None,
             timeout: None,
             tcp_transport: Arc::new(tcp_transport.async_try_clone().await.into_diagnostic()?),
         })
@@ -58,6 +65,16 @@ impl BackgroundNode {
         self
     }
 
+    /// Send the requests to the node manager of a remote node, through this node.
+    ///
+    /// The route must lead to a secure channel listener of the remote node, for example
+    /// `/dnsaddr/remote.example.com/tcp/4000/secure/api`. The remote node only handles the
+    /// requests authorized by its policies for the `node-manager` resource.
+    pub fn set_remote(&mut self, remote: MultiAddr) -> &Self {
+        self.remote = Some(remote);
+        self
+    }
+
     /// Use a default timeout for making requests
     pub fn set_timeout(&mut self, timeout: Duration) -> &Self {
         self.timeout = Some(timeout);
@@ -87,10 +104,8 @@ impl BackgroundNode {
         T: Encode<()>,
         R: for<'b> Decode<'b, ()>,
     {
-        let client = self.make_client_with_timeout(Some(timeout)).await?;
-        client
-            .ask(ctx, req)
-            .await
+        let bytes = self.request(ctx, req, Some(timeout)).await?;
+        Response::parse_response_reply::<R>(bytes.as_slice())
             .into_diagnostic()?
             .success()
             .into_diagnostic()
@@ -101,13 +116,19 @@ impl BackgroundNode {
     where
         T: Encode<()>,
     {
-        let client = self.make_client().await?;
-        client
-            .tell(ctx, req)
-            .await
-            .into_diagnostic()?
-            .success()
-            .into_diagnostic()
+        let request_header = req.header().clone();
+        let bytes = self.request(ctx, req, self.timeout).await?;
+        let (response, decoder) =
+            Response::parse_response_header(bytes.as_slice()).into_diagnostic()?;
+        let reply = if !response.is_ok() {
+            Reply::Failed(
+                Error::from_failed_request(&request_header, &response.parse_err_msg(decoder)),
+                response.status(),
+            )
+        } else {
+            Successful(())
+        };
+        reply.success().into_diagnostic()
     }
 
     /// Send a request and expect either a decodable response or an API error.
@@ -121,8 +142,42 @@ impl BackgroundNode {
         T: Encode<()>,
         R: for<'b> Decode<'b, ()>,
     {
-        let client = self.make_client().await?;
-        client.ask(ctx, req).await.into_diagnostic()
+        let bytes = self.request(ctx, req, self.timeout).await?;
+        Response::parse_response_reply::<R>(bytes.as_slice()).into_diagnostic()
+    }
+
+    /// Send a request and return the encoded response.
+    /// The requests to a remote node are sent by this node, which returns the response
+    async fn request<T>(
+        &self,
+        ctx: &Context,
+        req: Request<T>,
+        timeout: Option<Duration>,
+    ) -> miette::Result<Vec<u8>>
+    where
+        T: Encode<()>,
+    {
+        let client = self.make_client_with_timeout(timeout).await?;
+        match &self.remote {
+            None => client
+                .request_with_timeout(ctx, req, timeout)
+                .await
+                .into_diagnostic(),
+            Some(remote) => {
+                let to: MultiAddr = format!("{remote}/service/{NODEMANAGER_ADDR}")
+                    .parse()
+                    .into_diagnostic()?;
+                let message = req.to_vec().into_diagnostic()?;
+                let reply: Reply<Vec<u8>> = client
+                    .ask(
+                        ctx,
+                        Request::post("/v0/message").body(SendMessage::new(&to, message)),
+                    )
+                    .await
+                    .into_diagnostic()?;
+                reply.success().into_diagnostic()
+            }
+        }
     }
 
     /// Make a route to the node and connect using TCP
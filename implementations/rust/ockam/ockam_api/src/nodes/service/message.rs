@@ -6,12 +6,17 @@ use tracing::trace;
 use minicbor::Decoder;
 use minicbor::{Decode, Encode};
 
+use ockam::identity::Identifier;
 use ockam_core::api::{RequestHeader, Response};
-use ockam_core::{self, async_trait, AsyncTryClone, Result};
+use ockam_core::{
+    self, async_trait, Address, AllowAll, AllowOnwardAddress, AsyncTryClone, Mailbox, Mailboxes,
+    Result,
+};
 use ockam_multiaddr::MultiAddr;
-use ockam_node::{Context, MessageSendReceiveOptions};
+use ockam_node::{Context, MessageReceiveOptions, MessageSendReceiveOptions};
 
 use crate::error::ApiError;
+use crate::nodes::service::Caller;
 use crate::nodes::{NodeManager, NodeManagerWorker};
 
 const TARGET: &str = "ockam_api::message";
@@ -45,15 +50,24 @@ impl NodeManagerWorker {
         ctx: &Context,
         req: &RequestHeader,
         dec: &mut Decoder<'_>,
+        caller: &Caller,
     ) -> Result<Vec<u8>> {
         let req_body: SendMessage = dec.decode()?;
         let multiaddr = req_body.multiaddr()?;
         let msg = req_body.message.to_vec();
 
-        let res = self
-            .node_manager
-            .send_message(ctx, &multiaddr, msg, None)
-            .await;
+        let res = match caller {
+            Caller::Local => {
+                self.node_manager
+                    .send_message(ctx, &multiaddr, msg, None)
+                    .await
+            }
+            Caller::Remote(identifier) => {
+                self.node_manager
+                    .send_message_on_behalf_of(ctx, &multiaddr, msg, identifier)
+                    .await
+            }
+        };
         match res {
             Ok(r) => Ok(Response::ok(req).body(r).to_vec()?),
             Err(err) => {
@@ -64,6 +78,61 @@ impl NodeManagerWorker {
     }
 }
 
+impl NodeManager {
+    /// Send a message on behalf of a remote caller and return the response.
+    ///
+    /// The message is sent from an address registered for the caller, so that if the route
+    /// leads back to the node manager, the message is authorized like a request of the caller,
+    /// rather than like a request of the node itself
+    pub(crate) async fn send_message_on_behalf_of(
+        &self,
+        ctx: &Context,
+        addr: &MultiAddr,
+        message: Vec<u8>,
+        caller: &Identifier,
+    ) -> Result<Vec<u8>> {
+        let connection_ctx = Arc::new(ctx.async_try_clone().await?);
+        let connection = self
+            .make_connection(connection_ctx, addr, None, None, None, None)
+            .await?;
+        let route = connection.route(self.tcp_transport()).await?;
+
+        let next = route.next()?.clone();
+        let address = Address::random_tagged("NodeManager.send_message.remote_caller");
+        let mailboxes = Mailboxes::new(
+            Mailbox::new(
+                address.clone(),
+                Arc::new(AllowAll),
+                Arc::new(AllowOnwardAddress(next.clone())),
+            ),
+            vec![],
+        );
+        if let Some(flow_control_id) = ctx
+            .flow_controls()
+            .find_flow_control_with_producer_address(&next)
+            .map(|x| x.flow_control_id().clone())
+        {
+            // To be able to receive the response
+            ctx.flow_controls()
+                .add_consumer(address.clone(), &flow_control_id);
+        }
+        let mut child_ctx = ctx.new_detached_with_mailboxes(mailboxes).await?;
+
+        trace!(target: TARGET, route = %route, %caller, "sending message on behalf of a remote caller");
+        self.remote_callers.insert(address.clone(), caller.clone());
+        let response = match child_ctx.send(route, message).await {
+            Ok(()) => {
+                child_ctx
+                    .receive_extended::<Vec<u8>>(MessageReceiveOptions::new())
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        self.remote_callers.remove(&address);
+        Ok(response?.body())
+    }
+}
+
 #[async_trait]
 impl MessageSender for NodeManager {
     async fn send_message(
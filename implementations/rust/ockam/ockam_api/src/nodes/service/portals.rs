@@ -20,12 +20,16 @@ use crate::config::lookup::ProjectLookup;
 use crate::error::ApiError;
 use crate::nodes::connection::Connection;
 use crate::nodes::models::portal::{
-    CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus,
+    CreateInlet, CreateOutlet, DeletePortal, DeletedPortal, InletList, InletStatus, OutletList,
+    OutletStatus, UpdateInlet, UpdateOutlet,
 };
 use crate::nodes::registry::{InletInfo, OutletInfo};
 use crate::nodes::service::random_alias;
 use crate::nodes::InMemoryNode;
-use crate::session::sessions::{Replacer, Session, MAX_CONNECT_TIME, MAX_RECOVERY_TIME};
+use crate::session::sessions::{
+    Key, ReplacedRoute, Replacer, Session, MAX_CONNECT_TIME, MAX_RECOVERY_TIME,
+};
+use crate::session::Medic;
 use crate::{actions, resources, DefaultAddress};
 
 use super::{NodeManager, NodeManagerWorker};
@@ -74,11 +78,46 @@ impl NodeManagerWorker {
 
     pub(super) async fn delete_inlet(
         &self,
+        ctx: &Context,
         req: &RequestHeader,
         alias: &str,
     ) -> Result<Response<InletStatus>, Response<Error>> {
-        match self.node_manager.delete_inlet(alias).await {
+        match self.node_manager.delete_inlet(ctx, alias).await {
+            Ok(status) => Ok(Response::ok(req).body(status)),
+            Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn update_inlet(
+        &self,
+        ctx: &Context,
+        req: &RequestHeader,
+        alias: &str,
+        update_inlet: UpdateInlet,
+    ) -> Result<Response<InletStatus>, Response<Error>> {
+        let UpdateInlet {
+            outlet_addr,
+            authorized,
+            wait_for_outlet_duration,
+            clear_authorized,
+        } = update_inlet;
+        match self
+            .node_manager
+            .update_inlet(
+                ctx,
+                alias,
+                outlet_addr,
+                wait_for_outlet_duration,
+                authorized,
+                clear_authorized,
+            )
+            .await
+        {
             Ok(status) => Ok(Response::ok(req).body(status)),
+            Err(e) if e.code().kind == Kind::NotFound => Err(Response::not_found(
+                req,
+                &format!("Inlet with alias {alias} not found"),
+            )),
             Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
         }
     }
@@ -152,6 +191,27 @@ impl NodeManagerWorker {
         }
     }
 
+    pub(super) async fn update_outlet(
+        &self,
+        ctx: &Context,
+        req: &RequestHeader,
+        alias: &str,
+        update_outlet: UpdateOutlet,
+    ) -> Result<Response<OutletStatus>, Response<Error>> {
+        match self
+            .node_manager
+            .update_outlet(ctx, alias, update_outlet.socket_addr)
+            .await
+        {
+            Ok(status) => Ok(Response::ok(req).body(status)),
+            Err(e) if e.code().kind == Kind::NotFound => Err(Response::not_found(
+                req,
+                &format!("Outlet with alias {alias} not found"),
+            )),
+            Err(e) => Err(Response::bad_request(req, &format!("{e:?}"))),
+        }
+    }
+
     pub(super) async fn show_outlet(
         &self,
         req: &RequestHeader,
@@ -171,6 +231,46 @@ impl NodeManagerWorker {
     }
 }
 
+/// PORTALS
+impl NodeManagerWorker {
+    /// Delete the inlet and the outlet having the given alias
+    pub(super) async fn delete_portal(
+        &self,
+        ctx: &Context,
+        req: &RequestHeader,
+        delete_portal: DeletePortal,
+    ) -> Result<Response<DeletedPortal>, Response<Error>> {
+        let alias = delete_portal.alias;
+        let inlet = if self.node_manager.registry.inlets.contains_key(&alias).await {
+            match self.node_manager.delete_inlet(ctx, &alias).await {
+                Ok(status) => Some(status),
+                Err(e) => return Err(Response::bad_request(req, &format!("{e:?}"))),
+            }
+        } else {
+            None
+        };
+        let outlet = match self.node_manager.delete_outlet(&alias).await {
+            Ok(outlet) => outlet.map(|outlet_info| {
+                OutletStatus::new(
+                    outlet_info.socket_addr,
+                    outlet_info.worker_addr,
+                    &alias,
+                    None,
+                )
+            }),
+            Err(e) => return Err(Response::bad_request(req, &format!("{e:?}"))),
+        };
+
+        if inlet.is_none() && outlet.is_none() {
+            return Err(Response::not_found(
+                req,
+                &format!("Portal with alias {alias} not found"),
+            ));
+        }
+        Ok(Response::ok(req).body(DeletedPortal { inlet, outlet }))
+    }
+}
+
 /// OUTLETS
 impl NodeManager {
     pub async fn create_outlet(
@@ -213,27 +313,11 @@ impl NodeManager {
             .access_control(&resource, &actions::HANDLE_MESSAGE, trust_context_id, None)
             .await?;
 
-        let options = TcpOutletOptions::new().with_incoming_access_control(access_control);
-        let options = if !check_credential {
-            options.as_consumer(&self.api_transport_flow_control_id)
-        } else {
-            options
-        };
-
-        let options = if reachable_from_default_secure_channel {
-            // Accept messages from the default secure channel listener
-            if let Some(flow_control_id) = ctx
-                .flow_controls()
-                .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
-            {
-                options.as_consumer(&flow_control_id)
-            } else {
-                options
-            }
-        } else {
-            options
-        };
-
+        let options = self.outlet_options(
+            ctx,
+            access_control.clone(),
+            reachable_from_default_secure_channel,
+        );
         let res = self
             .tcp_transport
             .create_tcp_outlet(worker_addr.clone(), socket_addr, options)
@@ -246,7 +330,12 @@ impl NodeManager {
                     .outlets
                     .insert(
                         alias.clone(),
-                        OutletInfo::new(&socket_addr, Some(&worker_addr)),
+                        OutletInfo::new(
+                            &socket_addr,
+                            Some(&worker_addr),
+                            access_control,
+                            reachable_from_default_secure_channel,
+                        ),
                     )
                     .await;
 
@@ -264,6 +353,106 @@ impl NodeManager {
         })
     }
 
+    /// Change the address an outlet connects to.
+    ///
+    /// The outlet keeps its alias and worker address, so that the inlets sending messages
+    /// to it are not changed. The connections which are already established are kept.
+    pub async fn update_outlet(
+        &self,
+        ctx: &Context,
+        alias: &str,
+        socket_addr: SocketAddr,
+    ) -> Result<OutletStatus> {
+        info!(%alias, %socket_addr, "Handling request to update outlet portal");
+        let outlet = match self.registry.outlets.get(alias).await {
+            Some(outlet) => outlet,
+            None => {
+                let message = format!("Outlet with alias {alias} not found");
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::NotFound,
+                    message,
+                ));
+            }
+        };
+
+        // The outlet worker is started again, on the same address, with the new target
+        self.tcp_transport
+            .stop_outlet(outlet.worker_addr.clone())
+            .await?;
+        wait_for_worker_to_stop(ctx, &outlet.worker_addr).await?;
+        let options = self.outlet_options(
+            ctx,
+            outlet.access_control.clone(),
+            outlet.reachable_from_default_secure_channel,
+        );
+        if let Err(e) = self
+            .tcp_transport
+            .create_tcp_outlet(outlet.worker_addr.clone(), socket_addr, options)
+            .await
+        {
+            warn!(%alias, at = %socket_addr, err = %e, "Failed to update TCP outlet");
+            let options = self.outlet_options(
+                ctx,
+                outlet.access_control.clone(),
+                outlet.reachable_from_default_secure_channel,
+            );
+            self.tcp_transport
+                .create_tcp_outlet(outlet.worker_addr.clone(), outlet.socket_addr, options)
+                .await?;
+            let message = format!("Failed to update outlet: {}", e);
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::Internal,
+                message,
+            ));
+        }
+
+        self.registry
+            .outlets
+            .insert(
+                alias.to_string(),
+                OutletInfo {
+                    socket_addr,
+                    ..outlet.clone()
+                },
+            )
+            .await;
+        Ok(OutletStatus::new(
+            socket_addr,
+            outlet.worker_addr,
+            alias,
+            None,
+        ))
+    }
+
+    /// Return the options of an outlet, with the flow controls of the transports and
+    /// secure channels which can send messages to it
+    fn outlet_options(
+        &self,
+        ctx: &Context,
+        access_control: Arc<dyn IncomingAccessControl>,
+        reachable_from_default_secure_channel: bool,
+    ) -> TcpOutletOptions {
+        let options = TcpOutletOptions::new().with_incoming_access_control(access_control);
+        let options = if !self.enable_credential_checks {
+            options.as_consumer(&self.api_transport_flow_control_id)
+        } else {
+            options
+        };
+
+        if reachable_from_default_secure_channel {
+            // Accept messages from the default secure channel listener
+            if let Some(flow_control_id) = ctx
+                .flow_controls()
+                .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+            {
+                return options.as_consumer(&flow_control_id);
+            }
+        }
+        options
+    }
+
     pub async fn delete_outlet(&self, alias: &str) -> Result<Option<OutletInfo>> {
         info!(%alias, "Handling request to delete outlet portal");
         if let Some(deleted_outlet) = self.registry.outlets.remove(alias).await {
@@ -405,7 +594,15 @@ impl NodeManager {
                     .inlets
                     .insert(
                         alias.clone(),
-                        InletInfo::new(&listen_addr, Some(&worker_addr), &outlet_route),
+                        InletInfo::new(
+                            &listen_addr,
+                            Some(&worker_addr),
+                            &outlet_route,
+                            &outlet_addr,
+                            &prefix_route,
+                            &suffix_route,
+                            access_control.clone(),
+                        ),
                     )
                     .await;
                 (
@@ -530,12 +727,10 @@ impl InMemoryNode {
         let duration = wait_for_outlet_duration.unwrap_or(Duration::from_secs(5));
         let connection_ctx = Arc::new(ctx.async_try_clone().await?);
         let connection = self
-            .make_connection(
+            .make_reconnecting_connection(
                 connection_ctx.clone(),
                 &outlet_addr,
-                None,
                 authorized.clone(),
-                None,
                 Some(duration),
             )
             .await?;
@@ -551,44 +746,297 @@ impl InMemoryNode {
                 outlet_addr.clone(),
             )
             .await?;
-        if !connection.route(self.tcp_transport()).await?.is_empty() {
-            debug! {
-                %inlet.alias,
-                %inlet.bind_addr,
-                %inlet.worker_addr,
-                ping_addr = %connection.transport_route(),
-                "Creating session for TCP inlet"
-            };
-            let mut session = Session::new(connection.transport_route());
-
-            let repl = Self::portal_replacer(
-                self.node_manager.clone(),
+        let connection = Arc::new(Mutex::new(connection));
+        let session = self
+            .monitor_inlet(
                 connection_ctx,
-                connection,
+                connection.clone(),
+                &inlet.alias,
                 Address::from_string(inlet.worker_addr.clone()),
                 listen_addr,
                 outlet_addr,
                 prefix_route,
                 suffix_route,
-                authorized,
+                authorized.clone(),
                 access_control,
-            );
-            session.set_replacer(repl);
-            self.add_session(session);
-        };
+            )
+            .await?;
+
+        if let Some(mut inlet_info) = self.registry.inlets.get(&inlet.alias).await {
+            inlet_info.authorized = authorized;
+            inlet_info.session = session;
+            inlet_info.connection = Some(connection);
+            self.registry
+                .inlets
+                .insert(inlet.alias.clone(), inlet_info)
+                .await;
+        }
         Ok(inlet)
     }
 
+    /// Change the outlet of an inlet. The inlet keeps its alias and listen address.
+    ///
+    /// The connection to the new outlet is made first, so that the inlet is unchanged
+    /// if the new outlet can't be reached. The connection to the previous outlet is closed
+    /// once the inlet uses the new one.
+    ///
+    /// The authorized identity is kept when `authorized` is `None`, unless `clear_authorized` is set.
+    pub async fn update_inlet(
+        &self,
+        ctx: &Context,
+        alias: &str,
+        outlet_addr: MultiAddr,
+        wait_for_outlet_duration: Option<Duration>,
+        authorized: Option<Identifier>,
+        clear_authorized: bool,
+    ) -> Result<InletStatus> {
+        info!(%alias, %outlet_addr, "Handling request to update inlet portal");
+        let inlet = match self.registry.inlets.get(alias).await {
+            Some(inlet) => inlet,
+            None => {
+                let message = format!("Inlet with alias {alias} not found");
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::NotFound,
+                    message,
+                ));
+            }
+        };
+        let authorized = if clear_authorized {
+            None
+        } else {
+            authorized.or_else(|| inlet.authorized.clone())
+        };
+
+        let duration = wait_for_outlet_duration.unwrap_or(Duration::from_secs(5));
+        let connection_ctx = Arc::new(ctx.async_try_clone().await?);
+        let connection = self
+            .make_reconnecting_connection(
+                connection_ctx.clone(),
+                &outlet_addr,
+                authorized.clone(),
+                Some(duration),
+            )
+            .await?;
+        // The new connection must be closed on every error path from here
+        let outlet_route = match connection.route(self.tcp_transport()).await {
+            Ok(route) => route![
+                inlet.prefix_route.clone(),
+                route,
+                inlet.suffix_route.clone()
+            ],
+            Err(e) => {
+                self.close_connection(ctx, &connection).await;
+                return Err(e);
+            }
+        };
+
+        // The inlet is started again, on the same address, with the new route, once
+        // the workers of the previous inlet are stopped
+        let previous_session = inlet
+            .session
+            .as_ref()
+            .and_then(|key| self.medic_handle.remove_session(key));
+        let stopped = match self
+            .tcp_transport
+            .stop_inlet(inlet.worker_addr.clone())
+            .await
+        {
+            Ok(()) => wait_for_worker_to_stop(ctx, &inlet.worker_addr).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = stopped {
+            warn!(%alias, err = %e, "Failed to stop TCP inlet before updating it");
+            self.close_connection(ctx, &connection).await;
+            // The previous inlet is still monitored, its session recreates it if necessary
+            if let Some(session) = previous_session {
+                self.medic_handle.add_session(session);
+            }
+            return Err(e);
+        }
+        let options =
+            TcpInletOptions::new().with_incoming_access_control(inlet.access_control.clone());
+        let (socket_addr, worker_addr) = match self
+            .tcp_transport
+            .create_inlet(inlet.bind_addr.clone(), outlet_route.clone(), options)
+            .await
+        {
+            Ok(created) => created,
+            Err(e) => {
+                warn!(%alias, to = %outlet_addr, err = %e, "Failed to update TCP inlet");
+                self.close_connection(ctx, &connection).await;
+                self.restore_inlet(alias, inlet).await;
+                let message = format!("Failed to update TCP inlet: {}", e);
+                return Err(ockam_core::Error::new(
+                    Origin::Node,
+                    Kind::Internal,
+                    message,
+                ));
+            }
+        };
+
+        // The previous connection is not used by the inlet anymore
+        if let Some(previous_connection) = &inlet.connection {
+            let previous_connection = previous_connection.lock().unwrap().clone();
+            self.close_connection(ctx, &previous_connection).await;
+        }
+
+        let listen_addr = socket_addr.to_string();
+        let connection = Arc::new(Mutex::new(connection));
+        // The new connection is kept with the inlet, and closed when the inlet is deleted,
+        // even if it can't be monitored
+        let session = match self
+            .monitor_inlet(
+                connection_ctx,
+                connection.clone(),
+                alias,
+                worker_addr.clone(),
+                listen_addr.clone(),
+                outlet_addr.clone(),
+                inlet.prefix_route.clone(),
+                inlet.suffix_route.clone(),
+                authorized.clone(),
+                inlet.access_control.clone(),
+            )
+            .await
+        {
+            Ok(session) => session,
+            Err(e) => {
+                warn!(%alias, err = %e, "Failed to monitor the updated TCP inlet");
+                None
+            }
+        };
+        self.registry
+            .inlets
+            .insert(
+                alias.to_string(),
+                InletInfo {
+                    worker_addr: worker_addr.clone(),
+                    outlet_route: outlet_route.clone(),
+                    outlet_addr,
+                    authorized,
+                    session,
+                    connection: Some(connection),
+                    ..inlet
+                },
+            )
+            .await;
+        Ok(InletStatus::new(
+            listen_addr,
+            worker_addr.to_string(),
+            alias,
+            None,
+            outlet_route.to_string(),
+        ))
+    }
+
+    /// Start an inlet again with its previous route, after a failed update.
+    /// If this is not possible, the inlet is removed.
+    async fn restore_inlet(&self, alias: &str, inlet: InletInfo) {
+        let options =
+            TcpInletOptions::new().with_incoming_access_control(inlet.access_control.clone());
+        match self
+            .tcp_transport
+            .create_inlet(inlet.bind_addr.clone(), inlet.outlet_route.clone(), options)
+            .await
+        {
+            Ok((_, worker_addr)) => {
+                // The connection to the previous outlet is not monitored anymore
+                let inlet = InletInfo {
+                    worker_addr,
+                    session: None,
+                    ..inlet
+                };
+                self.registry.inlets.insert(alias.to_string(), inlet).await;
+            }
+            Err(e) => {
+                error!(%alias, err = %e, "Failed to restore TCP inlet");
+                self.registry.inlets.remove(alias).await;
+            }
+        }
+    }
+
+    /// Delete an inlet, stop monitoring its connection to the outlet and close that connection
+    pub async fn delete_inlet(&self, ctx: &Context, alias: &str) -> Result<InletStatus> {
+        let inlet = self.registry.inlets.get(alias).await;
+        if let Some(key) = inlet.as_ref().and_then(|inlet| inlet.session.as_ref()) {
+            self.medic_handle.remove_session(key);
+        }
+        let status = self.node_manager.delete_inlet(alias).await?;
+        if let Some(connection) = inlet.and_then(|inlet| inlet.connection) {
+            let connection = connection.lock().unwrap().clone();
+            self.close_connection(ctx, &connection).await;
+        }
+        Ok(status)
+    }
+
+    /// Create a session monitoring the connection of an inlet to its outlet, which recreates
+    /// the inlet when the connection is lost. There is no session if the outlet is local.
+    #[allow(clippy::too_many_arguments)]
+    async fn monitor_inlet(
+        &self,
+        connection_ctx: Arc<Context>,
+        connection: Arc<Mutex<Connection>>,
+        alias: &str,
+        inlet_address: Address,
+        listen_addr: String,
+        outlet_addr: MultiAddr,
+        prefix_route: Route,
+        suffix_route: Route,
+        authorized: Option<Identifier>,
+        access_control: Arc<dyn IncomingAccessControl>,
+    ) -> Result<Option<Key>> {
+        let current_connection = connection.lock().unwrap().clone();
+        if current_connection
+            .route(self.tcp_transport())
+            .await?
+            .is_empty()
+        {
+            return Ok(None);
+        }
+        debug! {
+            %alias,
+            %listen_addr,
+            %inlet_address,
+            ping_addr = %current_connection.transport_route(),
+            "Creating session for TCP inlet"
+        };
+        let mut session = Session::new(current_connection.transport_route());
+        session.set_resource("tcp-inlet", alias);
+        session.set_tcp_connection(Medic::watch_tcp_connection(
+            self.tcp_transport(),
+            &current_connection,
+        ));
+
+        let repl = Self::portal_replacer(
+            self.node_manager.clone(),
+            connection_ctx,
+            connection,
+            alias.to_string(),
+            inlet_address,
+            listen_addr,
+            outlet_addr,
+            prefix_route,
+            suffix_route,
+            authorized,
+            access_control,
+        );
+        session.set_replacer(repl);
+        Ok(Some(self.add_session(session)))
+    }
+
     /// Create a session replacer.
     ///
     /// This returns a function that accepts the previous ping address (e.g.
     /// the secure channel worker address) and constructs the whole route
-    /// again.
+    /// again. The new connection is stored in `connection_arc`, which is shared
+    /// with the inlet registry entry.
     #[allow(clippy::too_many_arguments)]
     fn portal_replacer(
         node_manager: Arc<NodeManager>,
         ctx: Arc<Context>,
-        connection: Connection,
+        connection_arc: Arc<Mutex<Connection>>,
+        alias: String,
         inlet_address: Address,
         bind: String,
         addr: MultiAddr,
@@ -597,11 +1045,11 @@ impl InMemoryNode {
         authorized: Option<Identifier>,
         access: Arc<dyn IncomingAccessControl>,
     ) -> Replacer {
-        let connection_arc = Arc::new(Mutex::new(connection.clone()));
         let inlet_address_arc = Arc::new(Mutex::new(inlet_address));
         let node_manager = node_manager.clone();
 
         Box::new(move |previous_addr| {
+            let alias = alias.clone();
             let addr = addr.clone();
             let authorized = authorized.clone();
             let bind = bind.clone();
@@ -618,23 +1066,10 @@ impl InMemoryNode {
                 debug!(%previous_addr, %addr, "creating new tcp inlet");
                 // The future that recreates the inlet:
                 let f = async {
-                    //stop/delete previous secure channels
-                    for encryptor in &previous_connection.secure_channel_encryptors {
-                        let result = node_manager.delete_secure_channel(&ctx, encryptor).await;
-                        if let Err(error) = result {
-                            //we can't do much more
-                            debug!("cannot delete secure channel `{encryptor}`: {error}");
-                        }
-                    }
-                    if let Some(tcp_connection) = previous_connection.tcp_connection.as_ref() {
-                        if let Err(error) = node_manager
-                            .tcp_transport
-                            .disconnect(tcp_connection.sender_address().clone())
-                            .await
-                        {
-                            debug!("cannot stop tcp worker `{tcp_connection}`: {error}");
-                        }
-                    }
+                    //stop/delete previous secure channels and tcp connection
+                    node_manager
+                        .close_connection(&ctx, &previous_connection)
+                        .await;
 
                     // The previous inlet worker needs to be stopped:
                     if let Err(error) = node_manager
@@ -647,12 +1082,10 @@ impl InMemoryNode {
 
                     // Now a connection attempt is made
                     let new_connection = node_manager
-                        .make_connection(
+                        .make_reconnecting_connection(
                             ctx.clone(),
                             &addr,
-                            None,
                             authorized,
-                            None,
                             Some(MAX_CONNECT_TIME),
                         )
                         .await?;
@@ -670,9 +1103,18 @@ impl InMemoryNode {
                         .create_inlet(bind, normalized_route, options)
                         .await?
                         .1;
-                    *inlet_address_arc.lock().unwrap() = new_inlet_address;
+                    *inlet_address_arc.lock().unwrap() = new_inlet_address.clone();
 
-                    Ok(new_connection.transport_route())
+                    // The inlet must be stopped at its new address when it is deleted
+                    if let Some(mut inlet) = node_manager.registry.inlets.get(&alias).await {
+                        inlet.worker_addr = new_inlet_address;
+                        node_manager.registry.inlets.insert(alias, inlet).await;
+                    }
+
+                    let tcp_connection =
+                        Medic::watch_tcp_connection(node_manager.tcp_transport(), &new_connection);
+                    Ok(ReplacedRoute::new(new_connection.transport_route())
+                        .with_tcp_connection(tcp_connection))
                 };
 
                 // The above future is given some limited time to succeed.
@@ -691,3 +1133,122 @@ impl InMemoryNode {
         })
     }
 }
+
+/// Wait until a stopped worker has released its address, so that it can be used again
+async fn wait_for_worker_to_stop(ctx: &Context, address: &Address) -> Result<()> {
+    for _ in 0..100 {
+        if !ctx.list_workers().await?.contains(address) {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let message = format!("The worker {address} was not stopped");
+    Err(ockam_core::Error::new(Origin::Node, Kind::Timeout, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{start_manager_for_tests, NodeManagerHandle};
+    use std::net::TcpListener;
+
+    /// Create an outlet on the node, and an inlet reaching it through a secure channel
+    /// with the node itself
+    async fn create_portal(
+        ctx: &Context,
+        handle: &NodeManagerHandle,
+        authorized: Option<Identifier>,
+    ) -> Result<TcpListener> {
+        let target = TcpListener::bind("127.0.0.1:0").unwrap();
+        handle
+            .node_manager
+            .create_outlet(
+                ctx,
+                target.local_addr().unwrap(),
+                "outlet".into(),
+                Some("outlet".to_string()),
+                true,
+            )
+            .await?;
+        handle
+            .node_manager
+            .create_inlet(
+                ctx,
+                "127.0.0.1:0".to_string(),
+                Some("inlet".to_string()),
+                route![],
+                route![],
+                outlet_multiaddr(),
+                None,
+                authorized,
+            )
+            .await?;
+        Ok(target)
+    }
+
+    fn outlet_multiaddr() -> MultiAddr {
+        "/secure/api/service/outlet".parse().unwrap()
+    }
+
+    async fn secure_channel_encryptors(handle: &NodeManagerHandle) -> Vec<Address> {
+        handle
+            .node_manager
+            .list_secure_channels()
+            .await
+            .iter()
+            .map(|info| info.sc().encryptor_address().clone())
+            .collect()
+    }
+
+    #[ockam_macros::test]
+    async fn updating_an_inlet_closes_its_previous_connection(ctx: &mut Context) -> Result<()> {
+        let handle = start_manager_for_tests(ctx).await?;
+        let _target = create_portal(ctx, &handle, None).await?;
+        let previous_encryptors = secure_channel_encryptors(&handle).await;
+        assert_eq!(previous_encryptors.len(), 1);
+
+        handle
+            .node_manager
+            .update_inlet(ctx, "inlet", outlet_multiaddr(), None, None, false)
+            .await?;
+
+        let encryptors = secure_channel_encryptors(&handle).await;
+        assert_eq!(encryptors.len(), 1);
+        assert!(!encryptors.contains(&previous_encryptors[0]));
+        wait_for_worker_to_stop(ctx, &previous_encryptors[0]).await?;
+
+        // The connection is closed as well when the inlet is deleted
+        handle.node_manager.delete_inlet(ctx, "inlet").await?;
+        assert!(secure_channel_encryptors(&handle).await.is_empty());
+        wait_for_worker_to_stop(ctx, &encryptors[0]).await?;
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn updating_an_inlet_keeps_its_authorized_identity_unless_cleared(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let handle = start_manager_for_tests(ctx).await?;
+        let _target = create_portal(ctx, &handle, Some(handle.identifier.clone())).await?;
+
+        handle
+            .node_manager
+            .update_inlet(ctx, "inlet", outlet_multiaddr(), None, None, false)
+            .await?;
+        let inlet = handle.node_manager.registry.inlets.get("inlet").await;
+        assert_eq!(
+            inlet.and_then(|inlet| inlet.authorized),
+            Some(handle.identifier.clone())
+        );
+
+        handle
+            .node_manager
+            .update_inlet(ctx, "inlet", outlet_multiaddr(), None, None, true)
+            .await?;
+        let inlet = handle.node_manager.registry.inlets.get("inlet").await;
+        assert_eq!(inlet.and_then(|inlet| inlet.authorized), None);
+
+        ctx.stop().await
+    }
+}
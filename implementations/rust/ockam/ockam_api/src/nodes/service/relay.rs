@@ -15,14 +15,15 @@ use ockam_node::Context;
 
 use crate::error::ApiError;
 use crate::nodes::connection::Connection;
-use crate::nodes::models::relay::{CreateRelay, RelayInfo};
+use crate::nodes::models::relay::{CreateRelay, RegisteredRelay, RelayInfo};
 use crate::nodes::models::secure_channel::{
     CreateSecureChannelRequest, CreateSecureChannelResponse,
 };
 use crate::nodes::service::in_memory_node::InMemoryNode;
 use crate::nodes::BackgroundNode;
-use crate::session::sessions::{Replacer, Session};
+use crate::session::sessions::{ReplacedRoute, Replacer, Session};
 use crate::session::sessions::{MAX_CONNECT_TIME, MAX_RECOVERY_TIME};
+use crate::session::Medic;
 
 use super::{NodeManager, NodeManagerWorker};
 
@@ -78,6 +79,25 @@ impl NodeManagerWorker {
         debug!("Handling GetRelays request");
         Ok(Response::ok(req).body(self.node_manager.get_relays().await))
     }
+
+    pub async fn get_registered_relays(
+        &self,
+        req: &RequestHeader,
+    ) -> Result<Response<Vec<RegisteredRelay>>, Response<Error>> {
+        debug!("Handling GetRegisteredRelays request");
+        Ok(Response::ok(req).body(self.node_manager.get_registered_relays()))
+    }
+
+    pub async fn evict_registered_relay(
+        &self,
+        ctx: &Context,
+        req: &RequestHeader,
+        name: &str,
+    ) -> Result<Response<RegisteredRelay>, Response<Error>> {
+        self.node_manager
+            .evict_registered_relay(ctx, req, name)
+            .await
+    }
 }
 
 impl NodeManager {
@@ -96,6 +116,48 @@ impl NodeManager {
         relays
     }
 
+    /// This function returns the relays which were registered by other nodes
+    /// at the relay service of this node
+    pub fn get_registered_relays(&self) -> Vec<RegisteredRelay> {
+        self.relay_registry
+            .get_all()
+            .iter()
+            .map(RegisteredRelay::from)
+            .collect()
+    }
+
+    /// This function stops a relay which was registered by another node
+    /// at the relay service of this node, and removes it from the relay registry.
+    /// The ownership of the relay name is released, so that any identity can register it
+    pub async fn evict_registered_relay(
+        &self,
+        ctx: &Context,
+        req: &RequestHeader,
+        name: &str,
+    ) -> Result<Response<RegisteredRelay>, Response<Error>> {
+        debug!(%name, "Handling EvictRegisteredRelay request");
+        match self.relay_registry.get(name) {
+            Some(entry) => match ctx.stop_worker(entry.address().clone()).await {
+                Ok(_) => {
+                    self.relay_registry.release(name);
+                    debug!(%name, "Successfully evicted relay");
+                    Ok(Response::ok(req).body(RegisteredRelay::from(&entry)))
+                }
+                Err(err) => {
+                    error!(%name, ?err, "Failed to evict relay");
+                    Err(Response::internal_error(
+                        req,
+                        &format!("Failed to evict relay {}: {}", name, err),
+                    ))
+                }
+            },
+            None => Err(Response::not_found(
+                req,
+                &format!("Relay {} is not registered at this node.", name),
+            )),
+        }
+    }
+
     /// Create a new Relay
     /// The Connection encapsulates the list of workers required on the relay route.
     /// This route is monitored in the `InMemoryNode` and the workers are restarted if necessary
@@ -215,13 +277,11 @@ impl InMemoryNode {
         debug!(addr = %address, alias = ?alias, at_rust_node = ?at_rust_node, "Handling CreateRelay request");
         let connection_ctx = Arc::new(ctx.async_try_clone().await?);
         let connection = self
-            .make_connection(
+            .make_reconnecting_connection(
                 connection_ctx.clone(),
                 &address.clone(),
-                None,
                 authorized.clone(),
                 None,
-                None,
             )
             .await?;
         connection.add_default_consumers(connection_ctx.clone());
@@ -244,6 +304,10 @@ impl InMemoryNode {
 
         if !at_rust_node && !connection.transport_route().is_empty() {
             let ping_route = connection.transport_route().clone();
+            let tcp_connection = Medic::watch_tcp_connection(self.tcp_transport(), &connection);
+            let name = alias
+                .clone()
+                .unwrap_or_else(|| relay.remote_address().to_string());
             let repl = Self::relay_replacer(
                 self.node_manager.clone(),
                 Arc::new(ctx.async_try_clone().await?),
@@ -253,6 +317,8 @@ impl InMemoryNode {
                 authorized,
             );
             let mut session = Session::new(ping_route);
+            session.set_resource("relay", name);
+            session.set_tcp_connection(tcp_connection);
             session.set_replacer(repl);
             self.add_session(session);
         };
@@ -307,12 +373,10 @@ impl InMemoryNode {
                     }
 
                     let connection = node_manager
-                        .make_connection(
+                        .make_reconnecting_connection(
                             ctx.clone(),
                             &addr,
-                            None,
                             authorized,
-                            None,
                             Some(MAX_CONNECT_TIME),
                         )
                         .await?;
@@ -327,7 +391,10 @@ impl InMemoryNode {
                     } else {
                         RemoteRelay::create(&ctx, route, options).await?;
                     }
-                    Ok(connection.transport_route())
+                    let tcp_connection =
+                        Medic::watch_tcp_connection(node_manager.tcp_transport(), &connection);
+                    Ok(ReplacedRoute::new(connection.transport_route())
+                        .with_tcp_connection(tcp_connection))
                 };
                 match timeout(MAX_RECOVERY_TIME, f).await {
                     Err(_) => {
@@ -403,6 +470,7 @@ impl SecureChannelsCreation for InMemoryNode {
                 identity_name,
                 Some(vec![authorized]),
                 credential_name,
+                None,
                 timeout,
             )
             .await
@@ -437,3 +505,94 @@ impl SecureChannelsCreation for BackgroundNode {
         Ok(response.addr)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::NODEMANAGER_ADDR;
+    use crate::test_utils::{start_manager_for_tests, NodeManagerTestOptions};
+    use ockam_core::api::Status;
+    use ockam_core::route;
+
+    async fn get_registered_relays(ctx: &Context) -> Result<Vec<RegisteredRelay>> {
+        let response: Vec<u8> = ctx
+            .send_and_receive(route![NODEMANAGER_ADDR], Request::get("/relays").to_vec()?)
+            .await?;
+        let (header, mut decoder) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Ok));
+        Ok(decoder.decode()?)
+    }
+
+    async fn evict_registered_relay(ctx: &Context, name: &str) -> Result<Option<Status>> {
+        let response: Vec<u8> = ctx
+            .send_and_receive(
+                route![NODEMANAGER_ADDR],
+                Request::delete(format!("/relays/{name}")).to_vec()?,
+            )
+            .await?;
+        let (header, _) = Response::parse_response_header(&response)?;
+        Ok(header.status())
+    }
+
+    #[ockam_macros::test]
+    async fn registered_relays_expire_with_the_relay_ttl(ctx: &mut Context) -> Result<()> {
+        let _handle = NodeManagerTestOptions::new()
+            .with_relay_ttl(Duration::from_secs(1))
+            .start(ctx)
+            .await?;
+
+        // No heartbeats are sent for this relay
+        let relay = RemoteRelay::create_static_without_heartbeats(
+            ctx,
+            route![],
+            "expiring",
+            RemoteRelayOptions::new(),
+        )
+        .await?;
+        let relays = get_registered_relays(ctx).await?;
+        assert_eq!(relays.len(), 1);
+        assert_eq!(relays[0].name(), relay.remote_address());
+
+        ctx.sleep(Duration::from_secs(4)).await;
+        assert!(get_registered_relays(ctx).await?.is_empty());
+        assert!(!ctx
+            .list_workers()
+            .await?
+            .contains(&relay.remote_address().into()));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn registered_relays_can_be_evicted(ctx: &mut Context) -> Result<()> {
+        let _handle = start_manager_for_tests(ctx).await?;
+
+        let relay = RemoteRelay::create_static_without_heartbeats(
+            ctx,
+            route![],
+            "evicted",
+            RemoteRelayOptions::new(),
+        )
+        .await?;
+        assert_eq!(get_registered_relays(ctx).await?.len(), 1);
+
+        assert_eq!(
+            evict_registered_relay(ctx, relay.remote_address()).await?,
+            Some(Status::Ok)
+        );
+        // The relay is removed from the registry once its worker is stopped
+        let mut attempts = 0;
+        while !get_registered_relays(ctx).await?.is_empty() {
+            attempts += 1;
+            assert!(attempts < 50, "the relay was not evicted");
+            ctx.sleep(Duration::from_millis(100)).await;
+        }
+
+        assert_eq!(
+            evict_registered_relay(ctx, relay.remote_address()).await?,
+            Some(Status::NotFound)
+        );
+
+        ctx.stop().await
+    }
+}
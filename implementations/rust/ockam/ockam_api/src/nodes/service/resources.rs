@@ -0,0 +1,320 @@
+use minicbor::Decoder;
+
+use ockam::{Address, Context, Result};
+use ockam_core::api::{Method, RequestHeader, ResponseHeader};
+
+use crate::cli_state::{NodeResource, NodeResourceKind, NodeState, StateDirTrait};
+use crate::nodes::models::portal::{
+    CreateInlet, CreateOutlet, DeletePortal, InletStatus, OutletStatus, UpdateInlet, UpdateOutlet,
+};
+use crate::nodes::models::relay::RelayInfo;
+use crate::nodes::models::secure_channel::{
+    CreateSecureChannelListenerRequest, DeleteSecureChannelListenerRequest,
+};
+use crate::nodes::models::services::{
+    DeleteServiceRequest, StartKafkaConsumerRequest, StartKafkaDirectRequest,
+    StartKafkaOutletRequest, StartKafkaProducerRequest, StartServiceRequest,
+};
+use crate::nodes::{NodeManager, NodeManagerWorker, NODEMANAGER_ADDR};
+use crate::DefaultAddress;
+
+impl NodeManagerWorker {
+    /// Record the resources created, or deleted, by a successful request so that
+    /// they can be recreated when the node is started again
+    pub(super) fn persist_resource(&self, req: &RequestHeader, body: &[u8], response: &[u8]) {
+        if !self.node_manager.persist_resources {
+            return;
+        }
+        if let Err(err) = self.node_manager.update_resources(req, body, response) {
+            warn!(path = %req.path(), %err, "Failed to persist a node resource");
+        }
+    }
+}
+
+impl NodeManager {
+    fn update_resources(&self, req: &RequestHeader, body: &[u8], response: &[u8]) -> Result<()> {
+        let mut dec = Decoder::new(response);
+        let header: ResponseHeader = dec.decode()?;
+        if !header.is_ok() {
+            return Ok(());
+        }
+
+        use Method::*;
+        use NodeResourceKind::*;
+        let method = match req.method() {
+            Some(m) => m,
+            None => return Ok(()),
+        };
+        let path = req.path();
+        let node_state = self.cli_state.nodes.get(&self.node_name)?;
+
+        match (method, req.path_segments::<5>().as_slice()) {
+            (Post, ["node", "secure_channel_listener"]) => {
+                let request: CreateSecureChannelListenerRequest = Decoder::new(body).decode()?;
+                node_state.add_resource(NodeResource::new(
+                    SecureChannelListener,
+                    address_name(&request.addr),
+                    path,
+                    body.to_vec(),
+                ))?
+            }
+            (Delete, ["node", "secure_channel_listener"]) => {
+                let request: DeleteSecureChannelListenerRequest = Decoder::new(body).decode()?;
+                node_state.remove_resource(SecureChannelListener, &address_name(&request.addr))?
+            }
+            (Post, ["node", "outlet"]) => {
+                // Store the alias in the request, it might have been generated by the node
+                let status: OutletStatus = dec.decode()?;
+                let mut request: CreateOutlet = Decoder::new(body).decode()?;
+                request.alias = Some(status.alias.clone());
+                node_state.add_resource(NodeResource::new(
+                    TcpOutlet,
+                    status.alias,
+                    path,
+                    minicbor::to_vec(request)?,
+                ))?
+            }
+            (Delete, ["node", "outlet", alias]) => node_state.remove_resource(TcpOutlet, alias)?,
+            (Post, ["node", "inlet"]) => {
+                let status: InletStatus = dec.decode()?;
+                let mut request: CreateInlet = Decoder::new(body).decode()?;
+                request.alias = Some(status.alias.clone());
+                node_state.add_resource(NodeResource::new(
+                    TcpInlet,
+                    status.alias,
+                    path,
+                    minicbor::to_vec(request)?,
+                ))?
+            }
+            (Delete, ["node", "inlet", alias]) => node_state.remove_resource(TcpInlet, alias)?,
+            (Patch, ["node", "outlet", alias]) => {
+                let update: UpdateOutlet = Decoder::new(body).decode()?;
+                if let Some(mut resource) = find_resource(&node_state, TcpOutlet, alias)? {
+                    let mut request: CreateOutlet = Decoder::new(&resource.body).decode()?;
+                    request.socket_addr = update.socket_addr;
+                    resource.body = minicbor::to_vec(request)?;
+                    node_state.add_resource(resource)?
+                }
+            }
+            (Patch, ["node", "inlet", alias]) => {
+                let update: UpdateInlet = Decoder::new(body).decode()?;
+                if let Some(mut resource) = find_resource(&node_state, TcpInlet, alias)? {
+                    let mut request: CreateInlet = Decoder::new(&resource.body).decode()?;
+                    request.outlet_addr = update.outlet_addr;
+                    if update.clear_authorized {
+                        request.authorized = None;
+                    } else if update.authorized.is_some() {
+                        request.authorized = update.authorized;
+                    }
+                    if update.wait_for_outlet_duration.is_some() {
+                        request.wait_for_outlet_duration = update.wait_for_outlet_duration;
+                    }
+                    resource.body = minicbor::to_vec(request)?;
+                    node_state.add_resource(resource)?
+                }
+            }
+            (Delete, ["node", "portal"]) => {
+                let request: DeletePortal = Decoder::new(body).decode()?;
+                node_state.remove_resource(TcpInlet, &request.alias)?;
+                node_state.remove_resource(TcpOutlet, &request.alias)?
+            }
+            (Post, ["node", "forwarder"]) => {
+                let info: RelayInfo = dec.decode()?;
+                node_state.add_resource(NodeResource::new(
+                    Relay,
+                    info.remote_address(),
+                    path,
+                    body.to_vec(),
+                ))?
+            }
+            (Delete, ["node", "forwarder", remote_address]) => {
+                node_state.remove_resource(Relay, remote_address)?
+            }
+            (Post, ["node", "services", service]) => {
+                let mut dec = Decoder::new(body);
+                let address = match *service {
+                    DefaultAddress::KAFKA_OUTLET => dec
+                        .decode::<StartServiceRequest<StartKafkaOutletRequest>>()?
+                        .address()
+                        .to_string(),
+                    DefaultAddress::KAFKA_CONSUMER => dec
+                        .decode::<StartServiceRequest<StartKafkaConsumerRequest>>()?
+                        .address()
+                        .to_string(),
+                    DefaultAddress::KAFKA_PRODUCER => dec
+                        .decode::<StartServiceRequest<StartKafkaProducerRequest>>()?
+                        .address()
+                        .to_string(),
+                    DefaultAddress::KAFKA_DIRECT => dec
+                        .decode::<StartServiceRequest<StartKafkaDirectRequest>>()?
+                        .address()
+                        .to_string(),
+                    _ => return Ok(()),
+                };
+                node_state.add_resource(NodeResource::new(
+                    KafkaService,
+                    address_name(&address),
+                    path,
+                    body.to_vec(),
+                ))?
+            }
+            (Delete, ["node", "services", _]) => {
+                let request: DeleteServiceRequest = Decoder::new(body).decode()?;
+                node_state.remove_resource(KafkaService, request.address().address())?
+            }
+            _ => {}
+        };
+
+        Ok(())
+    }
+}
+
+fn find_resource(
+    node_state: &NodeState,
+    kind: NodeResourceKind,
+    name: &str,
+) -> Result<Option<NodeResource>> {
+    Ok(node_state
+        .resources()?
+        .into_iter()
+        .find(|r| r.kind == kind && r.name == name))
+}
+
+/// Addresses are recorded without their transport type
+fn address_name(address: &str) -> String {
+    address
+        .parse::<Address>()
+        .map(|a| a.address().to_string())
+        .unwrap_or_else(|_| address.to_string())
+}
+
+/// Recreate the resources persisted for a node, once its node manager is started.
+///
+/// Resources are created in dependency order, by replaying the requests which
+/// created them. The ones which can't be recreated are kept for the next start.
+pub async fn restore_resources(ctx: &Context, node_state: &NodeState) -> Result<()> {
+    let mut resources = node_state.resources()?;
+    if resources.is_empty() {
+        return Ok(());
+    }
+    resources.sort_by_key(|r| r.kind);
+
+    // The node manager records the resources again when they are created
+    node_state.set_resources(&[])?;
+    for resource in resources {
+        let mut request = minicbor::to_vec(RequestHeader::new(
+            Method::Post,
+            resource.path.clone(),
+            true,
+        ))?;
+        request.extend_from_slice(&resource.body);
+
+        let result: Result<Vec<u8>> = ctx.send_and_receive(NODEMANAGER_ADDR, request).await;
+        let restored = match &result {
+            Ok(response) => Decoder::new(response)
+                .decode::<ResponseHeader>()
+                .map(|header| header.is_ok())
+                .unwrap_or(false),
+            Err(_) => false,
+        };
+        if restored {
+            info!(kind = ?resource.kind, name = %resource.name, "Restored a node resource");
+        } else {
+            warn!(kind = ?resource.kind, name = %resource.name, "Failed to restore a node resource");
+            node_state.add_resource(resource)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::NodeManagerTestOptions;
+    use ockam_core::api::{Request, Response, Status};
+    use ockam_core::route;
+    use std::net::TcpListener;
+
+    async fn post<T: minicbor::Encode<()>>(ctx: &Context, path: &str, body: T) -> Result<()> {
+        let response: Vec<u8> = ctx
+            .send_and_receive(
+                route![NODEMANAGER_ADDR],
+                Request::post(path).body(body).to_vec()?,
+            )
+            .await?;
+        let (header, _) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Ok));
+        Ok(())
+    }
+
+    #[ockam_macros::test]
+    async fn resources_are_restored_when_the_node_restarts(ctx: &mut Context) -> Result<()> {
+        let mut handle = NodeManagerTestOptions::new()
+            .persistent()
+            .start(ctx)
+            .await?;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let outlet_address = Address::from_string("outlet");
+        post(
+            ctx,
+            "/node/outlet",
+            CreateOutlet::new(
+                listener.local_addr().unwrap(),
+                outlet_address.clone(),
+                "db".to_string(),
+                false,
+            ),
+        )
+        .await?;
+        let listener_address = Address::from_string("listener");
+        post(
+            ctx,
+            "/node/secure_channel_listener",
+            CreateSecureChannelListenerRequest::new(&listener_address, None, None, None),
+        )
+        .await?;
+
+        let node_state = handle
+            .cli_state
+            .nodes
+            .get(&handle.node_manager.node_name())?;
+        let mut persisted: Vec<(NodeResourceKind, String)> = node_state
+            .resources()?
+            .into_iter()
+            .map(|r| (r.kind, r.name))
+            .collect();
+        persisted.sort();
+        assert_eq!(
+            persisted,
+            vec![
+                (
+                    NodeResourceKind::SecureChannelListener,
+                    "listener".to_string()
+                ),
+                (NodeResourceKind::TcpOutlet, "db".to_string()),
+            ]
+        );
+
+        // The resources are lost when the node stops
+        ctx.stop_worker(outlet_address.clone()).await?;
+        ctx.stop_worker(listener_address.clone()).await?;
+        handle.restart(ctx).await?;
+
+        let workers = ctx.list_workers().await?;
+        assert!(workers.contains(&outlet_address));
+        assert!(workers.contains(&listener_address));
+        let outlets = handle.node_manager.list_outlets().await;
+        assert_eq!(outlets.list.len(), 1);
+        assert_eq!(outlets.list[0].alias, "db");
+        assert_eq!(node_state.resources()?.len(), 2);
+
+        // The restarted node manager can still manage the other transports
+        assert!(handle.node_manager.web_socket_transport.is_some());
+        #[cfg(unix)]
+        assert!(handle.node_manager.uds_transport.is_some());
+
+        ctx.stop().await
+    }
+}
@@ -57,6 +57,7 @@ impl NodeManagerWorker {
             timeout,
             identity_name: identity,
             credential_name,
+            disclosed_attributes,
             ..
         } = dec.decode()?;
 
@@ -91,6 +92,7 @@ impl NodeManagerWorker {
                 identity,
                 authorized_identifiers,
                 credential_name,
+                disclosed_attributes,
                 timeout,
             )
             .await?;
@@ -258,6 +260,7 @@ impl NodeManager {
         identity_name: Option<String>,
         authorized_identifiers: Option<Vec<Identifier>>,
         credential_name: Option<String>,
+        disclosed_attributes: Option<Vec<String>>,
         timeout: Option<Duration>,
     ) -> Result<SecureChannel> {
         let identifier = self.get_identifier(identity_name.clone()).await?;
@@ -284,6 +287,8 @@ impl NodeManager {
                 authorized_identifiers,
                 timeout,
                 credential,
+                disclosed_attributes,
+                Some(addr.to_string()),
             )
             .await?;
 
@@ -330,6 +335,9 @@ impl NodeManager {
         Ok(credential)
     }
 
+    /// Create a secure channel. When a resumption key is given, a new channel to the same
+    /// destination is resumed in one round-trip with the ticket of the previous channel
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn create_secure_channel_internal(
         &self,
         ctx: &Context,
@@ -338,6 +346,8 @@ impl NodeManager {
         authorized_identifiers: Option<Vec<Identifier>>,
         timeout: Option<Duration>,
         credential: Option<CredentialAndPurposeKey>,
+        disclosed_attributes: Option<Vec<String>>,
+        resumption_key: Option<String>,
     ) -> Result<SecureChannel> {
         debug!(%sc_route, "Creating secure channel");
         let options = SecureChannelOptions::new();
@@ -357,6 +367,14 @@ impl NodeManager {
             options
         };
 
+        // The attributes needed by the listener are chosen by the holder of the credential
+        let options = match disclosed_attributes {
+            Some(attributes) => options.with_disclosed_attributes(
+                attributes.into_iter().map(String::into_bytes).collect(),
+            ),
+            None => options,
+        };
+
         let options = match authorized_identifiers.clone() {
             Some(ids) => options.with_trust_policy(TrustMultiIdentifiersPolicy::new(ids)),
             None => options.with_trust_policy(TrustEveryonePolicy),
@@ -367,6 +385,11 @@ impl NodeManager {
             None => options,
         };
 
+        let options = match resumption_key {
+            Some(key) => options.with_resumption(key),
+            None => options,
+        };
+
         let sc = self
             .secure_channels
             .create_secure_channel(ctx, identifier, sc_route.clone(), options)
@@ -418,8 +441,9 @@ impl NodeManager {
         let secure_channels = self.build_secure_channels(vault_name.clone()).await?;
         let identifier = self.get_identifier(identity_name.clone()).await?;
 
-        let options =
-            SecureChannelListenerOptions::new().as_consumer(&self.api_transport_flow_control_id);
+        let options = SecureChannelListenerOptions::new()
+            .as_consumer(&self.api_transport_flow_control_id)
+            .with_resumption();
 
         let options = match authorized_identifiers {
             Some(ids) => options.with_trust_policy(TrustMultiIdentifiersPolicy::new(ids)),
@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use minicbor::Decoder;
+
+use ockam::{Result, Routed};
+use ockam_core::api::{RequestHeader, Response};
+use ockam_core::{Address, AllowAll, DenyAll};
+use ockam_node::tokio::time::timeout;
+use ockam_node::{tokio, Context};
+
+use crate::nodes::models::session::{GetSessionEvents, SessionEventList, SessionList};
+use crate::nodes::NodeManagerWorker;
+
+/// Default duration of a request waiting for session events
+const DEFAULT_EVENTS_WAIT: Duration = Duration::from_secs(30);
+
+/// Maximum duration of a request waiting for session events
+const MAX_EVENTS_WAIT: Duration = Duration::from_secs(60);
+
+impl NodeManagerWorker {
+    pub(super) fn get_sessions(&self, req: &RequestHeader) -> Response<SessionList> {
+        let statuses = self.node_manager.medic_handle.session_statuses();
+        Response::ok(req).body(SessionList::new(statuses))
+    }
+
+    /// Respond with the session events following the sequence number of the request.
+    ///
+    /// If there are no such events yet, the response is sent by a separate task as soon as
+    /// there is a new event or the wait duration is elapsed, so that the node manager can
+    /// keep handling other requests in the meantime.
+    pub(super) async fn get_session_events(
+        &self,
+        ctx: &Context,
+        msg: &Routed<Vec<u8>>,
+        req: &RequestHeader,
+        dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        let request: GetSessionEvents = match dec.decode() {
+            Ok(request) => request,
+            Err(e) => {
+                let r = Response::bad_request(req, &format!("invalid session events request: {e}"))
+                    .to_vec()?;
+                return ctx.send(msg.return_route(), r).await;
+            }
+        };
+        let medic_handle = &self.node_manager.medic_handle;
+        let after = request.after.unwrap_or_default();
+        let wait = request
+            .wait
+            .unwrap_or(DEFAULT_EVENTS_WAIT)
+            .min(MAX_EVENTS_WAIT);
+
+        // Subscribe before looking for events, in order to be notified of any event
+        // recorded after this point
+        let mut subscription = medic_handle.subscribe_session_events();
+        let (events, last) = medic_handle.session_events(after);
+        if !events.is_empty() || wait.is_zero() {
+            let r = Response::ok(req)
+                .body(SessionEventList { events, last })
+                .to_vec()?;
+            return ctx.send(msg.return_route(), r).await;
+        }
+
+        let reply_ctx = ctx
+            .new_detached(
+                Address::random_tagged("NodeManager.session_events"),
+                DenyAll,
+                AllowAll,
+            )
+            .await?;
+        let sessions = medic_handle.sessions();
+        let return_route = msg.return_route();
+        let req = req.clone();
+        tokio::spawn(async move {
+            let _ = timeout(wait, subscription.changed()).await;
+            let (events, last) = sessions.lock().unwrap().events_after(after);
+            let r = match Response::ok(&req)
+                .body(SessionEventList { events, last })
+                .to_vec()
+            {
+                Ok(r) => r,
+                Err(e) => {
+                    error!(%e, "failed to encode the session events");
+                    return;
+                }
+            };
+            if let Err(e) = reply_ctx.send(return_route, r).await {
+                debug!(%e, "failed to send the session events");
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::NODEMANAGER_ADDR;
+    use crate::test_utils::start_manager_for_tests;
+    use ockam_core::api::{Request, Status};
+    use ockam_core::route;
+
+    #[ockam_macros::test]
+    async fn invalid_session_events_request_is_answered(ctx: &mut Context) -> Result<()> {
+        let _handle = start_manager_for_tests(ctx).await?;
+
+        let response: Vec<u8> = ctx
+            .send_and_receive(
+                route![NODEMANAGER_ADDR],
+                Request::get("/node/sessions/events").to_vec()?,
+            )
+            .await?;
+        let (header, _) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::BadRequest));
+
+        let response: Vec<u8> = ctx
+            .send_and_receive(
+                route![NODEMANAGER_ADDR],
+                Request::get("/node/sessions/events")
+                    .body(GetSessionEvents::new(Some(0), Some(Duration::ZERO)))
+                    .to_vec()?,
+            )
+            .await?;
+        let (header, _) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Ok));
+
+        ctx.stop().await
+    }
+}
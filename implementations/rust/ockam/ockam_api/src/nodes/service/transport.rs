@@ -181,12 +181,15 @@ impl NodeManagerWorker {
         dec: &mut Decoder<'_>,
         ctx: &Context,
     ) -> Result<Response<TransportStatus>, Response<Error>> {
-        let CreateTcpConnection { addr, .. } = dec.decode()?;
+        let CreateTcpConnection { addr, liveness } = dec.decode()?;
 
         info!("Handling request to create a new TCP connection: {}", addr);
         let socket_addr = addr.to_string();
 
-        let options = TcpConnectionOptions::new();
+        let mut options = TcpConnectionOptions::new();
+        if let Some(liveness) = liveness {
+            options = liveness.connection_options(options);
+        }
 
         // Add all Hop workers as consumers for Demo purposes
         // Production nodes should not run any Hop workers
@@ -232,13 +235,16 @@ impl NodeManagerWorker {
         req: &RequestHeader,
         dec: &mut Decoder<'_>,
     ) -> Result<Response<TransportStatus>, Response<Error>> {
-        let CreateTcpListener { addr, .. } = dec.decode()?;
+        let CreateTcpListener { addr, liveness } = dec.decode()?;
 
         use {super::TransportType::*, TransportMode::*};
 
         info!("Handling request to create a new tcp listener: {}", addr);
 
-        let options = TcpListenerOptions::new();
+        let mut options = TcpListenerOptions::new();
+        if let Some(liveness) = liveness {
+            options = liveness.listener_options(options);
+        }
         let res = self.node_manager.tcp_transport.listen(&addr, options).await;
 
         let response = match res {
@@ -0,0 +1,341 @@
+use minicbor::Decoder;
+
+use ockam::Result;
+use ockam_core::api::{Error, RequestHeader, Response};
+use ockam_core::Address;
+use ockam_transport_uds::UdsTransport;
+
+use crate::error::ApiError;
+use crate::nodes::models::transport::{
+    CreateTransportConnection, CreateTransportListener, DeleteTransport, TransportList,
+    TransportMode, TransportStatus, TransportType,
+};
+use crate::nodes::registry::{TransportConnectionInfo, TransportListenerInfo};
+
+use super::{NodeManager, NodeManagerWorker};
+
+impl NodeManager {
+    /// Start a UDS listener on the given path and register it so that it can
+    /// be listed and deleted through the node manager API
+    pub async fn create_uds_listener(&self, path: &str) -> Result<Address> {
+        let uds = self
+            .uds_transport()
+            .ok_or_else(|| ApiError::core("The UDS transport is not enabled"))?;
+        let listener = uds.start_listener(path).await?;
+
+        // Unix socket addresses can't be displayed, the requested path is used instead
+        self.registry
+            .uds_listeners
+            .insert(
+                listener.processor_address().clone(),
+                TransportListenerInfo::new(path),
+            )
+            .await;
+        Ok(listener.processor_address().clone())
+    }
+}
+
+impl NodeManagerWorker {
+    fn uds_transport(&self, req: &RequestHeader) -> Result<&UdsTransport, Response<Error>> {
+        self.node_manager
+            .uds_transport()
+            .ok_or_else(|| Response::bad_request(req, "The UDS transport is not enabled"))
+    }
+
+    pub(super) async fn get_uds_connections(&self, req: &RequestHeader) -> Response<TransportList> {
+        let map = |(peer, info): (String, TransportConnectionInfo)| {
+            TransportStatus::without_flow_control(
+                TransportType::Uds,
+                TransportMode::Outgoing,
+                peer,
+                info.worker_addr.to_string(),
+                "<none>",
+            )
+        };
+
+        Response::ok(req).body(TransportList::new(
+            self.node_manager
+                .registry
+                .uds_connections
+                .entries()
+                .await
+                .into_iter()
+                .map(map)
+                .collect(),
+        ))
+    }
+
+    pub(super) async fn create_uds_connection(
+        &self,
+        req: &RequestHeader,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Response<TransportStatus>, Response<Error>> {
+        let CreateTransportConnection { addr, .. } = dec.decode()?;
+        let uds = self.uds_transport(req)?;
+
+        info!("Handling request to create a new UDS connection: {}", addr);
+        let worker_addr = match uds.connect(&addr).await {
+            Ok(worker_addr) => worker_addr,
+            Err(msg) => {
+                error!("{}", msg.to_string());
+                return Err(Response::bad_request(
+                    req,
+                    &format!("Unable to connect to {}: {}", addr, msg),
+                ));
+            }
+        };
+
+        self.node_manager
+            .registry
+            .uds_connections
+            .insert(
+                addr.clone(),
+                TransportConnectionInfo::new(worker_addr.clone()),
+            )
+            .await;
+
+        let status = TransportStatus::without_flow_control(
+            TransportType::Uds,
+            TransportMode::Outgoing,
+            addr,
+            worker_addr.to_string(),
+            "<none>",
+        );
+        Ok(Response::ok(req).body(status))
+    }
+
+    pub(super) async fn delete_uds_connection(
+        &self,
+        req: &RequestHeader,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Response<()>, Response<Error>> {
+        let body: DeleteTransport = dec.decode()?;
+        let uds = self.uds_transport(req)?;
+
+        info!("Handling request to disconnect from: {}", body.address);
+        match uds.disconnect(&body.address).await {
+            Ok(_) => {
+                self.node_manager
+                    .registry
+                    .uds_connections
+                    .remove(&body.address)
+                    .await;
+                Ok(Response::ok(req))
+            }
+            Err(err) => Err(Response::bad_request(
+                req,
+                &format!("Unable to disconnect from {}: {}", body.address, err),
+            )),
+        }
+    }
+
+    pub(super) async fn get_uds_listeners(&self, req: &RequestHeader) -> Response<TransportList> {
+        let map = |(address, info): (Address, TransportListenerInfo)| {
+            TransportStatus::without_flow_control(
+                TransportType::Uds,
+                TransportMode::Listen,
+                info.socket_addr,
+                "<none>",
+                address.to_string(),
+            )
+        };
+
+        Response::ok(req).body(TransportList::new(
+            self.node_manager
+                .registry
+                .uds_listeners
+                .entries()
+                .await
+                .into_iter()
+                .map(map)
+                .collect(),
+        ))
+    }
+
+    pub(super) async fn create_uds_listener(
+        &self,
+        req: &RequestHeader,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Response<TransportStatus>, Response<Error>> {
+        let CreateTransportListener { addr, .. } = dec.decode()?;
+
+        info!("Handling request to create a new UDS listener: {}", addr);
+        let processor_address = match self.node_manager.create_uds_listener(&addr).await {
+            Ok(processor_address) => processor_address,
+            Err(msg) => {
+                error!("{}", msg.to_string());
+                return Err(Response::bad_request(
+                    req,
+                    &format!("Unable to listen on {}: {}", addr, msg),
+                ));
+            }
+        };
+
+        let status = TransportStatus::without_flow_control(
+            TransportType::Uds,
+            TransportMode::Listen,
+            addr,
+            "<none>",
+            processor_address.to_string(),
+        );
+        Ok(Response::ok(req).body(status))
+    }
+
+    pub(super) async fn delete_uds_listener(
+        &self,
+        req: &RequestHeader,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Response<()>, Response<Error>> {
+        let body: DeleteTransport = dec.decode()?;
+        let uds = self.uds_transport(req)?;
+
+        info!("Handling request to stop listener: {}", body.address);
+
+        // The listener can be referred to by its socket address or its processor address
+        let listener_address = match self
+            .node_manager
+            .registry
+            .uds_listeners
+            .entries()
+            .await
+            .into_iter()
+            .find(|(address, info)| {
+                info.socket_addr == body.address || address == &Address::from(&body.address)
+            }) {
+            Some((address, _)) => address,
+            None => {
+                return Err(Response::bad_request(
+                    req,
+                    &format!("Listener {} was not found in the registry.", body.address),
+                ));
+            }
+        };
+
+        match uds.stop_listener(&listener_address).await {
+            Ok(_) => {
+                self.node_manager
+                    .registry
+                    .uds_listeners
+                    .remove(&listener_address)
+                    .await;
+                Ok(Response::ok(req))
+            }
+            Err(err) => Err(Response::bad_request(
+                req,
+                &format!("Unable to stop listener {}: {}", listener_address, err),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli_state::random_name;
+    use crate::echoer::Echoer;
+    use crate::nodes::NODEMANAGER_ADDR;
+    use crate::test_utils::start_manager_for_tests;
+    use ockam_core::api::{Request, Status};
+    use ockam_core::{route, AsyncTryClone};
+    use ockam_multiaddr::proto::{Service, Unix};
+    use ockam_multiaddr::MultiAddr;
+    use ockam_node::Context;
+    use std::sync::Arc;
+
+    /// Send a request to the node manager and decode the body of its successful response
+    async fn ask<T: for<'b> minicbor::Decode<'b, ()>>(
+        ctx: &Context,
+        request: Vec<u8>,
+    ) -> Result<T> {
+        let response: Vec<u8> = ctx
+            .send_and_receive(route![NODEMANAGER_ADDR], request)
+            .await?;
+        let (header, mut decoder) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Ok));
+        Ok(decoder.decode()?)
+    }
+
+    fn socket_path() -> String {
+        std::env::temp_dir()
+            .join(format!("{}.sock", random_name()))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[ockam_macros::test]
+    async fn create_list_and_delete_uds_connections(ctx: &mut Context) -> Result<()> {
+        let _handle = start_manager_for_tests(ctx).await?;
+        let path = socket_path();
+        let _listener: TransportStatus = ask(
+            ctx,
+            Request::post("/node/uds/listener")
+                .body(CreateTransportListener::new(path.clone()))
+                .to_vec()?,
+        )
+        .await?;
+
+        let connection: TransportStatus = ask(
+            ctx,
+            Request::post("/node/uds/connection")
+                .body(CreateTransportConnection::new(path.clone()))
+                .to_vec()?,
+        )
+        .await?;
+
+        let connections: TransportList =
+            ask(ctx, Request::get("/node/uds/connection").to_vec()?).await?;
+        assert_eq!(connections.list.len(), 1);
+        assert_eq!(connections.list[0].socket_addr, path);
+        assert_eq!(connections.list[0].worker_addr, connection.worker_addr);
+
+        let response: Vec<u8> = ctx
+            .send_and_receive(
+                route![NODEMANAGER_ADDR],
+                Request::delete("/node/uds/connection")
+                    .body(DeleteTransport::new(path.clone()))
+                    .to_vec()?,
+            )
+            .await?;
+        let (header, _) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Ok));
+
+        let connections: TransportList =
+            ask(ctx, Request::get("/node/uds/connection").to_vec()?).await?;
+        assert!(connections.list.is_empty());
+
+        let _ = std::fs::remove_file(path);
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn connect_to_a_uds_multiaddr(ctx: &mut Context) -> Result<()> {
+        let handle = start_manager_for_tests(ctx).await?;
+        let path = socket_path();
+        handle.node_manager.create_uds_listener(&path).await?;
+        ctx.start_worker("uds_echoer", Echoer).await?;
+
+        let mut addr = MultiAddr::default();
+        addr.push_back(Unix::new(path.as_str())).unwrap();
+        addr.push_back(Service::new("uds_echoer")).unwrap();
+        let connection = handle
+            .node_manager
+            .make_connection(
+                Arc::new(ctx.async_try_clone().await?),
+                &addr,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await?;
+
+        let route = connection
+            .route(handle.node_manager.tcp_transport())
+            .await?;
+        let reply: String = ctx.send_and_receive(route, "hello".to_string()).await?;
+        assert_eq!(reply, "hello");
+
+        let _ = std::fs::remove_file(path);
+        ctx.stop().await
+    }
+}
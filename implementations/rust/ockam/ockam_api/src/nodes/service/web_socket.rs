@@ -0,0 +1,344 @@
+use minicbor::Decoder;
+
+use ockam::Result;
+use ockam_core::api::{Error, RequestHeader, Response};
+use ockam_core::Address;
+use ockam_transport_websocket::{WebSocketListener, WebSocketTransport};
+
+use crate::error::ApiError;
+use crate::nodes::models::transport::{
+    CreateTransportConnection, CreateTransportListener, DeleteTransport, TransportList,
+    TransportMode, TransportStatus, TransportType,
+};
+use crate::nodes::registry::{TransportConnectionInfo, TransportListenerInfo};
+
+use super::{NodeManager, NodeManagerWorker};
+
+impl NodeManager {
+    /// Start a WebSocket listener on the given address and register it so that
+    /// it can be listed and deleted through the node manager API
+    pub async fn create_web_socket_listener(&self, addr: &str) -> Result<WebSocketListener> {
+        let ws = self
+            .web_socket_transport()
+            .ok_or_else(|| ApiError::core("The WebSocket transport is not enabled"))?;
+        let listener = ws.start_listener(addr).await?;
+
+        self.registry
+            .ws_listeners
+            .insert(
+                listener.processor_address().clone(),
+                TransportListenerInfo::new(listener.socket_address().to_string()),
+            )
+            .await;
+        Ok(listener)
+    }
+}
+
+impl NodeManagerWorker {
+    fn web_socket_transport(
+        &self,
+        req: &RequestHeader,
+    ) -> Result<&WebSocketTransport, Response<Error>> {
+        self.node_manager
+            .web_socket_transport()
+            .ok_or_else(|| Response::bad_request(req, "The WebSocket transport is not enabled"))
+    }
+
+    pub(super) async fn get_ws_connections(&self, req: &RequestHeader) -> Response<TransportList> {
+        let map = |(peer, info): (String, TransportConnectionInfo)| {
+            TransportStatus::without_flow_control(
+                TransportType::WebSocket,
+                TransportMode::Outgoing,
+                peer,
+                info.worker_addr.to_string(),
+                "<none>",
+            )
+        };
+
+        Response::ok(req).body(TransportList::new(
+            self.node_manager
+                .registry
+                .ws_connections
+                .entries()
+                .await
+                .into_iter()
+                .map(map)
+                .collect(),
+        ))
+    }
+
+    pub(super) async fn create_ws_connection(
+        &self,
+        req: &RequestHeader,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Response<TransportStatus>, Response<Error>> {
+        let CreateTransportConnection { addr, .. } = dec.decode()?;
+        let ws = self.web_socket_transport(req)?;
+
+        info!(
+            "Handling request to create a new WebSocket connection: {}",
+            addr
+        );
+        let worker_addr = match ws.connect(&addr).await {
+            Ok(worker_addr) => worker_addr,
+            Err(msg) => {
+                error!("{}", msg.to_string());
+                return Err(Response::bad_request(
+                    req,
+                    &format!("Unable to connect to {}: {}", addr, msg),
+                ));
+            }
+        };
+
+        self.node_manager
+            .registry
+            .ws_connections
+            .insert(
+                addr.clone(),
+                TransportConnectionInfo::new(worker_addr.clone()),
+            )
+            .await;
+
+        let status = TransportStatus::without_flow_control(
+            TransportType::WebSocket,
+            TransportMode::Outgoing,
+            addr,
+            worker_addr.to_string(),
+            "<none>",
+        );
+        Ok(Response::ok(req).body(status))
+    }
+
+    pub(super) async fn delete_ws_connection(
+        &self,
+        req: &RequestHeader,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Response<()>, Response<Error>> {
+        let body: DeleteTransport = dec.decode()?;
+        let ws = self.web_socket_transport(req)?;
+
+        info!("Handling request to disconnect from: {}", body.address);
+        match ws.disconnect(&body.address).await {
+            Ok(_) => {
+                self.node_manager
+                    .registry
+                    .ws_connections
+                    .remove(&body.address)
+                    .await;
+                Ok(Response::ok(req))
+            }
+            Err(err) => Err(Response::bad_request(
+                req,
+                &format!("Unable to disconnect from {}: {}", body.address, err),
+            )),
+        }
+    }
+
+    pub(super) async fn get_ws_listeners(&self, req: &RequestHeader) -> Response<TransportList> {
+        let map = |(address, info): (Address, TransportListenerInfo)| {
+            TransportStatus::without_flow_control(
+                TransportType::WebSocket,
+                TransportMode::Listen,
+                info.socket_addr,
+                "<none>",
+                address.to_string(),
+            )
+        };
+
+        Response::ok(req).body(TransportList::new(
+            self.node_manager
+                .registry
+                .ws_listeners
+                .entries()
+                .await
+                .into_iter()
+                .map(map)
+                .collect(),
+        ))
+    }
+
+    pub(super) async fn create_ws_listener(
+        &self,
+        req: &RequestHeader,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Response<TransportStatus>, Response<Error>> {
+        let CreateTransportListener { addr, .. } = dec.decode()?;
+
+        info!(
+            "Handling request to create a new WebSocket listener: {}",
+            addr
+        );
+        let listener = match self.node_manager.create_web_socket_listener(&addr).await {
+            Ok(listener) => listener,
+            Err(msg) => {
+                error!("{}", msg.to_string());
+                return Err(Response::bad_request(
+                    req,
+                    &format!("Unable to listen on {}: {}", addr, msg),
+                ));
+            }
+        };
+
+        let status = TransportStatus::without_flow_control(
+            TransportType::WebSocket,
+            TransportMode::Listen,
+            listener.socket_address().to_string(),
+            "<none>",
+            listener.processor_address().to_string(),
+        );
+        Ok(Response::ok(req).body(status))
+    }
+
+    pub(super) async fn delete_ws_listener(
+        &self,
+        req: &RequestHeader,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Response<()>, Response<Error>> {
+        let body: DeleteTransport = dec.decode()?;
+        let ws = self.web_socket_transport(req)?;
+
+        info!("Handling request to stop listener: {}", body.address);
+
+        // The listener can be referred to by its socket address or its processor address
+        let listener_address = match self
+            .node_manager
+            .registry
+            .ws_listeners
+            .entries()
+            .await
+            .into_iter()
+            .find(|(address, info)| {
+                info.socket_addr == body.address || address == &Address::from(&body.address)
+            }) {
+            Some((address, _)) => address,
+            None => {
+                return Err(Response::bad_request(
+                    req,
+                    &format!("Listener {} was not found in the registry.", body.address),
+                ));
+            }
+        };
+
+        match ws.stop_listener(&listener_address).await {
+            Ok(_) => {
+                self.node_manager
+                    .registry
+                    .ws_listeners
+                    .remove(&listener_address)
+                    .await;
+                Ok(Response::ok(req))
+            }
+            Err(err) => Err(Response::bad_request(
+                req,
+                &format!("Unable to stop listener {}: {}", listener_address, err),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::echoer::Echoer;
+    use crate::nodes::NODEMANAGER_ADDR;
+    use crate::test_utils::start_manager_for_tests;
+    use ockam_core::api::{Request, Status};
+    use ockam_core::{route, AsyncTryClone};
+    use ockam_multiaddr::MultiAddr;
+    use ockam_node::Context;
+    use std::sync::Arc;
+
+    /// Send a request to the node manager and decode the body of its successful response
+    async fn ask<T: for<'b> minicbor::Decode<'b, ()>>(
+        ctx: &Context,
+        request: Vec<u8>,
+    ) -> Result<T> {
+        let response: Vec<u8> = ctx
+            .send_and_receive(route![NODEMANAGER_ADDR], request)
+            .await?;
+        let (header, mut decoder) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Ok));
+        Ok(decoder.decode()?)
+    }
+
+    #[ockam_macros::test]
+    async fn create_list_and_delete_web_socket_connections(ctx: &mut Context) -> Result<()> {
+        let _handle = start_manager_for_tests(ctx).await?;
+        let listener: TransportStatus = ask(
+            ctx,
+            Request::post("/node/ws/listener")
+                .body(CreateTransportListener::new("127.0.0.1:0".to_string()))
+                .to_vec()?,
+        )
+        .await?;
+        let peer = listener.socket_addr;
+
+        let connection: TransportStatus = ask(
+            ctx,
+            Request::post("/node/ws/connection")
+                .body(CreateTransportConnection::new(peer.clone()))
+                .to_vec()?,
+        )
+        .await?;
+        assert_ne!(connection.worker_addr, "<none>");
+
+        let connections: TransportList =
+            ask(ctx, Request::get("/node/ws/connection").to_vec()?).await?;
+        assert_eq!(connections.list.len(), 1);
+        assert_eq!(connections.list[0].socket_addr, peer);
+        assert_eq!(connections.list[0].worker_addr, connection.worker_addr);
+
+        let response: Vec<u8> = ctx
+            .send_and_receive(
+                route![NODEMANAGER_ADDR],
+                Request::delete("/node/ws/connection")
+                    .body(DeleteTransport::new(peer))
+                    .to_vec()?,
+            )
+            .await?;
+        let (header, _) = Response::parse_response_header(&response)?;
+        assert_eq!(header.status(), Some(Status::Ok));
+
+        let connections: TransportList =
+            ask(ctx, Request::get("/node/ws/connection").to_vec()?).await?;
+        assert!(connections.list.is_empty());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn connect_to_a_web_socket_multiaddr(ctx: &mut Context) -> Result<()> {
+        let handle = start_manager_for_tests(ctx).await?;
+        let listener = handle
+            .node_manager
+            .create_web_socket_listener("127.0.0.1:0")
+            .await?;
+        ctx.start_worker("ws_echoer", Echoer).await?;
+
+        let port = listener.socket_address().port();
+        let addr: MultiAddr = format!("/ip4/127.0.0.1/tcp/{port}/ws/service/ws_echoer")
+            .parse()
+            .unwrap();
+        let connection = handle
+            .node_manager
+            .make_connection(
+                Arc::new(ctx.async_try_clone().await?),
+                &addr,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await?;
+        // The tcp port is used by the WebSocket connection, no tcp connection is made
+        assert!(connection.tcp_connection.is_none());
+
+        let route = connection
+            .route(handle.node_manager.tcp_transport())
+            .await?;
+        let reply: String = ctx.send_and_receive(route, "hello".to_string()).await?;
+        assert_eq!(reply, "hello");
+
+        ctx.stop().await
+    }
+}
@@ -2,18 +2,21 @@ use minicbor::{Decode, Encode};
 use tokio::task::JoinHandle;
 use tracing as log;
 
-use ockam::{LocalMessage, Route, TransportMessage, Worker};
+use ockam::{LocalMessage, TransportMessage, Worker};
 use ockam_core::compat::sync::{Arc, Mutex};
 use ockam_core::{
     route, Address, AllowAll, AsyncTryClone, Decodable, DenyAll, Encodable, Error, Routed, LOCAL,
 };
-use ockam_node::tokio::sync::mpsc;
+use ockam_node::tokio::sync::{mpsc, watch};
 use ockam_node::tokio::task::JoinSet;
 use ockam_node::tokio::time::{sleep, timeout, Duration};
 use ockam_node::Context;
 use ockam_node::{tokio, WorkerBuilder};
+use ockam_transport_tcp::{TcpConnectionEvent, TcpTransport};
 
-use crate::session::sessions::{Key, Ping, Session, Sessions, Status};
+use crate::nodes::connection::Connection;
+use crate::nodes::models::session::{SessionEvent, SessionStatus};
+use crate::session::sessions::{Key, Ping, ReplacedRoute, Session, Sessions, Status};
 use crate::DefaultAddress;
 
 pub(crate) mod sessions;
@@ -28,7 +31,7 @@ pub struct Medic {
     delay: Duration,
     sessions: Arc<Mutex<Sessions>>,
     pings: JoinSet<(Key, Result<(), Error>)>,
-    replacements: JoinSet<(Key, Result<Route, Error>)>,
+    replacements: JoinSet<(Key, Result<ReplacedRoute, Error>)>,
 }
 
 #[derive(Debug, Copy, Clone, Encode, Decode)]
@@ -62,25 +65,54 @@ impl Medic {
             .with_outgoing_access_control(DenyAll)
             .start(&ctx)
             .await?;
+        let (events_tx, events_rx) = mpsc::channel(32);
+        WorkerBuilder::new(ConnectionEvents(events_tx))
+            .with_address(ConnectionEvents::address())
+            .with_outgoing_access_control(DenyAll)
+            .start(&ctx)
+            .await?;
         let sessions = self.sessions.clone();
-        let handle = tokio::spawn(self.go(ctx, rx));
+        let handle = tokio::spawn(self.go(ctx, rx, events_rx));
         Ok((handle, sessions))
     }
 
     pub async fn stop(ctx: &Context) -> Result<(), Error> {
+        ctx.stop_worker(ConnectionEvents::address()).await?;
         ctx.stop_worker(Collector::address()).await
     }
 
+    /// Notify the medic when the TCP connection of a session drops and when it is
+    /// re-established, if that connection reconnects automatically.
+    ///
+    /// Return the address of the TCP connection to set on the session, see
+    /// [`Session::set_tcp_connection`].
+    pub(crate) fn watch_tcp_connection(
+        tcp: &TcpTransport,
+        connection: &Connection,
+    ) -> Option<Address> {
+        let address = connection.tcp_connection.as_ref()?.sender_address().clone();
+        // Only the connections created with reconnection options publish events
+        tcp.subscribe_to_connection_events(&address, ConnectionEvents::address())
+            .ok()
+            .map(|_| address)
+    }
+
     /// Continuously check all sessions.
     ///
     /// This method never returns. It will ping all healthy sessions and
     /// trigger replacements for the unhealthy ones.
-    async fn go(mut self, ctx: Context, mut rx: mpsc::Receiver<Message>) {
+    async fn go(
+        mut self,
+        ctx: Context,
+        mut rx: mpsc::Receiver<Message>,
+        mut events: mpsc::Receiver<TcpConnectionEvent>,
+    ) {
         let ctx = Arc::new(ctx);
         loop {
             log::trace!("check sessions");
             {
                 let mut sessions = self.sessions.lock().unwrap();
+                let mut degraded = Vec::new();
                 for (&key, session) in sessions.iter_mut() {
                     if session.pings().len() < MAX_FAILURES {
                         let m = Message::new(session.key());
@@ -125,7 +157,7 @@ impl Medic {
                             Status::Up | Status::Down => {
                                 log::warn!(%key, "session unresponsive");
                                 let f = session.replacement(session.ping_route().clone());
-                                session.set_status(Status::Degraded);
+                                degraded.push(key);
                                 log::info!(%key, "replacing session");
                                 let retry_delay = self.retry_delay;
                                 self.replacements.spawn(async move {
@@ -139,13 +171,20 @@ impl Medic {
                         }
                     }
                 }
+                for key in degraded {
+                    sessions.set_status(&key, Status::Degraded);
+                }
             }
 
-            let _ = timeout(self.delay, self.get_results(&mut rx)).await;
+            let _ = timeout(self.delay, self.get_results(&mut rx, &mut events)).await;
         }
     }
 
-    async fn get_results(&mut self, rx: &mut mpsc::Receiver<Message>) {
+    async fn get_results(
+        &mut self,
+        rx: &mut mpsc::Receiver<Message>,
+        events: &mut mpsc::Receiver<TcpConnectionEvent>,
+    ) {
         loop {
             tokio::select! {
                 p = self.pings.join_next(), if !self.pings.is_empty() => match p {
@@ -160,20 +199,20 @@ impl Medic {
                     Some(Ok((k, Err(e)))) => {
                         log::warn!(key = %k, err = %e, "replacing session failed");
                         let mut sessions = self.sessions.lock().unwrap();
-                        if let Some(s) = sessions.session_mut(&k) {
-                           s.set_status(Status::Down);
-                        }
+                        sessions.set_status(&k, Status::Down);
                     }
-                    Some(Ok((k, Ok(ping_route)))) => {
+                    Some(Ok((k, Ok(replaced)))) => {
                         let mut sessions = self.sessions.lock().unwrap();
                         if let Some(s) = sessions.session_mut(&k) {
-                            log::info!(key = %k, ping_route = %ping_route, "replacement is up");
-                            s.set_status(Status::Up);
-                            s.set_ping_address(ping_route);
+                            log::info!(key = %k, ping_route = %replaced.ping_route, "replacement is up");
+                            s.set_ping_address(replaced.ping_route);
+                            s.set_tcp_connection(replaced.tcp_connection);
                             s.clear_pings();
                         }
+                        sessions.set_status(&k, Status::Up);
                     }
                 },
+                Some(e) = events.recv() => self.handle_connection_event(e),
                 Some(m) = rx.recv() => {
                     if let Some(s) = self.sessions.lock().unwrap().session_mut(&m.key) {
                         if s.pings().contains(&m.ping) {
@@ -191,6 +230,38 @@ impl Medic {
     }
 }
 
+impl Medic {
+    /// Update the sessions using a TCP connection which dropped or was re-established
+    fn handle_connection_event(&mut self, event: TcpConnectionEvent) {
+        let mut sessions = self.sessions.lock().unwrap();
+        match event {
+            TcpConnectionEvent::Disconnected { address } => {
+                for key in sessions.keys_using_tcp_connection(&address) {
+                    log::info!(%key, connection = %address, "session connection dropped");
+                    if sessions.session(&key).map(|s| s.status()) == Some(Status::Up) {
+                        sessions.set_status(&key, Status::Down);
+                    }
+                }
+            }
+            TcpConnectionEvent::Reconnected { address, .. } => {
+                for key in sessions.keys_using_tcp_connection(&address) {
+                    let session = match sessions.session_mut(&key) {
+                        Some(session) if session.status() != Status::Degraded => session,
+                        _ => continue,
+                    };
+                    // The secure channels and workers using the connection are replaced
+                    // right away, without waiting for pings to fail
+                    log::info!(%key, connection = %address, "replacing session after reconnection");
+                    let f = session.replacement(session.ping_route().clone());
+                    self.replacements.spawn(async move { (key, f.await) });
+                    sessions.set_status(&key, Status::Degraded);
+                }
+            }
+            TcpConnectionEvent::Closed { .. } => {}
+        }
+    }
+}
+
 impl Message {
     fn new(k: Key) -> Self {
         Self {
@@ -243,6 +314,36 @@ impl Worker for Collector {
     }
 }
 
+/// Receives the events of the reconnecting TCP connections used by sessions
+/// and forwards them to the medic.
+#[derive(Debug)]
+struct ConnectionEvents(mpsc::Sender<TcpConnectionEvent>);
+
+impl ConnectionEvents {
+    const NAME: &'static str = "ockam.session.connection_events";
+
+    fn address() -> Address {
+        Address::new(LOCAL, Self::NAME)
+    }
+}
+
+#[ockam::worker]
+impl Worker for ConnectionEvents {
+    type Message = TcpConnectionEvent;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        _: &mut Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<(), Error> {
+        if self.0.send(msg.body()).await.is_err() {
+            log::debug!("connection events could not be sent to medic")
+        }
+        Ok(())
+    }
+}
+
 pub struct MedicHandle {
     handle: JoinHandle<()>,
     sessions: Arc<Mutex<Sessions>>,
@@ -271,6 +372,32 @@ impl MedicHandle {
         let mut sessions = self.sessions.lock().unwrap();
         sessions.add(session)
     }
+
+    /// Stop monitoring a session, and return it
+    pub fn remove_session(&self, key: &Key) -> Option<Session> {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.remove(key)
+    }
+
+    pub(crate) fn sessions(&self) -> Arc<Mutex<Sessions>> {
+        self.sessions.clone()
+    }
+
+    /// Return the status of the monitored sessions
+    pub fn session_statuses(&self) -> Vec<SessionStatus> {
+        self.sessions.lock().unwrap().statuses()
+    }
+
+    /// Return the status changes of the monitored sessions following the `after` sequence
+    /// number, and the sequence number of the last status change
+    pub fn session_events(&self, after: u64) -> (Vec<SessionEvent>, u64) {
+        self.sessions.lock().unwrap().events_after(after)
+    }
+
+    /// Return a receiver notified of each status change of the monitored sessions
+    pub fn subscribe_session_events(&self) -> watch::Receiver<u64> {
+        self.sessions.lock().unwrap().subscribe()
+    }
 }
 
 #[cfg(test)]
@@ -282,12 +409,13 @@ mod tests {
     use ockam::{route, Address, Context};
     use ockam_core::compat::sync::Arc;
     use ockam_core::{AsyncTryClone, Result};
+    use ockam_transport_tcp::TcpConnectionEvent;
 
     use crate::echoer::Echoer;
     use crate::hop::Hop;
-    use crate::session::sessions::Session;
     use crate::session::sessions::Status;
-    use crate::session::Medic;
+    use crate::session::sessions::{ReplacedRoute, Session};
+    use crate::session::{ConnectionEvents, Medic};
 
     #[ockam::test]
     async fn test_session_monitoring(ctx: &mut Context) -> Result<()> {
@@ -323,7 +451,7 @@ mod tests {
                         tokio::time::sleep(std::time::Duration::from_millis(100)).await;
                     }
                     // en empty route would do the trick, but a hop is more realistic
-                    Ok(route!["hop"])
+                    Ok(ReplacedRoute::new(route!["hop"]))
                 })
             }));
 
@@ -370,8 +498,74 @@ mod tests {
             continue;
         }
 
+        {
+            // The status changes are recorded as session events
+            let guard = sessions.lock().unwrap();
+            let (events, last) = guard.events_after(0);
+            assert_eq!(last, 2);
+            assert_eq!(events.len(), 2);
+            assert_eq!(events[0].status, "degraded");
+            assert_eq!(events[0].previous_status, "up");
+            assert_eq!(events[0].recovery_time_ms, None);
+            assert_eq!(events[1].status, "up");
+            assert!(events[1].recovery_time_ms.is_some());
+            assert!(guard.events_after(last).0.is_empty());
+        }
+
         // Shut down the test
         medic_task.abort();
         ctx.stop().await
     }
+
+    #[ockam::test]
+    async fn test_session_replaced_on_reconnection(ctx: &mut Context) -> Result<()> {
+        let (medic_task, sessions) = Medic::new().start(ctx.async_try_clone().await?).await?;
+        ctx.start_worker(Address::from_string("echo"), Echoer)
+            .await?;
+        ctx.start_worker(Address::from_string("hop"), Hop).await?;
+
+        let replacer_called = Arc::new(AtomicBool::new(false));
+        {
+            // The session is healthy, so it is only replaced because of its connection
+            let mut session = Session::new(route!["hop"]);
+            session.set_tcp_connection(Some(Address::from_string("tcp_connection")));
+            let replacer_called = replacer_called.clone();
+            session.set_replacer(Box::new(move |_| {
+                let replacer_called = replacer_called.clone();
+                Box::pin(async move {
+                    replacer_called.store(true, Ordering::Release);
+                    Ok(ReplacedRoute::new(route!["hop"])
+                        .with_tcp_connection(Some(Address::from_string("new_tcp_connection"))))
+                })
+            }));
+            sessions.lock().unwrap().add(session);
+        }
+
+        ctx.send(
+            route![ConnectionEvents::address()],
+            TcpConnectionEvent::Reconnected {
+                address: Address::from_string("tcp_connection"),
+                socket_address: "127.0.0.1:4000".to_string(),
+            },
+        )
+        .await?;
+
+        loop {
+            {
+                let guard = sessions.lock().unwrap();
+                let (_, session) = guard.iter().next().unwrap();
+                if replacer_called.load(Ordering::Acquire) && session.status() == Status::Up {
+                    assert_eq!(
+                        session.tcp_connection(),
+                        Some(&Address::from_string("new_tcp_connection"))
+                    );
+                    break;
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+
+        medic_task.abort();
+        ctx.stop().await
+    }
 }
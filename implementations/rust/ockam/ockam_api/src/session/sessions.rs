@@ -1,15 +1,20 @@
 use core::fmt;
 use core::future::Future;
 use core::pin::Pin;
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
 use minicbor::bytes::ByteArray;
 use minicbor::{Decode, Encode};
 use tracing as log;
 
+use ockam::identity::utils::now;
 use ockam_core::compat::collections::HashMap;
 use ockam_core::compat::rand;
-use ockam_core::{Error, Route};
+use ockam_core::{Address, Error, Route};
+use ockam_node::tokio::sync::watch;
+
+use crate::nodes::models::session::{SessionEvent, SessionStatus};
 
 //most sessions replacer are dependent on the node manager, if many session
 //fails concurrently, which is the common scenario we need extra time
@@ -17,18 +22,55 @@ use ockam_core::{Error, Route};
 pub const MAX_RECOVERY_TIME: Duration = Duration::from_secs(30);
 pub const MAX_CONNECT_TIME: Duration = Duration::from_secs(15);
 
-pub type Replacement = Pin<Box<dyn Future<Output = Result<Route, Error>> + Send>>;
+pub type Replacement = Pin<Box<dyn Future<Output = Result<ReplacedRoute, Error>> + Send>>;
 pub type Replacer = Box<dyn FnMut(Route) -> Replacement + Send>;
 
+/// Result of a session replacement
+#[derive(Debug, Clone)]
+pub struct ReplacedRoute {
+    /// Route used to ping the replaced session
+    pub ping_route: Route,
+    /// Address of the TCP connection of the replaced session, when it is
+    /// re-established automatically after it drops
+    pub tcp_connection: Option<Address>,
+}
+
+impl ReplacedRoute {
+    pub fn new(ping_route: Route) -> Self {
+        Self {
+            ping_route,
+            tcp_connection: None,
+        }
+    }
+
+    pub fn with_tcp_connection(mut self, tcp_connection: Option<Address>) -> Self {
+        self.tcp_connection = tcp_connection;
+        self
+    }
+}
+
+/// Number of status changes kept for the subscribers of session events
+const MAX_EVENTS: usize = 100;
+
 #[derive(Debug)]
 pub struct Sessions {
     map: HashMap<Key, Session>,
+    events: VecDeque<SessionEvent>,
+    last_event: watch::Sender<u64>,
 }
 
 pub struct Session {
     key: Key,
+    kind: String,
+    name: String,
     ping_route: Route,
+    /// Reconnecting TCP connection used by the session, if any
+    tcp_connection: Option<Address>,
     status: Status,
+    status_since: u64,
+    /// Time at which the session stopped being up
+    down_since: Option<Instant>,
+    last_recovery_time: Option<Duration>,
     replace: Replacer,
     pings: Vec<Ping>,
 }
@@ -40,12 +82,26 @@ pub enum Status {
     Up,
 }
 
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Down => f.write_str("down"),
+            Status::Degraded => f.write_str("degraded"),
+            Status::Up => f.write_str("up"),
+        }
+    }
+}
+
 impl fmt::Debug for Session {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Session")
             .field("key", &self.key)
+            .field("kind", &self.kind)
+            .field("name", &self.name)
             .field("ping_route", &self.ping_route)
+            .field("tcp_connection", &self.tcp_connection)
             .field("status", &self.status)
+            .field("status_since", &self.status_since)
             .field("pings", &self.pings)
             .finish()
     }
@@ -55,6 +111,8 @@ impl Sessions {
     pub fn new() -> Self {
         Self {
             map: HashMap::new(),
+            events: VecDeque::new(),
+            last_event: watch::channel(0).0,
         }
     }
 
@@ -70,6 +128,18 @@ impl Sessions {
         k
     }
 
+    pub fn remove(&mut self, k: &Key) -> Option<Session> {
+        let s = self.map.remove(k);
+        if s.is_some() {
+            log::debug! {
+                target: "ockam_api::session",
+                key = %k,
+                "session removed"
+            }
+        }
+        s
+    }
+
     #[allow(unused)]
     pub fn session(&self, k: &Key) -> Option<&Session> {
         self.map.get(k)
@@ -79,6 +149,15 @@ impl Sessions {
         self.map.get_mut(k)
     }
 
+    /// Return the keys of the sessions using the given TCP connection
+    pub fn keys_using_tcp_connection(&self, tcp_connection: &Address) -> Vec<Key> {
+        self.map
+            .values()
+            .filter(|s| s.tcp_connection.as_ref() == Some(tcp_connection))
+            .map(|s| s.key)
+            .collect()
+    }
+
     #[allow(unused)]
     pub fn iter(&self) -> impl Iterator<Item = (&Key, &Session)> + '_ {
         self.map.iter()
@@ -87,15 +166,83 @@ impl Sessions {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Key, &mut Session)> + '_ {
         self.map.iter_mut()
     }
+
+    /// Change the status of a session and record the change as a session event
+    pub fn set_status(&mut self, k: &Key, status: Status) {
+        let s = match self.map.get_mut(k) {
+            Some(s) => s,
+            None => return,
+        };
+        let previous_status = s.status;
+        if previous_status == status {
+            return;
+        }
+        s.set_status(status);
+        log::info! {
+            target: "ockam_api::session",
+            key = %k,
+            from = %previous_status,
+            to = %status,
+            "session status changed"
+        }
+        let seq = *self.last_event.borrow() + 1;
+        let event = SessionEvent {
+            seq,
+            key: k.to_string(),
+            kind: s.kind.clone(),
+            name: s.name.clone(),
+            status: status.to_string(),
+            previous_status: previous_status.to_string(),
+            at: s.status_since,
+            recovery_time_ms: if status == Status::Up {
+                s.last_recovery_time.map(|d| d.as_millis() as u64)
+            } else {
+                None
+            },
+        };
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+        self.last_event.send_replace(seq);
+    }
+
+    /// Return the status of all the sessions
+    pub fn statuses(&self) -> Vec<SessionStatus> {
+        self.map.values().map(|s| s.session_status()).collect()
+    }
+
+    /// Return the recorded session events which are more recent than the `after` sequence
+    /// number, together with the sequence number of the last event
+    pub fn events_after(&self, after: u64) -> (Vec<SessionEvent>, u64) {
+        let events = self
+            .events
+            .iter()
+            .filter(|e| e.seq > after)
+            .cloned()
+            .collect();
+        (events, *self.last_event.borrow())
+    }
+
+    /// Return a receiver notified with the sequence number of each new session event
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.last_event.subscribe()
+    }
 }
 
 impl Session {
     pub fn new(ping_route: Route) -> Self {
         Self {
             key: Key::new(),
+            kind: String::new(),
+            name: String::new(),
             ping_route,
+            tcp_connection: None,
             status: Status::Up,
-            replace: Box::new(move |r| Box::pin(async move { Ok(r) })),
+            status_since: unix_time(),
+            down_since: None,
+            last_recovery_time: None,
+            replace: Box::new(move |r| Box::pin(async move { Ok(ReplacedRoute::new(r)) })),
             pings: Vec::new(),
         }
     }
@@ -104,6 +251,13 @@ impl Session {
         self.key
     }
 
+    /// Set the kind and name of the resource using this session, for example
+    /// `tcp-inlet` and the alias of an inlet
+    pub fn set_resource(&mut self, kind: impl Into<String>, name: impl Into<String>) {
+        self.kind = kind.into();
+        self.name = name.into();
+    }
+
     pub fn ping_route(&self) -> &Route {
         &self.ping_route
     }
@@ -112,12 +266,48 @@ impl Session {
         self.ping_route = ping_route;
     }
 
+    pub fn tcp_connection(&self) -> Option<&Address> {
+        self.tcp_connection.as_ref()
+    }
+
+    /// Set the TCP connection used by the session, when it is re-established
+    /// automatically after it drops. The session is then replaced as soon as
+    /// the connection is re-established
+    pub fn set_tcp_connection(&mut self, tcp_connection: Option<Address>) {
+        self.tcp_connection = tcp_connection;
+    }
+
     pub fn status(&self) -> Status {
         self.status
     }
 
     pub fn set_status(&mut self, s: Status) {
-        self.status = s
+        if self.status == s {
+            return;
+        }
+        match s {
+            Status::Up => {
+                self.last_recovery_time = self.down_since.take().map(|t| t.elapsed());
+            }
+            Status::Degraded | Status::Down => {
+                self.down_since.get_or_insert_with(Instant::now);
+            }
+        }
+        self.status = s;
+        self.status_since = unix_time();
+    }
+
+    pub fn session_status(&self) -> SessionStatus {
+        SessionStatus {
+            key: self.key.to_string(),
+            kind: self.kind.clone(),
+            name: self.name.clone(),
+            ping_route: self.ping_route.to_string(),
+            status: self.status.to_string(),
+            status_since: self.status_since,
+            pending_pings: self.pings.len() as u64,
+            last_recovery_time_ms: self.last_recovery_time.map(|d| d.as_millis() as u64),
+        }
     }
 
     pub fn replacement(&mut self, ping_route: Route) -> Replacement {
@@ -141,6 +331,10 @@ impl Session {
     }
 }
 
+fn unix_time() -> u64 {
+    now().map(|t| t.0).unwrap_or_default()
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Encode, Decode)]
 #[rustfmt::skip]
 pub struct Key(#[n(0)] ByteArray<24>);
@@ -1,4 +1,5 @@
 use miette::miette;
+use std::iter::Peekable;
 use std::net::{SocketAddrV4, SocketAddrV6};
 
 use ockam::TcpTransport;
@@ -6,10 +7,13 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{Address, Error, Result, Route, TransportType, LOCAL};
 use ockam_multiaddr::proto::{
-    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Worker,
+    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Unix, Worker, Ws,
 };
-use ockam_multiaddr::{Code, MultiAddr, Protocol};
-use ockam_transport_tcp::{TcpConnection, TcpConnectionOptions, TCP};
+use ockam_multiaddr::{Code, MultiAddr, ProtoValue, Protocol};
+use ockam_transport_tcp::{TcpConnection, TcpConnectionOptions, TcpReconnectOptions, TCP};
+#[cfg(unix)]
+use ockam_transport_uds::UDS;
+use ockam_transport_websocket::WS;
 
 use crate::error::ApiError;
 
@@ -52,7 +56,7 @@ pub fn local_multiaddr_to_route(ma: &MultiAddr) -> Result<Route> {
                 ))
             }
 
-            code @ (Ip4::CODE | Ip6::CODE | DnsAddr::CODE) => {
+            code @ (Ip4::CODE | Ip6::CODE | DnsAddr::CODE | Unix::CODE) => {
                 return Err(Error::new(
                     Origin::Api,
                     Kind::Invalid,
@@ -83,26 +87,45 @@ pub struct MultiAddrToRouteResult {
 pub async fn multiaddr_to_route(
     ma: &MultiAddr,
     tcp: &TcpTransport,
+) -> Option<MultiAddrToRouteResult> {
+    multiaddr_to_reconnecting_route(ma, tcp, None).await
+}
+
+/// Convert a multi-address to an Ockam route, creating the TCP connection it contains, if any.
+///
+/// When reconnection options are given, the TCP connection is re-established when it drops
+/// and keeps the same sender address, see [`TcpConnectionOptions::with_reconnect`].
+pub async fn multiaddr_to_reconnecting_route(
+    ma: &MultiAddr,
+    tcp: &TcpTransport,
+    reconnect: Option<TcpReconnectOptions>,
 ) -> Option<MultiAddrToRouteResult> {
     let mut rb = Route::new();
     let mut it = ma.iter().peekable();
 
     let mut flow_control_id = None;
-    let mut number_of_tcp_hops = 0;
+    let mut number_of_transport_hops = 0;
     let mut tcp_connection = None;
 
     while let Some(p) = it.next() {
         match p.code() {
             Ip4::CODE => {
-                if number_of_tcp_hops >= 1 {
-                    return None; // Only 1 TCP hop is allowed
+                if number_of_transport_hops >= 1 {
+                    return None; // Only 1 transport hop is allowed
                 }
 
                 let ip4 = p.cast::<Ip4>()?;
                 let port = it.next()?.cast::<Tcp>()?;
                 let socket_addr = SocketAddrV4::new(*ip4, *port);
 
-                let options = TcpConnectionOptions::new();
+                // WebSocket connections are established lazily by the WebSocket router
+                if it.next_if(|p| p.code() == Ws::CODE).is_some() {
+                    number_of_transport_hops += 1;
+                    rb = rb.append(Address::new(WS, socket_addr.to_string()));
+                    continue;
+                }
+
+                let options = tcp_connection_options(&reconnect);
                 flow_control_id = Some(options.flow_control_id().clone());
 
                 let connection = match tcp.connect(socket_addr.to_string(), options).await {
@@ -113,21 +136,27 @@ pub async fn multiaddr_to_route(
                     }
                 };
 
-                number_of_tcp_hops += 1;
+                number_of_transport_hops += 1;
                 rb = rb.append(connection.sender_address().clone());
 
                 tcp_connection = Some(connection);
             }
             Ip6::CODE => {
-                if number_of_tcp_hops >= 1 {
-                    return None; // Only 1 TCP hop is allowed
+                if number_of_transport_hops >= 1 {
+                    return None; // Only 1 transport hop is allowed
                 }
 
                 let ip6 = p.cast::<Ip6>()?;
                 let port = it.next()?.cast::<Tcp>()?;
                 let socket_addr = SocketAddrV6::new(*ip6, *port, 0, 0);
 
-                let options = TcpConnectionOptions::new();
+                if it.next_if(|p| p.code() == Ws::CODE).is_some() {
+                    number_of_transport_hops += 1;
+                    rb = rb.append(Address::new(WS, socket_addr.to_string()));
+                    continue;
+                }
+
+                let options = tcp_connection_options(&reconnect);
                 flow_control_id = Some(options.flow_control_id().clone());
 
                 let connection = match tcp.connect(socket_addr.to_string(), options).await {
@@ -138,24 +167,31 @@ pub async fn multiaddr_to_route(
                     }
                 };
 
-                number_of_tcp_hops += 1;
+                number_of_transport_hops += 1;
                 rb = rb.append(connection.sender_address().clone());
 
                 tcp_connection = Some(connection);
             }
             DnsAddr::CODE => {
-                if number_of_tcp_hops >= 1 {
-                    return None; // Only 1 TCP hop is allowed
+                if number_of_transport_hops >= 1 {
+                    return None; // Only 1 transport hop is allowed
                 }
 
                 let host = p.cast::<DnsAddr>()?;
                 if let Some(p) = it.peek() {
                     if p.code() == Tcp::CODE {
                         let port = p.cast::<Tcp>()?;
+                        let peer = format!("{}:{}", &*host, *port);
+                        let _ = it.next();
+
+                        if it.next_if(|p| p.code() == Ws::CODE).is_some() {
+                            number_of_transport_hops += 1;
+                            rb = rb.append(Address::new(WS, peer));
+                            continue;
+                        }
 
-                        let options = TcpConnectionOptions::new();
+                        let options = tcp_connection_options(&reconnect);
                         flow_control_id = Some(options.flow_control_id().clone());
-                        let peer = format!("{}:{}", &*host, *port);
 
                         let connection = match tcp.connect(&peer, options).await {
                             Ok(c) => c,
@@ -165,17 +201,26 @@ pub async fn multiaddr_to_route(
                             }
                         };
 
-                        number_of_tcp_hops += 1;
+                        number_of_transport_hops += 1;
                         rb = rb.append(connection.sender_address().clone());
 
                         tcp_connection = Some(connection);
 
-                        let _ = it.next();
-
                         continue;
                     }
                 }
             }
+            #[cfg(unix)]
+            Unix::CODE => {
+                if number_of_transport_hops >= 1 {
+                    return None; // Only 1 transport hop is allowed
+                }
+
+                // Unix domain socket connections are established lazily by the UDS router
+                let path = p.cast::<Unix>()?;
+                number_of_transport_hops += 1;
+                rb = rb.append(Address::new(UDS, &*path));
+            }
             Worker::CODE => {
                 let local = p.cast::<Worker>()?;
                 rb = rb.append(Address::new(LOCAL, &*local))
@@ -202,6 +247,13 @@ pub async fn multiaddr_to_route(
     })
 }
 
+fn tcp_connection_options(reconnect: &Option<TcpReconnectOptions>) -> TcpConnectionOptions {
+    match reconnect {
+        Some(reconnect) => TcpConnectionOptions::new().with_reconnect(reconnect.clone()),
+        None => TcpConnectionOptions::new(),
+    }
+}
+
 /// Resolve all the multiaddresses which represent transport addresses
 /// For example /tcp/127.0.0.1/port/4000 is transformed to the Address (TCP, "127.0.0.1:4000")
 /// The creation of a TCP worker and the substitution of that transport address to a worker address
@@ -216,13 +268,19 @@ pub fn multiaddr_to_transport_route(ma: &MultiAddr) -> Option<Route> {
                 let ip4 = p.cast::<Ip4>()?;
                 let port = it.next()?.cast::<Tcp>()?;
                 let socket_addr = SocketAddrV4::new(*ip4, *port);
-                route = route.append(Address::new(TCP, socket_addr.to_string()))
+                route = route.append(Address::new(
+                    transport_type(&mut it),
+                    socket_addr.to_string(),
+                ))
             }
             Ip6::CODE => {
                 let ip6 = p.cast::<Ip6>()?;
                 let port = it.next()?.cast::<Tcp>()?;
                 let socket_addr = SocketAddrV6::new(*ip6, *port, 0, 0);
-                route = route.append(Address::new(TransportType::new(1), socket_addr.to_string()))
+                route = route.append(Address::new(
+                    transport_type(&mut it),
+                    socket_addr.to_string(),
+                ))
             }
             DnsAddr::CODE => {
                 let host = p.cast::<DnsAddr>()?;
@@ -230,12 +288,17 @@ pub fn multiaddr_to_transport_route(ma: &MultiAddr) -> Option<Route> {
                     if p.code() == Tcp::CODE {
                         let port = p.cast::<Tcp>()?;
                         let addr = format!("{}:{}", &*host, *port);
-                        route = route.append(Address::new(TransportType::new(1), addr));
                         let _ = it.next();
+                        route = route.append(Address::new(transport_type(&mut it), addr));
                         continue;
                     }
                 }
             }
+            #[cfg(unix)]
+            Unix::CODE => {
+                let path = p.cast::<Unix>()?;
+                route = route.append(Address::new(UDS, &*path))
+            }
             Worker::CODE => {
                 let local = p.cast::<Worker>()?;
                 route = route.append(Address::new(LOCAL, &*local))
@@ -257,6 +320,16 @@ pub fn multiaddr_to_transport_route(ma: &MultiAddr) -> Option<Route> {
     Some(route.into())
 }
 
+/// Return the transport used over a tcp port: WebSocket when the port is followed
+/// by `/ws`, which is then consumed, and TCP otherwise
+fn transport_type<'a>(it: &mut Peekable<impl Iterator<Item = ProtoValue<'a>>>) -> TransportType {
+    if it.next_if(|p| p.code() == Ws::CODE).is_some() {
+        WS
+    } else {
+        TCP
+    }
+}
+
 /// Try to convert a multiaddr to an Ockam Address
 pub fn multiaddr_to_addr(ma: &MultiAddr) -> Option<Address> {
     let mut it = ma.iter().peekable();
@@ -336,6 +409,10 @@ pub fn is_local_node(ma: &MultiAddr) -> miette::Result<bool> {
                     .map(|ip4| ip4.is_loopback())
                     .ok_or_else(|| miette!("Invalid \"ip4\" value"))?;
             }
+            // A "/unix" socket is always on the local host
+            Unix::CODE => {
+                at_rust_node = true;
+            }
             // A "/ip6" will be local if it matches the loopback address
             Ip6::CODE => {
                 at_rust_node = p
@@ -364,6 +441,8 @@ pub fn local_worker(code: &Code) -> Result<bool> {
         | Ip4::CODE
         | Ip6::CODE
         | Tcp::CODE
+        | Ws::CODE
+        | Unix::CODE
         | Secure::CODE => Ok(false),
         Worker::CODE | Service::CODE => Ok(true),
 
@@ -375,25 +454,33 @@ pub fn local_worker(code: &Code) -> Result<bool> {
 pub mod test_utils {
     use ockam::identity::storage::InMemoryStorage;
     use ockam::identity::utils::AttributesBuilder;
+    use ockam::identity::{
+        CredentialSchemas, SecureChannels, PROJECT_MEMBER_SCHEMA, TRUST_CONTEXT_ID,
+    };
     use ockam::identity::{Identifier, Identity, MAX_CREDENTIAL_VALIDITY};
-    use ockam::identity::{SecureChannels, PROJECT_MEMBER_SCHEMA, TRUST_CONTEXT_ID};
     use ockam::Result;
     use ockam_core::compat::sync::Arc;
     use ockam_core::flow_control::FlowControls;
-    use ockam_core::AsyncTryClone;
+    use ockam_core::{Address, AsyncTryClone};
 
     use ockam_node::Context;
     use ockam_transport_tcp::TcpTransport;
+    #[cfg(unix)]
+    use ockam_transport_uds::UdsTransport;
+    use ockam_transport_websocket::WebSocketTransport;
+    use std::time::Duration;
 
     use crate::cli_state::{
         random_name, traits::*, CliState, IdentityConfig, NodeConfig, VaultConfig,
     };
     use crate::config::cli::{CredentialRetrieverConfig, TrustAuthorityConfig, TrustContextConfig};
+    use crate::nodes::service::resources::restore_resources;
     use crate::nodes::service::{
         NodeManagerGeneralOptions, NodeManagerTransportOptions, NodeManagerTrustOptions,
     };
     use crate::nodes::InMemoryNode;
     use crate::nodes::{NodeManagerWorker, NODEMANAGER_ADDR};
+    use crate::DefaultAddress;
 
     /// This struct is used by tests, it has two responsibilities:
     /// - guard to delete the cli state at the end of the test, the cli state
@@ -406,6 +493,72 @@ pub mod test_utils {
         pub tcp: TcpTransport,
         pub secure_channels: Arc<SecureChannels>,
         pub identifier: Identifier,
+        node_name: String,
+        trust_context_config: TrustContextConfig,
+        web_socket_transport: WebSocketTransport,
+        #[cfg(unix)]
+        uds_transport: UdsTransport,
+    }
+
+    impl NodeManagerHandle {
+        /// Stop the node manager, then start it again with the same node state and
+        /// recreate the persisted resources, as when a node is restarted.
+        /// The resources which were created on the node must be stopped beforehand
+        pub async fn restart(&mut self, context: &Context) -> Result<()> {
+            let _ = self.node_manager.stop(context).await;
+            let addresses: Vec<Address> = DefaultAddress::iter()
+                .chain([NODEMANAGER_ADDR])
+                .map(Address::from)
+                .collect();
+            for address in &addresses {
+                let _ = context.stop_worker(address.clone()).await;
+            }
+            while context
+                .list_workers()
+                .await?
+                .iter()
+                .any(|address| addresses.contains(address))
+            {
+                context.sleep(Duration::from_millis(10)).await;
+            }
+
+            // The transports can't be registered twice on the same node, the new
+            // node manager uses new handles on the existing ones
+            let transport_options = NodeManagerTransportOptions::new(
+                FlowControls::generate_flow_control_id(),
+                self.tcp.async_try_clone().await?,
+            )
+            .with_web_socket_transport(self.web_socket_transport.async_try_clone().await?);
+            #[cfg(unix)]
+            let transport_options =
+                transport_options.with_uds_transport(self.uds_transport.async_try_clone().await?);
+            let node_manager = Arc::new(
+                InMemoryNode::new(
+                    context,
+                    NodeManagerGeneralOptions::new(
+                        self.cli_state.clone(),
+                        self.node_name.clone(),
+                        None,
+                        true,
+                        true,
+                    ),
+                    transport_options,
+                    NodeManagerTrustOptions::new(Some(self.trust_context_config.clone())),
+                )
+                .await?,
+            );
+            context
+                .start_worker(
+                    NODEMANAGER_ADDR,
+                    NodeManagerWorker::new(node_manager.clone()),
+                )
+                .await?;
+            self.secure_channels = node_manager.secure_channels.clone();
+            self.node_manager = node_manager;
+
+            let node_state = self.cli_state.nodes.get(&self.node_name)?;
+            restore_resources(context, &node_state).await
+        }
     }
 
     impl Drop for NodeManagerHandle {
@@ -420,6 +573,53 @@ pub mod test_utils {
     /// things *will* break.
     // #[must_use] make sense to enable only on rust 1.67+
     pub async fn start_manager_for_tests(context: &mut Context) -> Result<NodeManagerHandle> {
+        NodeManagerTestOptions::new().start(context).await
+    }
+
+    /// Options of the node manager started by a test
+    #[derive(Default)]
+    pub struct NodeManagerTestOptions {
+        credential_schemas: CredentialSchemas,
+        relay_ttl: Option<Duration>,
+        persistent: bool,
+    }
+
+    impl NodeManagerTestOptions {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Validate the credentials verified by the node manager with the given schemas
+        pub fn with_credential_schemas(mut self, credential_schemas: CredentialSchemas) -> Self {
+            self.credential_schemas = credential_schemas;
+            self
+        }
+
+        /// Evict the relays registered at the relay service of the node manager when they
+        /// stop sending heartbeats for longer than the given duration
+        pub fn with_relay_ttl(mut self, relay_ttl: Duration) -> Self {
+            self.relay_ttl = Some(relay_ttl);
+            self
+        }
+
+        /// Persist the resources created through the API of the node manager, so that they
+        /// are recreated when the node manager is restarted with [`NodeManagerHandle::restart`]
+        pub fn persistent(mut self) -> Self {
+            self.persistent = true;
+            self
+        }
+
+        /// Starts a local node manager with these options and returns a handle to it,
+        /// see [`start_manager_for_tests`]
+        pub async fn start(self, context: &mut Context) -> Result<NodeManagerHandle> {
+            start_manager(context, self).await
+        }
+    }
+
+    async fn start_manager(
+        context: &mut Context,
+        options: NodeManagerTestOptions,
+    ) -> Result<NodeManagerHandle> {
         let tcp = TcpTransport::create(context).await?;
         let cli_state = CliState::test()?;
 
@@ -473,22 +673,40 @@ pub mod test_utils {
         let node_config = NodeConfig::try_from(&cli_state).unwrap();
         cli_state.nodes.create(&node_name, node_config)?;
 
+        let web_socket_transport = WebSocketTransport::create(context).await?;
+        let transport_options = NodeManagerTransportOptions::new(
+            FlowControls::generate_flow_control_id(), // FIXME
+            tcp.async_try_clone().await?,
+        )
+        .with_web_socket_transport(web_socket_transport.async_try_clone().await?);
+        #[cfg(unix)]
+        let uds_transport = UdsTransport::create(context).await?;
+        #[cfg(unix)]
+        let transport_options =
+            transport_options.with_uds_transport(uds_transport.async_try_clone().await?);
+
+        let trust_context_config = TrustContextConfig::new(
+            "test_trust_context".to_string(),
+            Some(TrustAuthorityConfig::new(
+                hex::encode(&identity.export().unwrap()),
+                Some(CredentialRetrieverConfig::FromMemory(minicbor::to_vec(
+                    &credential,
+                )?)),
+            )),
+        );
         let node_manager = InMemoryNode::new(
             context,
-            NodeManagerGeneralOptions::new(cli_state.clone(), node_name, None, true, false),
-            NodeManagerTransportOptions::new(
-                FlowControls::generate_flow_control_id(), // FIXME
-                tcp.async_try_clone().await?,
-            ),
-            NodeManagerTrustOptions::new(Some(TrustContextConfig::new(
-                "test_trust_context".to_string(),
-                Some(TrustAuthorityConfig::new(
-                    hex::encode(&identity.export().unwrap()),
-                    Some(CredentialRetrieverConfig::FromMemory(minicbor::to_vec(
-                        &credential,
-                    )?)),
-                )),
-            ))),
+            NodeManagerGeneralOptions::new(
+                cli_state.clone(),
+                node_name.clone(),
+                None,
+                true,
+                options.persistent,
+            )
+            .with_relay_ttl(options.relay_ttl),
+            transport_options,
+            NodeManagerTrustOptions::new(Some(trust_context_config.clone()))
+                .with_credential_schemas(options.credential_schemas),
         )
         .await?;
         let node_manager = Arc::new(node_manager);
@@ -512,6 +730,11 @@ pub mod test_utils {
             tcp: tcp.async_try_clone().await?,
             secure_channels: secure_channels.clone(),
             identifier: identity.identifier().clone(),
+            node_name,
+            trust_context_config,
+            web_socket_transport,
+            #[cfg(unix)]
+            uds_transport,
         })
     }
 
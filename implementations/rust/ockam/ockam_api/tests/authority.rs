@@ -348,6 +348,9 @@ async fn default_configuration() -> Result<Configuration> {
         no_direct_authentication: true,
         no_token_enrollment: true,
         okta: None,
+        credential_schemas: vec![],
+        delegation: None,
+        selective_disclosure: false,
     };
 
     // Hack to create Authority Identity using the same vault and storage
@@ -1,11 +1,11 @@
 use crate::node::util::run_ockam;
-use crate::util::{embedded_node_that_is_not_stopped, exitcode};
+use crate::util::{embedded_node_that_is_not_stopped, exitcode, read_credential_schemas};
 use crate::util::{local_cmd, node_rpc};
 use crate::{docs, identity, CommandGlobalOpts, Result};
 use clap::{ArgGroup, Args};
 use miette::Context as _;
 use miette::{miette, IntoDiagnostic};
-use ockam::identity::{AttributesEntry, Identifier};
+use ockam::identity::{AttributesEntry, CredentialSchema, Identifier};
 use ockam::Context;
 use ockam_api::authority_node;
 use ockam_api::authority_node::{OktaConfiguration, TrustedIdentity};
@@ -88,6 +88,21 @@ pub struct CreateCommand {
     #[arg(long, value_name = "ATTRIBUTE_NAMES", default_value = None)]
     attributes: Option<Vec<String>>,
 
+    /// Path of a JSON file declaring the types and constraints of the attributes of the
+    /// credentials issued with a given schema identifier. Can be repeated
+    #[arg(long = "credential-schema", value_name = "PATH")]
+    credential_schemas: Vec<PathBuf>,
+
+    /// Hex encoded delegation credential, created with `ockam credential delegate`, to issue
+    /// credentials as a sub-authority of another authority
+    #[arg(long, value_name = "DELEGATION")]
+    delegation: Option<String>,
+
+    /// Issue credentials where each attribute is committed individually, so that project
+    /// members can present only the attributes needed by each node
+    #[arg(long, value_name = "BOOL", default_value_t = false)]
+    selective_disclosure: bool,
+
     /// Run the node in foreground.
     #[arg(long, short, value_name = "BOOL", default_value_t = false)]
     foreground: bool,
@@ -176,6 +191,20 @@ async fn spawn_background_node(
         });
     }
 
+    for credential_schema in &cmd.credential_schemas {
+        args.push("--credential-schema".to_string());
+        args.push(credential_schema.to_string_lossy().to_string());
+    }
+
+    if let Some(delegation) = &cmd.delegation {
+        args.push("--delegation".to_string());
+        args.push(delegation.clone());
+    }
+
+    if cmd.selective_disclosure {
+        args.push("--selective-disclosure".to_string());
+    }
+
     if let Some(vault) = &cmd.vault {
         args.push("--vault".to_string());
         args.push(vault.clone());
@@ -227,6 +256,11 @@ impl CreateCommand {
         }
     }
 
+    /// Return the credential schemas declared in the schema files
+    pub(crate) fn credential_schemas(&self) -> Result<Vec<CredentialSchema>> {
+        read_credential_schemas(&self.credential_schemas)
+    }
+
     pub fn logging_to_file(&self) -> bool {
         // Background nodes will spawn a foreground node in a child process.
         // In that case, the child process will log to files.
@@ -329,6 +363,7 @@ async fn start_authority_node(
     )?;
 
     let trusted_identities = cmd.trusted_identities(&identifier)?;
+    let credential_schemas = cmd.credential_schemas()?;
 
     let configuration = authority_node::Configuration {
         identifier,
@@ -342,6 +377,9 @@ async fn start_authority_node(
         no_direct_authentication: cmd.no_direct_authentication,
         no_token_enrollment: cmd.no_token_enrollment,
         okta: okta_configuration,
+        credential_schemas,
+        delegation: cmd.delegation,
+        selective_disclosure: cmd.selective_disclosure,
     };
     authority_node::start_node(&ctx, &configuration)
         .await
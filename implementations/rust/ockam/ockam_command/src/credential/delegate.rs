@@ -0,0 +1,115 @@
+use crate::credential::{delegated_ttl, delegation_root, parse_delegation};
+use crate::identity::{get_identity_name, initialize_identity_if_default};
+use crate::output::{CredentialAndPurposeKeyDisplay, EncodeFormat};
+use crate::{
+    util::{node_rpc, parsers::identity_identifier_parser},
+    vault::default_vault_name,
+    CommandGlobalOpts,
+};
+use clap::Args;
+use miette::IntoDiagnostic;
+use ockam::identity::models::{CredentialAndPurposeKey, DelegationConstraints};
+use ockam::identity::Identifier;
+use ockam::identity::TRUST_CONTEXT_ID;
+use ockam::Context;
+use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
+use std::time::Duration;
+
+/// Issue a delegation credential, allowing an identity to issue credentials as a sub-authority
+#[derive(Clone, Debug, Args)]
+pub struct DelegateCommand {
+    #[arg(long = "as")]
+    pub as_identity: Option<String>,
+
+    /// Identifier of the sub-authority
+    #[arg(long = "for", value_name = "IDENTIFIER", value_parser = identity_identifier_parser)]
+    pub identity_identifier: Identifier,
+
+    /// Attributes that the sub-authority can attest, in `key` format to allow any value,
+    /// or in `key=prefix` format to only allow values starting with a prefix.
+    /// The `trust_context_id` of the root authority is always allowed
+    #[arg(short, long = "attribute", value_name = "ATTRIBUTE")]
+    pub attributes: Vec<String>,
+
+    /// Maximum validity duration, in seconds, of the credentials issued by the sub-authority.
+    /// By default, the longest duration allowed to the issuing identity
+    #[arg(long, value_name = "SECONDS")]
+    pub max_ttl: Option<u64>,
+
+    /// Number of further levels of sub-authorities that the sub-authority can delegate to
+    #[arg(long, value_name = "DEPTH", default_value_t = 0)]
+    pub max_depth: u8,
+
+    /// Hex encoded delegation of the issuing identity, when it is itself a sub-authority
+    #[arg(long, value_name = "DELEGATION", value_parser = parse_delegation)]
+    pub delegation: Option<CredentialAndPurposeKey>,
+
+    #[arg()]
+    pub vault: Option<String>,
+
+    /// Encoding Format
+    #[arg(long = "encoding", value_enum, default_value = "plain")]
+    encode_format: EncodeFormat,
+}
+
+impl DelegateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_identity_if_default(&opts, &self.as_identity);
+        node_rpc(run_impl, (opts, self));
+    }
+
+    fn constraints(&self, root: &Identifier, ttl: Duration) -> DelegationConstraints {
+        let max_ttl = self.max_ttl.map_or(ttl, Duration::from_secs);
+        // The credentials issued by sub-authorities belong to the trust context of the root authority
+        let mut constraints = DelegationConstraints::new(max_ttl)
+            .with_attribute_value(TRUST_CONTEXT_ID.to_vec(), root.to_string())
+            .with_max_depth(self.max_depth);
+        for attr in &self.attributes {
+            constraints = match attr.split_once('=') {
+                Some((key, prefix)) => constraints.with_attribute_prefix(key, prefix),
+                None => constraints.with_attribute(attr.as_str()),
+            };
+        }
+        constraints
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, DelegateCommand),
+) -> miette::Result<()> {
+    let identity_name = get_identity_name(&opts.state, &cmd.as_identity);
+    let ident_state = opts.state.identities.get(&identity_name)?;
+    let issuer = ident_state.identifier();
+
+    let vault_name = cmd
+        .vault
+        .clone()
+        .unwrap_or_else(|| default_vault_name(&opts.state));
+    let vault = opts.state.vaults.get(&vault_name)?.get().await?;
+    let identities = opts.state.get_identities(vault).await?;
+
+    let root = match &cmd.delegation {
+        Some(delegation) => delegation_root(delegation)?,
+        None => issuer.clone(),
+    };
+
+    let ttl = delegated_ttl(cmd.delegation.as_ref())?;
+    let delegation = identities
+        .credentials()
+        .credentials_creation()
+        .issue_delegation(
+            &issuer,
+            &cmd.identity_identifier,
+            cmd.constraints(&root, ttl),
+            ttl,
+            cmd.delegation.as_ref(),
+        )
+        .await
+        .into_diagnostic()?;
+
+    cmd.encode_format
+        .println_value(&CredentialAndPurposeKeyDisplay(delegation))?;
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use ockam_core::compat::collections::HashMap;
 
+use crate::credential::{delegated_ttl, delegation_root, parse_delegation};
 use crate::identity::{get_identity_name, initialize_identity_if_default};
 use crate::{
     util::{node_rpc, parsers::identity_identifier_parser},
@@ -10,6 +11,7 @@ use clap::Args;
 
 use crate::output::{CredentialAndPurposeKeyDisplay, EncodeFormat};
 use miette::{miette, IntoDiagnostic};
+use ockam::identity::models::CredentialAndPurposeKey;
 use ockam::identity::utils::AttributesBuilder;
 use ockam::identity::Identifier;
 use ockam::identity::{MAX_CREDENTIAL_VALIDITY, PROJECT_MEMBER_SCHEMA, TRUST_CONTEXT_ID};
@@ -28,6 +30,15 @@ pub struct IssueCommand {
     #[arg(short, long = "attribute", value_name = "ATTRIBUTE")]
     pub attributes: Vec<String>,
 
+    /// Hex encoded delegation of the issuing identity, to issue the credential as a sub-authority
+    #[arg(long, value_name = "DELEGATION", value_parser = parse_delegation)]
+    pub delegation: Option<CredentialAndPurposeKey>,
+
+    /// Commit each attribute individually, so that the member can present only the attributes
+    /// needed by each node. The trust context identifier stays in clear
+    #[arg(long, default_value_t = false)]
+    pub selective_disclosure: bool,
+
     #[arg()]
     pub vault: Option<String>,
 
@@ -74,27 +85,51 @@ async fn run_impl(
     let identities = opts.state.get_identities(vault).await?;
     let issuer = ident_state.identifier();
 
+    // A sub-authority issues credentials in the trust context of its root authority
+    let trust_context_id = match &cmd.delegation {
+        Some(delegation) => delegation_root(delegation)?,
+        None => auth_identity_identifier,
+    };
     let mut attributes_builder = AttributesBuilder::with_schema(PROJECT_MEMBER_SCHEMA)
-        .with_attribute(
-            TRUST_CONTEXT_ID.to_vec(),
-            auth_identity_identifier.to_string(),
-        );
+        .with_attribute(TRUST_CONTEXT_ID.to_vec(), trust_context_id.to_string());
     for (key, value) in cmd.attributes()? {
         attributes_builder =
             attributes_builder.with_attribute(key.as_bytes().to_vec(), value.as_bytes().to_vec());
     }
 
-    let credential = identities
-        .credentials()
-        .credentials_creation()
-        .issue_credential(
-            &issuer,
-            cmd.identity_identifier(),
-            attributes_builder.build(),
-            MAX_CREDENTIAL_VALIDITY,
-        )
-        .await
-        .into_diagnostic()?;
+    let credentials_creation = identities.credentials().credentials_creation();
+    let credential = match &cmd.delegation {
+        delegation if cmd.selective_disclosure => credentials_creation
+            .issue_credential_with_committed_attributes(
+                &issuer,
+                cmd.identity_identifier(),
+                attributes_builder.build(),
+                &[TRUST_CONTEXT_ID],
+                delegated_ttl(delegation.as_ref())?,
+                delegation.as_ref(),
+            )
+            .await
+            .into_diagnostic()?,
+        Some(delegation) => credentials_creation
+            .issue_delegated_credential(
+                &issuer,
+                cmd.identity_identifier(),
+                attributes_builder.build(),
+                delegated_ttl(Some(delegation))?,
+                delegation,
+            )
+            .await
+            .into_diagnostic()?,
+        None => credentials_creation
+            .issue_credential(
+                &issuer,
+                cmd.identity_identifier(),
+                attributes_builder.build(),
+                MAX_CREDENTIAL_VALIDITY,
+            )
+            .await
+            .into_diagnostic()?,
+    };
 
     cmd.encode_format
         .println_value(&CredentialAndPurposeKeyDisplay(credential))?;
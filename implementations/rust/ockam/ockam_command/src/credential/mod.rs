@@ -1,3 +1,4 @@
+pub(crate) mod delegate;
 pub(crate) mod get;
 pub(crate) mod issue;
 pub(crate) mod list;
@@ -7,14 +8,18 @@ pub(crate) mod store;
 pub(crate) mod verify;
 
 use colorful::Colorful;
+pub(crate) use delegate::DelegateCommand;
 pub(crate) use get::GetCommand;
 pub(crate) use issue::IssueCommand;
 pub(crate) use list::ListCommand;
+use ockam::identity::MAX_CREDENTIAL_VALIDITY;
 use ockam::identity::{Identifier, Identities, Identity};
 use ockam_api::cli_state::{CredentialState, StateItemTrait};
 pub(crate) use present::PresentCommand;
 pub(crate) use show::ShowCommand;
+use std::cmp::min;
 use std::sync::Arc;
+use std::time::Duration;
 pub(crate) use store::StoreCommand;
 pub(crate) use verify::VerifyCommand;
 
@@ -22,7 +27,7 @@ use crate::output::{CredentialAndPurposeKeyDisplay, Output};
 use crate::{CommandGlobalOpts, Result};
 use clap::{Args, Subcommand};
 use miette::IntoDiagnostic;
-use ockam::identity::models::CredentialAndPurposeKey;
+use ockam::identity::models::{CredentialAndPurposeKey, PurposeKeyAttestationData};
 use ockam_api::cli_state::traits::StateDirTrait;
 
 /// Manage Credentials
@@ -36,6 +41,7 @@ pub struct CredentialCommand {
 #[derive(Clone, Debug, Subcommand)]
 pub enum CredentialSubcommand {
     #[command(display_order = 900)]
+    Delegate(DelegateCommand),
     Get(GetCommand),
     Issue(IssueCommand),
     List(ListCommand),
@@ -48,6 +54,7 @@ pub enum CredentialSubcommand {
 impl CredentialCommand {
     pub fn run(self, options: CommandGlobalOpts) {
         match self.subcommand {
+            CredentialSubcommand::Delegate(c) => c.run(options),
             CredentialSubcommand::Get(c) => c.run(options),
             CredentialSubcommand::Issue(c) => c.run(options),
             CredentialSubcommand::List(c) => c.run(options),
@@ -93,6 +100,35 @@ pub async fn validate_encoded_cred(
     Ok(())
 }
 
+/// Return a delegation credential passed as a hex encoded string on the command line
+pub(crate) fn parse_delegation(value: &str) -> Result<CredentialAndPurposeKey> {
+    Ok(minicbor::decode(&hex::decode(value)?)?)
+}
+
+/// Return the validity duration of the credentials issued with an optional delegation
+pub(crate) fn delegated_ttl(delegation: Option<&CredentialAndPurposeKey>) -> Result<Duration> {
+    let max_ttl = match delegation {
+        Some(delegation) => delegation
+            .get_credential_data()?
+            .delegation
+            .map(|constraints| Duration::from_secs(constraints.max_ttl.0)),
+        None => None,
+    };
+    Ok(max_ttl.map_or(MAX_CREDENTIAL_VALIDITY, |max_ttl| {
+        min(max_ttl, MAX_CREDENTIAL_VALIDITY)
+    }))
+}
+
+/// Return the identifier of the authority at the top of a chain of delegations
+pub(crate) fn delegation_root(delegation: &CredentialAndPurposeKey) -> Result<Identifier> {
+    let mut current = delegation;
+    while let Some(parent) = &current.delegation {
+        current = parent;
+    }
+    let versioned_data = current.purpose_key_attestation.get_versioned_data()?;
+    Ok(PurposeKeyAttestationData::get_data(&versioned_data)?.subject)
+}
+
 pub struct CredentialOutput {
     name: String,
     credential: String,
@@ -1,7 +1,7 @@
 use clap::Args;
 
 use ockam::Context;
-use ockam_api::nodes::{BackgroundNode, Credentials};
+use ockam_api::nodes::Credentials;
 use ockam_multiaddr::MultiAddr;
 
 use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
@@ -37,7 +37,7 @@ async fn run_impl(
     cmd: PresentCommand,
 ) -> miette::Result<()> {
     let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
-    let node = BackgroundNode::create(ctx, &opts.state, &node_name).await?;
+    let node = opts.background_node(ctx, &node_name).await?;
     node.present_credential(ctx, &cmd.to, cmd.oneway).await?;
     Ok(())
 }
@@ -2,7 +2,6 @@ use clap::Args;
 
 use ockam::Context;
 use ockam_api::address::extract_address_value;
-use ockam_api::nodes::BackgroundNode;
 use ockam_core::flow_control::FlowControlId;
 use ockam_multiaddr::MultiAddr;
 
@@ -43,7 +42,7 @@ async fn run_impl(
 ) -> miette::Result<()> {
     let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
     let node_name = extract_address_value(&node_name)?;
-    let node = BackgroundNode::create(ctx, &opts.state, &node_name).await?;
+    let node = opts.background_node(ctx, &node_name).await?;
     node.tell(ctx, api::add_consumer(cmd.flow_control_id, cmd.address))
         .await?;
 
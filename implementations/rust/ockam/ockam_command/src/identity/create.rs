@@ -1,10 +1,11 @@
 use crate::terminal::OckamColor;
 use crate::util::node_rpc;
-use crate::{docs, fmt_log, fmt_ok, CommandGlobalOpts};
+use crate::{docs, fmt_log, fmt_ok, CommandGlobalOpts, Result};
 use clap::Args;
 use colorful::Colorful;
 use miette::miette;
-use ockam::identity::Identifier;
+use ockam::identity::models::RecoveryPolicy;
+use ockam::identity::{Identifier, Identities};
 use ockam::Context;
 use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
 use ockam_vault::{HandleToSecret, SigningSecretKeyHandle};
@@ -32,6 +33,16 @@ pub struct CreateCommand {
     /// Key ID to use for the identity creation
     #[arg(short, long)]
     key_id: Option<String>,
+
+    /// Name of an identity whose key can be used to recover this identity if its key is lost.
+    /// The current key of the recovery identity is registered, and must be kept to sign a recovery request
+    #[arg(long = "recovery-identity", value_name = "IDENTITY_NAME")]
+    recovery_identities: Vec<String>,
+
+    /// Number of recovery identities which must sign a new key to recover this identity.
+    /// All the recovery identities are required by default
+    #[arg(long, value_name = "THRESHOLD", requires = "recovery_identities")]
+    recovery_threshold: Option<u8>,
 }
 
 impl CreateCommand {
@@ -40,6 +51,8 @@ impl CreateCommand {
             name,
             vault,
             key_id,
+            recovery_identities: vec![],
+            recovery_threshold: None,
         }
     }
 
@@ -81,34 +94,30 @@ impl CreateCommand {
 
             let vault = vault_state.get().await?;
 
-            let identities_creation = opts
-                .state
-                .get_identities(vault)
-                .await?
-                .identities_creation();
+            let identities = opts.state.get_identities(vault).await?;
+            let mut identity_builder = identities.identities_creation().identity_builder();
 
             // Create an identity using the KMS key, if provided.
-            let identity = match &self.key_id {
-                Some(key_id) => {
-                    if !vault_state.config().is_aws() {
-                        Err(miette!(
-                            "Vault {} is not an AWS KMS vault",
-                            self.vault.clone().unwrap_or("default".to_string()),
-                        ))
-                    } else {
-                        let handle = SigningSecretKeyHandle::ECDSASHA256CurveP256(
-                            HandleToSecret::new(key_id.as_bytes().to_vec()),
-                        );
-
-                        Ok(identities_creation
-                            .identity_builder()
-                            .with_existing_key(handle)
-                            .build()
-                            .await?)
-                    }
+            if let Some(key_id) = &self.key_id {
+                if !vault_state.config().is_aws() {
+                    return Err(miette!(
+                        "Vault {} is not an AWS KMS vault",
+                        self.vault.clone().unwrap_or("default".to_string()),
+                    )
+                    .into());
                 }
-                None => Ok(identities_creation.create_identity().await?),
-            }?;
+                let handle = SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(
+                    key_id.as_bytes().to_vec(),
+                ));
+                identity_builder = identity_builder.with_existing_key(handle);
+            }
+
+            if !self.recovery_identities.is_empty() {
+                let recovery_policy = self.recovery_policy(&opts, &identities).await?;
+                identity_builder = identity_builder.with_recovery_policy(recovery_policy);
+            }
+
+            let identity = identity_builder.build().await?;
 
             opts.state
                 .create_identity_state(identity.identifier(), Some(&self.name))
@@ -149,4 +158,26 @@ impl CreateCommand {
             .write_line()?;
         Ok(identifier.clone())
     }
+
+    /// Use the latest keys of the recovery identities as recovery keys
+    async fn recovery_policy(
+        &self,
+        opts: &CommandGlobalOpts,
+        identities: &Identities,
+    ) -> Result<RecoveryPolicy> {
+        let mut recovery_keys = vec![];
+        for name in &self.recovery_identities {
+            let identifier = opts.state.identities.get(name)?.identifier();
+            let recovery_identity = identities.get_identity(&identifier).await?;
+            recovery_keys.push(recovery_identity.get_latest_public_key()?);
+        }
+        let threshold = self
+            .recovery_threshold
+            .unwrap_or(recovery_keys.len().min(u8::MAX as usize) as u8);
+        Ok(RecoveryPolicy::new(threshold, recovery_keys).map_err(|_| {
+            miette!(
+                "The recovery threshold must be between 1 and the number of recovery identities"
+            )
+        })?)
+    }
 }
@@ -2,12 +2,14 @@ mod create;
 mod default;
 mod delete;
 mod list;
+mod recover;
 mod show;
 
 use colorful::Colorful;
 pub use create::CreateCommand;
 pub(crate) use delete::DeleteCommand;
 pub(crate) use list::ListCommand;
+pub(crate) use recover::RecoverCommand;
 pub(crate) use show::ShowCommand;
 
 use crate::identity::default::DefaultCommand;
@@ -38,6 +40,7 @@ pub enum IdentitySubcommand {
     List(ListCommand),
     Default(DefaultCommand),
     Delete(DeleteCommand),
+    Recover(RecoverCommand),
 }
 
 impl IdentityCommand {
@@ -48,6 +51,7 @@ impl IdentityCommand {
             IdentitySubcommand::List(c) => c.run(options),
             IdentitySubcommand::Delete(c) => c.run(options),
             IdentitySubcommand::Default(c) => c.run(options),
+            IdentitySubcommand::Recover(c) => c.run(options),
         }
     }
 }
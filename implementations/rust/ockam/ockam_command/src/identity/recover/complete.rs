@@ -0,0 +1,128 @@
+use crate::identity::recover::{parse_recovery_request, parse_recovery_signature};
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::vault::default_vault_name;
+use crate::{docs, fmt_log, fmt_ok, CommandGlobalOpts};
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use ockam::identity::IdentitiesKeys;
+use ockam::Context;
+use ockam_api::cli_state::traits::StateDirTrait;
+
+const AFTER_LONG_HELP: &str = include_str!("./static/complete/after_long_help.txt");
+
+/// Replace the lost key of an identity with the new key of a recovery request,
+/// once it is signed by enough recovery identities
+#[derive(Clone, Debug, Args)]
+#[command(
+arg_required_else_help = true,
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct CompleteCommand {
+    /// Name of the identity to recover
+    name: String,
+
+    /// Recovery request, hex encoded, or the path of a file containing it
+    #[arg(long, value_name = "REQUEST")]
+    request: String,
+
+    /// Signature of the request by a recovery identity, hex encoded, or the path of a file containing it
+    #[arg(
+        long = "signature",
+        value_name = "SIGNATURE",
+        required_unless_present = "recovery_identities"
+    )]
+    signatures: Vec<String>,
+
+    /// Name of a recovery identity signing the request, when its key is stored in the vault
+    #[arg(long = "recovery-identity", value_name = "IDENTITY_NAME")]
+    recovery_identities: Vec<String>,
+
+    /// Vault name containing the new identity key, created with the request
+    #[arg(long, value_name = "VAULT_NAME")]
+    vault: Option<String>,
+}
+
+impl CompleteCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, CompleteCommand),
+) -> miette::Result<()> {
+    let identifier = opts.state.identities.get(&cmd.name)?.identifier();
+    let request = parse_recovery_request(&cmd.request)?;
+
+    let vault_name = cmd
+        .vault
+        .clone()
+        .unwrap_or_else(|| default_vault_name(&opts.state));
+    let vault = opts.state.vaults.get(&vault_name)?.get().await?;
+    let identities = opts.state.get_identities(vault).await?;
+    let identities_keys = identities.identities_keys();
+
+    let identity = identities
+        .get_identity(&identifier)
+        .await
+        .into_diagnostic()?;
+    if identity.change_history() != &request.change_history {
+        return Err(miette!(
+            "The recovery request was not created for the identity {}",
+            cmd.name
+        ));
+    }
+
+    let mut signatures = vec![];
+    for signature in &cmd.signatures {
+        signatures.push(parse_recovery_signature(signature)?);
+    }
+    for name in &cmd.recovery_identities {
+        let recovery_identifier = opts.state.identities.get(name)?.identifier();
+        let recovery_identity = identities
+            .get_identity(&recovery_identifier)
+            .await
+            .into_diagnostic()?;
+        let recovery_key = identities_keys
+            .get_recovery_key(&identity, &recovery_identity)
+            .await
+            .into_diagnostic()?;
+        signatures.push(
+            identities_keys
+                .create_recovery_signature(&identity, &request.change, &recovery_key)
+                .await
+                .into_diagnostic()?,
+        );
+    }
+    let change =
+        IdentitiesKeys::add_recovery_signatures(request.change, signatures).into_diagnostic()?;
+
+    identities
+        .identities_creation()
+        .recover_identity(&identifier, change)
+        .await
+        .into_diagnostic()?;
+
+    opts.terminal
+        .stdout()
+        .plain(
+            fmt_ok!(
+                "Identity {} \n",
+                identifier
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color())
+            ) + &fmt_log!(
+                "recovered successfully with a new key as {}",
+                &cmd.name
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color())
+            ),
+        )
+        .machine(identifier.clone())
+        .json(serde_json::json!({ "identity": { "identifier": &identifier } }))
+        .write_line()?;
+    Ok(())
+}
@@ -0,0 +1,87 @@
+mod complete;
+mod request;
+mod sign;
+
+use crate::{docs, CommandGlobalOpts};
+use clap::{Args, Subcommand};
+use complete::CompleteCommand;
+use miette::{Context as _, IntoDiagnostic};
+use minicbor::{Decode, Encode};
+use ockam::identity::models::{Change, ChangeHistory, RecoverySignature};
+use request::RequestCommand;
+use sign::SignCommand;
+
+const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/after_long_help.txt");
+
+/// Replace the lost key of an identity with a new key signed by its recovery identities
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP),
+)]
+pub struct RecoverCommand {
+    #[command(subcommand)]
+    subcommand: RecoverSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum RecoverSubcommand {
+    Request(RequestCommand),
+    Sign(SignCommand),
+    Complete(CompleteCommand),
+}
+
+impl RecoverCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            RecoverSubcommand::Request(c) => c.run(options),
+            RecoverSubcommand::Sign(c) => c.run(options),
+            RecoverSubcommand::Complete(c) => c.run(options),
+        }
+    }
+}
+
+/// Recovery change of an identity, sent to the holders of its recovery identities
+#[derive(Clone, Debug, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct RecoveryRequest {
+    /// Change history of the identity to recover
+    #[n(1)] change_history: ChangeHistory,
+    /// Change with the new key of the identity, to be signed by the recovery identities
+    #[n(2)] change: Change,
+}
+
+impl RecoveryRequest {
+    fn encode(&self) -> miette::Result<String> {
+        Ok(hex::encode(minicbor::to_vec(self).into_diagnostic()?))
+    }
+}
+
+/// Parse a hex encoded recovery request, or the path of a file containing it
+fn parse_recovery_request(hex_encoded_data_or_path: &str) -> miette::Result<RecoveryRequest> {
+    let decoded = decode_hex_or_file(hex_encoded_data_or_path)
+        .context("Failed to decode the recovery request")?;
+    minicbor::decode(&decoded)
+        .into_diagnostic()
+        .context("Failed to parse the recovery request")
+}
+
+/// Parse a hex encoded recovery signature, or the path of a file containing it
+fn parse_recovery_signature(hex_encoded_data_or_path: &str) -> miette::Result<RecoverySignature> {
+    let decoded = decode_hex_or_file(hex_encoded_data_or_path)
+        .context("Failed to decode the recovery signature")?;
+    minicbor::decode(&decoded)
+        .into_diagnostic()
+        .context("Failed to parse the recovery signature")
+}
+
+fn decode_hex_or_file(hex_encoded_data_or_path: &str) -> miette::Result<Vec<u8>> {
+    match std::fs::read_to_string(hex_encoded_data_or_path) {
+        Ok(data) => hex::decode(data.trim()).into_diagnostic(),
+        Err(_) => hex::decode(hex_encoded_data_or_path.trim()).into_diagnostic(),
+    }
+}
@@ -0,0 +1,76 @@
+use crate::identity::recover::RecoveryRequest;
+use crate::util::node_rpc;
+use crate::vault::default_vault_name;
+use crate::{docs, CommandGlobalOpts};
+use clap::Args;
+use miette::IntoDiagnostic;
+use ockam::Context;
+use ockam_api::cli_state::traits::StateDirTrait;
+
+const AFTER_LONG_HELP: &str = include_str!("./static/request/after_long_help.txt");
+
+/// Create a request to replace the lost key of an identity with a new key.
+/// The request must be signed by the recovery identities
+#[derive(Clone, Debug, Args)]
+#[command(
+arg_required_else_help = true,
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct RequestCommand {
+    /// Name of the identity to recover
+    name: String,
+
+    /// Vault name to store the new identity key
+    #[arg(long, value_name = "VAULT_NAME")]
+    vault: Option<String>,
+}
+
+impl RequestCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, RequestCommand),
+) -> miette::Result<()> {
+    let identifier = opts.state.identities.get(&cmd.name)?.identifier();
+
+    let vault_name = cmd
+        .vault
+        .clone()
+        .unwrap_or_else(|| default_vault_name(&opts.state));
+    let vault = opts.state.vaults.get(&vault_name)?.get().await?;
+    let identities = opts.state.get_identities(vault).await?;
+
+    let identity = identities
+        .get_identity(&identifier)
+        .await
+        .into_diagnostic()?;
+    let options = identities
+        .identities_creation()
+        .identity_builder()
+        .build_options()
+        .await
+        .into_diagnostic()?;
+    let change = identities
+        .identities_keys()
+        .create_recovery_change(&identity, options)
+        .await
+        .into_diagnostic()?;
+
+    let request = RecoveryRequest {
+        change_history: identity.change_history().clone(),
+        change,
+    }
+    .encode()?;
+
+    opts.terminal
+        .stdout()
+        .plain(&request)
+        .machine(&request)
+        .json(serde_json::json!({ "request": &request }))
+        .write_line()?;
+    Ok(())
+}
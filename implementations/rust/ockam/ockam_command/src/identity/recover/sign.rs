@@ -0,0 +1,90 @@
+use crate::identity::recover::parse_recovery_request;
+use crate::util::node_rpc;
+use crate::vault::default_vault_name;
+use crate::{docs, fmt_log, CommandGlobalOpts};
+use clap::Args;
+use miette::IntoDiagnostic;
+use ockam::identity::Identity;
+use ockam::Context;
+use ockam_api::cli_state::traits::StateDirTrait;
+
+const AFTER_LONG_HELP: &str = include_str!("./static/sign/after_long_help.txt");
+
+/// Sign a recovery request with the key registered for a recovery identity
+#[derive(Clone, Debug, Args)]
+#[command(
+arg_required_else_help = true,
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct SignCommand {
+    /// Recovery request, hex encoded, or the path of a file containing it
+    request: String,
+
+    /// Name of the recovery identity signing the request
+    #[arg(long = "recovery-identity", value_name = "IDENTITY_NAME")]
+    recovery_identity: String,
+
+    /// Vault name containing the key of the recovery identity
+    #[arg(long, value_name = "VAULT_NAME")]
+    vault: Option<String>,
+}
+
+impl SignCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, SignCommand),
+) -> miette::Result<()> {
+    let request = parse_recovery_request(&cmd.request)?;
+    let recovery_identifier = opts
+        .state
+        .identities
+        .get(&cmd.recovery_identity)?
+        .identifier();
+
+    let vault_name = cmd
+        .vault
+        .clone()
+        .unwrap_or_else(|| default_vault_name(&opts.state));
+    let vault = opts.state.vaults.get(&vault_name)?.get().await?;
+    let identities = opts.state.get_identities(vault).await?;
+    let identities_keys = identities.identities_keys();
+
+    let identity = Identity::import_from_change_history(
+        None,
+        request.change_history,
+        identities.vault().verifying_vault,
+    )
+    .await
+    .into_diagnostic()?;
+    let recovery_identity = identities
+        .get_identity(&recovery_identifier)
+        .await
+        .into_diagnostic()?;
+
+    let recovery_key = identities_keys
+        .get_recovery_key(&identity, &recovery_identity)
+        .await
+        .into_diagnostic()?;
+    let signature = identities_keys
+        .create_recovery_signature(&identity, &request.change, &recovery_key)
+        .await
+        .into_diagnostic()?;
+    let signature = hex::encode(minicbor::to_vec(&signature).into_diagnostic()?);
+
+    opts.terminal.write_line(&fmt_log!(
+        "Signed the recovery request of the identity {}",
+        identity.identifier()
+    ))?;
+    opts.terminal
+        .stdout()
+        .plain(&signature)
+        .machine(&signature)
+        .json(serde_json::json!({ "signature": &signature }))
+        .write_line()?;
+    Ok(())
+}
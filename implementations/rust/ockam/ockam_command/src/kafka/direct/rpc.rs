@@ -5,7 +5,6 @@ use tokio::{sync::Mutex, try_join};
 
 use ockam::Context;
 use ockam_api::nodes::models::services::{StartKafkaDirectRequest, StartServiceRequest};
-use ockam_api::nodes::BackgroundNode;
 use ockam_api::port_range::PortRange;
 use ockam_core::api::Request;
 use ockam_multiaddr::MultiAddr;
@@ -53,7 +52,7 @@ pub async fn start(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) ->
     let is_finished = Mutex::new(false);
     let send_req = async {
         let node_name = get_node_name(&opts.state, &node_opts.at_node);
-        let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+        let node = opts.background_node(&ctx, &node_name).await?;
 
         let payload = StartKafkaDirectRequest::new(
             bind_address.to_owned(),
@@ -32,31 +32,31 @@ fn kafka_producer_default_addr() -> String {
     DefaultAddress::KAFKA_PRODUCER.to_string()
 }
 
-fn kafka_default_project_route() -> MultiAddr {
+pub(crate) fn kafka_default_project_route() -> MultiAddr {
     MultiAddr::from_str(KAFKA_DEFAULT_PROJECT_ROUTE).expect("Failed to parse default project route")
 }
 
-fn kafka_default_outlet_server() -> SocketAddr {
+pub(crate) fn kafka_default_outlet_server() -> SocketAddr {
     SocketAddr::from_str(KAFKA_DEFAULT_BOOTSTRAP_ADDRESS)
         .expect("Failed to parse default bootstrap address")
 }
 
-fn kafka_default_consumer_server() -> SocketAddr {
+pub(crate) fn kafka_default_consumer_server() -> SocketAddr {
     SocketAddr::from_str(KAFKA_DEFAULT_CONSUMER_SERVER)
         .expect("Failed to parse default consumer server")
 }
 
-fn kafka_default_consumer_port_range() -> PortRange {
+pub(crate) fn kafka_default_consumer_port_range() -> PortRange {
     PortRange::from_str(KAFKA_DEFAULT_CONSUMER_PORT_RANGE)
         .expect("Failed to parse default consumer port range")
 }
 
-fn kafka_default_producer_server() -> SocketAddr {
+pub(crate) fn kafka_default_producer_server() -> SocketAddr {
     SocketAddr::from_str(KAFKA_DEFAULT_PRODUCER_SERVER)
         .expect("Failed to parse default producer server")
 }
 
-fn kafka_default_producer_port_range() -> PortRange {
+pub(crate) fn kafka_default_producer_port_range() -> PortRange {
     PortRange::from_str(KAFKA_DEFAULT_PRODUCER_PORT_RANGE)
         .expect("Failed to parse default producer port range")
 }
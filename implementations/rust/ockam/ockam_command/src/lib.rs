@@ -56,11 +56,14 @@ mod subscription;
 pub mod tcp;
 mod terminal;
 mod trust_context;
+#[cfg(unix)]
+mod uds;
 mod upgrade;
 pub mod util;
 mod vault;
 mod version;
 mod worker;
+mod ws;
 
 use crate::admin::AdminCommand;
 use crate::authority::AuthorityCommand;
@@ -75,7 +78,7 @@ use clap::{ArgAction, Args, Parser, Subcommand};
 
 use crate::kafka::direct::KafkaDirectCommand;
 use crate::kafka::outlet::KafkaOutletCommand;
-use crate::output::{Output, OutputFormat};
+use crate::output::{Output, OutputFilter, OutputFormat, Query};
 use crate::sidecar::SidecarCommand;
 use colorful::Colorful;
 use completion::CompletionCommand;
@@ -94,8 +97,11 @@ use markdown::MarkdownCommand;
 use message::MessageCommand;
 use miette::GraphicalReportHandler;
 use node::NodeCommand;
+use ockam::Context;
 use ockam_api::cli_state::CliState;
+use ockam_api::nodes::BackgroundNode;
 use ockam_core::env::get_env_with_default;
+use ockam_multiaddr::MultiAddr;
 use once_cell::sync::Lazy;
 use policy::PolicyCommand;
 use project::ProjectCommand;
@@ -113,11 +119,14 @@ use tcp::{
     outlet::TcpOutletCommand,
 };
 use trust_context::TrustContextCommand;
+#[cfg(unix)]
+use uds::{connection::UdsConnectionCommand, listener::UdsListenerCommand};
 use upgrade::check_if_an_upgrade_is_available;
 use util::{exitcode, exitcode::ExitCode};
 use vault::VaultCommand;
 use version::Version;
 use worker::WorkerCommand;
+use ws::{connection::WsConnectionCommand, listener::WsListenerCommand};
 
 const ABOUT: &str = include_str!("./static/about.txt");
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
@@ -191,6 +200,27 @@ pub struct GlobalArgs {
     )]
     output_format: OutputFormat,
 
+    /// Only output the part of the result selected by this jq-style path, for example
+    /// `.inlets[].alias`. A selected string is printed without quotes with the plain output format
+    #[arg(hide = docs::hide(), global = true, long, value_name = "QUERY")]
+    query: Option<Query>,
+
+    /// Columns displayed with the table output format, separated by commas
+    #[arg(
+    hide = docs::hide(),
+    global = true,
+    long,
+    value_name = "COLUMNS",
+    value_delimiter = ','
+    )]
+    columns: Vec<String>,
+
+    /// Send the requests of the command to the node at the end of this route, through the
+    /// local node, for example `/dnsaddr/host/tcp/4000/secure/api`. The remote node must
+    /// have `node-manager` policies authorizing the identity of the local node
+    #[arg(global = true, long, value_name = "ROUTE")]
+    remote: Option<MultiAddr>,
+
     // if test_argument_parser is true, command arguments are checked
     // but the command is not executed.
     #[arg(global = true, long, hide = true)]
@@ -218,6 +248,9 @@ impl Default for GlobalArgs {
             no_color: no_color_default_value(),
             no_input: no_input_default_value(),
             output_format: OutputFormat::Plain,
+            query: None,
+            columns: vec![],
+            remote: None,
             test_argument_parser: false,
         }
     }
@@ -229,6 +262,10 @@ impl GlobalArgs {
         clone.quiet = true;
         clone
     }
+
+    pub fn output_filter(&self) -> OutputFilter {
+        OutputFilter::new(self.query.clone(), self.columns.clone())
+    }
 }
 
 #[derive(Clone)]
@@ -256,7 +293,8 @@ impl CommandGlobalOpts {
             global_args.no_color,
             global_args.no_input,
             global_args.output_format.clone(),
-        );
+        )
+        .set_output_filter(global_args.output_filter());
         Self {
             global_args,
             state,
@@ -277,7 +315,34 @@ impl CommandGlobalOpts {
     where
         T: Output + serde::Serialize,
     {
-        self.global_args.output_format.println_value(t)
+        self.global_args
+            .output_format
+            .println_value(t, &self.global_args.output_filter())
+    }
+
+    /// Create a client for a local node. If a remote node was given with `--remote`,
+    /// the requests are forwarded to that node by the local node.
+    pub async fn background_node(
+        &self,
+        ctx: &Context,
+        node_name: &str,
+    ) -> miette::Result<BackgroundNode> {
+        let mut node = BackgroundNode::create(ctx, &self.state, node_name).await?;
+        if let Some(remote) = &self.global_args.remote {
+            node.set_remote(remote.clone());
+        }
+        Ok(node)
+    }
+
+    /// Return an error if a remote node was given with `--remote`, for the commands
+    /// which create, start or inspect the local nodes themselves
+    pub fn reject_remote(&self) -> miette::Result<()> {
+        match &self.global_args.remote {
+            Some(remote) => Err(miette::miette!(
+                "The --remote argument ({remote}) is not supported by this command, which only applies to local nodes"
+            )),
+            None => Ok(()),
+        }
     }
 }
 
@@ -289,7 +354,8 @@ impl CommandGlobalOpts {
             global_args.no_color,
             global_args.no_input,
             global_args.output_format.clone(),
-        );
+        )
+        .set_output_filter(global_args.output_filter());
         Self {
             global_args,
             state,
@@ -321,6 +387,13 @@ pub enum OckamSubcommand {
     TcpOutlet(TcpOutletCommand),
     TcpInlet(TcpInletCommand),
 
+    WsListener(WsListenerCommand),
+    WsConnection(WsConnectionCommand),
+    #[cfg(unix)]
+    UdsListener(UdsListenerCommand),
+    #[cfg(unix)]
+    UdsConnection(UdsConnectionCommand),
+
     KafkaOutlet(KafkaOutletCommand),
     KafkaConsumer(KafkaConsumerCommand),
     KafkaDirect(KafkaDirectCommand),
@@ -444,6 +517,12 @@ impl OckamCommand {
             OckamSubcommand::TcpConnection(c) => c.run(options),
             OckamSubcommand::TcpOutlet(c) => c.run(options),
             OckamSubcommand::TcpInlet(c) => c.run(options),
+            OckamSubcommand::WsListener(c) => c.run(options),
+            OckamSubcommand::WsConnection(c) => c.run(options),
+            #[cfg(unix)]
+            OckamSubcommand::UdsListener(c) => c.run(options),
+            #[cfg(unix)]
+            OckamSubcommand::UdsConnection(c) => c.run(options),
 
             OckamSubcommand::KafkaConsumer(c) => c.run(options),
             OckamSubcommand::KafkaProducer(c) => c.run(options),
@@ -6,7 +6,6 @@ use miette::{Context as _, IntoDiagnostic};
 use ockam::Context;
 use ockam_api::address::extract_address_value;
 use ockam_api::nodes::service::message::{MessageSender, SendMessage};
-use ockam_api::nodes::BackgroundNode;
 use ockam_api::nodes::InMemoryNode;
 use ockam_core::api::Request;
 use ockam_multiaddr::MultiAddr;
@@ -82,7 +81,7 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, SendCommand)) -> mie
         // or an in-memory node
         let response: Vec<u8> = if let Some(node) = &cmd.from {
             let node_name = extract_address_value(node)?;
-            BackgroundNode::create(ctx, &opts.state, &node_name)
+            opts.background_node(ctx, &node_name)
                 .await?
                 .set_timeout(cmd.timeout)
                 .ask(ctx, req(&to, msg_bytes))
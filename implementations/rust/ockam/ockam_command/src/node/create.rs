@@ -11,11 +11,14 @@ use tokio::sync::Mutex;
 use tokio::time::{sleep, Duration};
 use tokio::try_join;
 
+use ockam::identity::{CredentialSchemas, Identifier};
 use ockam::{Address, AsyncTryClone, TcpListenerOptions};
 use ockam::{Context, TcpTransport};
 use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
 use ockam_api::cli_state::{add_project_info_to_node_state, init_node_state, random_name};
+use ockam_api::nodes::models::relay::RelayReservation;
 use ockam_api::nodes::models::transport::CreateTransportJson;
+use ockam_api::nodes::service::resources::restore_resources;
 use ockam_api::nodes::service::NodeManagerTrustOptions;
 use ockam_api::nodes::BackgroundNode;
 use ockam_api::nodes::InMemoryNode;
@@ -29,13 +32,19 @@ use ockam_api::{
 };
 use ockam_core::api::{Request, ResponseHeader, Status};
 use ockam_core::{route, LOCAL};
+#[cfg(unix)]
+use ockam_transport_uds::UdsTransport;
+use ockam_transport_websocket::WebSocketTransport;
 
 use crate::node::util::{spawn_node, NodeManagerDefaults};
+use crate::run::{NodeConfigFile, NodeConfigWatcher};
 use crate::secure_channel::listener::create as secure_channel_listener;
 use crate::service::config::Config;
 use crate::terminal::OckamColor;
 use crate::util::api::TrustContextOpts;
-use crate::util::{api, parse_node_name};
+use crate::util::duration::duration_parser;
+use crate::util::parsers::relay_reservation_parser;
+use crate::util::{api, parse_node_name, read_credential_schemas};
 use crate::util::{embedded_node_that_is_not_stopped, exitcode};
 use crate::util::{local_cmd, node_rpc};
 use crate::{docs, shutdown, CommandGlobalOpts, Result};
@@ -75,6 +84,52 @@ pub struct CreateCommand {
     )]
     pub tcp_listener_address: String,
 
+    /// Start a WebSocket listener at this address (eg. 127.0.0.1:7000)
+    #[arg(display_order = 900, long, value_name = "SOCKET_ADDRESS")]
+    pub ws_listener_address: Option<String>,
+
+    /// Start a Unix domain socket listener at this path
+    #[cfg(unix)]
+    #[arg(display_order = 900, long, value_name = "PATH")]
+    pub uds_listener_path: Option<PathBuf>,
+
+    /// Don't recreate the inlets, outlets, relays and other resources created on this node
+    /// when it is started again with `ockam node start`
+    #[arg(display_order = 900, long)]
+    pub ephemeral: bool,
+
+    /// Path to a configuration file declaring the inlets, outlets, relays, policies and
+    /// trusted identities of the node. The file is watched, and its changes are applied
+    /// to the running node
+    #[arg(
+        display_order = 900,
+        long,
+        value_name = "PATH",
+        conflicts_with = "trusted"
+    )]
+    pub config: Option<PathBuf>,
+
+    /// Path of a JSON file declaring the types and constraints of the attributes of the
+    /// credentials verified by this node, for a given schema identifier. Can be repeated
+    #[arg(display_order = 900, long = "credential-schema", value_name = "PATH")]
+    pub credential_schemas: Vec<PathBuf>,
+
+    /// Stop the relays registered at the relay service of this node when they don't send
+    /// heartbeats for longer than this duration. By default, relays never expire
+    #[arg(display_order = 900, long, value_name = "DURATION", value_parser = duration_parser)]
+    pub relay_ttl: Option<Duration>,
+
+    /// Only allow an identity to register relays whose name starts with the given prefix at
+    /// the relay service of this node, written as `<prefix>=<identifier>`. Can be repeated,
+    /// a prefix can be reserved to several identities
+    #[arg(
+        display_order = 900,
+        long = "relay-reserved-prefix",
+        value_name = "PREFIX=IDENTIFIER",
+        value_parser = relay_reservation_parser
+    )]
+    pub relay_reservations: Vec<(String, Identifier)>,
+
     /// `node create` started a child process to run this node in foreground.
     #[arg(long, hide = true)]
     pub child_process: bool,
@@ -117,6 +172,14 @@ impl Default for CreateCommand {
             node_name: random_name(),
             exit_on_eof: false,
             tcp_listener_address: node_manager_defaults.tcp_listener_address,
+            ws_listener_address: None,
+            #[cfg(unix)]
+            uds_listener_path: None,
+            ephemeral: false,
+            config: None,
+            credential_schemas: vec![],
+            relay_ttl: None,
+            relay_reservations: vec![],
             foreground: false,
             child_process: false,
             launch_config: None,
@@ -164,6 +227,17 @@ impl CreateCommand {
         }
     }
 
+    /// Path of the UDS listener to start. Unix domain sockets are only supported on unix
+    #[cfg(unix)]
+    pub fn uds_listener_path(&self) -> Option<&PathBuf> {
+        self.uds_listener_path.as_ref()
+    }
+
+    #[cfg(not(unix))]
+    pub fn uds_listener_path(&self) -> Option<&PathBuf> {
+        None
+    }
+
     pub fn logging_to_stdout(&self) -> bool {
         !self.logging_to_file()
     }
@@ -186,6 +260,7 @@ pub(crate) async fn background_mode(
     ctx: Context,
     (opts, cmd): (CommandGlobalOpts, CreateCommand),
 ) -> miette::Result<()> {
+    opts.reject_remote()?;
     let node_name = &parse_node_name(&cmd.node_name)?;
     opts.terminal.write_line(&fmt_log!(
         "Creating Node {}...\n",
@@ -200,6 +275,16 @@ pub(crate) async fn background_mode(
         ));
     }
 
+    // Report an invalid configuration file before starting the node
+    if let Some(path) = &cmd.config {
+        let contents = std::fs::read_to_string(path)
+            .into_diagnostic()
+            .wrap_err_with(|| {
+                format!("Failed to read the configuration file {}", path.display())
+            })?;
+        NodeConfigFile::parse(node_name, &contents)?;
+    }
+
     let is_finished: Mutex<bool> = Mutex::new(false);
 
     let send_req = async {
@@ -244,6 +329,7 @@ pub(crate) async fn background_mode(
 
 // Create a new node in the foreground (i.e. in this OS process)
 fn foreground_mode(opts: CommandGlobalOpts, cmd: CreateCommand) -> miette::Result<()> {
+    opts.reject_remote()?;
     embedded_node_that_is_not_stopped(run_foreground_node, (opts, cmd))?;
     Ok(())
 }
@@ -287,13 +373,37 @@ async fn run_foreground_node(
         .await
         .into_diagnostic()?;
 
+    // The configuration file is kept, so that it is still watched when the node is restarted
+    let config_file = match &cmd.config {
+        Some(path) => Some(std::fs::canonicalize(path).into_diagnostic()?),
+        None => None,
+    };
+
     let node_state = opts.state.nodes.get(&node_name)?;
+    // The schemas are kept in the node setup, so that they are used again when the node is restarted
+    let credential_schemas = if cmd.credential_schemas.is_empty() {
+        node_state.config().setup().credential_schemas.clone()
+    } else {
+        read_credential_schemas(&cmd.credential_schemas)?
+    };
+    // The relay ttl is kept in the node setup as well
+    let relay_ttl = cmd.relay_ttl.or(node_state.config().setup().relay_ttl);
+    let relay_reservations = if cmd.relay_reservations.is_empty() {
+        node_state.config().setup().relay_reservations.clone()
+    } else {
+        group_relay_reservations(&cmd.relay_reservations)
+    };
     node_state.set_pid(process::id() as i32)?;
     node_state.set_setup(
         &node_state
             .config()
             .setup_mut()
             .set_verbose(opts.global_args.verbose)
+            .set_ephemeral(cmd.ephemeral)
+            .set_config_file(config_file.clone())
+            .set_credential_schemas(credential_schemas.clone())
+            .set_relay_ttl(relay_ttl)
+            .set_relay_reservations(relay_reservations.clone())
             .set_api_transport(
                 CreateTransportJson::new(
                     TransportType::Tcp,
@@ -304,7 +414,31 @@ async fn run_foreground_node(
             ),
     )?;
 
-    let pre_trusted_identities = load_pre_trusted_identities(&cmd)?;
+    // The identities trusted by a node created with a configuration file are reloaded
+    // each time they are checked, so that the changes made to the file are taken into account
+    let config_watcher = match &config_file {
+        Some(path) => Some(NodeConfigWatcher::new(&opts, &node_name, path)?),
+        None => None,
+    };
+    let pre_trusted_identities = match &config_watcher {
+        Some(watcher) => Some(
+            PreTrustedIdentities::new_from_disk(
+                watcher.trusted_identities_file().to_path_buf(),
+                true,
+            )
+            .into_diagnostic()?,
+        ),
+        None => load_pre_trusted_identities(&cmd)?,
+    };
+
+    let transport_options = NodeManagerTransportOptions::new(
+        listener.flow_control_id().clone(),
+        tcp.async_try_clone().await.into_diagnostic()?,
+    )
+    .with_web_socket_transport(WebSocketTransport::create(&ctx).await.into_diagnostic()?);
+    #[cfg(unix)]
+    let transport_options =
+        transport_options.with_uds_transport(UdsTransport::create(&ctx).await.into_diagnostic()?);
 
     let node_man = InMemoryNode::new(
         &ctx,
@@ -314,15 +448,33 @@ async fn run_foreground_node(
             pre_trusted_identities,
             cmd.launch_config.is_none(),
             true,
+        )
+        .with_relay_ttl(relay_ttl)
+        .with_relay_reservations(relay_reservations),
+        transport_options,
+        NodeManagerTrustOptions::new(trust_context_config).with_credential_schemas(
+            credential_schemas
+                .into_iter()
+                .fold(CredentialSchemas::new(), |schemas, schema| {
+                    schemas.with_schema(schema)
+                }),
         ),
-        NodeManagerTransportOptions::new(
-            listener.flow_control_id().clone(),
-            tcp.async_try_clone().await.into_diagnostic()?,
-        ),
-        NodeManagerTrustOptions::new(trust_context_config),
     )
     .await
     .into_diagnostic()?;
+    if let Some(address) = &cmd.ws_listener_address {
+        node_man
+            .create_web_socket_listener(address)
+            .await
+            .into_diagnostic()?;
+    }
+    #[cfg(unix)]
+    if let Some(path) = &cmd.uds_listener_path {
+        node_man
+            .create_uds_listener(&path.to_string_lossy())
+            .await
+            .into_diagnostic()?;
+    }
     let node_manager_worker = NodeManagerWorker::new(Arc::new(node_man));
 
     ctx.flow_controls()
@@ -347,6 +499,23 @@ async fn run_foreground_node(
         }
     }
 
+    if !cmd.ephemeral {
+        restore_resources(&ctx, &node_state)
+            .await
+            .into_diagnostic()?;
+    }
+
+    let config_watcher = match config_watcher {
+        Some(watcher) => match watcher.start(&ctx, opts.clone(), &tcp).await {
+            Ok(handle) => Some(handle),
+            Err(err) => {
+                ctx.stop().await.into_diagnostic()?;
+                return Err(err);
+            }
+        },
+        None => None,
+    };
+
     // Create a channel for communicating back to the main thread
     let (tx, mut rx) = tokio::sync::mpsc::channel(2);
     shutdown::wait(
@@ -362,6 +531,8 @@ async fn run_foreground_node(
     if let Ok(state) = opts.state.nodes.get(&node_name) {
         let _ = state.kill_process(false);
     }
+    // Stop watching the configuration file before stopping the node
+    drop(config_watcher);
     ctx.stop().await.into_diagnostic()?;
     opts.terminal
         .write_line(format!("{}Node stopped successfully", "✔︎".light_green()).as_str())
@@ -370,6 +541,18 @@ async fn run_foreground_node(
     Ok(())
 }
 
+/// Group the identities allowed to register relays by reserved prefix
+fn group_relay_reservations(reservations: &[(String, Identifier)]) -> Vec<RelayReservation> {
+    let mut grouped: Vec<RelayReservation> = vec![];
+    for (prefix, identifier) in reservations {
+        match grouped.iter_mut().find(|r| &r.prefix == prefix) {
+            Some(reservation) => reservation.identifiers.push(identifier.clone()),
+            None => grouped.push(RelayReservation::new(prefix, vec![identifier.clone()])),
+        }
+    }
+    grouped
+}
+
 pub fn load_pre_trusted_identities(cmd: &CreateCommand) -> Result<Option<PreTrustedIdentities>> {
     let command = cmd.clone();
     let pre_trusted_identities = match (
@@ -475,6 +658,13 @@ pub async fn spawn_background_node(
         cmd.credential.as_ref(),
         trust_context_path.as_ref(),
         cmd.trust_context_opts.project.as_ref(),
+        cmd.ws_listener_address.as_ref(),
+        cmd.uds_listener_path(),
+        cmd.config.as_ref(),
+        &cmd.credential_schemas,
+        cmd.relay_ttl,
+        &cmd.relay_reservations,
+        cmd.ephemeral,
         cmd.logging_to_file(),
     )?;
 
@@ -40,6 +40,7 @@ async fn run_impl(
     ctx: Context,
     (opts, _cmd): (CommandGlobalOpts, ListCommand),
 ) -> miette::Result<()> {
+    opts.reject_remote()?;
     // Before printing node states we verify them.
     // We send a QueryStatus request to every node on
     // record. If the response yields a different pid to the
@@ -7,6 +7,7 @@ use delete::DeleteCommand;
 use list::ListCommand;
 use logs::LogCommand;
 use ockam_api::cli_state::{CliState, StateDirTrait};
+use sessions::SessionsCommand;
 use show::ShowCommand;
 use start::StartCommand;
 use stop::StopCommand;
@@ -19,11 +20,14 @@ mod delete;
 mod list;
 mod logs;
 mod models;
+mod sessions;
 mod show;
 mod start;
 mod stop;
 pub mod util;
 pub use create::*;
+pub(crate) use show::is_node_up;
+pub(crate) use start::start_node;
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/after_long_help.txt");
@@ -53,6 +57,8 @@ pub enum NodeSubcommand {
     Logs(LogCommand),
     Show(ShowCommand),
     #[command(display_order = 800)]
+    Sessions(SessionsCommand),
+    #[command(display_order = 800)]
     Start(StartCommand),
     #[command(display_order = 800)]
     Stop(StopCommand),
@@ -67,6 +73,7 @@ impl NodeCommand {
             NodeSubcommand::Delete(c) => c.run(options),
             NodeSubcommand::List(c) => c.run(options),
             NodeSubcommand::Show(c) => c.run(options),
+            NodeSubcommand::Sessions(c) => c.run(options),
             NodeSubcommand::Start(c) => c.run(options),
             NodeSubcommand::Stop(c) => c.run(options),
             NodeSubcommand::Logs(c) => c.run(options),
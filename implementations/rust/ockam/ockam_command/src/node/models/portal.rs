@@ -15,6 +15,8 @@ pub struct ShowInletStatus {
     pub listen_address: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub route_to_outlet: Option<MultiAddr>,
+    /// True if the inlet is recreated when the node restarts
+    pub persisted: bool,
 }
 
 impl From<InletStatus> for ShowInletStatus {
@@ -22,6 +24,7 @@ impl From<InletStatus> for ShowInletStatus {
         Self {
             listen_address: value.bind_addr,
             route_to_outlet: Route::parse(value.outlet_route).and_then(|r| route_to_multiaddr(&r)),
+            persisted: false,
         }
     }
 }
@@ -32,6 +35,8 @@ pub struct ShowOutletStatus {
     pub forward_address: SocketAddr,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<MultiAddr>,
+    /// True if the outlet is recreated when the node restarts
+    pub persisted: bool,
 }
 
 impl From<OutletStatus> for ShowOutletStatus {
@@ -39,6 +44,7 @@ impl From<OutletStatus> for ShowOutletStatus {
         Self {
             forward_address: value.socket_addr,
             address: addr_to_multiaddr(value.worker_addr),
+            persisted: false,
         }
     }
 }
@@ -11,6 +11,8 @@ pub struct ShowSecureChannelListener {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<MultiAddr>,
     pub flow_control: FlowControlId,
+    /// True if the listener is recreated when the node restarts
+    pub persisted: bool,
 }
 
 impl From<ShowSecureChannelListenerResponse> for ShowSecureChannelListener {
@@ -18,6 +20,7 @@ impl From<ShowSecureChannelListenerResponse> for ShowSecureChannelListener {
         Self {
             address: addr_to_multiaddr(value.addr),
             flow_control: value.flow_control_id,
+            persisted: false,
         }
     }
 }
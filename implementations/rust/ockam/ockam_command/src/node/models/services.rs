@@ -9,6 +9,8 @@ pub struct ShowServiceStatus {
     pub address: Option<MultiAddr>,
     #[serde(rename = "type")]
     pub service_type: String,
+    /// True if the service is recreated when the node restarts
+    pub persisted: bool,
 }
 
 impl From<ServiceStatus> for ShowServiceStatus {
@@ -16,6 +18,7 @@ impl From<ServiceStatus> for ShowServiceStatus {
         Self {
             address: addr_to_multiaddr(value.addr),
             service_type: value.service_type,
+            persisted: false,
         }
     }
 }
@@ -113,7 +113,9 @@ impl Display for ShowNodeResponse {
             writeln!(buffer, "      Mode: {}", &e.mode)?;
             writeln!(buffer, "      Socket: {}", &e.socket)?;
             writeln!(buffer, "      Worker: {}", &e.worker)?;
-            writeln!(buffer, "      FlowControlId: {}", &e.flow_control)?;
+            if let Some(flow_control) = &e.flow_control {
+                writeln!(buffer, "      FlowControlId: {}", flow_control)?;
+            }
         }
 
         writeln!(buffer, "  Secure Channel Listeners:")?;
@@ -123,6 +125,7 @@ impl Display for ShowNodeResponse {
                 writeln!(buffer, "      Address: {ma}")?;
             }
             writeln!(buffer, "      FlowControlId: {}", &e.flow_control)?;
+            writeln!(buffer, "      Lifetime: {}", lifetime(e.persisted))?;
         }
 
         writeln!(buffer, "  Inlets:")?;
@@ -132,6 +135,7 @@ impl Display for ShowNodeResponse {
             if let Some(r) = &e.route_to_outlet {
                 writeln!(buffer, "      Route To Outlet: {r}")?;
             }
+            writeln!(buffer, "      Lifetime: {}", lifetime(e.persisted))?;
         }
 
         writeln!(buffer, "  Outlets:")?;
@@ -141,6 +145,7 @@ impl Display for ShowNodeResponse {
             if let Some(ma) = &e.address {
                 writeln!(buffer, "      Address: {ma}")?;
             }
+            writeln!(buffer, "      Lifetime: {}", lifetime(e.persisted))?;
         }
 
         writeln!(buffer, "  Services:")?;
@@ -150,12 +155,24 @@ impl Display for ShowNodeResponse {
             if let Some(ma) = &e.address {
                 writeln!(buffer, "      Address: {ma}")?;
             }
+            if e.persisted {
+                writeln!(buffer, "      Lifetime: {}", lifetime(e.persisted))?;
+            }
         }
 
         Ok(())
     }
 }
 
+/// Persisted resources are recreated when the node restarts, ephemeral ones are not
+fn lifetime(persisted: bool) -> &'static str {
+    if persisted {
+        "persisted"
+    } else {
+        "ephemeral"
+    }
+}
+
 impl Output for ShowNodeResponse {
     fn output(&self) -> crate::error::Result<String> {
         Ok(self.to_string())
@@ -10,7 +10,8 @@ pub struct ShowTransportStatus {
     pub mode: TransportMode,
     pub socket: String,
     pub worker: String,
-    pub flow_control: FlowControlId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flow_control: Option<FlowControlId>,
 }
 
 impl From<TransportStatus> for ShowTransportStatus {
@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+
+use ockam::identity::TimestampInSeconds;
+use ockam_api::nodes::models::session::{
+    GetSessionEvents, SessionEvent, SessionEventList, SessionList, SessionStatus,
+};
+use ockam_api::nodes::BackgroundNode;
+use ockam_core::api::Request;
+use ockam_node::Context;
+
+use crate::node::get_node_name;
+use crate::output::{human_readable_time, Output};
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::{docs, CommandGlobalOpts, Result};
+
+const LONG_ABOUT: &str = include_str!("./static/sessions/long_about.txt");
+const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/sessions/after_long_help.txt");
+
+/// Duration of each request waiting for new session events
+const EVENTS_WAIT: Duration = Duration::from_secs(30);
+
+/// Show the health of the sessions monitored by a node
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    before_help = docs::before_help(PREVIEW_TAG),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct SessionsCommand {
+    /// Name of the node to retrieve the sessions from
+    #[arg()]
+    node_name: Option<String>,
+
+    /// Keep running and print the status changes of the sessions
+    #[arg(long, short)]
+    follow: bool,
+}
+
+impl SessionsCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, SessionsCommand),
+) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_name);
+    let node = opts.background_node(&ctx, &node_name).await?;
+
+    if !cmd.follow {
+        let sessions: SessionList = node.ask(&ctx, Request::get("/node/sessions")).await?;
+        let list = opts.terminal.build_list(
+            &sessions.list,
+            &format!("Sessions on {node_name}"),
+            &format!("No sessions found on {node_name}."),
+        )?;
+        opts.terminal
+            .stdout()
+            .plain(list)
+            .json(serde_json::to_string_pretty(&sessions.list).into_diagnostic()?)
+            .write_line()?;
+        return Ok(());
+    }
+
+    // Only print the events which happen from now on
+    let mut last = next_events(&ctx, &node, None, Duration::ZERO).await?.last;
+    loop {
+        let events = next_events(&ctx, &node, Some(last), EVENTS_WAIT).await?;
+        for event in events.events.iter() {
+            opts.terminal
+                .clone()
+                .stdout()
+                .plain(event.output()?)
+                .json(serde_json::to_string(event).into_diagnostic()?)
+                .write_line()?;
+        }
+        // The sequence number goes back to 0 when the node is restarted
+        last = events.last;
+    }
+}
+
+async fn next_events(
+    ctx: &Context,
+    node: &BackgroundNode,
+    after: Option<u64>,
+    wait: Duration,
+) -> miette::Result<SessionEventList> {
+    node.ask_with_timeout(
+        ctx,
+        Request::get("/node/sessions/events").body(GetSessionEvents::new(after, Some(wait))),
+        wait + Duration::from_secs(10),
+    )
+    .await
+}
+
+fn recovery_time(ms: Option<u64>) -> String {
+    match ms {
+        Some(ms) => format!("{:.1}s", ms as f64 / 1000.0),
+        None => "n/a".to_string(),
+    }
+}
+
+impl Output for SessionStatus {
+    fn output(&self) -> Result<String> {
+        Ok(format!(
+            r#"Session {}:
+    Resource: {} {}
+    Route: {}
+    Status: {} since {}
+    Pending Pings: {}
+    Last Recovery Time: {}"#,
+            self.key,
+            self.kind,
+            self.name,
+            self.ping_route,
+            self.status,
+            human_readable_time(TimestampInSeconds(self.status_since)),
+            self.pending_pings,
+            recovery_time(self.last_recovery_time_ms),
+        ))
+    }
+
+    fn list_output(&self) -> Result<String> {
+        Ok(format!(
+            "{} {}\nStatus {} since {}, last recovery time {}",
+            self.kind,
+            self.name.clone().color(OckamColor::PrimaryResource.color()),
+            self.status
+                .clone()
+                .color(OckamColor::PrimaryResource.color()),
+            human_readable_time(TimestampInSeconds(self.status_since)),
+            recovery_time(self.last_recovery_time_ms),
+        ))
+    }
+}
+
+impl Output for SessionEvent {
+    fn output(&self) -> Result<String> {
+        let recovery = match self.recovery_time_ms {
+            Some(ms) => format!(", recovered in {}", recovery_time(Some(ms))),
+            None => String::new(),
+        };
+        Ok(format!(
+            "{} {} {} is {} (was {}){}",
+            human_readable_time(TimestampInSeconds(self.at)),
+            self.kind,
+            self.name.clone().color(OckamColor::PrimaryResource.color()),
+            self.status
+                .clone()
+                .color(OckamColor::PrimaryResource.color()),
+            self.previous_status,
+            recovery,
+        ))
+    }
+}
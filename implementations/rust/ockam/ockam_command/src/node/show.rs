@@ -8,7 +8,7 @@ use ockam_node::Context;
 use tokio_retry::strategy::FixedInterval;
 use tracing::{info, trace, warn};
 
-use ockam_api::cli_state::{CliState, StateDirTrait, StateItemTrait};
+use ockam_api::cli_state::{CliState, NodeResourceKind, StateDirTrait, StateItemTrait};
 use ockam_api::nodes::models::portal::{InletList, OutletList};
 
 use crate::node::get_node_name;
@@ -53,7 +53,7 @@ async fn run_impl(
     (opts, cmd): (CommandGlobalOpts, ShowCommand),
 ) -> miette::Result<()> {
     let node_name = get_node_name(&opts.state, &cmd.node_name);
-    let mut node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    let mut node = opts.background_node(&ctx, &node_name).await?;
     let is_default = check_default(&opts, &node_name);
     print_query_status(&opts, &ctx, &node_name, &mut node, false, is_default).await?;
     Ok(())
@@ -101,12 +101,24 @@ pub async fn print_query_status(
                 Err(_) => String::from("None"),
             });
 
+            // Resources which are recreated when the node restarts
+            let resources = node_state.resources()?;
+            let is_persisted = |kind: NodeResourceKind, name: &str| {
+                resources.iter().any(|r| r.kind == kind && r.name == name)
+            };
+
             // Get list of services for the node
             let services: ServiceList = node.ask(ctx, api::list_services()).await?;
             node_info.services = services
                 .list
                 .into_iter()
-                .map(ShowServiceStatus::from)
+                .map(|s| {
+                    let persisted = is_persisted(NodeResourceKind::KafkaService, &s.addr);
+                    ShowServiceStatus {
+                        persisted,
+                        ..s.into()
+                    }
+                })
                 .collect();
 
             // Get list of TCP listeners for node
@@ -123,19 +135,42 @@ pub async fn print_query_status(
             node_info.secure_channel_listeners = listeners
                 .list
                 .into_iter()
-                .map(ShowSecureChannelListener::from)
+                .map(|l| {
+                    let persisted =
+                        is_persisted(NodeResourceKind::SecureChannelListener, l.addr.address());
+                    ShowSecureChannelListener {
+                        persisted,
+                        ..l.into()
+                    }
+                })
                 .collect();
 
             // Get list of inlets
             let inlets: InletList = node.ask(ctx, api::list_inlets()).await?;
-            node_info.inlets = inlets.list.into_iter().map(ShowInletStatus::from).collect();
+            node_info.inlets = inlets
+                .list
+                .into_iter()
+                .map(|i| {
+                    let persisted = is_persisted(NodeResourceKind::TcpInlet, &i.alias);
+                    ShowInletStatus {
+                        persisted,
+                        ..i.into()
+                    }
+                })
+                .collect();
 
             // Get list of outlets
             let outlets: OutletList = node.ask(ctx, api::list_outlets()).await?;
             node_info.outlets = outlets
                 .list
                 .into_iter()
-                .map(ShowOutletStatus::from)
+                .map(|o| {
+                    let persisted = is_persisted(NodeResourceKind::TcpOutlet, &o.alias);
+                    ShowOutletStatus {
+                        persisted,
+                        ..o.into()
+                    }
+                })
                 .collect();
 
             node_info
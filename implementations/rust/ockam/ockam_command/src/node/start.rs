@@ -40,8 +40,9 @@ impl StartCommand {
 
 async fn run_impl(
     ctx: Context,
-    (mut opts, cmd): (CommandGlobalOpts, StartCommand),
+    (opts, cmd): (CommandGlobalOpts, StartCommand),
 ) -> miette::Result<()> {
+    opts.reject_remote()?;
     let node_name = get_node_name(&opts.state, &cmd.node_name);
 
     let node_state = opts.state.nodes.get(&node_name)?;
@@ -56,14 +57,27 @@ async fn run_impl(
             .write_line()?;
         return Ok(());
     }
+    start_node(&opts, &node_name)?;
+
+    // Print node status
+    let mut node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    let is_default = check_default(&opts, &node_name);
+    print_query_status(&opts, &ctx, &node_name, &mut node, true, is_default).await?;
+
+    Ok(())
+}
+
+/// Start a stopped node again, with the configuration it was created with
+pub(crate) fn start_node(opts: &CommandGlobalOpts, node_name: &str) -> miette::Result<()> {
+    let node_state = opts.state.nodes.get(node_name)?;
     node_state.kill_process(false)?;
     let node_setup = node_state.config().setup();
+    let mut opts = opts.clone();
     opts.global_args.verbose = node_setup.verbose;
 
-    // Restart node
     spawn_node(
         &opts,
-        &node_name,                                    // The selected node name
+        node_name,                                     // The selected node name
         &node_setup.api_transport()?.addr.to_string(), // The selected node api address
         None,                                          // No project information available
         None,                                          // No trusted identities
@@ -74,13 +88,15 @@ async fn run_impl(
         None,                                          // Credential
         None,                                          // Trust Context
         None,                                          // Project Name
+        None,                                          // WebSocket listener
+        None,                                          // UDS listener
+        node_setup.config_file.as_ref(),               // Keep watching the configuration file
+        &[],                                           // Credential schemas are kept in the setup
+        None,                                          // The relay ttl is kept in the setup
+        &[],                                           // Relay reservations are kept in the setup
+        node_setup.ephemeral,                          // Keep the node ephemeral
         true,                                          // Restarted nodes will log to files
     )?;
 
-    // Print node status
-    let mut node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
-    let is_default = check_default(&opts, &node_name);
-    print_query_status(&opts, &ctx, &node_name, &mut node, true, is_default).await?;
-
     Ok(())
 }
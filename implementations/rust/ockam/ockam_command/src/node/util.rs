@@ -2,11 +2,13 @@ use std::env::current_exe;
 use std::fs::OpenOptions;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
+use std::time::Duration;
 
 use miette::Context as _;
 use miette::{miette, IntoDiagnostic};
 use rand::random;
 
+use ockam::identity::Identifier;
 use ockam_api::cli_state::StateDirTrait;
 use ockam_core::env::get_env_with_default;
 
@@ -73,6 +75,13 @@ pub fn spawn_node(
     credential: Option<&String>,
     trust_context: Option<&PathBuf>,
     project_name: Option<&String>,
+    ws_listener_address: Option<&String>,
+    uds_listener_path: Option<&PathBuf>,
+    config_file: Option<&PathBuf>,
+    credential_schemas: &[PathBuf],
+    relay_ttl: Option<Duration>,
+    relay_reservations: &[(String, Identifier)],
+    ephemeral: bool,
     logging_to_file: bool,
 ) -> miette::Result<()> {
     let mut args = vec![
@@ -149,6 +158,52 @@ pub fn spawn_node(
         args.push(project_name.to_string());
     }
 
+    if let Some(address) = ws_listener_address {
+        args.push("--ws-listener-address".to_string());
+        args.push(address.to_string());
+    }
+
+    if let Some(path) = uds_listener_path {
+        args.push("--uds-listener-path".to_string());
+        args.push(
+            path.to_str()
+                .unwrap_or_else(|| panic!("unsupported path {path:?}"))
+                .to_string(),
+        );
+    }
+
+    if let Some(path) = config_file {
+        args.push("--config".to_string());
+        args.push(
+            path.to_str()
+                .unwrap_or_else(|| panic!("unsupported path {path:?}"))
+                .to_string(),
+        );
+    }
+
+    for path in credential_schemas {
+        args.push("--credential-schema".to_string());
+        args.push(
+            path.to_str()
+                .unwrap_or_else(|| panic!("unsupported path {path:?}"))
+                .to_string(),
+        );
+    }
+
+    if let Some(relay_ttl) = relay_ttl {
+        args.push("--relay-ttl".to_string());
+        args.push(format!("{}ms", relay_ttl.as_millis()));
+    }
+
+    for (prefix, identifier) in relay_reservations {
+        args.push("--relay-reserved-prefix".to_string());
+        args.push(format!("{prefix}={identifier}"));
+    }
+
+    if ephemeral {
+        args.push("--ephemeral".to_string());
+    }
+
     args.push(name.to_owned());
 
     run_ockam(opts, name, args, logging_to_file)
@@ -397,7 +397,7 @@ impl Output for VaultState {
     }
 }
 
-fn human_readable_time(time: TimestampInSeconds) -> String {
+pub(crate) fn human_readable_time(time: TimestampInSeconds) -> String {
     use time::format_description::well_known::iso8601::*;
     use time::Error::Format;
     use time::OffsetDateTime;
@@ -548,6 +548,35 @@ impl fmt::Display for CredentialDisplay {
             }))
             .finish()?;
 
+        if let Some(attribute_commitments) = &credential_data.attribute_commitments {
+            writeln!(f)?;
+            write!(f, "  Committed attributes: {}", attribute_commitments.len())?;
+        }
+
+        if let Some(delegation) = &credential_data.delegation {
+            writeln!(f)?;
+            writeln!(f, "Delegation: ")?;
+            write!(
+                f,
+                "  Max TTL: {}s; Max depth: {}; Attributes: ",
+                delegation.max_ttl.0, delegation.max_depth
+            )?;
+            f.debug_map()
+                .entries(delegation.attributes.iter().map(|c| {
+                    let value = match (&c.value, &c.value_prefix) {
+                        (Some(value), _) => std::str::from_utf8(value)
+                            .unwrap_or("**binary**")
+                            .to_string(),
+                        (None, Some(prefix)) => {
+                            format!("{}*", std::str::from_utf8(prefix).unwrap_or("**binary**"))
+                        }
+                        (None, None) => "*".to_string(),
+                    };
+                    (std::str::from_utf8(&c.key).unwrap_or("**binary**"), value)
+                }))
+                .finish()?;
+        }
+
         Ok(())
     }
 }
@@ -623,6 +652,18 @@ impl fmt::Display for CredentialAndPurposeKeyDisplay {
         // TODO: Could borrow using a lifetime
         writeln!(f, "Credential:")?;
         writeln!(f, "{}", CredentialDisplay(self.0.credential.clone()))?;
+        if let Some(disclosures) = &self.0.disclosures {
+            write!(f, "  Disclosed attributes: ")?;
+            f.debug_map()
+                .entries(disclosures.iter().map(|d| {
+                    (
+                        std::str::from_utf8(&d.key).unwrap_or("**binary**"),
+                        std::str::from_utf8(&d.value).unwrap_or("**binary**"),
+                    )
+                }))
+                .finish()?;
+            writeln!(f)?;
+        }
         writeln!(f)?;
         writeln!(f, "Purpose key:")?;
         writeln!(
@@ -631,6 +672,15 @@ impl fmt::Display for CredentialAndPurposeKeyDisplay {
             PurposeKeyDisplay(self.0.purpose_key_attestation.clone())
         )?;
 
+        if let Some(delegation) = &self.0.delegation {
+            writeln!(f, "Issuer delegation:")?;
+            writeln!(
+                f,
+                "{}",
+                CredentialAndPurposeKeyDisplay(delegation.as_ref().clone())
+            )?;
+        }
+
         Ok(())
     }
 }
@@ -698,6 +748,14 @@ impl fmt::Display for IdentityDisplay {
                 "    revoke_all_purpose_keys: {}",
                 change.data().revoke_all_purpose_keys
             )?;
+            if let Some(recovery_policy) = &change.data().recovery_policy {
+                writeln!(
+                    f,
+                    "    recovery_policy:         {} of {} recovery keys",
+                    recovery_policy.threshold,
+                    recovery_policy.recovery_keys.len()
+                )?;
+            }
         }
 
         Ok(())
@@ -1,35 +1,241 @@
 use crate::output::output::Output;
+use crate::output::Query;
 use crate::Result;
 use clap::ValueEnum;
+use cli_table::{Cell, Style, Table};
 use miette::{Context, IntoDiagnostic};
+use serde_json::Value;
 
-/// There are 2 available formats:
+/// There are 5 available formats:
 ///
 ///  - Plain formats a user readable string
 ///  - Json returns some prettified JSON
+///  - Yaml returns the same data as YAML
+///  - Toml returns the same data as TOML
+///  - Table displays the same data as a table, with one row per element of a list
 #[derive(Debug, Clone, ValueEnum, PartialEq, Eq)]
 pub enum OutputFormat {
     Plain,
     Json,
+    Yaml,
+    Toml,
+    Table,
+}
+
+/// Selection applied to the structured output of a command, before it is formatted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputFilter {
+    query: Option<Query>,
+    columns: Vec<String>,
+}
+
+impl OutputFilter {
+    pub fn new(query: Option<Query>, columns: Vec<String>) -> Self {
+        Self { query, columns }
+    }
+
+    /// Return true if the plain output must be replaced by the selected structured output
+    pub fn has_query(&self) -> bool {
+        self.query.is_some()
+    }
 }
 
 impl OutputFormat {
     /// Print a value on the console for any value having a textual Output and a JSON
     /// representation via serde
-    pub fn println_value<T>(&self, t: &T) -> Result<()>
+    pub fn println_value<T>(&self, t: &T, filter: &OutputFilter) -> Result<()>
     where
         T: Output + serde::Serialize,
     {
         let output = match self {
-            OutputFormat::Plain => t
+            OutputFormat::Plain if !filter.has_query() => t
                 .output()
                 .into_diagnostic()
                 .context("Failed to serialize output")?,
-            OutputFormat::Json => serde_json::to_string_pretty(t)
-                .into_diagnostic()
-                .context("Failed to serialize output")?,
+            _ => {
+                let value = serde_json::to_value(t)
+                    .into_diagnostic()
+                    .context("Failed to serialize output")?;
+                self.format_value(value, filter)?
+            }
         };
         println!("{output}");
         Ok(())
     }
+
+    /// Format the JSON output of a command, after applying the filter.
+    /// The JSON output is returned unchanged if it is not filtered.
+    pub fn format_json(&self, json: &str, filter: &OutputFilter) -> Result<String> {
+        if *self == OutputFormat::Json && !filter.has_query() {
+            return Ok(json.to_string());
+        }
+        let value: Value = serde_json::from_str(json)
+            .into_diagnostic()
+            .context("The JSON output of the command is invalid")?;
+        self.format_value(value, filter)
+    }
+
+    fn format_value(&self, value: Value, filter: &OutputFilter) -> Result<String> {
+        let value = match &filter.query {
+            Some(query) => query.apply(&value),
+            None => value,
+        };
+        let output = match self {
+            // A selected string is printed as is, so that it can be used by scripts
+            OutputFormat::Plain => match value {
+                Value::String(s) => s,
+                value => serde_json::to_string_pretty(&value).into_diagnostic()?,
+            },
+            OutputFormat::Json => serde_json::to_string_pretty(&value).into_diagnostic()?,
+            OutputFormat::Yaml => serde_yaml::to_string(&value)
+                .into_diagnostic()?
+                .trim_end()
+                .to_string(),
+            OutputFormat::Toml => format_toml(value)?,
+            OutputFormat::Table => format_table(&value, &filter.columns)?,
+        };
+        Ok(output)
+    }
+}
+
+/// A TOML document is a table and has no null values: the null values are omitted,
+/// a list is written as an array of tables named `items`, and any other value as is
+fn format_toml(value: Value) -> Result<String> {
+    let output = match without_nulls(value) {
+        Value::Null => String::new(),
+        value @ Value::Object(_) => toml::to_string_pretty(&value).into_diagnostic()?,
+        value @ Value::Array(_) => {
+            toml::to_string_pretty(&serde_json::json!({ "items": value })).into_diagnostic()?
+        }
+        value => toml::Value::try_from(value).into_diagnostic()?.to_string(),
+    };
+    Ok(output.trim_end().to_string())
+}
+
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, without_nulls(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .filter(|v| !v.is_null())
+                .map(without_nulls)
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// Display a list of objects as a table, with one row per object and one column per field.
+/// An object is displayed as a table with a single row, and any other value as a single cell.
+///
+/// If no columns are given, all the fields are displayed, in the order of their first occurrence.
+fn format_table(value: &Value, columns: &[String]) -> Result<String> {
+    let rows: Vec<&Value> = match value {
+        Value::Array(items) => items.iter().collect(),
+        value => vec![value],
+    };
+    if rows.is_empty() {
+        return Ok(String::new());
+    }
+
+    let columns: Vec<String> = if !columns.is_empty() {
+        columns.to_vec()
+    } else if rows.iter().all(|row| row.is_object()) {
+        let mut columns: Vec<String> = vec![];
+        for row in &rows {
+            for key in row.as_object().into_iter().flat_map(|o| o.keys()) {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+        columns
+    } else {
+        vec![]
+    };
+
+    let table = if columns.is_empty() {
+        rows.iter()
+            .map(|row| vec![table_cell(row).cell()])
+            .collect::<Vec<_>>()
+            .table()
+    } else {
+        rows.iter()
+            .map(|row| {
+                columns
+                    .iter()
+                    .map(|column| table_cell(row.get(column).unwrap_or(&Value::Null)).cell())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+            .table()
+            .title(
+                columns
+                    .iter()
+                    .map(|column| column.as_str().cell().bold(true)),
+            )
+    };
+    Ok(table.display()?.to_string())
+}
+
+/// Strings are displayed without quotes and nested values as compact JSON
+fn table_cell(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn format_filtered_json_output() {
+        let json = r#"[{"alias":"i1","bind_addr":"127.0.0.1:5000"},{"alias":"i2"}]"#;
+        let filter = OutputFilter::new(Some(Query::from_str(".[0].alias").unwrap()), vec![]);
+        assert_eq!(
+            OutputFormat::Plain.format_json(json, &filter).unwrap(),
+            "i1"
+        );
+        assert_eq!(
+            OutputFormat::Json.format_json(json, &filter).unwrap(),
+            "\"i1\""
+        );
+
+        let filter = OutputFilter::default();
+        assert_eq!(OutputFormat::Json.format_json(json, &filter).unwrap(), json);
+        assert_eq!(
+            OutputFormat::Yaml
+                .format_json(r#"{"alias":"i1","port":5000}"#, &filter)
+                .unwrap(),
+            "alias: i1\nport: 5000"
+        );
+        assert_eq!(
+            OutputFormat::Toml
+                .format_json(r#"{"alias":"i1","port":5000,"outlet":null}"#, &filter)
+                .unwrap(),
+            "alias = \"i1\"\nport = 5000"
+        );
+        let toml = OutputFormat::Toml.format_json(json, &filter).unwrap();
+        assert!(toml.starts_with("[[items]]"));
+        assert_eq!(toml.matches("[[items]]").count(), 2);
+        assert!(toml.contains("bind_addr = \"127.0.0.1:5000\""));
+
+        let filter = OutputFilter::new(None, vec!["bind_addr".to_string()]);
+        let table = OutputFormat::Table.format_json(json, &filter).unwrap();
+        assert!(table.contains("bind_addr"));
+        assert!(table.contains("127.0.0.1:5000"));
+        assert!(!table.contains("i1"));
+    }
 }
@@ -0,0 +1,184 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde_json::Value;
+
+/// A jq-style path selecting a part of the structured output of a command.
+///
+/// A query is a sequence of:
+///
+///  - `.key` or `."key"`: the value of a field of an object
+///  - `[N]`: the N-th element of an array, counted from the end if N is negative
+///  - `[]`: all the elements of an array, or all the values of an object
+///
+/// For example `.inlets[].alias` selects the aliases of all the inlets of a node.
+/// Missing fields and elements are selected as `null`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Query {
+    query: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Iterate,
+}
+
+impl Query {
+    /// Return the part of the value selected by the query
+    pub fn apply(&self, value: &Value) -> Value {
+        select(&self.segments, value)
+    }
+}
+
+fn select(segments: &[Segment], value: &Value) -> Value {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return value.clone(),
+    };
+    match segment {
+        Segment::Key(key) => select(rest, value.get(key).unwrap_or(&Value::Null)),
+        Segment::Index(index) => {
+            let element = value.as_array().and_then(|items| {
+                let index = if *index < 0 {
+                    items.len() as i64 + index
+                } else {
+                    *index
+                };
+                usize::try_from(index).ok().and_then(|i| items.get(i))
+            });
+            select(rest, element.unwrap_or(&Value::Null))
+        }
+        Segment::Iterate => {
+            let items: Vec<&Value> = match value {
+                Value::Array(items) => items.iter().collect(),
+                Value::Object(map) => map.values().collect(),
+                _ => return Value::Null,
+            };
+            // Nested iterations are flattened into a single list
+            let flatten = rest.contains(&Segment::Iterate);
+            let mut selected = vec![];
+            for item in items {
+                match select(rest, item) {
+                    Value::Array(values) if flatten => selected.extend(values),
+                    value => selected.push(value),
+                }
+            }
+            Value::Array(selected)
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| format!("Invalid query `{query}`: {reason}");
+        let trimmed = query.trim();
+        if !trimmed.starts_with('.') {
+            return Err(invalid("a query must start with `.`"));
+        }
+
+        let mut segments = vec![];
+        let mut chars = trimmed.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => match chars.peek() {
+                    None | Some('[') => {}
+                    Some('"') => {
+                        chars.next();
+                        let key: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                        segments.push(Segment::Key(key));
+                    }
+                    Some(_) => {
+                        let mut key = String::new();
+                        while let Some(c) = chars.peek() {
+                            if c.is_alphanumeric() || *c == '_' || *c == '-' {
+                                key.push(*c);
+                                chars.next();
+                            } else {
+                                break;
+                            }
+                        }
+                        if key.is_empty() {
+                            return Err(invalid("expected a field name after `.`"));
+                        }
+                        segments.push(Segment::Key(key));
+                    }
+                },
+                '[' => {
+                    let index: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                    let index = index.trim();
+                    if index.is_empty() {
+                        segments.push(Segment::Iterate);
+                    } else {
+                        let index = index
+                            .parse()
+                            .map_err(|_| invalid("an array index must be an integer"))?;
+                        segments.push(Segment::Index(index));
+                    }
+                }
+                _ => return Err(invalid(&format!("unexpected character `{c}`"))),
+            }
+        }
+
+        Ok(Query {
+            query: trimmed.to_string(),
+            segments,
+        })
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn query(q: &str) -> Query {
+        Query::from_str(q).unwrap()
+    }
+
+    #[test]
+    fn select_values() {
+        let value = json!({
+            "name": "n1",
+            "tcp-inlets": [
+                { "alias": "i1", "ports": [1, 2] },
+                { "alias": "i2", "ports": [3] },
+            ],
+        });
+
+        assert_eq!(query(".").apply(&value), value);
+        assert_eq!(query(".name").apply(&value), json!("n1"));
+        assert_eq!(query(".missing").apply(&value), Value::Null);
+        assert_eq!(query(".\"tcp-inlets\"[0].alias").apply(&value), json!("i1"));
+        assert_eq!(query(".tcp-inlets[-1].alias").apply(&value), json!("i2"));
+        assert_eq!(query(".tcp-inlets[5]").apply(&value), Value::Null);
+        assert_eq!(
+            query(".tcp-inlets[].alias").apply(&value),
+            json!(["i1", "i2"])
+        );
+        assert_eq!(
+            query(".tcp-inlets[].ports[]").apply(&value),
+            json!([1, 2, 3])
+        );
+        assert_eq!(query(".[]").apply(&json!({"a": 1})), json!([1]));
+    }
+
+    #[test]
+    fn reject_invalid_queries() {
+        assert!(Query::from_str("name").is_err());
+        assert!(Query::from_str(".name[x]").is_err());
+        assert!(Query::from_str(".name | length").is_err());
+        assert!(Query::from_str("..").is_err());
+    }
+}
@@ -10,7 +10,6 @@ use ockam::Context;
 use ockam_abac::Resource;
 use ockam_api::cli_state::StateDirTrait;
 use ockam_api::nodes::models::policy::{Expression, PolicyList};
-use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
 use crate::node::get_node_name;
@@ -48,7 +47,7 @@ async fn run_impl(ctx: &Context, opts: CommandGlobalOpts, cmd: ListCommand) -> m
         return Err(miette!("The node '{}' is not running", &node_name));
     }
 
-    let node = BackgroundNode::create(ctx, &opts.state, &node_name).await?;
+    let node = opts.background_node(ctx, &node_name).await?;
     let is_finished: Mutex<bool> = Mutex::new(false);
 
     let get_policies = async {
@@ -14,7 +14,6 @@ use ockam_api::address::extract_address_value;
 use ockam_api::is_local_node;
 use ockam_api::nodes::models::relay::RelayInfo;
 use ockam_api::nodes::service::relay::Relays;
-use ockam_api::nodes::BackgroundNode;
 use ockam_multiaddr::proto::Project;
 use ockam_multiaddr::{MultiAddr, Protocol};
 
@@ -58,7 +57,7 @@ impl CreateCommand {
     }
 }
 
-fn parse_at(input: &str) -> Result<MultiAddr> {
+pub(crate) fn parse_at(input: &str) -> Result<MultiAddr> {
     let mut at = input.to_string();
     if !input.contains('/') {
         at = format!("/node/{}", input);
@@ -97,7 +96,7 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> m
                 return Err(miette!("--authorized can not be used with project addresses").into());
             };
             info!("creating a relay at {} to {node_name}", cmd.at);
-            let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+            let node = opts.background_node(&ctx, &node_name).await?;
             node.create_relay(&ctx, &ma, Some(alias.clone()), cmd.authorized)
                 .await?
         };
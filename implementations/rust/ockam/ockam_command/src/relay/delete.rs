@@ -4,7 +4,6 @@ use miette::{miette, IntoDiagnostic};
 
 use ockam::Context;
 use ockam_api::nodes::models::relay::RelayInfo;
-use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
 use crate::node::get_node_name;
@@ -46,7 +45,7 @@ pub async fn run_impl(
     let relay_name = cmd.relay_name.clone();
     let at = get_node_name(&opts.state, &cmd.at);
     let node_name = parse_node_name(&at)?;
-    let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    let node = opts.background_node(&ctx, &node_name).await?;
 
     // Check if relay exists
     node.ask_and_get_reply::<_, RelayInfo>(
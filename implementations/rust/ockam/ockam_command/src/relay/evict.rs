@@ -0,0 +1,76 @@
+use clap::Args;
+use colorful::Colorful;
+
+use ockam::Context;
+use ockam_api::nodes::models::relay::RegisteredRelay;
+use ockam_core::api::Request;
+
+use crate::node::get_node_name;
+use crate::terminal::OckamColor;
+use crate::util::{node_rpc, parse_node_name};
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const AFTER_LONG_HELP: &str = include_str!("./static/evict/after_long_help.txt");
+
+/// Stop a Relay registered by another node at the relay service of a node
+#[derive(Clone, Debug, Args)]
+#[command(
+arg_required_else_help = false,
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct EvictCommand {
+    /// Name of the registered Relay to stop
+    #[arg(display_order = 900, required = true)]
+    relay_name: String,
+
+    /// Node at which the Relay is registered. If not provided, the default node will be used
+    #[arg(global = true, long, value_name = "NODE")]
+    pub at: Option<String>,
+
+    /// Confirm the eviction without prompting
+    #[arg(display_order = 901, long, short)]
+    yes: bool,
+}
+
+impl EvictCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+pub async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, EvictCommand),
+) -> miette::Result<()> {
+    let relay_name = cmd.relay_name.clone();
+    let at = get_node_name(&opts.state, &cmd.at);
+    let node_name = parse_node_name(&at)?;
+    let node = opts.background_node(&ctx, &node_name).await?;
+
+    if opts
+        .terminal
+        .confirmed_with_flag_or_prompt(cmd.yes, "Are you sure you want to evict this relay?")?
+    {
+        let relay: RegisteredRelay = node
+            .ask(&ctx, Request::delete(format!("/relays/{relay_name}")))
+            .await?;
+
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "Relay {} registered at Node {} has been evicted.",
+                relay
+                    .name()
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color()),
+                node_name
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color())
+            ))
+            .machine(relay.name())
+            .json(serde_json::json!({ "relay": { "name": relay.name(),
+                "node": node_name } }))
+            .write_line()?;
+    }
+    Ok(())
+}
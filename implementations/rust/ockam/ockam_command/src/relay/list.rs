@@ -1,6 +1,8 @@
 use clap::Args;
 use colorful::Colorful;
 use miette::{miette, IntoDiagnostic};
+use minicbor::Decode;
+use serde::Serialize;
 use tokio::sync::Mutex;
 use tokio::try_join;
 use tracing::trace;
@@ -8,14 +10,14 @@ use tracing::trace;
 use ockam::Context;
 use ockam_api::address::extract_address_value;
 use ockam_api::cli_state::StateDirTrait;
-use ockam_api::nodes::models::relay::RelayInfo;
-use ockam_api::nodes::BackgroundNode;
+use ockam_api::nodes::models::relay::{RegisteredRelay, RelayInfo};
 use ockam_core::api::Request;
 
 use crate::node::{get_node_name, initialize_node_if_default};
+use crate::output::Output;
 use crate::terminal::OckamColor;
 use crate::util::node_rpc;
-use crate::{docs, CommandGlobalOpts};
+use crate::{docs, CommandGlobalOpts, Result};
 
 const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/list/after_long_help.txt");
@@ -31,33 +33,75 @@ pub struct ListCommand {
     ///  List all the relays relaying traffic to the specified node
     #[arg(global = true, long, value_name = "NODE")]
     pub to: Option<String>,
+
+    /// List all the relays registered by other nodes at the relay service of the specified node
+    #[arg(global = true, long, value_name = "NODE", conflicts_with = "to")]
+    pub at: Option<String>,
 }
 
 impl ListCommand {
     pub fn run(self, options: CommandGlobalOpts) {
-        initialize_node_if_default(&options, &self.to);
+        initialize_node_if_default(&options, &self.node());
         node_rpc(run_impl, (options, self));
     }
+
+    fn node(&self) -> Option<String> {
+        self.at.clone().or_else(|| self.to.clone())
+    }
 }
 
 async fn run_impl(
     ctx: Context,
     (opts, cmd): (CommandGlobalOpts, ListCommand),
 ) -> miette::Result<()> {
-    let to = get_node_name(&opts.state, &cmd.to);
-    let node_name = extract_address_value(&to)?;
+    let node = get_node_name(&opts.state, &cmd.node());
+    let node_name = extract_address_value(&node)?;
 
     if !opts.state.nodes.get(&node_name)?.is_running() {
         return Err(miette!("The node '{}' is not running", node_name));
     }
 
-    let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    if cmd.at.is_some() {
+        list_relays::<RegisteredRelay>(
+            &ctx,
+            &opts,
+            &node_name,
+            "/relays",
+            &format!("Relays registered at Node {node_name}"),
+            &format!("No Relays registered at node {node_name}."),
+        )
+        .await
+    } else {
+        list_relays::<RelayInfo>(
+            &ctx,
+            &opts,
+            &node_name,
+            "/node/forwarder",
+            &format!("Relays on Node {node_name}"),
+            &format!("No Relays found on node {node_name}."),
+        )
+        .await
+    }
+}
+
+async fn list_relays<T>(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    path: &str,
+    header: &str,
+    empty_message: &str,
+) -> miette::Result<()>
+where
+    T: Output + Serialize + std::fmt::Debug + for<'b> Decode<'b, ()>,
+{
+    let node = opts.background_node(ctx, node_name).await?;
     let is_finished: Mutex<bool> = Mutex::new(false);
 
     let get_relays = async {
-        let relay_infos: Vec<RelayInfo> = node.ask(&ctx, Request::get("/node/forwarder")).await?;
+        let relays: Vec<T> = node.ask(ctx, Request::get(path)).await?;
         *is_finished.lock().await = true;
-        Ok(relay_infos)
+        Ok(relays)
     };
 
     let output_messages = vec![format!(
@@ -74,11 +118,7 @@ async fn run_impl(
     let (relays, _) = try_join!(get_relays, progress_output)?;
     trace!(?relays, "Relays retrieved");
 
-    let plain = opts.terminal.build_list(
-        &relays,
-        &format!("Relays on Node {node_name}"),
-        &format!("No Relays found on node {node_name}."),
-    )?;
+    let plain = opts.terminal.build_list(&relays, header, empty_message)?;
     let json = serde_json::to_string_pretty(&relays).into_diagnostic()?;
 
     opts.terminal
@@ -88,3 +128,41 @@ async fn run_impl(
         .write_line()?;
     Ok(())
 }
+
+impl Output for RegisteredRelay {
+    fn output(&self) -> Result<String> {
+        Ok(format!(
+            r#"Relay {}:
+    Route: {}
+    Owner: {}
+    Created At: {}
+    Last Seen: {}
+    Messages Forwarded: {}
+    Bytes Forwarded: {}"#,
+            self.name(),
+            self.forward_route(),
+            self.owner()
+                .map(|o| o.to_string())
+                .unwrap_or("<none>".into()),
+            self.created_at(),
+            self.last_seen(),
+            self.messages_forwarded(),
+            self.bytes_forwarded(),
+        ))
+    }
+
+    fn list_output(&self) -> Result<String> {
+        Ok(format!(
+            r#"Relay {}
+Owner {}
+Last Seen {}, {} messages forwarded"#,
+            self.name().color(OckamColor::PrimaryResource.color()),
+            self.owner()
+                .map(|o| o.to_string())
+                .unwrap_or("<none>".into())
+                .color(OckamColor::PrimaryResource.color()),
+            self.last_seen(),
+            self.messages_forwarded(),
+        ))
+    }
+}
@@ -2,13 +2,15 @@ use clap::{Args, Subcommand};
 
 pub(crate) use create::CreateCommand;
 pub(crate) use delete::DeleteCommand;
+use evict::EvictCommand;
 pub(crate) use list::ListCommand;
 pub(crate) use show::ShowCommand;
 
 use crate::{docs, CommandGlobalOpts};
 
-mod create;
+pub(crate) mod create;
 mod delete;
+mod evict;
 mod list;
 mod show;
 
@@ -34,6 +36,7 @@ pub enum RelaySubCommand {
     List(ListCommand),
     Show(ShowCommand),
     Delete(DeleteCommand),
+    Evict(EvictCommand),
 }
 
 impl RelayCommand {
@@ -43,6 +46,7 @@ impl RelayCommand {
             RelaySubCommand::List(c) => c.run(opts),
             RelaySubCommand::Show(c) => c.run(opts),
             RelaySubCommand::Delete(c) => c.run(opts),
+            RelaySubCommand::Evict(c) => c.run(opts),
         }
     }
 }
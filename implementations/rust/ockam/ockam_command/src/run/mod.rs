@@ -1,18 +1,26 @@
 mod parser;
+mod plan;
+mod resources;
+mod watch;
 
 use crate::util::node_rpc;
 use crate::{docs, CommandGlobalOpts};
-use clap::Args;
+use clap::{Args, ValueEnum};
 use miette::Context as _;
 use miette::{miette, IntoDiagnostic};
 use ockam::Context;
-pub use parser::ConfigRunner;
+pub use parser::{ConfigRunner, NodeConfigFile};
 use std::path::PathBuf;
+pub use watch::{NodeConfigWatcher, NodeConfigWatcherHandle};
 
-/// Create nodes given a declarative configuration file
+/// Create, update or delete nodes given a declarative configuration file
 #[derive(Clone, Debug, Args)]
 #[command(hide = docs::hide())]
 pub struct RunCommand {
+    /// What to do with the nodes of the recipe
+    #[arg(value_enum, default_value_t = RunAction::Apply)]
+    pub action: RunAction,
+
     /// Path to the recipe file
     #[arg(long, conflicts_with = "inline")]
     pub recipe: Option<PathBuf>,
@@ -21,11 +29,24 @@ pub struct RunCommand {
     #[arg(long, conflicts_with = "recipe")]
     pub inline: Option<String>,
 
-    /// If true, block until all the created node exits it also
-    /// propagate signals to created nodes.
+    /// If true, block until all the nodes of the recipe exit. The nodes are
+    /// stopped when the command is interrupted.
     /// To be used with docker or kubernetes.
-    #[arg(long)]
+    #[arg(long, conflicts_with = "plan")]
     pub blocking: bool,
+
+    /// Only print the changes which would be made to the nodes
+    #[arg(long)]
+    pub plan: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RunAction {
+    /// Create the nodes and resources of the recipe, update the ones which changed
+    /// and delete the resources which are not declared anymore
+    Apply,
+    /// Delete the nodes of the recipe
+    Down,
 }
 
 impl RunCommand {
@@ -34,11 +55,12 @@ impl RunCommand {
     }
 }
 
-async fn rpc(_ctx: Context, (opts, cmd): (CommandGlobalOpts, RunCommand)) -> miette::Result<()> {
-    run_impl(opts, cmd).await
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, RunCommand)) -> miette::Result<()> {
+    run_impl(&ctx, opts, cmd).await
 }
 
-async fn run_impl(opts: CommandGlobalOpts, cmd: RunCommand) -> miette::Result<()> {
+async fn run_impl(ctx: &Context, opts: CommandGlobalOpts, cmd: RunCommand) -> miette::Result<()> {
+    opts.reject_remote()?;
     let config = match cmd.inline {
         Some(config) => config,
         None => {
@@ -70,5 +92,10 @@ async fn run_impl(opts: CommandGlobalOpts, cmd: RunCommand) -> miette::Result<()
             std::fs::read_to_string(path).into_diagnostic()?
         }
     };
-    ConfigRunner::go(opts, &config, cmd.blocking).await
+    let runner = ConfigRunner::parse(&config)?;
+    match (cmd.action, cmd.plan) {
+        (RunAction::Apply, true) => runner.plan(ctx, &opts).await,
+        (RunAction::Apply, false) => runner.apply(ctx, &opts, cmd.blocking).await,
+        (RunAction::Down, plan) => runner.down(&opts, plan),
+    }
 }
@@ -1,48 +1,75 @@
-use crate::{shutdown, CommandGlobalOpts};
-use duct::Expression;
-use miette::IntoDiagnostic;
-use ockam_api::cli_state::StateDirTrait;
-use ockam_core::compat::collections::HashMap;
-use once_cell::sync::Lazy;
-use serde::Deserialize;
-use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::fmt::Debug;
-use tracing::debug;
+use std::str::FromStr;
+
+use miette::{miette, IntoDiagnostic};
+use serde::Deserialize;
 
+use ockam::Context;
+use ockam_abac::{Action, Expr, Resource as PolicyResource};
+use ockam_api::address::extract_address_value;
+use ockam_api::bootstrapped_identities_store::PreTrustedIdentities;
+use ockam_api::port_range::PortRange;
+use ockam_core::Address;
+use ockam_multiaddr::MultiAddr;
+
+use crate::kafka::{
+    kafka_default_consumer_port_range, kafka_default_consumer_server, kafka_default_outlet_server,
+    kafka_default_producer_port_range, kafka_default_producer_server, kafka_default_project_route,
+};
+use crate::relay::create::parse_at;
+use crate::run::resources::{DeclaredResource, KafkaServiceType, ResourceKind, ResourceSpec};
+use crate::util::parsers::{identity_identifier_parser, socket_addr_parser};
+use crate::CommandGlobalOpts;
+
+/// The nodes declared in a recipe, sorted so that each node comes after the
+/// node it depends on
 pub struct ConfigRunner {
-    commands_sorted: Vec<ParsedCommand>,
-    commands_index: BTreeMap<String, usize>,
+    pub(super) nodes: Vec<NodeSpec>,
+    nodes_index: BTreeSet<String>,
 }
 
-#[derive(Clone)]
-pub struct ParsedCommand {
-    pub id: String,
+/// A node declared in a recipe, with the resources it must have
+#[derive(Clone, Debug)]
+pub struct NodeSpec {
+    pub name: String,
     pub depends_on: Option<String>,
-    pub cmd: Expression,
-    pub block_on_node: Option<String>,
+    pub enrollment_ticket: Option<String>,
+    pub trust_context: Option<String>,
+    pub resources: Vec<DeclaredResource>,
 }
 
 impl ConfigRunner {
     fn new() -> Self {
         Self {
-            commands_sorted: vec![],
-            commands_index: Default::default(),
+            nodes: vec![],
+            nodes_index: Default::default(),
         }
     }
 
-    pub async fn go(opts: CommandGlobalOpts, config: &str, blocking: bool) -> miette::Result<()> {
+    /// Parse a recipe
+    pub fn parse(config: &str) -> miette::Result<Self> {
         let mut cr = Self::new();
-        cr.parse(config, blocking)?;
-        cr.run(opts).await?;
-        Ok(())
+        cr.parse_config(config)?;
+        Ok(cr)
+    }
+
+    /// Apply a recipe to the nodes, as `ockam run` does
+    pub async fn go(
+        ctx: &Context,
+        opts: CommandGlobalOpts,
+        config: &str,
+        blocking: bool,
+    ) -> miette::Result<()> {
+        Self::parse(config)?.apply(ctx, &opts, blocking).await
     }
 
-    fn parse(&mut self, config: &str, blocking: bool) -> miette::Result<()> {
+    fn parse_config(&mut self, config: &str) -> miette::Result<()> {
         let config: Config = serde_yaml::from_str(config).into_diagnostic()?;
         let mut visited = HashSet::new();
         let mut nodes = VecDeque::new();
         for (name, node) in config.nodes {
-            nodes.push_back((name, node));
+            nodes.push_back((name, node.unwrap_or_default()));
         }
         while let Some((name, node)) = nodes.pop_front() {
             // If the node depends on another node, check if that node has been parsed.
@@ -50,7 +77,7 @@ impl ConfigRunner {
                 // If the dependency has been visited already but not
                 // parsed, we have a circular dependency.
                 if visited.contains(depends_on) {
-                    return Err(miette::miette!(
+                    return Err(miette!(
                         "Circular dependency detected: {} -> {}",
                         depends_on,
                         name
@@ -58,18 +85,12 @@ impl ConfigRunner {
                 }
                 // If the dependency has been parsed, remove it from the control
                 // vector and proceed with the current node.
-                if self
-                    .commands_index
-                    .contains_key(&format!("node/{depends_on}"))
-                {
+                if self.nodes_index.contains(depends_on) {
                     visited.remove(depends_on);
                 }
                 // If the dependency has not been parsed, push the current
                 // node back to the queue and continue with the next one.
-                if !self
-                    .commands_index
-                    .contains_key(&format!("node/{depends_on}"))
-                {
+                if !self.nodes_index.contains(depends_on) {
                     visited.insert(name.clone());
                     nodes.push_back((name, node));
                     continue;
@@ -77,68 +98,10 @@ impl ConfigRunner {
             }
             // Remove it from the control vector and parse it.
             visited.remove(&name);
-            node.parse(&name, blocking, self)?;
-        }
-        Ok(())
-    }
-
-    async fn run(self, opts: CommandGlobalOpts) -> miette::Result<()> {
-        let mut spawned_nodes = vec![];
-        let mut handlers = vec![];
-
-        for command in self.commands_sorted.into_iter() {
-            debug!("Running command: {}: {:?}", command.id, command.cmd);
-
-            // If a command fails it will show the appropriate error in its subshell.
-            // No need to return an error here.
-            if let Some(spawn_node) = command.block_on_node {
-                let result = command.cmd.start();
-                match result {
-                    Ok(handle) => {
-                        handlers.push(handle);
-                        spawned_nodes.push(spawn_node);
-                    }
-                    Err(_err) => {
-                        break;
-                    }
-                }
-
-                // the next command will expect the node to be up and running
-                //TODO: wait for the node availability rather than sleeping
-                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-            } else if command.cmd.run().is_err() {
-                break;
-            }
+            let node = node.parse(&name)?;
+            self.nodes_index.insert(name);
+            self.nodes.push(node);
         }
-
-        if !spawned_nodes.is_empty() {
-            // Create a channel for communicating back to the main thread
-            let (tx, mut rx) = tokio::sync::mpsc::channel(2);
-
-            // Spawn a thread to trigger shutdown when all nodes are done
-            {
-                let tx = tx.clone();
-                tokio::spawn(async move {
-                    while let Some(handle) = handlers.pop() {
-                        let _ = handle.wait();
-                    }
-                    let _ = tx.send(()).await;
-                });
-            }
-
-            // Wait for CTRL+C or any other exit condition (like receiving a signal)
-            shutdown::wait(opts.terminal, true, true, tx, &mut rx).await?;
-
-            // Send a SIGTERM to all nodes if they are still running
-            for node_name in spawned_nodes {
-                if let Ok(node) = opts.state.nodes.get(node_name) {
-                    if node.is_running() {
-                        let _ = node.kill_process(false);
-                    }
-                }
-            }
-        }
-
         Ok(())
     }
 }
@@ -147,7 +110,7 @@ impl ConfigRunner {
 /// ```yml
 /// nodes:
 ///   telegraf:
-///     enrollment-token: $OCKAM_TELEGRAF_TOKEN
+///     enrollment-ticket: $OCKAM_TELEGRAF_TICKET
 ///     tcp-inlets:
 ///       telegraf:
 ///         from: '127.0.0.1:8087'
@@ -155,7 +118,7 @@ impl ConfigRunner {
 ///         access_control: '(= subject.component "influxdb")'
 ///
 ///   influxdb:
-///     enrollment-token: $OCKAM_INFLUXDB_TOKEN
+///     enrollment-ticket: $OCKAM_INFLUXDB_TICKET
 ///     tcp-outlets:
 ///       influxdb:
 ///         from: /service/outlet
@@ -164,183 +127,289 @@ impl ConfigRunner {
 ///     relays:
 ///       influxdb:
 ///         at: /project/default
+///
+///   kafka:
+///     trust-context: my_trust_context
+///     secure-channel-listeners:
+///       kafka_listener:
+///         authorized: [I0123456789abcdef0123456789abcdef01234567]
+///     policies:
+///       - resource: kafka-consumer
+///         expression: '(= subject.component "kafka")'
+///     kafka-services:
+///       kafka_consumer:
+///         type: consumer
+///         bootstrap-server: '127.0.0.1:4000'
+///         brokers-port-range: 4001-4100
+///         project-route: /project/default
 /// ```
 #[derive(Debug, Deserialize)]
 pub struct Config {
-    pub nodes: HashMap<String, NodeConfig>,
+    pub nodes: BTreeMap<String, Option<NodeConfig>>,
 }
 
 /// Defines the structure of a node in the config file.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct NodeConfig {
     #[serde(rename(deserialize = "depends-on"))]
     pub depends_on: Option<String>,
     #[serde(rename(deserialize = "enrollment-ticket"))]
     pub enrollment_ticket: Option<String>,
+    #[serde(rename(deserialize = "trust-context"))]
+    pub trust_context: Option<String>,
+    #[serde(rename(deserialize = "secure-channel-listeners"))]
+    pub secure_channel_listeners: Option<BTreeMap<String, Option<SecureChannelListenerConfig>>>,
+    pub policies: Option<Vec<PolicyConfig>>,
     #[serde(rename(deserialize = "tcp-inlets"))]
-    pub tcp_inlets: Option<HashMap<String, InletConfig>>,
+    pub tcp_inlets: Option<BTreeMap<String, InletConfig>>,
     #[serde(rename(deserialize = "tcp-outlets"))]
-    pub tcp_outlets: Option<HashMap<String, OutletConfig>>,
-    pub relays: Option<HashMap<String, RelayConfig>>,
+    pub tcp_outlets: Option<BTreeMap<String, OutletConfig>>,
+    pub relays: Option<BTreeMap<String, RelayConfig>>,
+    #[serde(rename(deserialize = "kafka-services"))]
+    pub kafka_services: Option<BTreeMap<String, KafkaServiceConfig>>,
 }
 
 impl NodeConfig {
-    fn parse(self, node_name: &str, blocking: bool, cmds: &mut ConfigRunner) -> miette::Result<()> {
-        let mut insert_command =
-            |subject: &str, name: &str, depends_on, args: &[&str], blocks: bool| {
-                debug!("Parsed command: {} {}", binary_path(), args.join(" "));
-                let cmd = duct::cmd(binary_path(), args);
-                let id = format!("{subject}/{name}");
-                if cmds.commands_index.contains_key(&id) {
-                    return Err(miette::miette!(
-                        "There can't be {}s with the same name: {}",
-                        subject,
-                        name
-                    ));
-                }
-                cmds.commands_index
-                    .insert(id.clone(), cmds.commands_sorted.len());
-
-                let block_on_node = if blocks {
-                    Some(node_name.to_string())
-                } else {
-                    None
-                };
-
-                cmds.commands_sorted.push(ParsedCommand {
-                    id,
-                    depends_on,
-                    cmd,
-                    block_on_node,
-                });
-                Ok(())
+    fn parse(self, node_name: &str) -> miette::Result<NodeSpec> {
+        let mut resources = vec![];
+
+        for (name, listener) in self.secure_channel_listeners.unwrap_or_default() {
+            let listener = listener.unwrap_or_default();
+            let address = Address::from_str(&name)
+                .map_err(|_| miette!("Invalid secure channel listener address: {name}"))?;
+            let authorized = match listener.authorized {
+                Some(identifiers) => Some(
+                    identifiers
+                        .iter()
+                        .map(|i| identity_identifier_parser(i))
+                        .collect::<crate::Result<Vec<_>>>()?,
+                ),
+                None => None,
             };
-
-        // Always enroll since it's an idempotent operation.
-        // The trust context is named after the node.
-        if let Some(enroll_ticket) = &self.enrollment_ticket {
-            insert_command(
-                "node",
-                &format!("{}/enroll", node_name),
-                None,
-                &[
-                    "project",
-                    "enroll",
-                    "--new-trust-context-name",
-                    node_name,
-                    enroll_ticket,
-                ],
-                false,
-            )?;
+            resources.push(DeclaredResource::new(
+                ResourceKind::SecureChannelListener,
+                name,
+                ResourceSpec::SecureChannelListener {
+                    address,
+                    authorized,
+                    identity: listener.identity,
+                },
+            ));
         }
 
-        // Always create the node, if it already exists (but not running) it'll be-started.
-        let args = {
-            let mut args = vec!["node", "create", node_name];
-            if blocking {
-                args.push("--foreground");
-            }
-            if self.enrollment_ticket.is_some() {
-                args.push("--trust-context");
-                args.push(node_name);
+        // Policies apply to all the resources of a given type. Each policy can be
+        // declared several times, but only with the same expression.
+        let mut policies: BTreeMap<String, DeclaredResource> = BTreeMap::new();
+        let mut add_policy = |resource: &str, action: &str, expression: &str| {
+            let resource = PolicyResource::new(resource);
+            let action = Action::new(action);
+            let expression = Expr::from_str(expression)
+                .map_err(|e| miette!("Invalid policy expression `{expression}`: {e}"))?;
+            let policy = DeclaredResource::new(
+                ResourceKind::Policy,
+                format!("{resource}/{action}"),
+                ResourceSpec::Policy {
+                    resource,
+                    action,
+                    expression,
+                },
+            );
+            if let Some(declared) = policies.get(&policy.name) {
+                if declared.attributes != policy.attributes {
+                    return Err(miette!(
+                        "The node {} declares different policies for {}. \
+                        Access controls apply to all the tcp-inlets, or all the tcp-outlets, of a node",
+                        node_name,
+                        policy.name
+                    ));
+                }
             }
-            args
+            policies.insert(policy.name.clone(), policy);
+            Ok(())
         };
-        insert_command(
-            "node",
-            node_name,
-            self.depends_on.map(|s| format!("node/{s}")),
-            &args,
-            blocking,
-        )?;
-
-        // TODO: all commands should support both `/node/{name}` and `{name}` formats.
-        let node_name_formatted = format!("/node/{node_name}");
-
-        if let Some(tcp_inlets) = &self.tcp_inlets {
-            for (name, inlet) in tcp_inlets {
-                // TODO: store inlets in CliState; Then check if the inlet already exists. If it doesn't, create it.
-                if let Some(exp) = &inlet.access_control {
-                    let args = &[
-                        "policy",
-                        "create",
-                        "--at",
-                        &node_name_formatted,
-                        "--resource",
-                        "tcp-inlet",
-                        "--expression",
-                        exp,
-                    ];
-                    insert_command("policy", name, None, args, false)?;
-                }
-                let args = &[
-                    "tcp-inlet",
-                    "create",
-                    "--at",
-                    &node_name_formatted,
-                    "--from",
-                    &inlet.from,
-                    "--to",
-                    &inlet.to,
-                    "--alias",
-                    name,
-                ];
-                insert_command("inlet", name, None, args, false)?;
+        for policy in self.policies.iter().flatten() {
+            add_policy(
+                &policy.resource,
+                policy.action.as_deref().unwrap_or("handle_message"),
+                &policy.expression,
+            )?;
+        }
+        for inlet in self.tcp_inlets.iter().flat_map(|i| i.values()) {
+            if let Some(expression) = &inlet.access_control {
+                add_policy("tcp-inlet", "handle_message", expression)?;
             }
         }
+        for outlet in self.tcp_outlets.iter().flat_map(|o| o.values()) {
+            if let Some(expression) = &outlet.access_control {
+                add_policy("tcp-outlet", "handle_message", expression)?;
+            }
+        }
+        resources.extend(policies.into_values());
+
+        for (name, outlet) in self.tcp_outlets.unwrap_or_default() {
+            let from = extract_address_value(&outlet.from)
+                .into_diagnostic()?
+                .parse::<Address>()
+                .map_err(|_| miette!("Invalid outlet address: {}", outlet.from))?;
+            resources.push(DeclaredResource::new(
+                ResourceKind::TcpOutlet,
+                name,
+                ResourceSpec::TcpOutlet {
+                    from,
+                    to: socket_addr_parser(&outlet.to)?,
+                },
+            ));
+        }
+
+        for (name, relay) in self.relays.unwrap_or_default() {
+            resources.push(DeclaredResource::new(
+                ResourceKind::Relay,
+                name,
+                ResourceSpec::Relay {
+                    at: parse_at(&relay.at)?,
+                },
+            ));
+        }
+
+        for (name, inlet) in self.tcp_inlets.unwrap_or_default() {
+            resources.push(DeclaredResource::new(
+                ResourceKind::TcpInlet,
+                name,
+                ResourceSpec::TcpInlet {
+                    from: socket_addr_parser(&inlet.from)?,
+                    to: MultiAddr::from_str(&inlet.to)
+                        .map_err(|_| miette!("Invalid inlet route: {}", inlet.to))?,
+                },
+            ));
+        }
 
-        if let Some(tcp_outlets) = &self.tcp_outlets {
-            for (name, outlet) in tcp_outlets {
-                // TODO: store outlets in CliState; Then check if the outlet already exists. If it doesn't, create it.
-                if let Some(exp) = &outlet.access_control {
-                    let args = &[
-                        "policy",
-                        "create",
-                        "--at",
-                        &node_name_formatted,
-                        "--resource",
-                        "tcp-outlet",
-                        "--expression",
-                        exp,
-                    ];
-                    insert_command("policy", name, None, args, false)?;
+        for (name, service) in self.kafka_services.unwrap_or_default() {
+            let (bootstrap_server, brokers_port_range) = match service.service_type {
+                KafkaServiceType::Outlet => (
+                    kafka_default_outlet_server(),
+                    kafka_default_consumer_port_range(),
+                ),
+                KafkaServiceType::Consumer => (
+                    kafka_default_consumer_server(),
+                    kafka_default_consumer_port_range(),
+                ),
+                KafkaServiceType::Producer => (
+                    kafka_default_producer_server(),
+                    kafka_default_producer_port_range(),
+                ),
+            };
+            let bootstrap_server = match &service.bootstrap_server {
+                Some(s) => socket_addr_parser(s)?,
+                None => bootstrap_server,
+            };
+            let brokers_port_range = match &service.brokers_port_range {
+                Some(r) => PortRange::from_str(r)
+                    .map_err(|_| miette!("Invalid brokers port range: {r}"))?,
+                None => brokers_port_range,
+            };
+            let project_route = match &service.project_route {
+                Some(r) => {
+                    MultiAddr::from_str(r).map_err(|_| miette!("Invalid project route: {r}"))?
                 }
-                let args = &[
-                    "tcp-outlet",
-                    "create",
-                    "--at",
-                    &node_name_formatted,
-                    "--from",
-                    &outlet.from,
-                    "--to",
-                    &outlet.to,
-                    "--alias",
-                    name,
-                ];
-                insert_command("outlet", name, None, args, false)?;
-            }
+                None => kafka_default_project_route(),
+            };
+            resources.push(DeclaredResource::new(
+                ResourceKind::KafkaService,
+                name,
+                ResourceSpec::KafkaService {
+                    service_type: service.service_type,
+                    bootstrap_server,
+                    brokers_port_range,
+                    project_route,
+                },
+            ));
         }
 
-        if let Some(relays) = &self.relays {
-            for (name, relay) in relays {
-                // TODO: store relays in CliState; Then check if the relay already exists. If it doesn't, create it.
-                let args = &[
-                    "relay",
-                    "create",
-                    name,
-                    "--to",
-                    &node_name_formatted,
-                    "--at",
-                    &relay.at,
-                ];
-                insert_command("relay", name, None, args, false)?;
-            }
+        resources.sort_by(|r1, r2| (r1.kind, &r1.name).cmp(&(r2.kind, &r2.name)));
+        Ok(NodeSpec {
+            name: node_name.to_string(),
+            depends_on: self.depends_on,
+            enrollment_ticket: self.enrollment_ticket,
+            trust_context: self.trust_context,
+            resources,
+        })
+    }
+}
+
+/// The configuration file of a node created with `ockam node create --config`.
+///
+/// It declares the resources of the node, with the same structure as a node of a recipe,
+/// and the identities trusted by the node, with their attributes:
+/// ```yml
+/// trusted-identities:
+///   I0123456789abcdef0123456789abcdef01234567:
+///     component: telegraf
+/// tcp-outlets:
+///   influxdb:
+///     from: /service/outlet
+///     to: '127.0.0.1:8086'
+///     access_control: '(= subject.component "telegraf")'
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct NodeConfigFile {
+    #[serde(rename(deserialize = "trusted-identities"))]
+    pub trusted_identities: Option<BTreeMap<String, BTreeMap<String, String>>>,
+    #[serde(flatten)]
+    pub node: NodeConfig,
+}
+
+/// The resources and trusted identities declared in a node configuration file
+#[derive(Clone, Debug)]
+pub struct NodeConfigSpec {
+    pub node: NodeSpec,
+    /// Trusted identities, in the JSON format read by the node
+    pub trusted_identities: String,
+}
+
+impl NodeConfigFile {
+    /// Parse the configuration file of the given node
+    pub fn parse(node_name: &str, contents: &str) -> miette::Result<NodeConfigSpec> {
+        let config: Self = serde_yaml::from_str(contents).into_diagnostic()?;
+        let node = config.node;
+        if node.depends_on.is_some()
+            || node.enrollment_ticket.is_some()
+            || node.trust_context.is_some()
+        {
+            return Err(miette!(
+                "The depends-on, enrollment-ticket and trust-context entries can only be used in a recipe run with `ockam run`"
+            ));
         }
 
-        Ok(())
+        let mut trusted_identities = BTreeMap::new();
+        for (identifier, attributes) in config.trusted_identities.unwrap_or_default() {
+            let identifier = identity_identifier_parser(&identifier)?;
+            trusted_identities.insert(identifier.to_string(), attributes);
+        }
+        let trusted_identities = serde_json::to_string(&trusted_identities).into_diagnostic()?;
+        PreTrustedIdentities::new_from_string(&trusted_identities)?;
+
+        Ok(NodeConfigSpec {
+            node: node.parse(node_name)?,
+            trusted_identities,
+        })
     }
 }
 
+/// Defines the structure of a secure channel listener in the config file.
+#[derive(Debug, Default, Deserialize)]
+pub struct SecureChannelListenerConfig {
+    pub authorized: Option<Vec<String>>,
+    pub identity: Option<String>,
+}
+
+/// Defines the structure of a policy in the config file.
+#[derive(Debug, Deserialize)]
+pub struct PolicyConfig {
+    pub resource: String,
+    pub action: Option<String>,
+    pub expression: String,
+}
+
 /// Defines the structure of a tcp inlet in the config file.
 #[derive(Debug, Deserialize)]
 pub struct InletConfig {
@@ -363,14 +432,17 @@ pub struct RelayConfig {
     pub at: String,
 }
 
-static BINARY_PATH: Lazy<String> = Lazy::new(|| {
-    std::env::args()
-        .next()
-        .expect("Failed to get the binary path")
-});
-
-fn binary_path() -> &'static str {
-    &BINARY_PATH
+/// Defines the structure of a kafka service in the config file.
+#[derive(Debug, Deserialize)]
+pub struct KafkaServiceConfig {
+    #[serde(rename(deserialize = "type"))]
+    pub service_type: KafkaServiceType,
+    #[serde(rename(deserialize = "bootstrap-server"))]
+    pub bootstrap_server: Option<String>,
+    #[serde(rename(deserialize = "brokers-port-range"))]
+    pub brokers_port_range: Option<String>,
+    #[serde(rename(deserialize = "project-route"))]
+    pub project_route: Option<String>,
 }
 
 #[cfg(test)]
@@ -400,21 +472,125 @@ mod tests {
                     access_control: '(= subject.component "influxdb")'
         "#;
 
-        let mut sut = ConfigRunner::new();
-        sut.parse(config, false).unwrap();
+        let sut = ConfigRunner::parse(config).unwrap();
 
-        assert_eq!(sut.commands_sorted.len(), 7);
-        assert_eq!(sut.commands_sorted[0].id, "node/influxdb");
-        assert_eq!(sut.commands_sorted[1].id, "policy/influxdb");
-        assert_eq!(sut.commands_sorted[2].id, "outlet/influxdb");
-        assert_eq!(sut.commands_sorted[3].id, "relay/influxdb");
-        assert_eq!(sut.commands_sorted[4].id, "node/telegraf");
+        assert_eq!(sut.nodes.len(), 2);
+        assert_eq!(sut.nodes[0].name, "influxdb");
+        assert_eq!(
+            resource_ids(&sut.nodes[0]),
+            vec![
+                "policy/tcp-outlet/handle_message",
+                "tcp-outlet/influxdb",
+                "relay/influxdb",
+            ]
+        );
+        assert_eq!(sut.nodes[1].name, "telegraf");
+        assert_eq!(sut.nodes[1].depends_on.as_ref().unwrap(), "influxdb");
         assert_eq!(
-            sut.commands_sorted[4].depends_on.as_ref().unwrap(),
-            "node/influxdb"
+            resource_ids(&sut.nodes[1]),
+            vec!["policy/tcp-inlet/handle_message", "tcp-inlet/telegraf",]
         );
-        assert_eq!(sut.commands_sorted[5].id, "policy/telegraf");
-        assert_eq!(sut.commands_sorted[6].id, "inlet/telegraf");
+    }
+
+    #[test]
+    fn test_parse_config_with_all_resources() {
+        let config = r#"
+            nodes:
+              kafka:
+                trust-context: kafka
+                secure-channel-listeners:
+                  kafka_listener:
+                policies:
+                  - resource: kafka-consumer
+                    expression: '(= subject.component "kafka")'
+                  - resource: tcp-outlet
+                    expression: '(= subject.component "kafka")'
+                tcp-outlets:
+                  kafka:
+                    from: /service/outlet
+                    to: '127.0.0.1:9092'
+                    access_control: '(= subject.component "kafka")'
+                kafka-services:
+                  kafka_consumer:
+                    type: consumer
+                    brokers-port-range: 6001-6100
+        "#;
+
+        let sut = ConfigRunner::parse(config).unwrap();
+
+        let node = &sut.nodes[0];
+        assert_eq!(node.trust_context.as_deref(), Some("kafka"));
+        assert_eq!(
+            resource_ids(node),
+            vec![
+                "secure-channel-listener/kafka_listener",
+                "policy/kafka-consumer/handle_message",
+                "policy/tcp-outlet/handle_message",
+                "tcp-outlet/kafka",
+                "kafka-service/kafka_consumer",
+            ]
+        );
+        let consumer = &node.resources[4];
+        assert_eq!(consumer.attributes["bootstrap-server"], "127.0.0.1:4000");
+        assert_eq!(consumer.attributes["brokers-port-range"], "6001-6100");
+    }
+
+    #[test]
+    fn detect_conflicting_access_controls() {
+        let config = r#"
+            nodes:
+              telegraf:
+                tcp-inlets:
+                  telegraf1:
+                    from: '127.0.0.1:8087'
+                    to: /project/default/service/forward_to_influxdb1/secure/api/service/outlet
+                    access_control: '(= subject.component "influxdb1")'
+                  telegraf2:
+                    from: '127.0.0.1:8088'
+                    to: /project/default/service/forward_to_influxdb2/secure/api/service/outlet
+                    access_control: '(= subject.component "influxdb2")'
+        "#;
+
+        let result = ConfigRunner::parse(config);
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("declares different policies for tcp-inlet/handle_message"));
+    }
+
+    #[test]
+    fn test_parse_node_config_file() {
+        let config = r#"
+            trusted-identities:
+              I0123456789abcdef0123456789abcdef01234567:
+                component: telegraf
+            tcp-outlets:
+              influxdb:
+                from: /service/outlet
+                to: '127.0.0.1:8086'
+                access_control: '(= subject.component "telegraf")'
+        "#;
+
+        let spec = NodeConfigFile::parse("influxdb", config).unwrap();
+        assert_eq!(spec.node.name, "influxdb");
+        assert_eq!(
+            resource_ids(&spec.node),
+            vec!["policy/tcp-outlet/handle_message", "tcp-outlet/influxdb"]
+        );
+        assert_eq!(
+            spec.trusted_identities,
+            r#"{"I0123456789abcdef0123456789abcdef01234567":{"component":"telegraf"}}"#
+        );
+
+        let config = r#"
+            enrollment-ticket: ticket
+        "#;
+        assert!(NodeConfigFile::parse("influxdb", config).is_err());
+    }
+
+    fn resource_ids(node: &NodeSpec) -> Vec<String> {
+        node.resources.iter().map(|r| r.id()).collect()
     }
 
     #[test]
@@ -474,8 +650,7 @@ mod tests {
             ),
         ];
         for (config, expected) in cases {
-            let mut sut = ConfigRunner::new();
-            let result = sut.parse(config, false);
+            let result = ConfigRunner::parse(config);
             match expected {
                 Ok(_) => assert!(result.is_ok()),
                 Err(_) => {
@@ -0,0 +1,504 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use colorful::Colorful;
+use miette::{miette, Context as _};
+
+use ockam::Context;
+use ockam_api::cli_state::{CliState, StateDirTrait};
+use ockam_api::nodes::BackgroundNode;
+
+use crate::node::util::delete_node;
+use crate::node::{is_node_up, spawn_background_node, start_node, CreateCommand};
+use crate::project::enroll::{parse_enroll_ticket, project_enroll};
+use crate::project::EnrollCommand;
+use crate::run::parser::{ConfigRunner, NodeSpec};
+use crate::run::resources::{existing_resources, DeclaredResource, ExistingResource, ResourceKind};
+use crate::util::api::{CloudOpts, TrustContextOpts};
+use crate::{fmt_ok, shutdown, CommandGlobalOpts};
+
+/// A change to make to a node so that it matches a recipe
+#[derive(Clone, Debug)]
+pub enum Change {
+    Create(DeclaredResource),
+    Update {
+        existing: ExistingResource,
+        declared: DeclaredResource,
+    },
+    Delete(ExistingResource),
+}
+
+impl Change {
+    pub(super) async fn apply(
+        &self,
+        ctx: &Context,
+        opts: &CommandGlobalOpts,
+        node_name: &str,
+        node: &BackgroundNode,
+    ) -> miette::Result<()> {
+        match self {
+            Change::Create(declared) => declared.create(ctx, opts, node_name, node).await,
+            Change::Update { existing, declared } => {
+                // A policy is replaced when it is created again
+                if declared.kind != ResourceKind::Policy {
+                    existing.delete(ctx, node).await?;
+                }
+                declared.create(ctx, opts, node_name, node).await
+            }
+            Change::Delete(existing) => existing.delete(ctx, node).await,
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (sign, id, attributes) = match self {
+            Change::Create(declared) => (
+                "+",
+                declared.id(),
+                declared
+                    .attributes
+                    .iter()
+                    .map(|(key, value)| format!("{key}: {value}"))
+                    .collect(),
+            ),
+            Change::Update { existing, declared } => (
+                "~",
+                declared.id(),
+                changed_attributes(existing, declared)
+                    .into_iter()
+                    .map(|(key, old, new)| format!("{key}: {old} -> {new}"))
+                    .collect(),
+            ),
+            Change::Delete(existing) => ("-", existing.id(), vec![]),
+        };
+        write!(f, "{sign} {id}")?;
+        if !attributes.is_empty() {
+            write!(f, " ({})", attributes.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the attributes of an existing resource which differ from the declared ones.
+/// The attributes which can't be retrieved from the node are not compared.
+fn changed_attributes<'a>(
+    existing: &'a ExistingResource,
+    declared: &'a DeclaredResource,
+) -> Vec<(&'static str, &'a String, &'a String)> {
+    existing
+        .attributes
+        .iter()
+        .filter_map(|(key, old)| match declared.attributes.get(key) {
+            Some(new) if new != old => Some((*key, old, new)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Compute the changes to make to the existing resources of a node so that
+/// they match the declared ones.
+///
+/// The resources which are not declared anymore are deleted first, in the
+/// reverse order of their creation, so that their ports and addresses can be reused.
+pub fn diff(declared: &[DeclaredResource], mut existing: Vec<ExistingResource>) -> Vec<Change> {
+    let mut changes = vec![];
+    for resource in declared {
+        match existing
+            .iter()
+            .position(|e| e.kind == resource.kind && e.name == resource.name)
+        {
+            Some(index) => {
+                let current = existing.remove(index);
+                if !changed_attributes(&current, resource).is_empty() {
+                    changes.push(Change::Update {
+                        existing: current,
+                        declared: resource.clone(),
+                    });
+                }
+            }
+            None => changes.push(Change::Create(resource.clone())),
+        }
+    }
+
+    existing.sort_by(|e1, e2| (e2.kind, &e2.name).cmp(&(e1.kind, &e1.name)));
+    existing
+        .into_iter()
+        .map(Change::Delete)
+        .chain(changes)
+        .collect()
+}
+
+/// Keep only the existing resources which are declared, or which were created by
+/// a previous configuration. The resources created with other commands are never
+/// updated nor deleted.
+pub fn owned_resources(
+    declared: &[DeclaredResource],
+    owned: &[String],
+    existing: Vec<ExistingResource>,
+) -> Vec<ExistingResource> {
+    existing
+        .into_iter()
+        .filter(|e| {
+            owned.contains(&e.id())
+                || declared
+                    .iter()
+                    .any(|d| d.kind == e.kind && d.name == e.name)
+        })
+        .collect()
+}
+
+/// Record the resources owned by the configuration once a change is applied
+fn record_change(owned: &mut Vec<String>, change: &Change) {
+    match change {
+        Change::Create(declared) | Change::Update { declared, .. } => {
+            if !owned.contains(&declared.id()) {
+                owned.push(declared.id());
+            }
+        }
+        Change::Delete(existing) => owned.retain(|id| id != &existing.id()),
+    }
+}
+
+enum NodeStatus {
+    Missing,
+    Stopped,
+    Running,
+}
+
+fn node_status(state: &CliState, node_name: &str) -> NodeStatus {
+    match state.nodes.get(node_name) {
+        Ok(node) if node.is_running() => NodeStatus::Running,
+        Ok(_) => NodeStatus::Stopped,
+        Err(_) => NodeStatus::Missing,
+    }
+}
+
+impl ConfigRunner {
+    /// Print the changes which would be made to the nodes to match the recipe
+    pub async fn plan(&self, ctx: &Context, opts: &CommandGlobalOpts) -> miette::Result<()> {
+        let mut plan = vec![];
+        for spec in &self.nodes {
+            match node_status(&opts.state, &spec.name) {
+                NodeStatus::Missing => {
+                    plan.push(format!("+ node/{}", spec.name));
+                    for resource in &spec.resources {
+                        plan.push(format!("    {}", Change::Create(resource.clone())));
+                    }
+                }
+                NodeStatus::Stopped => {
+                    plan.push(format!(
+                        "~ node/{} (stopped, it will be started and its resources compared with the recipe)",
+                        spec.name
+                    ));
+                    let owned = opts.state.nodes.get(&spec.name)?.config_resources()?;
+                    for resource in &spec.resources {
+                        if !owned.contains(&resource.id()) {
+                            plan.push(format!("    {}", Change::Create(resource.clone())));
+                        }
+                    }
+                    for id in owned {
+                        if !spec.resources.iter().any(|r| r.id() == id) {
+                            plan.push(format!("    - {id}"));
+                        }
+                    }
+                }
+                NodeStatus::Running => {
+                    let node = BackgroundNode::create(ctx, &opts.state, &spec.name).await?;
+                    let changes = node_changes(ctx, opts, spec, &node).await?;
+                    if !changes.is_empty() {
+                        plan.push(format!("~ node/{}", spec.name));
+                        for change in changes {
+                            plan.push(format!("    {change}"));
+                        }
+                    }
+                }
+            }
+        }
+
+        let plan = if plan.is_empty() {
+            "The nodes match the recipe, there is nothing to change".to_string()
+        } else {
+            plan.join("\n")
+        };
+        opts.terminal.clone().stdout().plain(plan).write_line()?;
+        Ok(())
+    }
+
+    /// Create, start and update the nodes so that they match the recipe.
+    ///
+    /// If `blocking` is true, wait until all the nodes have stopped, or until the
+    /// command is interrupted, in which case the nodes are stopped.
+    pub async fn apply(
+        &self,
+        ctx: &Context,
+        opts: &CommandGlobalOpts,
+        blocking: bool,
+    ) -> miette::Result<()> {
+        for spec in &self.nodes {
+            let node = ensure_node_is_running(ctx, opts, spec).await?;
+            let node_state = opts.state.nodes.get(&spec.name)?;
+            let mut owned = node_state.config_resources()?;
+            for change in node_changes(ctx, opts, spec, &node).await? {
+                let result = change.apply(ctx, opts, &spec.name, &node).await;
+                // A failed change might have been partially applied
+                if result.is_ok() || !matches!(change, Change::Delete(_)) {
+                    record_change(&mut owned, &change);
+                }
+                node_state.set_config_resources(&owned)?;
+                result.wrap_err_with(|| {
+                    format!("Failed to apply `{change}` to node/{}", spec.name)
+                })?;
+                opts.terminal
+                    .write_line(&fmt_ok!("node/{}: {change}", spec.name))?;
+            }
+        }
+
+        if blocking {
+            self.wait_for_nodes(opts).await?;
+        }
+        Ok(())
+    }
+
+    /// Delete the nodes of the recipe, in the reverse order of their creation.
+    /// If `plan` is true, only print the nodes which would be deleted.
+    pub fn down(&self, opts: &CommandGlobalOpts, plan: bool) -> miette::Result<()> {
+        let mut deleted = vec![];
+        for spec in self.nodes.iter().rev() {
+            if !opts.state.nodes.exists(&spec.name) {
+                continue;
+            }
+            if !plan {
+                delete_node(opts, &spec.name, false)?;
+                opts.terminal
+                    .write_line(&fmt_ok!("node/{} deleted", spec.name))?;
+            }
+            deleted.push(format!("- node/{}", spec.name));
+        }
+
+        if plan {
+            let plan = if deleted.is_empty() {
+                "None of the nodes of the recipe exist, there is nothing to delete".to_string()
+            } else {
+                deleted.join("\n")
+            };
+            opts.terminal.clone().stdout().plain(plan).write_line()?;
+        }
+        Ok(())
+    }
+
+    async fn wait_for_nodes(&self, opts: &CommandGlobalOpts) -> miette::Result<()> {
+        // Create a channel for communicating back to the main thread
+        let (tx, mut rx) = tokio::sync::mpsc::channel(2);
+
+        // Spawn a task to trigger shutdown when all nodes are stopped
+        {
+            let tx = tx.clone();
+            let state = opts.state.clone();
+            let node_names: Vec<String> = self.nodes.iter().map(|n| n.name.clone()).collect();
+            tokio::spawn(async move {
+                while node_names
+                    .iter()
+                    .any(|n| matches!(node_status(&state, n), NodeStatus::Running))
+                {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+                let _ = tx.send(()).await;
+            });
+        }
+
+        // Wait for CTRL+C or any other exit condition (like receiving a signal)
+        shutdown::wait(opts.terminal.clone(), true, true, tx, &mut rx).await?;
+
+        // Send a SIGTERM to all nodes if they are still running
+        for spec in &self.nodes {
+            if let Ok(node) = opts.state.nodes.get(&spec.name) {
+                if node.is_running() {
+                    let _ = node.kill_process(false);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare the resources declared for a node with the ones it currently has,
+/// ignoring the resources which were not created by the configuration
+async fn node_changes(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    spec: &NodeSpec,
+    node: &BackgroundNode,
+) -> miette::Result<Vec<Change>> {
+    let mut declared = spec.resources.clone();
+    for resource in declared.iter_mut() {
+        resource.resolve_addresses(opts);
+    }
+    let owned = opts.state.nodes.get(&spec.name)?.config_resources()?;
+    let existing = existing_resources(ctx, opts, &spec.name, node, &declared).await?;
+    Ok(diff(
+        &declared,
+        owned_resources(&declared, &owned, existing),
+    ))
+}
+
+/// Create the node if it doesn't exist yet, or start it if it is stopped
+async fn ensure_node_is_running(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    spec: &NodeSpec,
+) -> miette::Result<BackgroundNode> {
+    match node_status(&opts.state, &spec.name) {
+        NodeStatus::Running => {}
+        NodeStatus::Stopped => {
+            start_node(opts, &spec.name)?;
+            opts.terminal
+                .write_line(&fmt_ok!("node/{} started", spec.name))?;
+        }
+        NodeStatus::Missing => {
+            create_node(ctx, opts, spec).await?;
+            opts.terminal
+                .write_line(&fmt_ok!("node/{} created", spec.name))?;
+        }
+    }
+
+    let mut node = BackgroundNode::create(ctx, &opts.state, &spec.name).await?;
+    if !is_node_up(ctx, &spec.name, &mut node, opts.state.clone(), true).await? {
+        return Err(miette!("The node {} could not be started", spec.name));
+    }
+    // The node used to check the status has a short timeout
+    BackgroundNode::create(ctx, &opts.state, &spec.name).await
+}
+
+async fn create_node(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    spec: &NodeSpec,
+) -> miette::Result<()> {
+    let mut trust_context = spec.trust_context.clone();
+
+    // Enroll only once, since enrollment tickets can only be used once.
+    // The trust context of the project is named after the node, unless the recipe names it.
+    if let Some(ticket) = &spec.enrollment_ticket {
+        let trust_context_name = trust_context.clone().unwrap_or_else(|| spec.name.clone());
+        if opts.state.trust_contexts.get(&trust_context_name).is_err() {
+            let cmd = EnrollCommand {
+                okta: false,
+                enroll_ticket: Some(parse_enroll_ticket(ticket)?),
+                cloud_opts: CloudOpts { identity: None },
+                trust_opts: TrustContextOpts::default(),
+                new_trust_context_name: Some(trust_context_name.clone()),
+                force: false,
+            };
+            project_enroll(ctx, opts, cmd).await?;
+        }
+        trust_context = Some(trust_context_name);
+    }
+
+    let mut cmd = CreateCommand::default();
+    cmd.node_name = spec.name.clone();
+    cmd.trust_context_opts.trust_context = trust_context;
+    spawn_background_node(opts, cmd).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run::resources::ResourceSpec;
+    use ockam_core::Address;
+
+    fn outlet(name: &str, to: &str) -> DeclaredResource {
+        DeclaredResource::new(
+            ResourceKind::TcpOutlet,
+            name,
+            ResourceSpec::TcpOutlet {
+                from: Address::from_string(name),
+                to: to.parse().unwrap(),
+            },
+        )
+    }
+
+    fn existing_outlet(name: &str, to: &str) -> ExistingResource {
+        ExistingResource::new(
+            ResourceKind::TcpOutlet,
+            name,
+            format!("/node/outlet/{name}"),
+        )
+        .with_attribute("from", format!("/service/{name}"))
+        .with_attribute("to", to)
+    }
+
+    fn ids(changes: &[Change]) -> Vec<String> {
+        changes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn diff_creates_updates_and_deletes_resources() {
+        let declared = vec![
+            outlet("unchanged", "127.0.0.1:5000"),
+            outlet("updated", "127.0.0.1:6001"),
+            outlet("created", "127.0.0.1:7000"),
+        ];
+        let existing = vec![
+            existing_outlet("deleted_1", "127.0.0.1:4000"),
+            existing_outlet("unchanged", "127.0.0.1:5000"),
+            existing_outlet("updated", "127.0.0.1:6000"),
+            existing_outlet("deleted_2", "127.0.0.1:4001"),
+        ];
+
+        // The deleted resources come first, in the reverse order
+        assert_eq!(
+            ids(&diff(&declared, existing)),
+            vec![
+                "- tcp-outlet/deleted_2".to_string(),
+                "- tcp-outlet/deleted_1".to_string(),
+                "~ tcp-outlet/updated (to: 127.0.0.1:6000 -> 127.0.0.1:6001)".to_string(),
+                "+ tcp-outlet/created (from: /service/created, to: 127.0.0.1:7000)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_without_changes_is_empty() {
+        let declared = vec![outlet("o", "127.0.0.1:5000")];
+        let existing = vec![existing_outlet("o", "127.0.0.1:5000")];
+        assert!(diff(&declared, existing).is_empty());
+    }
+
+    #[test]
+    fn resources_not_owned_by_the_configuration_are_kept() {
+        let declared = vec![outlet("declared", "127.0.0.1:5000")];
+        let owned = vec!["tcp-outlet/removed".to_string()];
+        let existing = vec![
+            existing_outlet("declared", "127.0.0.1:5000"),
+            existing_outlet("removed", "127.0.0.1:6000"),
+            existing_outlet("manual", "127.0.0.1:7000"),
+        ];
+
+        let existing = owned_resources(&declared, &owned, existing);
+        assert_eq!(
+            ids(&diff(&declared, existing)),
+            vec!["- tcp-outlet/removed".to_string()]
+        );
+    }
+
+    #[test]
+    fn owned_resources_are_recorded() {
+        let mut owned = vec![];
+        record_change(&mut owned, &Change::Create(outlet("o", "127.0.0.1:5000")));
+        record_change(
+            &mut owned,
+            &Change::Update {
+                existing: existing_outlet("o", "127.0.0.1:5000"),
+                declared: outlet("o", "127.0.0.1:5001"),
+            },
+        );
+        assert_eq!(owned, vec!["tcp-outlet/o".to_string()]);
+
+        record_change(
+            &mut owned,
+            &Change::Delete(existing_outlet("o", "127.0.0.1:5001")),
+        );
+        assert!(owned.is_empty());
+    }
+}
@@ -0,0 +1,606 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+
+use miette::{miette, IntoDiagnostic};
+use serde::Deserialize;
+
+use ockam::identity::Identifier;
+use ockam::Context;
+use ockam_abac::{Action, Expr, Resource as PolicyResource};
+use ockam_api::cli_state::{NodeResourceKind, StateDirTrait, StateItemTrait};
+use ockam_api::is_local_node;
+use ockam_api::nodes::models::policy::{Policy, PolicyList};
+use ockam_api::nodes::models::portal::{
+    CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus,
+};
+use ockam_api::nodes::models::relay::RelayInfo;
+use ockam_api::nodes::models::secure_channel::{
+    CreateSecureChannelListenerRequest, SecureChannelListenersList,
+};
+use ockam_api::nodes::models::services::{
+    DeleteServiceRequest, ServiceList, StartKafkaConsumerRequest, StartKafkaOutletRequest,
+    StartKafkaProducerRequest, StartServiceRequest,
+};
+use ockam_api::nodes::service::relay::Relays;
+use ockam_api::nodes::BackgroundNode;
+use ockam_api::port_range::PortRange;
+use ockam_api::DefaultAddress;
+use ockam_core::api::Request;
+use ockam_core::{route, Address};
+use ockam_multiaddr::proto::Project;
+use ockam_multiaddr::{MultiAddr, Protocol as _};
+
+use crate::policy::{add_default_project_policy, has_policy, policy_path};
+use crate::util::api;
+use crate::util::process_nodes_multiaddr;
+use crate::CommandGlobalOpts;
+
+/// Kinds of resources which can be declared for a node in a recipe.
+///
+/// They are created in this order, and deleted in the reverse order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ResourceKind {
+    SecureChannelListener,
+    Policy,
+    TcpOutlet,
+    Relay,
+    TcpInlet,
+    KafkaService,
+}
+
+impl Display for ResourceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ResourceKind::SecureChannelListener => "secure-channel-listener",
+            ResourceKind::Policy => "policy",
+            ResourceKind::TcpOutlet => "tcp-outlet",
+            ResourceKind::Relay => "relay",
+            ResourceKind::TcpInlet => "tcp-inlet",
+            ResourceKind::KafkaService => "kafka-service",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Types of Kafka services which can be declared in a recipe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KafkaServiceType {
+    Outlet,
+    Consumer,
+    Producer,
+}
+
+impl KafkaServiceType {
+    fn service_name(&self) -> &'static str {
+        match self {
+            KafkaServiceType::Outlet => DefaultAddress::KAFKA_OUTLET,
+            KafkaServiceType::Consumer => DefaultAddress::KAFKA_CONSUMER,
+            KafkaServiceType::Producer => DefaultAddress::KAFKA_PRODUCER,
+        }
+    }
+}
+
+/// A resource, as declared in a recipe
+#[derive(Clone, Debug)]
+pub struct DeclaredResource {
+    pub kind: ResourceKind,
+    pub name: String,
+    /// Attributes compared with the ones of the existing resource, and displayed in a plan
+    pub attributes: BTreeMap<&'static str, String>,
+    spec: ResourceSpec,
+}
+
+#[derive(Clone, Debug)]
+pub enum ResourceSpec {
+    SecureChannelListener {
+        address: Address,
+        authorized: Option<Vec<Identifier>>,
+        identity: Option<String>,
+    },
+    Policy {
+        resource: PolicyResource,
+        action: Action,
+        expression: Expr,
+    },
+    TcpOutlet {
+        /// Address of the outlet worker
+        from: Address,
+        to: SocketAddr,
+    },
+    Relay {
+        at: MultiAddr,
+    },
+    TcpInlet {
+        from: SocketAddr,
+        to: MultiAddr,
+    },
+    KafkaService {
+        service_type: KafkaServiceType,
+        bootstrap_server: SocketAddr,
+        brokers_port_range: PortRange,
+        project_route: MultiAddr,
+    },
+}
+
+impl DeclaredResource {
+    pub fn new(kind: ResourceKind, name: impl Into<String>, spec: ResourceSpec) -> Self {
+        let mut attributes = BTreeMap::new();
+        match &spec {
+            ResourceSpec::SecureChannelListener {
+                authorized,
+                identity,
+                ..
+            } => {
+                if let Some(authorized) = authorized {
+                    let authorized: Vec<String> =
+                        authorized.iter().map(|i| i.to_string()).collect();
+                    attributes.insert("authorized", authorized.join(","));
+                }
+                if let Some(identity) = identity {
+                    attributes.insert("identity", identity.clone());
+                }
+            }
+            ResourceSpec::Policy { expression, .. } => {
+                attributes.insert("expression", expression.to_string());
+            }
+            ResourceSpec::TcpOutlet { from, to } => {
+                attributes.insert("from", format!("/service/{}", from.address()));
+                attributes.insert("to", to.to_string());
+            }
+            ResourceSpec::Relay { at } => {
+                attributes.insert("at", at.to_string());
+            }
+            ResourceSpec::TcpInlet { from, to } => {
+                attributes.insert("from", from.to_string());
+                attributes.insert("to", to.to_string());
+            }
+            ResourceSpec::KafkaService {
+                service_type,
+                bootstrap_server,
+                brokers_port_range,
+                project_route,
+            } => {
+                attributes.insert("type", service_type.service_name().to_string());
+                attributes.insert("bootstrap-server", bootstrap_server.to_string());
+                if *service_type != KafkaServiceType::Outlet {
+                    attributes.insert("brokers-port-range", brokers_port_range.to_string());
+                    attributes.insert("project-route", project_route.to_string());
+                }
+            }
+        }
+        Self {
+            kind,
+            name: name.into(),
+            attributes,
+            spec,
+        }
+    }
+
+    /// Identifier of the resource in the node, used in plans and error messages
+    pub fn id(&self) -> String {
+        format!("{}/{}", self.kind, self.name)
+    }
+
+    /// Node names used in the addresses of the resource are replaced by the address of
+    /// their TCP listener, the same way as when the resource is created, so that they
+    /// can be compared with the existing resources
+    pub fn resolve_addresses(&mut self, opts: &CommandGlobalOpts) {
+        if let ResourceSpec::TcpInlet { to, .. } = &self.spec {
+            if let Ok(to) = process_nodes_multiaddr(to, &opts.state) {
+                self.attributes.insert("to", to.to_string());
+            }
+        }
+    }
+
+    /// Create the resource on the given node
+    pub async fn create(
+        &self,
+        ctx: &Context,
+        opts: &CommandGlobalOpts,
+        node_name: &str,
+        node: &BackgroundNode,
+    ) -> miette::Result<()> {
+        match &self.spec {
+            ResourceSpec::SecureChannelListener {
+                address,
+                authorized,
+                identity,
+            } => {
+                let payload = CreateSecureChannelListenerRequest::new(
+                    address,
+                    authorized.clone(),
+                    None,
+                    identity.clone(),
+                );
+                node.tell(
+                    ctx,
+                    Request::post("/node/secure_channel_listener").body(payload),
+                )
+                .await?;
+            }
+            ResourceSpec::Policy {
+                resource,
+                action,
+                expression,
+            } => {
+                let payload = Policy::new(expression.clone());
+                node.tell(
+                    ctx,
+                    Request::post(policy_path(resource, action)).body(payload),
+                )
+                .await?;
+            }
+            ResourceSpec::TcpOutlet { from, to } => {
+                add_project_policy_if_missing(ctx, opts, node_name, "tcp-outlet").await?;
+                let payload = CreateOutlet::new(*to, from.clone(), Some(self.name.clone()), true);
+                let _: OutletStatus = node
+                    .ask(ctx, Request::post("/node/outlet").body(payload))
+                    .await?;
+            }
+            ResourceSpec::Relay { at } => {
+                let alias = relay_alias(&self.name, at)?;
+                let at = process_nodes_multiaddr(at, &opts.state)?;
+                node.create_relay(ctx, &at, Some(alias), None).await?;
+            }
+            ResourceSpec::TcpInlet { from, to } => {
+                add_project_policy_if_missing(ctx, opts, node_name, "tcp-inlet").await?;
+                let to = process_nodes_multiaddr(to, &opts.state)?;
+                let mut payload = if to.matches(0, &[Project::CODE.into()]) {
+                    CreateInlet::via_project(from.to_string(), to, route![], route![])
+                } else {
+                    CreateInlet::to_node(from.to_string(), to, route![], route![], None)
+                };
+                payload.set_alias(&self.name);
+                let _: InletStatus = node
+                    .ask(ctx, Request::post("/node/inlet").body(payload))
+                    .await?;
+            }
+            ResourceSpec::KafkaService {
+                service_type,
+                bootstrap_server,
+                brokers_port_range,
+                project_route,
+            } => {
+                let path = format!("/node/services/{}", service_type.service_name());
+                let project_route = process_nodes_multiaddr(project_route, &opts.state)?;
+                match service_type {
+                    KafkaServiceType::Outlet => {
+                        let payload = StartKafkaOutletRequest::new(*bootstrap_server);
+                        let payload = StartServiceRequest::new(payload, &self.name);
+                        node.tell(ctx, Request::post(path).body(payload)).await?;
+                    }
+                    KafkaServiceType::Consumer => {
+                        let payload = StartKafkaConsumerRequest::new(
+                            *bootstrap_server,
+                            *brokers_port_range,
+                            project_route,
+                        );
+                        let payload = StartServiceRequest::new(payload, &self.name);
+                        node.tell(ctx, Request::post(path).body(payload)).await?;
+                    }
+                    KafkaServiceType::Producer => {
+                        let payload = StartKafkaProducerRequest::new(
+                            *bootstrap_server,
+                            *brokers_port_range,
+                            project_route,
+                        );
+                        let payload = StartServiceRequest::new(payload, &self.name);
+                        node.tell(ctx, Request::post(path).body(payload)).await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The resource as it was before being updated to this declaration, rebuilt from the
+    /// attributes of the existing resource. This allows an update to be reverted when the
+    /// resource was not created by a previous configuration.
+    /// It fails if some attributes of the existing resource are not known.
+    pub fn previous_version(&self, existing: &ExistingResource) -> miette::Result<Self> {
+        let attribute = |key: &str| {
+            existing.attributes.get(key).ok_or_else(|| {
+                miette!(
+                    "The {key} of {} is not known, it can't be restored",
+                    existing.id()
+                )
+            })
+        };
+        let spec = match &self.spec {
+            ResourceSpec::Policy {
+                resource, action, ..
+            } => ResourceSpec::Policy {
+                resource: resource.clone(),
+                action: action.clone(),
+                expression: Expr::try_from(attribute("expression")?.as_str()).into_diagnostic()?,
+            },
+            ResourceSpec::TcpOutlet { .. } => ResourceSpec::TcpOutlet {
+                from: Address::from_string(attribute("from")?.trim_start_matches("/service/")),
+                to: attribute("to")?.parse().into_diagnostic()?,
+            },
+            ResourceSpec::TcpInlet { .. } => ResourceSpec::TcpInlet {
+                from: attribute("from")?.parse().into_diagnostic()?,
+                to: attribute("to")?.parse().into_diagnostic()?,
+            },
+            ResourceSpec::SecureChannelListener { .. }
+            | ResourceSpec::Relay { .. }
+            | ResourceSpec::KafkaService { .. } => {
+                return Err(miette!(
+                    "The previous version of {} can't be restored",
+                    existing.id()
+                ))
+            }
+        };
+        Ok(Self::new(self.kind, &self.name, spec))
+    }
+
+    /// The resource as it exists on a node once it has been created, so that it can be deleted
+    pub fn to_existing(&self) -> miette::Result<ExistingResource> {
+        let path = match &self.spec {
+            ResourceSpec::SecureChannelListener { .. } => {
+                "/node/secure_channel_listener".to_string()
+            }
+            ResourceSpec::Policy {
+                resource, action, ..
+            } => policy_path(resource, action),
+            ResourceSpec::TcpOutlet { .. } => format!("/node/outlet/{}", self.name),
+            ResourceSpec::Relay { at } => {
+                format!("/node/forwarder/{}", relay_alias(&self.name, at)?)
+            }
+            ResourceSpec::TcpInlet { .. } => format!("/node/inlet/{}", self.name),
+            ResourceSpec::KafkaService { service_type, .. } => {
+                format!("/node/services/{}", service_type.service_name())
+            }
+        };
+        let mut existing = ExistingResource::new(self.kind, &self.name, path);
+        existing.attributes = self.attributes.clone();
+        existing.delete_body = match &self.spec {
+            ResourceSpec::SecureChannelListener { address, .. } => {
+                Some(DeleteBody::SecureChannelListener(address.clone()))
+            }
+            ResourceSpec::KafkaService { .. } => Some(DeleteBody::Service(self.name.clone())),
+            _ => None,
+        };
+        Ok(existing)
+    }
+}
+
+/// Relays created at a rust node are prefixed, so that they can be found by the name of the relay
+fn relay_alias(name: &str, at: &MultiAddr) -> miette::Result<String> {
+    if is_local_node(at)? {
+        Ok(format!("forward_to_{name}"))
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+/// A resource which currently exists on a node
+#[derive(Clone, Debug)]
+pub struct ExistingResource {
+    pub kind: ResourceKind,
+    pub name: String,
+    /// The attributes which could be retrieved from the node.
+    /// Attributes which are not known are not compared.
+    pub attributes: BTreeMap<&'static str, String>,
+    /// Path of the request deleting the resource
+    delete_path: String,
+    /// Body of the request deleting the resource, if any
+    delete_body: Option<DeleteBody>,
+}
+
+#[derive(Clone, Debug)]
+enum DeleteBody {
+    SecureChannelListener(Address),
+    Service(String),
+}
+
+impl ExistingResource {
+    pub(crate) fn new(
+        kind: ResourceKind,
+        name: impl Into<String>,
+        delete_path: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            attributes: BTreeMap::new(),
+            delete_path: delete_path.into(),
+            delete_body: None,
+        }
+    }
+
+    pub(crate) fn with_attribute(mut self, key: &'static str, value: impl Into<String>) -> Self {
+        self.attributes.insert(key, value.into());
+        self
+    }
+
+    fn with_delete_body(mut self, body: DeleteBody) -> Self {
+        self.delete_body = Some(body);
+        self
+    }
+
+    /// Identifier of the resource in the node, used in plans and error messages
+    pub fn id(&self) -> String {
+        format!("{}/{}", self.kind, self.name)
+    }
+
+    /// Delete the resource from the given node
+    pub async fn delete(&self, ctx: &Context, node: &BackgroundNode) -> miette::Result<()> {
+        let path = self.delete_path.clone();
+        match &self.delete_body {
+            Some(DeleteBody::SecureChannelListener(address)) => {
+                node.tell(ctx, api::delete_secure_channel_listener(address))
+                    .await?
+            }
+            Some(DeleteBody::Service(address)) => {
+                node.tell(
+                    ctx,
+                    Request::delete(path).body(DeleteServiceRequest::new(address.clone())),
+                )
+                .await?
+            }
+            None => node.tell(ctx, Request::delete(path)).await?,
+        }
+        Ok(())
+    }
+}
+
+/// Retrieve the resources existing on a running node.
+///
+/// Policies are only retrieved for the resources declared in the recipe, since they
+/// can't be listed for the whole node.
+pub async fn existing_resources(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    node: &BackgroundNode,
+    declared: &[DeclaredResource],
+) -> miette::Result<Vec<ExistingResource>> {
+    let mut existing = vec![];
+
+    let listeners: SecureChannelListenersList =
+        node.ask(ctx, api::list_secure_channel_listener()).await?;
+    for listener in listeners.list {
+        let name = listener.addr.address().to_string();
+        // The default listener is created with the node
+        if name == DefaultAddress::SECURE_CHANNEL_LISTENER {
+            continue;
+        }
+        existing.push(
+            ExistingResource::new(
+                ResourceKind::SecureChannelListener,
+                name,
+                "/node/secure_channel_listener",
+            )
+            .with_delete_body(DeleteBody::SecureChannelListener(listener.addr)),
+        );
+    }
+
+    for resource in declared {
+        if let ResourceSpec::Policy {
+            resource: policy_resource,
+            action,
+            ..
+        } = &resource.spec
+        {
+            let policies: PolicyList = node
+                .ask(ctx, Request::get(format!("/policy/{policy_resource}")))
+                .await?;
+            if let Some(policy) = policies.expressions().iter().find(|e| e.action() == action) {
+                existing.push(
+                    ExistingResource::new(
+                        ResourceKind::Policy,
+                        &resource.name,
+                        policy_path(policy_resource, action),
+                    )
+                    .with_attribute("expression", policy.expr().to_string()),
+                );
+            }
+        }
+    }
+
+    let outlets: OutletList = node.ask(ctx, api::list_outlets()).await?;
+    for outlet in outlets.list {
+        existing.push(
+            ExistingResource::new(
+                ResourceKind::TcpOutlet,
+                &outlet.alias,
+                format!("/node/outlet/{}", outlet.alias),
+            )
+            .with_attribute("from", format!("/service/{}", outlet.worker_addr.address()))
+            .with_attribute("to", outlet.socket_addr.to_string()),
+        );
+    }
+
+    let relays: Vec<RelayInfo> = node.ask(ctx, Request::get("/node/forwarder")).await?;
+    for relay in relays {
+        let remote_address = relay.remote_address();
+        let name = remote_address
+            .strip_prefix("forward_to_")
+            .unwrap_or(remote_address);
+        existing.push(ExistingResource::new(
+            ResourceKind::Relay,
+            name,
+            format!("/node/forwarder/{remote_address}"),
+        ));
+    }
+
+    // The route to the outlet is only known if the inlet was recorded by the node
+    let recorded_inlets: BTreeMap<String, MultiAddr> = opts
+        .state
+        .nodes
+        .get(node_name)?
+        .resources()?
+        .into_iter()
+        .filter(|r| r.kind == NodeResourceKind::TcpInlet)
+        .filter_map(|r| {
+            let request: CreateInlet = minicbor::decode(&r.body).ok()?;
+            Some((r.name, request.outlet_addr().clone()))
+        })
+        .collect();
+    let inlets: InletList = node.ask(ctx, api::list_inlets()).await?;
+    for inlet in inlets.list {
+        let mut resource = ExistingResource::new(
+            ResourceKind::TcpInlet,
+            &inlet.alias,
+            format!("/node/inlet/{}", inlet.alias),
+        )
+        .with_attribute("from", &inlet.bind_addr);
+        if let Some(to) = recorded_inlets.get(&inlet.alias) {
+            resource = resource.with_attribute("to", to.to_string());
+        }
+        existing.push(resource);
+    }
+
+    let services: ServiceList = node.ask(ctx, api::list_services()).await?;
+    for service in services.list {
+        let service_type = service.service_type.as_str();
+        if ![
+            DefaultAddress::KAFKA_OUTLET,
+            DefaultAddress::KAFKA_CONSUMER,
+            DefaultAddress::KAFKA_PRODUCER,
+        ]
+        .contains(&service_type)
+        {
+            continue;
+        }
+        existing.push(
+            ExistingResource::new(
+                ResourceKind::KafkaService,
+                &service.addr,
+                format!("/node/services/{service_type}"),
+            )
+            .with_attribute("type", service_type)
+            .with_delete_body(DeleteBody::Service(service.addr.clone())),
+        );
+    }
+
+    Ok(existing)
+}
+
+/// Inlets and outlets of a project node are only reachable by the project members,
+/// unless the recipe declares a policy for them
+async fn add_project_policy_if_missing(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    resource: &str,
+) -> miette::Result<()> {
+    let project = opts
+        .state
+        .nodes
+        .get(node_name)?
+        .config()
+        .setup()
+        .project
+        .to_owned();
+    let resource = PolicyResource::new(resource);
+    if let Some(project) = project {
+        if !has_policy(node_name, ctx, opts, &resource).await? {
+            add_default_project_policy(node_name, ctx, opts, project, &resource).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,484 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use miette::{miette, Context as _, IntoDiagnostic};
+use notify::{Event, RecursiveMode, Watcher};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info, warn};
+
+use ockam::{Context, TcpTransport};
+use ockam_api::cli_state::StateDirTrait;
+use ockam_api::nodes::BackgroundNode;
+use ockam_core::{Address, AllowAll};
+
+use crate::run::parser::{NodeConfigFile, NodeConfigSpec};
+use crate::run::plan::{diff, Change};
+use crate::run::resources::{existing_resources, DeclaredResource, ResourceKind};
+use crate::CommandGlobalOpts;
+
+/// Delay between a change of the configuration file and its reload, so that the file is
+/// completely written and the events of a single save are handled together
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(200);
+
+/// Keep a node in sync with its configuration file.
+///
+/// Each time the file changes, the resources which were added, removed or modified
+/// are changed on the node, and the trusted identities are replaced. If one of the
+/// changes fails, all the changes made so far are reverted and the node keeps
+/// its previous configuration.
+///
+/// Only the resources declared in the file are managed: the resources created with
+/// other commands are left untouched.
+pub struct NodeConfigWatcher {
+    node_name: String,
+    path: PathBuf,
+    /// Last contents read from the file
+    contents: String,
+    /// Configuration to apply when the watcher starts
+    initial: Option<NodeConfigSpec>,
+    /// Resources declared by the last configuration applied to the node
+    applied: Vec<DeclaredResource>,
+    /// Trusted identities of the last configuration applied to the node
+    trusted_identities: String,
+    trusted_identities_file: PathBuf,
+}
+
+impl NodeConfigWatcher {
+    /// Read the configuration file of a node and write the identities it trusts.
+    /// This must be done before the node manager is created, since it reads
+    /// the trusted identities from the node directory.
+    pub fn new(opts: &CommandGlobalOpts, node_name: &str, path: &Path) -> miette::Result<Self> {
+        let contents = read_config_file(path)?;
+        let spec = NodeConfigFile::parse(node_name, &contents)?;
+        let trusted_identities_file = opts.state.nodes.get(node_name)?.trusted_identities_file();
+        write_atomically(&trusted_identities_file, &spec.trusted_identities)?;
+        Ok(Self {
+            node_name: node_name.to_string(),
+            path: path.to_path_buf(),
+            contents,
+            trusted_identities: spec.trusted_identities.clone(),
+            initial: Some(spec),
+            applied: vec![],
+            trusted_identities_file,
+        })
+    }
+
+    /// Path of the file listing the identities trusted by the node
+    pub fn trusted_identities_file(&self) -> &Path {
+        &self.trusted_identities_file
+    }
+
+    /// Apply the configuration to the node, then watch the file for changes.
+    /// This must be done once the node manager is started, with the transport of the node.
+    /// The file stops being watched when the returned handle is dropped.
+    pub async fn start(
+        mut self,
+        ctx: &Context,
+        opts: CommandGlobalOpts,
+        tcp: &TcpTransport,
+    ) -> miette::Result<NodeConfigWatcherHandle> {
+        let ctx = ctx
+            .new_detached(
+                Address::random_tagged("NodeConfigWatcher.ctx"),
+                AllowAll,
+                AllowAll,
+            )
+            .await
+            .into_diagnostic()?;
+
+        let node = BackgroundNode::new(tcp, &opts.state, &self.node_name).await?;
+        if let Some(spec) = self.initial.take() {
+            self.apply(&ctx, &opts, &node, spec).await?;
+        }
+
+        // The directory is watched, since editors often replace the file instead of modifying it
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let file_name = self.path.file_name().map(|name| name.to_os_string());
+        let mut file_watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event {
+                if !event.kind.is_access()
+                    && event
+                        .paths
+                        .iter()
+                        .any(|path| path.file_name() == file_name.as_deref())
+                {
+                    let _ = events_tx.send(());
+                }
+            }
+        })
+        .into_diagnostic()?;
+        let directory = match self.path.parent() {
+            Some(directory) if !directory.as_os_str().is_empty() => directory,
+            _ => Path::new("."),
+        };
+        file_watcher
+            .watch(directory, RecursiveMode::NonRecursive)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to watch the directory {}", directory.display()))?;
+
+        let (stop_tx, mut stop) = oneshot::channel();
+        tokio::spawn(async move {
+            // Events are not sent anymore once the file watcher is dropped
+            let _file_watcher = file_watcher;
+            loop {
+                tokio::select! {
+                    _ = &mut stop => break,
+                    event = events.recv() => if event.is_none() { break },
+                }
+                tokio::time::sleep(DEBOUNCE_DELAY).await;
+                while events.try_recv().is_ok() {}
+
+                let contents = match read_config_file(&self.path) {
+                    Ok(contents) => contents,
+                    Err(err) => {
+                        warn!(path = %self.path.display(), %err, "Failed to read the node configuration file");
+                        continue;
+                    }
+                };
+                if contents == self.contents {
+                    continue;
+                }
+                // An invalid configuration is only reported once
+                self.contents = contents.clone();
+                match self.reload(&ctx, &opts, &node, &contents).await {
+                    Ok(()) => {
+                        info!(path = %self.path.display(), "Node configuration applied")
+                    }
+                    Err(err) => error!(
+                        path = %self.path.display(),
+                        %err,
+                        "Failed to apply the node configuration, the previous configuration is kept"
+                    ),
+                }
+            }
+            info!(path = %self.path.display(), "Stopped watching the node configuration file");
+        });
+        Ok(NodeConfigWatcherHandle { _stop: stop_tx })
+    }
+
+    async fn reload(
+        &mut self,
+        ctx: &Context,
+        opts: &CommandGlobalOpts,
+        node: &BackgroundNode,
+        contents: &str,
+    ) -> miette::Result<()> {
+        let spec = NodeConfigFile::parse(&self.node_name, contents)?;
+        self.apply(ctx, opts, node, spec).await
+    }
+
+    /// Apply all the changes between the last applied configuration and the new one,
+    /// or none of them
+    async fn apply(
+        &mut self,
+        ctx: &Context,
+        opts: &CommandGlobalOpts,
+        node: &BackgroundNode,
+        spec: NodeConfigSpec,
+    ) -> miette::Result<()> {
+        let mut declared = spec.node.resources;
+        for resource in declared.iter_mut() {
+            resource.resolve_addresses(opts);
+        }
+
+        // The resources removed from the file must be deleted from the node
+        let mut managed = declared.clone();
+        for resource in &self.applied {
+            if !managed
+                .iter()
+                .any(|r| r.kind == resource.kind && r.name == resource.name)
+            {
+                managed.push(resource.clone());
+            }
+        }
+        let existing = existing_resources(ctx, opts, &self.node_name, node, &managed)
+            .await?
+            .into_iter()
+            .filter(|e| managed.iter().any(|r| r.kind == e.kind && r.name == e.name))
+            .collect();
+        let changes = diff(&declared, existing);
+
+        let trusted_identities_changed = spec.trusted_identities != self.trusted_identities;
+        if trusted_identities_changed {
+            write_atomically(&self.trusted_identities_file, &spec.trusted_identities)?;
+        }
+
+        let mut applied = vec![];
+        for change in changes {
+            let description = change.to_string();
+            let result = change.apply(ctx, opts, &self.node_name, node).await;
+            // A failed change might have been partially applied
+            applied.push(change);
+            if let Err(err) = result {
+                self.rollback(ctx, opts, node, applied, trusted_identities_changed)
+                    .await;
+                return Err(err).wrap_err_with(|| {
+                    format!(
+                        "Failed to apply `{description}` to node/{}, all the changes were reverted",
+                        self.node_name
+                    )
+                });
+            }
+            info!(node = %self.node_name, change = %description, "Node configuration change applied");
+        }
+        if trusted_identities_changed {
+            info!(node = %self.node_name, "Trusted identities updated");
+        }
+
+        let owned: Vec<String> = declared.iter().map(|r| r.id()).collect();
+        opts.state
+            .nodes
+            .get(&self.node_name)?
+            .set_config_resources(&owned)?;
+        self.applied = declared;
+        self.trusted_identities = spec.trusted_identities;
+        Ok(())
+    }
+
+    /// Revert the given changes, in the reverse order.
+    /// Errors are only logged, so that as much as possible of the previous
+    /// configuration is restored.
+    async fn rollback(
+        &self,
+        ctx: &Context,
+        opts: &CommandGlobalOpts,
+        node: &BackgroundNode,
+        applied: Vec<Change>,
+        trusted_identities_changed: bool,
+    ) {
+        for change in applied.iter().rev() {
+            let result = match self.revert(change) {
+                Ok(revert) => self.apply_revert(ctx, opts, node, revert).await,
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(()) => {
+                    info!(node = %self.node_name, %change, "Node configuration change reverted")
+                }
+                Err(err) => {
+                    warn!(node = %self.node_name, %change, %err, "Failed to revert a node configuration change")
+                }
+            }
+        }
+
+        if trusted_identities_changed {
+            if let Err(err) =
+                write_atomically(&self.trusted_identities_file, &self.trusted_identities)
+            {
+                warn!(node = %self.node_name, %err, "Failed to restore the trusted identities");
+            }
+        }
+    }
+
+    /// Return the action reverting a change.
+    /// A resource is restored as declared by the last applied configuration or, if it was
+    /// not declared there, as it existed on the node before being updated
+    fn revert(&self, change: &Change) -> miette::Result<Revert> {
+        let previous = |kind: ResourceKind, name: &str| {
+            self.applied
+                .iter()
+                .find(|r| r.kind == kind && r.name == name)
+                .cloned()
+        };
+        match change {
+            Change::Create(declared) => Ok(Revert::Delete(declared.clone())),
+            Change::Update { existing, declared } => {
+                let previous = match previous(declared.kind, &declared.name) {
+                    Some(previous) => previous,
+                    None => declared.previous_version(existing)?,
+                };
+                Ok(Revert::Replace {
+                    current: declared.clone(),
+                    previous,
+                })
+            }
+            Change::Delete(existing) => match previous(existing.kind, &existing.name) {
+                Some(previous) => Ok(Revert::Recreate(previous)),
+                None => Err(miette!(
+                    "{} was not declared by the previous configuration",
+                    existing.id()
+                )),
+            },
+        }
+    }
+
+    async fn apply_revert(
+        &self,
+        ctx: &Context,
+        opts: &CommandGlobalOpts,
+        node: &BackgroundNode,
+        revert: Revert,
+    ) -> miette::Result<()> {
+        match revert {
+            Revert::Delete(current) => current.to_existing()?.delete(ctx, node).await,
+            Revert::Replace { current, previous } => {
+                // A policy is replaced when it is created again
+                if current.kind != ResourceKind::Policy {
+                    let _ = current.to_existing()?.delete(ctx, node).await;
+                }
+                previous.create(ctx, opts, &self.node_name, node).await
+            }
+            Revert::Recreate(previous) => previous.create(ctx, opts, &self.node_name, node).await,
+        }
+    }
+}
+
+/// Handle of a [`NodeConfigWatcher`] watching a configuration file.
+/// The file stops being watched when the handle is dropped, for instance when the node stops
+pub struct NodeConfigWatcherHandle {
+    _stop: oneshot::Sender<()>,
+}
+
+/// Action reverting a change made to a node
+#[derive(Debug)]
+enum Revert {
+    /// Delete a created resource
+    Delete(DeclaredResource),
+    /// Replace an updated resource with its previous version
+    Replace {
+        current: DeclaredResource,
+        previous: DeclaredResource,
+    },
+    /// Create again a deleted resource
+    Recreate(DeclaredResource),
+}
+
+fn read_config_file(path: &Path) -> miette::Result<String> {
+    std::fs::read_to_string(path)
+        .into_diagnostic()
+        .wrap_err_with(|| {
+            format!(
+                "Failed to read the node configuration file {}",
+                path.display()
+            )
+        })
+}
+
+/// Write a file through a temporary file, so that it is never read partially written
+fn write_atomically(path: &Path, contents: &str) -> miette::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, contents).into_diagnostic()?;
+    std::fs::rename(&tmp, path).into_diagnostic()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run::resources::{ExistingResource, ResourceSpec};
+
+    fn watcher(applied: Vec<DeclaredResource>) -> NodeConfigWatcher {
+        NodeConfigWatcher {
+            node_name: "n1".to_string(),
+            path: PathBuf::from("n1.yaml"),
+            contents: String::new(),
+            initial: None,
+            applied,
+            trusted_identities: String::new(),
+            trusted_identities_file: PathBuf::from("trusted_identities.json"),
+        }
+    }
+
+    fn outlet(name: &str, to: &str) -> DeclaredResource {
+        DeclaredResource::new(
+            ResourceKind::TcpOutlet,
+            name,
+            ResourceSpec::TcpOutlet {
+                from: Address::from_string(name),
+                to: to.parse().unwrap(),
+            },
+        )
+    }
+
+    fn existing_outlet(name: &str, to: &str) -> ExistingResource {
+        ExistingResource::new(
+            ResourceKind::TcpOutlet,
+            name,
+            format!("/node/outlet/{name}"),
+        )
+        .with_attribute("from", format!("/service/{name}"))
+        .with_attribute("to", to)
+    }
+
+    fn outlet_target(resource: &DeclaredResource) -> &str {
+        resource.attributes["to"].as_str()
+    }
+
+    #[test]
+    fn revert_deletes_created_resources() {
+        let watcher = watcher(vec![]);
+        let revert = watcher
+            .revert(&Change::Create(outlet("o1", "127.0.0.1:5000")))
+            .unwrap();
+        match revert {
+            Revert::Delete(current) => assert_eq!(current.name, "o1"),
+            revert => panic!("unexpected revert {revert:?}"),
+        }
+    }
+
+    #[test]
+    fn revert_restores_the_applied_version_of_updated_resources() {
+        let watcher = watcher(vec![outlet("o1", "127.0.0.1:5000")]);
+        let revert = watcher
+            .revert(&Change::Update {
+                existing: existing_outlet("o1", "127.0.0.1:5000"),
+                declared: outlet("o1", "127.0.0.1:6000"),
+            })
+            .unwrap();
+        match revert {
+            Revert::Replace { current, previous } => {
+                assert_eq!(outlet_target(&current), "127.0.0.1:6000");
+                assert_eq!(outlet_target(&previous), "127.0.0.1:5000");
+            }
+            revert => panic!("unexpected revert {revert:?}"),
+        }
+    }
+
+    #[test]
+    fn revert_restores_unmanaged_resources_as_they_existed() {
+        // The outlet was created by another command, then declared in the configuration
+        let watcher = watcher(vec![]);
+        let revert = watcher
+            .revert(&Change::Update {
+                existing: existing_outlet("o1", "127.0.0.1:4000"),
+                declared: outlet("o1", "127.0.0.1:6000"),
+            })
+            .unwrap();
+        match revert {
+            Revert::Replace { previous, .. } => {
+                assert_eq!(previous.name, "o1");
+                assert_eq!(outlet_target(&previous), "127.0.0.1:4000");
+            }
+            revert => panic!("unexpected revert {revert:?}"),
+        }
+    }
+
+    #[test]
+    fn revert_recreates_deleted_resources() {
+        let watcher = watcher(vec![outlet("o1", "127.0.0.1:5000")]);
+        let revert = watcher
+            .revert(&Change::Delete(existing_outlet("o1", "127.0.0.1:5000")))
+            .unwrap();
+        match revert {
+            Revert::Recreate(previous) => assert_eq!(outlet_target(&previous), "127.0.0.1:5000"),
+            revert => panic!("unexpected revert {revert:?}"),
+        }
+    }
+
+    #[test]
+    fn revert_fails_for_resources_which_can_not_be_restored() {
+        let watcher = watcher(vec![]);
+        assert!(watcher
+            .revert(&Change::Delete(existing_outlet("o1", "127.0.0.1:5000")))
+            .is_err());
+
+        // The target of the outlet is not known
+        let existing = ExistingResource::new(ResourceKind::TcpOutlet, "o1", "/node/outlet/o1")
+            .with_attribute("from", "/service/o1");
+        assert!(watcher
+            .revert(&Change::Update {
+                existing,
+                declared: outlet("o1", "127.0.0.1:6000"),
+            })
+            .is_err());
+    }
+}
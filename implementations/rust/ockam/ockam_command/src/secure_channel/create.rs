@@ -61,6 +61,11 @@ pub struct CreateCommand {
     /// Name of a stored Credential to use within this Secure Channel
     #[arg(short, long)]
     pub credential: Option<String>,
+
+    /// Name of an attribute of the credential to present to the listener, when the credential
+    /// was issued with selective disclosure. Can be repeated. All the attributes are presented by default
+    #[arg(long = "disclose", value_name = "ATTRIBUTE")]
+    pub disclosed_attributes: Option<Vec<String>>,
 }
 
 impl CreateCommand {
@@ -111,7 +116,7 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> m
     let is_finished: Mutex<bool> = Mutex::new(false);
 
     let from = cmd.parse_from_node()?;
-    let node = BackgroundNode::create(&ctx, &opts.state, &from).await?;
+    let node = opts.background_node(&ctx, &from).await?;
     let to = cmd.parse_to_route(&opts, &ctx, &node).await?;
     let authorized_identifiers = cmd.authorized.clone();
 
@@ -123,6 +128,10 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> m
             Some(identity_name),
             cmd.credential.clone(),
         );
+        let payload = match cmd.disclosed_attributes.clone() {
+            Some(attributes) => payload.with_disclosed_attributes(attributes),
+            None => payload,
+        };
         let request = Request::post("/node/secure_channel").body(payload);
         let response: CreateSecureChannelResponse = node.ask(&ctx, request).await?;
         *is_finished.lock().await = true;
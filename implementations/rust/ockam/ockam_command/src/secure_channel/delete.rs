@@ -5,7 +5,6 @@ use colorful::Colorful;
 use serde_json::json;
 
 use ockam::{route, Context};
-use ockam_api::nodes::BackgroundNode;
 use ockam_api::{nodes::models::secure_channel::DeleteSecureChannelResponse, route_to_multiaddr};
 use ockam_core::{Address, AddressParseError};
 
@@ -64,10 +63,10 @@ impl DeleteCommand {
                             println!("{multiaddr}")
                         }
 
-                        // if output format is json, write json to stdout.
-                        if options.global_args.output_format == OutputFormat::Json {
+                        // if output format is not plain, write the structured output to stdout.
+                        if options.global_args.output_format != OutputFormat::Plain {
                             let json = json!([{ "address": multiaddr.to_string() }]);
-                            println!("{json}");
+                            let _ = options.terminal.clone().stdout().json(json).write_line();
                         }
 
                         // if stderr is interactive/tty and we haven't been asked to be quiet
@@ -155,7 +154,7 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, DeleteCommand)) -> m
         let at = get_node_name(&opts.state, &cmd.at);
         let node_name = parse_node_name(&at)?;
         let address = &cmd.address;
-        let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+        let node = opts.background_node(&ctx, &node_name).await?;
         let response: DeleteSecureChannelResponse =
             node.ask(&ctx, api::delete_secure_channel(address)).await?;
         cmd.print_output(&node_name, address, &opts, response);
@@ -58,14 +58,18 @@ impl SecureRelayOutlet {
 }
 
 async fn rpc(
-    _ctx: Context,
+    ctx: Context,
     (opts, cmd): (CommandGlobalOpts, SecureRelayOutlet),
 ) -> miette::Result<()> {
-    cmd.create_config_and_start(opts).await
+    cmd.create_config_and_start(&ctx, opts).await
 }
 
 impl SecureRelayOutlet {
-    pub async fn create_config_and_start(self, opts: CommandGlobalOpts) -> miette::Result<()> {
+    pub async fn create_config_and_start(
+        self,
+        ctx: &Context,
+        opts: CommandGlobalOpts,
+    ) -> miette::Result<()> {
         let stdout = opts.terminal.clone().stdout();
 
         let enrollment_ticket: String = if let Some(t) = self.enroll.enroll_ticket.as_ref() {
@@ -110,6 +114,6 @@ impl SecureRelayOutlet {
             ))
             .write_line()?;
 
-        ConfigRunner::go(opts, &recipe, true).await
+        ConfigRunner::go(ctx, opts, &recipe, true).await
     }
 }
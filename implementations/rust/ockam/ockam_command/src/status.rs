@@ -48,6 +48,7 @@ async fn run_impl(
     opts: CommandGlobalOpts,
     cmd: StatusCommand,
 ) -> miette::Result<()> {
+    opts.reject_remote()?;
     let identities_details = get_identities_details(&opts, cmd.all)?;
     let nodes_details = get_nodes_details(ctx, &opts).await?;
     let orchestrator_version =
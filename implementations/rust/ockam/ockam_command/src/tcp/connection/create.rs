@@ -5,10 +5,10 @@ use serde_json::json;
 
 use ockam_api::address::extract_address_value;
 use ockam_api::nodes::models::transport::TransportStatus;
-use ockam_api::nodes::BackgroundNode;
 use ockam_node::Context;
 
 use crate::node::{get_node_name, initialize_node_if_default};
+use crate::tcp::util::TcpLivenessArgs;
 use crate::util::is_tty;
 use crate::{
     docs,
@@ -36,6 +36,9 @@ pub struct CreateCommand {
     /// The address to connect to
     #[arg(id = "to", short, long, value_name = "ADDRESS")]
     pub address: String,
+
+    #[command(flatten)]
+    pub liveness: TcpLivenessArgs,
 }
 
 impl CreateCommand {
@@ -49,9 +52,9 @@ impl CreateCommand {
         opts: &CommandGlobalOpts,
         response: &TransportStatus,
     ) -> miette::Result<()> {
-        // if output format is json, write json to stdout.
+        // if output format is not plain, write the structured output to stdout.
         match opts.global_args.output_format {
-            OutputFormat::Plain => {
+            OutputFormat::Plain if !opts.global_args.output_filter().has_query() => {
                 if !is_tty(std::io::stdout()) {
                     println!("{}", response.multiaddr().into_diagnostic()?);
                     return Ok(());
@@ -78,9 +81,9 @@ impl CreateCommand {
                     );
                 }
             }
-            OutputFormat::Json => {
+            _ => {
                 let json = json!([{"route": response.multiaddr().into_diagnostic()? }]);
-                println!("{json}");
+                opts.terminal.clone().stdout().json(json).write_line()?;
             }
         }
         Ok(())
@@ -93,7 +96,7 @@ async fn run_impl(
 ) -> miette::Result<()> {
     let from = get_node_name(&opts.state, &cmd.node_opts.from);
     let node_name = extract_address_value(&from)?;
-    let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    let node = opts.background_node(&ctx, &node_name).await?;
     let request = api::create_tcp_connection(&cmd);
     let transport_status: TransportStatus = node.ask(&ctx, request).await?;
     cmd.print_output(&opts, &transport_status)
@@ -1,7 +1,7 @@
 use clap::Args;
 use colorful::Colorful;
 
-use ockam_api::nodes::{models, BackgroundNode};
+use ockam_api::nodes::models;
 use ockam_core::api::Request;
 use ockam_node::Context;
 
@@ -43,7 +43,7 @@ async fn run_impl(
     )? {
         let address = cmd.address;
         let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
-        let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+        let node = opts.background_node(&ctx, &node_name).await?;
         let req = Request::delete("/node/tcp/connection")
             .body(models::transport::DeleteTransport::new(address.clone()));
         node.tell(&ctx, req).await?;
@@ -9,7 +9,6 @@ use tokio::try_join;
 use ockam_api::address::extract_address_value;
 use ockam_api::cli_state::StateDirTrait;
 use ockam_api::nodes::models::transport::{TransportList, TransportStatus};
-use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 use ockam_node::Context;
 
@@ -50,7 +49,7 @@ async fn run_impl(
         return Err(miette!("The node '{}' is not running", node_name));
     }
 
-    let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    let node = opts.background_node(&ctx, &node_name).await?;
     let is_finished: Mutex<bool> = Mutex::new(false);
 
     let get_transports = async {
@@ -3,7 +3,6 @@ use clap::Args;
 use ockam::Context;
 use ockam_api::address::extract_address_value;
 use ockam_api::nodes::models::transport::TransportStatus;
-use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
 use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
@@ -39,7 +38,7 @@ async fn run_impl(
 ) -> miette::Result<()> {
     let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
     let node_name = extract_address_value(&node_name)?;
-    let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    let node = opts.background_node(&ctx, &node_name).await?;
     let transport_status: TransportStatus = node
         .ask(
             &ctx,
@@ -56,7 +55,9 @@ async fn run_impl(
         "  Processor address: {}",
         transport_status.processor_address
     );
-    println!("  Flow Control Id: {}", transport_status.flow_control_id);
+    if let Some(flow_control_id) = &transport_status.flow_control_id {
+        println!("  Flow Control Id: {}", flow_control_id);
+    }
 
     Ok(())
 }
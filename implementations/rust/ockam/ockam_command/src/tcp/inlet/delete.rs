@@ -2,7 +2,6 @@ use clap::Args;
 use colorful::Colorful;
 
 use ockam::Context;
-use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
 use crate::fmt_ok;
@@ -48,7 +47,7 @@ pub async fn run_impl(
         let alias = cmd.alias.clone();
         let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
         let node_name = parse_node_name(&node_name)?;
-        let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+        let node = opts.background_node(&ctx, &node_name).await?;
         node.tell(&ctx, Request::delete(format!("/node/inlet/{alias}")))
             .await?;
 
@@ -2,6 +2,7 @@ pub(crate) mod create;
 mod delete;
 mod list;
 mod show;
+mod update;
 
 use crate::{docs, CommandGlobalOpts};
 use clap::{Args, Subcommand};
@@ -9,6 +10,7 @@ use create::CreateCommand;
 use delete::DeleteCommand;
 pub(crate) use list::ListCommand;
 pub(crate) use show::ShowCommand;
+use update::UpdateCommand;
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/after_long_help.txt");
@@ -32,6 +34,7 @@ pub enum TcpInletSubCommand {
     Delete(DeleteCommand),
     List(ListCommand),
     Show(ShowCommand),
+    Update(UpdateCommand),
 }
 
 impl TcpInletCommand {
@@ -41,6 +44,7 @@ impl TcpInletCommand {
             TcpInletSubCommand::Delete(c) => c.run(options),
             TcpInletSubCommand::List(c) => c.run(options),
             TcpInletSubCommand::Show(c) => c.run(options),
+            TcpInletSubCommand::Update(c) => c.run(options),
         }
     }
 }
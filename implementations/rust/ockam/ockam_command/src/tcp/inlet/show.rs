@@ -5,7 +5,6 @@ use miette::IntoDiagnostic;
 
 use ockam::Context;
 use ockam_api::nodes::models::portal::InletStatus;
-use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
 use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
@@ -46,7 +45,7 @@ pub async fn run_impl(
     let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
     let node_name = parse_node_name(&node_name)?;
 
-    let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    let node = opts.background_node(&ctx, &node_name).await?;
     let inlet_status: InletStatus = node.ask(&ctx, make_api_request(cmd)?).await?;
 
     let json = serde_json::to_string(&inlet_status).into_diagnostic()?;
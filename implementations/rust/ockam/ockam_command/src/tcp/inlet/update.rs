@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+
+use ockam::identity::Identifier;
+use ockam::Context;
+use ockam_api::nodes::models::portal::{InletStatus, UpdateInlet};
+use ockam_core::api::Request;
+use ockam_multiaddr::proto::Project;
+use ockam_multiaddr::{MultiAddr, Protocol as _};
+
+use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
+use crate::tcp::util::alias_parser;
+use crate::terminal::OckamColor;
+use crate::util::duration::duration_parser;
+use crate::util::{node_rpc, parse_node_name, process_nodes_multiaddr};
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const AFTER_LONG_HELP: &str = include_str!("./static/update/after_long_help.txt");
+
+/// Change the route of a TCP Inlet to its outlet.
+/// The inlet keeps accepting tcp connections on the same address
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct UpdateCommand {
+    /// Name assigned to the inlet that will be updated
+    #[arg(display_order = 900, required = true, id = "ALIAS", value_parser = alias_parser)]
+    alias: String,
+
+    /// New route to a tcp outlet.
+    #[arg(long, display_order = 900, id = "ROUTE")]
+    to: MultiAddr,
+
+    /// Authorized identity for secure channel connection. The current one is kept if not provided
+    #[arg(
+        long,
+        name = "AUTHORIZED",
+        display_order = 900,
+        conflicts_with = "clear_authorized"
+    )]
+    authorized: Option<Identifier>,
+
+    /// Remove the authorized identity of the inlet
+    #[arg(long, display_order = 900)]
+    clear_authorized: bool,
+
+    /// Time to wait for the new outlet to be available.
+    #[arg(long, display_order = 900, id = "WAIT", default_value = "5s", value_parser = duration_parser)]
+    connection_wait: Duration,
+
+    /// Node on which the tcp inlet is running. If none are provided, the default node will be used
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl UpdateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_node_if_default(&opts, &self.node_opts.at_node);
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+pub async fn run_impl(
+    ctx: Context,
+    (opts, mut cmd): (CommandGlobalOpts, UpdateCommand),
+) -> miette::Result<()> {
+    cmd.to = process_nodes_multiaddr(&cmd.to, &opts.state)?;
+    if cmd.to.matches(0, &[Project::CODE.into()]) && cmd.authorized.is_some() {
+        return Err(miette!(
+            "--authorized can not be used with project addresses"
+        ));
+    }
+
+    let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
+    let node_name = parse_node_name(&node_name)?;
+    let node = opts.background_node(&ctx, &node_name).await?;
+    let mut update = UpdateInlet::new(
+        cmd.to.clone(),
+        cmd.authorized.clone(),
+        Some(cmd.connection_wait),
+    );
+    if cmd.clear_authorized {
+        update = update.clear_authorized();
+    }
+    let req = Request::patch(format!("/node/inlet/{}", cmd.alias)).body(update);
+    let inlet_status: InletStatus = node.ask(&ctx, req).await?;
+
+    let json = serde_json::to_string_pretty(&inlet_status).into_diagnostic()?;
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "TCP Inlet {} on node {} now sends traffic to the outlet at {}",
+            &cmd.alias
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            &node_name
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            &cmd.to
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        ))
+        .machine(&inlet_status.bind_addr)
+        .json(json)
+        .write_line()?;
+
+    Ok(())
+}
@@ -2,13 +2,13 @@ use clap::Args;
 use miette::IntoDiagnostic;
 
 use ockam_api::nodes::models::transport::{CreateTcpListener, TransportStatus};
-use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 use ockam_multiaddr::proto::{DnsAddr, Tcp};
 use ockam_multiaddr::MultiAddr;
 use ockam_node::Context;
 
 use crate::node::{get_node_name, initialize_node_if_default};
+use crate::tcp::util::TcpLivenessArgs;
 use crate::util::{node_rpc, parse_node_name};
 use crate::{docs, CommandGlobalOpts};
 
@@ -24,6 +24,9 @@ pub struct CreateCommand {
 
     /// Address for this listener (eg. 127.0.0.1:7000)
     pub address: String,
+
+    #[command(flatten)]
+    pub liveness: TcpLivenessArgs,
 }
 
 impl CreateCommand {
@@ -39,12 +42,13 @@ async fn run_impl(
 ) -> miette::Result<()> {
     let node_name = get_node_name(&opts.state, &cmd.at);
     let node_name = parse_node_name(&node_name)?;
-    let node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
+    let node = opts.background_node(&ctx, &node_name).await?;
+    let mut body = CreateTcpListener::new(cmd.address);
+    if let Some(liveness) = cmd.liveness.liveness() {
+        body = body.with_liveness(liveness);
+    }
     let transport_status: TransportStatus = node
-        .ask(
-            &ctx,
-            Request::post("/node/tcp/listener").body(CreateTcpListener::new(cmd.address)),
-        )
+        .ask(&ctx, Request::post("/node/tcp/listener").body(body))
         .await?;
 
     let socket = transport_status.socket_addr().into_diagnostic()?;
@@ -2,6 +2,7 @@ pub mod create;
 mod delete;
 pub mod list;
 mod show;
+mod update;
 
 use crate::{docs, CommandGlobalOpts};
 use clap::{Args, Subcommand};
@@ -9,6 +10,7 @@ use create::CreateCommand;
 use delete::DeleteCommand;
 use list::ListCommand;
 use show::ShowCommand;
+use update::UpdateCommand;
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/after_long_help.txt");
@@ -32,6 +34,7 @@ pub enum TcpOutletSubCommand {
     Delete(DeleteCommand),
     List(ListCommand),
     Show(ShowCommand),
+    Update(UpdateCommand),
 }
 
 impl TcpOutletCommand {
@@ -41,6 +44,7 @@ impl TcpOutletCommand {
             TcpOutletSubCommand::Delete(c) => c.run(options),
             TcpOutletSubCommand::List(c) => c.run(options),
             TcpOutletSubCommand::Show(c) => c.run(options),
+            TcpOutletSubCommand::Update(c) => c.run(options),
         }
     }
 }
@@ -0,0 +1,75 @@
+use std::net::SocketAddr;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+
+use ockam::Context;
+use ockam_api::nodes::models::portal::{OutletStatus, UpdateOutlet};
+use ockam_core::api::Request;
+
+use crate::node::{get_node_name, initialize_node_if_default, NodeOpts};
+use crate::tcp::util::alias_parser;
+use crate::terminal::OckamColor;
+use crate::util::parsers::socket_addr_parser;
+use crate::util::{node_rpc, parse_node_name};
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const AFTER_LONG_HELP: &str = include_str!("./static/update/after_long_help.txt");
+
+/// Change the TCP address of a TCP Outlet.
+/// The outlet keeps its address, so the inlets sending traffic to it don't need to be changed
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct UpdateCommand {
+    /// Name assigned to the outlet that will be updated
+    #[arg(display_order = 900, required = true, id = "ALIAS", value_parser = alias_parser)]
+    alias: String,
+
+    /// New TCP address to send raw tcp traffic.
+    #[arg(long, display_order = 902, id = "SOCKET_ADDRESS", value_parser = socket_addr_parser)]
+    to: SocketAddr,
+
+    /// Node on which the tcp outlet is running. If none are provided, the default node will be used
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl UpdateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_node_if_default(&opts, &self.node_opts.at_node);
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+pub async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, UpdateCommand),
+) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.node_opts.at_node);
+    let node_name = parse_node_name(&node_name)?;
+    let node = opts.background_node(&ctx, &node_name).await?;
+    let req = Request::patch(format!("/node/outlet/{}", cmd.alias)).body(UpdateOutlet::new(cmd.to));
+    let outlet_status: OutletStatus = node.ask(&ctx, req).await?;
+
+    let json = serde_json::to_string_pretty(&outlet_status).into_diagnostic()?;
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "TCP Outlet {} on node {} now sends traffic to {}",
+            &cmd.alias
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            &node_name
+                .to_string()
+                .color(OckamColor::PrimaryResource.color()),
+            &cmd.to
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
+        ))
+        .machine(outlet_status.socket_addr.to_string())
+        .json(json)
+        .write_line()?;
+
+    Ok(())
+}
@@ -1,6 +1,13 @@
-use crate::Result;
+use std::time::Duration;
+
+use clap::Args;
 use miette::miette;
 
+use ockam_api::nodes::models::transport::TcpLiveness;
+
+use crate::util::duration::duration_parser;
+use crate::Result;
+
 pub fn alias_parser(arg: &str) -> Result<String> {
     if arg.contains(':') {
         Err(miette!("an alias must not contain ':' characters").into())
@@ -8,3 +15,42 @@ pub fn alias_parser(arg: &str) -> Result<String> {
         Ok(arg.to_string())
     }
 }
+
+/// Flags used to detect dead peers on TCP connections
+#[derive(Clone, Debug, Args)]
+pub struct TcpLivenessArgs {
+    /// Inactivity before TCP keepalive probes are sent (eg. 5m)
+    #[arg(long, value_name = "DURATION", value_parser = duration_parser, conflicts_with = "no_keepalive")]
+    pub keepalive: Option<Duration>,
+
+    /// Disable TCP keepalive probes
+    #[arg(long)]
+    pub no_keepalive: bool,
+
+    /// Send a heartbeat when nothing else was sent for that long (eg. 30s)
+    #[arg(long, value_name = "DURATION", value_parser = duration_parser)]
+    pub heartbeat_interval: Option<Duration>,
+
+    /// Close the connection when nothing, not even a heartbeat, was received for that long (eg. 2m)
+    #[arg(long, value_name = "DURATION", value_parser = duration_parser)]
+    pub idle_timeout: Option<Duration>,
+}
+
+impl TcpLivenessArgs {
+    /// Return the settings to send to the node, if any flag was given
+    pub fn liveness(&self) -> Option<TcpLiveness> {
+        if self.keepalive.is_none()
+            && !self.no_keepalive
+            && self.heartbeat_interval.is_none()
+            && self.idle_timeout.is_none()
+        {
+            return None;
+        }
+        Some(TcpLiveness {
+            keepalive_time: self.keepalive.map(|d| d.as_millis() as u64),
+            no_keepalive: self.no_keepalive,
+            heartbeat_interval: self.heartbeat_interval.map(|d| d.as_millis() as u64),
+            idle_timeout: self.idle_timeout.map(|d| d.as_millis() as u64),
+        })
+    }
+}
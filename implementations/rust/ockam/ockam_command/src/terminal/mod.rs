@@ -17,6 +17,7 @@ use ockam_core::env::{get_env, get_env_with_default, FromString};
 use ockam_core::errcode::Kind;
 
 use crate::error::Error;
+use crate::output::OutputFilter;
 use crate::{fmt_list, fmt_log, fmt_warn, OutputFormat, Result};
 
 pub mod colors;
@@ -31,6 +32,7 @@ pub struct Terminal<T: TerminalWriter, WriteMode = ToStdErr> {
     quiet: bool,
     no_input: bool,
     output_format: OutputFormat,
+    output_filter: OutputFilter,
     mode: WriteMode,
 }
 
@@ -209,6 +211,7 @@ impl<W: TerminalWriter> Terminal<W> {
             quiet,
             no_input,
             output_format,
+            output_filter: OutputFilter::default(),
             mode: ToStdErr,
         }
     }
@@ -273,6 +276,12 @@ impl<W: TerminalWriter> Terminal<W> {
         clone.quiet = true;
         clone
     }
+
+    /// Set the selection applied to the structured output of the commands
+    pub fn set_output_filter(mut self, output_filter: OutputFilter) -> Self {
+        self.output_filter = output_filter;
+        self
+    }
 }
 
 // Logging mode
@@ -354,6 +363,7 @@ impl<W: TerminalWriter> Terminal<W, ToStdErr> {
             quiet: self.quiet,
             no_input: self.no_input,
             output_format: self.output_format,
+            output_filter: self.output_filter,
             mode: ToStdOut {
                 output: Output::new(),
             },
@@ -396,8 +406,8 @@ impl<W: TerminalWriter> Terminal<W, ToStdOut> {
         let json = self.mode.output.json.as_ref();
 
         let msg = match self.output_format {
-            OutputFormat::Plain => {
-                if self.stdout.is_tty() {
+            OutputFormat::Plain if !self.output_filter.has_query() => {
+                let msg = if self.stdout.is_tty() {
                     // If not set, fallback with the following priority: Machine -> JSON
                     match (plain, machine, json) {
                         (Some(plain), _, _) => plain,
@@ -413,12 +423,15 @@ impl<W: TerminalWriter> Terminal<W, ToStdOut> {
                         (None, None, Some(plain)) => plain,
                         _ => unreachable!(),
                     }
-                }
-            }
-            // If not set, no fallback is provided and returns an error
-            OutputFormat::Json => {
-                json.ok_or(miette!("JSON output is not defined for this command"))?
+                };
+                msg.clone()
             }
+            // If not set, no fallback is provided and returns an error.
+            // The other formats, and queries, are computed from the JSON output
+            _ => self.output_format.format_json(
+                json.ok_or(miette!("JSON output is not defined for this command"))?,
+                &self.output_filter,
+            )?,
         };
         self.stdout.write_line(msg)
     }
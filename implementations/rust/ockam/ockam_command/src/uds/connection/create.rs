@@ -0,0 +1,63 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+
+use ockam_api::nodes::models::transport::{CreateTransportConnection, TransportStatus};
+use ockam_core::api::Request;
+use ockam_node::Context;
+
+use crate::node::{get_node_name, initialize_node_if_default};
+use crate::util::{node_rpc, parse_node_name};
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const AFTER_LONG_HELP: &str = include_str!("./static/create/after_long_help.txt");
+
+/// Create a Unix domain socket connection
+#[derive(Args, Clone, Debug)]
+#[command(arg_required_else_help = true, after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct CreateCommand {
+    /// Node that will initiate the connection
+    #[arg(global = true, short, long, value_name = "NODE")]
+    pub from: Option<String>,
+
+    /// The path of the socket file to connect to (eg. /tmp/ockam.sock)
+    #[arg(id = "to", short, long, value_name = "ADDRESS")]
+    pub address: String,
+}
+
+impl CreateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        initialize_node_if_default(&opts, &self.from);
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, CreateCommand),
+) -> miette::Result<()> {
+    let node_name = get_node_name(&opts.state, &cmd.from);
+    let node_name = parse_node_name(&node_name)?;
+    let node = opts.background_node(&ctx, &node_name).await?;
+    let transport_status: TransportStatus = node
+        .ask(
+            &ctx,
+            Request::post("/node/uds/connection")
+                .body(CreateTransportConnection::new(cmd.address.clone())),
+        )
+        .await?;
+
+    let multiaddr = transport_status.multiaddr().into_diagnostic()?;
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "UDS connection to {} created! You can send messages to it via this route:\n`{}`",
+            cmd.address,
+            multiaddr
+        ))
+        .machine(multiaddr.to_string())
+        .json(serde_json::json!({ "route": multiaddr.to_string() }))
+        .write_line()?;
+
+    Ok(())
+}
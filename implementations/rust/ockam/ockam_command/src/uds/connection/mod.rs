@@ -0,0 +1,40 @@
+mod create;
+mod delete;
+mod list;
+
+pub(crate) use create::CreateCommand;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use list::ListCommand;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+
+/// Manage UDS Connections
+#[derive(Args, Clone, Debug)]
+#[command(arg_required_else_help = true)]
+pub struct UdsConnectionCommand {
+    #[command(subcommand)]
+    subcommand: UdsConnectionSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum UdsConnectionSubCommand {
+    /// Create a uds connection from the selected node
+    Create(CreateCommand),
+
+    /// Delete a uds connection on the selected node
+    Delete(DeleteCommand),
+
+    /// List the uds connections of the selected node
+    List(ListCommand),
+}
+
+impl UdsConnectionCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            UdsConnectionSubCommand::Create(c) => c.run(options),
+            UdsConnectionSubCommand::Delete(c) => c.run(options),
+            UdsConnectionSubCommand::List(c) => c.run(options),
+        }
+    }
+}
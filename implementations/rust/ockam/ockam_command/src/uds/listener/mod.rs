@@ -0,0 +1,39 @@
+mod create;
+mod delete;
+mod list;
+
+pub(crate) use create::CreateCommand;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use list::ListCommand;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+
+/// Manage UDS Listeners
+#[derive(Args, Clone, Debug)]
+pub struct UdsListenerCommand {
+    #[command(subcommand)]
+    subcommand: UdsListenerSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum UdsListenerSubCommand {
+    /// Create uds listener on the selected node
+    Create(CreateCommand),
+
+    /// Delete uds listener on the selected node
+    Delete(DeleteCommand),
+
+    /// List uds listeners registered on the selected node
+    List(ListCommand),
+}
+
+impl UdsListenerCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            UdsListenerSubCommand::Create(c) => c.run(options),
+            UdsListenerSubCommand::Delete(c) => c.run(options),
+            UdsListenerSubCommand::List(c) => c.run(options),
+        }
+    }
+}
@@ -39,11 +39,36 @@ pub(crate) fn list_tcp_listeners() -> Request<()> {
     Request::get("/node/tcp/listener")
 }
 
+/// Construct a request to query node WebSocket listeners
+pub(crate) fn list_ws_listeners() -> Request<()> {
+    Request::get("/node/ws/listener")
+}
+
+/// Construct a request to query node WebSocket connections
+pub(crate) fn list_ws_connections() -> Request<()> {
+    Request::get("/node/ws/connection")
+}
+
+/// Construct a request to query node UDS listeners
+#[cfg(unix)]
+pub(crate) fn list_uds_listeners() -> Request<()> {
+    Request::get("/node/uds/listener")
+}
+
+/// Construct a request to query node UDS connections
+#[cfg(unix)]
+pub(crate) fn list_uds_connections() -> Request<()> {
+    Request::get("/node/uds/connection")
+}
+
 /// Construct a request to create node tcp connection
 pub(crate) fn create_tcp_connection(
     cmd: &crate::tcp::connection::CreateCommand,
 ) -> Request<models::transport::CreateTcpConnection> {
-    let payload = models::transport::CreateTcpConnection::new(cmd.address.clone());
+    let mut payload = models::transport::CreateTcpConnection::new(cmd.address.clone());
+    if let Some(liveness) = cmd.liveness.liveness() {
+        payload = payload.with_liveness(liveness);
+    }
 
     Request::post("/node/tcp/connection").body(payload)
 }
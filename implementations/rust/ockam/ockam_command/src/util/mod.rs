@@ -1,6 +1,6 @@
 use std::{
     net::{SocketAddr, TcpListener},
-    path::Path,
+    path::{Path, PathBuf},
     str::FromStr,
 };
 
@@ -8,6 +8,7 @@ use miette::Context as _;
 use miette::{miette, IntoDiagnostic};
 use tracing::error;
 
+use ockam::identity::CredentialSchema;
 use ockam::{Address, Context, NodeBuilder};
 use ockam_api::cli_state::{CliState, StateDirTrait, StateItemTrait};
 use ockam_api::config::lookup::{InternetAddress, LookupMeta};
@@ -234,6 +235,26 @@ pub fn clean_nodes_multiaddr(
     Ok((new_ma, lookup_meta))
 }
 
+/// Read credential schemas from JSON files
+pub fn read_credential_schemas(paths: &[PathBuf]) -> Result<Vec<CredentialSchema>> {
+    paths
+        .iter()
+        .map(|path| {
+            let contents = std::fs::read_to_string(path)?;
+            serde_json::from_str::<CredentialSchema>(&contents).map_err(|e| {
+                crate::Error::new(
+                    exitcode::CONFIG,
+                    miette!(
+                        "Cannot parse the credential schema {}: {}",
+                        path.display(),
+                        e
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
 pub fn comma_separated<T: AsRef<str>>(data: &[T]) -> String {
     use itertools::Itertools;
 
@@ -30,6 +30,19 @@ pub(crate) fn identity_identifier_parser(input: &str) -> Result<Identifier> {
     Identifier::from_str(input).map_err(|_| miette!("Invalid identity identifier: {input}").into())
 }
 
+/// Helper fn for parsing a relay name prefix reserved to an identity, written as
+/// `<prefix>=<identifier>`
+pub(crate) fn relay_reservation_parser(input: &str) -> Result<(String, Identifier)> {
+    match input.rsplit_once('=') {
+        Some((prefix, identifier)) if !prefix.is_empty() => {
+            Ok((prefix.to_string(), identity_identifier_parser(identifier)?))
+        }
+        _ => Err(
+            miette!("Invalid relay reservation, expected <prefix>=<identifier>: {input}").into(),
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::Ipv6Addr;
@@ -97,4 +110,19 @@ mod tests {
         let invalid_input = "192,166,0.1:9999";
         assert!(socket_addr_parser(invalid_input).is_err());
     }
+
+    #[test]
+    fn test_relay_reservation() {
+        let (prefix, identifier) =
+            relay_reservation_parser("payments=Ie86be15e83d1c93e24dd1967010b01b6df491b45").unwrap();
+        assert_eq!(prefix, "payments");
+        assert_eq!(
+            identifier,
+            Identifier::from_str("Ie86be15e83d1c93e24dd1967010b01b6df491b45").unwrap()
+        );
+
+        assert!(relay_reservation_parser("payments").is_err());
+        assert!(relay_reservation_parser("=Ie86be15e83d1c93e24dd1967010b01b6df491b45").is_err());
+        assert!(relay_reservation_parser("payments=invalid").is_err());
+    }
 }
@@ -0,0 +1,40 @@
+mod create;
+mod delete;
+mod list;
+
+pub(crate) use create::CreateCommand;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use list::ListCommand;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+
+/// Manage WebSocket Connections
+#[derive(Args, Clone, Debug)]
+#[command(arg_required_else_help = true)]
+pub struct WsConnectionCommand {
+    #[command(subcommand)]
+    subcommand: WsConnectionSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum WsConnectionSubCommand {
+    /// Create a ws connection from the selected node
+    Create(CreateCommand),
+
+    /// Delete a ws connection on the selected node
+    Delete(DeleteCommand),
+
+    /// List the ws connections of the selected node
+    List(ListCommand),
+}
+
+impl WsConnectionCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            WsConnectionSubCommand::Create(c) => c.run(options),
+            WsConnectionSubCommand::Delete(c) => c.run(options),
+            WsConnectionSubCommand::List(c) => c.run(options),
+        }
+    }
+}
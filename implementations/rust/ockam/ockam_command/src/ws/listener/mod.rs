@@ -0,0 +1,39 @@
+mod create;
+mod delete;
+mod list;
+
+pub(crate) use create::CreateCommand;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use list::ListCommand;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+
+/// Manage WebSocket Listeners
+#[derive(Args, Clone, Debug)]
+pub struct WsListenerCommand {
+    #[command(subcommand)]
+    subcommand: WsListenerSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum WsListenerSubCommand {
+    /// Create ws listener on the selected node
+    Create(CreateCommand),
+
+    /// Delete ws listener on the selected node
+    Delete(DeleteCommand),
+
+    /// List ws listeners registered on the selected node
+    List(ListCommand),
+}
+
+impl WsListenerCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            WsListenerSubCommand::Create(c) => c.run(options),
+            WsListenerSubCommand::Delete(c) => c.run(options),
+            WsListenerSubCommand::List(c) => c.run(options),
+        }
+    }
+}
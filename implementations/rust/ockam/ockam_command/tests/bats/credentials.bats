@@ -57,3 +57,24 @@ teardown() {
   run_failure "$OCKAM" credential show smart_la_cred
   assert_output --partial "Unable to find credential named smart_la_cred"
 }
+
+@test "credential - issue and verify a credential delegated by a root authority" {
+  run_success "$OCKAM" identity create root
+  root_short=$($OCKAM identity show root)
+
+  run_success "$OCKAM" identity create regional
+  regional_short=$($OCKAM identity show regional)
+
+  run_success "$OCKAM" identity create member
+  member_short=$($OCKAM identity show member)
+
+  "$OCKAM" credential delegate --as root --for "$regional_short" --attribute city=New --encoding hex >"$OCKAM_HOME/delegation"
+
+  "$OCKAM" credential issue --as regional --for "$member_short" --attribute city="New York" --delegation "$(cat "$OCKAM_HOME/delegation")" --encoding hex >"$OCKAM_HOME/credential"
+
+  run_success "$OCKAM" credential verify --issuer "$root_short" --credential-path "$OCKAM_HOME/credential"
+  assert_output --partial "true"
+
+  # The delegation doesn't allow that attribute value
+  run_failure "$OCKAM" credential issue --as regional --for "$member_short" --attribute city="Los Angeles" --delegation "$(cat "$OCKAM_HOME/delegation")"
+}
@@ -64,3 +64,38 @@ teardown() {
   run_success "$OCKAM" identity default "${i}"
   assert_output "${i}"
 }
+
+@test "identity - recover with recovery identities" {
+  r1=$(random_str)
+  r2=$(random_str)
+  r3=$(random_str)
+  i=$(random_str)
+  run_success "$OCKAM" identity create "${r1}"
+  run_success "$OCKAM" identity create "${r2}"
+  run_success "$OCKAM" identity create "${r3}"
+  run_success "$OCKAM" identity create "${i}" --recovery-identity "${r1}" --recovery-identity "${r2}" --recovery-identity "${r3}" --recovery-threshold 2
+  identifier=$($OCKAM identity show "${i}")
+
+  run_success "$OCKAM" identity show "${i}" --full
+  assert_output --partial "recovery_policy:         2 of 3 recovery keys"
+
+  run_success "$OCKAM" identity recover request "${i}"
+  request="$output"
+
+  # The recovery identities sign the request separately
+  run_success "$OCKAM" identity recover sign "${request}" --recovery-identity "${r1}"
+  signature1="$output"
+  run_success "$OCKAM" identity recover sign "${request}" --recovery-identity "${r3}"
+  signature3="$output"
+
+  # One recovery identity is not enough
+  run_failure "$OCKAM" identity recover complete "${i}" --request "${request}" --signature "${signature1}"
+
+  # An identity which is not a recovery identity can't sign
+  run_failure "$OCKAM" identity recover sign "${request}" --recovery-identity "${i}"
+
+  run_success "$OCKAM" identity recover complete "${i}" --request "${request}" --signature "${signature1}" --signature "${signature3}"
+  run_success "$OCKAM" identity show "${i}" --full
+  assert_output --partial "Change[1]:"
+  assert_output --partial "Identifier: ${identifier}"
+}
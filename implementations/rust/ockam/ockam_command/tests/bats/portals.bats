@@ -122,6 +122,49 @@ teardown() {
   assert_output --partial "not found"
 }
 
+@test "portals - update a tcp outlet" {
+  port="$(random_port)"
+  run_success "$OCKAM" node create n1
+
+  run_success $OCKAM tcp-outlet create --at /node/n1 --to "127.0.0.1:$port" --alias "test-outlet"
+
+  run_success $OCKAM tcp-outlet update "test-outlet" --at /node/n1 --to 127.0.0.1:5000
+  run_success $OCKAM tcp-outlet show "test-outlet" --at /node/n1
+  assert_output --partial "From Outlet: /service/outlet"
+  assert_output --regexp "To TCP: 127.0.0.1:5000"
+
+  # Test if non-existing TCP outlet returns NotFound
+  run_failure $OCKAM tcp-outlet update "non-existing-outlet" --at /node/n1 --to 127.0.0.1:5000
+  assert_output --partial "not found"
+}
+
+@test "portals - update a tcp inlet and move tcp traffic through its new outlet" {
+  port="$(random_port)"
+  run_success "$OCKAM" node create n1
+  run_success "$OCKAM" node create n2
+  run_success "$OCKAM" node create n3
+
+  run_success "$OCKAM" tcp-outlet create --at /node/n1 --to "127.0.0.1:$(random_port)"
+  run_success "$OCKAM" tcp-outlet create --at /node/n3 --to 127.0.0.1:5000
+  run_success "$OCKAM" tcp-inlet create --at /node/n2 --from "127.0.0.1:$port" --to /node/n1/service/outlet --alias "test-inlet"
+
+  run_success "$OCKAM" tcp-inlet update "test-inlet" --at /node/n2 --to /node/n3/service/outlet
+  run_success curl --fail --head --max-time 10 "127.0.0.1:$port"
+}
+
+@test "portals - the session of a tcp inlet is monitored by its node" {
+  run_success "$OCKAM" node create n1
+  run_success "$OCKAM" node create n2
+
+  run_success "$OCKAM" tcp-outlet create --at /node/n1 --to "127.0.0.1:$(random_port)"
+  run_success "$OCKAM" tcp-inlet create --at /node/n2 --from "127.0.0.1:$(random_port)" --to /node/n1/service/outlet --alias "test-inlet"
+
+  run_success "$OCKAM" node sessions n2 --output json
+  assert_output --partial "\"kind\": \"tcp-inlet\""
+  assert_output --partial "\"name\": \"test-inlet\""
+  assert_output --partial "\"status\": \"up\""
+}
+
 @test "portals - create an inlet/outlet pair and move tcp traffic through it" {
   port="$(random_port)"
   run_success "$OCKAM" node create n1
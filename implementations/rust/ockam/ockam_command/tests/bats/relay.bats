@@ -47,6 +47,23 @@ teardown() {
   assert_output --partial "[]"
 }
 
+@test "relay - evict a relay registered at a node" {
+  run_success --separate-stderr "$OCKAM" node create n1
+  run_success --separate-stderr "$OCKAM" node create n2
+  run_success "$OCKAM" relay create blue --at /node/n1 --to /node/n2
+
+  run_success "$OCKAM" relay list --at /node/n1
+  assert_output --partial "forward_to_blue"
+
+  run_success "$OCKAM" relay evict forward_to_blue --at /node/n1 --yes
+  sleep 1
+  run_success "$OCKAM" relay list --at /node/n1
+  refute_output --partial "forward_to_blue"
+
+  # The relay is not registered anymore
+  run_failure "$OCKAM" relay evict forward_to_blue --at /node/n1 --yes
+}
+
 @test "relay - show a relay on a node" {
   run_success --separate-stderr "$OCKAM" node create n1
   run_success --separate-stderr "$OCKAM" node create n2
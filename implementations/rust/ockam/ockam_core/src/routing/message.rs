@@ -1,5 +1,8 @@
 //! Submodule to expose routing message types.
 
+mod channel_closed;
+pub use channel_closed::*;
+
 mod local_message;
 pub use local_message::*;
 
@@ -0,0 +1,90 @@
+use crate::compat::{string::String, vec::Vec};
+use crate::{Decodable, Encodable, LocalInfo, LocalMessage, Result};
+use serde::{Deserialize, Serialize};
+
+/// ChannelClosed LocalInfo unique Identifier
+pub const CHANNEL_CLOSED_IDENTIFIER: &str = "CHANNEL_CLOSED_IDENTIFIER";
+
+/// LocalInfo unique Identifier of the subscriptions to the closing of a channel
+pub const CHANNEL_CLOSED_SUBSCRIPTION_IDENTIFIER: &str = "CHANNEL_CLOSED_SUBSCRIPTION_IDENTIFIER";
+
+/// LocalInfo unique Identifier of the cancellations of subscriptions to the closing of a channel
+pub const CHANNEL_CLOSED_UNSUBSCRIPTION_IDENTIFIER: &str =
+    "CHANNEL_CLOSED_UNSUBSCRIPTION_IDENTIFIER";
+
+/// LocalInfo marking the notification sent by a channel, for example a secure channel,
+/// when the channel is closed.
+///
+/// A worker subscribes to this notification by adding [`ChannelClosedLocalInfo::subscription`]
+/// to a message sent through the channel. The notification is then sent to the first address
+/// of the return route of that message. It has an empty payload: workers like TCP portals
+/// check for this `LocalInfo` before decoding a message, and release the resources
+/// depending on the channel.
+///
+/// A subscribed worker which stops before the channel sends a message with an empty payload
+/// and [`ChannelClosedLocalInfo::unsubscription`] to the channel, which stops tracking it.
+/// That message is not forwarded by the channel.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChannelClosedLocalInfo {
+    reason: String,
+}
+
+impl ChannelClosedLocalInfo {
+    /// Create a new `ChannelClosedLocalInfo`
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
+
+    /// Reason why the channel was closed
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// Encode `ChannelClosedLocalInfo` to general `LocalInfo`
+    pub fn to_local_info(&self) -> Result<LocalInfo> {
+        Ok(LocalInfo::new(
+            CHANNEL_CLOSED_IDENTIFIER.into(),
+            self.encode()?,
+        ))
+    }
+
+    /// Find `ChannelClosedLocalInfo` in the list of general `LocalInfo` of that `LocalMessage`
+    pub fn find_info(local_msg: &LocalMessage) -> Option<Self> {
+        local_msg
+            .local_info()
+            .iter()
+            .find(|x| x.type_identifier() == CHANNEL_CLOSED_IDENTIFIER)
+            .and_then(|x| Self::decode(x.data()).ok())
+    }
+
+    /// `LocalInfo` subscribing the sender of a message to the closing of the channel
+    /// the message is sent through
+    pub fn subscription() -> LocalInfo {
+        LocalInfo::new(CHANNEL_CLOSED_SUBSCRIPTION_IDENTIFIER.into(), Vec::new())
+    }
+
+    /// Return true if the `LocalMessage` subscribes its sender to the closing of the channel
+    pub fn is_subscription(local_msg: &LocalMessage) -> bool {
+        local_msg
+            .local_info()
+            .iter()
+            .any(|x| x.type_identifier() == CHANNEL_CLOSED_SUBSCRIPTION_IDENTIFIER)
+    }
+
+    /// `LocalInfo` cancelling the subscription of the sender of a message to the closing
+    /// of the channel the message is sent to
+    pub fn unsubscription() -> LocalInfo {
+        LocalInfo::new(CHANNEL_CLOSED_UNSUBSCRIPTION_IDENTIFIER.into(), Vec::new())
+    }
+
+    /// Return true if the `LocalMessage` cancels the subscription of its sender to the
+    /// closing of the channel
+    pub fn is_unsubscription(local_msg: &LocalMessage) -> bool {
+        local_msg
+            .local_info()
+            .iter()
+            .any(|x| x.type_identifier() == CHANNEL_CLOSED_UNSUBSCRIPTION_IDENTIFIER)
+    }
+}
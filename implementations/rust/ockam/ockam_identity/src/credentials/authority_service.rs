@@ -39,6 +39,11 @@ impl AuthorityService {
         }
     }
 
+    /// Return true if this authority can provide credentials
+    pub fn has_credentials_retriever(&self) -> bool {
+        self.own_credential.is_some()
+    }
+
     /// Retrieve the credential for an identity within this authority
     pub async fn credential(
         &self,
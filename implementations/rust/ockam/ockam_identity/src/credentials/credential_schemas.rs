@@ -0,0 +1,407 @@
+use crate::models::{Attributes, CredentialSchemaIdentifier};
+use crate::{IdentityError, TRUST_CONTEXT_ID_UTF8};
+
+use core::str::from_utf8;
+use minicbor::bytes::ByteVec;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use serde::{Deserialize, Serialize};
+
+/// Type of the values of an attribute.
+///
+/// Attribute values are stored as UTF-8 strings: integers in decimal, booleans as `true` or
+/// `false`, and lists as comma-separated items
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeType {
+    /// UTF-8 string
+    String,
+    /// Signed 64 bits integer
+    Integer,
+    /// `true` or `false`
+    Boolean,
+    /// Comma-separated list of items
+    List,
+}
+
+/// Value of an attribute, parsed according to its [`AttributeSchema`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttributeValue {
+    /// String value
+    String(String),
+    /// Integer value
+    Integer(i64),
+    /// Boolean value
+    Boolean(bool),
+    /// List of values
+    List(Vec<AttributeValue>),
+}
+
+/// Type and constraints of an attribute in a [`CredentialSchema`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeSchema {
+    /// Type of the attribute
+    #[serde(rename = "type")]
+    pub attribute_type: AttributeType,
+    /// Type of the items of a list, strings by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<AttributeType>,
+    /// The attribute must be present in the credentials using the schema
+    #[serde(default)]
+    pub required: bool,
+    /// Allowed values, or allowed items for a list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub one_of: Option<Vec<String>>,
+    /// Minimum value of an integer, or of the integer items of a list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<i64>,
+    /// Maximum value of an integer, or of the integer items of a list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i64>,
+    /// Maximum length of a string, or maximum number of items of a list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+}
+
+impl AttributeSchema {
+    /// Create the schema of an optional attribute without constraints
+    pub fn new(attribute_type: AttributeType) -> Self {
+        Self {
+            attribute_type,
+            items: None,
+            required: false,
+            one_of: None,
+            min: None,
+            max: None,
+            max_length: None,
+        }
+    }
+
+    /// Parse and check the value of an attribute
+    pub fn parse(&self, value: &[u8]) -> Result<AttributeValue> {
+        let value = from_utf8(value).map_err(|_| IdentityError::InvalidCredentialAttribute)?;
+        match self.attribute_type {
+            AttributeType::List => {
+                let item_type = self.items.unwrap_or(AttributeType::String);
+                if item_type == AttributeType::List {
+                    // Lists can't be nested
+                    return Err(IdentityError::InvalidCredentialAttribute.into());
+                }
+                let items: Vec<&str> = if value.is_empty() {
+                    vec![]
+                } else {
+                    value.split(',').map(|item| item.trim()).collect()
+                };
+                self.check_length(items.len())?;
+                let items = items
+                    .into_iter()
+                    .map(|item| self.parse_item(item_type, item))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(AttributeValue::List(items))
+            }
+            AttributeType::String => {
+                self.check_length(value.chars().count())?;
+                self.parse_item(AttributeType::String, value)
+            }
+            item_type => self.parse_item(item_type, value),
+        }
+    }
+
+    fn parse_item(&self, item_type: AttributeType, item: &str) -> Result<AttributeValue> {
+        if let Some(one_of) = &self.one_of {
+            if !one_of.iter().any(|allowed| allowed == item) {
+                return Err(IdentityError::InvalidCredentialAttribute.into());
+            }
+        }
+
+        match item_type {
+            AttributeType::String => Ok(AttributeValue::String(item.to_string())),
+            AttributeType::Integer => {
+                let i: i64 = item
+                    .parse()
+                    .map_err(|_| IdentityError::InvalidCredentialAttribute)?;
+                if self.min.map(|min| i < min).unwrap_or(false)
+                    || self.max.map(|max| i > max).unwrap_or(false)
+                {
+                    return Err(IdentityError::InvalidCredentialAttribute.into());
+                }
+                Ok(AttributeValue::Integer(i))
+            }
+            AttributeType::Boolean => match item {
+                "true" => Ok(AttributeValue::Boolean(true)),
+                "false" => Ok(AttributeValue::Boolean(false)),
+                _ => Err(IdentityError::InvalidCredentialAttribute.into()),
+            },
+            AttributeType::List => Err(IdentityError::InvalidCredentialAttribute.into()),
+        }
+    }
+
+    fn check_length(&self, length: usize) -> Result<()> {
+        match self.max_length {
+            Some(max_length) if length > max_length => {
+                Err(IdentityError::InvalidCredentialAttribute.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Declaration of the attributes expected in the credentials with a given
+/// [`CredentialSchemaIdentifier`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialSchema {
+    /// Value of the [`CredentialSchemaIdentifier`]
+    pub identifier: u64,
+    /// Human-readable name of the schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Attributes declared by the schema
+    pub attributes: BTreeMap<String, AttributeSchema>,
+    /// Accept attributes which are not declared, as strings
+    #[serde(default)]
+    pub allow_other_attributes: bool,
+}
+
+impl CredentialSchema {
+    /// Create a schema without attributes
+    pub fn new(identifier: CredentialSchemaIdentifier) -> Self {
+        Self {
+            identifier: identifier.0,
+            name: None,
+            attributes: BTreeMap::new(),
+            allow_other_attributes: false,
+        }
+    }
+
+    /// Declare an attribute
+    pub fn with_attribute(mut self, name: impl Into<String>, schema: AttributeSchema) -> Self {
+        self.attributes.insert(name.into(), schema);
+        self
+    }
+
+    /// Accept attributes which are not declared
+    pub fn with_other_attributes(mut self) -> Self {
+        self.allow_other_attributes = true;
+        self
+    }
+
+    /// [`CredentialSchemaIdentifier`] of the schema
+    pub fn identifier(&self) -> CredentialSchemaIdentifier {
+        CredentialSchemaIdentifier(self.identifier)
+    }
+
+    /// Parse the value of an attribute. Return `None` if the attribute is not declared
+    pub fn parse_attribute(&self, key: &[u8], value: &[u8]) -> Option<Result<AttributeValue>> {
+        let key = from_utf8(key).ok()?;
+        self.attributes.get(key).map(|schema| schema.parse(value))
+    }
+
+    /// Check that the attributes follow the schema.
+    /// The required attributes are only checked if `check_required` is true, since a credential
+    /// presentation may not disclose all the attributes
+    pub fn validate(&self, attributes: &Attributes, check_required: bool) -> Result<()> {
+        if attributes.schema != self.identifier() {
+            return Err(IdentityError::InvalidCredentialAttribute.into());
+        }
+
+        for (key, value) in attributes.map.iter() {
+            match self.parse_attribute(key, value) {
+                Some(parsed) => {
+                    parsed?;
+                }
+                // The trust context is added by the issuer to all the credentials
+                None if key.as_slice() == TRUST_CONTEXT_ID_UTF8.as_bytes() => {}
+                None if self.allow_other_attributes => {}
+                None => return Err(IdentityError::InvalidCredentialAttribute.into()),
+            }
+        }
+
+        if check_required {
+            for (name, schema) in self.attributes.iter() {
+                let key = ByteVec::from(name.as_bytes().to_vec());
+                if schema.required && !attributes.map.contains_key(&key) {
+                    return Err(IdentityError::InvalidCredentialAttribute.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Registry of the [`CredentialSchema`]s known to issuers and verifiers.
+/// Credentials with a schema which is not registered are not validated
+#[derive(Clone, Debug, Default)]
+pub struct CredentialSchemas {
+    schemas: BTreeMap<u64, CredentialSchema>,
+}
+
+impl CredentialSchemas {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a schema, replacing any schema with the same identifier
+    pub fn with_schema(mut self, schema: CredentialSchema) -> Self {
+        self.schemas.insert(schema.identifier, schema);
+        self
+    }
+
+    /// Return a registered schema
+    pub fn get(&self, identifier: &CredentialSchemaIdentifier) -> Option<&CredentialSchema> {
+        self.schemas.get(&identifier.0)
+    }
+
+    /// Check attributes against their schema, if it is registered
+    pub fn validate(&self, attributes: &Attributes, check_required: bool) -> Result<()> {
+        match self.get(&attributes.schema) {
+            Some(schema) => schema.validate(attributes, check_required),
+            None => Ok(()),
+        }
+    }
+
+    /// Parse the value of an attribute if its schema is registered and declares it
+    pub fn parse_attribute(
+        &self,
+        identifier: &CredentialSchemaIdentifier,
+        key: &[u8],
+        value: &[u8],
+    ) -> Option<Result<AttributeValue>> {
+        self.get(identifier)?.parse_attribute(key, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::AttributesBuilder;
+
+    fn schema() -> CredentialSchema {
+        CredentialSchema::new(CredentialSchemaIdentifier(42))
+            .with_attribute(
+                "role",
+                AttributeSchema {
+                    required: true,
+                    one_of: Some(vec!["admin".to_string(), "member".to_string()]),
+                    ..AttributeSchema::new(AttributeType::String)
+                },
+            )
+            .with_attribute(
+                "max_connections",
+                AttributeSchema {
+                    min: Some(0),
+                    max: Some(100),
+                    ..AttributeSchema::new(AttributeType::Integer)
+                },
+            )
+            .with_attribute("is_admin", AttributeSchema::new(AttributeType::Boolean))
+            .with_attribute(
+                "ports",
+                AttributeSchema {
+                    items: Some(AttributeType::Integer),
+                    max_length: Some(3),
+                    ..AttributeSchema::new(AttributeType::List)
+                },
+            )
+    }
+
+    #[test]
+    fn test_validate_attributes() -> Result<()> {
+        let schemas = CredentialSchemas::new().with_schema(schema());
+        let valid = AttributesBuilder::with_schema(CredentialSchemaIdentifier(42))
+            .with_attribute("role", "admin")
+            .with_attribute("max_connections", "10")
+            .with_attribute("is_admin", "true")
+            .with_attribute("ports", "80,443")
+            .build();
+        schemas.validate(&valid, true)?;
+
+        // invalid values
+        for (key, value) in [
+            ("role", "root"),
+            ("max_connections", "101"),
+            ("max_connections", "ten"),
+            ("is_admin", "yes"),
+            ("ports", "1,2,3,4"),
+            ("ports", "80,http"),
+            ("unknown", "value"),
+        ] {
+            let mut attributes = valid.clone();
+            attributes.map.insert(
+                key.as_bytes().to_vec().into(),
+                value.as_bytes().to_vec().into(),
+            );
+            assert!(
+                schemas.validate(&attributes, true).is_err(),
+                "{key}={value}"
+            );
+        }
+
+        // missing required attribute
+        let attributes = AttributesBuilder::with_schema(CredentialSchemaIdentifier(42))
+            .with_attribute("is_admin", "false")
+            .build();
+        assert!(schemas.validate(&attributes, true).is_err());
+        schemas.validate(&attributes, false)?;
+
+        // unknown schemas are not validated
+        let attributes = AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+            .with_attribute("anything", "goes")
+            .build();
+        schemas.validate(&attributes, true)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let schemas = CredentialSchemas::new().with_schema(schema());
+        let id = CredentialSchemaIdentifier(42);
+
+        assert_eq!(
+            schemas
+                .parse_attribute(&id, b"max_connections", b"10")
+                .unwrap()
+                .unwrap(),
+            AttributeValue::Integer(10)
+        );
+        assert_eq!(
+            schemas
+                .parse_attribute(&id, b"is_admin", b"true")
+                .unwrap()
+                .unwrap(),
+            AttributeValue::Boolean(true)
+        );
+        assert_eq!(
+            schemas
+                .parse_attribute(&id, b"ports", b"80, 443")
+                .unwrap()
+                .unwrap(),
+            AttributeValue::List(vec![
+                AttributeValue::Integer(80),
+                AttributeValue::Integer(443)
+            ])
+        );
+        assert!(schemas.parse_attribute(&id, b"unknown", b"value").is_none());
+    }
+
+    #[test]
+    fn test_schema_file() {
+        let schema: CredentialSchema = serde_json::from_str(
+            r#"{
+                "identifier": 42,
+                "attributes": {
+                    "role": {"type": "string", "required": true, "one_of": ["admin", "member"]},
+                    "max_connections": {"type": "integer", "min": 0, "max": 100},
+                    "is_admin": {"type": "boolean"},
+                    "ports": {"type": "list", "items": "integer", "max_length": 3}
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(schema, super::schema());
+    }
+}
@@ -1,11 +1,16 @@
 use crate::models::{
-    Attributes, Credential, CredentialAndPurposeKey, CredentialData, Identifier, VersionedData,
+    AttributeDisclosure, Attributes, Credential, CredentialAndPurposeKey, CredentialData,
+    DelegationConstraints, Identifier, VersionedData,
 };
 use crate::utils::{add_seconds, now};
-use crate::{IdentitiesRepository, Identity, PurposeKeyCreation};
+use crate::{IdentitiesRepository, Identity, IdentityError, PurposeKeyCreation, DELEGATION_SCHEMA};
 
+use core::cmp::min;
 use core::time::Duration;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 use ockam_vault::{VaultForSigning, VaultForVerifyingSignatures};
 
@@ -47,6 +52,145 @@ impl CredentialsCreation {
         subject: &Identifier,
         subject_attributes: Attributes,
         ttl: Duration,
+    ) -> Result<CredentialAndPurposeKey> {
+        self.issue(issuer, subject, subject_attributes, None, None, None, ttl)
+            .await
+    }
+
+    /// Issue a [`Credential`] where each attribute is committed individually, so that the
+    /// subject can present only the attributes needed by a verifier, using
+    /// [`CredentialAndPurposeKey::disclose`].
+    /// The returned [`CredentialAndPurposeKey`] discloses all the attributes
+    pub async fn issue_credential_with_selective_disclosure(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        subject_attributes: Attributes,
+        ttl: Duration,
+    ) -> Result<CredentialAndPurposeKey> {
+        self.issue_credential_with_committed_attributes(
+            issuer,
+            subject,
+            subject_attributes,
+            &[],
+            ttl,
+            None,
+        )
+        .await
+    }
+
+    /// Issue a [`Credential`] with selective disclosure, where only the attributes with the
+    /// given keys stay in clear, since every verifier needs them. The other attributes are
+    /// committed individually.
+    /// When the issuer is a sub-authority, its delegation must be given
+    pub async fn issue_credential_with_committed_attributes(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        subject_attributes: Attributes,
+        clear_attribute_keys: &[&[u8]],
+        ttl: Duration,
+        issuer_delegation: Option<&CredentialAndPurposeKey>,
+    ) -> Result<CredentialAndPurposeKey> {
+        let (attributes, disclosures) =
+            Self::commit_attributes(subject_attributes, clear_attribute_keys);
+
+        self.issue(
+            issuer,
+            subject,
+            attributes,
+            Some(disclosures),
+            None,
+            issuer_delegation,
+            ttl,
+        )
+        .await
+    }
+
+    /// Issue a delegation [`Credential`], allowing the subject to issue [`Credential`]s
+    /// satisfying the [`DelegationConstraints`] as a sub-authority of the issuer.
+    /// When the issuer is itself a sub-authority, its own delegation must be given, and it must
+    /// allow these narrower constraints
+    pub async fn issue_delegation(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        constraints: DelegationConstraints,
+        ttl: Duration,
+        issuer_delegation: Option<&CredentialAndPurposeKey>,
+    ) -> Result<CredentialAndPurposeKey> {
+        let attributes = Attributes {
+            schema: DELEGATION_SCHEMA,
+            map: BTreeMap::new(),
+        };
+
+        self.issue(
+            issuer,
+            subject,
+            attributes,
+            None,
+            Some(constraints),
+            issuer_delegation,
+            ttl,
+        )
+        .await
+    }
+
+    /// Issue a [`Credential`] as a sub-authority, with the delegation received from its issuer.
+    /// The attributes must be allowed by the delegation, and the [`Credential`] expires at
+    /// the latest when the delegation expires
+    pub async fn issue_delegated_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        subject_attributes: Attributes,
+        ttl: Duration,
+        issuer_delegation: &CredentialAndPurposeKey,
+    ) -> Result<CredentialAndPurposeKey> {
+        self.issue(
+            issuer,
+            subject,
+            subject_attributes,
+            None,
+            None,
+            Some(issuer_delegation),
+            ttl,
+        )
+        .await
+    }
+
+    /// Split the attributes between the ones which stay in clear in the [`Credential`],
+    /// because every verifier needs them, and the ones committed individually
+    fn commit_attributes(
+        attributes: Attributes,
+        clear_attribute_keys: &[&[u8]],
+    ) -> (Attributes, Vec<AttributeDisclosure>) {
+        let mut clear_attributes = BTreeMap::new();
+        let mut disclosures = Vec::new();
+        for (key, value) in attributes.map {
+            if clear_attribute_keys.contains(&key.as_slice()) {
+                clear_attributes.insert(key, value);
+            } else {
+                disclosures.push(AttributeDisclosure::new(key.into(), value.into()));
+            }
+        }
+        let attributes = Attributes {
+            schema: attributes.schema,
+            map: clear_attributes,
+        };
+        (attributes, disclosures)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn issue(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        subject_attributes: Attributes,
+        disclosures: Option<Vec<AttributeDisclosure>>,
+        delegation_constraints: Option<DelegationConstraints>,
+        issuer_delegation: Option<&CredentialAndPurposeKey>,
+        ttl: Duration,
     ) -> Result<CredentialAndPurposeKey> {
         // TODO: Allow manual PurposeKey management
         let issuer_purpose_key = self
@@ -63,7 +207,47 @@ impl CredentialsCreation {
         .await?;
 
         let created_at = now()?;
-        let expires_at = add_seconds(&created_at, ttl.as_secs());
+        let mut expires_at = add_seconds(&created_at, ttl.as_secs());
+
+        if let Some(issuer_delegation) = issuer_delegation {
+            // Check the Credential here, rather than issuing a Credential failing verification
+            let delegation = issuer_delegation.get_credential_data()?;
+            let constraints = match delegation.delegation {
+                Some(constraints) if delegation.subject.as_ref() == Some(issuer) => constraints,
+                _ => return Err(IdentityError::InvalidDelegation.into()),
+            };
+
+            expires_at = min(expires_at, delegation.expires_at);
+            let attributes_allowed = constraints.allows_attributes(&subject_attributes)
+                && disclosures
+                    .iter()
+                    .flatten()
+                    .all(|d| constraints.allows_attribute(&d.key, &d.value));
+            let delegation_allowed = delegation_constraints
+                .as_ref()
+                .map_or(true, |delegated| constraints.allows_delegation(delegated));
+            if created_at < delegation.created_at
+                || !constraints.allows_lifetime(created_at, expires_at)
+                || !attributes_allowed
+                || !delegation_allowed
+            {
+                return Err(IdentityError::InvalidDelegation.into());
+            }
+        }
+
+        let attribute_commitments = match &disclosures {
+            Some(disclosures) => {
+                let mut attribute_commitments = Vec::with_capacity(disclosures.len());
+                for disclosure in disclosures {
+                    attribute_commitments
+                        .push(disclosure.commitment(self.verifying_vault.as_ref()).await?);
+                }
+                // The order of the commitments must not reveal the order of the attributes
+                attribute_commitments.sort();
+                Some(attribute_commitments)
+            }
+            None => None,
+        };
 
         let credential_data = CredentialData {
             subject: Some(subject.clone()),
@@ -71,6 +255,8 @@ impl CredentialsCreation {
             subject_attributes,
             created_at,
             expires_at,
+            attribute_commitments,
+            delegation: delegation_constraints,
         };
         let credential_data = minicbor::to_vec(credential_data)?;
 
@@ -93,9 +279,18 @@ impl CredentialsCreation {
             signature,
         };
 
+        // A sub-authority is not known by the verifiers, which only trust the authorities
+        let issuer_change_history = match issuer_delegation {
+            Some(_) => Some(self.identities_repository.get_identity(issuer).await?),
+            None => None,
+        };
+
         let res = CredentialAndPurposeKey {
             credential,
             purpose_key_attestation: issuer_purpose_key.attestation().clone(),
+            disclosures,
+            delegation: issuer_delegation.map(|d| Box::new(d.clone())),
+            issuer_change_history,
         };
 
         Ok(res)
@@ -11,6 +11,7 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::{Result, Routed, Worker};
 use ockam_node::Context;
 
+use core::cmp::min;
 use core::time::Duration;
 use minicbor::Decoder;
 use tracing::trace;
@@ -25,6 +26,10 @@ pub const TRUST_CONTEXT_ID_UTF8: &str = "trust_context_id";
 /// Identifier for the schema of a project credential
 pub const PROJECT_MEMBER_SCHEMA: CredentialSchemaIdentifier = CredentialSchemaIdentifier(1);
 
+/// Identifier for the schema of a delegation credential, allowing its subject to issue
+/// credentials as a sub-authority
+pub const DELEGATION_SCHEMA: CredentialSchemaIdentifier = CredentialSchemaIdentifier(2);
+
 /// Maximum duration for a valid credential in seconds (30 days)
 pub const MAX_CREDENTIAL_VALIDITY: Duration = Duration::from_secs(30 * 24 * 3600);
 
@@ -34,6 +39,8 @@ pub struct CredentialsIssuer {
     credentials: Arc<Credentials>,
     issuer: Identifier,
     subject_attributes: Attributes,
+    delegation: Option<CredentialAndPurposeKey>,
+    selective_disclosure: bool,
 }
 
 impl CredentialsIssuer {
@@ -53,9 +60,25 @@ impl CredentialsIssuer {
             credentials,
             issuer: issuer.clone(),
             subject_attributes,
+            delegation: None,
+            selective_disclosure: false,
         }
     }
 
+    /// Issue credentials as a sub-authority, with the delegation received from its issuer
+    pub fn with_delegation(mut self, delegation: CredentialAndPurposeKey) -> Self {
+        self.delegation = Some(delegation);
+        self
+    }
+
+    /// Issue credentials where each attribute is committed individually, so that their
+    /// subjects can present only the attributes needed by each verifier.
+    /// The trust context identifier stays in clear since every verifier checks it
+    pub fn with_selective_disclosure(mut self) -> Self {
+        self.selective_disclosure = true;
+        self
+    }
+
     async fn issue_credential(
         &self,
         subject: &Identifier,
@@ -77,16 +100,53 @@ impl CredentialsIssuer {
                 .insert(key.clone().into(), value.clone().into());
         }
 
-        let credential = self
-            .credentials
-            .credentials_creation()
-            .issue_credential(
-                &self.issuer,
-                subject,
-                subject_attributes,
-                MAX_CREDENTIAL_VALIDITY,
-            )
-            .await?;
+        self.credentials
+            .credential_schemas()
+            .validate(&subject_attributes, true)?;
+
+        // The credentials can't be valid longer than allowed by the delegation
+        let ttl = match &self.delegation {
+            Some(delegation) => match delegation.get_credential_data()?.delegation {
+                Some(constraints) => min(
+                    MAX_CREDENTIAL_VALIDITY,
+                    Duration::from_secs(constraints.max_ttl.0),
+                ),
+                None => MAX_CREDENTIAL_VALIDITY,
+            },
+            None => MAX_CREDENTIAL_VALIDITY,
+        };
+
+        let credentials_creation = self.credentials.credentials_creation();
+        let credential = match (&self.delegation, self.selective_disclosure) {
+            (delegation, true) => {
+                credentials_creation
+                    .issue_credential_with_committed_attributes(
+                        &self.issuer,
+                        subject,
+                        subject_attributes,
+                        &[TRUST_CONTEXT_ID],
+                        ttl,
+                        delegation.as_ref(),
+                    )
+                    .await?
+            }
+            (Some(delegation), false) => {
+                credentials_creation
+                    .issue_delegated_credential(
+                        &self.issuer,
+                        subject,
+                        subject_attributes,
+                        ttl,
+                        delegation,
+                    )
+                    .await?
+            }
+            (None, false) => {
+                credentials_creation
+                    .issue_credential(&self.issuer, subject, subject_attributes, ttl)
+                    .await?
+            }
+        };
 
         Ok(Some(credential))
     }
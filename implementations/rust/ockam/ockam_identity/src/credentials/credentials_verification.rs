@@ -1,9 +1,12 @@
 use crate::identities::AttributesEntry;
-use crate::models::{CredentialAndPurposeKey, CredentialData, Identifier, PurposePublicKey};
+use crate::models::{
+    AttributeDisclosure, CredentialAndPurposeKey, CredentialData, DelegationConstraints,
+    Identifier, PurposePublicKey,
+};
 use crate::utils::now;
 use crate::{
-    CredentialAndPurposeKeyData, IdentitiesRepository, IdentityError, PurposeKeyVerification,
-    TimestampInSeconds,
+    CredentialAndPurposeKeyData, CredentialSchemas, IdentitiesRepository, IdentityError,
+    PurposeKeyVerification, TimestampInSeconds,
 };
 
 use ockam_core::compat::collections::BTreeMap;
@@ -16,11 +19,15 @@ use ockam_vault::VaultForVerifyingSignatures;
 /// possible time dyssynchronization
 const MAX_ALLOWED_TIME_DRIFT: TimestampInSeconds = TimestampInSeconds(5);
 
+/// Maximum number of delegations between a sub-authority and an authority
+const MAX_DELEGATION_CHAIN_LENGTH: usize = 8;
+
 /// Service for managing [`Credential`]s
 pub struct CredentialsVerification {
     purpose_keys_verification: Arc<PurposeKeyVerification>,
     verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
     identities_repository: Arc<dyn IdentitiesRepository>,
+    credential_schemas: Arc<CredentialSchemas>,
 }
 
 impl CredentialsVerification {
@@ -29,11 +36,13 @@ impl CredentialsVerification {
         purpose_keys_verification: Arc<PurposeKeyVerification>,
         verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
         identities_repository: Arc<dyn IdentitiesRepository>,
+        credential_schemas: Arc<CredentialSchemas>,
     ) -> Self {
         Self {
             purpose_keys_verification,
             verifying_vault,
             identities_repository,
+            credential_schemas,
         }
     }
 
@@ -45,25 +54,74 @@ impl CredentialsVerification {
 
 impl CredentialsVerification {
     /// Verify a [`Credential`]
+    /// The issuer must be one of the authorities, or a sub-authority presenting a chain of
+    /// delegations up to one of the authorities
     // TODO: Move to CredentialsVerification
     pub async fn verify_credential(
         &self,
         expected_subject: Option<&Identifier>,
         authorities: &[Identifier],
         credential_and_purpose_key: &CredentialAndPurposeKey,
+    ) -> Result<CredentialAndPurposeKeyData> {
+        let CredentialAndPurposeKeyData {
+            mut credential_data,
+            purpose_key_data,
+        } = self
+            .verify_signed_credential(expected_subject, credential_and_purpose_key)
+            .await?;
+
+        let delegation_constraints = if authorities.contains(&purpose_key_data.subject) {
+            None
+        } else {
+            match &credential_and_purpose_key.delegation {
+                Some(delegation) => {
+                    let delegation = self
+                        .verify_delegation(&purpose_key_data.subject, authorities, delegation)
+                        .await?;
+                    Some(Self::delegation_constraints(&delegation, &credential_data)?)
+                }
+                None => return Err(IdentityError::UnknownAuthority.into()),
+            }
+        };
+
+        if let Some(disclosures) = &credential_and_purpose_key.disclosures {
+            self.verify_disclosures(&mut credential_data, disclosures)
+                .await?;
+        }
+
+        // The committed attributes which are not disclosed can't be checked
+        let check_required = credential_data.attribute_commitments.is_none();
+        self.credential_schemas
+            .validate(&credential_data.subject_attributes, check_required)?;
+
+        if let Some(delegation_constraints) = delegation_constraints {
+            if !delegation_constraints.allows_attributes(&credential_data.subject_attributes) {
+                return Err(IdentityError::InvalidDelegation.into());
+            }
+        }
+
+        Ok(CredentialAndPurposeKeyData {
+            credential_data,
+            purpose_key_data,
+        })
+    }
+
+    /// Verify the signature, subject and validity time range of a [`Credential`],
+    /// without checking who issued it
+    async fn verify_signed_credential(
+        &self,
+        expected_subject: Option<&Identifier>,
+        credential_and_purpose_key: &CredentialAndPurposeKey,
     ) -> Result<CredentialAndPurposeKeyData> {
         let purpose_key_data = self
             .purpose_keys_verification
-            .verify_purpose_key_attestation(
+            .verify_purpose_key_attestation_with_change_history(
                 None,
                 &credential_and_purpose_key.purpose_key_attestation,
+                credential_and_purpose_key.issuer_change_history.as_ref(),
             )
             .await?;
 
-        if !authorities.contains(&purpose_key_data.subject) {
-            return Err(IdentityError::UnknownAuthority.into());
-        }
-
         let public_key = match purpose_key_data.public_key.clone() {
             PurposePublicKey::SecureChannelStatic(_) => {
                 return Err(IdentityError::InvalidKeyType.into())
@@ -155,7 +213,6 @@ impl CredentialsVerification {
         }
 
         // FIXME: Verify if given authority is allowed to issue credentials with given Schema <-- Should be handled somewhere in the TrustContext
-        // FIXME: Verify if Schema aligns with Attributes <-- Should be handled somewhere in the TrustContext
 
         Ok(CredentialAndPurposeKeyData {
             credential_data,
@@ -163,7 +220,120 @@ impl CredentialsVerification {
         })
     }
 
-    /// Receive someone's [`Credential`]: verify and put attributes from it to the storage
+    /// Verify the delegation [`Credential`] of a sub-authority, and the delegations of its
+    /// issuers up to one of the authorities.
+    /// Return the [`CredentialData`] of the delegation of the sub-authority
+    async fn verify_delegation(
+        &self,
+        sub_authority: &Identifier,
+        authorities: &[Identifier],
+        delegation: &CredentialAndPurposeKey,
+    ) -> Result<CredentialData> {
+        // Delegations from the sub-authority up to an authority
+        let mut chain: Vec<CredentialData> = Vec::new();
+        let mut subject = sub_authority.clone();
+        let mut current = delegation;
+        loop {
+            if chain.len() == MAX_DELEGATION_CHAIN_LENGTH {
+                return Err(IdentityError::InvalidDelegation.into());
+            }
+
+            let data = self
+                .verify_signed_credential(Some(&subject), current)
+                .await?;
+            chain.push(data.credential_data);
+            if authorities.contains(&data.purpose_key_data.subject) {
+                break;
+            }
+
+            current = match &current.delegation {
+                Some(delegation) => delegation,
+                None => return Err(IdentityError::UnknownAuthority.into()),
+            };
+            subject = data.purpose_key_data.subject;
+        }
+
+        // Each delegation must be allowed by the delegation of its issuer
+        for delegations in chain.windows(2) {
+            Self::delegation_constraints(&delegations[1], &delegations[0])?;
+        }
+
+        Ok(chain.swap_remove(0))
+    }
+
+    /// Return the [`DelegationConstraints`] of a delegation, after checking that they allow
+    /// the validity time range of a [`Credential`] issued with it, and its own delegation if any
+    fn delegation_constraints(
+        delegation: &CredentialData,
+        credential_data: &CredentialData,
+    ) -> Result<DelegationConstraints> {
+        let constraints = match &delegation.delegation {
+            Some(constraints) => constraints,
+            // This is not a delegation Credential
+            None => return Err(IdentityError::InvalidDelegation.into()),
+        };
+
+        if credential_data.created_at < delegation.created_at
+            || credential_data.expires_at > delegation.expires_at
+            || !constraints.allows_lifetime(credential_data.created_at, credential_data.expires_at)
+        {
+            return Err(IdentityError::InvalidDelegation.into());
+        }
+
+        if let Some(delegated) = &credential_data.delegation {
+            if !constraints.allows_delegation(delegated) {
+                return Err(IdentityError::InvalidDelegation.into());
+            }
+        }
+
+        Ok(constraints.clone())
+    }
+
+    /// Check that the disclosed attributes were committed by the issuer, and add them to the
+    /// attributes of the [`CredentialData`].
+    /// The committed attributes which are not disclosed stay unknown to the verifier
+    async fn verify_disclosures(
+        &self,
+        credential_data: &mut CredentialData,
+        disclosures: &[AttributeDisclosure],
+    ) -> Result<()> {
+        let attribute_commitments = match &credential_data.attribute_commitments {
+            Some(attribute_commitments) => attribute_commitments,
+            // Nothing was committed
+            None => return Err(IdentityError::CredentialVerificationFailed.into()),
+        };
+
+        let mut disclosed_attributes = BTreeMap::new();
+        for disclosure in disclosures {
+            let commitment = disclosure.commitment(self.verifying_vault.as_ref()).await?;
+            if !attribute_commitments.contains(&commitment) {
+                // The issuer didn't attest that attribute
+                return Err(IdentityError::CredentialVerificationFailed.into());
+            }
+
+            if credential_data
+                .subject_attributes
+                .map
+                .contains_key(&disclosure.key)
+                || disclosed_attributes
+                    .insert(disclosure.key.clone(), disclosure.value.clone())
+                    .is_some()
+            {
+                // An attribute can't have several values
+                return Err(IdentityError::CredentialVerificationFailed.into());
+            }
+        }
+
+        credential_data
+            .subject_attributes
+            .map
+            .append(&mut disclosed_attributes);
+
+        Ok(())
+    }
+
+    /// Receive someone's [`Credential`]: verify and put attributes from it to the storage.
+    /// Only the attributes disclosed by the presentation are stored
     pub async fn receive_presented_credential(
         &self,
         subject: &Identifier,
@@ -178,6 +348,7 @@ impl CredentialsVerification {
             )
             .await?;
 
+        let schema = credential_data.credential_data.subject_attributes.schema;
         let map = credential_data.credential_data.subject_attributes.map;
         let map: BTreeMap<_, _> = map
             .into_iter()
@@ -192,7 +363,8 @@ impl CredentialsVerification {
                     now()?,
                     Some(credential_data.credential_data.expires_at),
                     Some(credential_data.purpose_key_data.subject),
-                ),
+                )
+                .with_schema(schema),
             )
             .await?;
 
@@ -1,4 +1,5 @@
 mod authority_service;
+mod credential_schemas;
 #[allow(clippy::module_inception)]
 mod credentials;
 mod credentials_creation;
@@ -11,6 +12,7 @@ mod one_time_code;
 mod trust_context;
 
 pub use authority_service::*;
+pub use credential_schemas::*;
 pub use credentials::*;
 pub use credentials_creation::*;
 pub use credentials_issuer::*;
@@ -50,6 +50,24 @@ pub enum IdentityError {
     InvalidHex,
     /// Secret Key doesn't correspond to the Identity
     WrongSecretKey,
+    /// The key renewal interval of a secure channel is not supported
+    InvalidKeyRenewalInterval,
+    /// The group is unknown, or the sender is not its admin
+    UnknownGroup,
+    /// The identity is not a member of the group
+    UnknownGroupMember,
+    /// The epoch of a group key is unknown or outdated
+    InvalidGroupEpoch,
+    /// A group message could not be verified
+    InvalidGroupMessage,
+    /// The threshold of a recovery policy cannot be reached with its keys
+    InvalidRecoveryPolicy,
+    /// The key is not a recovery key of the Identity
+    UnknownRecoveryKey,
+    /// A credential attribute doesn't follow its credential schema
+    InvalidCredentialAttribute,
+    /// A credential is not allowed by the delegation of its issuer
+    InvalidDelegation,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
@@ -1,8 +1,9 @@
 use crate::identities::{IdentitiesKeys, IdentitiesRepository};
 use crate::purpose_keys::storage::{PurposeKeysRepository, PurposeKeysStorage};
 use crate::{
-    Credentials, CredentialsServer, CredentialsServerModule, Identifier, IdentitiesBuilder,
-    IdentitiesCreation, IdentitiesReader, IdentitiesStorage, Identity, PurposeKeys, Vault,
+    CredentialSchemas, Credentials, CredentialsServer, CredentialsServerModule, Identifier,
+    IdentitiesBuilder, IdentitiesCreation, IdentitiesReader, IdentitiesStorage, Identity,
+    PurposeKeys, Vault,
 };
 
 use ockam_core::compat::sync::Arc;
@@ -15,6 +16,7 @@ pub struct Identities {
     vault: Vault,
     identities_repository: Arc<dyn IdentitiesRepository>,
     purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
+    credential_schemas: Arc<CredentialSchemas>,
 }
 
 impl Identities {
@@ -33,6 +35,11 @@ impl Identities {
         self.purpose_keys_repository.clone()
     }
 
+    /// Return the credential schemas
+    pub fn credential_schemas(&self) -> Arc<CredentialSchemas> {
+        self.credential_schemas.clone()
+    }
+
     /// Get an [`Identity`] from the repository
     pub async fn get_identity(&self, identifier: &Identifier) -> Result<Identity> {
         let change_history = self.identities_repository.get_identity(identifier).await?;
@@ -54,6 +61,7 @@ impl Identities {
         Arc::new(PurposeKeys::new(
             self.vault.clone(),
             self.identities_repository.as_identities_reader(),
+            self.identities_repository.as_identities_writer(),
             self.identities_keys(),
             self.purpose_keys_repository.clone(),
         ))
@@ -88,6 +96,7 @@ impl Identities {
             self.vault.verifying_vault.clone(),
             self.purpose_keys(),
             self.identities_repository.clone(),
+            self.credential_schemas.clone(),
         ))
     }
 
@@ -103,11 +112,13 @@ impl Identities {
         vault: Vault,
         identities_repository: Arc<dyn IdentitiesRepository>,
         purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
+        credential_schemas: Arc<CredentialSchemas>,
     ) -> Identities {
         Identities {
             vault,
             identities_repository,
             purpose_keys_repository,
+            credential_schemas,
         }
     }
 
@@ -117,6 +128,7 @@ impl Identities {
             vault: Vault::create(),
             repository: IdentitiesStorage::create(),
             purpose_keys_repository: PurposeKeysStorage::create(),
+            credential_schemas: Arc::new(CredentialSchemas::new()),
         }
     }
 }
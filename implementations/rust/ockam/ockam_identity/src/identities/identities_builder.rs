@@ -1,7 +1,7 @@
 use crate::identities::{Identities, IdentitiesRepository, IdentitiesStorage};
 use crate::purpose_keys::storage::{PurposeKeysRepository, PurposeKeysStorage};
 use crate::storage::Storage;
-use crate::{Vault, VaultStorage};
+use crate::{CredentialSchemas, Vault, VaultStorage};
 
 use ockam_core::compat::sync::Arc;
 
@@ -11,6 +11,7 @@ pub struct IdentitiesBuilder {
     pub(crate) vault: Vault,
     pub(crate) repository: Arc<dyn IdentitiesRepository>,
     pub(crate) purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
+    pub(crate) credential_schemas: Arc<CredentialSchemas>,
 }
 
 /// Return a default identities
@@ -56,12 +57,19 @@ impl IdentitiesBuilder {
         self
     }
 
+    /// Set the schemas used to validate the attributes of issued and verified credentials
+    pub fn with_credential_schemas(mut self, credential_schemas: CredentialSchemas) -> Self {
+        self.credential_schemas = Arc::new(credential_schemas);
+        self
+    }
+
     /// Build identities
     pub fn build(self) -> Arc<Identities> {
         Arc::new(Identities::new(
             self.vault,
             self.repository,
             self.purpose_keys_repository,
+            self.credential_schemas,
         ))
     }
 }
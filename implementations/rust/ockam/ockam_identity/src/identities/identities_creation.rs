@@ -3,7 +3,7 @@ use ockam_core::Result;
 use ockam_vault::{SigningSecretKeyHandle, VaultForSigning, VaultForVerifyingSignatures};
 
 use crate::identities::identity_builder::IdentityBuilder;
-use crate::models::{ChangeHistory, Identifier};
+use crate::models::{Change, ChangeHistory, Identifier};
 use crate::{IdentitiesKeys, IdentitiesRepository, Identity, IdentityError};
 use crate::{IdentityHistoryComparison, IdentityOptions};
 
@@ -129,6 +129,31 @@ impl IdentitiesCreation {
         Ok(())
     }
 
+    /// Replace the lost key of an existing `Identity` with a recovery [`Change`] signed by a
+    /// quorum of its recovery keys, and update the stored version
+    pub async fn recover_identity(
+        &self,
+        identifier: &Identifier,
+        change: Change,
+    ) -> Result<Identity> {
+        let change_history = self.repository.get_identity(identifier).await?;
+
+        let identity = Identity::import_from_change_history(
+            Some(identifier),
+            change_history,
+            self.verifying_vault.clone(),
+        )
+        .await?;
+
+        let identity = self.identities_keys().recover_key(identity, change).await?;
+
+        self.repository
+            .update_identity(identity.identifier(), identity.change_history())
+            .await?;
+
+        Ok(identity)
+    }
+
     /// Import an existing Identity from its binary format
     /// Its secret is expected to exist in the Vault (either generated there, or some Vault
     /// implementations may allow importing a secret)
@@ -2,7 +2,7 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::Result;
 use ockam_vault::{SigningKeyType, SigningSecretKeyHandle};
 
-use crate::models::TimestampInSeconds;
+use crate::models::{RecoveryPolicy, TimestampInSeconds};
 use crate::utils::now;
 use crate::IdentitiesCreation;
 use crate::{Identity, IdentityOptions};
@@ -30,6 +30,7 @@ pub struct IdentityBuilder {
     revoke_all_purpose_keys: bool,
     key: Key,
     ttl: Ttl,
+    recovery_policy: Option<RecoveryPolicy>,
 }
 
 impl IdentityBuilder {
@@ -40,6 +41,7 @@ impl IdentityBuilder {
             revoke_all_purpose_keys: false,
             key: Key::Generate(SigningKeyType::EdDSACurve25519),
             ttl: Ttl::CreatedNowWithTtl(DEFAULT_IDENTITY_TTL),
+            recovery_policy: None,
         }
     }
 
@@ -80,6 +82,12 @@ impl IdentityBuilder {
         self
     }
 
+    /// Register recovery keys, a quorum of which can authorize a new key if this key is lost
+    pub fn with_recovery_policy(mut self, recovery_policy: RecoveryPolicy) -> Self {
+        self.recovery_policy = Some(recovery_policy);
+        self
+    }
+
     /// Create the corresponding [`IdentityOptions`] object
     pub async fn build_options(self) -> Result<IdentityOptions> {
         let key = match self.key {
@@ -105,8 +113,11 @@ impl IdentityBuilder {
             } => (created_at, expires_at),
         };
 
-        let options =
+        let mut options =
             IdentityOptions::new(key, self.revoke_all_purpose_keys, created_at, expires_at);
+        if let Some(recovery_policy) = self.recovery_policy {
+            options = options.with_recovery_policy(recovery_policy);
+        }
 
         Ok(options)
     }
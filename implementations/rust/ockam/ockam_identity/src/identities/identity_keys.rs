@@ -1,5 +1,7 @@
 use crate::identity::Identity;
-use crate::models::{Change, ChangeData, ChangeHash, ChangeHistory, VersionedData};
+use crate::models::{
+    Change, ChangeData, ChangeHash, ChangeHistory, RecoverySignature, VersionedData,
+};
 use crate::{IdentityError, IdentityOptions};
 
 use ockam_core::compat::sync::Arc;
@@ -16,7 +18,7 @@ pub struct IdentitiesKeys {
 
 impl IdentitiesKeys {
     pub(crate) async fn create_initial_key(&self, options: IdentityOptions) -> Result<Identity> {
-        let change = self.make_change(options, None).await?;
+        let change = self.make_change(options, None, None).await?;
         let change_history = ChangeHistory(vec![change]);
 
         let identity = Identity::import_from_change_history(
@@ -56,10 +58,17 @@ impl IdentitiesKeys {
 
         let last_secret_key = self.get_secret_key(&identity).await?;
 
+        // Keep the recovery keys, unless new ones are given
+        let mut options = options;
+        if options.recovery_policy.is_none() {
+            options.recovery_policy = last_change.data().recovery_policy.clone();
+        }
+
         let change = self
             .make_change(
                 options,
-                Some((last_change.change_hash().clone(), last_secret_key.clone())),
+                Some(last_change.change_hash().clone()),
+                Some(last_secret_key.clone()),
             )
             .await?;
 
@@ -82,6 +91,148 @@ impl IdentitiesKeys {
         Ok(identity)
     }
 
+    /// Create a [`Change`] with a new key, when the secret key of the identity was lost.
+    /// The change is not valid until it is signed, using [`IdentitiesKeys::sign_recovery_change`],
+    /// by a quorum of the recovery keys registered in the last change of the identity
+    pub async fn create_recovery_change(
+        &self,
+        identity: &Identity,
+        options: IdentityOptions,
+    ) -> Result<Change> {
+        let last_change = match identity.changes().last() {
+            Some(last_change) => last_change,
+            None => return Err(IdentityError::EmptyIdentity.into()),
+        };
+        if last_change.data().recovery_policy.is_none() {
+            return Err(IdentityError::UnknownRecoveryKey.into());
+        }
+
+        // Keep the recovery keys, unless new ones are given
+        let mut options = options;
+        if options.recovery_policy.is_none() {
+            options.recovery_policy = last_change.data().recovery_policy.clone();
+        }
+
+        let mut change = self
+            .make_change(options, Some(last_change.change_hash().clone()), None)
+            .await?;
+        change.recovery_signatures = Some(vec![]);
+
+        Ok(change)
+    }
+
+    /// Sign a recovery [`Change`] of an identity with one of its recovery keys,
+    /// and add the signature to the change.
+    /// The recovery key must be present in the identity vault of this module
+    pub async fn sign_recovery_change(
+        &self,
+        identity: &Identity,
+        change: Change,
+        recovery_key: &SigningSecretKeyHandle,
+    ) -> Result<Change> {
+        let recovery_signature = self
+            .create_recovery_signature(identity, &change, recovery_key)
+            .await?;
+        Self::add_recovery_signatures(change, vec![recovery_signature])
+    }
+
+    /// Create the signature of a recovery [`Change`] with one of the recovery keys of an identity.
+    /// The signatures created by the holders of the recovery keys, possibly in different vaults,
+    /// are combined with [`IdentitiesKeys::add_recovery_signatures`]
+    pub async fn create_recovery_signature(
+        &self,
+        identity: &Identity,
+        change: &Change,
+        recovery_key: &SigningSecretKeyHandle,
+    ) -> Result<RecoverySignature> {
+        let last_change = match identity.changes().last() {
+            Some(last_change) => last_change,
+            None => return Err(IdentityError::EmptyIdentity.into()),
+        };
+        let recovery_policy = match &last_change.data().recovery_policy {
+            Some(recovery_policy) => recovery_policy,
+            None => return Err(IdentityError::UnknownRecoveryKey.into()),
+        };
+
+        let versioned_data = change.get_versioned_data()?;
+        let change_data = ChangeData::get_data(&versioned_data)?;
+        if change_data.previous_change.as_ref() != Some(last_change.change_hash()) {
+            // The change must replace the latest key of the identity
+            return Err(IdentityError::ConsistencyError.into());
+        }
+
+        let public_key = self
+            .identity_vault
+            .get_verifying_public_key(recovery_key)
+            .await?;
+        let key_index = recovery_policy
+            .key_index(&public_key)
+            .ok_or(IdentityError::UnknownRecoveryKey)?;
+
+        let hash = self.verifying_vault.sha256(&change.data).await?;
+        let signature = self.identity_vault.sign(recovery_key, &hash.0).await?;
+
+        Ok(RecoverySignature {
+            key_index,
+            signature: signature.into(),
+        })
+    }
+
+    /// Combine the signatures of a recovery [`Change`].
+    /// A new signature replaces a previous signature made with the same recovery key
+    pub fn add_recovery_signatures(
+        change: Change,
+        signatures: Vec<RecoverySignature>,
+    ) -> Result<Change> {
+        let mut change = change;
+        let mut recovery_signatures = change.recovery_signatures.take().unwrap_or_default();
+        for signature in signatures {
+            recovery_signatures.retain(|s| s.key_index != signature.key_index);
+            recovery_signatures.push(signature);
+        }
+        change.recovery_signatures = Some(recovery_signatures);
+
+        Ok(change)
+    }
+
+    /// Return the secret key of a recovery identity which was registered as a recovery key
+    /// of an identity. The recovery identity may have rotated its key since then, so all its
+    /// keys are checked, starting with the latest one
+    pub async fn get_recovery_key(
+        &self,
+        identity: &Identity,
+        recovery_identity: &Identity,
+    ) -> Result<SigningSecretKeyHandle> {
+        let last_change = match identity.changes().last() {
+            Some(last_change) => last_change,
+            None => return Err(IdentityError::EmptyIdentity.into()),
+        };
+        let recovery_policy = match &last_change.data().recovery_policy {
+            Some(recovery_policy) => recovery_policy,
+            None => return Err(IdentityError::UnknownRecoveryKey.into()),
+        };
+
+        for change in recovery_identity.changes().iter().rev() {
+            let public_key = change.primary_public_key();
+            if recovery_policy.key_index(public_key).is_some() {
+                return self.identity_vault.get_secret_key_handle(public_key).await;
+            }
+        }
+
+        Err(IdentityError::UnknownRecoveryKey.into())
+    }
+
+    /// Add a recovery [`Change`] signed by a quorum of recovery keys to an identity
+    pub async fn recover_key(&self, identity: Identity, change: Change) -> Result<Identity> {
+        if change.recovery_signatures.is_none() {
+            return Err(IdentityError::IdentityVerificationFailed.into());
+        }
+
+        identity
+            .add_change(change, self.verifying_vault.clone())
+            .await
+    }
+
     /// Return the secret key of an identity
     pub async fn get_secret_key(&self, identity: &Identity) -> Result<SigningSecretKeyHandle> {
         if let Some(last_change) = identity.changes().last() {
@@ -100,7 +251,8 @@ impl IdentitiesKeys {
     async fn make_change(
         &self,
         identity_options: IdentityOptions,
-        previous: Option<(ChangeHash, SigningSecretKeyHandle)>,
+        previous_change: Option<ChangeHash>,
+        previous_key: Option<SigningSecretKeyHandle>,
     ) -> Result<Change> {
         let secret_key = identity_options.signing_secret_key_handle;
         let public_key = self
@@ -109,11 +261,12 @@ impl IdentitiesKeys {
             .await?;
 
         let change_data = ChangeData {
-            previous_change: previous.as_ref().map(|x| x.0.clone()),
+            previous_change,
             primary_public_key: public_key.into(),
             revoke_all_purpose_keys: identity_options.revoke_all_purpose_keys,
             created_at: identity_options.created_at,
             expires_at: identity_options.expires_at,
+            recovery_policy: identity_options.recovery_policy,
         };
 
         let change_data = minicbor::to_vec(&change_data)?;
@@ -131,8 +284,9 @@ impl IdentitiesKeys {
         let self_signature = self_signature.into();
 
         // If we have previous_key passed we should sign using it
-        // If there is no previous_key - we're creating new identity, so we just generated the key
-        let previous_signature = match previous.map(|x| x.1) {
+        // If there is no previous_key - we're creating new identity, so we just generated the key,
+        // or we're recovering the identity, and the recovery keys sign the change instead
+        let previous_signature = match previous_key {
             Some(previous_key) => {
                 let previous_signature = self.identity_vault.sign(&previous_key, &hash.0).await?;
 
@@ -145,6 +299,7 @@ impl IdentitiesKeys {
             data: versioned_data,
             signature: self_signature,
             previous_signature,
+            recovery_signatures: None,
         };
 
         Ok(change)
@@ -155,7 +310,7 @@ impl IdentitiesKeys {
 mod test {
     use super::*;
     use crate::identities;
-    use crate::models::Identifier;
+    use crate::models::{Identifier, RecoveryPolicy};
     use crate::utils::now;
     use core::str::FromStr;
     use ockam_core::errcode::{Kind, Origin};
@@ -263,4 +418,197 @@ mod test {
 
         ctx.stop().await
     }
+
+    #[ockam_macros::test]
+    async fn test_identity_recovery(ctx: &mut Context) -> Result<()> {
+        let identities = identities();
+        let identities_keys = identities.identities_keys();
+        let identity_vault = identities_keys.identity_vault.clone();
+
+        let mut recovery_keys = vec![];
+        let mut recovery_public_keys = vec![];
+        for _ in 0..3 {
+            let key = identity_vault
+                .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+                .await?;
+            recovery_public_keys.push(identity_vault.get_verifying_public_key(&key).await?);
+            recovery_keys.push(key);
+        }
+        assert!(RecoveryPolicy::new(0, vec![]).is_err());
+        // A key can't be listed twice to reach the threshold alone
+        assert!(RecoveryPolicy::new(
+            2,
+            vec![
+                recovery_public_keys[0].clone(),
+                recovery_public_keys[0].clone()
+            ]
+        )
+        .is_err());
+        let recovery_policy = RecoveryPolicy::new(2, recovery_public_keys)?;
+
+        let key1 = identity_vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+        let now = now()?;
+        let options1 = IdentityOptions::new(key1, false, now, now + 120.into())
+            .with_recovery_policy(recovery_policy.clone());
+        let identity1 = identities_keys.create_initial_key(options1).await?;
+
+        // The recovery keys are kept when the key is rotated
+        let key2 = identity_vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+        let options2 = IdentityOptions::new(key2, false, now + 10.into(), now + 130.into());
+        let identity2 = identities_keys
+            .rotate_key_with_options(identity1, options2)
+            .await?;
+        assert_eq!(
+            identity2.get_latest_change()?.data().recovery_policy,
+            Some(recovery_policy)
+        );
+
+        // The key of the identity is lost, a new key is signed by the recovery keys
+        let key3 = identity_vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+        let options3 = IdentityOptions::new(key3.clone(), false, now + 20.into(), now + 140.into());
+        let change = identities_keys
+            .create_recovery_change(&identity2, options3)
+            .await?;
+
+        // One signature is not enough, even if it's repeated
+        let change = identities_keys
+            .sign_recovery_change(&identity2, change, &recovery_keys[0])
+            .await?;
+        let change = identities_keys
+            .sign_recovery_change(&identity2, change, &recovery_keys[0])
+            .await?;
+        assert!(identities_keys
+            .recover_key(identity2.clone(), change.clone())
+            .await
+            .is_err());
+
+        // A key which is not a recovery key can't sign
+        let other_key = identity_vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+        assert!(identities_keys
+            .sign_recovery_change(&identity2, change.clone(), &other_key)
+            .await
+            .is_err());
+
+        let change = identities_keys
+            .sign_recovery_change(&identity2, change, &recovery_keys[2])
+            .await?;
+        let identity3 = identities_keys
+            .recover_key(identity2.clone(), change)
+            .await?;
+        assert_eq!(identity3.identifier(), identity2.identifier());
+        assert_eq!(identities_keys.get_secret_key(&identity3).await?, key3);
+
+        // Check if verification succeeds
+        let _ = Identity::import_from_change_history(
+            Some(identity3.identifier()),
+            identity3.change_history().clone(),
+            identities.vault().verifying_vault,
+        )
+        .await?;
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_identity_recovery_with_separate_vaults(ctx: &mut Context) -> Result<()> {
+        // Each recovery identity is stored in its own vault
+        let mut recovery_vaults = vec![];
+        let mut recovery_identities = vec![];
+        for _ in 0..3 {
+            let identities = identities();
+            let recovery_identity = identities.identities_creation().create_identity().await?;
+            recovery_vaults.push(identities);
+            recovery_identities.push(recovery_identity);
+        }
+
+        // The first recovery identity rotated its key before being registered
+        let identities_creation = recovery_vaults[0].identities_creation();
+        identities_creation
+            .rotate_identity(recovery_identities[0].identifier())
+            .await?;
+        recovery_identities[0] = recovery_vaults[0]
+            .get_identity(recovery_identities[0].identifier())
+            .await?;
+
+        let recovery_public_keys = recovery_identities
+            .iter()
+            .map(|i| i.get_latest_public_key())
+            .collect::<Result<Vec<_>>>()?;
+        let recovery_policy = RecoveryPolicy::new(2, recovery_public_keys)?;
+
+        let identities = identities();
+        let identities_creation = identities.identities_creation();
+        let identities_keys = identities.identities_keys();
+        let options = identities_creation
+            .identity_builder()
+            .with_recovery_policy(recovery_policy)
+            .build_options()
+            .await?;
+        let identity = identities_creation
+            .create_identity_with_options(options)
+            .await?;
+
+        // The holder of the identity creates the recovery change with a new key
+        let options = identities_creation
+            .identity_builder()
+            .build_options()
+            .await?;
+        let new_key = options.signing_secret_key_handle().clone();
+        let change = identities_keys
+            .create_recovery_change(&identity, options)
+            .await?;
+
+        // The holders of the recovery identities only get the public identity and the change
+        let mut signatures = vec![];
+        for (recovery_vault, recovery_identity) in recovery_vaults.iter().zip(&recovery_identities)
+        {
+            let identity = recovery_vault
+                .identities_creation()
+                .import(Some(identity.identifier()), &identity.export()?)
+                .await?;
+            let recovery_keys = recovery_vault.identities_keys();
+            let recovery_key = recovery_keys
+                .get_recovery_key(&identity, recovery_identity)
+                .await?;
+            signatures.push(
+                recovery_keys
+                    .create_recovery_signature(&identity, &change, &recovery_key)
+                    .await?,
+            );
+        }
+
+        // An identity which is not a recovery identity has no recovery key
+        let other_identity = identities_creation.create_identity().await?;
+        assert!(identities_keys
+            .get_recovery_key(&identity, &other_identity)
+            .await
+            .is_err());
+
+        // One signature is not enough
+        let partially_signed =
+            IdentitiesKeys::add_recovery_signatures(change.clone(), vec![signatures[1].clone()])?;
+        assert!(identities_creation
+            .recover_identity(identity.identifier(), partially_signed.clone())
+            .await
+            .is_err());
+
+        // The signatures are combined by the holder of the identity
+        let signed =
+            IdentitiesKeys::add_recovery_signatures(partially_signed, vec![signatures[0].clone()])?;
+        let recovered = identities_creation
+            .recover_identity(identity.identifier(), signed)
+            .await?;
+        assert_eq!(recovered.identifier(), identity.identifier());
+        assert_eq!(identities_keys.get_secret_key(&recovered).await?, new_key);
+
+        ctx.stop().await
+    }
 }
@@ -1,3 +1,4 @@
+use crate::models::RecoveryPolicy;
 use crate::TimestampInSeconds;
 use ockam_vault::SigningSecretKeyHandle;
 
@@ -7,6 +8,7 @@ pub struct IdentityOptions {
     pub(super) revoke_all_purpose_keys: bool,
     pub(super) created_at: TimestampInSeconds,
     pub(super) expires_at: TimestampInSeconds,
+    pub(super) recovery_policy: Option<RecoveryPolicy>,
 }
 
 impl IdentityOptions {
@@ -22,9 +24,16 @@ impl IdentityOptions {
             revoke_all_purpose_keys,
             created_at,
             expires_at,
+            recovery_policy: None,
         }
     }
 
+    /// Register recovery keys which can authorize the next key if this key is lost
+    pub fn with_recovery_policy(mut self, recovery_policy: RecoveryPolicy) -> Self {
+        self.recovery_policy = Some(recovery_policy);
+        self
+    }
+
     /// New key
     pub fn signing_secret_key_handle(&self) -> &SigningSecretKeyHandle {
         &self.signing_secret_key_handle
@@ -44,4 +53,9 @@ impl IdentityOptions {
     pub fn expires_at(&self) -> TimestampInSeconds {
         self.expires_at
     }
+
+    /// Recovery keys
+    pub fn recovery_policy(&self) -> Option<&RecoveryPolicy> {
+        self.recovery_policy.as_ref()
+    }
 }
@@ -1,4 +1,4 @@
-use crate::models::{Identifier, TimestampInSeconds};
+use crate::models::{CredentialSchemaIdentifier, Identifier, TimestampInSeconds};
 use minicbor::{Decode, Encode};
 use ockam_core::compat::borrow::ToOwned;
 use ockam_core::compat::{collections::BTreeMap, vec::Vec};
@@ -14,6 +14,8 @@ pub struct AttributesEntry {
     #[n(2)] added: TimestampInSeconds,
     #[n(3)] expires: Option<TimestampInSeconds>,
     #[n(4)] attested_by: Option<Identifier>,
+    #[serde(default)]
+    #[n(5)] schema: Option<CredentialSchemaIdentifier>,
 }
 
 impl AttributesEntry {
@@ -33,9 +35,16 @@ impl AttributesEntry {
             added,
             expires,
             attested_by,
+            schema: None,
         }
     }
 
+    /// Set the schema of the credential these attributes come from
+    pub fn with_schema(mut self, schema: CredentialSchemaIdentifier) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
     /// The entry attributes
     pub fn attrs(&self) -> &BTreeMap<Vec<u8>, Vec<u8>> {
         &self.attrs
@@ -55,4 +64,9 @@ impl AttributesEntry {
     pub fn attested_by(&self) -> Option<Identifier> {
         self.attested_by.to_owned()
     }
+
+    /// Schema of the credential these attributes come from, if any
+    pub fn schema(&self) -> Option<CredentialSchemaIdentifier> {
+        self.schema.to_owned()
+    }
 }
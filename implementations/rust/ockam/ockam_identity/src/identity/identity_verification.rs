@@ -1,4 +1,7 @@
-use crate::models::{Change, ChangeData, ChangeHash, ChangeSignature, CHANGE_HASH_LEN};
+use crate::models::{
+    Change, ChangeData, ChangeHash, ChangeSignature, PrimaryPublicKey, RecoverySignature,
+    CHANGE_HASH_LEN,
+};
 use crate::verified_change::VerifiedChange;
 use crate::{Identity, IdentityError};
 use arrayref::array_ref;
@@ -98,6 +101,13 @@ impl Identity {
                 return Err(IdentityError::IdentityVerificationFailed.into());
             }
 
+            if let Some(recovery_policy) = &change_details.change_data.recovery_policy {
+                if recovery_policy.check().is_err() {
+                    // The threshold can't be reached
+                    return Err(IdentityError::IdentityVerificationFailed.into());
+                }
+            }
+
             to_be_verified_changes.push(VerifiedChange::new(
                 change_details.change_data.clone(),
                 change_details.change_hash.clone(),
@@ -165,10 +175,22 @@ impl Identity {
                 {
                     return Err(IdentityError::IdentityVerificationFailed.into());
                 }
+            } else if let Some(recovery_signatures) = &new_change.recovery_signatures {
+                Self::verify_recovery_signatures(
+                    last_verified_change,
+                    new_change_details.change_full_hash,
+                    recovery_signatures,
+                    vault.clone(),
+                )
+                .await?
             } else {
-                // Previous signature should be present if it's not the first change
+                // Previous signature or recovery signatures should be present
+                // if it's not the first change
                 return Err(IdentityError::IdentityVerificationFailed.into());
             }
+        } else if new_change.recovery_signatures.is_some() {
+            // The first change can't be recovered
+            return Err(IdentityError::IdentityVerificationFailed.into());
         }
 
         if !Self::verify_change_signature(
@@ -188,4 +210,139 @@ impl Identity {
 
         Ok(())
     }
+
+    /// Check that a quorum of the recovery keys of the last change signed the new change.
+    /// This authorizes a new Primary Public Key when the previous one was lost
+    async fn verify_recovery_signatures(
+        last_verified_change: &VerifiedChange,
+        hash: [u8; 32],
+        recovery_signatures: &[RecoverySignature],
+        vault: Arc<dyn VaultForVerifyingSignatures>,
+    ) -> Result<()> {
+        let recovery_policy = match &last_verified_change.data().recovery_policy {
+            Some(recovery_policy) => recovery_policy,
+            // The previous key didn't register any recovery key
+            None => return Err(IdentityError::IdentityVerificationFailed.into()),
+        };
+
+        // Distinct signers are counted, not signatures or key indexes
+        let mut signers: Vec<&PrimaryPublicKey> = Vec::new();
+        for recovery_signature in recovery_signatures {
+            let recovery_key = match recovery_policy
+                .recovery_keys
+                .get(recovery_signature.key_index as usize)
+            {
+                Some(recovery_key) => recovery_key,
+                None => return Err(IdentityError::IdentityVerificationFailed.into()),
+            };
+
+            if signers.contains(&recovery_key) {
+                // The same key can't be counted twice
+                return Err(IdentityError::IdentityVerificationFailed.into());
+            }
+            signers.push(recovery_key);
+
+            if !Self::verify_change_signature(
+                &recovery_key.clone().into(),
+                hash,
+                &recovery_signature.signature,
+                vault.clone(),
+            )
+            .await?
+            {
+                return Err(IdentityError::IdentityVerificationFailed.into());
+            }
+        }
+
+        if signers.len() < recovery_policy.threshold as usize {
+            // Not enough recovery keys signed the change
+            return Err(IdentityError::IdentityVerificationFailed.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RecoveryPolicy;
+    use crate::utils::now;
+    use ockam_vault::{
+        SigningKeyType, SoftwareVaultForSigning, SoftwareVaultForVerifyingSignatures,
+        VaultForSigning,
+    };
+
+    #[tokio::test]
+    async fn test_recovery_signatures_count_distinct_signers() -> Result<()> {
+        let signing_vault = SoftwareVaultForSigning::create();
+        let mut keys = vec![];
+        let mut public_keys = vec![];
+        for _ in 0..2 {
+            let key = signing_vault
+                .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+                .await?;
+            public_keys.push(PrimaryPublicKey::from(
+                signing_vault.get_verifying_public_key(&key).await?,
+            ));
+            keys.push(key);
+        }
+
+        // A policy listing the same key twice is invalid, but the verification
+        // must not rely on this check to count the signers
+        let recovery_policy = RecoveryPolicy {
+            threshold: 2,
+            recovery_keys: vec![
+                public_keys[0].clone(),
+                public_keys[0].clone(),
+                public_keys[1].clone(),
+            ],
+        };
+        assert!(recovery_policy.check().is_err());
+
+        let now = now()?;
+        let data = ChangeData {
+            previous_change: None,
+            primary_public_key: public_keys[1].clone(),
+            revoke_all_purpose_keys: false,
+            created_at: now,
+            expires_at: now + 120.into(),
+            recovery_policy: Some(recovery_policy),
+        };
+        let last_verified_change = VerifiedChange::new(
+            data,
+            ChangeHash([0; CHANGE_HASH_LEN]),
+            public_keys[1].clone().into(),
+        );
+
+        let hash = [1; SHA256_LENGTH];
+        let signature = |key_index: u8, signature: ChangeSignature| RecoverySignature {
+            key_index,
+            signature,
+        };
+        let signature0: ChangeSignature = signing_vault.sign(&keys[0], &hash).await?.into();
+        let signature1: ChangeSignature = signing_vault.sign(&keys[1], &hash).await?.into();
+        let vault = SoftwareVaultForVerifyingSignatures::create();
+
+        // The same key under two indexes is only one signer
+        assert!(Identity::verify_recovery_signatures(
+            &last_verified_change,
+            hash,
+            &[
+                signature(0, signature0.clone()),
+                signature(1, signature0.clone()),
+            ],
+            vault.clone(),
+        )
+        .await
+        .is_err());
+
+        Identity::verify_recovery_signatures(
+            &last_verified_change,
+            hash,
+            &[signature(0, signature0), signature(2, signature1)],
+            vault,
+        )
+        .await
+    }
 }
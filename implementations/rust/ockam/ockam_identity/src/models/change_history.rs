@@ -26,6 +26,20 @@ pub struct Change {
     /// Self-signature over the data using the key
     /// from the previous [`Change`] in the [`ChangeHistory`]
     #[n(3)] pub previous_signature: Option<ChangeSignature>,
+    /// Signatures over the data using the recovery keys of the previous [`Change`].
+    /// They replace the `previous_signature` when the previous key was lost
+    #[n(4)] pub recovery_signatures: Option<Vec<RecoverySignature>>,
+}
+
+/// Signature of a [`Change`] using one of the recovery keys of the previous [`Change`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RecoverySignature {
+    /// Index of the key in the [`RecoveryPolicy`] of the previous [`Change`]
+    #[n(1)] pub key_index: u8,
+    /// Signature over the data using that key
+    #[n(2)] pub signature: ChangeSignature,
 }
 
 /// [`Change`] signature
@@ -56,6 +70,20 @@ pub struct ChangeData {
     #[n(4)] pub created_at: TimestampInSeconds,
     /// Expiration [`TimestampInSeconds`] (UTC)
     #[n(5)] pub expires_at: TimestampInSeconds,
+    /// Keys which can authorize the next [`Change`] if the Primary Public Key is lost
+    #[n(6)] pub recovery_policy: Option<RecoveryPolicy>,
+}
+
+/// Set of recovery keys, a quorum of which can authorize a new Primary Public Key.
+/// The keys are usually the Primary Public Keys of other identities, or keys kept offline
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RecoveryPolicy {
+    /// Number of recovery keys which must sign the next [`Change`]
+    #[n(1)] pub threshold: u8,
+    /// Recovery keys
+    #[n(2)] pub recovery_keys: Vec<PrimaryPublicKey>,
 }
 
 /// [`Change`]'s public key
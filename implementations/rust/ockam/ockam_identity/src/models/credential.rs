@@ -3,6 +3,7 @@ use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 use ockam_core::compat::{collections::BTreeMap, vec::Vec};
 use ockam_vault::{ECDSASHA256CurveP256Signature, EdDSACurve25519Signature};
+use serde::{Deserialize, Serialize};
 
 /// Credential
 #[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
@@ -43,12 +44,67 @@ pub struct CredentialData {
     #[n(4)] pub created_at: TimestampInSeconds,
     /// Expiration [`TimestampInSeconds`] (UTC)
     #[n(5)] pub expires_at: TimestampInSeconds,
+    /// Commitments to attributes which are not in `subject_attributes`, and which are only
+    /// revealed by the [`AttributeDisclosure`]s presented with the Credential
+    #[n(6)] pub attribute_commitments: Option<Vec<AttributeCommitment>>,
+    /// When present, the Subject is a sub-authority which can issue [`Credential`]s
+    /// satisfying these [`DelegationConstraints`]
+    #[n(7)] pub delegation: Option<DelegationConstraints>,
 }
 
-/// Number that determines which keys&values to expect in the [`Attributes`]
+/// SHA-256 hash of the CBOR serialized [`AttributeDisclosure`] of an attribute
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(transparent)]
+pub struct AttributeCommitment(#[cbor(n(0), with = "minicbor::bytes")] pub [u8; 32]);
+
+/// Attribute key&value revealed by the holder of a Credential, with the random salt
+/// which prevents guessing the attributes which are not revealed from their [`AttributeCommitment`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AttributeDisclosure {
+    /// Random salt
+    #[cbor(with = "minicbor::bytes")]
+    #[n(1)] pub salt: Vec<u8>,
+    /// Attribute key
+    #[n(2)] pub key: ByteVec,
+    /// Attribute value
+    #[n(3)] pub value: ByteVec,
+}
+
+/// Constraints on the [`Credential`]s that a sub-authority can issue, signed by its issuer
 #[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
 #[rustfmt::skip]
+#[cbor(map)]
+pub struct DelegationConstraints {
+    /// Attributes that the sub-authority can attest
+    #[n(1)] pub attributes: Vec<AttributeConstraint>,
+    /// Maximum validity duration of the issued [`Credential`]s
+    #[n(2)] pub max_ttl: TimestampInSeconds,
+    /// Number of further levels of sub-authorities that the sub-authority can delegate to
+    #[n(3)] pub max_depth: u8,
+}
+
+/// Attribute that a sub-authority can attest, optionally restricted to a given value,
+/// or to values with a given prefix
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AttributeConstraint {
+    /// Attribute key
+    #[n(1)] pub key: ByteVec,
+    /// Required prefix of the attribute value
+    #[n(2)] pub value_prefix: Option<ByteVec>,
+    /// Required value of the attribute
+    #[n(3)] pub value: Option<ByteVec>,
+}
+
+/// Number that determines which keys&values to expect in the [`Attributes`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[rustfmt::skip]
 #[cbor(transparent)]
+#[serde(transparent)]
 pub struct CredentialSchemaIdentifier(#[n(0)] pub u64);
 
 /// Set a keys&values that an Authority (issuer) attests about the Subject
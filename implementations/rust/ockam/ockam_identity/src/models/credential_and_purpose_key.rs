@@ -1,5 +1,7 @@
-use crate::models::{Credential, PurposeKeyAttestation};
+use crate::models::{AttributeDisclosure, ChangeHistory, Credential, PurposeKeyAttestation};
 use minicbor::{Decode, Encode};
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::vec::Vec;
 
 /// [`Credential`] and the corresponding [`PurposeKeyAttestation`] that was used to issue that
 /// [`Credential`] and will be used to verify it
@@ -12,4 +14,13 @@ pub struct CredentialAndPurposeKey {
     /// Corresponding [`PurposeKeyAttestation`] that was used to issue that
     /// [`Credential`] and will be used to verify it
     #[n(2)] pub purpose_key_attestation: PurposeKeyAttestation,
+    /// Committed attributes of the [`Credential`] revealed to the verifier.
+    /// The holder can remove the ones which the verifier doesn't need
+    #[n(3)] pub disclosures: Option<Vec<AttributeDisclosure>>,
+    /// Delegation [`Credential`] of the issuer when it is a sub-authority, itself followed by the
+    /// delegations of its own issuer up to an authority
+    #[n(4)] pub delegation: Option<Box<CredentialAndPurposeKey>>,
+    /// [`ChangeHistory`] of the issuer when it is a sub-authority, so that a verifier which
+    /// only knows the authorities can verify the [`PurposeKeyAttestation`]
+    #[n(5)] pub issuer_change_history: Option<ChangeHistory>,
 }
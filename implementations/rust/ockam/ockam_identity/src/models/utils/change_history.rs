@@ -1,7 +1,9 @@
 use crate::models::utils::get_versioned_data;
 use crate::models::{
-    Change, ChangeData, ChangeHistory, ChangeSignature, PrimaryPublicKey, VersionedData,
+    Change, ChangeData, ChangeHistory, ChangeSignature, PrimaryPublicKey, RecoveryPolicy,
+    VersionedData,
 };
+use crate::IdentityError;
 
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
@@ -21,6 +23,49 @@ impl ChangeData {
     }
 }
 
+impl RecoveryPolicy {
+    /// Create a [`RecoveryPolicy`] where `threshold` keys out of `recovery_keys`
+    /// must sign the next [`Change`]
+    pub fn new(threshold: u8, recovery_keys: Vec<VerifyingPublicKey>) -> Result<Self> {
+        let policy = Self {
+            threshold,
+            recovery_keys: recovery_keys.into_iter().map(|k| k.into()).collect(),
+        };
+        policy.check()?;
+        Ok(policy)
+    }
+
+    /// Check that the threshold can be reached with distinct recovery keys
+    pub fn check(&self) -> Result<()> {
+        if self.threshold == 0
+            || self.recovery_keys.len() > u8::MAX as usize
+            || self.threshold as usize > self.recovery_keys.len()
+        {
+            return Err(IdentityError::InvalidRecoveryPolicy.into());
+        }
+
+        // A key listed twice would count twice towards the threshold
+        let has_duplicates = self
+            .recovery_keys
+            .iter()
+            .enumerate()
+            .any(|(i, key)| self.recovery_keys[..i].contains(key));
+        if has_duplicates {
+            return Err(IdentityError::InvalidRecoveryPolicy.into());
+        }
+        Ok(())
+    }
+
+    /// Index of a recovery key
+    pub fn key_index(&self, public_key: &VerifyingPublicKey) -> Option<u8> {
+        let public_key = PrimaryPublicKey::from(public_key.clone());
+        self.recovery_keys
+            .iter()
+            .position(|k| k == &public_key)
+            .map(|i| i as u8)
+    }
+}
+
 impl ChangeHistory {
     /// Export [`ChangeHistory`] to a binary format using CBOR
     pub fn export(&self) -> Result<Vec<u8>> {
@@ -1,9 +1,20 @@
 use crate::models::utils::get_versioned_data;
-use crate::models::{CredentialData, CredentialSignature, VersionedData};
+use crate::models::{
+    AttributeCommitment, AttributeConstraint, AttributeDisclosure, Attributes,
+    CredentialAndPurposeKey, CredentialData, CredentialSignature, DelegationConstraints,
+    TimestampInSeconds, VersionedData,
+};
 use crate::Credential;
 
+use core::time::Duration;
+use minicbor::bytes::ByteVec;
+use ockam_core::compat::rand::random;
+use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
-use ockam_vault::Signature;
+use ockam_vault::{Signature, VaultForVerifyingSignatures};
+
+/// Length of the random salt of an [`AttributeDisclosure`]
+const ATTRIBUTE_SALT_LEN: usize = 16;
 
 impl Credential {
     /// Extract [`VersionedData`]
@@ -19,6 +30,186 @@ impl CredentialData {
     }
 }
 
+impl CredentialAndPurposeKey {
+    /// Extract the [`CredentialData`] of the [`Credential`], without verifying it
+    pub fn get_credential_data(&self) -> Result<CredentialData> {
+        CredentialData::get_data(&self.credential.get_versioned_data()?)
+    }
+
+    /// Keys of the committed attributes revealed by this presentation
+    pub fn disclosed_attribute_keys(&self) -> Vec<Vec<u8>> {
+        self.disclosures
+            .iter()
+            .flatten()
+            .map(|d| d.key.to_vec())
+            .collect()
+    }
+
+    /// Create a presentation of this [`Credential`] revealing only the given committed attributes.
+    /// The signature of the issuer stays valid, and the other attributes can't be guessed
+    pub fn disclose(&self, attribute_keys: &[&[u8]]) -> Self {
+        let disclosures = self.disclosures.as_ref().map(|disclosures| {
+            disclosures
+                .iter()
+                .filter(|d| attribute_keys.contains(&d.key.as_slice()))
+                .cloned()
+                .collect()
+        });
+
+        Self {
+            credential: self.credential.clone(),
+            purpose_key_attestation: self.purpose_key_attestation.clone(),
+            disclosures,
+            delegation: self.delegation.clone(),
+            issuer_change_history: self.issuer_change_history.clone(),
+        }
+    }
+}
+
+impl DelegationConstraints {
+    /// Create constraints allowing no attributes, no further delegation, and [`Credential`]s
+    /// valid for at most `max_ttl`
+    pub fn new(max_ttl: Duration) -> Self {
+        Self {
+            attributes: Vec::new(),
+            max_ttl: TimestampInSeconds(max_ttl.as_secs()),
+            max_depth: 0,
+        }
+    }
+
+    /// Allow an attribute with any value
+    pub fn with_attribute(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.attributes.push(AttributeConstraint {
+            key: ByteVec::from(key.into()),
+            value_prefix: None,
+            value: None,
+        });
+        self
+    }
+
+    /// Allow an attribute with exactly the given value
+    pub fn with_attribute_value(
+        mut self,
+        key: impl Into<Vec<u8>>,
+        value: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.attributes.push(AttributeConstraint {
+            key: ByteVec::from(key.into()),
+            value_prefix: None,
+            value: Some(ByteVec::from(value.into())),
+        });
+        self
+    }
+
+    /// Allow an attribute with values starting with the given prefix
+    pub fn with_attribute_prefix(
+        mut self,
+        key: impl Into<Vec<u8>>,
+        value_prefix: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.attributes.push(AttributeConstraint {
+            key: ByteVec::from(key.into()),
+            value_prefix: Some(ByteVec::from(value_prefix.into())),
+            value: None,
+        });
+        self
+    }
+
+    /// Allow the sub-authority to delegate to `max_depth` further levels of sub-authorities
+    pub fn with_max_depth(mut self, max_depth: u8) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Return true if an attribute with that key and value can be attested
+    pub fn allows_attribute(&self, key: &[u8], value: &[u8]) -> bool {
+        self.attributes
+            .iter()
+            .any(|c| c.key.as_slice() == key && c.allows_value(value))
+    }
+
+    /// Return true if all the attributes can be attested
+    pub fn allows_attributes(&self, attributes: &Attributes) -> bool {
+        attributes
+            .map
+            .iter()
+            .all(|(key, value)| self.allows_attribute(key, value))
+    }
+
+    /// Return true if a [`Credential`] can be valid for that time range
+    pub fn allows_lifetime(
+        &self,
+        created_at: TimestampInSeconds,
+        expires_at: TimestampInSeconds,
+    ) -> bool {
+        created_at <= expires_at && expires_at - created_at <= self.max_ttl
+    }
+
+    /// Return true if the sub-authority can delegate the given constraints, which must be
+    /// narrower than these ones
+    pub fn allows_delegation(&self, other: &DelegationConstraints) -> bool {
+        other.max_depth < self.max_depth
+            && other.max_ttl <= self.max_ttl
+            && other.attributes.iter().all(|delegated| {
+                self.attributes
+                    .iter()
+                    .any(|c| c.allows_constraint(delegated))
+            })
+    }
+}
+
+impl AttributeConstraint {
+    /// Return true if an attribute value satisfies this constraint
+    pub fn allows_value(&self, value: &[u8]) -> bool {
+        self.value
+            .as_ref()
+            .map_or(true, |expected| value == expected.as_slice())
+            && self
+                .value_prefix
+                .as_ref()
+                .map_or(true, |prefix| value.starts_with(prefix))
+    }
+
+    /// Return true if all the values allowed by a delegated constraint on the same attribute
+    /// are also allowed by this constraint
+    pub fn allows_constraint(&self, delegated: &AttributeConstraint) -> bool {
+        if self.key != delegated.key {
+            return false;
+        }
+        match &delegated.value {
+            Some(value) => self.allows_value(value),
+            None => {
+                self.value.is_none()
+                    && match (&self.value_prefix, &delegated.value_prefix) {
+                        (None, _) => true,
+                        (Some(prefix), Some(other_prefix)) => other_prefix.starts_with(prefix),
+                        (Some(_), None) => false,
+                    }
+            }
+        }
+    }
+}
+
+impl AttributeDisclosure {
+    /// Create a disclosure for an attribute with a random salt
+    pub fn new(key: Vec<u8>, value: Vec<u8>) -> Self {
+        Self {
+            salt: random::<[u8; ATTRIBUTE_SALT_LEN]>().to_vec(),
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Compute the [`AttributeCommitment`] signed by the issuer for this attribute
+    pub async fn commitment(
+        &self,
+        vault: &dyn VaultForVerifyingSignatures,
+    ) -> Result<AttributeCommitment> {
+        let hash = vault.sha256(&minicbor::to_vec(self)?).await?;
+        Ok(AttributeCommitment(hash.0))
+    }
+}
+
 impl From<CredentialSignature> for Signature {
     fn from(value: CredentialSignature) -> Self {
         match value {
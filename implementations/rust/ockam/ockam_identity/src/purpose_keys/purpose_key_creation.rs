@@ -6,9 +6,9 @@ use crate::models::{
 };
 use crate::purpose_keys::storage::PurposeKeysRepository;
 use crate::{
-    CredentialPurposeKey, CredentialPurposeKeyBuilder, IdentitiesKeys, IdentitiesReader, Identity,
-    IdentityError, Purpose, PurposeKeyVerification, SecureChannelPurposeKey,
-    SecureChannelPurposeKeyBuilder, TimestampInSeconds, Vault,
+    CredentialPurposeKey, CredentialPurposeKeyBuilder, IdentitiesKeys, IdentitiesReader,
+    IdentitiesWriter, Identity, IdentityError, Purpose, PurposeKeyVerification,
+    SecureChannelPurposeKey, SecureChannelPurposeKeyBuilder, TimestampInSeconds, Vault,
 };
 
 /// This struct supports all the services related to identities
@@ -16,6 +16,7 @@ use crate::{
 pub struct PurposeKeyCreation {
     vault: Vault,
     identities_reader: Arc<dyn IdentitiesReader>,
+    identities_writer: Arc<dyn IdentitiesWriter>,
     identity_keys: Arc<IdentitiesKeys>,
     repository: Arc<dyn PurposeKeysRepository>,
 }
@@ -25,12 +26,14 @@ impl PurposeKeyCreation {
     pub(crate) fn new(
         vault: Vault,
         identities_reader: Arc<dyn IdentitiesReader>,
+        identities_writer: Arc<dyn IdentitiesWriter>,
         identity_keys: Arc<IdentitiesKeys>,
         repository: Arc<dyn PurposeKeysRepository>,
     ) -> Self {
         Self {
             vault,
             identities_reader,
+            identities_writer,
             identity_keys,
             repository,
         }
@@ -46,6 +49,7 @@ impl PurposeKeyCreation {
         Arc::new(PurposeKeyVerification::new(
             self.vault.verifying_vault.clone(),
             self.identities_reader.clone(),
+            self.identities_writer.clone(),
         ))
     }
 
@@ -58,6 +62,7 @@ impl PurposeKeyCreation {
             Arc::new(Self::new(
                 self.vault.clone(),
                 self.identities_reader.clone(),
+                self.identities_writer.clone(),
                 self.identity_keys.clone(),
                 self.repository.clone(),
             )),
@@ -74,6 +79,7 @@ impl PurposeKeyCreation {
             Arc::new(Self::new(
                 self.vault.clone(),
                 self.identities_reader.clone(),
+                self.identities_writer.clone(),
                 self.identity_keys.clone(),
                 self.repository.clone(),
             )),
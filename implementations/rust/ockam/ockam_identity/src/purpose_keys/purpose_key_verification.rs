@@ -2,9 +2,12 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::Result;
 use ockam_vault::VaultForVerifyingSignatures;
 
-use crate::models::{Identifier, PurposeKeyAttestation, PurposeKeyAttestationData};
+use crate::models::{ChangeHistory, Identifier, PurposeKeyAttestation, PurposeKeyAttestationData};
 use crate::utils::now;
-use crate::{IdentitiesReader, Identity, IdentityError, TimestampInSeconds};
+use crate::{
+    IdentitiesReader, IdentitiesWriter, Identity, IdentityError, IdentityHistoryComparison,
+    TimestampInSeconds,
+};
 
 /// We allow purpose keys to be created in the future related to this machine's time due to
 /// possible time dyssynchronization
@@ -15,6 +18,7 @@ const MAX_ALLOWED_TIME_DRIFT: TimestampInSeconds = TimestampInSeconds(5);
 pub struct PurposeKeyVerification {
     verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
     identities_reader: Arc<dyn IdentitiesReader>,
+    identities_writer: Arc<dyn IdentitiesWriter>,
 }
 
 impl PurposeKeyVerification {
@@ -22,10 +26,12 @@ impl PurposeKeyVerification {
     pub(crate) fn new(
         verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
         identities_reader: Arc<dyn IdentitiesReader>,
+        identities_writer: Arc<dyn IdentitiesWriter>,
     ) -> Self {
         Self {
             verifying_vault,
             identities_reader,
+            identities_writer,
         }
     }
 }
@@ -36,6 +42,19 @@ impl PurposeKeyVerification {
         &self,
         expected_subject: Option<&Identifier>,
         attestation: &PurposeKeyAttestation,
+    ) -> Result<PurposeKeyAttestationData> {
+        self.verify_purpose_key_attestation_with_change_history(expected_subject, attestation, None)
+            .await
+    }
+
+    /// Verify a [`PurposeKeyAttestation`] with the [`ChangeHistory`] presented by its subject.
+    /// The presented [`ChangeHistory`] is used, and stored, when it extends the known one,
+    /// for example after a key rotation of the subject
+    pub async fn verify_purpose_key_attestation_with_change_history(
+        &self,
+        expected_subject: Option<&Identifier>,
+        attestation: &PurposeKeyAttestation,
+        change_history: Option<&ChangeHistory>,
     ) -> Result<PurposeKeyAttestationData> {
         let versioned_data_hash = self.verifying_vault.sha256(&attestation.data).await?;
 
@@ -54,16 +73,9 @@ impl PurposeKeyVerification {
             }
         }
 
-        let change_history = self
-            .identities_reader
-            .get_identity(&purpose_key_data.subject)
+        let identity = self
+            .get_subject_identity(&purpose_key_data.subject, change_history)
             .await?;
-        let identity = Identity::import_from_change_history(
-            Some(&purpose_key_data.subject),
-            change_history,
-            self.verifying_vault.clone(),
-        )
-        .await?;
 
         let latest_change = identity.get_latest_change()?;
 
@@ -120,4 +132,61 @@ impl PurposeKeyVerification {
 
         Ok(purpose_key_data)
     }
+
+    /// Return the [`Identity`] of the subject of an attestation:
+    ///  - the presented [`ChangeHistory`] if the subject is unknown
+    ///  - the presented [`ChangeHistory`] if it extends the known one, which is then updated
+    ///  - the known [`ChangeHistory`] if no [`ChangeHistory`] is presented
+    ///
+    /// A presented [`ChangeHistory`] which is older or conflicts with the known one is rejected.
+    /// The identifier is checked against the change history when importing it
+    async fn get_subject_identity(
+        &self,
+        subject: &Identifier,
+        presented: Option<&ChangeHistory>,
+    ) -> Result<Identity> {
+        let known = self.identities_reader.retrieve_identity(subject).await?;
+        let presented = match presented {
+            Some(presented) => presented.clone(),
+            None => {
+                let known = match known {
+                    Some(known) => known,
+                    None => self.identities_reader.get_identity(subject).await?,
+                };
+                return Identity::import_from_change_history(
+                    Some(subject),
+                    known,
+                    self.verifying_vault.clone(),
+                )
+                .await;
+            }
+        };
+
+        let presented = Identity::import_from_change_history(
+            Some(subject),
+            presented,
+            self.verifying_vault.clone(),
+        )
+        .await?;
+        if let Some(known) = known {
+            let known = Identity::import_from_change_history(
+                Some(subject),
+                known,
+                self.verifying_vault.clone(),
+            )
+            .await?;
+            match presented.compare(&known) {
+                IdentityHistoryComparison::Newer => {
+                    self.identities_writer
+                        .update_identity(subject, presented.change_history())
+                        .await?
+                }
+                IdentityHistoryComparison::Equal => {}
+                IdentityHistoryComparison::Older | IdentityHistoryComparison::Conflict => {
+                    return Err(IdentityError::PurposeKeyAttestationVerificationFailed.into())
+                }
+            }
+        }
+        Ok(presented)
+    }
 }
@@ -1,13 +1,17 @@
 use ockam_core::compat::sync::Arc;
 
 use crate::purpose_keys::storage::PurposeKeysRepository;
-use crate::{IdentitiesKeys, IdentitiesReader, PurposeKeyCreation, PurposeKeyVerification, Vault};
+use crate::{
+    IdentitiesKeys, IdentitiesReader, IdentitiesWriter, PurposeKeyCreation, PurposeKeyVerification,
+    Vault,
+};
 
 /// This struct supports all the services related to identities
 #[derive(Clone)]
 pub struct PurposeKeys {
     vault: Vault,
     identities_reader: Arc<dyn IdentitiesReader>,
+    identities_writer: Arc<dyn IdentitiesWriter>,
     identity_keys: Arc<IdentitiesKeys>,
     repository: Arc<dyn PurposeKeysRepository>,
 }
@@ -17,12 +21,14 @@ impl PurposeKeys {
     pub fn new(
         vault: Vault,
         identities_reader: Arc<dyn IdentitiesReader>,
+        identities_writer: Arc<dyn IdentitiesWriter>,
         identity_keys: Arc<IdentitiesKeys>,
         repository: Arc<dyn PurposeKeysRepository>,
     ) -> Self {
         Self {
             vault,
             identities_reader,
+            identities_writer,
             identity_keys,
             repository,
         }
@@ -38,6 +44,7 @@ impl PurposeKeys {
         Arc::new(PurposeKeyCreation::new(
             self.vault.clone(),
             self.identities_reader.clone(),
+            self.identities_writer.clone(),
             self.identity_keys.clone(),
             self.repository.clone(),
         ))
@@ -48,6 +55,7 @@ impl PurposeKeys {
         Arc::new(PurposeKeyVerification::new(
             self.vault.verifying_vault.clone(),
             self.identities_reader.clone(),
+            self.identities_writer.clone(),
         ))
     }
 }
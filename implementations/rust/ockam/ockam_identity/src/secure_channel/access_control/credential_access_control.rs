@@ -1,13 +1,19 @@
 use core::fmt::{Debug, Formatter};
 use ockam_core::access_control::IncomingAccessControl;
-use ockam_core::compat::{boxed::Box, sync::Arc, vec::Vec};
+use ockam_core::compat::{boxed::Box, string::String, sync::Arc, vec::Vec};
 use ockam_core::Result;
 use ockam_core::{async_trait, RelayMessage};
+use tracing::debug;
 
 use crate::identities::IdentitiesRepository;
 use crate::secure_channel::local_info::IdentitySecureChannelLocalInfo;
 
-/// Access control checking that message senders have a specific set of attributes
+/// Access control checking that message senders have a specific set of attributes.
+///
+/// The attributes are the ones stored when the senders presented their credentials. When a
+/// credential supports selective disclosure, the sender only needs to disclose the
+/// [`CredentialAccessControl::required_attribute_keys`], for example with
+/// [`crate::SecureChannelOptions::with_disclosed_attributes`]
 #[derive(Clone)]
 pub struct CredentialAccessControl {
     required_attributes: Vec<(Vec<u8>, Vec<u8>)>,
@@ -25,6 +31,14 @@ impl CredentialAccessControl {
             storage,
         }
     }
+
+    /// Keys of the attributes which the senders must present
+    pub fn required_attribute_keys(&self) -> Vec<Vec<u8>> {
+        self.required_attributes
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
 }
 
 impl Debug for CredentialAccessControl {
@@ -55,7 +69,15 @@ impl IncomingAccessControl for CredentialAccessControl {
             for required_attribute in self.required_attributes.iter() {
                 let attr_val = match attributes.attrs().get(&required_attribute.0) {
                     Some(v) => v,
-                    None => return Ok(false), // No required key
+                    None => {
+                        // No required key, it might not have been disclosed by the sender
+                        debug!(
+                            "the attribute {} was not presented by {}",
+                            String::from_utf8_lossy(&required_attribute.0),
+                            msg_identity_id.their_identity_id()
+                        );
+                        return Ok(false);
+                    }
                 };
 
                 if &required_attribute.1 != attr_val {
@@ -1,18 +1,26 @@
+use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
-use ockam_core::{Any, Result, Routed, TransportMessage};
+use ockam_core::{route, Any, ChannelClosedLocalInfo, Result, Routed, TransportMessage};
 use ockam_core::{Decodable, LocalMessage};
 use ockam_node::Context;
 
-use crate::models::Identifier;
-use crate::secure_channel::encryptor::{Encryptor, KEY_RENEWAL_INTERVAL};
+use crate::models::{Identifier, TimestampInSeconds};
+use crate::secure_channel::encryptor::Encryptor;
 use crate::secure_channel::key_tracker::KeyTracker;
+use crate::secure_channel::message::{
+    CloseMessage, CloseReason, RefreshCredentialsMessage, SecureChannelMessage,
+};
 use crate::secure_channel::nonce_tracker::NonceTracker;
-use crate::secure_channel::Addresses;
-use crate::{DecryptionRequest, DecryptionResponse, IdentityError, IdentitySecureChannelLocalInfo};
+use crate::secure_channel::{Addresses, SecureChannelSharedState};
+use crate::utils::now;
+use crate::{
+    DecryptionRequest, DecryptionResponse, Identities, IdentityError,
+    IdentitySecureChannelLocalInfo, TrustContext,
+};
 
 use ockam_vault::{AeadSecretKeyHandle, VaultForSecureChannels};
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
 pub(crate) struct DecryptorHandler {
     //for debug purposes only
@@ -20,6 +28,50 @@ pub(crate) struct DecryptorHandler {
     pub(crate) addresses: Addresses,
     pub(crate) their_identity_id: Identifier,
     pub(crate) decryptor: Decryptor,
+    pub(crate) credentials_expiration: CredentialsExpiration,
+    /// True if the other end of the channel sends secure channel messages.
+    /// Otherwise it only sends plain transport messages
+    pub(crate) secure_channel_messages: bool,
+    pub(crate) shared_state: SecureChannelSharedState,
+    /// Reason sent by the other end of the channel, if it closed the channel
+    pub(crate) closed_by_other_end: Option<CloseReason>,
+}
+
+/// Track the expiration of the credentials presented by the other end of the channel
+pub(crate) struct CredentialsExpiration {
+    identities: Arc<Identities>,
+    trust_context: Option<TrustContext>,
+    /// Earliest expiration of the last credentials presented by the other end of the channel
+    expires_at: Option<TimestampInSeconds>,
+    /// If true the channel is closed when the credentials expire without being refreshed.
+    /// Otherwise the channel stays open, without the attributes of the other party
+    close_on_expiration: bool,
+    /// True when the expiration of the credentials has already been reported
+    expired: bool,
+}
+
+impl CredentialsExpiration {
+    pub(crate) fn new(
+        identities: Arc<Identities>,
+        trust_context: Option<TrustContext>,
+        expires_at: Option<TimestampInSeconds>,
+        close_on_expiration: bool,
+    ) -> Self {
+        Self {
+            identities,
+            trust_context,
+            expires_at,
+            close_on_expiration,
+            expired: false,
+        }
+    }
+
+    fn is_expired(&self) -> Result<bool> {
+        Ok(match self.expires_at {
+            Some(expires_at) => expires_at <= now()?,
+            None => false,
+        })
+    }
 }
 
 impl DecryptorHandler {
@@ -29,12 +81,20 @@ impl DecryptorHandler {
         key: AeadSecretKeyHandle,
         vault: Arc<dyn VaultForSecureChannels>,
         their_identity_id: Identifier,
+        their_key_renewal_interval: u64,
+        secure_channel_messages: bool,
+        credentials_expiration: CredentialsExpiration,
+        shared_state: SecureChannelSharedState,
     ) -> Self {
         Self {
             role,
             addresses,
             their_identity_id,
-            decryptor: Decryptor::new(key, vault),
+            decryptor: Decryptor::new(key, vault, their_key_renewal_interval),
+            credentials_expiration,
+            secure_channel_messages,
+            shared_state,
+            closed_by_other_end: None,
         }
     }
 
@@ -84,8 +144,44 @@ impl DecryptorHandler {
         // Decrypt the binary
         let decrypted_payload = self.decryptor.decrypt(&payload).await?;
 
+        if !self.secure_channel_messages {
+            return self.handle_payload(ctx, decrypted_payload).await;
+        }
+        match minicbor::decode(&decrypted_payload)? {
+            SecureChannelMessage::Payload(payload) => self.handle_payload(ctx, payload).await,
+            SecureChannelMessage::RefreshCredentials(message) => {
+                self.handle_refresh_credentials(message).await
+            }
+            SecureChannelMessage::Close(message) => self.handle_close(ctx, message).await,
+        }
+    }
+
+    async fn handle_payload(&mut self, ctx: &mut Context, payload: Vec<u8>) -> Result<()> {
+        if self.credentials_expiration.is_expired()? {
+            if self.credentials_expiration.close_on_expiration {
+                warn!(
+                    "SecureChannel {} at {} is closed because the credentials of {} expired",
+                    self.role, &self.addresses.decryptor_remote, self.their_identity_id
+                );
+                self.shared_state
+                    .set_close_reason(CloseReason::CredentialsExpired);
+                return ctx
+                    .stop_worker(self.addresses.decryptor_remote.clone())
+                    .await;
+            }
+            if !self.credentials_expiration.expired {
+                // The attributes of the other party expire with its credentials, the identities
+                // repository doesn't return them anymore
+                warn!(
+                    "SecureChannel {} at {}: the credentials of {} expired, its attributes cannot be used anymore",
+                    self.role, &self.addresses.decryptor_remote, self.their_identity_id
+                );
+                self.credentials_expiration.expired = true;
+            }
+        }
+
         // Encrypted data should be a TransportMessage
-        let mut transport_message = TransportMessage::decode(&decrypted_payload)?;
+        let mut transport_message = TransportMessage::decode(&payload)?;
 
         // Add encryptor hop in the return_route (instead of our address)
         transport_message
@@ -115,8 +211,109 @@ impl DecryptorHandler {
         }
     }
 
-    /// Remove the channel keys on shutdown
-    pub(crate) async fn shutdown(&self) -> Result<()> {
+    /// Verify the renewed credentials of the other party and store its attributes.
+    /// Invalid credentials are ignored and the channel keeps the previous credentials
+    async fn handle_refresh_credentials(
+        &mut self,
+        message: RefreshCredentialsMessage,
+    ) -> Result<()> {
+        debug!(
+            "SecureChannel {} received renewed credentials {}",
+            self.role, &self.addresses.decryptor_remote
+        );
+
+        let expiration = &mut self.credentials_expiration;
+        let authorities = match &expiration.trust_context {
+            Some(trust_context) => trust_context.authorities().await?,
+            None => {
+                warn!(
+                    "SecureChannel {} at {} cannot verify renewed credentials without a trust context",
+                    self.role, &self.addresses.decryptor_remote
+                );
+                return Ok(());
+            }
+        };
+
+        let mut expirations = Vec::new();
+        for credential in &message.credentials {
+            let result = expiration
+                .identities
+                .credentials()
+                .credentials_verification()
+                .receive_presented_credential(&self.their_identity_id, &authorities, credential)
+                .await;
+            match result {
+                Ok(()) => {
+                    expirations.push(credential.get_credential_data()?.expires_at);
+                }
+                Err(e) => {
+                    warn!(
+                        "a renewed credential of {} could not be validated {}",
+                        self.their_identity_id,
+                        e.to_string()
+                    );
+                }
+            }
+        }
+
+        if let Some(expires_at) = expirations.into_iter().min() {
+            expiration.expires_at = Some(expires_at);
+            expiration.expired = false;
+            info!(
+                "SecureChannel {} at {} refreshed the credentials of {}",
+                self.role, &self.addresses.decryptor_remote, self.their_identity_id
+            );
+        }
+        Ok(())
+    }
+
+    /// The other end closed the channel: stop this end without notifying the other end
+    async fn handle_close(&mut self, ctx: &mut Context, message: CloseMessage) -> Result<()> {
+        info!(
+            "SecureChannel {} at {} was closed by {}: {}",
+            self.role, &self.addresses.decryptor_remote, self.their_identity_id, message.reason
+        );
+        self.shared_state.closed_by_other_end();
+        self.closed_by_other_end = Some(message.reason);
+        ctx.stop_worker(self.addresses.decryptor_remote.clone())
+            .await
+    }
+
+    /// Notify the local workers which subscribed to the closing of the channel,
+    /// so that they can release the resources depending on the channel.
+    /// Then remove the channel keys
+    pub(crate) async fn shutdown(&self, ctx: &Context) -> Result<()> {
+        let reason = match (self.closed_by_other_end, self.shared_state.close_reason()) {
+            (Some(reason), _) => format!("closed by {}: {}", self.their_identity_id, reason),
+            (None, Some(reason)) => reason.to_string(),
+            (None, None) => CloseReason::Closed.to_string(),
+        };
+        // Mark the notification like the other messages received through the channel,
+        // so that it is accepted by the access controls of the subscribers
+        let local_info = IdentitySecureChannelLocalInfo::mark(
+            vec![ChannelClosedLocalInfo::new(reason).to_local_info()?],
+            self.their_identity_id.clone(),
+        )?;
+        for destination in self.shared_state.closing_subscribers() {
+            let msg = LocalMessage::new(
+                TransportMessage::v1(
+                    route![destination.clone()],
+                    route![self.addresses.encryptor.clone()],
+                    vec![],
+                ),
+                local_info.clone(),
+            );
+            if let Err(e) = ctx
+                .forward_from_address(msg, self.addresses.decryptor_internal.clone())
+                .await
+            {
+                debug!(
+                    "SecureChannel {} at {} could not notify {} about its closing: {}",
+                    self.role, &self.addresses.decryptor_remote, destination, e
+                );
+            }
+        }
+
         self.decryptor.shutdown().await
     }
 }
@@ -128,11 +325,17 @@ pub(crate) struct Decryptor {
 }
 
 impl Decryptor {
-    pub fn new(key: AeadSecretKeyHandle, vault: Arc<dyn VaultForSecureChannels>) -> Self {
+    /// Create a decryptor for the messages of an encryptor deriving a new key
+    /// every `key_renewal_interval` messages
+    pub fn new(
+        key: AeadSecretKeyHandle,
+        vault: Arc<dyn VaultForSecureChannels>,
+        key_renewal_interval: u64,
+    ) -> Self {
         Self {
             vault,
-            key_tracker: KeyTracker::new(key, KEY_RENEWAL_INTERVAL),
-            nonce_tracker: NonceTracker::new(),
+            key_tracker: KeyTracker::new(key, key_renewal_interval),
+            nonce_tracker: NonceTracker::new(key_renewal_interval),
         }
     }
 
@@ -151,7 +354,7 @@ impl Decryptor {
         }
 
         let (nonce, nonce_buffer) = Self::convert_nonce_from_small(&payload[..8])?;
-        let nonce_tracker = self.nonce_tracker.mark(nonce)?;
+        self.nonce_tracker.check(nonce)?;
 
         // get the key corresponding to the current nonce and
         // rekey if necessary
@@ -169,7 +372,7 @@ impl Decryptor {
             .await;
 
         if result.is_ok() {
-            self.nonce_tracker = nonce_tracker;
+            self.nonce_tracker.mark(nonce);
             if let Some(key_to_delete) = self.key_tracker.update_key(key)? {
                 self.vault.delete_aead_secret_key(key_to_delete).await?;
             }
@@ -4,19 +4,23 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_vault::{AeadSecretKeyHandle, VaultForSecureChannels};
 
+use crate::models::TimestampInSeconds;
+use crate::secure_channel::key_renewal::KeyRenewal;
+use crate::utils::now;
 use crate::IdentityError;
 
+// To simplify the implementation the other end of the channel uses the same interval for
+// the size of the message window it accepts and for the message period used to rekey.
+// This means it only needs to keep the current key and the previous one.
 pub(crate) struct Encryptor {
     key: AeadSecretKeyHandle,
     nonce: u64,
     vault: Arc<dyn VaultForSecureChannels>,
+    key_renewal: KeyRenewal,
+    /// Creation time of the current key, when the age of the keys is limited
+    key_created_at: Option<TimestampInSeconds>,
 }
 
-// To simplify the implementation we use the same constant for the size of the message
-// window we accept with the message period used to rekey.
-// This means we only need to keep the current key and the previous one.
-pub(crate) const KEY_RENEWAL_INTERVAL: u64 = 32;
-
 impl Encryptor {
     /// We use u64 nonce since it's convenient to work with it (e.g. increment)
     /// But we use 8-byte be format to send it over to the other side (according to noise spec)
@@ -47,17 +51,32 @@ impl Encryptor {
     }
 
     pub async fn encrypt(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
-        let current_nonce = self.nonce;
+        let interval = self.key_renewal.interval;
+        let mut current_nonce = self.nonce;
+        let mut rekey = current_nonce > 0 && current_nonce % interval == 0;
+
+        // When the current key is too old, skip the remaining nonces of its interval,
+        // so that both ends of the channel move to the next key
+        if !rekey && self.is_key_too_old()? {
+            current_nonce = (current_nonce / interval + 1)
+                .checked_mul(interval)
+                .ok_or(IdentityError::NonceOverflow)?;
+            rekey = true;
+        }
+
         if current_nonce == u64::MAX {
             return Err(IdentityError::NonceOverflow.into());
         }
 
-        self.nonce += 1;
+        self.nonce = current_nonce + 1;
 
-        if current_nonce > 0 && current_nonce % KEY_RENEWAL_INTERVAL == 0 {
+        if rekey {
             let new_key = Self::rekey(&self.vault, &self.key).await?;
             let old_key = core::mem::replace(&mut self.key, new_key);
             self.vault.delete_aead_secret_key(old_key).await?;
+            if self.key_renewal.max_key_age.is_some() {
+                self.key_created_at = Some(now()?);
+            }
         }
 
         let (small_nonce, nonce) = Self::convert_nonce_from_u64(current_nonce);
@@ -74,12 +93,35 @@ impl Encryptor {
         Ok(res)
     }
 
+    /// Return true if the current key is older than the maximum age of the keys, if there is one
+    fn is_key_too_old(&mut self) -> Result<bool> {
+        let max_key_age = match self.key_renewal.max_key_age {
+            Some(max_key_age) => max_key_age,
+            None => return Ok(false),
+        };
+        let now = now()?;
+        let key_created_at = *self.key_created_at.get_or_insert(now);
+        Ok(now.saturating_sub(key_created_at.0) >= max_key_age.as_secs())
+    }
+
     pub fn new(
         key: AeadSecretKeyHandle,
         nonce: u64,
         vault: Arc<dyn VaultForSecureChannels>,
+        key_renewal: KeyRenewal,
     ) -> Self {
-        Self { key, nonce, vault }
+        let key_created_at = if key_renewal.max_key_age.is_some() {
+            now().ok()
+        } else {
+            None
+        };
+        Self {
+            key,
+            nonce,
+            vault,
+            key_renewal,
+            key_created_at,
+        }
     }
 
     pub(crate) async fn shutdown(&self) -> Result<()> {
@@ -1,13 +1,31 @@
+use core::time::Duration;
 use ockam_core::compat::boxed::Box;
-use ockam_core::{async_trait, Decodable, Encodable, Route};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, Address, ChannelClosedLocalInfo, Decodable, Encodable, Route};
 use ockam_core::{Any, Result, Routed, TransportMessage, Worker};
-use ockam_node::Context;
-use tracing::debug;
+use ockam_node::{Context, DelayedEvent};
+use tracing::{debug, info, warn};
 
+use crate::models::{CredentialData, Identifier, TimestampInSeconds};
 use crate::secure_channel::addresses::Addresses;
 use crate::secure_channel::api::{EncryptionRequest, EncryptionResponse};
 use crate::secure_channel::encryptor::Encryptor;
-use crate::IdentityError;
+use crate::secure_channel::message::{
+    CloseMessage, RefreshCredentialsMessage, SecureChannelMessage,
+};
+use crate::secure_channel::options::disclose_attributes;
+use crate::secure_channel::SecureChannelSharedState;
+use crate::utils::now;
+use crate::{IdentityError, TrustContext};
+
+/// Maximum time before the expiration of our credentials, at which renewed
+/// credentials are presented to the other end of the channel.
+/// An authority caches a credential until the last minute of its validity, so there
+/// is no point in asking for a renewed credential earlier than this
+const CREDENTIALS_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Delay before trying again to refresh our credentials, when no renewed credential could be retrieved
+const CREDENTIALS_REFRESH_RETRY_DELAY: Duration = Duration::from_secs(10);
 
 pub(crate) struct EncryptorWorker {
     //for debug purposes only
@@ -15,6 +33,59 @@ pub(crate) struct EncryptorWorker {
     addresses: Addresses,
     remote_route: Route,
     encryptor: Encryptor,
+    credentials_refresh: Option<CredentialsRefresh>,
+    /// True if the other end of the channel accepts secure channel messages.
+    /// Otherwise payloads are sent as plain transport messages
+    secure_channel_messages: bool,
+    shared_state: SecureChannelSharedState,
+}
+
+/// Data used to present renewed credentials to the other end of the channel
+/// before the credentials presented during the handshake expire
+pub(crate) struct CredentialsRefresh {
+    identifier: Identifier,
+    trust_context: TrustContext,
+    /// Validity of the last credentials presented to the other end of the channel
+    created_at: TimestampInSeconds,
+    expires_at: TimestampInSeconds,
+    /// Committed attributes of the renewed credentials presented to the other end, all by default
+    disclosed_attributes: Option<Vec<Vec<u8>>>,
+    timer: DelayedEvent<Vec<u8>>,
+}
+
+impl CredentialsRefresh {
+    pub(crate) fn new(
+        identifier: Identifier,
+        trust_context: TrustContext,
+        credential_data: &CredentialData,
+        disclosed_attributes: Option<Vec<Vec<u8>>>,
+        timer: DelayedEvent<Vec<u8>>,
+    ) -> Self {
+        Self {
+            identifier,
+            trust_context,
+            created_at: credential_data.created_at,
+            expires_at: credential_data.expires_at,
+            disclosed_attributes,
+            timer,
+        }
+    }
+
+    /// Address of the timer triggering the refresh of the credentials
+    pub(crate) fn timer_address(&self) -> Address {
+        self.timer.address()
+    }
+
+    /// Schedule the next refresh, at the latest one margin before the expiration of the
+    /// current credentials. The margin is reduced to a fifth of the credentials validity
+    /// for short-lived credentials
+    async fn schedule(&mut self) -> Result<()> {
+        let validity = self.expires_at.0.saturating_sub(self.created_at.0);
+        let margin = CREDENTIALS_REFRESH_MARGIN.as_secs().min(validity / 5);
+        let refresh_at = self.expires_at.0.saturating_sub(margin);
+        let delay = Duration::from_secs(refresh_at.saturating_sub(now()?.0));
+        self.timer.schedule(delay).await
+    }
 }
 
 impl EncryptorWorker {
@@ -23,13 +94,112 @@ impl EncryptorWorker {
         addresses: Addresses,
         remote_route: Route,
         encryptor: Encryptor,
+        credentials_refresh: Option<CredentialsRefresh>,
+        secure_channel_messages: bool,
+        shared_state: SecureChannelSharedState,
     ) -> Self {
         Self {
             role,
             addresses,
             remote_route,
             encryptor,
+            credentials_refresh,
+            secure_channel_messages,
+            shared_state,
+        }
+    }
+
+    /// Encrypt a message and send it to the decryptor on the other side
+    async fn send_message(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+        message: SecureChannelMessage,
+    ) -> Result<()> {
+        self.send_encrypted(ctx, &minicbor::to_vec(message)?).await
+    }
+
+    /// Encrypt a binary payload and send it to the decryptor on the other side
+    async fn send_encrypted(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+        payload: &[u8],
+    ) -> Result<()> {
+        let encrypted_payload = self.encryptor.encrypt(payload).await?;
+
+        ctx.send_from_address(
+            self.remote_route.clone(),
+            encrypted_payload,
+            self.addresses.encryptor.clone(),
+        )
+        .await
+    }
+
+    /// Present a renewed credential to the other end of the channel, if the trust context
+    /// provides a credential which expires later than the current one
+    async fn handle_refresh_credentials(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+    ) -> Result<()> {
+        let (identifier, trust_context, expires_at, disclosed_attributes) =
+            match &self.credentials_refresh {
+                Some(refresh) => (
+                    refresh.identifier.clone(),
+                    refresh.trust_context.clone(),
+                    refresh.expires_at,
+                    refresh.disclosed_attributes.clone(),
+                ),
+                None => return Ok(()),
+            };
+
+        let renewed = match trust_context.get_credential(ctx, &identifier).await {
+            Some(credential) => {
+                let credential_data = credential.get_credential_data()?;
+                if credential_data.expires_at > expires_at {
+                    Some((credential, credential_data))
+                } else {
+                    None
+                }
+            }
+            None => None,
+        };
+
+        match renewed {
+            Some((credential, credential_data)) => {
+                self.send_message(
+                    ctx,
+                    SecureChannelMessage::RefreshCredentials(RefreshCredentialsMessage {
+                        credentials: vec![disclose_attributes(
+                            credential,
+                            disclosed_attributes.as_deref(),
+                        )],
+                    }),
+                )
+                .await?;
+                info!(
+                    "SecureChannel {} presented a renewed credential at {}",
+                    self.role, &self.addresses.encryptor
+                );
+                if let Some(refresh) = self.credentials_refresh.as_mut() {
+                    refresh.created_at = credential_data.created_at;
+                    refresh.expires_at = credential_data.expires_at;
+                    refresh.schedule().await?;
+                }
+            }
+            None => {
+                warn!(
+                    "SecureChannel {} could not retrieve a renewed credential at {}",
+                    self.role, &self.addresses.encryptor
+                );
+                if let Some(refresh) = self.credentials_refresh.as_mut() {
+                    refresh
+                        .timer
+                        .schedule(CREDENTIALS_REFRESH_RETRY_DELAY)
+                        .await?;
+                }
+            }
         }
+
+        Ok(())
     }
 
     async fn handle_encrypt_api(
@@ -78,24 +248,36 @@ impl EncryptorWorker {
         // Remove our address
         let _ = onward_route.step();
 
+        // The sender stopped and doesn't need to be notified anymore.
+        // There is nothing to send to the other side
+        if ChannelClosedLocalInfo::is_unsubscription(msg.local_message()) {
+            if let Ok(sender) = return_route.next() {
+                self.shared_state.remove_closing_subscriber(sender);
+            }
+            return Ok(());
+        }
+
+        // The sender wants to be notified when the channel is closed
+        if ChannelClosedLocalInfo::is_subscription(msg.local_message()) {
+            if let Ok(sender) = return_route.next() {
+                self.shared_state.add_closing_subscriber(sender);
+            }
+        }
+
         let msg = TransportMessage::v1(
             onward_route,
             return_route,
             msg.into_transport_message().payload,
         );
 
-        // Encrypt the message
-        let encrypted_payload = self.encryptor.encrypt(&msg.encode()?).await?;
-
-        // Send the message to the decryptor on the other side
-        ctx.send_from_address(
-            self.remote_route.clone(),
-            encrypted_payload,
-            self.addresses.encryptor.clone(),
-        )
-        .await?;
-
-        Ok(())
+        // Encrypt the message and send it to the decryptor on the other side.
+        // Older implementations only accept the transport message itself
+        if self.secure_channel_messages {
+            self.send_message(ctx, SecureChannelMessage::Payload(msg.encode()?))
+                .await
+        } else {
+            self.send_encrypted(ctx, &msg.encode()?).await
+        }
     }
 }
 
@@ -104,6 +286,13 @@ impl Worker for EncryptorWorker {
     type Message = Any;
     type Context = Context;
 
+    async fn initialize(&mut self, _context: &mut Self::Context) -> Result<()> {
+        if let Some(refresh) = self.credentials_refresh.as_mut() {
+            refresh.schedule().await?;
+        }
+        Ok(())
+    }
+
     async fn handle_message(
         &mut self,
         ctx: &mut Self::Context,
@@ -115,6 +304,8 @@ impl Worker for EncryptorWorker {
             self.handle_encrypt(ctx, msg).await?;
         } else if msg_addr == self.addresses.encryptor_api {
             self.handle_encrypt_api(ctx, msg).await?;
+        } else if msg_addr == self.addresses.encryptor_internal {
+            self.handle_refresh_credentials(ctx).await?;
         } else {
             return Err(IdentityError::UnknownChannelMsgDestination.into());
         }
@@ -123,6 +314,31 @@ impl Worker for EncryptorWorker {
     }
 
     async fn shutdown(&mut self, context: &mut Self::Context) -> Result<()> {
+        // Notify the other end of the channel, unless it closed the channel itself
+        // or it doesn't accept secure channel messages
+        let reason = self
+            .shared_state
+            .close_reason()
+            .filter(|_| self.secure_channel_messages);
+        if let Some(reason) = reason {
+            match self
+                .send_message(
+                    context,
+                    SecureChannelMessage::Close(CloseMessage { reason }),
+                )
+                .await
+            {
+                Ok(()) => debug!(
+                    "SecureChannel {} at {} notified the other end about its closing: {}",
+                    self.role, &self.addresses.encryptor, reason
+                ),
+                Err(e) => debug!(
+                    "SecureChannel {} at {} could not notify the other end about its closing: {}",
+                    self.role, &self.addresses.encryptor, e
+                ),
+            }
+        }
+
         let _ = context
             .stop_worker(self.addresses.decryptor_internal.clone())
             .await;
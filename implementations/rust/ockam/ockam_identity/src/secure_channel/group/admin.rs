@@ -0,0 +1,180 @@
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Result, Route};
+use ockam_node::compat::asynchronous::Mutex;
+use ockam_node::{Context, MessageSendReceiveOptions};
+use ockam_vault::VerifyingPublicKey;
+use tracing::{info, warn};
+use zeroize::Zeroize;
+
+use crate::models::Identifier;
+use crate::secure_channel::group::message::{
+    GroupIdentifier, GroupKey, GroupMemberKey, GroupMessage, JoinRequest,
+};
+use crate::{IdentityError, IdentitySecureChannelLocalInfo};
+
+/// Length of the secret shared by the members of a group for one epoch
+const GROUP_SECRET_LEN: usize = 32;
+
+/// Admin of a group of identities exchanging messages encrypted once for the whole group.
+///
+/// The admin communicates with each member over a pairwise secure channel: it adds members to
+/// the group, removes them, and sends a new group key to all the members every time the members
+/// of the group change, so that a removed member cannot decrypt the next messages and a new
+/// member cannot decrypt the previous ones
+#[derive(Clone)]
+pub struct GroupAdmin {
+    identifier: GroupIdentifier,
+    state: Arc<Mutex<GroupAdminState>>,
+}
+
+#[derive(Default)]
+struct GroupAdminState {
+    epoch: u64,
+    members: BTreeMap<Identifier, GroupAdminMember>,
+}
+
+struct GroupAdminMember {
+    /// Route to the group member worker, over a secure channel
+    route: Route,
+    verifying_key: VerifyingPublicKey,
+}
+
+impl GroupAdmin {
+    /// Create a new group without members
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            identifier: GroupIdentifier::generate(),
+            state: Default::default(),
+        }
+    }
+
+    /// Identifier of the group
+    pub fn identifier(&self) -> &GroupIdentifier {
+        &self.identifier
+    }
+
+    /// Identifiers of the members of the group
+    pub async fn members(&self) -> Vec<Identifier> {
+        self.state.lock().await.members.keys().cloned().collect()
+    }
+
+    /// Add a member to the group, then send a new group key to all the members.
+    /// The route must lead to the group member worker over a secure channel with the member.
+    /// Return the identifier of the new member, as authenticated by the secure channel
+    pub async fn add_member(&self, ctx: &Context, route: impl Into<Route>) -> Result<Identifier> {
+        let route = route.into();
+        let mut state = self.state.lock().await;
+
+        let request = GroupMessage::Join(JoinRequest {
+            group_identifier: self.identifier.clone(),
+        });
+        let (identifier, response) = Self::request(ctx, route.clone(), request).await?;
+        let verifying_key = match response {
+            GroupMessage::Joined(response) => response.verifying_key,
+            _ => return Err(IdentityError::InvalidGroupMessage.into()),
+        };
+        info!("{} joined the group {}", identifier, self.identifier);
+        state.members.insert(
+            identifier.clone(),
+            GroupAdminMember {
+                route,
+                verifying_key,
+            },
+        );
+
+        self.send_new_key(ctx, &mut state).await?;
+        Ok(identifier)
+    }
+
+    /// Remove a member from the group, then send a new group key to the remaining members.
+    /// The removed member is notified so that it deletes the group keys
+    pub async fn remove_member(&self, ctx: &Context, identifier: &Identifier) -> Result<()> {
+        let mut state = self.state.lock().await;
+        let member = state
+            .members
+            .remove(identifier)
+            .ok_or(IdentityError::UnknownGroupMember)?;
+
+        let message = GroupMessage::Removed(self.identifier.clone());
+        if let Err(e) = ctx.send(member.route, minicbor::to_vec(message)?).await {
+            warn!(
+                "{} could not be notified of its removal from the group {}: {}",
+                identifier, self.identifier, e
+            );
+        }
+        info!(
+            "{} was removed from the group {}",
+            identifier, self.identifier
+        );
+
+        self.send_new_key(ctx, &mut state).await
+    }
+
+    /// Send a new group key to all the members
+    pub async fn rekey(&self, ctx: &Context) -> Result<()> {
+        let mut state = self.state.lock().await;
+        self.send_new_key(ctx, &mut state).await
+    }
+
+    /// Start a new epoch with a new random secret, and wait until all the members received it
+    async fn send_new_key(&self, ctx: &Context, state: &mut GroupAdminState) -> Result<()> {
+        state.epoch += 1;
+        let mut secret = vec![0u8; GROUP_SECRET_LEN];
+        thread_rng().fill_bytes(&mut secret);
+        let members: Vec<GroupMemberKey> = state
+            .members
+            .iter()
+            .map(|(identifier, member)| GroupMemberKey {
+                identifier: identifier.clone(),
+                verifying_key: member.verifying_key.clone(),
+            })
+            .collect();
+
+        let mut result = Ok(());
+        for (identifier, member) in state.members.iter() {
+            let key = GroupMessage::Key(GroupKey {
+                group_identifier: self.identifier.clone(),
+                epoch: state.epoch,
+                secret: secret.clone(),
+                members: members.clone(),
+            });
+            match Self::request(ctx, member.route.clone(), key).await {
+                Ok((their_identifier, GroupMessage::KeyReceived(received)))
+                    if &their_identifier == identifier && received.epoch == state.epoch => {}
+                Ok(_) => result = Err(IdentityError::InvalidGroupMessage.into()),
+                Err(e) => {
+                    warn!(
+                        "{} did not receive the key of epoch {} for the group {}: {}",
+                        identifier, state.epoch, self.identifier, e
+                    );
+                    result = Err(e)
+                }
+            }
+        }
+        secret.zeroize();
+        result
+    }
+
+    /// Send a message to a member over a secure channel and return its response, with the
+    /// identifier of the member
+    async fn request(
+        ctx: &Context,
+        route: Route,
+        message: GroupMessage,
+    ) -> Result<(Identifier, GroupMessage)> {
+        let response = ctx
+            .send_and_receive_extended::<Vec<u8>>(
+                route,
+                minicbor::to_vec(message)?,
+                MessageSendReceiveOptions::new(),
+            )
+            .await?;
+        let identifier = IdentitySecureChannelLocalInfo::find_info(response.local_message())?
+            .their_identity_id();
+        Ok((identifier, minicbor::decode(&response.body())?))
+    }
+}
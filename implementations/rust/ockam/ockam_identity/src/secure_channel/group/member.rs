@@ -0,0 +1,529 @@
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{
+    async_trait, Address, AllowAll, Any, Decodable, DenyAll, LocalMessage, Mailbox, Mailboxes,
+    Result, Route, Routed, TransportMessage, Worker,
+};
+use ockam_node::{Context, WorkerBuilder};
+use ockam_vault::{
+    AeadSecretKeyHandle, HKDFNumberOfOutputs, SecretBufferHandle, SigningKeyType,
+    SigningSecretKeyHandle, VaultForSecureChannels, VerifyingPublicKey,
+};
+use tracing::{debug, info, warn};
+
+use crate::models::Identifier;
+use crate::secure_channel::encryptor::Encryptor;
+use crate::secure_channel::group::message::{
+    EncryptedGroupMessage, EncryptedGroupMessageData, GroupIdentifier, GroupKey, GroupMemberKey,
+    GroupMessage, JoinRequest, JoinResponse, KeyReceived,
+};
+use crate::secure_channel::group::GroupMemberOptions;
+use crate::secure_channel::nonce_tracker::NonceTracker;
+use crate::{
+    GroupMember, IdentityError, IdentitySecureChannelLocalInfo, SecureChannelTrustInfo,
+    TrustPolicy, Vault,
+};
+
+/// Number of messages of a member which can be received out of order
+const GROUP_NONCE_WINDOW: u64 = 128;
+
+/// Addresses of a group member
+#[derive(Clone, Debug)]
+pub(crate) struct GroupMemberAddresses {
+    /// Used to receive the messages of the admin and the encrypted messages of the other members
+    pub(crate) remote: Address,
+    /// Used to send decrypted messages
+    pub(crate) internal: Address,
+    /// Used to receive plain messages that will be encrypted with the group key and forwarded
+    pub(crate) encryptor: Address,
+}
+
+impl GroupMemberAddresses {
+    fn new(remote: Address) -> Self {
+        Self {
+            remote,
+            internal: Address::random_tagged("GroupMember.internal"),
+            encryptor: Address::random_tagged("GroupMember.encryptor"),
+        }
+    }
+}
+
+/// Worker of a group member.
+///
+/// The admin of the group adds the member with a message sent over a secure channel, then
+/// sends it a new group key every time the members of the group change.
+///
+/// Plain messages routed through the encryptor address are encrypted once with the key of this
+/// member for the current epoch, signed, and forwarded to the rest of their onward route.
+/// The routes are not encrypted, so that the same message can be delivered to all the
+/// members of the group, for example by a relay.
+/// Encrypted messages received on the remote address are verified, decrypted, and forwarded
+/// to the rest of their onward route with an [`IdentitySecureChannelLocalInfo`] identifying
+/// the member who sent them.
+pub(crate) struct GroupMemberWorker {
+    vault: Vault,
+    identifier: Identifier,
+    addresses: GroupMemberAddresses,
+    trust_policy: Arc<dyn TrustPolicy>,
+    /// Key signing the messages sent by this member
+    signing_key: SigningSecretKeyHandle,
+    group: Option<Group>,
+}
+
+/// Group joined by this member
+struct Group {
+    identifier: GroupIdentifier,
+    admin: Identifier,
+    /// Keys of the current epoch, once received from the admin
+    current: Option<GroupEpoch>,
+    /// Keys of the previous epoch, to decrypt the messages sent before the last membership change
+    previous: Option<GroupEpoch>,
+}
+
+/// Keys of the members of the group for one epoch
+struct GroupEpoch {
+    epoch: u64,
+    /// Number of messages sent by this member during this epoch
+    counter: u64,
+    senders: BTreeMap<Identifier, GroupSender>,
+}
+
+/// Keys of one member of the group
+struct GroupSender {
+    key: AeadSecretKeyHandle,
+    verifying_key: VerifyingPublicKey,
+    nonce_tracker: NonceTracker,
+}
+
+#[ockam_core::worker]
+impl Worker for GroupMemberWorker {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        message: Routed<Self::Message>,
+    ) -> Result<()> {
+        if message.msg_addr() == self.addresses.encryptor {
+            return self.handle_encrypt(ctx, message).await;
+        }
+
+        let local_message = message.into_local_message();
+        let payload = Vec::<u8>::decode(&local_message.transport().payload)?;
+        match minicbor::decode(&payload)? {
+            GroupMessage::Join(request) => self.handle_join(ctx, local_message, request).await,
+            GroupMessage::Key(key) => self.handle_key(ctx, local_message, key).await,
+            GroupMessage::Removed(group_identifier) => {
+                self.handle_removed(local_message, group_identifier).await
+            }
+            GroupMessage::Encrypted(message) => {
+                self.handle_decrypt(ctx, local_message, message).await
+            }
+            GroupMessage::Joined(_) | GroupMessage::KeyReceived(_) => {
+                warn!(
+                    "Group member {} received an unexpected admin message",
+                    &self.addresses.remote
+                );
+                Ok(())
+            }
+        }
+    }
+
+    async fn shutdown(&mut self, _context: &mut Self::Context) -> Result<()> {
+        if let Some(group) = self.group.take() {
+            self.delete_group(group).await?;
+        }
+        self.vault
+            .credential_vault
+            .delete_signing_secret_key(self.signing_key.clone())
+            .await?;
+        Ok(())
+    }
+}
+
+impl GroupMemberWorker {
+    /// Start a group member, waiting to be added to a group by its admin
+    pub(crate) async fn create(
+        ctx: &Context,
+        vault: Vault,
+        identifier: Identifier,
+        address: Address,
+        options: GroupMemberOptions,
+    ) -> Result<GroupMember> {
+        let addresses = GroupMemberAddresses::new(address);
+        options.setup_flow_control(ctx.flow_controls(), &addresses);
+        let outgoing_access_control = options.create_access_control(ctx.flow_controls());
+
+        let signing_key = vault
+            .credential_vault
+            .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+            .await?;
+
+        let worker = Self {
+            vault,
+            identifier,
+            addresses: addresses.clone(),
+            trust_policy: options.trust_policy,
+            signing_key,
+            group: None,
+        };
+
+        let remote_mailbox = Mailbox::new(
+            addresses.remote.clone(),
+            // Messages are checked cryptographically
+            Arc::new(AllowAll),
+            // Answer the admin of the group
+            Arc::new(AllowAll),
+        );
+        let internal_mailbox = Mailbox::new(
+            addresses.internal.clone(),
+            Arc::new(DenyAll),
+            outgoing_access_control,
+        );
+        let encryptor_mailbox = Mailbox::new(
+            addresses.encryptor.clone(),
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        );
+
+        WorkerBuilder::new(worker)
+            .with_mailboxes(Mailboxes::new(
+                remote_mailbox,
+                vec![internal_mailbox, encryptor_mailbox],
+            ))
+            .start(ctx)
+            .await?;
+
+        Ok(GroupMember::new(
+            addresses.remote,
+            addresses.encryptor,
+            options.flow_control_id,
+        ))
+    }
+
+    /// Join a group when the admin is trusted. A member can only be part of one group
+    async fn handle_join(
+        &mut self,
+        ctx: &mut Context,
+        local_message: LocalMessage,
+        request: JoinRequest,
+    ) -> Result<()> {
+        let admin = IdentitySecureChannelLocalInfo::find_info(&local_message)?.their_identity_id();
+        let trust_info = SecureChannelTrustInfo::new(admin.clone());
+        if !self.trust_policy.check(&trust_info).await? {
+            warn!(
+                "Group member {} rejected the group {} of the untrusted admin {}",
+                &self.addresses.remote, request.group_identifier, admin
+            );
+            return Err(IdentityError::SecureChannelTrustCheckFailed.into());
+        }
+
+        match &self.group {
+            Some(group) if group.identifier != request.group_identifier || group.admin != admin => {
+                return Err(IdentityError::UnknownGroup.into());
+            }
+            Some(_) => {}
+            None => {
+                info!(
+                    "Group member {} joined the group {} of {}",
+                    &self.addresses.remote, request.group_identifier, admin
+                );
+                self.group = Some(Group {
+                    identifier: request.group_identifier,
+                    admin,
+                    current: None,
+                    previous: None,
+                });
+            }
+        }
+
+        let verifying_key = self
+            .vault
+            .credential_vault
+            .get_verifying_public_key(&self.signing_key)
+            .await?;
+        let response = GroupMessage::Joined(JoinResponse { verifying_key });
+        self.reply(ctx, local_message, response).await
+    }
+
+    /// Use the new key sent by the admin. The keys of the previous epoch are kept
+    /// to decrypt the messages which were sent before the change of the group members
+    async fn handle_key(
+        &mut self,
+        ctx: &mut Context,
+        local_message: LocalMessage,
+        key: GroupKey,
+    ) -> Result<()> {
+        self.check_admin(&local_message, &key.group_identifier)?;
+        let group = self.group.as_mut().ok_or(IdentityError::UnknownGroup)?;
+        if let Some(current) = &group.current {
+            if key.epoch <= current.epoch {
+                return Err(IdentityError::InvalidGroupEpoch.into());
+            }
+        }
+
+        let vault = self.vault.secure_channel_vault.clone();
+        let epoch = GroupEpoch::create(&vault, key.epoch, key.secret, key.members).await?;
+        let previous = group.previous.take();
+        group.previous = group.current.replace(epoch);
+        if let Some(previous) = previous {
+            previous.delete(&vault).await?;
+        }
+
+        debug!(
+            "Group member {} uses the key of epoch {} for the group {}",
+            &self.addresses.remote, key.epoch, key.group_identifier
+        );
+        let response = GroupMessage::KeyReceived(KeyReceived { epoch: key.epoch });
+        self.reply(ctx, local_message, response).await
+    }
+
+    /// Delete the keys of the group when this member is removed from the group
+    async fn handle_removed(
+        &mut self,
+        local_message: LocalMessage,
+        group_identifier: GroupIdentifier,
+    ) -> Result<()> {
+        self.check_admin(&local_message, &group_identifier)?;
+        if let Some(group) = self.group.take() {
+            info!(
+                "Group member {} was removed from the group {}",
+                &self.addresses.remote, group_identifier
+            );
+            self.delete_group(group).await?;
+        }
+        Ok(())
+    }
+
+    /// Encrypt a plain message and forward it to the rest of its onward route
+    async fn handle_encrypt(&mut self, ctx: &mut Context, message: Routed<Any>) -> Result<()> {
+        let mut transport_message = message.into_transport_message();
+        transport_message.onward_route.step()?;
+
+        let group = self.group.as_mut().ok_or(IdentityError::UnknownGroup)?;
+        let epoch = group.current.as_mut().ok_or(IdentityError::UnknownGroup)?;
+        let sender = epoch
+            .senders
+            .get(&self.identifier)
+            .ok_or(IdentityError::UnknownGroup)?;
+        if epoch.counter == u64::MAX {
+            return Err(IdentityError::NonceOverflow.into());
+        }
+        let counter = epoch.counter;
+        epoch.counter += 1;
+
+        let (_, nonce) = Encryptor::convert_nonce_from_u64(counter);
+        let ciphertext = self
+            .vault
+            .secure_channel_vault
+            .aead_encrypt(&sender.key, &transport_message.payload, &nonce, &[])
+            .await?;
+        let data = minicbor::to_vec(EncryptedGroupMessageData {
+            group_identifier: group.identifier.clone(),
+            epoch: epoch.epoch,
+            sender: self.identifier.clone(),
+            counter,
+            ciphertext,
+        })?;
+        let signature = self
+            .vault
+            .credential_vault
+            .sign(&self.signing_key, &data)
+            .await?;
+        let message = GroupMessage::Encrypted(EncryptedGroupMessage { data, signature });
+
+        ctx.send_from_address(
+            transport_message.onward_route,
+            minicbor::to_vec(message)?,
+            self.addresses.encryptor.clone(),
+        )
+        .await
+    }
+
+    /// Verify and decrypt a message sent by a member of the group, then forward it to the rest
+    /// of its onward route
+    async fn handle_decrypt(
+        &mut self,
+        ctx: &mut Context,
+        local_message: LocalMessage,
+        message: EncryptedGroupMessage,
+    ) -> Result<()> {
+        let data: EncryptedGroupMessageData = minicbor::decode(&message.data)?;
+        let group = self.group.as_mut().ok_or(IdentityError::UnknownGroup)?;
+        if data.group_identifier != group.identifier {
+            return Err(IdentityError::UnknownGroup.into());
+        }
+
+        // Messages of the previous epoch are only accepted from the current members
+        let is_current_member = group
+            .current
+            .as_ref()
+            .map(|current| current.senders.contains_key(&data.sender))
+            .unwrap_or(false);
+        let is_current_epoch =
+            group.current.as_ref().map(|current| current.epoch) == Some(data.epoch);
+        let epoch = [group.current.as_mut(), group.previous.as_mut()]
+            .into_iter()
+            .flatten()
+            .find(|epoch| epoch.epoch == data.epoch)
+            .ok_or(IdentityError::InvalidGroupEpoch)?;
+        if !is_current_epoch && !is_current_member {
+            return Err(IdentityError::InvalidGroupMessage.into());
+        }
+        let sender = epoch
+            .senders
+            .get_mut(&data.sender)
+            .ok_or(IdentityError::InvalidGroupMessage)?;
+
+        if !self
+            .vault
+            .verifying_vault
+            .verify_signature(&sender.verifying_key, &message.data, &message.signature)
+            .await?
+        {
+            return Err(IdentityError::InvalidGroupMessage.into());
+        }
+        sender.nonce_tracker.check(data.counter)?;
+        let (_, nonce) = Encryptor::convert_nonce_from_u64(data.counter);
+        let payload = self
+            .vault
+            .secure_channel_vault
+            .aead_decrypt(&sender.key, &data.ciphertext, &nonce, &[])
+            .await?;
+        sender.nonce_tracker.mark(data.counter);
+
+        let mut transport_message = local_message.into_transport_message();
+        transport_message.onward_route.step()?;
+        let transport_message = TransportMessage::v1(
+            transport_message.onward_route,
+            transport_message.return_route,
+            payload,
+        );
+
+        // Mark the message with the identifier of the member who sent it
+        let local_info = IdentitySecureChannelLocalInfo::mark(vec![], data.sender)?;
+        ctx.forward_from_address(
+            LocalMessage::new(transport_message, local_info),
+            self.addresses.internal.clone(),
+        )
+        .await
+    }
+
+    /// Check that a message was sent by the admin of the group, over a secure channel
+    fn check_admin(
+        &self,
+        local_message: &LocalMessage,
+        group_identifier: &GroupIdentifier,
+    ) -> Result<()> {
+        let their_identifier =
+            IdentitySecureChannelLocalInfo::find_info(local_message)?.their_identity_id();
+        match &self.group {
+            Some(group)
+                if &group.identifier == group_identifier && group.admin == their_identifier =>
+            {
+                Ok(())
+            }
+            _ => Err(IdentityError::UnknownGroup.into()),
+        }
+    }
+
+    /// Send a response to the admin
+    async fn reply(
+        &self,
+        ctx: &Context,
+        local_message: LocalMessage,
+        response: GroupMessage,
+    ) -> Result<()> {
+        let return_route: Route = local_message.into_transport_message().return_route;
+        ctx.send_from_address(
+            return_route,
+            minicbor::to_vec(response)?,
+            self.addresses.remote.clone(),
+        )
+        .await
+    }
+
+    async fn delete_group(&self, group: Group) -> Result<()> {
+        let vault = &self.vault.secure_channel_vault;
+        for epoch in [group.current, group.previous].into_iter().flatten() {
+            epoch.delete(vault).await?;
+        }
+        Ok(())
+    }
+}
+
+impl GroupEpoch {
+    /// Derive the keys of all the members from the secret of the epoch
+    async fn create(
+        vault: &Arc<dyn VaultForSecureChannels>,
+        epoch: u64,
+        secret: Vec<u8>,
+        members: Vec<GroupMemberKey>,
+    ) -> Result<Self> {
+        let secret = vault.import_secret_buffer(secret).await?;
+        let mut senders = BTreeMap::new();
+        let mut result = Ok(());
+        for member in members {
+            match Self::derive_sender_key(vault, &secret, &member.identifier).await {
+                Ok(key) => {
+                    let sender = GroupSender {
+                        key,
+                        verifying_key: member.verifying_key,
+                        nonce_tracker: NonceTracker::new(GROUP_NONCE_WINDOW),
+                    };
+                    senders.insert(member.identifier, sender);
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        vault.delete_secret_buffer(secret).await?;
+
+        let epoch = Self {
+            epoch,
+            counter: 0,
+            senders,
+        };
+        match result {
+            Ok(()) => Ok(epoch),
+            Err(e) => {
+                epoch.delete(vault).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// k = HKDF(salt = identifier of the sender, ikm = secret of the epoch).
+    /// Each member encrypts with its own key, so that the nonces of different members never collide
+    async fn derive_sender_key(
+        vault: &Arc<dyn VaultForSecureChannels>,
+        secret: &SecretBufferHandle,
+        sender: &Identifier,
+    ) -> Result<AeadSecretKeyHandle> {
+        let salt = vault.import_secret_buffer(sender.0.to_vec()).await?;
+        let hkdf_output = vault
+            .hkdf(&salt, Some(secret), HKDFNumberOfOutputs::Two)
+            .await;
+        vault.delete_secret_buffer(salt).await?;
+
+        let [key, unused]: [SecretBufferHandle; 2] = hkdf_output?
+            .0
+             .0
+            .try_into()
+            .map_err(|_| IdentityError::ConsistencyError)?;
+        vault.delete_secret_buffer(unused).await?;
+        vault.convert_secret_buffer_to_aead_key(key).await
+    }
+
+    async fn delete(self, vault: &Arc<dyn VaultForSecureChannels>) -> Result<()> {
+        for (_, sender) in self.senders {
+            vault.delete_aead_secret_key(sender.key).await?;
+        }
+        Ok(())
+    }
+}
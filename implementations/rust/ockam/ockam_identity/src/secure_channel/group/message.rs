@@ -0,0 +1,114 @@
+use core::fmt;
+use minicbor::{Decode, Encode};
+use ockam_core::compat::rand::random;
+use ockam_core::compat::vec::Vec;
+use ockam_vault::{Signature, VerifyingPublicKey};
+
+use crate::models::Identifier;
+
+/// Length of a group identifier
+const GROUP_IDENTIFIER_LEN: usize = 16;
+
+/// Random identifier of a group, created by the group admin
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[cbor(transparent)]
+pub struct GroupIdentifier(#[cbor(n(0), with = "minicbor::bytes")] pub [u8; GROUP_IDENTIFIER_LEN]);
+
+impl GroupIdentifier {
+    pub(crate) fn generate() -> Self {
+        Self(random())
+    }
+}
+
+impl fmt::Display for GroupIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+/// Messages received by a group member.
+///
+/// The admin messages are sent over a secure channel between the admin and each member.
+/// The encrypted messages are sent by the members of the group, over any route
+#[derive(Encode, Decode)]
+#[rustfmt::skip]
+pub(crate) enum GroupMessage {
+    /// Sent by the admin to add a member to the group
+    #[n(0)] Join(#[n(0)] JoinRequest),
+    /// Sent by a member to accept to join a group
+    #[n(1)] Joined(#[n(0)] JoinResponse),
+    /// Sent by the admin to each member every time the members of the group change
+    #[n(2)] Key(#[n(0)] GroupKey),
+    /// Sent by a member once it uses a new group key
+    #[n(3)] KeyReceived(#[n(0)] KeyReceived),
+    /// Sent by the admin to a member removed from the group
+    #[n(4)] Removed(#[n(0)] GroupIdentifier),
+    /// Message encrypted with the group key by a member of the group
+    #[n(5)] Encrypted(#[n(0)] EncryptedGroupMessage),
+}
+
+#[derive(Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct JoinRequest {
+    #[n(1)] pub(crate) group_identifier: GroupIdentifier,
+}
+
+#[derive(Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct JoinResponse {
+    /// Public key used to verify the messages sent by the new member
+    #[n(1)] pub(crate) verifying_key: VerifyingPublicKey,
+}
+
+/// Secret shared by the members of the group for a given epoch.
+/// A new epoch starts every time a member joins or leaves the group
+#[derive(Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct GroupKey {
+    #[n(1)] pub(crate) group_identifier: GroupIdentifier,
+    #[n(2)] pub(crate) epoch: u64,
+    #[cbor(n(3), with = "minicbor::bytes")] pub(crate) secret: Vec<u8>,
+    #[n(4)] pub(crate) members: Vec<GroupMemberKey>,
+}
+
+/// Member of the group for a given epoch, with the key verifying its messages
+#[derive(Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct GroupMemberKey {
+    #[n(1)] pub(crate) identifier: Identifier,
+    #[n(2)] pub(crate) verifying_key: VerifyingPublicKey,
+}
+
+#[derive(Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct KeyReceived {
+    #[n(1)] pub(crate) epoch: u64,
+}
+
+/// Message encrypted by a member, with a signature over the encoded data
+#[derive(Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct EncryptedGroupMessage {
+    /// CBOR binary of [`EncryptedGroupMessageData`]
+    #[cbor(n(1), with = "minicbor::bytes")] pub(crate) data: Vec<u8>,
+    /// Signature of the data by the sender
+    #[n(2)] pub(crate) signature: Signature,
+}
+
+#[derive(Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct EncryptedGroupMessageData {
+    #[n(1)] pub(crate) group_identifier: GroupIdentifier,
+    #[n(2)] pub(crate) epoch: u64,
+    #[n(3)] pub(crate) sender: Identifier,
+    /// Counter of the messages sent by the sender during this epoch, used as a nonce
+    #[n(4)] pub(crate) counter: u64,
+    #[cbor(n(5), with = "minicbor::bytes")] pub(crate) ciphertext: Vec<u8>,
+}
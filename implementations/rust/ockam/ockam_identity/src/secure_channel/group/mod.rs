@@ -0,0 +1,9 @@
+mod admin;
+mod member;
+mod message;
+mod options;
+
+pub use admin::*;
+pub(crate) use member::*;
+pub use message::GroupIdentifier;
+pub use options::*;
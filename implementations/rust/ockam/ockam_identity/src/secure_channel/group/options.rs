@@ -0,0 +1,85 @@
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
+use ockam_core::OutgoingAccessControl;
+
+use crate::secure_channel::group::GroupMemberAddresses;
+use crate::{TrustEveryonePolicy, TrustPolicy};
+
+use core::fmt;
+use core::fmt::Formatter;
+
+/// Options for a member of a group
+pub struct GroupMemberOptions {
+    pub(crate) consumer: Vec<FlowControlId>,
+    pub(crate) flow_control_id: FlowControlId,
+    pub(crate) trust_policy: Arc<dyn TrustPolicy>,
+}
+
+impl fmt::Debug for GroupMemberOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "FlowId: {}", self.flow_control_id)
+    }
+}
+
+impl GroupMemberOptions {
+    /// Mark the group member as a Producer with a random [`FlowControlId`] for the
+    /// messages decrypted with the group key
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            consumer: vec![],
+            flow_control_id: FlowControls::generate_flow_control_id(),
+            trust_policy: Arc::new(TrustEveryonePolicy),
+        }
+    }
+
+    /// Mark that this group member is a Consumer for the given [`FlowControlId`].
+    /// This is necessary to receive the messages of the group admin from a secure channel,
+    /// and the messages of the other members from a transport
+    pub fn as_consumer(mut self, id: &FlowControlId) -> Self {
+        self.consumer.push(id.clone());
+        self
+    }
+
+    /// Sets the trust policy checked for the admin of a group, when it adds this member to the group
+    pub fn with_trust_policy(mut self, trust_policy: impl TrustPolicy) -> Self {
+        self.trust_policy = Arc::new(trust_policy);
+        self
+    }
+
+    /// Freshly generated [`FlowControlId`]
+    pub fn producer_flow_control_id(&self) -> FlowControlId {
+        self.flow_control_id.clone()
+    }
+}
+
+impl GroupMemberOptions {
+    pub(crate) fn setup_flow_control(
+        &self,
+        flow_controls: &FlowControls,
+        addresses: &GroupMemberAddresses,
+    ) {
+        for id in &self.consumer {
+            flow_controls.add_consumer(addresses.remote.clone(), id);
+        }
+
+        flow_controls.add_producer(
+            addresses.internal.clone(),
+            &self.flow_control_id,
+            None,
+            vec![],
+        );
+    }
+
+    pub(crate) fn create_access_control(
+        &self,
+        flow_controls: &FlowControls,
+    ) -> Arc<dyn OutgoingAccessControl> {
+        Arc::new(FlowControlOutgoingAccessControl::new(
+            flow_controls,
+            self.flow_control_id.clone(),
+            None,
+        ))
+    }
+}
@@ -4,20 +4,28 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_vault::{
-    AeadSecretKeyHandle, HKDFNumberOfOutputs, SecretBufferHandle, VaultForSecureChannels,
-    X25519PublicKey, X25519SecretKeyHandle, X25519_PUBLIC_KEY_LENGTH,
+    AeadSecretKeyHandle, HKDFNumberOfOutputs, KemCiphertext, KemPublicKey, KemSecretKeyHandle,
+    SecretBufferHandle, VaultForSecureChannels, X25519PublicKey, X25519SecretKeyHandle,
+    X25519_PUBLIC_KEY_LENGTH,
 };
 use sha2::{Digest, Sha256};
 use Status::*;
 
 use crate::secure_channel::handshake::error::XXError;
-use crate::secure_channel::handshake::handshake_state_machine::{HandshakeKeys, Status};
+use crate::secure_channel::handshake::handshake_state_machine::{
+    HandshakeKeys, ResumptionSecret, Status,
+};
 use crate::secure_channel::Role;
 
 /// The number of bytes in a SHA256 digest
 pub const SHA256_SIZE: usize = 32;
 /// The number of bytes in AES-GCM tag
 pub const AES_GCM_TAGSIZE: usize = 16;
+/// The number of bytes of the responder message when a channel is resumed.
+/// A message 2 of the XX handshake is always longer
+pub const RESUMPTION_RESPONSE_LEN: usize = X25519_PUBLIC_KEY_LENGTH + AES_GCM_TAGSIZE;
+/// Label used to derive the identifier of a resumption ticket from the handshake hash
+const RESUMPTION_TICKET_LABEL: &[u8] = b"OCKAM_RESUMPTION_TICKET";
 
 /// Implementation of a Handshake for the noise protocol
 /// The first members are used in the implementation of some of the protocol steps, for example to
@@ -189,19 +197,34 @@ impl Handshake {
     }
 
     /// Set the final state of the state machine by creating the encryption / decryption keys
-    /// and return the other party identity
-    pub(super) async fn set_final_state(&mut self, role: Role) -> Result<()> {
+    /// and, if the resumption of the channel is enabled, the secret of the next resumption
+    pub(super) async fn set_final_state(
+        &mut self,
+        role: Role,
+        with_resumption: bool,
+    ) -> Result<()> {
         // k1, k2 = HKDF(ck, zerolen, 2)
+        // or k1, k2, resumption secret = HKDF(ck, zerolen, 3)
         let mut state = self.state.clone();
-        let (k1, k2) = self.compute_final_keys(&mut state).await?;
+        let (k1, k2, resumption_secret) =
+            self.compute_final_keys(&mut state, with_resumption).await?;
         let (encryption_key, decryption_key) = if role.is_initiator() {
             (k2, k1)
         } else {
             (k1, k2)
         };
+        let resumption_secret = resumption_secret.map(|secret| {
+            let mut label = RESUMPTION_TICKET_LABEL.to_vec();
+            label.extend_from_slice(&state.h);
+            ResumptionSecret {
+                ticket_id: HandshakeState::sha256(&label).to_vec(),
+                secret,
+            }
+        });
         state.status = Ready(HandshakeKeys {
             encryption_key,
             decryption_key,
+            resumption_secret,
         });
         // now remove the ephemeral keys which are not useful anymore
         self.state = state;
@@ -216,6 +239,195 @@ impl Handshake {
             _ => None,
         }
     }
+
+    /// Remove the secrets of a handshake which will not be completed, except for the
+    /// ephemeral key which is shared with the handshake used instead
+    pub(super) async fn discard(&mut self) -> Result<()> {
+        if let Some(ck) = self.state.ck.take() {
+            self.vault.delete_secret_buffer(ck).await?;
+        }
+        if let Some(k) = self.state.k.take() {
+            self.vault.delete_aead_secret_key(k).await?;
+        }
+        self.state.e = None;
+        Ok(())
+    }
+}
+
+/// Functions used to resume a channel with the Noise NNpsk0 pattern:
+///
+///   -> psk, e
+///   <- e, ee
+///
+/// The pre-shared key is the secret of a resumption ticket, created at the end of the
+/// previous handshake between the same identities. The ephemeral key of the initiator is
+/// sent in the message 1 of the XX handshake, so that the responder can complete a full
+/// handshake instead when it cannot resume the channel
+impl Handshake {
+    /// Create a handshake to resume a channel, sharing the keys of this XX handshake
+    pub(super) fn new_resumption(&self) -> Result<Handshake> {
+        Ok(Handshake {
+            vault: self.vault.clone(),
+            protocol_name: *RESUMPTION_PROTOCOL_NAME,
+            state: HandshakeState::new(self.state.s()?.clone(), self.state.e()?.clone()),
+        })
+    }
+
+    /// Initialize the handshake variables with a prologue binding the handshake to the ticket
+    /// and to both identities, then mix the pre-shared key. The pre-shared key is kept, so that
+    /// a ticket is only deleted by its owner once the resumption request has been verified
+    pub(super) async fn initialize_resumption(
+        &mut self,
+        prologue: &[u8],
+        psk: &SecretBufferHandle,
+    ) -> Result<()> {
+        let mut state = self.state.clone();
+        state.h = self.protocol_name();
+        state.k = None;
+        state.ck = Some(self.import_ck_secret(self.protocol_name().to_vec()).await?);
+        state.mix_hash(prologue);
+
+        // ck, k = HKDF(ck, psk, 2)
+        self.mix_key(&mut state, psk).await?;
+        self.state = state;
+        Ok(())
+    }
+
+    /// Return a tag proving that the initiator knows the pre-shared key
+    pub(super) async fn encode_resumption_request(&mut self) -> Result<Vec<u8>> {
+        let mut state = self.state.clone();
+        // e.pubKey is sent in the message 1 of the XX handshake
+        let e_pub_key = self.get_public_key(state.e()?).await?;
+        state.mix_hash(&e_pub_key.0);
+        self.mix_key(&mut state, &e_pub_key.0).await?;
+
+        let tag = self.encrypt_and_hash(&mut state, &[]).await?;
+        self.state = state;
+        Ok(tag)
+    }
+
+    /// Check that the initiator knows the pre-shared key
+    pub(super) async fn decode_resumption_request(
+        &mut self,
+        re_pub_key: X25519PublicKey,
+        tag: &[u8],
+    ) -> Result<()> {
+        let mut state = self.state.clone();
+        state.mix_hash(&re_pub_key.0);
+        self.mix_key(&mut state, &re_pub_key.0).await?;
+        state.re = Some(re_pub_key);
+
+        self.hash_and_decrypt(&mut state, tag).await?;
+        self.state = state;
+        Ok(())
+    }
+
+    /// Encode the response of the responder: its ephemeral public key and a tag proving
+    /// that it knows the pre-shared key
+    pub(super) async fn encode_resumption_response(&mut self) -> Result<Vec<u8>> {
+        let mut state = self.state.clone();
+        let e_pub_key = self.get_public_key(state.e()?).await?;
+        state.mix_hash(&e_pub_key.0);
+        self.mix_key(&mut state, &e_pub_key.0).await?;
+        let mut message = e_pub_key.0.to_vec();
+
+        // ck, k = HKDF(ck, DH(e, re), 2)
+        let dh = self.dh(state.e()?, state.re()?).await?;
+        self.hkdf(&mut state, dh).await?;
+
+        let tag = self.encrypt_and_hash(&mut state, &[]).await?;
+        message.extend(tag);
+        self.state = state;
+        Ok(message)
+    }
+
+    /// Decode the response of the responder
+    pub(super) async fn decode_resumption_response(&mut self, message: &[u8]) -> Result<()> {
+        if message.len() != RESUMPTION_RESPONSE_LEN {
+            return Err(XXError::MessageLenMismatch.into());
+        }
+        let mut state = self.state.clone();
+        let re_pub_key = Self::read_key(message)?;
+        state.mix_hash(re_pub_key);
+        self.mix_key(&mut state, re_pub_key).await?;
+        state.re = Some(X25519PublicKey(*re_pub_key));
+
+        // ck, k = HKDF(ck, DH(e, re), 2)
+        let dh = self.dh(state.e()?, state.re()?).await?;
+        self.hkdf(&mut state, dh).await?;
+
+        let tag = Self::read_end::<X25519_PUBLIC_KEY_LENGTH>(message)?;
+        self.hash_and_decrypt(&mut state, tag).await?;
+        self.state = state;
+        Ok(())
+    }
+
+    /// ck, k = HKDF(ck, data, 2), as done for ephemeral keys in the psk modes of Noise
+    async fn mix_key(&self, state: &mut HandshakeState, data: &[u8]) -> Result<()> {
+        let secret = self.vault.import_secret_buffer(data.to_vec()).await?;
+        self.hkdf(state, secret).await
+    }
+}
+
+/// Functions used for a hybrid post-quantum key exchange, where a ML-KEM shared secret is
+/// mixed into the chaining key, in addition to the X25519 Diffie-Hellman secrets:
+///
+///   -> e, ekem
+///   <- e, ee, s, es, kem ciphertext
+///   -> s, se
+///
+/// The ML-KEM public key of the initiator is sent in the payload of message 1. A responder
+/// supporting the hybrid key exchange returns a ciphertext in the encrypted payload of message 2
+/// and both parties mix the shared secret right after message 2. A responder which doesn't
+/// support it ignores the public key and the handshake is a regular XX handshake
+impl Handshake {
+    /// Generate an ephemeral ML-KEM key and return its public key
+    pub(super) async fn generate_kem_key(&self) -> Result<(KemSecretKeyHandle, KemPublicKey)> {
+        let secret_key = self.vault.generate_ephemeral_kem_secret_key().await?;
+        let public_key = self.vault.get_kem_public_key(&secret_key).await?;
+        Ok((secret_key, public_key))
+    }
+
+    /// Create a shared secret for the initiator and return the ciphertext to send back
+    pub(super) async fn encapsulate_kem_secret(
+        &self,
+        initiator_public_key: &KemPublicKey,
+    ) -> Result<(KemCiphertext, SecretBufferHandle)> {
+        self.vault.kem_encapsulate(initiator_public_key).await
+    }
+
+    /// Decapsulate the shared secret sent by the responder, then delete the ML-KEM key
+    pub(super) async fn decapsulate_kem_secret(
+        &self,
+        secret_key: KemSecretKeyHandle,
+        ciphertext: &KemCiphertext,
+    ) -> Result<SecretBufferHandle> {
+        let secret = self.vault.kem_decapsulate(&secret_key, ciphertext).await;
+        self.delete_kem_key(secret_key).await?;
+        secret
+    }
+
+    /// Delete an ML-KEM key which is not used anymore
+    pub(super) async fn delete_kem_key(&self, secret_key: KemSecretKeyHandle) -> Result<()> {
+        self.vault
+            .delete_ephemeral_kem_secret_key(secret_key)
+            .await?;
+        Ok(())
+    }
+
+    /// Delete a secret which is not used anymore, like the secret of a resumption ticket
+    pub(super) async fn delete_secret(&self, secret: SecretBufferHandle) -> Result<()> {
+        self.vault.delete_secret_buffer(secret).await?;
+        Ok(())
+    }
+
+    /// ck, k = HKDF(ck, kem shared secret, 2). The shared secret is deleted
+    pub(super) async fn mix_kem_secret(&mut self, secret: SecretBufferHandle) -> Result<()> {
+        let mut state = self.state.clone();
+        self.hkdf(&mut state, secret).await?;
+        self.state = state;
+        Ok(())
+    }
 }
 
 impl Handshake {
@@ -257,14 +469,21 @@ impl Handshake {
 
     /// Compute two derived ck, and k keys based on existing ck and k keys + a Diffie-Hellman key
     async fn hkdf(&self, state: &mut HandshakeState, dh: SecretBufferHandle) -> Result<()> {
-        let hkdf_output = self
-            .vault
-            .hkdf(state.ck()?, Some(&dh), HKDFNumberOfOutputs::Two)
-            .await?;
+        self.mix_key(state, &dh).await?;
 
         // The Diffie-Hellman secret is not useful anymore
         // we can delete it from memory
         self.vault.delete_secret_buffer(dh).await?;
+        Ok(())
+    }
+
+    /// Compute two derived ck, and k keys based on existing ck and k keys + a secret
+    /// which is kept by the caller
+    async fn mix_key(&self, state: &mut HandshakeState, secret: &SecretBufferHandle) -> Result<()> {
+        let hkdf_output = self
+            .vault
+            .hkdf(state.ck()?, Some(secret), HKDFNumberOfOutputs::Two)
+            .await?;
 
         let [new_ck, new_k]: [SecretBufferHandle; 2] = hkdf_output
             .0
@@ -288,21 +507,33 @@ impl Handshake {
         //_ => ,
     }
 
-    /// Compute the final encryption and decryption keys
+    /// Compute the final encryption and decryption keys, and a resumption secret if required.
+    /// The first two outputs of HKDF do not depend on the number of outputs
     async fn compute_final_keys(
         &self,
         state: &mut HandshakeState,
-    ) -> Result<(AeadSecretKeyHandle, AeadSecretKeyHandle)> {
+        with_resumption: bool,
+    ) -> Result<(
+        AeadSecretKeyHandle,
+        AeadSecretKeyHandle,
+        Option<SecretBufferHandle>,
+    )> {
+        let number_of_outputs = if with_resumption {
+            HKDFNumberOfOutputs::Three
+        } else {
+            HKDFNumberOfOutputs::Two
+        };
         let hkdf_output = self
             .vault
-            .hkdf(state.ck()?, None, HKDFNumberOfOutputs::Two)
+            .hkdf(state.ck()?, None, number_of_outputs)
             .await?;
 
-        let [k1, k2]: [SecretBufferHandle; 2] = hkdf_output
-            .0
-             .0
-            .try_into()
-            .map_err(|_| XXError::InternalVaultError)?;
+        let mut outputs = hkdf_output.0 .0.into_iter();
+        let (k1, k2) = match (outputs.next(), outputs.next()) {
+            (Some(k1), Some(k2)) => (k1, k2),
+            _ => return Err(XXError::InternalVaultError.into()),
+        };
+        let resumption_secret = outputs.next();
 
         let k1 = self.vault.convert_secret_buffer_to_aead_key(k1).await?;
         let k2 = self.vault.convert_secret_buffer_to_aead_key(k2).await?;
@@ -310,7 +541,7 @@ impl Handshake {
         self.vault.delete_secret_buffer(state.take_ck()?).await?;
         self.vault.delete_aead_secret_key(state.take_k()?).await?;
 
-        Ok((k1, k2))
+        Ok((k1, k2, resumption_secret))
     }
 
     /// Decrypt a ciphertext 'c' using the key 'k' and the additional data 'h'
@@ -355,10 +586,13 @@ impl Handshake {
 cfg_if! {
     if #[cfg(any(not(feature = "disable_default_noise_protocol"), feature = "OCKAM_XX_25519_AES256_GCM_SHA256"))] {
         pub const PROTOCOL_NAME: &[u8; 32] = b"Noise_XX_25519_AESGCM_SHA256\0\0\0\0";
+        pub const RESUMPTION_PROTOCOL_NAME: &[u8; 32] = b"Noise_NNpsk0_25519_AESGCM_SHA256";
     } else if #[cfg(feature = "OCKAM_XX_25519_AES128_GCM_SHA256")] {
         pub const PROTOCOL_NAME: &[u8; 32] = b"OCKAM_XX_25519_AES128_GCM_SHA256";
+        pub const RESUMPTION_PROTOCOL_NAME: &[u8; 32] = b"OCKAM_NNpsk0_25519_AES128_GCMSHA";
     } else if #[cfg(feature = "OCKAM_XX_25519_ChaChaPolyBLAKE2s")] {
         pub const PROTOCOL_NAME: &[u8; 32] = b"OCKAM_XX_25519_ChaChaPolyBLAKE2s";
+        pub const RESUMPTION_PROTOCOL_NAME: &[u8; 32] = b"OCKAM_NNpsk0_25519_ChaChaPolyB2s";
     }
 }
 
@@ -644,10 +878,156 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_resumption() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create();
+        let initiator_static_key = vault.generate_static_x25519_secret_key().await?;
+        let responder_static_key = vault.generate_static_x25519_secret_key().await?;
+
+        // a first handshake creates the same resumption secret on both sides
+        let mut initiator = Handshake::new(vault.clone(), initiator_static_key.clone()).await?;
+        let mut responder = Handshake::new(vault.clone(), responder_static_key.clone()).await?;
+        initiator.initialize().await?;
+        responder.initialize().await?;
+        let message1 = initiator.encode_message1(&[]).await?;
+        responder.decode_message1(&message1).await?;
+        let message2 = responder.encode_message2(&[]).await?;
+        initiator.decode_message2(&message2).await?;
+        let message3 = initiator.encode_message3(&[]).await?;
+        responder.decode_message3(&message3).await?;
+        initiator.set_final_state(Role::Initiator, true).await?;
+        responder.set_final_state(Role::Responder, true).await?;
+
+        let initiator_secret = resumption_secret(&initiator);
+        let responder_secret = resumption_secret(&responder);
+        assert_eq!(initiator_secret.ticket_id, responder_secret.ticket_id);
+
+        // the next handshake is resumed in one round-trip with that secret
+        let prologue = initiator_secret.ticket_id.clone();
+        let initiator_xx = Handshake::new(vault.clone(), initiator_static_key.clone()).await?;
+        let mut initiator = initiator_xx.new_resumption()?;
+        initiator
+            .initialize_resumption(&prologue, &initiator_secret.secret)
+            .await?;
+        let tag = initiator.encode_resumption_request().await?;
+
+        let responder_xx = Handshake::new(vault.clone(), responder_static_key.clone()).await?;
+        let mut responder = responder_xx.new_resumption()?;
+        responder
+            .initialize_resumption(&prologue, &responder_secret.secret)
+            .await?;
+        let initiator_e = vault.get_x25519_public_key(initiator.state.e()?).await?;
+        responder
+            .decode_resumption_request(initiator_e.clone(), &tag)
+            .await?;
+        let response = responder.encode_resumption_response().await?;
+        assert_eq!(response.len(), RESUMPTION_RESPONSE_LEN);
+        initiator.decode_resumption_response(&response).await?;
+
+        initiator.set_final_state(Role::Initiator, true).await?;
+        responder.set_final_state(Role::Responder, true).await?;
+        let initiator_keys = initiator.get_handshake_keys().unwrap();
+        let responder_keys = responder.get_handshake_keys().unwrap();
+        let nonce = [0u8; 12];
+        let cipher_text = vault
+            .aead_encrypt(&initiator_keys.encryption_key, b"hello", &nonce, &[])
+            .await?;
+        let plain_text = vault
+            .aead_decrypt(&responder_keys.decryption_key, &cipher_text, &nonce, &[])
+            .await?;
+        assert_eq!(plain_text, b"hello");
+
+        // a new secret is created for the next resumption
+        let next_initiator_secret = resumption_secret(&initiator);
+        let next_responder_secret = resumption_secret(&responder);
+        assert_eq!(
+            next_initiator_secret.ticket_id,
+            next_responder_secret.ticket_id
+        );
+        assert_ne!(next_initiator_secret.ticket_id, prologue);
+
+        // a resumption request made with another secret is rejected
+        let initiator_xx = Handshake::new(vault.clone(), initiator_static_key).await?;
+        let mut initiator = initiator_xx.new_resumption()?;
+        initiator
+            .initialize_resumption(&prologue, &next_initiator_secret.secret)
+            .await?;
+        let tag = initiator.encode_resumption_request().await?;
+
+        let responder_xx = Handshake::new(vault.clone(), responder_static_key).await?;
+        let mut responder = responder_xx.new_resumption()?;
+        let wrong_secret = vault.import_secret_buffer(vec![1; 32]).await?;
+        responder
+            .initialize_resumption(&prologue, &wrong_secret)
+            .await?;
+        let initiator_e = vault.get_x25519_public_key(initiator.state.e()?).await?;
+        assert!(responder
+            .decode_resumption_request(initiator_e, &tag)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "pqc")]
+    #[tokio::test]
+    async fn test_hybrid_key_exchange() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create();
+        let initiator_static_key = vault.generate_static_x25519_secret_key().await?;
+        let responder_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut initiator = Handshake::new(vault.clone(), initiator_static_key).await?;
+        let mut responder = Handshake::new(vault.clone(), responder_static_key).await?;
+        initiator.initialize().await?;
+        responder.initialize().await?;
+
+        // the initiator sends its ML-KEM public key in message 1
+        let (kem_secret_key, kem_public_key) = initiator.generate_kem_key().await?;
+        let message1 = initiator.encode_message1(&kem_public_key.0).await?;
+        let payload1 = responder.decode_message1(&message1).await?;
+
+        // the responder sends back a ciphertext in message 2 and both parties mix the secret
+        let (ciphertext, responder_secret) = responder
+            .encapsulate_kem_secret(&KemPublicKey(payload1))
+            .await?;
+        let message2 = responder.encode_message2(&ciphertext.0).await?;
+        responder.mix_kem_secret(responder_secret).await?;
+        let payload2 = initiator.decode_message2(&message2).await?;
+        let initiator_secret = initiator
+            .decapsulate_kem_secret(kem_secret_key, &KemCiphertext(payload2))
+            .await?;
+        initiator.mix_kem_secret(initiator_secret).await?;
+        assert_eq!(vault.number_of_ephemeral_kem_secrets(), 0);
+
+        let message3 = initiator.encode_message3(&[]).await?;
+        responder.decode_message3(&message3).await?;
+        initiator.set_final_state(Role::Initiator, false).await?;
+        responder.set_final_state(Role::Responder, false).await?;
+
+        let initiator_keys = initiator.get_handshake_keys().unwrap();
+        let responder_keys = responder.get_handshake_keys().unwrap();
+        let nonce = [0u8; 12];
+        let cipher_text = vault
+            .aead_encrypt(&initiator_keys.encryption_key, b"hello", &nonce, &[])
+            .await?;
+        let plain_text = vault
+            .aead_decrypt(&responder_keys.decryption_key, &cipher_text, &nonce, &[])
+            .await?;
+        assert_eq!(plain_text, b"hello");
+
+        Ok(())
+    }
+
     // --------------------
     // TESTS IMPLEMENTATION
     // --------------------
 
+    fn resumption_secret(handshake: &Handshake) -> ResumptionSecret {
+        handshake
+            .get_handshake_keys()
+            .and_then(|keys| keys.resumption_secret)
+            .unwrap()
+    }
+
     struct HandshakeMessages {
         initiator_static_key: X25519SecretKey,
         initiator_ephemeral_key: X25519SecretKey,
@@ -716,10 +1096,10 @@ mod tests {
         let decoded = responder.decode_message3(&result).await?;
         assert_eq!(decoded, messages.message3_payload);
 
-        let result = initiator.set_final_state(Role::Responder).await;
+        let result = initiator.set_final_state(Role::Responder, false).await;
         assert!(result.is_ok());
 
-        let result = responder.set_final_state(Role::Initiator).await;
+        let result = responder.set_final_state(Role::Initiator, false).await;
         assert!(result.is_ok());
 
         Ok(())
@@ -3,12 +3,17 @@ use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::{async_trait, Result};
-use ockam_vault::{AeadSecretKeyHandle, X25519PublicKey};
+use ockam_vault::{
+    AeadSecretKeyHandle, KemCiphertext, KemPublicKey, SecretBufferHandle, X25519PublicKey,
+};
 use tracing::{debug, warn};
 
 use crate::models::{
     ChangeHistory, CredentialAndPurposeKey, Identifier, PurposeKeyAttestation, PurposePublicKey,
+    TimestampInSeconds,
 };
+use crate::secure_channel::resumption::ResumptionRequest;
+use crate::secure_channel::{KeyRenewal, DEFAULT_KEY_RENEWAL_INTERVAL};
 use crate::{
     Identities, Identity, IdentityError, SecureChannelTrustInfo, TrustContext, TrustPolicy,
 };
@@ -50,6 +55,15 @@ pub(super) enum Status {
 pub(super) struct HandshakeKeys {
     pub(super) encryption_key: AeadSecretKeyHandle,
     pub(super) decryption_key: AeadSecretKeyHandle,
+    /// Secret used to resume the channel, when the resumption is enabled
+    pub(super) resumption_secret: Option<ResumptionSecret>,
+}
+
+/// Secret shared by both parties at the end of a handshake, to resume the channel later
+#[derive(Debug, Clone)]
+pub(super) struct ResumptionSecret {
+    pub(super) ticket_id: Vec<u8>,
+    pub(super) secret: SecretBufferHandle,
 }
 
 /// The end result of a handshake with identity/credentials exchange is
@@ -58,6 +72,18 @@ pub(super) struct HandshakeKeys {
 pub(super) struct HandshakeResults {
     pub(super) handshake_keys: HandshakeKeys,
     pub(super) their_identifier: Identifier,
+    /// Earliest expiration of the credentials presented by the other party, if any
+    pub(super) their_credentials_expiration: Option<TimestampInSeconds>,
+    /// Number of messages encrypted by the other party with the same key
+    pub(super) their_key_renewal_interval: u64,
+    /// True if the channel was resumed with the ticket of a previous channel
+    pub(super) resumed: bool,
+    /// True if a ML-KEM shared secret was mixed in the keys, in addition to the X25519 secrets
+    pub(super) post_quantum: bool,
+    /// True if the other party exchanges secure channel messages. Otherwise the messages are
+    /// exchanged as plain transport messages, and the credentials can neither be refreshed
+    /// nor the closing of the channel notified
+    pub(super) secure_channel_messages: bool,
 }
 
 /// This struct implements functions common to both initiator and the responder state machines
@@ -68,7 +94,14 @@ pub(super) struct CommonStateMachine {
     pub(super) credentials: Vec<CredentialAndPurposeKey>,
     pub(super) trust_policy: Arc<dyn TrustPolicy>,
     pub(super) trust_context: Option<TrustContext>,
+    /// Number of messages encrypted with the same key, sent to the other party
+    pub(super) key_renewal_interval: u64,
     their_identifier: Option<Identifier>,
+    their_credentials_expiration: Option<TimestampInSeconds>,
+    their_key_renewal_interval: u64,
+    resumed: bool,
+    pub(super) post_quantum: bool,
+    their_secure_channel_messages: bool,
 }
 
 impl CommonStateMachine {
@@ -79,6 +112,7 @@ impl CommonStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        key_renewal_interval: u64,
     ) -> Self {
         Self {
             identities,
@@ -87,7 +121,13 @@ impl CommonStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            key_renewal_interval,
             their_identifier: None,
+            their_credentials_expiration: None,
+            their_key_renewal_interval: DEFAULT_KEY_RENEWAL_INTERVAL,
+            resumed: false,
+            post_quantum: false,
+            their_secure_channel_messages: false,
         }
     }
 
@@ -97,8 +137,14 @@ impl CommonStateMachine {
     ///  - the current Identity Change History
     ///  - the current Secure Channel Purpose Key Attestation
     ///  - the Identity Credentials and corresponding Credentials Purpose Key Attestations
+    ///  - the number of messages encrypted with the same key
+    ///  - the ML-KEM ciphertext sent by the responder for a hybrid key exchange
+    ///  - the support of secure channel messages
     ///
-    pub(super) async fn make_identity_payload(&self) -> Result<Vec<u8>> {
+    pub(super) async fn make_identity_payload(
+        &self,
+        kem_ciphertext: Option<KemCiphertext>,
+    ) -> Result<Vec<u8>> {
         // prepare the payload that will be sent either in message 2 or message 3
         let change_history = self
             .identities
@@ -109,6 +155,9 @@ impl CommonStateMachine {
             change_history,
             purpose_key_attestation: self.purpose_key_attestation.clone(),
             credentials: self.credentials.clone(),
+            key_renewal_interval: Some(self.key_renewal_interval),
+            kem_ciphertext,
+            secure_channel_messages: Some(true),
         };
         Ok(minicbor::to_vec(payload)?)
     }
@@ -154,20 +203,39 @@ impl CommonStateMachine {
             }
         }
 
-        self.verify_credentials(identity.identifier(), peer.credentials)
+        self.their_key_renewal_interval = KeyRenewal::check_interval(
+            peer.key_renewal_interval
+                .unwrap_or(DEFAULT_KEY_RENEWAL_INTERVAL),
+        )?;
+        self.their_secure_channel_messages = peer.secure_channel_messages.unwrap_or(false);
+        self.their_credentials_expiration = self
+            .verify_credentials(identity.identifier(), peer.credentials)
             .await?;
         self.their_identifier = Some(identity.identifier().clone());
         Ok(())
     }
 
-    /// Verify that the credentials sent by the other party are valid using a trust context
-    /// and store them
-    async fn verify_credentials(
-        &self,
-        their_identifier: &Identifier,
-        credentials: Vec<CredentialAndPurposeKey>,
+    /// Use the identity of the other party of a previous channel, when this channel is resumed.
+    /// The identity and its credentials were verified during the previous handshake, but the
+    /// trust policy is checked again.
+    /// Parties resuming channels always exchange secure channel messages
+    pub(super) async fn set_resumed_identity(
+        &mut self,
+        their_identifier: Identifier,
+        their_credentials_expiration: Option<TimestampInSeconds>,
+        their_key_renewal_interval: u64,
     ) -> Result<()> {
-        // check our TrustPolicy
+        self.check_trust_policy(&their_identifier).await?;
+        self.their_credentials_expiration = their_credentials_expiration;
+        self.their_key_renewal_interval = their_key_renewal_interval;
+        self.their_identifier = Some(their_identifier);
+        self.resumed = true;
+        self.their_secure_channel_messages = true;
+        Ok(())
+    }
+
+    /// Check that the other party is trusted by our TrustPolicy
+    async fn check_trust_policy(&self, their_identifier: &Identifier) -> Result<()> {
         let trust_info = SecureChannelTrustInfo::new(their_identifier.clone());
         let trusted = self.trust_policy.check(&trust_info).await?;
         if !trusted {
@@ -178,6 +246,17 @@ impl CommonStateMachine {
             "Initiator checked trust policy for SecureChannel from: {}",
             their_identifier
         );
+        Ok(())
+    }
+
+    /// Verify that the credentials sent by the other party are valid using a trust context
+    /// and store them. Return the earliest expiration of those credentials
+    async fn verify_credentials(
+        &self,
+        their_identifier: &Identifier,
+        credentials: Vec<CredentialAndPurposeKey>,
+    ) -> Result<Option<TimestampInSeconds>> {
+        self.check_trust_policy(their_identifier).await?;
 
         if let Some(trust_context) = &self.trust_context {
             debug!(
@@ -210,7 +289,11 @@ impl CommonStateMachine {
             return Err(IdentityError::SecureChannelVerificationFailedMissingTrustContext.into());
         };
 
-        Ok(())
+        let mut expirations = Vec::new();
+        for credential in &credentials {
+            expirations.push(credential.get_credential_data()?.expires_at);
+        }
+        Ok(expirations.into_iter().min())
     }
 
     /// Return the results of the full handshake
@@ -224,6 +307,11 @@ impl CommonStateMachine {
             (Some(their_identifier), Some(handshake_keys)) => Some(HandshakeResults {
                 their_identifier,
                 handshake_keys,
+                their_credentials_expiration: self.their_credentials_expiration,
+                their_key_renewal_interval: self.their_key_renewal_interval,
+                resumed: self.resumed,
+                post_quantum: self.post_quantum,
+                secure_channel_messages: self.their_secure_channel_messages,
             }),
             _ => None,
         }
@@ -244,4 +332,45 @@ pub(super) struct IdentityAndCredentials {
     /// Credentials associated to the identity along with corresponding Credentials Purpose Keys
     /// to verify those Credentials
     #[n(3)] pub(super) credentials: Vec<CredentialAndPurposeKey>,
+    /// Number of messages encrypted with the same key by the sender.
+    /// The default interval is used when it is absent
+    #[n(4)] pub(super) key_renewal_interval: Option<u64>,
+    /// ML-KEM ciphertext sent by the responder when the initiator proposed a hybrid
+    /// post-quantum key exchange
+    #[n(5)] pub(super) kem_ciphertext: Option<KemCiphertext>,
+    /// True if the sender exchanges secure channel messages, to refresh its credentials and
+    /// notify the closing of the channel. Plain transport messages are exchanged when it is absent
+    #[n(6)] pub(super) secure_channel_messages: Option<bool>,
+}
+
+/// This internal structure is used as the payload of the message 1 of the XX protocol,
+/// to propose optional extensions of the handshake. It is sent in clear but it is
+/// part of the handshake hash. Responders which don't support an extension ignore it
+#[derive(Debug, Clone, Default, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(super) struct Message1Payload {
+    /// Request to resume a previous channel
+    #[n(1)] pub(super) resumption: Option<ResumptionRequest>,
+    /// ML-KEM public key of the initiator, for a hybrid post-quantum key exchange
+    #[n(2)] pub(super) kem_public_key: Option<KemPublicKey>,
+}
+
+impl Message1Payload {
+    /// Encode the payload, which is empty when no extension is proposed
+    pub(super) fn encode(&self) -> Result<Vec<u8>> {
+        if self.resumption.is_none() && self.kem_public_key.is_none() {
+            Ok(Vec::new())
+        } else {
+            Ok(minicbor::to_vec(self)?)
+        }
+    }
+
+    /// Decode the payload, ignoring an unknown format
+    pub(super) fn decode(payload: &[u8]) -> Self {
+        if payload.is_empty() {
+            return Self::default();
+        }
+        minicbor::decode(payload).unwrap_or_default()
+    }
 }
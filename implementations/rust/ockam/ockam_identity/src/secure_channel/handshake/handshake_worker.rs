@@ -3,18 +3,18 @@ use core::time::Duration;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{
-    AllowAll, Any, Decodable, DenyAll, Error, Mailbox, Mailboxes, OutgoingAccessControl, Route,
-    Routed,
+    AllowAll, AllowSourceAddress, Any, Decodable, DenyAll, Error, Mailbox, Mailboxes,
+    OutgoingAccessControl, Route, Routed,
 };
 use ockam_core::{AllowOnwardAddress, Result, Worker};
 use ockam_node::callback::CallbackSender;
-use ockam_node::{Context, WorkerBuilder};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
 use tracing::{debug, info};
 
 use crate::models::{CredentialAndPurposeKey, Identifier};
-use crate::secure_channel::decryptor::DecryptorHandler;
+use crate::secure_channel::decryptor::{CredentialsExpiration, DecryptorHandler};
 use crate::secure_channel::encryptor::Encryptor;
-use crate::secure_channel::encryptor_worker::EncryptorWorker;
+use crate::secure_channel::encryptor_worker::{CredentialsRefresh, EncryptorWorker};
 use crate::secure_channel::handshake::handshake_state_machine::Action::SendMessage;
 use crate::secure_channel::handshake::handshake_state_machine::Event::{
     Initialize, ReceivedMessage,
@@ -24,7 +24,9 @@ use crate::secure_channel::handshake::handshake_state_machine::{
 };
 use crate::secure_channel::handshake::initiator_state_machine::InitiatorStateMachine;
 use crate::secure_channel::handshake::responder_state_machine::ResponderStateMachine;
-use crate::secure_channel::{Addresses, Role};
+use crate::secure_channel::options::disclose_attributes;
+use crate::secure_channel::resumption::{Resumption, ResumptionTicket};
+use crate::secure_channel::{Addresses, KeyRenewal, Role, SecureChannelSharedState};
 use crate::{
     IdentityError, SecureChannelPurposeKey, SecureChannelRegistryEntry, SecureChannels,
     TrustContext, TrustPolicy,
@@ -41,6 +43,14 @@ pub(crate) struct HandshakeWorker {
     role: Role,
     remote_route: Option<Route>,
     decryptor_handler: Option<DecryptorHandler>,
+    /// Credentials presented to the other party, refreshed with the trust context before they expire
+    credentials: Vec<CredentialAndPurposeKey>,
+    /// Committed attributes of the credentials presented to the other party, all by default
+    disclosed_attributes: Option<Vec<Vec<u8>>>,
+    trust_context: Option<TrustContext>,
+    close_on_expired_credentials: bool,
+    resumption: Option<Resumption>,
+    key_renewal: KeyRenewal,
 }
 
 #[ockam_core::worker]
@@ -98,19 +108,20 @@ impl Worker for HandshakeWorker {
         };
 
         let transport_message = message.into_transport_message();
-        if let SendMessage(message) = self
+        let action = self
             .state_machine
             .on_event(ReceivedMessage(Vec::<u8>::decode(
                 &transport_message.payload,
             )?))
-            .await?
-        {
-            // set the remote route by taking the most up to date message return route
-            // In the case of the initiator the first return route mentions the secure channel listener
-            // address so we need to wait for the return route corresponding to the remote handshake worker
-            // when it has been spawned
-            self.remote_route = Some(transport_message.return_route);
+            .await?;
+
+        // set the remote route by taking the most up to date message return route
+        // In the case of the initiator the first return route mentions the secure channel listener
+        // address so we need to wait for the return route corresponding to the remote handshake worker
+        // when it has been spawned. When a channel is resumed, the initiator has no message to send back
+        self.remote_route = Some(transport_message.return_route);
 
+        if let SendMessage(message) = action {
             context
                 .send_from_address(
                     self.remote_route()?,
@@ -139,7 +150,7 @@ impl Worker for HandshakeWorker {
             .unregister_channel(&self.addresses.encryptor);
 
         if let Some(handler) = &self.decryptor_handler {
-            handler.shutdown().await?
+            handler.shutdown(context).await?
         }
 
         Ok(())
@@ -158,23 +169,46 @@ impl HandshakeWorker {
         trust_policy: Arc<dyn TrustPolicy>,
         decryptor_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
         credentials: Vec<CredentialAndPurposeKey>,
+        disclosed_attributes: Option<Vec<Vec<u8>>>,
         trust_context: Option<TrustContext>,
+        close_on_expired_credentials: bool,
+        resumption: Option<Resumption>,
+        key_renewal: KeyRenewal,
+        hybrid_key_exchange: bool,
         remote_route: Option<Route>,
         timeout: Option<Duration>,
         role: Role,
     ) -> Result<()> {
         let vault = secure_channels.identities.vault().secure_channel_vault;
         let identities = secure_channels.identities();
+        let credentials: Vec<CredentialAndPurposeKey> = credentials
+            .into_iter()
+            .map(|c| disclose_attributes(c, disclosed_attributes.as_deref()))
+            .collect();
         let state_machine: Box<dyn StateMachine> = if role.is_initiator() {
+            let resumption_key = resumption.as_ref().and_then(|r| r.key.as_deref());
+            let resumption_ticket = match resumption_key {
+                Some(key) => {
+                    secure_channels
+                        .resumption_tickets
+                        .take_initiator_ticket(&identifier, key)
+                        .await?
+                }
+                None => None,
+            };
             Box::new(
                 InitiatorStateMachine::new(
                     vault,
                     identities,
                     identifier.clone(),
                     purpose_key,
-                    credentials,
+                    credentials.clone(),
                     trust_policy,
-                    trust_context,
+                    trust_context.clone(),
+                    key_renewal.interval,
+                    resumption_ticket,
+                    resumption_key.is_some(),
+                    hybrid_key_exchange,
                 )
                 .await?,
             )
@@ -185,9 +219,14 @@ impl HandshakeWorker {
                     identities,
                     identifier.clone(),
                     purpose_key,
-                    credentials,
+                    credentials.clone(),
                     trust_policy,
-                    trust_context,
+                    trust_context.clone(),
+                    key_renewal.interval,
+                    resumption
+                        .as_ref()
+                        .map(|_| secure_channels.resumption_tickets.clone()),
+                    resumption.as_ref().and_then(|r| r.listener.clone()),
                 )
                 .await?,
             )
@@ -209,6 +248,12 @@ impl HandshakeWorker {
             remote_route: remote_route.clone(),
             addresses: addresses.clone(),
             decryptor_handler: None,
+            credentials,
+            disclosed_attributes,
+            trust_context,
+            close_on_expired_credentials,
+            resumption,
+            key_renewal,
         };
 
         WorkerBuilder::new(worker)
@@ -280,6 +325,44 @@ impl HandshakeWorker {
         Mailboxes::new(remote_mailbox, vec![internal_mailbox, api_mailbox])
     }
 
+    /// Our credentials are refreshed when they were presented to the other party and
+    /// the trust context has an authority which can provide renewed credentials
+    async fn create_credentials_refresh(
+        &self,
+        context: &Context,
+    ) -> Result<Option<CredentialsRefresh>> {
+        let trust_context = match &self.trust_context {
+            Some(trust_context)
+                if trust_context
+                    .authority()
+                    .map(|a| a.has_credentials_retriever())
+                    .unwrap_or(false) =>
+            {
+                trust_context
+            }
+            _ => return Ok(None),
+        };
+        let mut credentials_data = Vec::new();
+        for credential in &self.credentials {
+            credentials_data.push(credential.get_credential_data()?);
+        }
+        let credential_data = match credentials_data.into_iter().min_by_key(|d| d.expires_at) {
+            Some(credential_data) => credential_data,
+            None => return Ok(None),
+        };
+
+        let timer =
+            DelayedEvent::create(context, self.addresses.encryptor_internal.clone(), vec![])
+                .await?;
+        Ok(Some(CredentialsRefresh::new(
+            self.identifier.clone(),
+            trust_context.clone(),
+            &credential_data,
+            self.disclosed_attributes.clone(),
+            timer,
+        )))
+    }
+
     /// Finalize the handshake by creating a `Decryptor` and an `EncryptorWorker`
     /// Note that `EncryptorWorker` is actually started as an independent worker while
     /// the `Decryptor` is directly used by this worker to delegate the decryption of messages
@@ -288,6 +371,9 @@ impl HandshakeWorker {
         context: &Context,
         handshake_results: HandshakeResults,
     ) -> Result<DecryptorHandler> {
+        self.store_resumption_ticket(&handshake_results).await?;
+        let shared_state = SecureChannelSharedState::new();
+
         // create a decryptor to delegate the processing of all messages after the handshake
         let decryptor = DecryptorHandler::new(
             self.role.str(),
@@ -295,10 +381,26 @@ impl HandshakeWorker {
             handshake_results.handshake_keys.decryption_key,
             self.secure_channels.identities.vault().secure_channel_vault,
             handshake_results.their_identifier.clone(),
+            handshake_results.their_key_renewal_interval,
+            handshake_results.secure_channel_messages,
+            CredentialsExpiration::new(
+                self.secure_channels.identities(),
+                self.trust_context.clone(),
+                handshake_results.their_credentials_expiration,
+                self.close_on_expired_credentials,
+            ),
+            shared_state.clone(),
         );
 
         // create a separate encryptor worker which will be started independently
         {
+            // Renewed credentials can only be presented with a secure channel message
+            let credentials_refresh = if handshake_results.secure_channel_messages {
+                self.create_credentials_refresh(context).await?
+            } else {
+                None
+            };
+            let timer_address = credentials_refresh.as_ref().map(|r| r.timer_address());
             let encryptor = EncryptorWorker::new(
                 self.role.str(),
                 self.addresses.clone(),
@@ -307,7 +409,11 @@ impl HandshakeWorker {
                     handshake_results.handshake_keys.encryption_key,
                     0,
                     self.secure_channels.identities.vault().secure_channel_vault,
+                    self.key_renewal,
                 ),
+                credentials_refresh,
+                handshake_results.secure_channel_messages,
+                shared_state,
             );
 
             let next_hop = self.remote_route()?.next()?.clone();
@@ -322,17 +428,36 @@ impl HandshakeWorker {
                 Arc::new(AllowAll),
             );
 
+            let mut additional_mailboxes = vec![api_mailbox];
+            if let Some(timer_address) = timer_address {
+                additional_mailboxes.push(Mailbox::new(
+                    self.addresses.encryptor_internal.clone(),
+                    Arc::new(AllowSourceAddress(timer_address)),
+                    Arc::new(DenyAll),
+                ));
+            }
+
             WorkerBuilder::new(encryptor)
-                .with_mailboxes(Mailboxes::new(main_mailbox, vec![api_mailbox]))
+                .with_mailboxes(Mailboxes::new(main_mailbox, additional_mailboxes))
                 .start(context)
                 .await?;
         }
 
         info!(
-            "Initialized SecureChannel {} at local: {}, remote: {}",
+            "Initialized SecureChannel {} at local: {}, remote: {}{}{}",
             self.role.str(),
             &self.addresses.encryptor,
-            &self.addresses.decryptor_remote
+            &self.addresses.decryptor_remote,
+            if handshake_results.resumed {
+                " (resumed)"
+            } else {
+                ""
+            },
+            if handshake_results.post_quantum {
+                " (post-quantum)"
+            } else {
+                ""
+            }
         );
 
         let their_decryptor_address = self
@@ -359,4 +484,36 @@ impl HandshakeWorker {
 
         Ok(decryptor)
     }
+
+    /// Keep the ticket which can be used to resume this channel, if the resumption is enabled
+    async fn store_resumption_ticket(&self, handshake_results: &HandshakeResults) -> Result<()> {
+        let (resumption, resumption_secret) = match (
+            &self.resumption,
+            &handshake_results.handshake_keys.resumption_secret,
+        ) {
+            (Some(resumption), Some(resumption_secret)) => (resumption, resumption_secret),
+            _ => return Ok(()),
+        };
+
+        let ticket = ResumptionTicket::new(
+            resumption_secret.ticket_id.clone(),
+            self.identifier.clone(),
+            handshake_results.their_identifier.clone(),
+            resumption_secret.secret.clone(),
+            resumption.lifetime,
+            handshake_results.their_credentials_expiration,
+            handshake_results.their_key_renewal_interval,
+        )?
+        .with_scope(
+            resumption.listener.clone(),
+            self.trust_context.as_ref().map(|tc| tc.id().to_string()),
+        );
+
+        let tickets = &self.secure_channels.resumption_tickets;
+        match (self.role.is_initiator(), &resumption.key) {
+            (true, Some(key)) => tickets.store_initiator_ticket(key.clone(), ticket).await,
+            (false, _) => tickets.store_responder_ticket(ticket).await,
+            (true, None) => tickets.delete(ticket).await,
+        }
+    }
 }
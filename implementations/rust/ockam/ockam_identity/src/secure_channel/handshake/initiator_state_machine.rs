@@ -4,19 +4,23 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
-use ockam_vault::{VaultForSecureChannels, X25519PublicKey};
+use ockam_vault::{
+    KemCiphertext, KemPublicKey, KemSecretKeyHandle, VaultForSecureChannels, X25519PublicKey,
+};
+use tracing::debug;
 use Action::*;
 use Event::*;
 use Role::*;
 use Status::*;
 
-use crate::models::{CredentialAndPurposeKey, Identifier};
+use crate::models::{CredentialAndPurposeKey, Identifier, TimestampInSeconds};
 use crate::secure_channel::handshake::error::XXError;
-use crate::secure_channel::handshake::handshake::Handshake;
+use crate::secure_channel::handshake::handshake::{Handshake, RESUMPTION_RESPONSE_LEN};
 use crate::secure_channel::handshake::handshake_state_machine::{
     Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, IdentityAndCredentials,
-    StateMachine, Status,
+    Message1Payload, StateMachine, Status,
 };
+use crate::secure_channel::resumption::{ResumptionRequest, ResumptionTicket};
 use crate::{Identities, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy};
 
 /// Implementation of a state machine for the key exchange on the initiator side
@@ -28,7 +32,11 @@ impl StateMachine for InitiatorStateMachine {
             // Initialize the handshake and send message 1
             (Initial, Initialize) => {
                 self.initialize_handshake().await?;
-                let message1 = self.encode_message1(&[]).await?;
+                let message1_payload = Message1Payload {
+                    resumption: self.make_resumption_request().await?,
+                    kem_public_key: self.kem_public_key.take(),
+                };
+                let message1 = self.encode_message1(&message1_payload.encode()?).await?;
 
                 // Send message 1 and wait for message 2
                 self.handshake.state.status = WaitingForMessage2;
@@ -36,9 +44,19 @@ impl StateMachine for InitiatorStateMachine {
             }
             // Process message 2 and send message 3
             (WaitingForMessage2, ReceivedMessage(message)) => {
+                // The responder resumed the channel
+                if self.resumption.is_some() && message.len() == RESUMPTION_RESPONSE_LEN {
+                    self.resume(&message).await?;
+                    return Ok(NoAction);
+                }
+                // Otherwise the responder continued with a full handshake
+                self.discard_resumption().await?;
+
                 let message2_payload = self.decode_message2(&message).await?;
-                let their_identity_payload: IdentityAndCredentials =
+                let mut their_identity_payload: IdentityAndCredentials =
                     minicbor::decode(&message2_payload)?;
+                self.mix_kem_secret(their_identity_payload.kem_ciphertext.take())
+                    .await?;
                 self.verify_identity(their_identity_payload, &self.handshake.state.rs()?.clone())
                     .await?;
                 let identity_payload = self
@@ -46,7 +64,8 @@ impl StateMachine for InitiatorStateMachine {
                     .take()
                     .ok_or(XXError::InvalidInternalState)?;
                 let message3 = self.encode_message3(&identity_payload).await?;
-                self.set_final_state(Initiator).await?;
+                self.set_final_state(Initiator, self.with_resumption)
+                    .await?;
                 Ok(SendMessage(message3))
             }
             // incorrect state / event
@@ -72,6 +91,14 @@ pub(super) struct InitiatorStateMachine {
     pub(super) handshake: Handshake,
     /// this serialized payload contains an identity, its credentials and a signature of its static key
     pub(super) identity_payload: Option<Vec<u8>>,
+    /// Handshake resuming a previous channel with its ticket, if there is one
+    pub(super) resumption: Option<(Handshake, ResumptionTicket)>,
+    /// If true a resumption secret is created at the end of the handshake
+    pub(super) with_resumption: bool,
+    /// ML-KEM key used for a hybrid key exchange, if it was proposed to the responder
+    pub(super) kem_secret_key: Option<KemSecretKeyHandle>,
+    /// ML-KEM public key sent in message 1
+    pub(super) kem_public_key: Option<KemPublicKey>,
 }
 
 impl InitiatorStateMachine {
@@ -79,6 +106,7 @@ impl InitiatorStateMachine {
         to self.common {
             async fn verify_identity(&mut self, peer: IdentityAndCredentials, peer_public_key: &X25519PublicKey) -> Result<()>;
             fn make_handshake_results(&self, handshake_keys: Option<HandshakeKeys>) -> Option<HandshakeResults>;
+            async fn set_resumed_identity(&mut self, their_identifier: Identifier, their_credentials_expiration: Option<TimestampInSeconds>, their_key_renewal_interval: u64) -> Result<()>;
         }
     }
     delegate! {
@@ -88,13 +116,99 @@ impl InitiatorStateMachine {
             async fn encode_message1(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
             async fn decode_message2(&mut self, message: &[u8]) -> Result<Vec<u8>>;
             async fn encode_message3(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
-            async fn set_final_state(&mut self, role: Role) -> Result<()>;
+            async fn set_final_state(&mut self, role: Role, with_resumption: bool) -> Result<()>;
             fn get_handshake_keys(&self) -> Option<HandshakeKeys>;
         }
     }
 }
 
 impl InitiatorStateMachine {
+    /// Return a resumption request when a ticket is available to resume a previous channel:
+    /// the ticket identifier and a proof that we know the ticket secret
+    async fn make_resumption_request(&mut self) -> Result<Option<ResumptionRequest>> {
+        let (handshake, ticket) = match self.resumption.as_mut() {
+            Some(resumption) => resumption,
+            None => return Ok(None),
+        };
+        let key_renewal_interval = self.common.key_renewal_interval;
+        let prologue = ticket.prologue(
+            true,
+            key_renewal_interval,
+            ticket.their_key_renewal_interval,
+        );
+        handshake
+            .initialize_resumption(&prologue, &ticket.secret)
+            .await?;
+        // the ticket was taken from the store, its secret is not needed anymore
+        handshake.delete_secret(ticket.secret.clone()).await?;
+        let tag = handshake.encode_resumption_request().await?;
+        Ok(Some(ResumptionRequest {
+            ticket_id: ticket.ticket_id.clone(),
+            tag,
+            key_renewal_interval: Some(key_renewal_interval),
+        }))
+    }
+
+    /// Mix the ML-KEM shared secret sent by the responder into the handshake keys.
+    /// If the responder doesn't support the hybrid key exchange, the ML-KEM key is deleted and
+    /// the handshake continues as a regular XX handshake
+    async fn mix_kem_secret(&mut self, ciphertext: Option<KemCiphertext>) -> Result<()> {
+        match (self.kem_secret_key.take(), ciphertext) {
+            (Some(secret_key), Some(ciphertext)) => {
+                let secret = self
+                    .handshake
+                    .decapsulate_kem_secret(secret_key, &ciphertext)
+                    .await?;
+                self.handshake.mix_kem_secret(secret).await?;
+                self.common.post_quantum = true;
+                Ok(())
+            }
+            (Some(secret_key), None) => {
+                debug!("the responder doesn't support the hybrid key exchange");
+                self.handshake.delete_kem_key(secret_key).await
+            }
+            (None, Some(_)) => Err(XXError::UnexpectedKemCiphertext.into()),
+            (None, None) => Ok(()),
+        }
+    }
+
+    /// Complete the resumption of a previous channel with the response of the responder
+    async fn resume(&mut self, message: &[u8]) -> Result<()> {
+        let (mut handshake, ticket) = self
+            .resumption
+            .take()
+            .ok_or(XXError::InvalidInternalState)?;
+        handshake.decode_resumption_response(message).await?;
+        if let Some(secret_key) = self.kem_secret_key.take() {
+            self.handshake.delete_kem_key(secret_key).await?;
+        }
+        self.set_resumed_identity(
+            ticket.their_identifier.clone(),
+            ticket.their_credentials_expiration,
+            ticket.their_key_renewal_interval,
+        )
+        .await?;
+        handshake
+            .set_final_state(Initiator, self.with_resumption)
+            .await?;
+
+        // The XX handshake is not used anymore
+        self.handshake.discard().await?;
+        self.handshake = handshake;
+        Ok(())
+    }
+
+    /// Remove the secrets of the resumption handshake when the responder does a full handshake
+    async fn discard_resumption(&mut self) -> Result<()> {
+        if let Some((mut handshake, _)) = self.resumption.take() {
+            handshake.discard().await?;
+        }
+        Ok(())
+    }
+}
+
+impl InitiatorStateMachine {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         vault: Arc<dyn VaultForSecureChannels>,
         identities: Arc<Identities>,
@@ -103,6 +217,10 @@ impl InitiatorStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        key_renewal_interval: u64,
+        resumption_ticket: Option<ResumptionTicket>,
+        with_resumption: bool,
+        hybrid_key_exchange: bool,
     ) -> Result<InitiatorStateMachine> {
         let common = CommonStateMachine::new(
             identities,
@@ -111,13 +229,37 @@ impl InitiatorStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            key_renewal_interval,
         );
-        let identity_payload = common.make_identity_payload().await?;
+        let identity_payload = common.make_identity_payload(None).await?;
+
+        let handshake = Handshake::new(vault, purpose_key.key().clone()).await?;
+        let resumption = match resumption_ticket {
+            Some(ticket) => Some((handshake.new_resumption()?, ticket)),
+            None => None,
+        };
+        let (kem_secret_key, kem_public_key) = if hybrid_key_exchange {
+            match handshake.generate_kem_key().await {
+                Ok((secret_key, public_key)) => (Some(secret_key), Some(public_key)),
+                // Fall back to a classical key exchange if the vault doesn't support ML-KEM
+                Err(err) if err.code().kind == Kind::Unsupported => {
+                    debug!("the vault doesn't support the hybrid key exchange: {err}");
+                    (None, None)
+                }
+                Err(err) => return Err(err),
+            }
+        } else {
+            (None, None)
+        };
 
         Ok(InitiatorStateMachine {
             common,
-            handshake: Handshake::new(vault, purpose_key.key().clone()).await?,
+            handshake,
             identity_payload: Some(identity_payload),
+            resumption,
+            with_resumption,
+            kem_secret_key,
+            kem_public_key,
         })
     }
 }
@@ -3,20 +3,22 @@ use delegate::delegate;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
-use ockam_core::{Error, Result};
+use ockam_core::{Address, Error, Result};
 use ockam_vault::{VaultForSecureChannels, X25519PublicKey};
+use tracing::{debug, warn};
 use Action::*;
 use Event::*;
 use Role::*;
 use Status::*;
 
-use crate::models::{CredentialAndPurposeKey, Identifier};
-use crate::secure_channel::handshake::error::XXError;
+use crate::models::{CredentialAndPurposeKey, Identifier, TimestampInSeconds};
 use crate::secure_channel::handshake::handshake::Handshake;
 use crate::secure_channel::handshake::handshake_state_machine::{
     Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, IdentityAndCredentials,
-    StateMachine, Status,
+    Message1Payload, StateMachine, Status,
 };
+use crate::secure_channel::resumption::{ResumptionRequest, ResumptionTickets};
+use crate::secure_channel::{KeyRenewal, DEFAULT_KEY_RENEWAL_INTERVAL};
 use crate::{Identities, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy};
 
 /// Implementation of a state machine for the key exchange on the responder side
@@ -33,12 +35,35 @@ impl StateMachine for ResponderStateMachine {
             }
             // Process message 1 and send message 2
             (WaitingForMessage1, ReceivedMessage(message)) => {
-                self.decode_message1(&message).await?;
-                let identity_payload = self
-                    .identity_payload
-                    .take()
-                    .ok_or(XXError::InvalidInternalState)?;
+                let message1_payload =
+                    Message1Payload::decode(&self.decode_message1(&message).await?);
+
+                // Resume a previous channel if the initiator presents a valid ticket
+                if let Some(response) = self.resume(message1_payload.resumption).await? {
+                    return Ok(SendMessage(response));
+                }
+
+                // Send a ML-KEM ciphertext if the initiator proposed a hybrid key exchange.
+                // Without a ciphertext, the initiator continues with a classical key exchange
+                let (kem_ciphertext, kem_secret) = match &message1_payload.kem_public_key {
+                    Some(public_key) => {
+                        match self.handshake.encapsulate_kem_secret(public_key).await {
+                            Ok((ciphertext, secret)) => (Some(ciphertext), Some(secret)),
+                            Err(err) if err.code().kind == Kind::Unsupported => {
+                                debug!("the vault doesn't support the hybrid key exchange: {err}");
+                                (None, None)
+                            }
+                            Err(err) => return Err(err),
+                        }
+                    }
+                    None => (None, None),
+                };
+                let identity_payload = self.common.make_identity_payload(kem_ciphertext).await?;
                 let message2 = self.encode_message2(&identity_payload).await?;
+                if let Some(secret) = kem_secret {
+                    self.handshake.mix_kem_secret(secret).await?;
+                    self.common.post_quantum = true;
+                }
 
                 self.handshake.state.status = WaitingForMessage3;
                 Ok(SendMessage(message2))
@@ -50,7 +75,8 @@ impl StateMachine for ResponderStateMachine {
                     minicbor::decode(&message3_payload)?;
                 self.verify_identity(their_identity_payload, &self.handshake.state.rs()?.clone())
                     .await?;
-                self.set_final_state(Responder).await?;
+                self.set_final_state(Responder, self.resumption_tickets.is_some())
+                    .await?;
                 Ok(NoAction)
             }
             // incorrect state / event
@@ -73,8 +99,10 @@ impl StateMachine for ResponderStateMachine {
 pub struct ResponderStateMachine {
     common: CommonStateMachine,
     handshake: Handshake,
-    /// this serialized payload contains an identity, its credentials and a signature of its static key
-    identity_payload: Option<Vec<u8>>,
+    /// Tickets of the previous channels, when the resumption of channels is enabled
+    resumption_tickets: Option<ResumptionTickets>,
+    /// Listener accepting this channel, which must have created the resumption ticket
+    resumption_listener: Option<Address>,
 }
 
 impl ResponderStateMachine {
@@ -82,6 +110,7 @@ impl ResponderStateMachine {
         to self.common {
             async fn verify_identity(&mut self, peer: IdentityAndCredentials, peer_public_key: &X25519PublicKey) -> Result<()>;
             fn make_handshake_results(&self, handshake_keys: Option<HandshakeKeys>) -> Option<HandshakeResults>;
+            async fn set_resumed_identity(&mut self, their_identifier: Identifier, their_credentials_expiration: Option<TimestampInSeconds>, their_key_renewal_interval: u64) -> Result<()>;
         }
     }
     delegate! {
@@ -91,13 +120,94 @@ impl ResponderStateMachine {
             async fn decode_message1(&mut self, message: &[u8]) -> Result<Vec<u8>>;
             async fn encode_message2(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
             async fn decode_message3(&mut self, message: &[u8]) -> Result<Vec<u8>>;
-            async fn set_final_state(&mut self, role: Role) -> Result<()>;
+            async fn set_final_state(&mut self, role: Role, with_resumption: bool) -> Result<()>;
             fn get_handshake_keys(&self) -> Option<HandshakeKeys>;
         }
     }
 }
 
 impl ResponderStateMachine {
+    /// If message 1 contains a resumption request with a valid ticket, resume the
+    /// previous channel and return the response to send to the initiator.
+    /// Otherwise the handshake continues with message 2 of the XX handshake
+    async fn resume(&mut self, request: Option<ResumptionRequest>) -> Result<Option<Vec<u8>>> {
+        let (tickets, request) = match (&self.resumption_tickets, request) {
+            (Some(tickets), Some(request)) => (tickets.clone(), request),
+            _ => return Ok(None),
+        };
+        let trust_context_id = self.common.trust_context.as_ref().map(|tc| tc.id());
+        // the ticket is only removed once the request has been verified, so that an
+        // invalid request can not invalidate the ticket of the legitimate initiator
+        let ticket = match tickets.get_responder_ticket(&request.ticket_id).await? {
+            Some(ticket)
+                if ticket.is_in_scope(
+                    &self.common.identifier,
+                    self.resumption_listener.as_ref(),
+                    trust_context_id,
+                ) =>
+            {
+                ticket
+            }
+            Some(_) => {
+                warn!("resumption ticket created by another listener, doing a full handshake");
+                return Ok(None);
+            }
+            None => {
+                debug!("unknown or expired resumption ticket, doing a full handshake");
+                return Ok(None);
+            }
+        };
+
+        // the key renewal intervals of both parties are part of the prologue, so that the
+        // resumption fails if they differ from the ones expected by the initiator
+        let their_key_renewal_interval = KeyRenewal::check_interval(
+            request
+                .key_renewal_interval
+                .unwrap_or(DEFAULT_KEY_RENEWAL_INTERVAL),
+        )?;
+        let prologue = ticket.prologue(
+            false,
+            self.common.key_renewal_interval,
+            their_key_renewal_interval,
+        );
+        let mut handshake = self.handshake.new_resumption()?;
+        handshake
+            .initialize_resumption(&prologue, &ticket.secret)
+            .await?;
+        let re = self.handshake.state.re()?.clone();
+        if let Err(e) = handshake.decode_resumption_request(re, &request.tag).await {
+            warn!("invalid resumption request, doing a full handshake: {}", e);
+            handshake.discard().await?;
+            return Ok(None);
+        }
+
+        // a ticket can only be used once
+        match tickets.remove_responder_ticket(&ticket.ticket_id) {
+            Some(removed) => tickets.delete(removed).await?,
+            None => {
+                warn!("resumption ticket already used, doing a full handshake");
+                handshake.discard().await?;
+                return Ok(None);
+            }
+        }
+        self.set_resumed_identity(
+            ticket.their_identifier.clone(),
+            ticket.their_credentials_expiration,
+            their_key_renewal_interval,
+        )
+        .await?;
+        let response = handshake.encode_resumption_response().await?;
+        handshake.set_final_state(Responder, true).await?;
+
+        // The XX handshake is not used anymore
+        self.handshake.discard().await?;
+        self.handshake = handshake;
+        Ok(Some(response))
+    }
+}
+
+impl ResponderStateMachine {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         vault: Arc<dyn VaultForSecureChannels>,
         identities: Arc<Identities>,
@@ -106,6 +216,9 @@ impl ResponderStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        key_renewal_interval: u64,
+        resumption_tickets: Option<ResumptionTickets>,
+        resumption_listener: Option<Address>,
     ) -> Result<ResponderStateMachine> {
         let common = CommonStateMachine::new(
             identities,
@@ -114,13 +227,14 @@ impl ResponderStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            key_renewal_interval,
         );
-        let identity_payload = common.make_identity_payload().await?;
 
         Ok(ResponderStateMachine {
             common,
             handshake: Handshake::new(vault, purpose_key.key().clone()).await?,
-            identity_payload: Some(identity_payload),
+            resumption_tickets,
+            resumption_listener,
         })
     }
 }
@@ -0,0 +1,41 @@
+use core::time::Duration;
+use ockam_core::Result;
+
+use crate::IdentityError;
+
+/// Default number of messages encrypted with the same key on a secure channel
+pub const DEFAULT_KEY_RENEWAL_INTERVAL: u64 = 32;
+
+/// Maximum number of messages encrypted with the same key on a secure channel.
+/// The replay window of the other end of the channel keeps one bit per message of an interval
+pub const MAX_KEY_RENEWAL_INTERVAL: u64 = 1 << 20;
+
+/// Policy used to renew the key encrypting the messages sent on a secure channel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct KeyRenewal {
+    /// A new key is derived every `interval` messages.
+    /// This interval is sent to the other end of the channel during the handshake
+    pub(crate) interval: u64,
+    /// A new key is derived before sending a message when the current key is older
+    pub(crate) max_key_age: Option<Duration>,
+}
+
+impl Default for KeyRenewal {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_KEY_RENEWAL_INTERVAL,
+            max_key_age: None,
+        }
+    }
+}
+
+impl KeyRenewal {
+    /// Check that a key renewal interval, ours or the one of the other end of the channel,
+    /// is supported
+    pub(crate) fn check_interval(interval: u64) -> Result<u64> {
+        if interval == 0 || interval > MAX_KEY_RENEWAL_INTERVAL {
+            return Err(IdentityError::InvalidKeyRenewalInterval.into());
+        }
+        Ok(interval)
+    }
+}
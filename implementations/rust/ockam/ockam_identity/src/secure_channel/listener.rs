@@ -107,7 +107,14 @@ impl Worker for IdentityChannelListener {
             self.options.trust_policy.clone(),
             access_control.decryptor_outgoing_access_control,
             credentials,
+            self.options.disclosed_attributes.clone(),
             self.options.trust_context.clone(),
+            self.options.close_on_expired_credentials,
+            self.options
+                .resumption()
+                .map(|r| r.with_listener(ctx.address())),
+            self.options.key_renewal,
+            false,
             None,
             None,
             Role::Responder,
@@ -0,0 +1,56 @@
+use core::fmt;
+use minicbor::{Decode, Encode};
+use ockam_core::compat::vec::Vec;
+
+use crate::models::CredentialAndPurposeKey;
+
+/// Messages exchanged by the two ends of a secure channel once the handshake is done.
+/// They are encrypted before being sent to the other end of the channel.
+///
+/// Both ends announce their support of these messages during the handshake. With an older
+/// implementation, only the encoded [`ockam_core::TransportMessage`]s are exchanged.
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+pub(crate) enum SecureChannelMessage {
+    /// An encoded [`ockam_core::TransportMessage`] to forward to its onward route
+    #[n(0)] Payload(#[cbor(n(0), with = "minicbor::bytes")] Vec<u8>),
+    /// Renewed credentials of the sender, presented before its previous credentials expire
+    #[n(1)] RefreshCredentials(#[n(0)] RefreshCredentialsMessage),
+    /// Notification that the sender closed its end of the channel
+    #[n(2)] Close(#[n(0)] CloseMessage),
+}
+
+/// Credentials presented again by one end of a secure channel
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct RefreshCredentialsMessage {
+    #[n(1)] pub(crate) credentials: Vec<CredentialAndPurposeKey>,
+}
+
+/// Sent by one end of a secure channel before it stops its workers
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct CloseMessage {
+    #[n(1)] pub(crate) reason: CloseReason,
+}
+
+/// Reason why a secure channel was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub(crate) enum CloseReason {
+    /// The channel was deleted or its node was stopped
+    #[n(0)] Closed,
+    /// The credentials of the other end of the channel expired
+    #[n(1)] CredentialsExpired,
+}
+
+impl fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CloseReason::Closed => write!(f, "closed"),
+            CloseReason::CredentialsExpired => write!(f, "credentials expired"),
+        }
+    }
+}
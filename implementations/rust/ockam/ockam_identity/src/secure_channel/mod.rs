@@ -5,31 +5,42 @@ mod api;
 mod decryptor;
 mod encryptor;
 mod encryptor_worker;
+mod group;
 mod handshake;
+mod key_renewal;
 mod key_tracker;
 mod listener;
 mod local_info;
+mod message;
 mod nonce_tracker;
 mod options;
 mod registry;
+mod resumption;
 mod role;
+mod shared_state;
 /// List of trust policies to setup ABAC controls
 pub mod trust_policy;
 
 pub use access_control::*;
 pub(crate) use addresses::*;
 pub use api::*;
+pub use group::*;
 pub(crate) use handshake::*;
+pub(crate) use key_renewal::KeyRenewal;
+pub use key_renewal::{DEFAULT_KEY_RENEWAL_INTERVAL, MAX_KEY_RENEWAL_INTERVAL};
 pub(crate) use listener::*;
 pub use local_info::*;
 pub use options::*;
 pub use registry::*;
+pub use resumption::DEFAULT_RESUMPTION_TICKET_LIFETIME;
 pub(crate) use role::*;
+pub(crate) use shared_state::*;
 pub use trust_policy::*;
 
 #[cfg(test)]
 mod tests {
-    use crate::secure_channel::{decryptor::Decryptor, encryptor::Encryptor};
+    use crate::secure_channel::{decryptor::Decryptor, encryptor::Encryptor, KeyRenewal};
+    use core::time::Duration;
     use ockam_core::compat::rand::RngCore;
     use ockam_core::Result;
     use ockam_vault::{SoftwareVaultForSecureChannels, VaultForSecureChannels};
@@ -105,6 +116,58 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_encrypt_decrypt_with_key_renewal_interval() {
+        let key_renewal = KeyRenewal {
+            interval: 1000,
+            max_key_age: None,
+        };
+        let (mut encryptor, mut decryptor) =
+            create_encryptor_decryptor_with_key_renewal(key_renewal)
+                .await
+                .unwrap();
+
+        // Messages displaced by several hundreds are in the accepted window
+        let mut all_msgs: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+        for n in 0..3000u16 {
+            let msg = n.to_be_bytes().to_vec();
+            let ciphertext = encryptor.encrypt(&msg).await.unwrap();
+            all_msgs.push((msg, ciphertext));
+        }
+        for batch in all_msgs.chunks_mut(500) {
+            batch.shuffle(&mut thread_rng());
+            for (plaintext, ciphertext) in batch.iter() {
+                assert_eq!(plaintext, &decryptor.decrypt(ciphertext).await.unwrap());
+            }
+        }
+        // Repeated nonces are detected
+        for (_plaintext, ciphertext) in all_msgs.iter().rev().take(1000) {
+            assert!(decryptor.decrypt(ciphertext).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_decrypt_with_max_key_age() {
+        // The key is renewed for each message
+        let key_renewal = KeyRenewal {
+            interval: 32,
+            max_key_age: Some(Duration::ZERO),
+        };
+        let (mut encryptor, mut decryptor) =
+            create_encryptor_decryptor_with_key_renewal(key_renewal)
+                .await
+                .unwrap();
+
+        for n in 0..100 {
+            let msg = vec![n];
+            let ciphertext = encryptor.encrypt(&msg).await.unwrap();
+            // The remaining nonces of the interval of the previous key are skipped
+            let nonce = u64::from_be_bytes(ciphertext[..8].try_into().unwrap());
+            assert_eq!(nonce, (n as u64 + 1) * 32);
+            assert_eq!(msg, decryptor.decrypt(&ciphertext).await.unwrap());
+        }
+    }
+
     #[tokio::test]
     async fn test_attack_nonce() {
         let (mut encryptor, mut decryptor) = create_encryptor_decryptor().await.unwrap();
@@ -132,6 +195,12 @@ mod tests {
     }
 
     async fn create_encryptor_decryptor() -> Result<(Encryptor, Decryptor)> {
+        create_encryptor_decryptor_with_key_renewal(KeyRenewal::default()).await
+    }
+
+    async fn create_encryptor_decryptor_with_key_renewal(
+        key_renewal: KeyRenewal,
+    ) -> Result<(Encryptor, Decryptor)> {
         let vault1 = SoftwareVaultForSecureChannels::create();
         let vault2 = SoftwareVaultForSecureChannels::create();
 
@@ -146,8 +215,8 @@ mod tests {
         let key_on_v2 = vault2.convert_secret_buffer_to_aead_key(key_on_v2).await?;
 
         Ok((
-            Encryptor::new(key_on_v1, 0, vault1),
-            Decryptor::new(key_on_v2, vault2),
+            Encryptor::new(key_on_v1, 0, vault1, key_renewal),
+            Decryptor::new(key_on_v2, vault2, key_renewal.interval),
         ))
     }
 }
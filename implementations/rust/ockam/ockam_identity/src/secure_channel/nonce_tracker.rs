@@ -1,83 +1,137 @@
-use crate::secure_channel::encryptor::KEY_RENEWAL_INTERVAL;
+use ockam_core::compat::vec;
+use ockam_core::compat::vec::Vec;
+
 use crate::IdentityError;
 
-/// fails compilation if [`KEY_RENEWAL_INTERVAL`] + 1 is bigger than [`BitmapType::BITS`].
+/// Keep track of the nonces received on a secure channel to reject replayed messages.
 ///
-/// the +1 is needed since the current nonce is also marked as received, taking an extra bit
-/// even though we could check `current_nonce`, this compromise is for the sake of simplicity
-const _: [(); (KEY_RENEWAL_INTERVAL + 1 > BitmapType::BITS as u64) as usize] = [];
-type BitmapType = u64;
-
+/// The nonces older than the current one by up to `window` are accepted once, in any order.
+/// They are tracked with a bitmap used as a ring buffer, where the nonce `n` is at the
+/// position `n % bitmap_size`
 #[derive(Debug)]
 pub(crate) struct NonceTracker {
-    nonce_bitmap: BitmapType,
+    window: u64,
+    nonce_bitmap: Vec<u64>,
     current_nonce: u64,
 }
 
 impl NonceTracker {
-    pub(crate) fn new() -> Self {
+    /// Create a tracker accepting messages received out of order, up to `window` nonces
+    /// before the most recent one
+    pub(crate) fn new(window: u64) -> Self {
+        // the current nonce is also marked as received, taking an extra bit
+        let words = (window / u64::BITS as u64 + 1) as usize;
         Self {
-            nonce_bitmap: 0,
+            window,
+            nonce_bitmap: vec![0; words],
             current_nonce: 0,
         }
     }
 
-    /// Mark a nonce as received, reject all invalid nonce values
-    pub(crate) fn mark(&self, nonce: u64) -> ockam_core::Result<NonceTracker> {
-        let new_tracker = if nonce > self.current_nonce {
-            // normal case, we increase the nonce and move the window
-            let relative_shift: u64 = nonce - self.current_nonce;
-            if relative_shift > KEY_RENEWAL_INTERVAL {
+    /// Reject invalid nonce values: nonces too far in the future or in the past, and nonces
+    /// which were already received
+    pub(crate) fn check(&self, nonce: u64) -> ockam_core::Result<()> {
+        if nonce > self.current_nonce {
+            if nonce - self.current_nonce > self.window {
                 return Err(IdentityError::InvalidNonce.into());
             }
-            NonceTracker {
-                nonce_bitmap: self.nonce_bitmap.overflowing_shl(relative_shift as u32).0 | 1,
-                current_nonce: nonce,
-            }
         } else {
             // first message or an out of order message
-            let relative: u64 = self.current_nonce - nonce;
-            if relative > KEY_RENEWAL_INTERVAL {
+            if self.current_nonce - nonce > self.window || self.is_marked(nonce) {
                 return Err(IdentityError::InvalidNonce.into());
             }
+        }
+        Ok(())
+    }
 
-            #[allow(trivial_numeric_casts)]
-            let bit = (1 as BitmapType).overflowing_shl(relative as u32).0;
-            if self.nonce_bitmap & bit != 0 {
-                // we already processed this nonce
-                return Err(IdentityError::InvalidNonce.into());
-            }
-            NonceTracker {
-                nonce_bitmap: self.nonce_bitmap | bit,
-                current_nonce: self.current_nonce,
+    /// Mark a nonce as received. The nonce must have been checked first
+    pub(crate) fn mark(&mut self, nonce: u64) {
+        if nonce > self.current_nonce {
+            // move the window: the positions of the skipped nonces are reused
+            let size = self.bitmap_size();
+            let skipped = (nonce - self.current_nonce).min(size);
+            for n in nonce - skipped + 1..=nonce {
+                self.set(n, false);
             }
-        };
+            self.current_nonce = nonce;
+        }
+        self.set(nonce, true);
+    }
+
+    fn bitmap_size(&self) -> u64 {
+        self.nonce_bitmap.len() as u64 * u64::BITS as u64
+    }
+
+    fn position(&self, nonce: u64) -> (usize, u64) {
+        let bit = nonce % self.bitmap_size();
+        (
+            (bit / u64::BITS as u64) as usize,
+            1 << (bit % u64::BITS as u64),
+        )
+    }
 
-        Ok(new_tracker)
+    fn is_marked(&self, nonce: u64) -> bool {
+        let (word, mask) = self.position(nonce);
+        self.nonce_bitmap[word] & mask != 0
+    }
+
+    fn set(&mut self, nonce: u64, marked: bool) {
+        let (word, mask) = self.position(nonce);
+        if marked {
+            self.nonce_bitmap[word] |= mask;
+        } else {
+            self.nonce_bitmap[word] &= !mask;
+        }
     }
 }
 
-#[test]
-pub fn check_nonce_tracker() {
-    let mut tracker = NonceTracker::new();
-    tracker = tracker.mark(0).unwrap();
-    tracker = tracker.mark(1).unwrap();
-    tracker.mark(0).unwrap_err();
-    tracker.mark(KEY_RENEWAL_INTERVAL + 2).unwrap_err();
-    tracker = tracker.mark(KEY_RENEWAL_INTERVAL + 1).unwrap();
-    tracker.mark(1).unwrap_err();
-    tracker = tracker.mark(KEY_RENEWAL_INTERVAL + 2).unwrap();
-    tracker = tracker.mark(KEY_RENEWAL_INTERVAL + 3).unwrap();
-    tracker.mark(KEY_RENEWAL_INTERVAL + 1).unwrap_err();
-    tracker.mark(KEY_RENEWAL_INTERVAL + 2).unwrap_err();
-    tracker = tracker.mark(2 * KEY_RENEWAL_INTERVAL).unwrap();
-    tracker.mark(KEY_RENEWAL_INTERVAL - 1).unwrap_err();
-    tracker = tracker.mark(3 * KEY_RENEWAL_INTERVAL).unwrap();
-    tracker = tracker.mark(4 * KEY_RENEWAL_INTERVAL).unwrap();
-    for n in 3 * KEY_RENEWAL_INTERVAL + 1..4 * KEY_RENEWAL_INTERVAL {
-        tracker = tracker.mark(n).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure_channel::DEFAULT_KEY_RENEWAL_INTERVAL;
+
+    fn mark(tracker: &mut NonceTracker, nonce: u64) -> ockam_core::Result<()> {
+        tracker.check(nonce)?;
+        tracker.mark(nonce);
+        Ok(())
+    }
+
+    #[test]
+    fn check_nonce_tracker() {
+        check_nonce_tracker_with_window(DEFAULT_KEY_RENEWAL_INTERVAL);
     }
-    for n in 4 * KEY_RENEWAL_INTERVAL + 1..5 * KEY_RENEWAL_INTERVAL + 1 {
-        tracker = tracker.mark(n).unwrap();
+
+    #[test]
+    fn check_nonce_tracker_with_wide_window() {
+        check_nonce_tracker_with_window(1000);
+        check_nonce_tracker_with_window(1 << 16);
+    }
+
+    fn check_nonce_tracker_with_window(window: u64) {
+        let mut tracker = NonceTracker::new(window);
+        mark(&mut tracker, 0).unwrap();
+        mark(&mut tracker, 1).unwrap();
+        tracker.check(0).unwrap_err();
+        tracker.check(window + 2).unwrap_err();
+        mark(&mut tracker, window + 1).unwrap();
+        tracker.check(1).unwrap_err();
+        mark(&mut tracker, window + 2).unwrap();
+        mark(&mut tracker, window + 3).unwrap();
+        tracker.check(window + 1).unwrap_err();
+        tracker.check(window + 2).unwrap_err();
+        mark(&mut tracker, 2 * window).unwrap();
+        tracker.check(window - 1).unwrap_err();
+        mark(&mut tracker, 3 * window).unwrap();
+        mark(&mut tracker, 4 * window).unwrap();
+        for n in 3 * window + 1..4 * window {
+            mark(&mut tracker, n).unwrap();
+        }
+        for n in 4 * window + 1..5 * window + 1 {
+            mark(&mut tracker, n).unwrap();
+        }
+        // all the nonces of the window were received
+        for n in 4 * window..5 * window + 1 {
+            tracker.check(n).unwrap_err();
+        }
     }
 }
@@ -1,10 +1,12 @@
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
 use ockam_core::{Address, OutgoingAccessControl, Result};
 
 use crate::models::CredentialAndPurposeKey;
-use crate::secure_channel::Addresses;
+use crate::secure_channel::resumption::{Resumption, DEFAULT_RESUMPTION_TICKET_LIFETIME};
+use crate::secure_channel::{Addresses, KeyRenewal};
 use crate::{TrustContext, TrustEveryonePolicy, TrustPolicy};
 
 use core::fmt;
@@ -20,7 +22,13 @@ pub struct SecureChannelOptions {
     pub(crate) trust_policy: Arc<dyn TrustPolicy>,
     pub(crate) trust_context: Option<TrustContext>,
     pub(crate) credentials: Vec<CredentialAndPurposeKey>,
+    pub(crate) disclosed_attributes: Option<Vec<Vec<u8>>>,
+    pub(crate) close_on_expired_credentials: bool,
     pub(crate) timeout: Duration,
+    pub(crate) resumption_key: Option<String>,
+    pub(crate) resumption_ticket_lifetime: Duration,
+    pub(crate) key_renewal: KeyRenewal,
+    pub(crate) hybrid_key_exchange: bool,
 }
 
 impl fmt::Debug for SecureChannelOptions {
@@ -42,7 +50,13 @@ impl SecureChannelOptions {
             trust_policy: Arc::new(TrustEveryonePolicy),
             trust_context: None,
             credentials: vec![],
+            disclosed_attributes: None,
+            close_on_expired_credentials: false,
             timeout: DEFAULT_TIMEOUT,
+            resumption_key: None,
+            resumption_ticket_lifetime: DEFAULT_RESUMPTION_TICKET_LIFETIME,
+            key_renewal: KeyRenewal::default(),
+            hybrid_key_exchange: false,
         }
     }
 
@@ -70,12 +84,75 @@ impl SecureChannelOptions {
         self
     }
 
+    /// Only present the given attributes of the credentials which are issued with selective
+    /// disclosure, so that the other party only learns the attributes it needs to check.
+    /// All the attributes are presented by default
+    pub fn with_disclosed_attributes(mut self, attribute_keys: Vec<Vec<u8>>) -> Self {
+        self.disclosed_attributes = Some(attribute_keys);
+        self
+    }
+
+    /// Close the channel when the credentials presented by the other party expire without
+    /// being refreshed.
+    ///
+    /// By default the channel stays open and only logs a warning. The messages of the other
+    /// party are still authenticated with its identifier, but its attributes, which are stored
+    /// with the expiration of its credentials, are not returned anymore. Access controls
+    /// checking its attributes then deny its messages, while access controls only checking
+    /// its identifier still accept them
+    pub fn close_on_expired_credentials(mut self) -> Self {
+        self.close_on_expired_credentials = true;
+        self
+    }
+
     /// Set Trust Policy
     pub fn with_trust_policy(mut self, trust_policy: impl TrustPolicy) -> Self {
         self.trust_policy = Arc::new(trust_policy);
         self
     }
 
+    /// Resume the previous channel created with the same key, usually the address of the
+    /// other party, in one round-trip instead of doing a full handshake.
+    /// The listener of the other party must accept the resumption of channels.
+    /// If the channel cannot be resumed, a full handshake is done
+    pub fn with_resumption(mut self, key: impl Into<String>) -> Self {
+        self.resumption_key = Some(key.into());
+        self
+    }
+
+    /// Sets the lifetime of the ticket created to resume this channel, different from
+    /// the default one [`DEFAULT_RESUMPTION_TICKET_LIFETIME`]
+    pub fn with_resumption_ticket_lifetime(mut self, lifetime: Duration) -> Self {
+        self.resumption_ticket_lifetime = lifetime;
+        self
+    }
+
+    /// Sets the number of messages encrypted with the same key, different from the default
+    /// one [`crate::DEFAULT_KEY_RENEWAL_INTERVAL`].
+    /// It must be between 1 and [`crate::MAX_KEY_RENEWAL_INTERVAL`].
+    /// The other end of the channel accepts messages received out of order within that interval
+    pub fn with_key_renewal_interval(mut self, interval: u64) -> Self {
+        self.key_renewal.interval = interval;
+        self
+    }
+
+    /// Renew the key used to encrypt messages when it is older than the given age
+    pub fn with_max_key_age(mut self, max_key_age: Duration) -> Self {
+        self.key_renewal.max_key_age = Some(max_key_age);
+        self
+    }
+
+    /// Propose a hybrid key exchange to the other end of the channel, where a ML-KEM-768
+    /// shared secret is mixed into the channel keys in addition to the X25519 secrets.
+    /// The channel keys are then protected against an attacker recording the handshake today
+    /// and breaking X25519 later with a quantum computer.
+    /// If the listener doesn't support it, a regular handshake is performed.
+    /// The software vault supports ML-KEM when the `pqc` feature is enabled
+    pub fn with_hybrid_key_exchange(mut self) -> Self {
+        self.hybrid_key_exchange = true;
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn producer_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -83,6 +160,12 @@ impl SecureChannelOptions {
 }
 
 impl SecureChannelOptions {
+    pub(crate) fn resumption(&self) -> Option<Resumption> {
+        self.resumption_key
+            .clone()
+            .map(|key| Resumption::new(Some(key), self.resumption_ticket_lifetime))
+    }
+
     pub(crate) fn setup_flow_control(
         &self,
         flow_controls: &FlowControls,
@@ -130,6 +213,11 @@ pub struct SecureChannelListenerOptions {
     pub(crate) trust_policy: Arc<dyn TrustPolicy>,
     pub(crate) trust_context: Option<TrustContext>,
     pub(crate) credentials: Vec<CredentialAndPurposeKey>,
+    pub(crate) disclosed_attributes: Option<Vec<Vec<u8>>>,
+    pub(crate) close_on_expired_credentials: bool,
+    pub(crate) resumption: bool,
+    pub(crate) resumption_ticket_lifetime: Duration,
+    pub(crate) key_renewal: KeyRenewal,
 }
 
 impl fmt::Debug for SecureChannelListenerOptions {
@@ -150,6 +238,11 @@ impl SecureChannelListenerOptions {
             trust_policy: Arc::new(TrustEveryonePolicy),
             trust_context: None,
             credentials: vec![],
+            disclosed_attributes: None,
+            close_on_expired_credentials: false,
+            resumption: false,
+            resumption_ticket_lifetime: DEFAULT_RESUMPTION_TICKET_LIFETIME,
+            key_renewal: KeyRenewal::default(),
         }
     }
 
@@ -180,12 +273,61 @@ impl SecureChannelListenerOptions {
         self
     }
 
+    /// Only present the given attributes of the credentials which are issued with selective
+    /// disclosure, so that the other party only learns the attributes it needs to check.
+    /// All the attributes are presented by default
+    pub fn with_disclosed_attributes(mut self, attribute_keys: Vec<Vec<u8>>) -> Self {
+        self.disclosed_attributes = Some(attribute_keys);
+        self
+    }
+
+    /// Close the channel when the credentials presented by the other party expire without
+    /// being refreshed.
+    ///
+    /// By default the channel stays open and only logs a warning. The messages of the other
+    /// party are still authenticated with its identifier, but its attributes, which are stored
+    /// with the expiration of its credentials, are not returned anymore. Access controls
+    /// checking its attributes then deny its messages, while access controls only checking
+    /// its identifier still accept them
+    pub fn close_on_expired_credentials(mut self) -> Self {
+        self.close_on_expired_credentials = true;
+        self
+    }
+
     /// Set trust policy
     pub fn with_trust_policy(mut self, trust_policy: impl TrustPolicy) -> Self {
         self.trust_policy = Arc::new(trust_policy);
         self
     }
 
+    /// Accept the resumption of previous channels in one round-trip, when the initiator of a
+    /// channel presents the ticket created at the end of its previous channel with this listener
+    pub fn with_resumption(mut self) -> Self {
+        self.resumption = true;
+        self
+    }
+
+    /// Sets the lifetime of the tickets created to resume the channels, different from
+    /// the default one [`DEFAULT_RESUMPTION_TICKET_LIFETIME`]
+    pub fn with_resumption_ticket_lifetime(mut self, lifetime: Duration) -> Self {
+        self.resumption_ticket_lifetime = lifetime;
+        self
+    }
+
+    /// Sets the number of messages encrypted with the same key on the channels, different from
+    /// the default one [`crate::DEFAULT_KEY_RENEWAL_INTERVAL`].
+    /// It must be between 1 and [`crate::MAX_KEY_RENEWAL_INTERVAL`]
+    pub fn with_key_renewal_interval(mut self, interval: u64) -> Self {
+        self.key_renewal.interval = interval;
+        self
+    }
+
+    /// Renew the key used to encrypt messages on the channels when it is older than the given age
+    pub fn with_max_key_age(mut self, max_key_age: Duration) -> Self {
+        self.key_renewal.max_key_age = Some(max_key_age);
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn spawner_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -193,6 +335,14 @@ impl SecureChannelListenerOptions {
 }
 
 impl SecureChannelListenerOptions {
+    pub(crate) fn resumption(&self) -> Option<Resumption> {
+        if self.resumption {
+            Some(Resumption::new(None, self.resumption_ticket_lifetime))
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn setup_flow_control_for_listener(
         &self,
         flow_controls: &FlowControls,
@@ -252,3 +402,17 @@ impl SecureChannelListenerOptions {
         }
     }
 }
+
+/// Remove the committed attributes of a credential which must not be presented
+pub(crate) fn disclose_attributes(
+    credential: CredentialAndPurposeKey,
+    disclosed_attributes: Option<&[Vec<u8>]>,
+) -> CredentialAndPurposeKey {
+    match disclosed_attributes {
+        Some(attribute_keys) => {
+            let attribute_keys: Vec<&[u8]> = attribute_keys.iter().map(|k| k.as_slice()).collect();
+            credential.disclose(&attribute_keys)
+        }
+        None => credential,
+    }
+}
@@ -0,0 +1,334 @@
+use core::time::Duration;
+use minicbor::{Decode, Encode};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Address, Result};
+use ockam_vault::{SecretBufferHandle, VaultForSecureChannels};
+
+use crate::models::{Identifier, TimestampInSeconds};
+use crate::utils::{add_seconds, now};
+
+/// Default lifetime of the tickets used to resume a secure channel
+pub const DEFAULT_RESUMPTION_TICKET_LIFETIME: Duration = Duration::from_secs(3600);
+
+/// Maximum number of tickets kept by a secure channel listener
+const MAX_RESPONDER_TICKETS: usize = 10_000;
+
+/// Resumption parameters of a secure channel, when the resumption is enabled
+#[derive(Clone, Debug)]
+pub(crate) struct Resumption {
+    /// Name of the other end of the channel on the initiator side, for example its multiaddr.
+    /// A new channel with the same name can be resumed with the ticket of the previous channel
+    pub(crate) key: Option<String>,
+    /// Address of the listener which accepted the channel, on the responder side.
+    /// A ticket can only resume a channel on the listener which created it
+    pub(crate) listener: Option<Address>,
+    /// Lifetime of the tickets created at the end of a handshake
+    pub(crate) lifetime: Duration,
+}
+
+impl Resumption {
+    pub(crate) fn new(key: Option<String>, lifetime: Duration) -> Self {
+        Self {
+            key,
+            listener: None,
+            lifetime,
+        }
+    }
+
+    /// Set the address of the listener accepting the channels
+    pub(crate) fn with_listener(mut self, listener: Address) -> Self {
+        self.listener = Some(listener);
+        self
+    }
+}
+
+/// A ticket allows one end of a previous secure channel to establish a new channel in one
+/// round-trip, with keys derived from a secret shared by both ends at the end of the previous
+/// handshake. A ticket can be used only once: a new ticket is created each time a channel
+/// is established
+#[derive(Clone, Debug)]
+pub(crate) struct ResumptionTicket {
+    /// Identifier of the ticket, derived from the transcript of the previous handshake
+    pub(crate) ticket_id: Vec<u8>,
+    pub(crate) my_identifier: Identifier,
+    pub(crate) their_identifier: Identifier,
+    /// Secret shared by both ends of the previous channel
+    pub(crate) secret: SecretBufferHandle,
+    pub(crate) expires_at: TimestampInSeconds,
+    /// Earliest expiration of the credentials presented by the other end of the previous channel
+    pub(crate) their_credentials_expiration: Option<TimestampInSeconds>,
+    /// Key renewal interval of the other end of the previous channel
+    pub(crate) their_key_renewal_interval: u64,
+    /// Listener which accepted the previous channel, on the responder side
+    pub(crate) listener: Option<Address>,
+    /// Trust context used to verify the credentials of the other end of the previous channel
+    pub(crate) trust_context_id: Option<String>,
+}
+
+impl ResumptionTicket {
+    /// Create a ticket expiring after the given lifetime, or when the credentials of the
+    /// other end of the channel expire
+    pub(crate) fn new(
+        ticket_id: Vec<u8>,
+        my_identifier: Identifier,
+        their_identifier: Identifier,
+        secret: SecretBufferHandle,
+        lifetime: Duration,
+        their_credentials_expiration: Option<TimestampInSeconds>,
+        their_key_renewal_interval: u64,
+    ) -> Result<Self> {
+        let expires_at = add_seconds(&now()?, lifetime.as_secs());
+        let expires_at = match their_credentials_expiration {
+            Some(credentials_expiration) => expires_at.min(credentials_expiration),
+            None => expires_at,
+        };
+        Ok(Self {
+            ticket_id,
+            my_identifier,
+            their_identifier,
+            secret,
+            expires_at,
+            their_credentials_expiration,
+            their_key_renewal_interval,
+            listener: None,
+            trust_context_id: None,
+        })
+    }
+
+    /// Bind the ticket to the listener which accepted the channel and to the trust context
+    /// which verified the credentials of the other end
+    pub(crate) fn with_scope(
+        mut self,
+        listener: Option<Address>,
+        trust_context_id: Option<String>,
+    ) -> Self {
+        self.listener = listener;
+        self.trust_context_id = trust_context_id;
+        self
+    }
+
+    /// Return true if the ticket can resume a channel with the given identity, on the given
+    /// listener and trust context. A ticket created by a listener without a trust context,
+    /// or with a different one, can't bypass the verification of credentials by another listener
+    pub(crate) fn is_in_scope(
+        &self,
+        my_identifier: &Identifier,
+        listener: Option<&Address>,
+        trust_context_id: Option<&str>,
+    ) -> bool {
+        &self.my_identifier == my_identifier
+            && self.listener.as_ref() == listener
+            && self.trust_context_id.as_deref() == trust_context_id
+    }
+
+    fn is_expired(&self, now: TimestampInSeconds) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Data mixed in the resumption handshake, binding it to the ticket, to both identifiers
+    /// and to the key renewal intervals of both parties
+    pub(crate) fn prologue(
+        &self,
+        is_initiator: bool,
+        my_key_renewal_interval: u64,
+        their_key_renewal_interval: u64,
+    ) -> Vec<u8> {
+        let (initiator, responder) = if is_initiator {
+            (
+                (&self.my_identifier, my_key_renewal_interval),
+                (&self.their_identifier, their_key_renewal_interval),
+            )
+        } else {
+            (
+                (&self.their_identifier, their_key_renewal_interval),
+                (&self.my_identifier, my_key_renewal_interval),
+            )
+        };
+        let mut prologue = self.ticket_id.clone();
+        prologue.extend_from_slice(&initiator.0 .0);
+        prologue.extend_from_slice(&responder.0 .0);
+        prologue.extend_from_slice(&initiator.1.to_be_bytes());
+        prologue.extend_from_slice(&responder.1.to_be_bytes());
+        prologue
+    }
+}
+
+/// Request sent in the payload of the first handshake message when the initiator resumes a channel
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub(crate) struct ResumptionRequest {
+    #[cbor(n(1), with = "minicbor::bytes")] pub(crate) ticket_id: Vec<u8>,
+    /// Proof that the initiator knows the secret of the ticket
+    #[cbor(n(2), with = "minicbor::bytes")] pub(crate) tag: Vec<u8>,
+    /// Number of messages encrypted with the same key by the initiator
+    #[n(3)] pub(crate) key_renewal_interval: Option<u64>,
+}
+
+/// Tickets kept by a node to resume secure channels, as an initiator or as a responder
+#[derive(Clone)]
+pub(crate) struct ResumptionTickets {
+    vault: Arc<dyn VaultForSecureChannels>,
+    /// Tickets of the initiator, by identifier and name of the other end of the channel
+    initiator: Arc<RwLock<BTreeMap<(Identifier, String), ResumptionTicket>>>,
+    /// Tickets of the responder, by ticket identifier
+    responder: Arc<RwLock<BTreeMap<Vec<u8>, ResumptionTicket>>>,
+}
+
+impl ResumptionTickets {
+    pub(crate) fn new(vault: Arc<dyn VaultForSecureChannels>) -> Self {
+        Self {
+            vault,
+            initiator: Default::default(),
+            responder: Default::default(),
+        }
+    }
+
+    /// Take the ticket to resume a channel to the given name, if it is not expired
+    pub(crate) async fn take_initiator_ticket(
+        &self,
+        my_identifier: &Identifier,
+        key: &str,
+    ) -> Result<Option<ResumptionTicket>> {
+        let ticket = self
+            .initiator
+            .write()
+            .unwrap()
+            .remove(&(my_identifier.clone(), key.into()));
+        self.unexpired(ticket).await
+    }
+
+    /// Return the ticket with the given identifier, if it is not expired.
+    /// The ticket is kept until it is removed with [`Self::remove_responder_ticket`]
+    pub(crate) async fn get_responder_ticket(
+        &self,
+        ticket_id: &[u8],
+    ) -> Result<Option<ResumptionTicket>> {
+        let ticket = self.responder.read().unwrap().get(ticket_id).cloned();
+        match ticket {
+            Some(ticket) if ticket.is_expired(now()?) => {
+                if let Some(expired) = self.remove_responder_ticket(ticket_id) {
+                    self.delete(expired).await?;
+                }
+                Ok(None)
+            }
+            ticket => Ok(ticket),
+        }
+    }
+
+    /// Remove a ticket once a resumption request using it has been verified.
+    /// Return `None` if the ticket has already been removed
+    pub(crate) fn remove_responder_ticket(&self, ticket_id: &[u8]) -> Option<ResumptionTicket> {
+        self.responder.write().unwrap().remove(ticket_id)
+    }
+
+    /// Keep the ticket to resume the channel to the given name
+    pub(crate) async fn store_initiator_ticket(
+        &self,
+        key: String,
+        ticket: ResumptionTicket,
+    ) -> Result<()> {
+        let replaced = self
+            .initiator
+            .write()
+            .unwrap()
+            .insert((ticket.my_identifier.clone(), key), ticket);
+        if let Some(replaced) = replaced {
+            self.delete(replaced).await?;
+        }
+        Ok(())
+    }
+
+    /// Keep a ticket which the initiator of the channel can use to resume the channel.
+    /// Expired tickets are removed, as well as the oldest tickets when there are too many
+    pub(crate) async fn store_responder_ticket(&self, ticket: ResumptionTicket) -> Result<()> {
+        let now = now()?;
+        let removed = {
+            let mut tickets = self.responder.write().unwrap();
+            let mut removed = Vec::new();
+            let expired: Vec<Vec<u8>> = tickets
+                .values()
+                .filter(|t| t.is_expired(now))
+                .map(|t| t.ticket_id.clone())
+                .collect();
+            for ticket_id in expired {
+                removed.extend(tickets.remove(&ticket_id));
+            }
+            while tickets.len() >= MAX_RESPONDER_TICKETS {
+                let oldest = tickets
+                    .values()
+                    .min_by_key(|t| t.expires_at)
+                    .map(|t| t.ticket_id.clone());
+                match oldest.and_then(|ticket_id| tickets.remove(&ticket_id)) {
+                    Some(ticket) => removed.push(ticket),
+                    None => break,
+                }
+            }
+            removed.extend(tickets.insert(ticket.ticket_id.clone(), ticket));
+            removed
+        };
+        for ticket in removed {
+            self.delete(ticket).await?;
+        }
+        Ok(())
+    }
+
+    async fn unexpired(
+        &self,
+        ticket: Option<ResumptionTicket>,
+    ) -> Result<Option<ResumptionTicket>> {
+        match ticket {
+            Some(ticket) if ticket.is_expired(now()?) => {
+                self.delete(ticket).await?;
+                Ok(None)
+            }
+            ticket => Ok(ticket),
+        }
+    }
+
+    /// Remove the secret of a ticket which will not be used
+    pub(crate) async fn delete(&self, ticket: ResumptionTicket) -> Result<()> {
+        self.vault.delete_secret_buffer(ticket.secret).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::IDENTIFIER_LEN;
+    use crate::secure_channel::DEFAULT_KEY_RENEWAL_INTERVAL;
+    use ockam_vault::SoftwareVaultForSecureChannels;
+
+    #[tokio::test]
+    async fn test_responder_ticket_kept_until_removed() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create();
+        let tickets = ResumptionTickets::new(vault.clone());
+        let secret = vault.import_secret_buffer(vec![1; 32]).await?;
+        let ticket = ResumptionTicket::new(
+            b"ticket".to_vec(),
+            Identifier([1; IDENTIFIER_LEN]),
+            Identifier([2; IDENTIFIER_LEN]),
+            secret,
+            DEFAULT_RESUMPTION_TICKET_LIFETIME,
+            None,
+            DEFAULT_KEY_RENEWAL_INTERVAL,
+        )?;
+        tickets.store_responder_ticket(ticket).await?;
+
+        // a ticket which is only looked up, for example because its request is invalid,
+        // can still be used by the initiator
+        assert!(tickets.get_responder_ticket(b"ticket").await?.is_some());
+        assert!(tickets.get_responder_ticket(b"ticket").await?.is_some());
+
+        // a ticket can only be removed once
+        let ticket = tickets.remove_responder_ticket(b"ticket").unwrap();
+        tickets.delete(ticket).await?;
+        assert!(tickets.remove_responder_ticket(b"ticket").is_none());
+        assert!(tickets.get_responder_ticket(b"ticket").await?.is_none());
+        Ok(())
+    }
+}
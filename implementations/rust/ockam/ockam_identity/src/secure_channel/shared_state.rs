@@ -0,0 +1,87 @@
+use ockam_core::compat::collections::{BTreeSet, VecDeque};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Address;
+
+use crate::secure_channel::message::CloseReason;
+
+/// Maximum number of local workers notified when the channel is closed
+const MAX_CLOSING_SUBSCRIBERS: usize = 1024;
+
+/// State shared by the encryptor and the decryptor of a secure channel
+#[derive(Clone)]
+pub(crate) struct SecureChannelSharedState {
+    /// Reason sent to the other end of the channel when the channel is closed.
+    /// `None` when the other end closed the channel first and must not be notified
+    close_reason: Arc<RwLock<Option<CloseReason>>>,
+    /// Local workers to notify when the channel is closed
+    closing_subscribers: Arc<RwLock<ClosingSubscribers>>,
+}
+
+/// Addresses of the local workers to notify when the channel is closed. The workers
+/// unsubscribe when they stop, and only the most recent subscribers are kept in case
+/// they can't, since workers like TCP portals are created for each connection
+#[derive(Default)]
+struct ClosingSubscribers {
+    addresses: BTreeSet<Address>,
+    order: VecDeque<Address>,
+}
+
+impl SecureChannelSharedState {
+    pub(crate) fn new() -> Self {
+        Self {
+            close_reason: Arc::new(RwLock::new(Some(CloseReason::Closed))),
+            closing_subscribers: Default::default(),
+        }
+    }
+
+    /// Reason to send to the other end of the channel, if it must be notified of the closing
+    pub(crate) fn close_reason(&self) -> Option<CloseReason> {
+        *self.close_reason.read().unwrap()
+    }
+
+    /// Set the reason to send to the other end of the channel when the channel is closed
+    pub(crate) fn set_close_reason(&self, reason: CloseReason) {
+        let mut close_reason = self.close_reason.write().unwrap();
+        if close_reason.is_some() {
+            *close_reason = Some(reason);
+        }
+    }
+
+    /// The other end closed the channel, there is no need to notify it
+    pub(crate) fn closed_by_other_end(&self) {
+        *self.close_reason.write().unwrap() = None;
+    }
+
+    /// Notify a local worker when the channel is closed
+    pub(crate) fn add_closing_subscriber(&self, address: &Address) {
+        let mut subscribers = self.closing_subscribers.write().unwrap();
+        if subscribers.addresses.insert(address.clone()) {
+            subscribers.order.push_back(address.clone());
+            if subscribers.order.len() > MAX_CLOSING_SUBSCRIBERS {
+                if let Some(oldest) = subscribers.order.pop_front() {
+                    subscribers.addresses.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    /// Stop notifying a local worker, once it is stopped
+    pub(crate) fn remove_closing_subscriber(&self, address: &Address) {
+        let mut subscribers = self.closing_subscribers.write().unwrap();
+        if subscribers.addresses.remove(address) {
+            subscribers.order.retain(|a| a != address);
+        }
+    }
+
+    /// Local workers to notify when the channel is closed
+    pub(crate) fn closing_subscribers(&self) -> Vec<Address> {
+        self.closing_subscribers
+            .read()
+            .unwrap()
+            .order
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
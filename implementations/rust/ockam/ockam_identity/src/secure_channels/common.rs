@@ -92,3 +92,50 @@ impl SecureChannelListener {
         &self.flow_control_id
     }
 }
+
+/// Result of [`super::SecureChannels::create_group_member()`] call.
+#[derive(Debug, Clone)]
+pub struct GroupMember {
+    address: Address,
+    encryptor_address: Address,
+    flow_control_id: FlowControlId,
+}
+
+impl fmt::Display for GroupMember {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Worker: {}, Encryptor: {}, FlowId: {}",
+            self.address, self.encryptor_address, self.flow_control_id
+        )
+    }
+}
+
+impl GroupMember {
+    /// Constructor.
+    pub fn new(
+        address: Address,
+        encryptor_address: Address,
+        flow_control_id: FlowControlId,
+    ) -> Self {
+        Self {
+            address,
+            encryptor_address,
+            flow_control_id,
+        }
+    }
+    /// [`Address`] of the group member Worker, receiving the messages of the group admin
+    /// and the encrypted messages of the other members
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+    /// [`Address`] that can be used in a route to encrypt a message with the group key
+    /// and forward it to the rest of the route
+    pub fn encryptor_address(&self) -> &Address {
+        &self.encryptor_address
+    }
+    /// Freshly generated [`FlowControlId`] of the decrypted messages
+    pub fn flow_control_id(&self) -> &FlowControlId {
+        &self.flow_control_id
+    }
+}
@@ -6,17 +6,19 @@ use ockam_node::Context;
 use crate::identities::Identities;
 use crate::models::Identifier;
 use crate::secure_channel::handshake_worker::HandshakeWorker;
+use crate::secure_channel::resumption::ResumptionTickets;
 use crate::secure_channel::{
-    Addresses, IdentityChannelListener, Role, SecureChannelListenerOptions, SecureChannelOptions,
-    SecureChannelRegistry,
+    Addresses, GroupMemberOptions, GroupMemberWorker, IdentityChannelListener, KeyRenewal, Role,
+    SecureChannelListenerOptions, SecureChannelOptions, SecureChannelRegistry,
 };
-use crate::{SecureChannel, SecureChannelListener, SecureChannelsBuilder, Vault};
+use crate::{GroupMember, SecureChannel, SecureChannelListener, SecureChannelsBuilder, Vault};
 
 /// Identity implementation
 #[derive(Clone)]
 pub struct SecureChannels {
     pub(crate) identities: Arc<Identities>,
     pub(crate) secure_channel_registry: SecureChannelRegistry,
+    pub(crate) resumption_tickets: ResumptionTickets,
 }
 
 impl SecureChannels {
@@ -25,9 +27,11 @@ impl SecureChannels {
         identities: Arc<Identities>,
         secure_channel_registry: SecureChannelRegistry,
     ) -> Self {
+        let resumption_tickets = ResumptionTickets::new(identities.vault().secure_channel_vault);
         Self {
             identities,
             secure_channel_registry,
+            resumption_tickets,
         }
     }
 
@@ -66,6 +70,7 @@ impl SecureChannels {
     ) -> Result<SecureChannelListener> {
         let address = address.into();
         let options = options.into();
+        KeyRenewal::check_interval(options.key_renewal.interval)?;
         let flow_control_id = options.flow_control_id.clone();
 
         IdentityChannelListener::create(
@@ -90,6 +95,7 @@ impl SecureChannels {
     ) -> Result<SecureChannel> {
         let addresses = Addresses::generate(Role::Initiator);
         let options = options.into();
+        KeyRenewal::check_interval(options.key_renewal.interval)?;
         let flow_control_id = options.flow_control_id.clone();
 
         let route = route.into();
@@ -114,7 +120,12 @@ impl SecureChannels {
             options.trust_policy,
             access_control.decryptor_outgoing_access_control,
             options.credentials,
+            options.disclosed_attributes,
             options.trust_context,
+            options.close_on_expired_credentials,
+            options.resumption(),
+            options.key_renewal,
+            options.hybrid_key_exchange,
             Some(route),
             Some(options.timeout),
             Role::Initiator,
@@ -132,4 +143,23 @@ impl SecureChannels {
     pub async fn stop_secure_channel(&self, ctx: &Context, channel: &Address) -> Result<()> {
         ctx.stop_worker(channel.clone()).await
     }
+
+    /// Spawns a group member at given `Address` with given [`GroupMemberOptions`].
+    /// The member waits until the admin of a group adds it to the group, see [`crate::GroupAdmin`]
+    pub async fn create_group_member(
+        &self,
+        ctx: &Context,
+        identifier: &Identifier,
+        address: impl Into<Address>,
+        options: impl Into<GroupMemberOptions>,
+    ) -> Result<GroupMember> {
+        GroupMemberWorker::create(
+            ctx,
+            self.vault(),
+            identifier.clone(),
+            address.into(),
+            options.into(),
+        )
+        .await
+    }
 }
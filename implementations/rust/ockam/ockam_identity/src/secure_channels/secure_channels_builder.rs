@@ -4,7 +4,7 @@ use crate::identities::{Identities, IdentitiesRepository};
 use crate::secure_channel::SecureChannelRegistry;
 use crate::secure_channels::SecureChannels;
 use crate::storage::Storage;
-use crate::{IdentitiesBuilder, Vault, VaultStorage};
+use crate::{CredentialSchemas, IdentitiesBuilder, Vault, VaultStorage};
 
 /// This struct supports all the services related to secure channels
 #[derive(Clone)]
@@ -53,6 +53,15 @@ impl SecureChannelsBuilder {
             .with_identities_repository(identities.repository())
             .with_vault(identities.vault())
             .with_purpose_keys_repository(identities.purpose_keys_repository());
+        self.identities_builder.credential_schemas = identities.credential_schemas();
+        self
+    }
+
+    /// Set the schemas used to validate the attributes of credentials
+    pub fn with_credential_schemas(mut self, credential_schemas: CredentialSchemas) -> Self {
+        self.identities_builder = self
+            .identities_builder
+            .with_credential_schemas(credential_schemas);
         self
     }
 
@@ -1,18 +1,23 @@
 use core::time::Duration;
 use ockam_core::compat::sync::Arc;
-use ockam_core::{route, Address, AllowAll, Any, DenyAll, Mailboxes, Result, Routed, Worker};
-use ockam_identity::models::{CredentialSchemaIdentifier, Identifier};
+use ockam_core::{
+    async_trait, route, Address, AllowAll, Any, ChannelClosedLocalInfo, DenyAll, Encodable,
+    LocalMessage, Mailboxes, Result, Routed, TransportMessage, Worker,
+};
+use ockam_identity::models::{CredentialAndPurposeKey, CredentialSchemaIdentifier, Identifier};
 use ockam_identity::secure_channels::secure_channels;
 use ockam_identity::utils::AttributesBuilder;
 use ockam_identity::{
-    AuthorityService, DecryptionResponse, EncryptionRequest, EncryptionResponse,
-    IdentityAccessControlBuilder, IdentitySecureChannelLocalInfo, SecureChannelListenerOptions,
-    SecureChannelOptions, SecureChannels, TrustContext, TrustEveryonePolicy, TrustIdentifierPolicy,
-    Vault,
+    AuthorityService, Credentials, CredentialsRetriever, DecryptionResponse, EncryptionRequest,
+    EncryptionResponse, IdentityAccessControlBuilder, IdentitySecureChannelLocalInfo,
+    SecureChannelListenerOptions, SecureChannelOptions, SecureChannels, TrustContext,
+    TrustEveryonePolicy, TrustIdentifierPolicy, Vault, MAX_KEY_RENEWAL_INTERVAL,
 };
 use ockam_node::{Context, MessageReceiveOptions, WorkerBuilder};
 use ockam_vault::{
+    AeadSecretKeyHandle, HKDFNumberOfOutputs, HashOutput, HkdfOutput, SecretBufferHandle,
     SoftwareVaultForSecureChannels, SoftwareVaultForSigning, SoftwareVaultForVerifyingSignatures,
+    VaultForSecureChannels, X25519PublicKey, X25519SecretKeyHandle,
 };
 use std::sync::atomic::{AtomicU8, Ordering};
 
@@ -85,6 +90,356 @@ async fn test_channel(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_with_different_key_renewal_intervals(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    // each end of the channel uses its own interval, sent to the other end during the handshake
+    let bob_options = SecureChannelListenerOptions::new()
+        .with_key_renewal_interval(7)
+        .with_max_key_age(Duration::from_secs(3600));
+    let bob_listener = secure_channels
+        .create_secure_channel_listener(ctx, bob.identifier(), "bob_listener", bob_options)
+        .await?;
+
+    let alice_options = SecureChannelOptions::new().with_key_renewal_interval(1000);
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            alice_options,
+        )
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("child", bob_listener.flow_control_id());
+    ctx.flow_controls()
+        .add_consumer("child", alice_channel.flow_control_id());
+
+    for n in 0..100 {
+        child_ctx
+            .send(
+                route![alice_channel.clone(), child_ctx.address()],
+                format!("Hello, Bob! {n}"),
+            )
+            .await?;
+        let msg = child_ctx.receive::<String>().await?;
+        assert_eq!(format!("Hello, Bob! {n}"), msg.body());
+
+        child_ctx
+            .send(msg.return_route(), format!("Hello, Alice! {n}"))
+            .await?;
+        let msg = child_ctx.receive::<String>().await?;
+        assert_eq!(format!("Hello, Alice! {n}"), msg.body());
+    }
+
+    // the interval must be between 1 and MAX_KEY_RENEWAL_INTERVAL
+    let result = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_key_renewal_interval(0),
+        )
+        .await;
+    assert!(result.is_err());
+    let result = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "other_listener",
+            SecureChannelListenerOptions::new()
+                .with_key_renewal_interval(MAX_KEY_RENEWAL_INTERVAL + 1),
+        )
+        .await;
+    assert!(result.is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_with_hybrid_key_exchange(ctx: &mut Context) -> Result<()> {
+    let sc_vault = SoftwareVaultForSecureChannels::create();
+    let vault = Vault::new(
+        SoftwareVaultForSigning::create(),
+        sc_vault.clone(),
+        SoftwareVaultForSigning::create(),
+        SoftwareVaultForVerifyingSignatures::create(),
+    );
+    let secure_channels = SecureChannels::builder().with_vault(vault).build();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_hybrid_key_exchange(),
+        )
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("child", bob_listener.flow_control_id());
+    ctx.flow_controls()
+        .add_consumer("child", alice_channel.flow_control_id());
+
+    child_ctx
+        .send(
+            route![alice_channel.clone(), child_ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+    let msg = child_ctx.receive::<String>().await?;
+    assert_eq!("Hello, Bob!", msg.body());
+
+    child_ctx
+        .send(msg.return_route(), "Hello, Alice!".to_string())
+        .await?;
+    let msg = child_ctx.receive::<String>().await?;
+    assert_eq!("Hello, Alice!", msg.body());
+
+    // the ML-KEM key of the initiator is deleted at the end of the handshake
+    assert_eq!(sc_vault.number_of_ephemeral_kem_secrets(), 0);
+
+    ctx.stop().await
+}
+
+/// Vault for secure channels which doesn't implement the optional ML-KEM functions
+struct ClassicalVault(Arc<SoftwareVaultForSecureChannels>);
+
+#[async_trait]
+impl VaultForSecureChannels for ClassicalVault {
+    async fn x25519_ecdh(
+        &self,
+        secret_key_handle: &X25519SecretKeyHandle,
+        peer_public_key: &X25519PublicKey,
+    ) -> Result<SecretBufferHandle> {
+        self.0.x25519_ecdh(secret_key_handle, peer_public_key).await
+    }
+
+    async fn hash(&self, data: &[u8]) -> Result<HashOutput> {
+        self.0.hash(data).await
+    }
+
+    async fn hkdf(
+        &self,
+        salt: &SecretBufferHandle,
+        input_key_material: Option<&SecretBufferHandle>,
+        number_of_outputs: HKDFNumberOfOutputs,
+    ) -> Result<HkdfOutput> {
+        self.0
+            .hkdf(salt, input_key_material, number_of_outputs)
+            .await
+    }
+
+    async fn aead_encrypt(
+        &self,
+        secret_key_handle: &AeadSecretKeyHandle,
+        plain_text: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>> {
+        self.0
+            .aead_encrypt(secret_key_handle, plain_text, nonce, aad)
+            .await
+    }
+
+    async fn aead_decrypt(
+        &self,
+        secret_key_handle: &AeadSecretKeyHandle,
+        cipher_text: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>> {
+        self.0
+            .aead_decrypt(secret_key_handle, cipher_text, nonce, aad)
+            .await
+    }
+
+    async fn generate_static_x25519_secret_key(&self) -> Result<X25519SecretKeyHandle> {
+        self.0.generate_static_x25519_secret_key().await
+    }
+
+    async fn delete_static_x25519_secret_key(
+        &self,
+        secret_key_handle: X25519SecretKeyHandle,
+    ) -> Result<bool> {
+        self.0
+            .delete_static_x25519_secret_key(secret_key_handle)
+            .await
+    }
+
+    async fn generate_ephemeral_x25519_secret_key(&self) -> Result<X25519SecretKeyHandle> {
+        self.0.generate_ephemeral_x25519_secret_key().await
+    }
+
+    async fn delete_ephemeral_x25519_secret_key(
+        &self,
+        secret_key_handle: X25519SecretKeyHandle,
+    ) -> Result<bool> {
+        self.0
+            .delete_ephemeral_x25519_secret_key(secret_key_handle)
+            .await
+    }
+
+    async fn get_x25519_public_key(
+        &self,
+        secret_key_handle: &X25519SecretKeyHandle,
+    ) -> Result<X25519PublicKey> {
+        self.0.get_x25519_public_key(secret_key_handle).await
+    }
+
+    async fn get_x25519_secret_key_handle(
+        &self,
+        public_key: &X25519PublicKey,
+    ) -> Result<X25519SecretKeyHandle> {
+        self.0.get_x25519_secret_key_handle(public_key).await
+    }
+
+    async fn import_secret_buffer(&self, buffer: Vec<u8>) -> Result<SecretBufferHandle> {
+        self.0.import_secret_buffer(buffer).await
+    }
+
+    async fn delete_secret_buffer(&self, secret_buffer_handle: SecretBufferHandle) -> Result<bool> {
+        self.0.delete_secret_buffer(secret_buffer_handle).await
+    }
+
+    async fn convert_secret_buffer_to_aead_key(
+        &self,
+        secret_buffer_handle: SecretBufferHandle,
+    ) -> Result<AeadSecretKeyHandle> {
+        self.0
+            .convert_secret_buffer_to_aead_key(secret_buffer_handle)
+            .await
+    }
+
+    async fn delete_aead_secret_key(&self, secret_key_handle: AeadSecretKeyHandle) -> Result<bool> {
+        self.0.delete_aead_secret_key(secret_key_handle).await
+    }
+}
+
+#[ockam_macros::test]
+async fn test_hybrid_key_exchange_falls_back_without_ml_kem(ctx: &mut Context) -> Result<()> {
+    // Alice's vault supports ML-KEM, Bob's doesn't
+    let alice_sc_vault = SoftwareVaultForSecureChannels::create();
+    let alice_channels = SecureChannels::builder()
+        .with_vault(Vault::new(
+            SoftwareVaultForSigning::create(),
+            alice_sc_vault.clone(),
+            SoftwareVaultForSigning::create(),
+            SoftwareVaultForVerifyingSignatures::create(),
+        ))
+        .build();
+    let bob_channels = SecureChannels::builder()
+        .with_vault(Vault::new(
+            SoftwareVaultForSigning::create(),
+            Arc::new(ClassicalVault(SoftwareVaultForSecureChannels::create())),
+            SoftwareVaultForSigning::create(),
+            SoftwareVaultForVerifyingSignatures::create(),
+        ))
+        .with_identities_repository(alice_channels.identities().repository())
+        .build();
+
+    let alice = alice_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let bob = bob_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+
+    let alice_listener = alice_channels
+        .create_secure_channel_listener(
+            ctx,
+            alice.identifier(),
+            "alice_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+    let bob_listener = bob_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    // The responder can't encapsulate a secret, then the initiator can't generate a key
+    let alice_to_bob = alice_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_hybrid_key_exchange(),
+        )
+        .await?;
+    let bob_to_alice = bob_channels
+        .create_secure_channel(
+            ctx,
+            bob.identifier(),
+            route!["alice_listener"],
+            SecureChannelOptions::new().with_hybrid_key_exchange(),
+        )
+        .await?;
+
+    // The ML-KEM key proposed by Alice is deleted when Bob doesn't use it
+    assert_eq!(alice_sc_vault.number_of_ephemeral_kem_secrets(), 0);
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    for (channel, listener) in [(alice_to_bob, bob_listener), (bob_to_alice, alice_listener)] {
+        ctx.flow_controls()
+            .add_consumer("child", listener.flow_control_id());
+        child_ctx
+            .send(route![channel, child_ctx.address()], "Hello".to_string())
+            .await?;
+        let msg = child_ctx.receive::<String>().await?;
+        assert_eq!("Hello", msg.body());
+    }
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn test_channel_send_credentials(context: &mut Context) -> Result<()> {
     let secure_channels = secure_channels();
@@ -1100,39 +1455,745 @@ async fn should_stop_encryptor__and__decryptor__in__secure_channel(
 
     ctx.sleep(Duration::from_millis(100)).await;
 
+    // The other end of the channel is notified and stops as well
     assert_eq!(
         secure_channels
             .secure_channel_registry()
             .get_channel_list()
             .len(),
-        1
+        0
     );
 
     let workers = ctx.list_workers().await?;
     assert!(!workers.contains(channel1.decryptor_messaging_address()));
     assert!(!workers.contains(channel1.encryptor_messaging_address()));
-    assert!(workers.contains(channel2.decryptor_messaging_address()));
-    assert!(workers.contains(channel2.encryptor_messaging_address()));
+    assert!(!workers.contains(channel2.decryptor_messaging_address()));
+    assert!(!workers.contains(channel2.encryptor_messaging_address()));
 
-    secure_channels
-        .stop_secure_channel(ctx, channel2.encryptor_messaging_address())
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_close_notifies_the_other_end(ctx: &mut Context) -> Result<()> {
+    let alice_sc_vault = SoftwareVaultForSecureChannels::create();
+    let alice_vault = Vault::new(
+        SoftwareVaultForSigning::create(),
+        alice_sc_vault.clone(),
+        SoftwareVaultForSigning::create(),
+        SoftwareVaultForVerifyingSignatures::create(),
+    );
+    let bob_sc_vault = SoftwareVaultForSecureChannels::create();
+    let bob_vault = Vault::new(
+        SoftwareVaultForSigning::create(),
+        bob_sc_vault.clone(),
+        SoftwareVaultForSigning::create(),
+        SoftwareVaultForVerifyingSignatures::create(),
+    );
+
+    let secure_channels_alice = SecureChannels::builder().with_vault(alice_vault).build();
+    let secure_channels_bob = SecureChannels::builder().with_vault(bob_vault).build();
+
+    let alice = secure_channels_alice
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let bob = secure_channels_bob
+        .identities()
+        .identities_creation()
+        .create_identity()
         .await?;
 
-    ctx.sleep(Duration::from_millis(100)).await;
+    let bob_listener = secure_channels_bob
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
 
-    assert_eq!(
-        secure_channels
-            .secure_channel_registry()
-            .get_channel_list()
-            .len(),
-        0
-    );
+    let alice_channel = secure_channels_alice
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
 
-    let workers = ctx.list_workers().await?;
-    assert!(!workers.contains(channel1.decryptor_messaging_address()));
-    assert!(!workers.contains(channel1.encryptor_messaging_address()));
-    assert!(!workers.contains(channel2.decryptor_messaging_address()));
-    assert!(!workers.contains(channel2.encryptor_messaging_address()));
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("child", bob_listener.flow_control_id());
+    ctx.flow_controls()
+        .add_consumer("child", alice_channel.flow_control_id());
+
+    // Subscribe to the closing of alice's channel
+    child_ctx
+        .forward(LocalMessage::new(
+            TransportMessage::v1(
+                route![alice_channel.clone(), "child"],
+                route!["child"],
+                "Hello, Bob!".to_string().encode()?,
+            ),
+            vec![ChannelClosedLocalInfo::subscription()],
+        ))
+        .await?;
+    let msg = child_ctx.receive::<String>().await?;
+    assert_eq!("Hello, Bob!", msg.body());
+
+    assert_eq!(alice_sc_vault.number_of_ephemeral_aead_secrets(), 2);
+    assert_eq!(bob_sc_vault.number_of_ephemeral_aead_secrets(), 2);
+
+    // Bob closes its end of the channel
+    let bob_channel = secure_channels_bob
+        .secure_channel_registry()
+        .get_channel_list()[0]
+        .clone();
+    secure_channels_bob
+        .stop_secure_channel(ctx, bob_channel.encryptor_messaging_address())
+        .await?;
+
+    // The subscriber on alice's side is notified with the reason of the closing
+    let msg = child_ctx.receive::<Any>().await?;
+    let info = ChannelClosedLocalInfo::find_info(msg.local_message()).unwrap();
+    assert_eq!(
+        info.reason(),
+        format!("closed by {}: closed", bob.identifier())
+    );
+
+    ctx.sleep(Duration::from_millis(100)).await;
+
+    // Both ends of the channel are stopped and their keys are removed
+    assert!(secure_channels_alice
+        .secure_channel_registry()
+        .get_channel_list()
+        .is_empty());
+    assert!(secure_channels_bob
+        .secure_channel_registry()
+        .get_channel_list()
+        .is_empty());
+    assert_eq!(alice_sc_vault.number_of_ephemeral_aead_secrets(), 0);
+    assert_eq!(bob_sc_vault.number_of_ephemeral_aead_secrets(), 0);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_close_does_not_notify_unsubscribed_workers(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("child", bob_listener.flow_control_id());
+    ctx.flow_controls()
+        .add_consumer("child", alice_channel.flow_control_id());
+
+    // Subscribe to the closing of alice's channel
+    child_ctx
+        .forward(LocalMessage::new(
+            TransportMessage::v1(
+                route![alice_channel.clone(), "child"],
+                route!["child"],
+                "Hello, Bob!".to_string().encode()?,
+            ),
+            vec![ChannelClosedLocalInfo::subscription()],
+        ))
+        .await?;
+    let msg = child_ctx.receive::<String>().await?;
+    assert_eq!("Hello, Bob!", msg.body());
+
+    // Cancel the subscription, the message is not forwarded to the other end
+    child_ctx
+        .forward(LocalMessage::new(
+            TransportMessage::v1(route![alice_channel.clone()], route!["child"], vec![]),
+            vec![ChannelClosedLocalInfo::unsubscription()],
+        ))
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+
+    secure_channels
+        .stop_secure_channel(ctx, alice_channel.encryptor_address())
+        .await?;
+
+    let result = child_ctx
+        .receive_extended::<Any>(
+            MessageReceiveOptions::new().with_timeout(Duration::from_millis(500)),
+        )
+        .await;
+    assert!(result.is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_resumption(ctx: &mut Context) -> Result<()> {
+    let alice_sc_vault = SoftwareVaultForSecureChannels::create();
+    let alice_vault = Vault::new(
+        SoftwareVaultForSigning::create(),
+        alice_sc_vault.clone(),
+        SoftwareVaultForSigning::create(),
+        SoftwareVaultForVerifyingSignatures::create(),
+    );
+    let bob_sc_vault = SoftwareVaultForSecureChannels::create();
+    let bob_vault = Vault::new(
+        SoftwareVaultForSigning::create(),
+        bob_sc_vault.clone(),
+        SoftwareVaultForSigning::create(),
+        SoftwareVaultForVerifyingSignatures::create(),
+    );
+
+    let secure_channels_alice = SecureChannels::builder().with_vault(alice_vault).build();
+    let secure_channels_bob = SecureChannels::builder().with_vault(bob_vault).build();
+
+    let alice = secure_channels_alice
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let bob = secure_channels_bob
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+
+    let bob_listener = secure_channels_bob
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new()
+                .with_resumption()
+                .with_trust_policy(TrustIdentifierPolicy::new(alice.identifier().clone())),
+        )
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("child", bob_listener.flow_control_id());
+
+    // The first channel is created with a full handshake, the next ones are resumed
+    for _ in 0..3 {
+        let alice_channel = secure_channels_alice
+            .create_secure_channel(
+                ctx,
+                alice.identifier(),
+                route!["bob_listener"],
+                SecureChannelOptions::new()
+                    .with_resumption("bob")
+                    .with_trust_policy(TrustIdentifierPolicy::new(bob.identifier().clone())),
+            )
+            .await?;
+
+        ctx.send(
+            route![alice_channel.clone(), "child"],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+        let msg = child_ctx.receive::<String>().await?;
+        let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+        assert_eq!(&local_info.their_identity_id(), alice.identifier());
+        assert_eq!("Hello, Bob!", msg.body());
+
+        // Each side keeps only one ticket: a ticket can be used only once
+        assert_eq!(alice_sc_vault.number_of_ephemeral_buffer_secrets(), 1);
+        assert_eq!(bob_sc_vault.number_of_ephemeral_buffer_secrets(), 1);
+
+        secure_channels_alice
+            .stop_secure_channel(ctx, alice_channel.encryptor_address())
+            .await?;
+        ctx.sleep(Duration::from_millis(100)).await;
+    }
+
+    // A listener which doesn't accept resumption completes a full handshake instead
+    let plain_listener = secure_channels_bob
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_plain_listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+    ctx.flow_controls()
+        .add_consumer("child", plain_listener.flow_control_id());
+
+    let alice_channel = secure_channels_alice
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_plain_listener"],
+            SecureChannelOptions::new().with_resumption("bob_plain"),
+        )
+        .await?;
+    ctx.send(
+        route![alice_channel, "child"],
+        "Hello again, Bob!".to_string(),
+    )
+    .await?;
+    let msg = child_ctx.receive::<String>().await?;
+    assert_eq!("Hello again, Bob!", msg.body());
+
+    // A ticket can't resume a channel on another listener, which requires credentials.
+    // The full handshake fails since Alice can't verify the credential presented by Bob
+    let authority = secure_channels_bob
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let bob_credential = secure_channels_bob
+        .identities()
+        .credentials()
+        .credentials_creation()
+        .issue_credential(
+            authority.identifier(),
+            bob.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("is_bob", "true")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    let trust_context = TrustContext::new(
+        "test".to_string(),
+        Some(AuthorityService::new(
+            secure_channels_bob.identities().credentials(),
+            authority.identifier().clone(),
+            None,
+        )),
+    );
+    secure_channels_bob
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_trusted_listener",
+            SecureChannelListenerOptions::new()
+                .with_resumption()
+                .with_trust_context(trust_context)
+                .with_credential(bob_credential),
+        )
+        .await?;
+
+    let result = secure_channels_alice
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_trusted_listener"],
+            SecureChannelOptions::new()
+                .with_resumption("bob")
+                .with_timeout(Duration::from_secs(1)),
+        )
+        .await;
+    assert!(result.is_err());
+
+    ctx.stop().await
+}
+
+/// Issue a new short-lived credential each time a credential is retrieved
+struct ShortLivedCredentialsRetriever {
+    credentials: Arc<Credentials>,
+    authority: Identifier,
+}
+
+#[async_trait]
+impl CredentialsRetriever for ShortLivedCredentialsRetriever {
+    async fn retrieve(
+        &self,
+        _ctx: &Context,
+        for_identity: &Identifier,
+    ) -> Result<CredentialAndPurposeKey> {
+        self.credentials
+            .credentials_creation()
+            .issue_credential(
+                &self.authority,
+                for_identity,
+                AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                    .with_attribute("is_alice", "true")
+                    .build(),
+                Duration::from_secs(5),
+            )
+            .await
+    }
+}
+
+#[ockam_macros::test(timeout = 20000)]
+async fn test_channel_refresh_credentials(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let authority = identities_creation.create_identity().await?;
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let alice_trust_context = TrustContext::new(
+        "test".to_string(),
+        Some(AuthorityService::new(
+            secure_channels.identities().credentials(),
+            authority.identifier().clone(),
+            Some(Arc::new(ShortLivedCredentialsRetriever {
+                credentials: secure_channels.identities().credentials(),
+                authority: authority.identifier().clone(),
+            })),
+        )),
+    );
+    let bob_trust_context = TrustContext::new(
+        "test".to_string(),
+        Some(AuthorityService::new(
+            secure_channels.identities().credentials(),
+            authority.identifier().clone(),
+            None,
+        )),
+    );
+
+    let alice_credential = alice_trust_context
+        .get_credential(ctx, alice.identifier())
+        .await
+        .unwrap();
+
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new().with_trust_context(bob_trust_context),
+        )
+        .await?;
+
+    let _alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new()
+                .with_trust_context(alice_trust_context)
+                .with_credential(alice_credential),
+        )
+        .await?;
+
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert!(secure_channels
+        .identities()
+        .repository()
+        .get_attributes(alice.identifier())
+        .await?
+        .is_some());
+
+    // The first credential expires after 5 seconds, but a renewed credential
+    // has been presented through the channel in the meantime
+    ctx.sleep(Duration::from_secs(7)).await;
+    let alice_attributes = secure_channels
+        .identities()
+        .repository()
+        .get_attributes(alice.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(
+        "true".as_bytes(),
+        alice_attributes.attrs().get("is_alice".as_bytes()).unwrap()
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(timeout = 20000)]
+async fn test_channel_closed_on_expired_credentials(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let authority = identities_creation.create_identity().await?;
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let trust_context = TrustContext::new(
+        "test".to_string(),
+        Some(AuthorityService::new(
+            secure_channels.identities().credentials(),
+            authority.identifier().clone(),
+            None,
+        )),
+    );
+
+    let alice_credential = secure_channels
+        .identities()
+        .credentials()
+        .credentials_creation()
+        .issue_credential(
+            authority.identifier(),
+            alice.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("is_alice", "true")
+                .build(),
+            Duration::from_secs(2),
+        )
+        .await?;
+
+    let bob_listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new()
+                .with_trust_context(trust_context.clone())
+                .close_on_expired_credentials(),
+        )
+        .await?;
+
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new()
+                .with_trust_context(trust_context)
+                .with_credential(alice_credential),
+        )
+        .await?;
+
+    let received_count = Arc::new(AtomicU8::new(0));
+    WorkerBuilder::new(Receiver {
+        received_count: received_count.clone(),
+    })
+    .with_address("receiver")
+    .with_outgoing_access_control(DenyAll)
+    .start(ctx)
+    .await?;
+    ctx.flow_controls()
+        .add_consumer("receiver", bob_listener.flow_control_id());
+
+    ctx.send(
+        route![alice_channel.clone(), "receiver"],
+        "Hello".to_string(),
+    )
+    .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(received_count.load(Ordering::Relaxed), 1);
+
+    // Once the credential of Alice is expired, Bob closes the channel
+    ctx.sleep(Duration::from_secs(3)).await;
+    ctx.send(route![alice_channel, "receiver"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(received_count.load(Ordering::Relaxed), 1);
+    assert_eq!(
+        secure_channels
+            .secure_channel_registry()
+            .get_channel_list()
+            .len(),
+        1
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(timeout = 20000)]
+async fn test_channel_open_without_attributes_on_expired_credentials(
+    ctx: &mut Context,
+) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let authority = identities_creation.create_identity().await?;
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let trust_context = TrustContext::new(
+        "test".to_string(),
+        Some(AuthorityService::new(
+            secure_channels.identities().credentials(),
+            authority.identifier().clone(),
+            None,
+        )),
+    );
+
+    let alice_credential = secure_channels
+        .identities()
+        .credentials()
+        .credentials_creation()
+        .issue_credential(
+            authority.identifier(),
+            alice.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("is_alice", "true")
+                .build(),
+            Duration::from_secs(2),
+        )
+        .await?;
+
+    let bob_listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new().with_trust_context(trust_context.clone()),
+        )
+        .await?;
+
+    let alice_channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new()
+                .with_trust_context(trust_context)
+                .with_credential(alice_credential),
+        )
+        .await?;
+
+    let received_count = Arc::new(AtomicU8::new(0));
+    WorkerBuilder::new(Receiver {
+        received_count: received_count.clone(),
+    })
+    .with_address("receiver")
+    .with_outgoing_access_control(DenyAll)
+    .start(ctx)
+    .await?;
+    ctx.flow_controls()
+        .add_consumer("receiver", bob_listener.flow_control_id());
+
+    ctx.send(
+        route![alice_channel.clone(), "receiver"],
+        "Hello".to_string(),
+    )
+    .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(received_count.load(Ordering::Relaxed), 1);
+    assert!(secure_channels
+        .identities()
+        .repository()
+        .get_attributes(alice.identifier())
+        .await?
+        .is_some());
+
+    // Once the credential of Alice is expired, the channel stays open
+    // but the attributes of Alice are not available anymore
+    ctx.sleep(Duration::from_secs(3)).await;
+    ctx.send(route![alice_channel, "receiver"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(received_count.load(Ordering::Relaxed), 2);
+    assert!(secure_channels
+        .identities()
+        .repository()
+        .get_attributes(alice.identifier())
+        .await?
+        .is_none());
+    assert_eq!(
+        secure_channels
+            .secure_channel_registry()
+            .get_channel_list()
+            .len(),
+        2
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_presents_only_the_disclosed_attributes(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let authority = identities_creation.create_identity().await?;
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let trust_context = TrustContext::new(
+        "test".to_string(),
+        Some(AuthorityService::new(
+            secure_channels.identities().credentials(),
+            authority.identifier().clone(),
+            None,
+        )),
+    );
+
+    let alice_credential = secure_channels
+        .identities()
+        .credentials()
+        .credentials_creation()
+        .issue_credential_with_selective_disclosure(
+            authority.identifier(),
+            alice.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("is_alice", "true")
+                .with_attribute("email", "alice@example.com")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+
+    secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            bob.identifier(),
+            "bob_listener",
+            SecureChannelListenerOptions::new().with_trust_context(trust_context.clone()),
+        )
+        .await?;
+
+    // Alice only reveals to Bob the attribute he needs
+    secure_channels
+        .create_secure_channel(
+            ctx,
+            alice.identifier(),
+            route!["bob_listener"],
+            SecureChannelOptions::new()
+                .with_trust_context(trust_context)
+                .with_credential(alice_credential)
+                .with_disclosed_attributes(vec![b"is_alice".to_vec()]),
+        )
+        .await?;
+
+    ctx.sleep(Duration::from_millis(100)).await;
+    let alice_attributes = secure_channels
+        .identities()
+        .repository()
+        .get_attributes(alice.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(alice_attributes.attrs().len(), 1);
+    assert_eq!(
+        "true".as_bytes(),
+        alice_attributes.attrs().get("is_alice".as_bytes()).unwrap()
+    );
 
     ctx.stop().await
 }
@@ -4,12 +4,13 @@ use std::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, Any, DenyAll};
 use ockam_core::{route, Result, Routed, Worker};
-use ockam_identity::models::CredentialSchemaIdentifier;
+use ockam_identity::models::{CredentialSchemaIdentifier, DelegationConstraints};
 use ockam_identity::secure_channels::secure_channels;
 use ockam_identity::utils::AttributesBuilder;
 use ockam_identity::{
-    AuthorityService, CredentialAccessControl, CredentialsMemoryRetriever,
-    SecureChannelListenerOptions, SecureChannelOptions, TrustContext, TrustIdentifierPolicy,
+    AttributeSchema, AttributeType, AuthorityService, CredentialAccessControl, CredentialSchema,
+    CredentialSchemas, CredentialsMemoryRetriever, Identities, SecureChannelListenerOptions,
+    SecureChannelOptions, TrustContext, TrustIdentifierPolicy,
 };
 use ockam_node::{Context, WorkerBuilder};
 
@@ -310,6 +311,136 @@ async fn access_control(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn access_control_with_selective_disclosure(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let identities_repository = identities.repository();
+    let credentials = identities.credentials();
+    let credentials_service = identities.credentials_server();
+
+    let authority = identities_creation.create_identity().await?;
+    let server = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let options = SecureChannelListenerOptions::new();
+    let listener = secure_channels
+        .create_secure_channel_listener(ctx, server.identifier(), "listener", options)
+        .await?;
+
+    let trust_context = TrustContext::new(
+        "test_trust_context_id".to_string(),
+        Some(AuthorityService::new(
+            credentials.clone(),
+            authority.identifier().clone(),
+            None,
+        )),
+    );
+
+    ctx.flow_controls()
+        .add_consumer("credential_exchange", listener.flow_control_id());
+
+    credentials_service
+        .start(
+            ctx,
+            trust_context,
+            server.identifier().clone(),
+            "credential_exchange".into(),
+            false,
+        )
+        .await?;
+
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            client.identifier(),
+            route!["listener"],
+            SecureChannelOptions::new()
+                .with_trust_policy(TrustIdentifierPolicy::new(server.identifier().clone())),
+        )
+        .await?;
+
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential_with_selective_disclosure(
+            authority.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("is_superuser", "true")
+                .with_attribute("email", "client@example.com")
+                .with_attribute("cost_center", "42")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    assert_eq!(credential.disclosed_attribute_keys().len(), 3);
+
+    // Only the committed attributes are signed, not their values
+    let credential_data = credential.get_credential_data()?;
+    assert!(credential_data.subject_attributes.map.is_empty());
+    assert_eq!(credential_data.attribute_commitments.unwrap().len(), 3);
+
+    // A disclosure with another value is rejected
+    let mut tampered = credential.disclose(&[b"is_superuser"]);
+    tampered.disclosures.as_mut().unwrap()[0].value = b"false".to_vec().into();
+    assert!(credentials
+        .credentials_verification()
+        .verify_credential(
+            Some(client.identifier()),
+            &[authority.identifier().clone()],
+            &tampered,
+        )
+        .await
+        .is_err());
+
+    let counter = Arc::new(AtomicI8::new(0));
+
+    let worker = CountingWorker {
+        msgs_count: counter.clone(),
+    };
+
+    let required_attributes = vec![(b"is_superuser".to_vec(), b"true".to_vec())];
+    let access_control =
+        CredentialAccessControl::new(&required_attributes, identities_repository.clone());
+
+    ctx.flow_controls()
+        .add_consumer("counter", listener.flow_control_id());
+
+    WorkerBuilder::new(worker)
+        .with_address("counter")
+        .with_incoming_access_control(access_control)
+        .with_outgoing_access_control(DenyAll)
+        .start(ctx)
+        .await?;
+
+    // The client only reveals the attribute needed by the server
+    credentials_service
+        .present_credential(
+            ctx,
+            route![channel.clone(), "credential_exchange"],
+            credential.disclose(&[b"is_superuser"]),
+        )
+        .await?;
+
+    let attributes = identities_repository
+        .get_attributes(client.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(attributes.attrs().len(), 1);
+    assert_eq!(
+        attributes.attrs().get("is_superuser".as_bytes()).unwrap(),
+        b"true"
+    );
+
+    ctx.send(route![channel, "counter"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+    ctx.stop().await
+}
+
 struct CountingWorker {
     msgs_count: Arc<AtomicI8>,
 }
@@ -329,3 +460,400 @@ impl Worker for CountingWorker {
         Ok(())
     }
 }
+
+#[tokio::test]
+async fn verify_credential_with_schema() -> Result<()> {
+    let schema = CredentialSchema::new(CredentialSchemaIdentifier(42)).with_attribute(
+        "max_connections",
+        AttributeSchema {
+            required: true,
+            max: Some(100),
+            ..AttributeSchema::new(AttributeType::Integer)
+        },
+    );
+    let identities = Identities::builder()
+        .with_credential_schemas(CredentialSchemas::new().with_schema(schema))
+        .build();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    // A value which does not have the type declared by the schema is rejected
+    let invalid = credentials
+        .credentials_creation()
+        .issue_credential(
+            authority.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(42))
+                .with_attribute("max_connections", "many")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    assert!(credentials
+        .credentials_verification()
+        .verify_credential(
+            Some(client.identifier()),
+            &[authority.identifier().clone()],
+            &invalid,
+        )
+        .await
+        .is_err());
+
+    // A valid credential is accepted and its schema is stored with the subject attributes
+    let valid = credentials
+        .credentials_creation()
+        .issue_credential(
+            authority.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(42))
+                .with_attribute("max_connections", "10")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    credentials
+        .credentials_verification()
+        .receive_presented_credential(
+            client.identifier(),
+            &[authority.identifier().clone()],
+            &valid,
+        )
+        .await?;
+
+    let entry = identities
+        .repository()
+        .as_attributes_reader()
+        .get_attributes(client.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(entry.schema(), Some(CredentialSchemaIdentifier(42)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn verify_delegated_credential() -> Result<()> {
+    let identities = Identities::builder().build();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+    let credentials_creation = credentials.credentials_creation();
+    let credentials_verification = credentials.credentials_verification();
+
+    let root = identities_creation.create_identity().await?;
+    let regional = identities_creation.create_identity().await?;
+    let local = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+    let authorities = [root.identifier().clone()];
+
+    // The root authority lets a regional team issue "eu-" roles, and delegate once more
+    let regional_delegation = credentials_creation
+        .issue_delegation(
+            root.identifier(),
+            regional.identifier(),
+            DelegationConstraints::new(Duration::from_secs(3600))
+                .with_attribute_prefix("role", "eu-")
+                .with_max_depth(1),
+            Duration::from_secs(24 * 3600),
+            None,
+        )
+        .await?;
+
+    let credential = credentials_creation
+        .issue_delegated_credential(
+            regional.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("role", "eu-admin")
+                .build(),
+            Duration::from_secs(60),
+            &regional_delegation,
+        )
+        .await?;
+    let data = credentials_verification
+        .verify_credential(Some(client.identifier()), &authorities, &credential)
+        .await?;
+    assert_eq!(&data.purpose_key_data.subject, regional.identifier());
+
+    // Attributes and lifetimes which are not delegated can't be issued
+    assert!(credentials_creation
+        .issue_delegated_credential(
+            regional.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("role", "us-admin")
+                .build(),
+            Duration::from_secs(60),
+            &regional_delegation,
+        )
+        .await
+        .is_err());
+    assert!(credentials_creation
+        .issue_delegated_credential(
+            regional.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("role", "eu-admin")
+                .build(),
+            Duration::from_secs(7200),
+            &regional_delegation,
+        )
+        .await
+        .is_err());
+
+    // nor verified when the sub-authority attaches its delegation to them
+    let mut forged = credentials_creation
+        .issue_credential(
+            regional.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("role", "us-admin")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    assert!(credentials_verification
+        .verify_credential(Some(client.identifier()), &authorities, &forged)
+        .await
+        .is_err());
+    forged.delegation = Some(Box::new(regional_delegation.clone()));
+    assert!(credentials_verification
+        .verify_credential(Some(client.identifier()), &authorities, &forged)
+        .await
+        .is_err());
+
+    // The regional team can only delegate narrower constraints
+    assert!(credentials_creation
+        .issue_delegation(
+            regional.identifier(),
+            local.identifier(),
+            DelegationConstraints::new(Duration::from_secs(3600)).with_attribute("role"),
+            Duration::from_secs(3600),
+            Some(&regional_delegation),
+        )
+        .await
+        .is_err());
+    let local_delegation = credentials_creation
+        .issue_delegation(
+            regional.identifier(),
+            local.identifier(),
+            DelegationConstraints::new(Duration::from_secs(600))
+                .with_attribute_prefix("role", "eu-west-"),
+            Duration::from_secs(3600),
+            Some(&regional_delegation),
+        )
+        .await?;
+
+    // The chain is verified up to the root authority
+    let credential = credentials_creation
+        .issue_delegated_credential(
+            local.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("role", "eu-west-reader")
+                .build(),
+            Duration::from_secs(60),
+            &local_delegation,
+        )
+        .await?;
+    credentials_verification
+        .verify_credential(Some(client.identifier()), &authorities, &credential)
+        .await?;
+
+    // It stops at the first trusted authority, and fails if none is found
+    let data = credentials_verification
+        .verify_credential(
+            Some(client.identifier()),
+            &[regional.identifier().clone()],
+            &credential,
+        )
+        .await?;
+    assert_eq!(&data.purpose_key_data.subject, local.identifier());
+    assert!(credentials_verification
+        .verify_credential(
+            Some(client.identifier()),
+            &[client.identifier().clone()],
+            &credential,
+        )
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn verify_delegated_credential_knowing_only_the_authority() -> Result<()> {
+    let identities = Identities::builder().build();
+    let identities_creation = identities.identities_creation();
+    let credentials_creation = identities.credentials().credentials_creation();
+
+    let root = identities_creation.create_identity().await?;
+    let regional = identities_creation.create_identity().await?;
+    let local = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    // The verifier only knows the root authority
+    let verifier = Identities::builder().build();
+    verifier
+        .identities_creation()
+        .import(Some(root.identifier()), &root.export()?)
+        .await?;
+    let credentials_verification = verifier.credentials().credentials_verification();
+    let authorities = [root.identifier().clone()];
+
+    let regional_delegation = credentials_creation
+        .issue_delegation(
+            root.identifier(),
+            regional.identifier(),
+            DelegationConstraints::new(Duration::from_secs(3600))
+                .with_attribute_value("trust_context_id", root.identifier().to_string())
+                .with_attribute("role")
+                .with_max_depth(1),
+            Duration::from_secs(3600),
+            None,
+        )
+        .await?;
+    let local_delegation = credentials_creation
+        .issue_delegation(
+            regional.identifier(),
+            local.identifier(),
+            DelegationConstraints::new(Duration::from_secs(600))
+                .with_attribute_value("trust_context_id", root.identifier().to_string())
+                .with_attribute("role"),
+            Duration::from_secs(3600),
+            Some(&regional_delegation),
+        )
+        .await?;
+
+    // The sub-authorities present their change history with the credentials they issue
+    let credential = credentials_creation
+        .issue_delegated_credential(
+            local.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("trust_context_id", root.identifier().to_string())
+                .with_attribute("role", "reader")
+                .build(),
+            Duration::from_secs(60),
+            &local_delegation,
+        )
+        .await?;
+    let data = credentials_verification
+        .verify_credential(Some(client.identifier()), &authorities, &credential)
+        .await?;
+    assert_eq!(&data.purpose_key_data.subject, local.identifier());
+
+    // A change history which is not the one of the issuer is rejected
+    let mut forged = credential.clone();
+    forged.issuer_change_history = local_delegation.issuer_change_history.clone();
+    assert!(credentials_verification
+        .verify_credential(Some(client.identifier()), &authorities, &forged)
+        .await
+        .is_err());
+    forged.issuer_change_history = None;
+    assert!(credentials_verification
+        .verify_credential(Some(client.identifier()), &authorities, &forged)
+        .await
+        .is_err());
+
+    // The trust context id must be exactly the one of the root authority
+    assert!(credentials_creation
+        .issue_delegated_credential(
+            local.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("trust_context_id", format!("{}x", root.identifier()))
+                .build(),
+            Duration::from_secs(60),
+            &local_delegation,
+        )
+        .await
+        .is_err());
+    assert!(credentials_creation
+        .issue_delegation(
+            regional.identifier(),
+            local.identifier(),
+            DelegationConstraints::new(Duration::from_secs(600))
+                .with_attribute_prefix("trust_context_id", root.identifier().to_string()),
+            Duration::from_secs(3600),
+            Some(&regional_delegation),
+        )
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn verify_delegated_credential_after_a_key_rotation_of_the_issuer() -> Result<()> {
+    let identities = Identities::builder().build();
+    let identities_creation = identities.identities_creation();
+    let credentials_creation = identities.credentials().credentials_creation();
+
+    let root = identities_creation.create_identity().await?;
+    let local = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    // The verifier knows the root authority and a previous version of the sub-authority
+    let verifier = Identities::builder().build();
+    verifier
+        .identities_creation()
+        .import(Some(root.identifier()), &root.export()?)
+        .await?;
+    verifier
+        .identities_creation()
+        .import(Some(local.identifier()), &local.export()?)
+        .await?;
+    let credentials_verification = verifier.credentials().credentials_verification();
+    let authorities = [root.identifier().clone()];
+
+    let local_delegation = credentials_creation
+        .issue_delegation(
+            root.identifier(),
+            local.identifier(),
+            DelegationConstraints::new(Duration::from_secs(600)).with_attribute("role"),
+            Duration::from_secs(3600),
+            None,
+        )
+        .await?;
+
+    // The sub-authority rotates its key, the change history presented with the credential
+    // extends the known one and replaces it
+    identities_creation
+        .rotate_identity(local.identifier())
+        .await?;
+    let rotated = identities.get_identity(local.identifier()).await?;
+    let credential = credentials_creation
+        .issue_delegated_credential(
+            local.identifier(),
+            client.identifier(),
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("role", "reader")
+                .build(),
+            Duration::from_secs(60),
+            &local_delegation,
+        )
+        .await?;
+    credentials_verification
+        .verify_credential(Some(client.identifier()), &authorities, &credential)
+        .await?;
+    assert_eq!(
+        verifier
+            .get_identity(local.identifier())
+            .await?
+            .change_history(),
+        rotated.change_history()
+    );
+
+    // A change history older than the known one is rejected
+    let mut outdated = credential.clone();
+    outdated.issuer_change_history = Some(local.change_history().clone());
+    assert!(credentials_verification
+        .verify_credential(Some(client.identifier()), &authorities, &outdated)
+        .await
+        .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{
+    async_trait, route, AllowAll, Any, LocalMessage, Mailboxes, Result, Route, Routed,
+    TransportMessage, Worker,
+};
+use ockam_identity::secure_channels::secure_channels;
+use ockam_identity::{
+    GroupAdmin, GroupMember, GroupMemberOptions, Identity, IdentitySecureChannelLocalInfo,
+    SecureChannelListenerOptions, SecureChannelOptions, SecureChannels, TrustIdentifierPolicy,
+};
+use ockam_node::{Context, MessageReceiveOptions, WorkerBuilder};
+
+#[ockam_macros::test]
+async fn test_group_channel(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels();
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let admin = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+    let carol = identities_creation.create_identity().await?;
+    let dave = identities_creation.create_identity().await?;
+
+    // the admin adds each member over a secure channel
+    let group = GroupAdmin::new();
+    let bob_member = create_member(ctx, &secure_channels, &admin, &bob, "bob").await?;
+    let carol_member = create_member(ctx, &secure_channels, &admin, &carol, "carol").await?;
+    let dave_member = create_member(ctx, &secure_channels, &admin, &dave, "dave").await?;
+    for name in ["bob", "carol", "dave"] {
+        let channel = secure_channels
+            .create_secure_channel(
+                ctx,
+                admin.identifier(),
+                route![format!("{name}_listener")],
+                SecureChannelOptions::new(),
+            )
+            .await?;
+        group
+            .add_member(ctx, route![channel, format!("{name}_group")])
+            .await?;
+    }
+    assert_eq!(group.members().await.len(), 3);
+
+    // a message encrypted once by bob is delivered to carol and dave
+    let mut carol_ctx = create_app(ctx, &carol_member, "carol_app").await?;
+    let mut dave_ctx = create_app(ctx, &dave_member, "dave_app").await?;
+    WorkerBuilder::new(FanOut {
+        routes: vec![
+            route![carol_member.address().clone(), "carol_app"],
+            route![dave_member.address().clone(), "dave_app"],
+        ],
+    })
+    .with_address("fanout")
+    .start(ctx)
+    .await?;
+
+    ctx.send(
+        route![bob_member.encryptor_address().clone(), "fanout"],
+        "Hello, group!".to_string(),
+    )
+    .await?;
+    for app_ctx in [&mut carol_ctx, &mut dave_ctx] {
+        let msg = app_ctx.receive::<String>().await?;
+        let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+        assert_eq!(&local_info.their_identity_id(), bob.identifier());
+        assert_eq!("Hello, group!", msg.body());
+    }
+
+    // once dave is removed, he cannot decrypt the messages of the group anymore
+    group.remove_member(ctx, dave.identifier()).await?;
+    assert_eq!(group.members().await.len(), 2);
+
+    ctx.send(
+        route![carol_member.encryptor_address().clone(), "fanout"],
+        "Hello again!".to_string(),
+    )
+    .await?;
+    let msg = carol_ctx.receive::<String>().await?;
+    let local_info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+    assert_eq!(&local_info.their_identity_id(), carol.identifier());
+    assert_eq!("Hello again!", msg.body());
+
+    let result = dave_ctx
+        .receive_extended::<String>(
+            MessageReceiveOptions::new().with_timeout(Duration::from_millis(200)),
+        )
+        .await;
+    assert!(result.is_err());
+
+    // dave cannot send messages to the group anymore
+    let result = ctx
+        .send(
+            route![dave_member.encryptor_address().clone(), "fanout"],
+            "I'm still here".to_string(),
+        )
+        .await;
+    assert!(result.is_ok());
+    let result = carol_ctx
+        .receive_extended::<String>(
+            MessageReceiveOptions::new().with_timeout(Duration::from_millis(200)),
+        )
+        .await;
+    assert!(result.is_err());
+
+    ctx.stop().await
+}
+
+/// Create a group member with a secure channel listener, accepting to join the groups of the admin
+async fn create_member(
+    ctx: &Context,
+    secure_channels: &SecureChannels,
+    admin: &Identity,
+    member: &Identity,
+    name: &str,
+) -> Result<GroupMember> {
+    let listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            member.identifier(),
+            format!("{name}_listener"),
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+    let options = GroupMemberOptions::new()
+        .as_consumer(listener.flow_control_id())
+        .with_trust_policy(TrustIdentifierPolicy::new(admin.identifier().clone()));
+    secure_channels
+        .create_group_member(ctx, member.identifier(), format!("{name}_group"), options)
+        .await
+}
+
+/// Create a context receiving the messages decrypted by a group member
+async fn create_app(ctx: &Context, member: &GroupMember, address: &str) -> Result<Context> {
+    let app_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            address,
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    ctx.flow_controls()
+        .add_consumer(address, member.flow_control_id());
+    Ok(app_ctx)
+}
+
+/// Forward the same message to several routes, like a relay used by the members of a group
+struct FanOut {
+    routes: Vec<Route>,
+}
+
+#[async_trait]
+impl Worker for FanOut {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let return_route = msg.return_route();
+        let payload = msg.into_transport_message().payload;
+        for route in &self.routes {
+            let message =
+                TransportMessage::v1(route.clone(), return_route.clone(), payload.clone());
+            ctx.forward(LocalMessage::new(message, vec![])).await?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use super::{Buffer, Checked, Code, Codec, Protocol};
-use crate::proto::{DnsAddr, Node, Project, Secure, Service, Space, Tcp, Worker};
+use crate::proto::{DnsAddr, Node, Project, Secure, Service, Space, Tcp, Unix, Worker, Ws};
 use crate::{Error, ProtoValue};
 use core::fmt;
 use unsigned_varint::decode;
@@ -9,9 +9,13 @@ pub struct StdCodec;
 impl Codec for StdCodec {
     fn split_str<'a>(
         &self,
-        _prefix: &str,
+        prefix: &str,
         input: &'a str,
     ) -> Result<(Checked<&'a str>, &'a str), Error> {
+        // The ws protocol has no value, the input only contains the next protocols
+        if prefix == Ws::PREFIX {
+            return Ok((Checked(""), input));
+        }
         if let Some(p) = input.find('/') {
             let (x, y) = input.split_at(p);
             Ok((Checked(x), y))
@@ -49,13 +53,15 @@ impl Codec for StdCodec {
                 let (x, y) = input.split_at(2);
                 Ok((Checked(x), y))
             }
+            Ws::CODE => Ok((Checked(&input[..0]), input)),
             c @ Worker::CODE
             | c @ DnsAddr::CODE
             | c @ Service::CODE
             | c @ Node::CODE
             | c @ Project::CODE
             | c @ Space::CODE
-            | c @ Secure::CODE => {
+            | c @ Secure::CODE
+            | c @ Unix::CODE => {
                 let (len, input) = decode::usize(input)?;
                 if input.len() < len {
                     return Err(Error::required_bytes(c, len));
@@ -81,6 +87,8 @@ impl Codec for StdCodec {
             Project::CODE => Project::read_bytes(input).is_ok(),
             Space::CODE => Space::read_bytes(input).is_ok(),
             Secure::CODE => Secure::read_bytes(input).is_ok(),
+            Ws::CODE => Ws::read_bytes(input).is_ok(),
+            Unix::CODE => Unix::read_bytes(input).is_ok(),
             _ => false,
         }
     }
@@ -99,6 +107,8 @@ impl Codec for StdCodec {
             Project::CODE => Project::read_bytes(val.data())?.write_bytes(buf),
             Space::CODE => Space::read_bytes(val.data())?.write_bytes(buf),
             Secure::CODE => Secure::read_bytes(val.data())?.write_bytes(buf),
+            Ws::CODE => Ws::read_bytes(val.data())?.write_bytes(buf),
+            Unix::CODE => Unix::read_bytes(val.data())?.write_bytes(buf),
             code => return Err(Error::unregistered(code)),
         }
         Ok(())
@@ -153,6 +163,14 @@ impl Codec for StdCodec {
                 Secure::read_str(value)?.write_bytes(buf);
                 Ok(())
             }
+            Ws::PREFIX => {
+                Ws::read_str(value)?.write_bytes(buf);
+                Ok(())
+            }
+            Unix::PREFIX => {
+                Unix::read_str(value)?.write_bytes(buf);
+                Ok(())
+            }
             _ => Err(Error::unregistered_prefix(prefix)),
         }
     }
@@ -206,6 +224,14 @@ impl Codec for StdCodec {
                 Secure::read_bytes(value)?.write_str(f)?;
                 Ok(())
             }
+            Ws::CODE => {
+                Ws::read_bytes(value)?.write_str(f)?;
+                Ok(())
+            }
+            Unix::CODE => {
+                Unix::read_bytes(value)?.write_str(f)?;
+                Ok(())
+            }
             _ => Err(Error::unregistered(code)),
         }
     }
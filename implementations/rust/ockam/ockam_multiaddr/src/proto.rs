@@ -1,6 +1,8 @@
 use super::{Buffer, Checked, Code, Protocol};
 use crate::Error;
 use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::fmt;
 use core::ops::Deref;
 use core::str::{self, FromStr};
@@ -153,6 +155,127 @@ impl Protocol<'_> for Tcp {
     }
 }
 
+/// The WebSocket protocol, over the preceding `/tcp` port, e.g. `/ip4/127.0.0.1/tcp/80/ws`.
+///
+/// It has no value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Ws;
+
+impl Ws {
+    pub fn new() -> Self {
+        Ws
+    }
+}
+
+impl Protocol<'_> for Ws {
+    const CODE: Code = Code::new(477);
+    const PREFIX: &'static str = "ws";
+
+    fn read_str(input: Checked<&str>) -> Result<Self, Error> {
+        if input.is_empty() {
+            Ok(Ws)
+        } else {
+            Err(Error::message("the ws protocol has no value"))
+        }
+    }
+
+    fn read_bytes(input: Checked<&[u8]>) -> Result<Self, Error> {
+        if input.is_empty() {
+            Ok(Ws)
+        } else {
+            Err(Error::message("the ws protocol has no value"))
+        }
+    }
+
+    fn write_str(&self, f: &mut fmt::Formatter) -> Result<(), Error> {
+        write!(f, "/{}", Self::PREFIX)?;
+        Ok(())
+    }
+
+    fn write_bytes(&self, buf: &mut dyn Buffer) {
+        let mut b = encode::u32_buffer();
+        let uvi = encode::u32(Self::CODE.into(), &mut b);
+        buf.extend_with(uvi);
+    }
+}
+
+/// A Unix domain socket path.
+///
+/// In its string form the path is percent-encoded, i.e. `/` is written
+/// as `%2F` and `%` as `%25`, e.g. `/unix/%2Ftmp%2Fockam.sock`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Unix<'a>(Cow<'a, str>);
+
+impl<'a> Unix<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(s: S) -> Self {
+        Self(s.into())
+    }
+}
+
+impl Deref for Unix<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a> Protocol<'a> for Unix<'a> {
+    const CODE: Code = Code::new(400);
+    const PREFIX: &'static str = "unix";
+
+    fn read_str(input: Checked<&'a str>) -> Result<Self, Error> {
+        if !input.contains('%') {
+            return Ok(Self(Cow::Borrowed(input.0)));
+        }
+        let mut bytes = Vec::with_capacity(input.len());
+        let mut iter = input.bytes();
+        while let Some(b) = iter.next() {
+            if b != b'%' {
+                bytes.push(b);
+                continue;
+            }
+            let hex = [
+                iter.next()
+                    .ok_or_else(|| Error::message("invalid percent-encoding"))?,
+                iter.next()
+                    .ok_or_else(|| Error::message("invalid percent-encoding"))?,
+            ];
+            let hex = str::from_utf8(&hex).map_err(Error::message)?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(Error::message)?);
+        }
+        let s = String::from_utf8(bytes).map_err(Error::message)?;
+        Ok(Self(Cow::Owned(s)))
+    }
+
+    fn read_bytes(input: Checked<&'a [u8]>) -> Result<Self, Error> {
+        let s = str::from_utf8(&input).map_err(Error::message)?;
+        Ok(Self(Cow::Borrowed(s)))
+    }
+
+    fn write_str(&self, f: &mut fmt::Formatter) -> Result<(), Error> {
+        write!(f, "/{}/", Self::PREFIX)?;
+        for c in self.0.chars() {
+            match c {
+                '/' => f.write_str("%2F")?,
+                '%' => f.write_str("%25")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_bytes(&self, buf: &mut dyn Buffer) {
+        let mut b = encode::u32_buffer();
+        let uvi = encode::u32(Self::CODE.into(), &mut b);
+        buf.extend_with(uvi);
+        let mut b = encode::usize_buffer();
+        let uvi = encode::usize(self.0.len(), &mut b);
+        buf.extend_with(uvi);
+        buf.extend_with(self.0.as_bytes())
+    }
+}
+
 macro_rules! gen_str_proto {
     ($t:ident, $c:literal, $p:literal) => {
         #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -1,5 +1,7 @@
 use core::fmt;
-use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp};
+use ockam_multiaddr::proto::{
+    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Unix, Ws,
+};
 use ockam_multiaddr::{Code, Match, MultiAddr, Protocol};
 use quickcheck::{quickcheck, Arbitrary, Gen};
 use rand::distributions::{Alphanumeric, DistString};
@@ -132,6 +134,14 @@ quickcheck! {
                         addr.push_back(Space::new("space")).unwrap();
                         prot.push_back(Space::CODE);
                     }
+                    Ws::CODE => {
+                        addr.push_back(Ws::new()).unwrap();
+                        prot.push_back(Ws::CODE);
+                    }
+                    Unix::CODE => {
+                        addr.push_back(Unix::new("/tmp/ockam.sock")).unwrap();
+                        prot.push_back(Unix::CODE);
+                    }
                     _ => unreachable!()
                 }
             }
@@ -153,6 +163,8 @@ const PROTOS: &[Code] = &[
     Node::CODE,
     Project::CODE,
     Space::CODE,
+    Ws::CODE,
+    Unix::CODE,
 ];
 
 impl Arbitrary for Addr {
@@ -169,6 +181,8 @@ impl Arbitrary for Addr {
                 Project::CODE => a.push_back(Project::new(gen_string())).unwrap(),
                 Space::CODE => a.push_back(Space::new(gen_string())).unwrap(),
                 Node::CODE => a.push_back(Node::new(gen_string())).unwrap(),
+                Ws::CODE => a.push_back(Ws::new()).unwrap(),
+                Unix::CODE => a.push_back(Unix::new(gen_path())).unwrap(),
                 _ => unreachable!(),
             }
         }
@@ -220,3 +234,31 @@ fn gen_string() -> String {
     s.retain(|c| c != '/');
     s
 }
+
+fn gen_path() -> String {
+    let mut g = rand::thread_rng();
+    let mut v = vec![String::new()];
+    for _ in 1..=g.gen_range(1..=5) {
+        v.push(Alphanumeric.sample_string(&mut g, 8))
+    }
+    v.push(String::from("100%.sock"));
+    v.join("/")
+}
+
+#[test]
+fn unix_path_is_percent_encoded() {
+    let addr = MultiAddr::from_str("/unix/%2Ftmp%2Fockam%25.sock/service/api").unwrap();
+    let path = addr.first().unwrap().cast::<Unix>().unwrap();
+    assert_eq!(&*path, "/tmp/ockam%.sock");
+    assert_eq!(addr.to_string(), "/unix/%2Ftmp%2Fockam%25.sock/service/api");
+}
+
+#[test]
+fn ws_follows_the_tcp_port() {
+    let addr = MultiAddr::from_str("/ip4/127.0.0.1/tcp/8080/ws/service/api").unwrap();
+    let codes: Vec<Code> = addr.iter().map(|p| p.code()).collect();
+    assert_eq!(codes, vec![Ip4::CODE, Tcp::CODE, Ws::CODE, Service::CODE]);
+    assert_eq!(addr.to_string(), "/ip4/127.0.0.1/tcp/8080/ws/service/api");
+    assert_eq!(addr.to_socket_addr().unwrap(), "127.0.0.1:8080");
+    assert!(MultiAddr::from_str("/ip4/127.0.0.1/ws/8080").is_err());
+}
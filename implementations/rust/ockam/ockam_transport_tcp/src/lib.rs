@@ -24,7 +24,9 @@ mod registry;
 mod transport;
 
 use ockam_core::TransportType;
-pub use options::{TcpConnectionOptions, TcpListenerOptions};
+pub use options::{
+    TcpConnectionOptions, TcpKeepaliveOptions, TcpListenerOptions, TcpReconnectOptions,
+};
 pub use portal::{PortalInternalMessage, PortalMessage, MAX_PAYLOAD_SIZE};
 pub use registry::*;
 pub use transport::common::*;
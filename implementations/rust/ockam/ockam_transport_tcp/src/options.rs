@@ -1,4 +1,5 @@
 use crate::workers::Addresses;
+use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
 use ockam_core::{Address, AllowAll, IncomingAccessControl, OutgoingAccessControl};
@@ -9,10 +10,12 @@ pub(crate) struct TcpConnectionAccessControl {
 }
 
 /// Trust Options for a TCP connection
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TcpConnectionOptions {
     pub(super) consumer: Vec<FlowControlId>,
     pub(crate) flow_control_id: FlowControlId,
+    pub(crate) reconnect: Option<TcpReconnectOptions>,
+    pub(crate) liveness: TcpLivenessOptions,
 }
 
 impl TcpConnectionOptions {
@@ -22,9 +25,43 @@ impl TcpConnectionOptions {
         Self {
             consumer: vec![],
             flow_control_id: FlowControls::generate_flow_control_id(),
+            reconnect: None,
+            liveness: TcpLivenessOptions::default(),
         }
     }
 
+    /// Re-establish the connection when it drops, according to the given policy.
+    /// The connection then keeps the same sender [`Address`] across reconnections
+    pub fn with_reconnect(mut self, reconnect: TcpReconnectOptions) -> Self {
+        self.reconnect = Some(reconnect);
+        self
+    }
+
+    /// Configure the TCP keepalive probes of the socket. Keepalive is enabled by default
+    pub fn with_keepalive(mut self, keepalive: TcpKeepaliveOptions) -> Self {
+        self.liveness.keepalive = Some(keepalive);
+        self
+    }
+
+    /// Disable the TCP keepalive probes of the socket
+    pub fn without_keepalive(mut self) -> Self {
+        self.liveness.keepalive = None;
+        self
+    }
+
+    /// Send a heartbeat to the peer when nothing was sent during the given interval
+    pub fn with_heartbeat_interval(mut self, heartbeat_interval: Duration) -> Self {
+        self.liveness.heartbeat_interval = Some(heartbeat_interval);
+        self
+    }
+
+    /// Close the connection when nothing, not even a heartbeat, was received from
+    /// the peer during the given duration
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.liveness.idle_timeout = Some(idle_timeout);
+        self
+    }
+
     /// Mark that this Connection is a Consumer for to the given [`FlowControlId`]
     pub fn as_consumer(mut self, id: &FlowControlId) -> Self {
         self.consumer.push(id.clone());
@@ -40,15 +77,22 @@ impl TcpConnectionOptions {
 
 impl TcpConnectionOptions {
     pub(crate) fn setup_flow_control(&self, flow_controls: &FlowControls, addresses: &Addresses) {
+        let mut additional_addresses = vec![addresses.sender_address().clone()];
+        if addresses.return_address() != addresses.sender_address() {
+            additional_addresses.push(addresses.return_address().clone());
+        }
         flow_controls.add_producer(
             addresses.receiver_address().clone(),
             &self.flow_control_id,
             None,
-            vec![addresses.sender_address().clone()],
+            additional_addresses,
         );
 
         for id in &self.consumer {
             flow_controls.add_consumer(addresses.sender_address().clone(), id);
+            if addresses.return_address() != addresses.sender_address() {
+                flow_controls.add_consumer(addresses.return_address().clone(), id);
+            }
         }
     }
 
@@ -67,10 +111,139 @@ impl TcpConnectionOptions {
     }
 }
 
+/// Reconnection policy of a TCP connection, see [`TcpConnectionOptions::with_reconnect`]
+///
+/// Reconnection attempts are delayed with an exponential backoff, starting at the
+/// initial delay and doubling after each failed attempt, up to the maximum delay.
+/// The peer hostname is resolved again before each attempt.
+#[derive(Clone, Debug)]
+pub struct TcpReconnectOptions {
+    pub(crate) initial_delay: Duration,
+    pub(crate) max_delay: Duration,
+    pub(crate) max_attempts: Option<usize>,
+    pub(crate) max_buffered_messages: usize,
+}
+
+impl TcpReconnectOptions {
+    /// Retry forever, starting after 1 second and waiting at most 1 minute between
+    /// attempts. Up to 256 messages are kept while the connection is down.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            max_attempts: None,
+            max_buffered_messages: 256,
+        }
+    }
+
+    /// Delay before the first reconnection attempt
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// Upper bound on the delay between two reconnection attempts
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Give up, and close the connection, after that many failed attempts in a row
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Number of messages kept while the connection is down. Older messages are
+    /// dropped first
+    pub fn with_max_buffered_messages(mut self, max_buffered_messages: usize) -> Self {
+        self.max_buffered_messages = max_buffered_messages;
+        self
+    }
+
+    /// Delay before the given reconnection attempt, starting at 0
+    pub(crate) fn delay(&self, attempt: usize) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.min(u32::MAX as usize) as u32);
+        self.initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    /// Return true if no more attempts should be made after that many failures
+    pub(crate) fn is_exhausted(&self, failed_attempts: usize) -> bool {
+        self.max_attempts
+            .map(|max_attempts| failed_attempts >= max_attempts)
+            .unwrap_or(false)
+    }
+}
+
+/// TCP keepalive settings of a connection socket
+///
+/// Once the connection has been idle for the keepalive time, probes are sent at the
+/// given interval, and the connection is dropped after that many unanswered probes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpKeepaliveOptions {
+    pub(crate) time: Duration,
+    pub(crate) interval: Duration,
+    pub(crate) retries: u32,
+}
+
+impl TcpKeepaliveOptions {
+    /// Probe after 5 minutes of inactivity, then every 75 seconds, up to 2 times
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            time: Duration::from_secs(300),
+            interval: Duration::from_secs(75),
+            retries: 2,
+        }
+    }
+
+    /// Inactivity duration before the first probe is sent
+    pub fn with_time(mut self, time: Duration) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Interval between two probes
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Number of unanswered probes before the connection is dropped.
+    /// This setting is ignored on Windows
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+}
+
+/// Settings used to detect dead peers, shared by connections and listeners
+#[derive(Clone, Debug)]
+pub(crate) struct TcpLivenessOptions {
+    pub(crate) keepalive: Option<TcpKeepaliveOptions>,
+    pub(crate) heartbeat_interval: Option<Duration>,
+    pub(crate) idle_timeout: Option<Duration>,
+}
+
+impl Default for TcpLivenessOptions {
+    fn default() -> Self {
+        Self {
+            keepalive: Some(TcpKeepaliveOptions::new()),
+            heartbeat_interval: None,
+            idle_timeout: None,
+        }
+    }
+}
+
 /// Trust Options for a TCP listener
 #[derive(Debug)]
 pub struct TcpListenerOptions {
     pub(crate) flow_control_id: FlowControlId,
+    pub(crate) liveness: TcpLivenessOptions,
 }
 
 impl TcpListenerOptions {
@@ -81,9 +254,37 @@ impl TcpListenerOptions {
     pub fn new() -> Self {
         Self {
             flow_control_id: FlowControls::generate_flow_control_id(),
+            liveness: TcpLivenessOptions::default(),
         }
     }
 
+    /// Configure the TCP keepalive probes of accepted connections.
+    /// Keepalive is enabled by default
+    pub fn with_keepalive(mut self, keepalive: TcpKeepaliveOptions) -> Self {
+        self.liveness.keepalive = Some(keepalive);
+        self
+    }
+
+    /// Disable the TCP keepalive probes of accepted connections
+    pub fn without_keepalive(mut self) -> Self {
+        self.liveness.keepalive = None;
+        self
+    }
+
+    /// Send a heartbeat to the peer of an accepted connection when nothing was sent
+    /// during the given interval
+    pub fn with_heartbeat_interval(mut self, heartbeat_interval: Duration) -> Self {
+        self.liveness.heartbeat_interval = Some(heartbeat_interval);
+        self
+    }
+
+    /// Close accepted connections when nothing, not even a heartbeat, was received
+    /// from the peer during the given duration
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.liveness.idle_timeout = Some(idle_timeout);
+        self
+    }
+
     /// Getter for freshly generated [`FlowControlId`]
     pub fn spawner_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -131,3 +332,30 @@ impl TcpListenerOptions {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_delay_grows_exponentially_up_to_the_maximum() {
+        let options = TcpReconnectOptions::new()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(1));
+
+        assert_eq!(options.delay(0), Duration::from_millis(100));
+        assert_eq!(options.delay(1), Duration::from_millis(200));
+        assert_eq!(options.delay(3), Duration::from_millis(800));
+        assert_eq!(options.delay(4), Duration::from_secs(1));
+        assert_eq!(options.delay(1000), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn reconnect_attempts_are_unlimited_by_default() {
+        assert!(!TcpReconnectOptions::new().is_exhausted(usize::MAX));
+
+        let options = TcpReconnectOptions::new().with_max_attempts(3);
+        assert!(!options.is_exhausted(2));
+        assert!(options.is_exhausted(3));
+    }
+}
@@ -3,10 +3,11 @@ use crate::{portal::TcpPortalRecvProcessor, PortalInternalMessage, PortalMessage
 use core::time::Duration;
 use ockam_core::compat::{boxed::Box, net::SocketAddr, sync::Arc};
 use ockam_core::{
-    async_trait, AllowAll, AllowOnwardAddresses, AllowSourceAddress, Decodable, DenyAll,
-    IncomingAccessControl, Mailbox, Mailboxes,
+    async_trait, AllowAll, AllowOnwardAddresses, AllowSourceAddress, ChannelClosedLocalInfo,
+    Decodable, DenyAll, Encodable, IncomingAccessControl, LocalMessage, Mailbox, Mailboxes,
+    TransportMessage,
 };
-use ockam_core::{Any, Result, Route, Routed, Worker};
+use ockam_core::{Address, Any, Result, Route, Routed, Worker};
 use ockam_node::{Context, ProcessorBuilder, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use tokio::io::AsyncWriteExt;
@@ -42,6 +43,8 @@ pub(crate) struct TcpPortalWorker {
     peer: SocketAddr,
     addresses: Addresses,
     remote_route: Option<Route>,
+    /// First hop of the route to the other side, notifying this worker when it is closed
+    subscribed_channel: Option<Address>,
     is_disconnecting: bool,
     portal_type: PortalType,
 }
@@ -127,6 +130,7 @@ impl TcpPortalWorker {
             peer,
             addresses: addresses.clone(),
             remote_route: None,
+            subscribed_channel: None,
             is_disconnecting: false,
             portal_type,
         };
@@ -157,6 +161,7 @@ enum DisconnectionReason {
     FailedTx,
     FailedRx,
     Remote,
+    ChannelClosed,
 }
 
 impl TcpPortalWorker {
@@ -259,6 +264,10 @@ impl TcpPortalWorker {
             DisconnectionReason::Remote => {
                 self.stop_receiver(ctx).await?;
             }
+            DisconnectionReason::ChannelClosed => {
+                self.subscribed_channel = None;
+                self.stop_receiver(ctx).await?;
+            }
         }
 
         ctx.stop_worker(self.addresses.internal.clone()).await?;
@@ -272,14 +281,53 @@ impl TcpPortalWorker {
         Ok(())
     }
 
-    async fn handle_send_ping(&self, ctx: &Context, ping_route: Route) -> Result<State> {
-        // Force creation of Outlet on the other side
-        ctx.send_from_address(
-            ping_route,
-            PortalMessage::Ping,
+    /// Send a message to the other side of the portal, and subscribe to the closing
+    /// of the channels, like secure channels, that the message is sent through
+    async fn send_to_remote(
+        &mut self,
+        ctx: &Context,
+        route: Route,
+        msg: PortalMessage,
+    ) -> Result<()> {
+        self.subscribed_channel = Some(route.next()?.clone());
+        let msg = TransportMessage::v1(route, self.addresses.remote.clone(), msg.encode()?);
+        ctx.forward_from_address(
+            LocalMessage::new(msg, vec![ChannelClosedLocalInfo::subscription()]),
             self.addresses.remote.clone(),
         )
-        .await?;
+        .await
+    }
+
+    /// Tell the channel used to reach the other side that this worker doesn't need to be
+    /// notified of its closing anymore. The message is dropped by a TCP connection,
+    /// since it has no onward route after its first hop
+    async fn unsubscribe_from_channel(&mut self, ctx: &Context) {
+        let channel = match self.subscribed_channel.take() {
+            Some(channel) => channel,
+            None => return,
+        };
+        let msg = TransportMessage::v1(channel.clone(), self.addresses.remote.clone(), vec![]);
+        if let Err(e) = ctx
+            .forward_from_address(
+                LocalMessage::new(msg, vec![ChannelClosedLocalInfo::unsubscription()]),
+                self.addresses.remote.clone(),
+            )
+            .await
+        {
+            debug!(
+                "{:?} at: {} could not unsubscribe from {}: {}",
+                self.portal_type.str(),
+                self.addresses.internal,
+                channel,
+                e
+            );
+        }
+    }
+
+    async fn handle_send_ping(&mut self, ctx: &Context, ping_route: Route) -> Result<State> {
+        // Force creation of Outlet on the other side
+        self.send_to_remote(ctx, ping_route, PortalMessage::Ping)
+            .await?;
 
         debug!("Inlet at: {} sent ping", self.addresses.internal);
 
@@ -288,12 +336,8 @@ impl TcpPortalWorker {
 
     async fn handle_send_pong(&mut self, ctx: &Context, pong_route: Route) -> Result<State> {
         // Respond to Inlet
-        ctx.send_from_address(
-            pong_route.clone(),
-            PortalMessage::Pong,
-            self.addresses.remote.clone(),
-        )
-        .await?;
+        self.send_to_remote(ctx, pong_route.clone(), PortalMessage::Pong)
+            .await?;
 
         if self.write_half.is_none() {
             let stream = TcpStream::connect(self.peer)
@@ -343,8 +387,9 @@ impl Worker for TcpPortalWorker {
         Ok(())
     }
 
-    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
         self.registry.remove_portal_worker(&self.addresses.remote);
+        self.unsubscribe_from_channel(ctx).await;
 
         Ok(())
     }
@@ -356,6 +401,19 @@ impl Worker for TcpPortalWorker {
             return Ok(());
         }
 
+        // The channel used to reach the other side of the portal, like a secure channel, was closed
+        if let Some(info) = ChannelClosedLocalInfo::find_info(msg.local_message()) {
+            info!(
+                "{:?} at: {} stopped because its channel was closed: {}",
+                self.portal_type.str(),
+                self.addresses.internal,
+                info.reason()
+            );
+            return self
+                .start_disconnection(ctx, DisconnectionReason::ChannelClosed)
+                .await;
+        }
+
         // Remove our own address from the route so the other end
         // knows what to do with the incoming message
         let mut onward_route = msg.onward_route();
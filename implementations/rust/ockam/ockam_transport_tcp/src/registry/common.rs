@@ -157,3 +157,40 @@ impl TcpListenerInfo {
         &self.flow_control_id
     }
 }
+
+/// Information about a TCP connection which is re-established when it drops
+#[derive(Debug, Clone)]
+pub struct TcpReconnectingConnectionInfo {
+    address: Address,
+    peer: String,
+    subscribers: Vec<Address>,
+}
+
+impl TcpReconnectingConnectionInfo {
+    /// Constructor
+    pub fn new(address: Address, peer: String) -> Self {
+        Self {
+            address,
+            peer,
+            subscribers: vec![],
+        }
+    }
+
+    /// Stable address of the connection, which survives reconnections
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+    /// Peer the connection is re-established to
+    pub fn peer(&self) -> &str {
+        &self.peer
+    }
+    /// Addresses notified with [`TcpConnectionEvent`](crate::TcpConnectionEvent)s
+    pub fn subscribers(&self) -> &[Address] {
+        &self.subscribers
+    }
+    pub(crate) fn add_subscriber(&mut self, subscriber: Address) {
+        if !self.subscribers.contains(&subscriber) {
+            self.subscribers.push(subscriber);
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{TcpListenerInfo, TcpReceiverInfo, TcpRegistry, TcpSenderInfo};
+use crate::{
+    TcpListenerInfo, TcpReceiverInfo, TcpReconnectingConnectionInfo, TcpRegistry, TcpSenderInfo,
+};
 use ockam_core::Address;
 
 impl TcpRegistry {
@@ -72,4 +74,31 @@ impl TcpRegistry {
             lock.remove_receiver_processor(addr);
         }
     }
+    pub(crate) fn add_reconnecting_connection(&self, info: TcpReconnectingConnectionInfo) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.add_reconnecting_connection(info);
+        }
+    }
+    pub(crate) fn remove_reconnecting_connection(&self, addr: &Address) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.remove_reconnecting_connection(addr);
+        }
+    }
+    pub(crate) fn add_connection_subscriber(&self, addr: &Address, subscriber: Address) -> bool {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.add_connection_subscriber(addr, subscriber)
+        } else {
+            false
+        }
+    }
+    pub(crate) fn get_connection_subscribers(&self, addr: &Address) -> Vec<Address> {
+        self.registry
+            .read()
+            .unwrap()
+            .reconnecting_connections
+            .iter()
+            .find(|x| x.address() == addr)
+            .map(|x| x.subscribers().to_vec())
+            .unwrap_or_default()
+    }
 }
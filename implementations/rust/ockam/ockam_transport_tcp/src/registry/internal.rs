@@ -1,4 +1,4 @@
-use crate::{TcpListenerInfo, TcpReceiverInfo, TcpSenderInfo};
+use crate::{TcpListenerInfo, TcpReceiverInfo, TcpReconnectingConnectionInfo, TcpSenderInfo};
 use ockam_core::Address;
 
 #[derive(Default)]
@@ -10,6 +10,7 @@ pub(super) struct InternalRegistry {
     pub(super) listener_processors: Vec<TcpListenerInfo>,
     pub(super) sender_workers: Vec<TcpSenderInfo>,
     pub(super) receiver_processors: Vec<TcpReceiverInfo>,
+    pub(super) reconnecting_connections: Vec<TcpReconnectingConnectionInfo>,
 }
 
 impl InternalRegistry {
@@ -55,4 +56,28 @@ impl InternalRegistry {
     pub(super) fn remove_receiver_processor(&mut self, addr: &Address) {
         self.receiver_processors.retain(|x| x.address() != addr);
     }
+    pub(super) fn add_reconnecting_connection(&mut self, info: TcpReconnectingConnectionInfo) {
+        self.reconnecting_connections.push(info)
+    }
+    pub(super) fn remove_reconnecting_connection(&mut self, addr: &Address) {
+        self.reconnecting_connections
+            .retain(|x| x.address() != addr);
+    }
+    pub(super) fn add_connection_subscriber(
+        &mut self,
+        addr: &Address,
+        subscriber: Address,
+    ) -> bool {
+        match self
+            .reconnecting_connections
+            .iter_mut()
+            .find(|x| x.address() == addr)
+        {
+            Some(info) => {
+                info.add_subscriber(subscriber);
+                true
+            }
+            None => false,
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use crate::registry::internal::InternalRegistry;
-use crate::{TcpListenerInfo, TcpReceiverInfo, TcpSenderInfo};
+use crate::{TcpListenerInfo, TcpReceiverInfo, TcpReconnectingConnectionInfo, TcpSenderInfo};
 use ockam_core::compat::sync::{Arc, RwLock};
 
 /// Registry of all active workers and processors in TCP Transport to ease their lifecycle management
@@ -23,4 +23,13 @@ impl TcpRegistry {
     pub fn get_all_listeners(&self) -> Vec<TcpListenerInfo> {
         self.registry.read().unwrap().listener_processors.clone()
     }
+
+    /// Return the connections which are re-established when they drop
+    pub fn get_all_reconnecting_connections(&self) -> Vec<TcpReconnectingConnectionInfo> {
+        self.registry
+            .read()
+            .unwrap()
+            .reconnecting_connections
+            .clone()
+    }
 }
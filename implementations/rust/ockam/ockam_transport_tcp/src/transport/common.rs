@@ -2,9 +2,11 @@ use crate::TcpConnectionMode;
 use core::fmt;
 use core::fmt::Formatter;
 use ockam_core::compat::net::{SocketAddr, ToSocketAddrs};
+use ockam_core::compat::string::String;
 use ockam_core::flow_control::FlowControlId;
-use ockam_core::{Address, Result};
+use ockam_core::{Address, Message, Result};
 use ockam_transport_core::TransportError;
+use serde::{Deserialize, Serialize};
 
 /// Result of [`TcpTransport::connect`] call.
 #[derive(Clone, Debug)]
@@ -72,6 +74,30 @@ impl TcpConnection {
     }
 }
 
+/// Lifecycle event of a TCP connection which is re-established when it drops, sent
+/// to the subscribers registered with [`TcpTransport::subscribe_to_connection_events`](crate::TcpTransport::subscribe_to_connection_events)
+#[derive(Serialize, Deserialize, Message, Clone, Debug, PartialEq, Eq)]
+pub enum TcpConnectionEvent {
+    /// The connection dropped, messages are buffered until it is re-established
+    Disconnected {
+        /// Stable address of the connection
+        address: Address,
+    },
+    /// The connection was re-established
+    Reconnected {
+        /// Stable address of the connection
+        address: Address,
+        /// Socket address the connection was re-established to
+        socket_address: String,
+    },
+    /// The connection is closed for good, either because the reconnection attempts
+    /// were exhausted or because it was disconnected
+    Closed {
+        /// Stable address of the connection
+        address: Address,
+    },
+}
+
 /// Result of [`TcpTransport::listen`] call.
 #[derive(Clone, Debug)]
 pub struct TcpListener {
@@ -1,7 +1,10 @@
 use crate::transport::common::{resolve_peer, TcpConnection};
-use crate::workers::{Addresses, TcpRecvProcessor, TcpSendWorker};
-use crate::{TcpConnectionMode, TcpConnectionOptions, TcpTransport};
+use crate::workers::{Addresses, TcpReconnectWorker, TcpRecvProcessor, TcpSendWorker};
+use crate::{TcpConnectionMode, TcpConnectionOptions, TcpRegistry, TcpTransport};
+use ockam_core::compat::net::SocketAddr;
 use ockam_core::{Address, Result};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
 
 impl TcpTransport {
     /// Establish an outgoing TCP connection.
@@ -16,53 +19,68 @@ impl TcpTransport {
     /// let connection = tcp.connect("127.0.0.1:5000", TcpConnectionOptions::new()).await?; // and connect to port 5000
     /// # Ok(()) }
     /// ```
+    ///
+    /// When the options enable reconnection with
+    /// [`TcpConnectionOptions::with_reconnect`], the sender address of the returned
+    /// connection stays valid when the connection drops and is re-established.
     pub async fn connect(
         &self,
         peer: impl Into<String>,
         options: TcpConnectionOptions,
     ) -> Result<TcpConnection> {
+        let peer = peer.into();
         // Resolve peer address
-        let socket = resolve_peer(peer.into())?;
-
-        let (read_half, write_half) = TcpSendWorker::connect(socket).await?;
-
+        let socket = resolve_peer(peer.clone())?;
         let mode = TcpConnectionMode::Outgoing;
-        let addresses = Addresses::generate(mode);
 
-        options.setup_flow_control(self.ctx.flow_controls(), &addresses);
-        let flow_control_id = options.flow_control_id.clone();
-        let access_control = options.create_access_control(self.ctx.flow_controls());
+        let reconnect = match options.reconnect.clone() {
+            Some(reconnect) => reconnect,
+            None => {
+                let addresses = Addresses::generate(mode);
+                return connect_socket(
+                    &self.ctx,
+                    self.registry.clone(),
+                    socket,
+                    &addresses,
+                    options,
+                )
+                .await;
+            }
+        };
 
-        TcpSendWorker::start(
+        let stable_address = Address::random_tagged("TcpReconnectWorker");
+        let internal_address = Address::random_tagged("TcpReconnectWorker_int_addr");
+        let addresses = Addresses::generate_reconnecting(
+            mode,
+            stable_address.clone(),
+            internal_address.clone(),
+        );
+        let connection = connect_socket(
             &self.ctx,
             self.registry.clone(),
-            write_half,
-            &addresses,
             socket,
-            mode,
-            access_control.sender_incoming_access_control,
-            &flow_control_id,
+            &addresses,
+            options.clone(),
         )
         .await?;
 
-        TcpRecvProcessor::start(
+        TcpReconnectWorker::start(
             &self.ctx,
             self.registry.clone(),
-            read_half,
-            &addresses,
-            socket,
-            mode,
-            &flow_control_id,
-            access_control.receiver_outgoing_access_control,
+            peer,
+            options,
+            reconnect,
+            addresses,
+            internal_address,
         )
         .await?;
 
         Ok(TcpConnection::new(
-            addresses.sender_address().clone(),
-            addresses.receiver_address().clone(),
+            stable_address,
+            connection.receiver_address().clone(),
             socket,
             mode,
-            flow_control_id,
+            connection.flow_control_id().clone(),
         ))
     }
 
@@ -70,4 +88,75 @@ impl TcpTransport {
     pub async fn disconnect(&self, address: impl Into<Address>) -> Result<()> {
         self.ctx.stop_worker(address.into()).await
     }
+
+    /// Send [`TcpConnectionEvent`](crate::TcpConnectionEvent)s about a connection
+    /// created with [`TcpConnectionOptions::with_reconnect`] to the given address
+    pub fn subscribe_to_connection_events(
+        &self,
+        connection: &Address,
+        subscriber: impl Into<Address>,
+    ) -> Result<()> {
+        if self
+            .registry
+            .add_connection_subscriber(connection, subscriber.into())
+        {
+            Ok(())
+        } else {
+            Err(TransportError::UnknownRoute.into())
+        }
+    }
+}
+
+/// Open a TCP connection to the given socket address and start its Sender and
+/// Receiver with the given addresses
+pub(crate) async fn connect_socket(
+    ctx: &Context,
+    registry: TcpRegistry,
+    socket: SocketAddr,
+    addresses: &Addresses,
+    options: TcpConnectionOptions,
+) -> Result<TcpConnection> {
+    let liveness = options.liveness.clone();
+    let (read_half, write_half) =
+        TcpSendWorker::connect(socket, liveness.keepalive.as_ref()).await?;
+
+    let mode = TcpConnectionMode::Outgoing;
+
+    options.setup_flow_control(ctx.flow_controls(), addresses);
+    let flow_control_id = options.flow_control_id.clone();
+    let access_control = options.create_access_control(ctx.flow_controls());
+
+    TcpSendWorker::start(
+        ctx,
+        registry.clone(),
+        write_half,
+        addresses,
+        socket,
+        mode,
+        access_control.sender_incoming_access_control,
+        &flow_control_id,
+        liveness.heartbeat_interval,
+    )
+    .await?;
+
+    TcpRecvProcessor::start(
+        ctx,
+        registry,
+        read_half,
+        addresses,
+        socket,
+        mode,
+        &flow_control_id,
+        access_control.receiver_outgoing_access_control,
+        liveness.idle_timeout,
+    )
+    .await?;
+
+    Ok(TcpConnection::new(
+        addresses.sender_address().clone(),
+        addresses.receiver_address().clone(),
+        socket,
+        mode,
+        flow_control_id,
+    ))
 }
@@ -5,6 +5,7 @@ mod listener;
 mod portals;
 
 pub use common::*;
+pub(crate) use connection::connect_socket;
 
 pub use crate::portal::options::*;
 
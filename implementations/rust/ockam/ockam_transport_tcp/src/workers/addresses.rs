@@ -11,6 +11,12 @@ pub(crate) struct Addresses {
     receiver_address: Address,
     /// Receiver Processor Internal Address (to send messages to the Sender)
     receiver_internal_address: Address,
+    /// Address prepended by the Receiver to the return route of incoming messages.
+    /// This is the Sender address, unless the connection is re-established when it drops
+    return_address: Address,
+    /// Address notified when the Sender stops, for connections which are
+    /// re-established when they drop
+    close_notification_address: Option<Address>,
 }
 
 impl Addresses {
@@ -23,10 +29,27 @@ impl Addresses {
             Address::random_tagged(&format!("TcpRecvProcessor_int_addr_{}", mode));
 
         Self {
+            return_address: sender_address.clone(),
             sender_address,
             sender_internal_address,
             receiver_address,
             receiver_internal_address,
+            close_notification_address: None,
+        }
+    }
+
+    /// Addresses of a connection which is re-established when it drops: incoming
+    /// messages are returned through the stable `return_address` and the
+    /// `close_notification_address` is notified when the connection drops
+    pub(crate) fn generate_reconnecting(
+        mode: TcpConnectionMode,
+        return_address: Address,
+        close_notification_address: Address,
+    ) -> Self {
+        Self {
+            return_address,
+            close_notification_address: Some(close_notification_address),
+            ..Self::generate(mode)
         }
     }
     pub fn sender_internal_address(&self) -> &Address {
@@ -41,4 +64,10 @@ impl Addresses {
     pub fn receiver_internal_address(&self) -> &Address {
         &self.receiver_internal_address
     }
+    pub fn return_address(&self) -> &Address {
+        &self.return_address
+    }
+    pub fn close_notification_address(&self) -> Option<&Address> {
+        self.close_notification_address.as_ref()
+    }
 }
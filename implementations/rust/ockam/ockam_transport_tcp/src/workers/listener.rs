@@ -1,11 +1,11 @@
-use crate::workers::{Addresses, TcpRecvProcessor};
+use crate::workers::{set_keepalive, Addresses, TcpRecvProcessor};
 use crate::{TcpConnectionMode, TcpListenerInfo, TcpListenerOptions, TcpRegistry, TcpSendWorker};
 use ockam_core::{async_trait, compat::net::SocketAddr};
 use ockam_core::{Address, Processor, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 use tokio::net::TcpListener;
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// A TCP Listen processor
 ///
@@ -77,6 +77,12 @@ impl Processor for TcpListenProcessor {
         let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
         debug!("TCP connection accepted");
 
+        if let Some(keepalive) = &self.options.liveness.keepalive {
+            if let Err(err) = set_keepalive(&stream, keepalive) {
+                warn!("Couldn't enable keepalive for {}: {}", peer, err);
+            }
+        }
+
         let mode = TcpConnectionMode::Incoming;
         let addresses = Addresses::generate(mode);
 
@@ -99,6 +105,7 @@ impl Processor for TcpListenProcessor {
             mode,
             access_control.sender_incoming_access_control,
             &receiver_flow_control_id,
+            self.options.liveness.heartbeat_interval,
         )
         .await?;
 
@@ -112,6 +119,7 @@ impl Processor for TcpListenProcessor {
             mode,
             &receiver_flow_control_id,
             access_control.receiver_outgoing_access_control,
+            self.options.liveness.idle_timeout,
         )
         .await?;
 
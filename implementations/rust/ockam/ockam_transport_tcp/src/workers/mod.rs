@@ -1,9 +1,11 @@
 mod addresses;
 mod listener;
 mod receiver;
+mod reconnect;
 mod sender;
 
 pub(crate) use addresses::*;
 pub(crate) use listener::*;
 pub(crate) use receiver::*;
+pub(crate) use reconnect::*;
 pub(crate) use sender::*;
@@ -1,5 +1,6 @@
 use crate::workers::Addresses;
 use crate::{TcpConnectionMode, TcpReceiverInfo, TcpRegistry, TcpSendWorkerMsg};
+use core::time::Duration;
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::FlowControlId;
@@ -10,7 +11,7 @@ use ockam_core::{Decodable, LocalMessage, Processor, Result, TransportMessage};
 use ockam_node::{Context, ProcessorBuilder};
 use ockam_transport_core::TransportError;
 use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
-use tracing::{error, info, trace};
+use tracing::{error, info, trace, warn};
 
 /// A TCP receiving message processor
 ///
@@ -27,6 +28,7 @@ pub(crate) struct TcpRecvProcessor {
     addresses: Addresses,
     mode: TcpConnectionMode,
     flow_control_id: FlowControlId,
+    idle_timeout: Option<Duration>,
 }
 
 impl TcpRecvProcessor {
@@ -38,6 +40,7 @@ impl TcpRecvProcessor {
         addresses: Addresses,
         mode: TcpConnectionMode,
         flow_control_id: FlowControlId,
+        idle_timeout: Option<Duration>,
     ) -> Self {
         Self {
             registry,
@@ -46,6 +49,7 @@ impl TcpRecvProcessor {
             addresses,
             mode,
             flow_control_id,
+            idle_timeout,
         }
     }
 
@@ -59,6 +63,7 @@ impl TcpRecvProcessor {
         mode: TcpConnectionMode,
         flow_control_id: &FlowControlId,
         receiver_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+        idle_timeout: Option<Duration>,
     ) -> Result<()> {
         let receiver = TcpRecvProcessor::new(
             registry,
@@ -67,6 +72,7 @@ impl TcpRecvProcessor {
             addresses.clone(),
             mode,
             flow_control_id.clone(),
+            idle_timeout,
         );
 
         let mailbox = Mailbox::new(
@@ -88,6 +94,18 @@ impl TcpRecvProcessor {
 
         Ok(())
     }
+
+    /// Notify the Sender that the connection is closed and stop this processor
+    async fn notify_connection_closed(&self, ctx: &Context) -> Result<bool> {
+        ctx.send_from_address(
+            self.addresses.sender_internal_address().clone(),
+            TcpSendWorkerMsg::ConnectionClosed,
+            self.addresses.receiver_internal_address().clone(),
+        )
+        .await?;
+
+        Ok(false)
+    }
 }
 
 #[async_trait]
@@ -128,7 +146,23 @@ impl Processor for TcpRecvProcessor {
     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
         // Run in a loop until TcpWorkerPair::stop() is called
         // First read a message length header...
-        let len = match self.read_half.read_u16().await {
+        let len = match self.idle_timeout {
+            Some(idle_timeout) => {
+                match tokio::time::timeout(idle_timeout, self.read_half.read_u16()).await {
+                    Ok(len) => len,
+                    Err(_) => {
+                        warn!(
+                            "Nothing was received from peer '{}' for {:?}; dropping stream",
+                            self.socket_address, idle_timeout
+                        );
+
+                        return self.notify_connection_closed(ctx).await;
+                    }
+                }
+            }
+            None => self.read_half.read_u16().await,
+        };
+        let len = match len {
             Ok(len) => len,
             Err(_e) => {
                 info!(
@@ -136,15 +170,7 @@ impl Processor for TcpRecvProcessor {
                     self.socket_address
                 );
 
-                // Notify sender tx is closed
-                ctx.send_from_address(
-                    self.addresses.sender_internal_address().clone(),
-                    TcpSendWorkerMsg::ConnectionClosed,
-                    self.addresses.receiver_internal_address().clone(),
-                )
-                .await?;
-
-                return Ok(false);
+                return self.notify_connection_closed(ctx).await;
             }
         };
 
@@ -175,7 +201,7 @@ impl Processor for TcpRecvProcessor {
         // reply routing can be properly resolved
         msg.return_route
             .modify()
-            .prepend(self.addresses.sender_address().clone());
+            .prepend(self.addresses.return_address().clone());
 
         trace!("Message onward route: {}", msg.onward_route);
         trace!("Message return route: {}", msg.return_route);
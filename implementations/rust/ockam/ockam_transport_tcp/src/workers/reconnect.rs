@@ -0,0 +1,318 @@
+use crate::transport::common::resolve_peer;
+use crate::transport::connect_socket;
+use crate::workers::Addresses;
+use crate::{
+    TcpConnectionEvent, TcpConnectionMode, TcpConnectionOptions, TcpReconnectOptions,
+    TcpReconnectingConnectionInfo, TcpRegistry,
+};
+use ockam_core::compat::collections::VecDeque;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{
+    async_trait, Address, AllowAll, Any, Decodable, LocalMessage, Mailbox, Mailboxes, Message,
+    Result, Routed, Worker,
+};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+#[derive(Serialize, Deserialize, Message, Clone)]
+pub(crate) enum TcpReconnectMsg {
+    /// Sent by the current Sender when it stops
+    ConnectionClosed,
+    /// Sent by the retry timer
+    Reconnect,
+}
+
+/// A worker owning the stable address of a TCP connection which is
+/// re-established when it drops.
+///
+/// Messages sent to the stable address are forwarded to the current
+/// [`TcpSendWorker`](crate::workers::TcpSendWorker), or buffered while the
+/// connection is down. Incoming messages have the stable address in their
+/// return route, so replies keep flowing across reconnections.
+pub(crate) struct TcpReconnectWorker {
+    registry: TcpRegistry,
+    peer: String,
+    options: TcpConnectionOptions,
+    reconnect: TcpReconnectOptions,
+    address: Address,
+    internal_address: Address,
+    current: Option<Addresses>,
+    failed_attempts: usize,
+    buffer: VecDeque<LocalMessage>,
+    retry: Option<DelayedEvent<TcpReconnectMsg>>,
+}
+
+impl TcpReconnectWorker {
+    /// Start the worker for a connection which was just established with the
+    /// given addresses
+    pub(crate) async fn start(
+        ctx: &Context,
+        registry: TcpRegistry,
+        peer: String,
+        options: TcpConnectionOptions,
+        reconnect: TcpReconnectOptions,
+        current: Addresses,
+        internal_address: Address,
+    ) -> Result<()> {
+        let address = current.return_address().clone();
+        let worker = Self {
+            registry,
+            peer,
+            options,
+            reconnect,
+            address: address.clone(),
+            internal_address: internal_address.clone(),
+            current: Some(current),
+            failed_attempts: 0,
+            buffer: VecDeque::new(),
+            retry: None,
+        };
+
+        // Access to the stable address is controlled by the flow control of
+        // the connection, the same way as for a regular Sender.
+        // The internal address is random and only known to the Senders of this
+        // connection and to the retry timer, which are checked in handle_message
+        let main_mailbox = Mailbox::new(address, Arc::new(AllowAll), Arc::new(AllowAll));
+        let internal_mailbox =
+            Mailbox::new(internal_address, Arc::new(AllowAll), Arc::new(AllowAll));
+
+        WorkerBuilder::new(worker)
+            .with_mailboxes(Mailboxes::new(main_mailbox, vec![internal_mailbox]))
+            .start(ctx)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn notify(&self, ctx: &Context, event: TcpConnectionEvent) {
+        for subscriber in self.registry.get_connection_subscribers(&self.address) {
+            if let Err(err) = ctx
+                .send_from_address(
+                    subscriber.clone(),
+                    event.clone(),
+                    self.internal_address.clone(),
+                )
+                .await
+            {
+                debug!("Couldn't notify {} about {:?}: {}", subscriber, event, err);
+            }
+        }
+    }
+
+    async fn schedule_reconnect(&mut self) -> Result<()> {
+        let delay = self.reconnect.delay(self.failed_attempts);
+        debug!(
+            "Reconnecting {} to {} in {:?}",
+            self.address, self.peer, delay
+        );
+        if let Some(retry) = &mut self.retry {
+            retry.schedule(delay).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn forward(&self, ctx: &Context, mut msg: LocalMessage) -> Result<()> {
+        if let Some(current) = &self.current {
+            let transport_message = msg.transport_mut();
+            transport_message.onward_route.step()?;
+            transport_message
+                .onward_route
+                .modify()
+                .prepend(current.sender_address().clone());
+            ctx.forward(msg).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn handle_connection_closed(&mut self, ctx: &Context) -> Result<()> {
+        info!(
+            "TCP connection {} to {} dropped, reconnecting",
+            self.address, self.peer
+        );
+        self.current = None;
+        self.notify(
+            ctx,
+            TcpConnectionEvent::Disconnected {
+                address: self.address.clone(),
+            },
+        )
+        .await;
+        self.schedule_reconnect().await
+    }
+
+    async fn handle_reconnect(&mut self, ctx: &Context) -> Result<()> {
+        if self.current.is_some() {
+            return Ok(());
+        }
+
+        let addresses = Addresses::generate_reconnecting(
+            TcpConnectionMode::Outgoing,
+            self.address.clone(),
+            self.internal_address.clone(),
+        );
+        // Resolve the peer again, its address may have changed
+        let result = match resolve_peer(self.peer.clone()) {
+            Ok(socket) => connect_socket(
+                ctx,
+                self.registry.clone(),
+                socket,
+                &addresses,
+                self.options.clone(),
+            )
+            .await
+            .map(|_| socket),
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(socket) => {
+                info!(
+                    "TCP connection {} re-established to {}",
+                    self.address, socket
+                );
+                self.current = Some(addresses);
+                self.failed_attempts = 0;
+                self.notify(
+                    ctx,
+                    TcpConnectionEvent::Reconnected {
+                        address: self.address.clone(),
+                        socket_address: socket.to_string(),
+                    },
+                )
+                .await;
+
+                while let Some(msg) = self.buffer.pop_front() {
+                    self.forward(ctx, msg).await?;
+                }
+            }
+            Err(err) => {
+                self.failed_attempts += 1;
+                if self.reconnect.is_exhausted(self.failed_attempts) {
+                    warn!(
+                        "Giving up reconnecting {} to {} after {} attempts: {}",
+                        self.address, self.peer, self.failed_attempts, err
+                    );
+                    ctx.stop_worker(self.address.clone()).await?;
+                } else {
+                    debug!(
+                        "Couldn't reconnect {} to {}: {}",
+                        self.address, self.peer, err
+                    );
+                    self.schedule_reconnect().await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for TcpReconnectWorker {
+    type Context = Context;
+    type Message = Any;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await?;
+
+        self.registry
+            .add_reconnecting_connection(TcpReconnectingConnectionInfo::new(
+                self.address.clone(),
+                self.peer.clone(),
+            ));
+        self.retry = Some(
+            DelayedEvent::create(
+                ctx,
+                self.internal_address.clone(),
+                TcpReconnectMsg::Reconnect,
+            )
+            .await?,
+        );
+
+        // The connection may have dropped before this worker could be notified
+        let sender_is_running = match &self.current {
+            Some(current) => ctx.list_workers().await?.contains(current.sender_address()),
+            None => false,
+        };
+        if !sender_is_running {
+            self.current = None;
+            self.schedule_reconnect().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.retry = None;
+        self.notify(
+            ctx,
+            TcpConnectionEvent::Closed {
+                address: self.address.clone(),
+            },
+        )
+        .await;
+        self.registry.remove_reconnecting_connection(&self.address);
+
+        if let Some(current) = self.current.take() {
+            let _ = ctx.stop_worker(current.sender_address().clone()).await;
+        }
+
+        Ok(())
+    }
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        if msg.msg_addr() == self.internal_address {
+            let from_current_sender = self
+                .current
+                .as_ref()
+                .map(|current| &msg.src_addr() == current.sender_internal_address())
+                .unwrap_or(false);
+            let from_retry_timer = self
+                .retry
+                .as_ref()
+                .map(|retry| msg.src_addr() == retry.address())
+                .unwrap_or(false);
+
+            match TcpReconnectMsg::decode(msg.payload())? {
+                TcpReconnectMsg::ConnectionClosed if from_current_sender => {
+                    self.handle_connection_closed(ctx).await?
+                }
+                TcpReconnectMsg::Reconnect if from_retry_timer => {
+                    self.handle_reconnect(ctx).await?
+                }
+                _ => debug!(
+                    "Ignoring a reconnection message from {} for {}",
+                    msg.src_addr(),
+                    self.address
+                ),
+            }
+
+            return Ok(());
+        }
+
+        let msg = msg.into_local_message();
+        if self.current.is_some() {
+            self.forward(ctx, msg).await?;
+        } else {
+            if self.buffer.len() >= self.reconnect.max_buffered_messages {
+                warn!(
+                    "Dropping the oldest message buffered for {} while reconnecting",
+                    self.address
+                );
+                self.buffer.pop_front();
+            }
+            if self.reconnect.max_buffered_messages > 0 {
+                self.buffer.push_back(msg);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,18 +1,19 @@
-use crate::workers::Addresses;
-use crate::{TcpConnectionMode, TcpRegistry, TcpSenderInfo};
+use crate::workers::{Addresses, TcpReconnectMsg};
+use crate::{TcpConnectionMode, TcpKeepaliveOptions, TcpRegistry, TcpSenderInfo};
 use cfg_if::cfg_if;
 use core::time::Duration;
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{
     async_trait,
     compat::{net::SocketAddr, sync::Arc},
-    AllowSourceAddress, DenyAll, IncomingAccessControl,
+    AllowOnwardAddress, AllowSourceAddresses, DenyAll, IncomingAccessControl,
+    OutgoingAccessControl,
 };
 use ockam_core::{
-    Any, Decodable, Encodable, Mailbox, Mailboxes, Message, Result, Routed, TransportMessage,
-    Worker,
+    Any, Decodable, Encodable, Mailbox, Mailboxes, Message, Result, Route, Routed,
+    TransportMessage, Worker,
 };
-use ockam_node::{Context, WorkerBuilder};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use serde::{Deserialize, Serialize};
 use socket2::{SockRef, TcpKeepalive};
@@ -24,6 +25,7 @@ use tracing::{debug, info, trace, warn};
 #[derive(Serialize, Deserialize, Message, Clone)]
 pub(crate) enum TcpSendWorkerMsg {
     ConnectionClosed,
+    Heartbeat,
 }
 
 /// A TCP sending message worker
@@ -42,10 +44,13 @@ pub(crate) struct TcpSendWorker {
     mode: TcpConnectionMode,
     receiver_flow_control_id: FlowControlId,
     rx_should_be_stopped: bool,
+    heartbeat: Option<DelayedEvent<TcpSendWorkerMsg>>,
+    heartbeat_interval: Option<Duration>,
 }
 
 impl TcpSendWorker {
     /// Create a new `TcpSendWorker`
+    #[allow(clippy::too_many_arguments)]
     fn new(
         registry: TcpRegistry,
         write_half: OwnedWriteHalf,
@@ -53,6 +58,8 @@ impl TcpSendWorker {
         addresses: Addresses,
         mode: TcpConnectionMode,
         receiver_flow_control_id: FlowControlId,
+        heartbeat: Option<DelayedEvent<TcpSendWorkerMsg>>,
+        heartbeat_interval: Option<Duration>,
     ) -> Self {
         Self {
             registry,
@@ -62,6 +69,8 @@ impl TcpSendWorker {
             receiver_flow_control_id,
             mode,
             rx_should_be_stopped: true,
+            heartbeat,
+            heartbeat_interval,
         }
     }
 }
@@ -79,8 +88,26 @@ impl TcpSendWorker {
         mode: TcpConnectionMode,
         sender_incoming_access_control: Arc<dyn IncomingAccessControl>,
         receiver_flow_control_id: &FlowControlId,
+        heartbeat_interval: Option<Duration>,
     ) -> Result<()> {
         trace!("Creating new TCP worker pair");
+        let heartbeat = match heartbeat_interval {
+            Some(_) => Some(
+                DelayedEvent::create(
+                    ctx,
+                    addresses.sender_internal_address().clone(),
+                    TcpSendWorkerMsg::Heartbeat,
+                )
+                .await?,
+            ),
+            None => None,
+        };
+
+        let mut internal_sources = vec![addresses.receiver_internal_address().clone()];
+        if let Some(heartbeat) = &heartbeat {
+            internal_sources.push(heartbeat.address());
+        }
+
         let sender_worker = Self::new(
             registry,
             write_half,
@@ -88,6 +115,8 @@ impl TcpSendWorker {
             addresses.clone(),
             mode,
             receiver_flow_control_id.clone(),
+            heartbeat,
+            heartbeat_interval,
         );
 
         let main_mailbox = Mailbox::new(
@@ -96,12 +125,17 @@ impl TcpSendWorker {
             Arc::new(DenyAll),
         );
 
+        // The internal address is also used to notify about the connection drop
+        // when the connection is re-established by a TcpReconnectWorker
+        let internal_outgoing_access_control: Arc<dyn OutgoingAccessControl> =
+            match addresses.close_notification_address() {
+                Some(address) => Arc::new(AllowOnwardAddress(address.clone())),
+                None => Arc::new(DenyAll),
+            };
         let internal_mailbox = Mailbox::new(
             addresses.sender_internal_address().clone(),
-            Arc::new(AllowSourceAddress(
-                addresses.receiver_internal_address().clone(),
-            )),
-            Arc::new(DenyAll),
+            Arc::new(AllowSourceAddresses(internal_sources)),
+            internal_outgoing_access_control,
         );
 
         WorkerBuilder::new(sender_worker)
@@ -119,8 +153,18 @@ impl TcpSendWorker {
         Ok(())
     }
 
+    /// Schedule the next heartbeat, if heartbeats are enabled
+    async fn schedule_heartbeat(&mut self) -> Result<()> {
+        if let (Some(heartbeat), Some(interval)) = (&mut self.heartbeat, self.heartbeat_interval) {
+            heartbeat.schedule(interval).await?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) async fn connect(
         socket_address: SocketAddr,
+        keepalive: Option<&TcpKeepaliveOptions>,
     ) -> Result<(OwnedReadHalf, OwnedWriteHalf)> {
         debug!(addr = %socket_address, "Connecting");
         let connection = match TcpStream::connect(socket_address).await {
@@ -134,23 +178,34 @@ impl TcpSendWorker {
             }
         };
 
-        let mut keepalive = TcpKeepalive::new()
-            .with_time(Duration::from_secs(300))
-            .with_interval(Duration::from_secs(75));
-
-        cfg_if! {
-            if #[cfg(unix)] {
-               keepalive = keepalive.with_retries(2);
-            }
+        if let Some(keepalive) = keepalive {
+            set_keepalive(&connection, keepalive)?;
         }
 
-        let socket = SockRef::from(&connection);
-        socket.set_tcp_keepalive(&keepalive).unwrap();
-
         Ok(connection.into_split())
     }
 }
 
+/// Enable the TCP keepalive probes of the given socket
+pub(crate) fn set_keepalive(stream: &TcpStream, options: &TcpKeepaliveOptions) -> Result<()> {
+    let mut keepalive = TcpKeepalive::new()
+        .with_time(options.time)
+        .with_interval(options.interval);
+
+    cfg_if! {
+        if #[cfg(unix)] {
+           keepalive = keepalive.with_retries(options.retries);
+        }
+    }
+
+    let socket = SockRef::from(stream);
+    socket
+        .set_tcp_keepalive(&keepalive)
+        .map_err(TransportError::from)?;
+
+    Ok(())
+}
+
 #[async_trait]
 impl Worker for TcpSendWorker {
     type Context = Context;
@@ -167,10 +222,13 @@ impl Worker for TcpSendWorker {
             self.receiver_flow_control_id.clone(),
         ));
 
+        self.schedule_heartbeat().await?;
+
         Ok(())
     }
 
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.heartbeat = None;
         self.registry
             .remove_sender_worker(self.addresses.sender_address());
 
@@ -180,6 +238,17 @@ impl Worker for TcpSendWorker {
                 .await;
         }
 
+        if let Some(address) = self.addresses.close_notification_address() {
+            // The TcpReconnectWorker may have been stopped already
+            let _ = ctx
+                .send_from_address(
+                    address.clone(),
+                    TcpReconnectMsg::ConnectionClosed,
+                    self.addresses.sender_internal_address().clone(),
+                )
+                .await;
+        }
+
         Ok(())
     }
 
@@ -207,6 +276,19 @@ impl Worker for TcpSendWorker {
 
                     return Ok(());
                 }
+                TcpSendWorkerMsg::Heartbeat => {
+                    // A message with an empty onward route is dropped by the peer Receiver
+                    let msg =
+                        prepare_message(TransportMessage::v1(Route::new(), Route::new(), vec![]))?;
+
+                    if self.write_half.write_all(msg.as_slice()).await.is_err() {
+                        warn!("Failed to send heartbeat to peer {}", self.socket_address);
+                        self.stop(ctx).await?;
+
+                        return Ok(());
+                    }
+                    trace!("Sent heartbeat to peer {}", self.socket_address);
+                }
             }
         } else {
             let mut msg = msg.into_transport_message();
@@ -224,6 +306,8 @@ impl Worker for TcpSendWorker {
             }
         }
 
+        self.schedule_heartbeat().await?;
+
         Ok(())
     }
 }
@@ -1,7 +1,7 @@
 use ockam_core::compat::rand::{self, Rng};
 use ockam_core::{route, Result};
 use ockam_node::Context;
-use ockam_transport_tcp::{TcpConnectionOptions, TcpTransport};
+use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
 use std::time::Duration;
 use tracing::info;
 
@@ -48,3 +48,70 @@ async fn tcp_keepalive_test(ctx: &mut Context) -> Result<()> {
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_idle_timeout__no_heartbeat__should_close_connection(ctx: &mut Context) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = tcp
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerOptions::new().with_idle_timeout(Duration::from_millis(200)),
+        )
+        .await?;
+
+    let connection = tcp
+        .connect(listener.socket_string(), TcpConnectionOptions::new())
+        .await?;
+
+    ctx.sleep(Duration::from_millis(600)).await;
+
+    let senders = tcp.registry().get_all_sender_workers();
+    assert!(
+        senders
+            .iter()
+            .all(|x| x.address() != connection.sender_address()),
+        "The idle connection should be closed"
+    );
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_idle_timeout__heartbeat__should_keep_connection(ctx: &mut Context) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = tcp
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerOptions::new().with_idle_timeout(Duration::from_millis(200)),
+        )
+        .await?;
+
+    let connection = tcp
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().with_heartbeat_interval(Duration::from_millis(50)),
+        )
+        .await?;
+
+    ctx.sleep(Duration::from_millis(600)).await;
+
+    let senders = tcp.registry().get_all_sender_workers();
+    assert!(
+        senders
+            .iter()
+            .any(|x| x.address() == connection.sender_address()),
+        "The connection should be kept alive by heartbeats"
+    );
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -1,8 +1,11 @@
 use core::time::Duration;
 use ockam_core::compat::rand::{self, Rng};
-use ockam_core::{route, Result, Routed, Worker};
+use ockam_core::{route, AllowAll, DenyAll, Result, Routed, Worker};
 use ockam_node::Context;
-use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
+use ockam_transport_tcp::{
+    TcpConnectionEvent, TcpConnectionMode, TcpConnectionOptions, TcpListenerOptions,
+    TcpReconnectOptions, TcpTransport,
+};
 
 pub struct Echoer;
 
@@ -187,3 +190,76 @@ async fn tcp_lifecycle__stop_listener__should_stop_accepting_connections(
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_lifecycle__connection_drop__should_reconnect(ctx: &mut Context) -> Result<()> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let reconnect = TcpReconnectOptions::new().with_initial_delay(Duration::from_millis(50));
+    let connection = transport
+        .connect(
+            &listener.socket_string(),
+            TcpConnectionOptions::new().with_reconnect(reconnect),
+        )
+        .await?;
+
+    let mut events = ctx.new_detached("events", AllowAll, DenyAll).await?;
+    transport.subscribe_to_connection_events(connection.sender_address(), "events")?;
+
+    let reply1: String = ctx
+        .send_and_receive(route![connection.clone(), "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(reply1, "Hello", "Should receive the same message");
+
+    // Drop the connection from the listener side
+    let incoming = transport
+        .registry()
+        .get_all_sender_workers()
+        .into_iter()
+        .find(|sender| matches!(sender.mode(), TcpConnectionMode::Incoming))
+        .unwrap();
+    transport.disconnect(incoming.address().clone()).await?;
+
+    let event = events.receive::<TcpConnectionEvent>().await?.body();
+    assert_eq!(
+        event,
+        TcpConnectionEvent::Disconnected {
+            address: connection.sender_address().clone()
+        }
+    );
+    let event = events.receive::<TcpConnectionEvent>().await?.body();
+    assert!(matches!(event, TcpConnectionEvent::Reconnected { .. }));
+
+    let reply2: String = ctx
+        .send_and_receive(
+            route![connection.clone(), "echoer"],
+            "Hello again".to_string(),
+        )
+        .await?;
+    assert_eq!(
+        reply2, "Hello again",
+        "Should receive the same message after reconnecting"
+    );
+
+    transport.disconnect(connection.clone()).await?;
+    let event = events.receive::<TcpConnectionEvent>().await?.body();
+    assert_eq!(
+        event,
+        TcpConnectionEvent::Closed {
+            address: connection.sender_address().clone()
+        }
+    );
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -59,7 +59,7 @@ use ockam::{
 };
 use ockam_core::{route, Error, Result};
 use ockam_node::Context;
-use ockam_transport_udp::{UdpHolePuncher, UdpTransport, UDP};
+use ockam_transport_udp::{UdpHolePuncher, UdpHolePuncherOptions, UdpTransport, UDP};
 use rand::Rng;
 use std::ops::Range;
 use tracing::{error, info};
@@ -100,16 +100,21 @@ async fn do_main(ctx: &mut Context) -> Result<()> {
     );
 
     // Create transport, echoer service and puncher
-    UdpTransport::create(ctx).await?;
+    let udp = UdpTransport::create(ctx).await?;
     ctx.start_worker(ECHOER, Echoer).await?;
     let rendezvous_route = route![(UDP, rendezvous_addr), RENDEZVOUS];
-    let mut puncher = UdpHolePuncher::create(ctx, &this_name, &that_name, rendezvous_route).await?;
+    let host_candidates = udp.host_candidates().await?;
+    info!("Host candidates = {:?}", host_candidates);
+    let options = UdpHolePuncherOptions::new().with_host_candidates(host_candidates);
+    let mut puncher =
+        UdpHolePuncher::create_with_options(ctx, &this_name, &that_name, rendezvous_route, options)
+            .await?;
     info!("Puncher address = {:?}", puncher.address());
 
     // Wait for hole to open
     info!("Waiting for hole to open");
-    puncher.wait_for_hole_open().await?;
-    info!("Hole open!");
+    let peer_route = puncher.wait_for_hole_open().await?;
+    info!("Hole open! Peer route = {}", peer_route);
 
     // Exchange messages with peer
     let r = route![puncher.address(), ECHOER];
@@ -1,5 +1,5 @@
 use super::message::PunchMessage;
-use crate::{hole_puncher::worker::UdpHolePunchWorker, PunchError};
+use crate::{hole_puncher::worker::UdpHolePunchWorker, PunchError, UdpHolePuncherOptions};
 use ockam_core::{Address, AllowOnwardAddress, AllowSourceAddress, Result, Route};
 use ockam_node::Context;
 
@@ -15,6 +15,12 @@ use ockam_node::Context;
 /// 'zurg') the remote node will also need to create its own puncher
 /// (e.g. from 'bob' to 'alice', using 'zurg').
 ///
+/// Besides the public address seen by the Rendezvous service, punchers can
+/// advertise local (host) addresses, see [`UdpHolePuncherOptions`]. All the
+/// peer's candidates are probed in parallel and the one with the lowest
+/// measured round trip time is used. Host candidates are only preferred over
+/// public ones when their round trip times are equal.
+///
 /// # Warnings
 ///
 /// This UDP NAT Hole Puncher implementation is __currently a prototype__.
@@ -28,15 +34,19 @@ use ockam_node::Context;
 /// ```rust
 /// # use {ockam_node::Context, ockam_core::{Result, route}};
 /// # async fn test(ctx: &mut Context) -> Result<()> {
-/// use ockam_transport_udp::{UdpHolePuncher, UdpTransport, UDP};
+/// use ockam_transport_udp::{UdpHolePuncher, UdpHolePuncherOptions, UdpTransport, UDP};
 ///
 /// // Create transport
-/// UdpTransport::create(ctx).await?;
+/// let udp = UdpTransport::create(ctx).await?;
 ///
 /// // Create a NAT hole from us 'alice' to them 'bob' using
-/// // the Rendezvous service 'zurg' at public IP address `192.168.1.10:4000`
+/// // the Rendezvous service 'zurg' at public IP address `192.168.1.10:4000`,
+/// // also advertising our local addresses in case 'bob' is on the same network
 /// let rendezvous_route = route![(UDP, "192.168.1.10:4000"), "zurg"];
-/// let mut puncher = UdpHolePuncher::create(ctx, "alice", "bob", rendezvous_route).await?;
+/// let host_candidates = udp.host_candidates().await?;
+/// let options = UdpHolePuncherOptions::new().with_host_candidates(host_candidates);
+/// let mut puncher =
+///     UdpHolePuncher::create_with_options(ctx, "alice", "bob", rendezvous_route, options).await?;
 ///
 /// // Note: For this to work, 'bob' will likewise need to create a hole thru to us
 ///
@@ -67,6 +77,24 @@ impl UdpHolePuncher {
         puncher_name: S,
         peer_puncher_name: S,
         rendezvous_route: R,
+    ) -> Result<UdpHolePuncher> {
+        Self::create_with_options(
+            ctx,
+            puncher_name,
+            peer_puncher_name,
+            rendezvous_route,
+            UdpHolePuncherOptions::new(),
+        )
+        .await
+    }
+
+    /// Create a new UDP NAT Hole Puncher with the given options
+    pub async fn create_with_options<S: AsRef<str>, R: Into<Route>>(
+        ctx: &mut Context,
+        puncher_name: S,
+        peer_puncher_name: S,
+        rendezvous_route: R,
+        options: UdpHolePuncherOptions,
     ) -> Result<UdpHolePuncher> {
         // Check if we can reach the rendezvous service
         let rendezvous_route = rendezvous_route.into();
@@ -83,6 +111,7 @@ impl UdpHolePuncher {
             rendezvous_route,
             puncher_name.as_ref(),
             peer_puncher_name.as_ref(),
+            options.host_candidates,
         )
         .await?;
 
@@ -108,13 +137,17 @@ impl UdpHolePuncher {
     /// Note that the hole could close at anytime. If the hole closes, the
     /// puncher will automatically try to re-open it.
     ///
+    /// Returns the route to the peer's puncher that was selected.
+    ///
     /// Timeout is the same as that of [`Context::receive()`].
-    pub async fn wait_for_hole_open(&mut self) -> Result<()> {
+    pub async fn wait_for_hole_open(&mut self) -> Result<Route> {
         self.ctx
             .send(self.worker_main_addr.clone(), PunchMessage::WaitForHoleOpen)
             .await?;
-        self.ctx.receive::<()>().await?;
-        Ok(())
+        match self.ctx.receive::<PunchMessage>().await?.body() {
+            PunchMessage::HoleOpen(peer_route) => Ok(peer_route),
+            _ => Err(PunchError::Internal.into()),
+        }
     }
 
     /// Address of this UDP NAT Hole Puncher's worker.
@@ -1,14 +1,18 @@
-use ockam_core::Message;
+use ockam_core::{Message, Route};
 use serde::{Deserialize, Serialize};
 
 // TODO: Use CBOR encoding for messages
 
 /// Internal message type for UDP NAT Hole Puncher
+///
+/// `Ping` and `Pong` carry the index of the peer's candidate
+/// being probed, so replies can be matched to the candidate.
 #[derive(Serialize, Deserialize, Debug, Message, Clone)]
 pub(crate) enum PunchMessage {
-    Ping,
-    Pong,
+    Ping(u32),
+    Pong(u32),
     Heartbeat,
     WaitForHoleOpen,
+    HoleOpen(Route),
     Payload(Vec<u8>),
 }
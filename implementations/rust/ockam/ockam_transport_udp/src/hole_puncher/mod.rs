@@ -1,7 +1,9 @@
 pub use error::PunchError;
 pub use handle::UdpHolePuncher;
+pub use options::UdpHolePuncherOptions;
 
 mod error;
 mod handle;
 mod message;
+mod options;
 mod worker;
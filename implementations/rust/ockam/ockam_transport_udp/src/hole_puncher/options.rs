@@ -0,0 +1,24 @@
+use std::net::SocketAddr;
+
+/// Options for a UDP NAT Hole Puncher
+#[derive(Debug, Clone, Default)]
+pub struct UdpHolePuncherOptions {
+    pub(super) host_candidates: Vec<SocketAddr>,
+}
+
+impl UdpHolePuncherOptions {
+    /// Options with no host candidates, the peer will only try
+    /// the public address seen by the Rendezvous service
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Local socket addresses to advertise to the peer, in addition to the public
+    /// address seen by the Rendezvous service
+    ///
+    /// See [`UdpTransport::host_candidates`](crate::UdpTransport::host_candidates).
+    pub fn with_host_candidates(mut self, host_candidates: Vec<SocketAddr>) -> Self {
+        self.host_candidates = host_candidates;
+        self
+    }
+}
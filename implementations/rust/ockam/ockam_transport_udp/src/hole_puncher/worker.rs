@@ -1,11 +1,13 @@
 use crate::hole_puncher::message::PunchMessage;
-use crate::rendezvous_service::{RendezvousRequest, RendezvousResponse};
+use crate::rendezvous_service::{Candidate, RendezvousRequest, RendezvousResponse};
 use crate::PunchError;
 use ockam_core::{
     Address, AllowAll, Any, Decodable, Encodable, LocalMessage, Mailbox, Mailboxes, Result, Route,
     Routed, Worker,
 };
 use ockam_node::{Context, DelayedEvent, MessageSendReceiveOptions, WorkerBuilder};
+use std::cmp::Reverse;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, trace};
@@ -13,6 +15,8 @@ use tracing::{debug, trace};
 const HEARTBEAT_EVERY: Duration = Duration::from_secs(1);
 const HOLE_OPEN_TIMEOUT: Duration = Duration::from_secs(20);
 const PING_TRIES: usize = 5;
+// Must be well within the Rendezvous service's entry TTL
+const RENDEZVOUS_UPDATE_EVERY: Duration = Duration::from_secs(15);
 
 // UDP and NAT Hole Punching are unreliable protocols. Expect send and receive
 // failures and don't wait too long for them
@@ -20,10 +24,6 @@ const QUICK_TIMEOUT: Duration = Duration::from_secs(3);
 
 // TODO: Maybe, implement buffering of messages when hole is not open?
 
-// TODO: Possible future improvement, explicitly send list of possible
-// reachable addresses (usually local IPs) to Rendezvous service, to allow
-// opening holes to local nodes
-
 // TODO: Should `hole_puncher` and `rendezvous_service` files be moved to their
 // own crate outside  `ockam_transport_udp`?
 
@@ -32,6 +32,12 @@ const QUICK_TIMEOUT: Duration = Duration::from_secs(3);
 /// Using a remote Rendezvous service [`UdpRendezvousService`](crate::rendezvous_service::UdpRendezvousService`) tries to create
 /// one half of a bi-directional NAT hole with a remote Hole Puncher.
 ///
+/// In the spirit of ICE, the puncher registers its host candidates with the
+/// Rendezvous service, gets the peer's candidates back, probes all of them in
+/// parallel and selects the candidate which answers with the lowest round trip
+/// time. The candidates keep being probed while the hole is open, so a faster
+/// candidate is selected as soon as it answers.
+///
 /// See documentation for [`UdpHolePuncher`](crate::hole_puncher::UdpHolePuncher).
 ///
 /// # 'Main' Mailbox
@@ -70,10 +76,20 @@ pub(crate) struct UdpHolePunchWorker {
     this_puncher_name: String,
     /// Name of peer node's puncher
     peer_puncher_name: String,
+    /// Local addresses to advertise to the peer
+    host_candidates: Vec<SocketAddr>,
+    /// Timestamp of our most recent update of the Rendezvous service
+    rendezvous_updated_at: Option<Instant>,
     /// Is hole open to peer?
     hole_open: bool,
-    /// Route to peer node's puncher
-    peer_route: Option<Route>,
+    /// Candidate routes to peer node's puncher, as returned by the Rendezvous service
+    peer_candidates: Vec<Candidate>,
+    /// Address of peer node's puncher
+    peer_addr: Option<Address>,
+    /// Round trip times measured for each of `peer_candidates`
+    peer_rtts: Vec<RttEstimate>,
+    /// Index of the selected candidate in `peer_candidates`
+    selected: Option<usize>,
     /// Timestamp of most recent message received from peer
     peer_received_at: Instant,
     /// Option for our handle [`UdpHolePuncher`](crate::hole_puncher::UdpHolePuncher)
@@ -83,15 +99,18 @@ pub(crate) struct UdpHolePunchWorker {
 
 impl UdpHolePunchWorker {
     /// Update the Rendezvous service
-    async fn rendezvous_update(&self, ctx: &mut Context) -> Result<()> {
+    async fn rendezvous_update(&mut self, ctx: &mut Context) -> Result<()> {
         let msg = RendezvousRequest::Update {
             puncher_name: self.this_puncher_name.clone(),
+            host_candidates: self.host_candidates.clone(),
         };
-        ctx.send(self.rendezvous_route.clone(), msg).await
+        ctx.send(self.rendezvous_route.clone(), msg).await?;
+        self.rendezvous_updated_at = Some(Instant::now());
+        Ok(())
     }
 
     /// Query the Rendezvous service
-    async fn rendezvous_query(&self, ctx: &mut Context) -> Result<Route> {
+    async fn rendezvous_query(&self, ctx: &mut Context) -> Result<Vec<Candidate>> {
         let msg = RendezvousRequest::Query {
             puncher_name: self.peer_puncher_name.clone(),
         };
@@ -142,6 +161,7 @@ impl UdpHolePunchWorker {
         rendezvous_route: Route,
         this_puncher_name: &str,
         peer_puncher_name: &str,
+        host_candidates: Vec<SocketAddr>,
     ) -> Result<(Address, Address)> {
         // Create worker' addresses, heartbeat & mailboxes
         let main_addr =
@@ -179,8 +199,13 @@ impl UdpHolePunchWorker {
             rendezvous_route,
             this_puncher_name: String::from(this_puncher_name),
             peer_puncher_name: String::from(peer_puncher_name),
+            host_candidates,
+            rendezvous_updated_at: None,
             hole_open: false,
-            peer_route: None,
+            peer_candidates: vec![],
+            peer_rtts: vec![],
+            peer_addr: None,
+            selected: None,
             peer_received_at: Instant::now(),
             wait_for_hole_open_addr: None,
         };
@@ -192,15 +217,64 @@ impl UdpHolePunchWorker {
         Ok((main_addr, local_addr))
     }
 
+    /// Route to peer node's puncher through the selected candidate
+    fn peer_route(&self) -> Option<&Route> {
+        self.selected
+            .and_then(|i| self.peer_candidates.get(i))
+            .map(|c| &c.route)
+    }
+
+    /// Handle a reply to one of our probes, measuring the round trip time of
+    /// the candidate and selecting the candidate with the lowest one
+    async fn handle_pong(&mut self, ctx: &Context, index: u32) -> Result<()> {
+        let index = index as usize;
+        let rtt = match self.peer_rtts.get_mut(index) {
+            Some(rtt) => rtt,
+            None => {
+                trace!("Received Pong for unknown candidate {}. Ignoring", index);
+                return Ok(());
+            }
+        };
+        if let Some(rtt) = rtt.pong_received() {
+            trace!("Round trip time of candidate {} is {:?}", index, rtt);
+        }
+
+        let best = best_candidate(&self.peer_candidates, &self.peer_rtts);
+        if best != self.selected {
+            if let Some(candidate) = best.and_then(|i| self.peer_candidates.get(i)) {
+                debug!("Selecting candidate {:?} to reach peer", candidate);
+            }
+            self.selected = best;
+        }
+
+        if !self.hole_open {
+            trace!("Setting hole as open");
+            self.set_hole_open(ctx).await?;
+        }
+        Ok(())
+    }
+
     /// Update state to show the hole to peer is now open
     async fn set_hole_open(&mut self, ctx: &Context) -> Result<()> {
         self.hole_open = true;
 
         // Inform handle, if needed
         let addr = self.wait_for_hole_open_addr.take();
-        if let Some(a) = addr {
+        if let (Some(a), Some(peer_route)) = (addr, self.peer_route()) {
             trace!("Informing handle of hole opened to peer");
-            ctx.send(a, ()).await?;
+            ctx.send(a, PunchMessage::HoleOpen(peer_route.clone()))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Send probes to all the peer's candidates at once
+    async fn probe_candidates(&mut self, ctx: &Context) -> Result<()> {
+        for (index, candidate) in self.peer_candidates.iter().enumerate() {
+            trace!("Probing peer candidate {:?}", candidate);
+            self.peer_rtts[index].probe_sent();
+            ctx.send(candidate.route.clone(), PunchMessage::Ping(index as u32))
+                .await?;
         }
         Ok(())
     }
@@ -224,13 +298,14 @@ impl UdpHolePunchWorker {
         // Handle message
         let inner_msg = PunchMessage::decode(msg.payload())?;
         match inner_msg {
-            PunchMessage::Ping => {
+            PunchMessage::Ping(index) => {
                 trace!("Received Ping from peer. Will Pong.");
-                ctx.send(return_route.clone(), PunchMessage::Pong).await?;
+                ctx.send(return_route.clone(), PunchMessage::Pong(index))
+                    .await?;
             }
-            PunchMessage::Pong => {
-                trace!("Received Pong from peer. Setting as hole is open");
-                self.set_hole_open(ctx).await?;
+            PunchMessage::Pong(index) => {
+                trace!("Received Pong from peer for candidate {}", index);
+                self.handle_pong(ctx, index).await?;
             }
             PunchMessage::Payload(data) => {
                 trace!("Received Payload from peer. Will forward to local entity");
@@ -254,7 +329,8 @@ impl UdpHolePunchWorker {
     async fn handle_heartbeat(&mut self, ctx: &mut Context) -> Result<()> {
         debug!(
             "Heartbeat => Puncher: hole_open = {:?}, peer_route = {:?}",
-            self.hole_open, self.peer_route
+            self.hole_open,
+            self.peer_route()
         );
 
         // Schedule next heartbeat here in case something below errors
@@ -264,6 +340,7 @@ impl UdpHolePunchWorker {
         if self.hole_open && self.peer_received_at.elapsed() >= HOLE_OPEN_TIMEOUT {
             trace!("Not heard from peer for a while. Setting as hole closed.",);
             self.hole_open = false;
+            self.selected = None;
         }
 
         if !self.hole_open {
@@ -274,17 +351,30 @@ impl UdpHolePunchWorker {
             self.rendezvous_update(ctx).await?;
 
             // Query Rendezvous service
-            if let Ok(peer_route) = self.rendezvous_query(ctx).await {
-                self.peer_route = Some(peer_route.clone());
-
-                // Ping peer
-                ctx.send(peer_route.clone(), PunchMessage::Ping).await?;
+            if let Ok(peer_candidates) = self.rendezvous_query(ctx).await {
+                self.peer_addr = peer_candidates
+                    .first()
+                    .and_then(|c| c.route.recipient().ok());
+                self.peer_rtts = vec![RttEstimate::default(); peer_candidates.len()];
+                self.peer_candidates = peer_candidates;
+                self.selected = None;
+
+                // Probe all candidates in parallel
+                self.probe_candidates(ctx).await?;
             }
         } else {
-            // Do keepalive pings to try and keep the hole open
-            if let Some(peer_route) = self.peer_route.as_ref() {
-                trace!("Pinging peer for keepalive");
-                ctx.send(peer_route.clone(), PunchMessage::Ping).await?;
+            // Do keepalive pings to try and keep the hole open. All the
+            // candidates are pinged, so their round trip times stay up to date
+            trace!("Pinging peer for keepalive");
+            self.probe_candidates(ctx).await?;
+
+            // Keep our Rendezvous service entry from expiring, in case
+            // the peer needs to find us again
+            let update_due = self
+                .rendezvous_updated_at
+                .map_or(true, |at| at.elapsed() >= RENDEZVOUS_UPDATE_EVERY);
+            if update_due {
+                self.rendezvous_update(ctx).await?;
             }
         }
 
@@ -295,7 +385,7 @@ impl UdpHolePunchWorker {
     async fn handle_local(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
         debug!("Local => Puncher: {:?}", msg);
 
-        if let Some(peer_route) = self.peer_route.as_ref() {
+        if let Some(peer_route) = self.peer_route() {
             let mut msg = msg.into_transport_message();
             debug!("App => Puncher: {:?}", msg);
 
@@ -318,6 +408,52 @@ impl UdpHolePunchWorker {
     }
 }
 
+/// Round trip time measured by probing a peer candidate
+#[derive(Clone, Copy, Debug, Default)]
+struct RttEstimate {
+    /// When the last probe was sent
+    probe_sent_at: Option<Instant>,
+    /// Smoothed round trip time, once a probe has been answered
+    smoothed: Option<Duration>,
+}
+
+impl RttEstimate {
+    fn probe_sent(&mut self) {
+        self.probe_sent_at = Some(Instant::now());
+    }
+
+    /// Record the reply to the last probe, returning the updated round trip time
+    fn pong_received(&mut self) -> Option<Duration> {
+        let sample = self.probe_sent_at.take()?.elapsed();
+        self.add_sample(sample);
+        self.smoothed
+    }
+
+    /// Smooth the samples as TCP does, so that a single slow reply
+    /// doesn't make the selected candidate change
+    fn add_sample(&mut self, sample: Duration) {
+        self.smoothed = Some(match self.smoothed {
+            Some(smoothed) => (smoothed * 7 + sample) / 8,
+            None => sample,
+        });
+    }
+}
+
+/// Index of the candidate with the lowest round trip time, among the ones
+/// which answered. The most preferred kind of candidate wins ties.
+fn best_candidate(candidates: &[Candidate], rtts: &[RttEstimate]) -> Option<usize> {
+    candidates
+        .iter()
+        .zip(rtts)
+        .enumerate()
+        .filter_map(|(index, (candidate, rtt))| {
+            rtt.smoothed
+                .map(|rtt| (rtt, Reverse(candidate.kind.priority()), index))
+        })
+        .min()
+        .map(|(_, _, index)| index)
+}
+
 #[ockam_core::worker]
 impl Worker for UdpHolePunchWorker {
     type Message = Any;
@@ -342,8 +478,9 @@ impl Worker for UdpHolePunchWorker {
             addr if addr == self.main_addr => {
                 let return_route = msg.return_route();
                 let sender_addr = msg.sender()?;
-                let is_from_peer = match &self.peer_route {
-                    Some(r) => return_route.contains_route(r)?,
+                // Messages from the peer can come through any of its candidates
+                let is_from_peer = match &self.peer_addr {
+                    Some(a) => return_route.iter().any(|x| x == a),
                     None => false,
                 };
 
@@ -357,10 +494,12 @@ impl Worker for UdpHolePunchWorker {
                     let inner_msg = PunchMessage::decode(msg.payload())?;
                     match inner_msg {
                         PunchMessage::WaitForHoleOpen => {
-                            self.wait_for_hole_open_addr = Some(sender_addr)
-                            // TODO: If the hole is already open, the handle
-                            // will not be informed until it's closed and
-                            // opened again. Is this what we want?
+                            self.wait_for_hole_open_addr = Some(sender_addr);
+                            // Don't make the handle wait for the hole to be
+                            // closed and opened again
+                            if self.hole_open {
+                                self.set_hole_open(ctx).await?;
+                            }
                         }
                         _ => return Err(PunchError::Internal.into()),
                     }
@@ -378,3 +517,45 @@ impl Worker for UdpHolePunchWorker {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendezvous_service::CandidateKind;
+    use ockam_core::route;
+
+    fn rtt(millis: Option<u64>) -> RttEstimate {
+        let mut rtt = RttEstimate::default();
+        if let Some(millis) = millis {
+            rtt.add_sample(Duration::from_millis(millis));
+        }
+        rtt
+    }
+
+    #[test]
+    fn best_candidate_has_the_lowest_round_trip_time() {
+        let candidates = vec![
+            Candidate::new(CandidateKind::Host, route!["host_1"]),
+            Candidate::new(CandidateKind::Host, route!["host_2"]),
+            Candidate::new(CandidateKind::ServerReflexive, route!["reflexive"]),
+        ];
+
+        // Nothing answered yet
+        assert_eq!(best_candidate(&candidates, &[rtt(None); 3]), None);
+
+        // The fastest candidate wins, even if its kind is less preferred
+        let rtts = [rtt(None), rtt(Some(30)), rtt(Some(10))];
+        assert_eq!(best_candidate(&candidates, &rtts), Some(2));
+
+        // The most preferred kind wins ties
+        let rtts = [rtt(None), rtt(Some(10)), rtt(Some(10))];
+        assert_eq!(best_candidate(&candidates, &rtts), Some(1));
+    }
+
+    #[test]
+    fn round_trip_times_are_smoothed() {
+        let mut rtt = rtt(Some(80));
+        rtt.add_sample(Duration::from_millis(160));
+        assert_eq!(rtt.smoothed, Some(Duration::from_millis(90)));
+    }
+}
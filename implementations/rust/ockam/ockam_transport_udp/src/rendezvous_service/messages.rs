@@ -1,5 +1,6 @@
 use ockam_core::{Message, Result, Route};
 use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
 
 // TODO: Change this Request/Response protocol to use CBOR encoding for messages.
 
@@ -8,12 +9,18 @@ use serde::{Deserialize, Serialize};
 pub enum RendezvousRequest {
     /// Update service's internal table with the
     /// details of the sending node.
+    ///
+    /// Updates also act as heartbeats: entries which are not
+    /// updated within the service's TTL are evicted.
     Update {
         /// Name of sending node's puncher
         puncher_name: String,
+        /// Local (host) socket addresses the sending node can be reached on,
+        /// in addition to the public address seen by the service
+        host_candidates: Vec<SocketAddr>,
     },
-    /// Query service's internal table for the public
-    /// route to the named node.
+    /// Query service's internal table for the candidate
+    /// routes to the named node.
     Query {
         /// Name of puncher to lookup
         puncher_name: String,
@@ -25,6 +32,42 @@ pub enum RendezvousRequest {
 /// Response type for UDP Hole Punching Rendezvous service
 #[derive(Serialize, Deserialize, Debug, Message)]
 pub enum RendezvousResponse {
-    Query(Result<Route>),
+    Query(Result<Vec<Candidate>>),
     Pong,
 }
+
+/// Kind of a [`Candidate`], in the spirit of ICE (RFC 8445)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CandidateKind {
+    /// Public address of the node, as seen by the Rendezvous service
+    ServerReflexive,
+    /// Address of one of the node's local interfaces
+    Host,
+}
+
+impl CandidateKind {
+    /// Preference of this kind of candidate, higher is better.
+    ///
+    /// Candidates are selected by round trip time, the preference only
+    /// breaks ties. Host candidates are preferred as they avoid going
+    /// through any NAT.
+    pub fn priority(&self) -> u8 {
+        match self {
+            CandidateKind::ServerReflexive => 1,
+            CandidateKind::Host => 2,
+        }
+    }
+}
+
+/// A possible route to a remote puncher
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub kind: CandidateKind,
+    pub route: Route,
+}
+
+impl Candidate {
+    pub fn new(kind: CandidateKind, route: Route) -> Self {
+        Self { kind, route }
+    }
+}
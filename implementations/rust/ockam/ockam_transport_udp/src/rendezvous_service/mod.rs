@@ -1,4 +1,4 @@
-pub(crate) use messages::{RendezvousRequest, RendezvousResponse};
+pub(crate) use messages::{Candidate, CandidateKind, RendezvousRequest, RendezvousResponse};
 pub use rendezvous::UdpRendezvousService;
 
 mod messages;
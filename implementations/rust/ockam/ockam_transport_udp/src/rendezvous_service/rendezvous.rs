@@ -1,13 +1,18 @@
 use crate::{
-    rendezvous_service::{RendezvousRequest, RendezvousResponse},
+    rendezvous_service::{Candidate, CandidateKind, RendezvousRequest, RendezvousResponse},
     UDP,
 };
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{async_trait, Address, Error, Result, Route, Routed, Worker};
 use ockam_node::Context;
 use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
 use tracing::{debug, trace, warn};
 
+/// Default time after which an entry that has not been updated is evicted
+const DEFAULT_ENTRY_TTL: Duration = Duration::from_secs(60);
+
 /// High level management interface for UDP Rendezvous Service
 ///
 /// The Rendezvous service is a part of UDP NAT Hole Punching (see [Wikipedia](https://en.wikipedia.org/wiki/UDP_hole_punching)).
@@ -16,6 +21,9 @@ use tracing::{debug, trace, warn};
 ///
 /// To work, this service requires the UDP Transport to be working.
 ///
+/// Punchers are expected to regularly update the service with their details.
+/// Entries which have not been updated within the service's TTL are evicted.
+///
 /// # Example
 ///
 /// ```rust
@@ -35,34 +43,83 @@ pub struct UdpRendezvousService;
 impl UdpRendezvousService {
     /// Start a new Rendezvous service with the given local address
     pub async fn start(ctx: &Context, address: impl Into<Address>) -> Result<()> {
-        ctx.start_worker(address.into(), RendezvousWorker::new())
+        Self::start_with_entry_ttl(ctx, address, DEFAULT_ENTRY_TTL).await
+    }
+
+    /// Start a new Rendezvous service with the given local address, evicting
+    /// entries which have not been updated within `entry_ttl`
+    pub async fn start_with_entry_ttl(
+        ctx: &Context,
+        address: impl Into<Address>,
+        entry_ttl: Duration,
+    ) -> Result<()> {
+        ctx.start_worker(address.into(), RendezvousWorker::new(entry_ttl))
             .await
     }
 }
 
-// TODO: Implement mechanism for removing entries from internal map, possibly by
-// deleting 'old' entries on heartbeat events.
+/// Details registered by a remote puncher
+#[derive(Debug)]
+struct RendezvousEntry {
+    /// Public route to the puncher, as seen by this service
+    reflexive_route: Route,
+    /// Local socket addresses advertised by the puncher
+    host_candidates: Vec<SocketAddr>,
+    /// Time of the most recent update
+    updated_at: Instant,
+}
+
+impl RendezvousEntry {
+    /// Candidate routes to the puncher, most preferred first
+    fn candidates(&self) -> Vec<Candidate> {
+        let mut candidates = Vec::new();
+        for host in &self.host_candidates {
+            let mut route = self.reflexive_route.clone();
+            route
+                .modify()
+                .pop_front()
+                .prepend(Address::new(UDP, host.to_string()));
+            let candidate = Candidate::new(CandidateKind::Host, route);
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+
+        // A reflexive route which is the same as a host route means the
+        // puncher is not behind a NAT, no need to try it twice
+        if !candidates.iter().any(|c| c.route == self.reflexive_route) {
+            candidates.push(Candidate::new(
+                CandidateKind::ServerReflexive,
+                self.reflexive_route.clone(),
+            ));
+        }
+        candidates
+    }
+}
 
 /// Worker for the UDP NAT Hole Punching Rendezvous service
 ///
-/// Maintains an internal map for remote nodes and the public IP address
-/// from which they send UDP datagrams.
+/// Maintains an internal map for remote nodes, the public IP address
+/// from which they send UDP datagrams and the local addresses they advertise.
 ///
 /// Remote nodes can send requests to update and query the map.
+/// Entries are evicted when not updated within the configured TTL.
 struct RendezvousWorker {
-    map: BTreeMap<String, Route>,
+    map: BTreeMap<String, RendezvousEntry>,
+    entry_ttl: Duration,
 }
 
 impl Default for RendezvousWorker {
     fn default() -> Self {
-        Self::new()
+        Self::new(DEFAULT_ENTRY_TTL)
     }
 }
 
 impl RendezvousWorker {
-    fn new() -> Self {
+    fn new(entry_ttl: Duration) -> Self {
         Self {
             map: BTreeMap::new(),
+            entry_ttl,
         }
     }
 
@@ -81,11 +138,33 @@ impl RendezvousWorker {
         res.into()
     }
 
+    /// Remove entries which have not been updated within the TTL
+    fn evict_expired(&mut self) {
+        let entry_ttl = self.entry_ttl;
+        self.map.retain(|puncher_name, entry| {
+            let keep = entry.updated_at.elapsed() < entry_ttl;
+            if !keep {
+                debug!("Evicting expired entry for puncher {}", puncher_name);
+            }
+            keep
+        });
+    }
+
     // Handle Update request
-    fn handle_update(&mut self, puncher_name: &str, return_route: &Route) {
+    fn handle_update(
+        &mut self,
+        puncher_name: &str,
+        host_candidates: &[SocketAddr],
+        return_route: &Route,
+    ) {
         let r = Self::parse_route(return_route);
         if !r.is_empty() {
-            self.map.insert(puncher_name.to_owned(), r);
+            let entry = RendezvousEntry {
+                reflexive_route: r,
+                host_candidates: host_candidates.to_vec(),
+                updated_at: Instant::now(),
+            };
+            self.map.insert(puncher_name.to_owned(), entry);
         } else {
             // This could happen if a client erroneously contacts this service over TCP not UDP, for example
             warn!(
@@ -97,9 +176,9 @@ impl RendezvousWorker {
     }
 
     // Handle Query request
-    fn handle_query(&self, puncher_name: &String) -> Result<Route> {
+    fn handle_query(&self, puncher_name: &String) -> Result<Vec<Candidate>> {
         match self.map.get(puncher_name) {
-            Some(route) => Ok(route.clone()),
+            Some(entry) => Ok(entry.candidates()),
             None => Err(Error::new_without_cause(Origin::Other, Kind::NotFound)),
         }
     }
@@ -120,10 +199,18 @@ impl Worker for RendezvousWorker {
             msg,
             Self::parse_route(&msg.return_route())
         );
+
+        // Updates from punchers act as heartbeats, so any incoming
+        // message is a good time to forget about punchers that went away
+        self.evict_expired();
+
         let return_route = msg.return_route();
         match msg.as_body() {
-            RendezvousRequest::Update { puncher_name } => {
-                self.handle_update(puncher_name, &return_route);
+            RendezvousRequest::Update {
+                puncher_name,
+                host_candidates,
+            } => {
+                self.handle_update(puncher_name, host_candidates, &return_route);
             }
             RendezvousRequest::Query { puncher_name } => {
                 let res = self.handle_query(puncher_name);
@@ -141,13 +228,16 @@ impl Worker for RendezvousWorker {
 
 #[cfg(test)]
 mod tests {
-    use super::RendezvousWorker;
-    use crate::rendezvous_service::{RendezvousRequest, RendezvousResponse};
+    use super::{RendezvousWorker, DEFAULT_ENTRY_TTL};
+    use crate::rendezvous_service::{
+        Candidate, CandidateKind, RendezvousRequest, RendezvousResponse,
+    };
     use crate::{UdpRendezvousService, UdpTransport, UDP};
     use ockam_core::errcode::Origin;
     use ockam_core::{route, Error, Result, Route, Routed, TransportType, Worker};
     use ockam_node::Context;
     use std::net::SocketAddr;
+    use std::time::Duration;
     use tokio::net::UdpSocket;
     use tracing::debug;
 
@@ -179,19 +269,23 @@ mod tests {
 
     #[ockam_macros::test]
     async fn update_and_query(ctx: &mut Context) -> Result<()> {
-        let (rendezvous_route, send_addr) = test_setup(ctx).await?;
+        let (rendezvous_route, send_addr) = test_setup(ctx, DEFAULT_ENTRY_TTL).await?;
 
         let our_public_route = route![(UDP, send_addr.to_string()), ctx.address()];
+        let our_candidates = vec![Candidate::new(
+            CandidateKind::ServerReflexive,
+            our_public_route,
+        )];
 
         // Update service, should work
         //
         // Use Alice and Bob with the same address to check the service can
         // handle multiple internal mappings and that multiple map values
         // can be for the same node.
-        update_operation("Alice", ctx, &rendezvous_route)
+        update_operation("Alice", vec![], ctx, &rendezvous_route)
             .await
             .unwrap();
-        update_operation("Bob", ctx, &rendezvous_route)
+        update_operation("Bob", vec![], ctx, &rendezvous_route)
             .await
             .unwrap();
 
@@ -199,11 +293,11 @@ mod tests {
         let res = query_operation("Alice", ctx, &rendezvous_route)
             .await
             .unwrap();
-        assert_eq!(res, our_public_route);
+        assert_eq!(res, our_candidates);
         let res = query_operation("Bob", ctx, &rendezvous_route)
             .await
             .unwrap();
-        assert_eq!(res, our_public_route);
+        assert_eq!(res, our_candidates);
 
         // Query service for non-existent node, should error
         let res = query_operation("DoesNotExist", ctx, &rendezvous_route).await;
@@ -214,9 +308,71 @@ mod tests {
         Ok(())
     }
 
+    #[ockam_macros::test]
+    async fn host_candidates(ctx: &mut Context) -> Result<()> {
+        let (rendezvous_route, send_addr) = test_setup(ctx, DEFAULT_ENTRY_TTL).await?;
+
+        // Advertise a host address different from the one the service sees,
+        // plus the one the service sees, which should not be duplicated
+        let host_addr: SocketAddr = "127.0.0.2:5000".parse().unwrap();
+        update_operation("Alice", vec![host_addr, send_addr], ctx, &rendezvous_route)
+            .await
+            .unwrap();
+
+        let res = query_operation("Alice", ctx, &rendezvous_route)
+            .await
+            .unwrap();
+        assert_eq!(
+            res,
+            vec![
+                Candidate::new(
+                    CandidateKind::Host,
+                    route![(UDP, host_addr.to_string()), ctx.address()]
+                ),
+                Candidate::new(
+                    CandidateKind::Host,
+                    route![(UDP, send_addr.to_string()), ctx.address()]
+                ),
+            ]
+        );
+
+        // Shutdown
+        ctx.stop().await?;
+        Ok(())
+    }
+
+    #[ockam_macros::test]
+    async fn entries_expire(ctx: &mut Context) -> Result<()> {
+        let ttl = Duration::from_millis(500);
+        let (rendezvous_route, _) = test_setup(ctx, ttl).await?;
+
+        update_operation("Alice", vec![], ctx, &rendezvous_route)
+            .await
+            .unwrap();
+        update_operation("Bob", vec![], ctx, &rendezvous_route)
+            .await
+            .unwrap();
+
+        // Keep Alice alive with a heartbeat half way through the TTL
+        tokio::time::sleep(ttl / 2).await;
+        update_operation("Alice", vec![], ctx, &rendezvous_route)
+            .await
+            .unwrap();
+        tokio::time::sleep(ttl / 2 + Duration::from_millis(100)).await;
+
+        let res = query_operation("Alice", ctx, &rendezvous_route).await;
+        assert!(res.is_ok(), "Alice should still be registered");
+        let res = query_operation("Bob", ctx, &rendezvous_route).await;
+        assert!(res.is_err(), "Bob should have been evicted");
+
+        // Shutdown
+        ctx.stop().await?;
+        Ok(())
+    }
+
     #[ockam_macros::test]
     async fn ping(ctx: &mut Context) -> Result<()> {
-        let (rendezvous_route, _) = test_setup(ctx).await?;
+        let (rendezvous_route, _) = test_setup(ctx, DEFAULT_ENTRY_TTL).await?;
 
         let res: RendezvousResponse = ctx
             .send_and_receive(rendezvous_route, RendezvousRequest::Ping)
@@ -229,14 +385,14 @@ mod tests {
     }
 
     /// Helper
-    async fn test_setup(ctx: &mut Context) -> Result<(Route, SocketAddr)> {
+    async fn test_setup(ctx: &mut Context, entry_ttl: Duration) -> Result<(Route, SocketAddr)> {
         // Find an available port
         let bind_addr = *available_local_ports(1).await?.first().unwrap();
         debug!("bind_addr = {:?}", bind_addr);
 
         // Create transport, start rendezvous service, start echo service and listen
         let transport = UdpTransport::create(ctx).await?;
-        UdpRendezvousService::start(ctx, "rendezvous").await?;
+        UdpRendezvousService::start_with_entry_ttl(ctx, "rendezvous", entry_ttl).await?;
         let rendezvous_route = route![(UDP, bind_addr.to_string()), "rendezvous"];
         ctx.start_worker("echo", EchoUDPAddress).await?;
         let route_echo = route![(UDP, bind_addr.to_string()), "echo"];
@@ -250,9 +406,15 @@ mod tests {
     }
 
     /// Helper
-    async fn update_operation(puncher_name: &str, ctx: &mut Context, route: &Route) -> Result<()> {
+    async fn update_operation(
+        puncher_name: &str,
+        host_candidates: Vec<SocketAddr>,
+        ctx: &mut Context,
+        route: &Route,
+    ) -> Result<()> {
         let msg = RendezvousRequest::Update {
             puncher_name: String::from(puncher_name),
+            host_candidates,
         };
 
         // Send from our context's main address
@@ -260,7 +422,11 @@ mod tests {
     }
 
     /// Helper
-    async fn query_operation(puncher_name: &str, ctx: &Context, route: &Route) -> Result<Route> {
+    async fn query_operation(
+        puncher_name: &str,
+        ctx: &Context,
+        route: &Route,
+    ) -> Result<Vec<Candidate>> {
         let msg = RendezvousRequest::Query {
             puncher_name: String::from(puncher_name),
         };
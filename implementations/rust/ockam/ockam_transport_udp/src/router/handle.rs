@@ -1,6 +1,7 @@
 use crate::router::messages::{UdpRouterRequest, UdpRouterResponse};
 use ockam_core::{Address, AllowAll, Result};
 use ockam_node::Context;
+use ockam_transport_core::TransportError;
 use std::net::SocketAddr;
 
 /// A handle to connect to a UdpRouter
@@ -33,10 +34,26 @@ impl UdpRouterHandle {
     /// so the local node can act as a server to other nodes
     pub async fn listen(&self, local_addr: SocketAddr) -> Result<()> {
         let msg = UdpRouterRequest::Listen { local_addr };
-        let UdpRouterResponse::Listen(res) = self
+        match self
             .ctx
             .send_and_receive(self.api_addr.clone(), msg)
-            .await?;
-        res
+            .await?
+        {
+            UdpRouterResponse::Listen(res) => res,
+            _ => Err(TransportError::Protocol.into()),
+        }
+    }
+
+    /// Request the local address of the socket used
+    /// for messages initiated by the local node
+    pub async fn client_address(&self) -> Result<SocketAddr> {
+        match self
+            .ctx
+            .send_and_receive(self.api_addr.clone(), UdpRouterRequest::ClientAddress)
+            .await?
+        {
+            UdpRouterResponse::ClientAddress(addr) => Ok(addr),
+            _ => Err(TransportError::Protocol.into()),
+        }
     }
 }
@@ -7,9 +7,12 @@ pub enum UdpRouterRequest {
     /// Listen on a local UDP port so the local node can
     /// act as a server to other nodes
     Listen { local_addr: SocketAddr },
+    /// Get the local address of the socket used for 'client' messages
+    ClientAddress,
 }
 
 #[derive(Serialize, Deserialize, Debug, Message)]
 pub enum UdpRouterResponse {
     Listen(Result<()>),
+    ClientAddress(SocketAddr),
 }
@@ -38,6 +38,8 @@ pub(crate) struct UdpRouter {
     api_addr: Address,
     /// Sender for 'client' messages
     client_sender: Address,
+    /// Local address of the socket for 'client' messages
+    client_local_addr: SocketAddr,
 }
 
 impl UdpRouter {
@@ -59,7 +61,7 @@ impl UdpRouter {
         let handle = UdpRouterHandle::try_new(&child_ctx, &api_addr).await?;
 
         // Create sender, listener pair for 'client' messages
-        let client_sender = Self::create_sender_listener(
+        let (client_sender, client_local_addr) = Self::create_sender_listener(
             &child_ctx,
             SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 0),
         )
@@ -70,6 +72,7 @@ impl UdpRouter {
             main_addr: main_addr.clone(),
             api_addr: api_addr.clone(),
             client_sender,
+            client_local_addr,
         };
 
         let main_mailbox = Mailbox::new(
@@ -103,8 +106,12 @@ impl UdpRouter {
 
     /// Create a sender, listener pair for the given socket address.
     ///
-    /// Returns the address of the created sender.
-    async fn create_sender_listener(ctx: &Context, local_addr: SocketAddr) -> Result<Address> {
+    /// Returns the address of the created sender and the
+    /// actual local address the socket was bound to.
+    async fn create_sender_listener(
+        ctx: &Context,
+        local_addr: SocketAddr,
+    ) -> Result<(Address, SocketAddr)> {
         // This transport only supports IPv4
         if !local_addr.is_ipv4() {
             error!(local_addr = %local_addr, "This transport only supprts IPv4");
@@ -115,6 +122,9 @@ impl UdpRouter {
         let socket = UdpSocket::bind(local_addr)
             .await
             .map_err(|_| TransportError::InvalidAddress)?;
+        let bound_addr = socket
+            .local_addr()
+            .map_err(|_| TransportError::InvalidAddress)?;
 
         // Split socket into sink and stream
         let (sink, stream) = UdpFramed::new(socket, TransportMessageCodec).split();
@@ -130,7 +140,7 @@ impl UdpRouter {
         // Create listener
         UdpListenProcessor::start(ctx, stream, sender_addr.clone()).await?;
 
-        Ok(sender_addr)
+        Ok((sender_addr, bound_addr))
     }
 }
 
@@ -168,6 +178,10 @@ impl Worker for UdpRouter {
                     ctx.send_from_address(return_route, UdpRouterResponse::Listen(res), msg_addr)
                         .await?;
                 }
+                UdpRouterRequest::ClientAddress => {
+                    let res = UdpRouterResponse::ClientAddress(self.client_local_addr);
+                    ctx.send_from_address(return_route, res, msg_addr).await?;
+                }
             };
         } else {
             return Err(TransportError::Protocol.into());
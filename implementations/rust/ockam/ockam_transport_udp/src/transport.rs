@@ -2,6 +2,8 @@ use crate::router::{UdpRouter, UdpRouterHandle};
 use ockam_core::{async_trait, Result};
 use ockam_node::{Context, HasContext};
 use ockam_transport_core::TransportError;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use tracing::warn;
 
 /// High level management interface for UDP transport
 ///
@@ -27,6 +29,64 @@ impl UdpTransport {
             .map_err(|_| TransportError::InvalidAddress)?;
         self.router_handle.listen(bind_addr).await
     }
+
+    /// Local socket addresses on which this transport could receive replies
+    /// to messages initiated by the local node
+    ///
+    /// These are the IPv4 addresses of the local network interfaces, loopback
+    /// first, combined with the port of the transport's 'client' socket. They
+    /// can be advertised as host candidates by
+    /// [`UdpHolePuncher`](crate::UdpHolePuncher)s, so peers on the same host or
+    /// network can be reached without going through a NAT.
+    ///
+    /// No route to the internet is needed. If the interfaces can't be listed,
+    /// only the loopback address is returned.
+    pub async fn host_candidates(&self) -> Result<Vec<SocketAddr>> {
+        let client_addr = self.router_handle.client_address().await?;
+
+        let interfaces = match if_addrs::get_if_addrs() {
+            Ok(interfaces) => interfaces,
+            Err(err) => {
+                warn!(%err, "Failed to list the local network interfaces");
+                vec![]
+            }
+        };
+        let ips = host_ips(
+            interfaces
+                .iter()
+                .map(|interface| (interface.ip(), interface.is_loopback())),
+        );
+
+        Ok(ips
+            .into_iter()
+            .map(|ip| SocketAddr::new(ip, client_addr.port()))
+            .collect())
+    }
+}
+
+/// IP addresses which can be advertised as host candidates, given the
+/// addresses of the local interfaces and whether they are loopback addresses
+///
+/// The transport only supports IPv4, so IPv6 addresses are discarded.
+/// The loopback address is always returned, and comes first.
+fn host_ips(interfaces: impl IntoIterator<Item = (IpAddr, bool)>) -> Vec<IpAddr> {
+    let mut loopback = vec![IpAddr::V4(Ipv4Addr::LOCALHOST)];
+    let mut others = vec![];
+    for (ip, is_loopback) in interfaces {
+        if !ip.is_ipv4() || ip.is_unspecified() {
+            continue;
+        }
+        let ips = if is_loopback || ip.is_loopback() {
+            &mut loopback
+        } else {
+            &mut others
+        };
+        if !ips.contains(&ip) {
+            ips.push(ip);
+        }
+    }
+    loopback.extend(others);
+    loopback
 }
 
 /// This trait adds a `create_udp_transport` method to any struct returning a Context.
@@ -40,3 +100,32 @@ pub trait UdpTransportExtension: HasContext {
 }
 
 impl<A: HasContext> UdpTransportExtension for A {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    #[test]
+    fn host_ips_lists_ipv4_interfaces_loopback_first() {
+        let lan = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10));
+        let vpn = IpAddr::V4(Ipv4Addr::new(10, 8, 0, 2));
+        let ips = host_ips([
+            (lan, false),
+            (IpAddr::V6(Ipv6Addr::LOCALHOST), true),
+            (IpAddr::V4(Ipv4Addr::LOCALHOST), true),
+            (IpAddr::V6("fe80::1".parse().unwrap()), false),
+            (vpn, false),
+            (lan, false),
+        ]);
+        assert_eq!(ips, vec![IpAddr::V4(Ipv4Addr::LOCALHOST), lan, vpn]);
+    }
+
+    #[test]
+    fn host_ips_keeps_loopback_without_ipv4_interfaces() {
+        // An IPv6-only host, or one whose interfaces could not be listed
+        let ips = host_ips([(IpAddr::V6("2001:db8::1".parse().unwrap()), false)]);
+        assert_eq!(ips, vec![IpAddr::V4(Ipv4Addr::LOCALHOST)]);
+        assert_eq!(host_ips([]), vec![IpAddr::V4(Ipv4Addr::LOCALHOST)]);
+    }
+}
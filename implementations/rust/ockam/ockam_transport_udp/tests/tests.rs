@@ -1,8 +1,10 @@
 use ockam_core::compat::rand::{self, Rng};
 use ockam_core::{route, Address, AllowAll, Result, Routed, Worker};
 use ockam_node::{Context, MessageReceiveOptions, MessageSendReceiveOptions};
-use ockam_transport_udp::{UdpTransport, UDP};
-use std::net::SocketAddr;
+use ockam_transport_udp::{
+    UdpHolePuncher, UdpHolePuncherOptions, UdpRendezvousService, UdpTransport, UDP,
+};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::time::Duration;
 use tracing::{debug, error, trace};
 
@@ -192,6 +194,83 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+/// Punchers should probe all the peer's candidates and select one that works.
+///
+/// Each puncher pretends to be behind a NAT whose private network is a
+/// loopback alias (`127.0.0.2` for Alice, `127.0.0.3` for Bob) which the
+/// other puncher cannot reach, so the host candidates on those addresses
+/// must be discarded in favour of the ones which answer.
+#[ockam_macros::test]
+async fn hole_punch_selects_working_candidate(ctx: &mut Context) -> Result<()> {
+    // Find available ports
+    let ports = utils::available_local_ports(3).await?;
+    let rendezvous_addr = ports[0];
+    debug!("rendezvous_addr = {:?}", rendezvous_addr);
+
+    // Transport, Rendezvous service and echoer
+    let transport = UdpTransport::create(ctx).await?;
+    UdpRendezvousService::start(ctx, "rendezvous").await?;
+    transport.listen(rendezvous_addr.to_string()).await?;
+    ctx.start_worker("echoer", Echoer::new()).await?;
+    let rendezvous_route = route![(UDP, rendezvous_addr.to_string()), "rendezvous"];
+
+    // Candidates on the "private networks" nobody listens on
+    let private_addr =
+        |ip: [u8; 4], port: u16| SocketAddr::new(IpAddr::V4(Ipv4Addr::from(ip)), port);
+    let alice_private = private_addr([127, 0, 0, 2], ports[1].port());
+    let bob_private = private_addr([127, 0, 0, 3], ports[2].port());
+
+    let mut alice_candidates = transport.host_candidates().await?;
+    alice_candidates.insert(0, alice_private);
+    let bob_candidates = vec![bob_private];
+
+    // Punchers
+    let mut alice = UdpHolePuncher::create_with_options(
+        ctx,
+        "alice",
+        "bob",
+        rendezvous_route.clone(),
+        UdpHolePuncherOptions::new().with_host_candidates(alice_candidates),
+    )
+    .await?;
+    let mut bob = UdpHolePuncher::create_with_options(
+        ctx,
+        "bob",
+        "alice",
+        rendezvous_route,
+        UdpHolePuncherOptions::new().with_host_candidates(bob_candidates),
+    )
+    .await?;
+
+    // Wait for holes to open and check the selected routes
+    // don't go through the unreachable candidates
+    let alice_to_bob = alice.wait_for_hole_open().await?;
+    let bob_to_alice = bob.wait_for_hole_open().await?;
+    for (route, private) in [(&alice_to_bob, bob_private), (&bob_to_alice, alice_private)] {
+        let udp_hop = route
+            .iter()
+            .find(|x| x.transport_type() == UDP)
+            .map(|x| x.address().parse::<SocketAddr>().unwrap())
+            .unwrap();
+        assert_ne!(udp_hop, private, "Selected an unreachable candidate");
+    }
+
+    // Exchange messages through the hole
+    let msg = String::from("Hola");
+    let reply = ctx
+        .send_and_receive_extended::<String>(
+            route![alice.address(), "echoer"],
+            msg.clone(),
+            MessageSendReceiveOptions::new().with_timeout(TIMEOUT),
+        )
+        .await?
+        .body();
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    ctx.stop().await?;
+    Ok(())
+}
+
 pub struct Echoer {
     prev_src_addr: Option<String>,
 }
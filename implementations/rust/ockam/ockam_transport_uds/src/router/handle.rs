@@ -67,11 +67,18 @@ impl UdsRouterHandle {
 
 impl UdsRouterHandle {
     /// Bind an incoming connection listener for this router
-    pub async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<SocketAddr> {
+    ///
+    /// Returns the bound [`SocketAddr`] and the address of the listener processor
+    pub async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<(SocketAddr, Address)> {
         let socket_addr = addr.into();
         UdsListenProcessor::start(&self.ctx, self.async_try_clone().await?, socket_addr).await
     }
 
+    /// Stop an incoming connection listener previously started with `bind`
+    pub async fn unbind(&self, processor_address: Address) -> Result<()> {
+        self.ctx.stop_processor(processor_address).await
+    }
+
     /// Establish an outgoing UDS connection on an existing transport
     pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
         let response = self
@@ -86,6 +86,11 @@ impl UdsRouter {
     /// Handles any [`UdsRouterRequest::Connect`] messages received by
     /// this node's worker
     async fn handle_connect(&mut self, peer: String) -> Result<Address> {
+        // Reuse an existing connection to the same socket
+        if let Some(self_addr) = self.map.get(&Address::new(UDS, peer.as_str())) {
+            return Ok(self_addr.clone());
+        }
+
         let (peer_addr, pathnames) = UdsRouterHandle::resolve_peer(peer)?;
 
         let router_handle = self.create_self_handle().await?;
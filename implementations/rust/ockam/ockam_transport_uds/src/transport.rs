@@ -106,8 +106,59 @@ impl UdsTransport {
     /// # Ok(()) }
     /// ```
     pub async fn listen<S: AsRef<str>>(&self, bind_addr: S) -> Result<SocketAddr> {
+        Ok(self
+            .start_listener(bind_addr)
+            .await?
+            .socket_address()
+            .clone())
+    }
+
+    /// Binds the [`UdsTransport`] to listen to the given socket, returning a [`UdsListener`]
+    /// which can be used to stop listening later on.
+    ///
+    /// ```rust
+    /// use ockam_transport_uds::UdsTransport;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let uds = UdsTransport::create(&ctx).await?;
+    /// let listener = uds.start_listener("/tmp/socket-name").await?;
+    /// uds.stop_listener(listener.processor_address()).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn start_listener<S: AsRef<str>>(&self, bind_addr: S) -> Result<UdsListener> {
         let sock_addr = parse_socket_addr(bind_addr.as_ref())?;
-        self.router_handle.bind(sock_addr).await
+        let (socket_address, processor_address) = self.router_handle.bind(sock_addr).await?;
+        Ok(UdsListener {
+            socket_address,
+            processor_address,
+        })
+    }
+
+    /// Stops a listener started with [`UdsTransport::start_listener`] and removes its socket file.
+    ///
+    /// Connections which were already accepted by the listener are not closed.
+    pub async fn stop_listener(&self, processor_address: &Address) -> Result<()> {
+        self.router_handle.unbind(processor_address.clone()).await
+    }
+}
+
+/// A listener started with [`UdsTransport::start_listener`]
+#[derive(Clone, Debug)]
+pub struct UdsListener {
+    socket_address: SocketAddr,
+    processor_address: Address,
+}
+
+impl UdsListener {
+    /// Socket address the listener is bound to
+    pub fn socket_address(&self) -> &SocketAddr {
+        &self.socket_address
+    }
+
+    /// Address of the processor accepting incoming connections
+    pub fn processor_address(&self) -> &Address {
+        &self.processor_address
     }
 }
 
@@ -1,4 +1,5 @@
 use std::os::unix::net::SocketAddr;
+use std::path::PathBuf;
 
 use ockam_core::{
     async_trait, compat::sync::Arc, Address, AllowSourceAddress, AsyncTryClone, DenyAll, Mailbox,
@@ -19,17 +20,19 @@ use crate::{router::UdsRouterHandle, std_socket_addr_from_tokio, workers::UdsSen
 pub(crate) struct UdsListenProcessor {
     inner: UnixListener,
     router_handle: UdsRouterHandle,
+    path: PathBuf,
 }
 
 impl UdsListenProcessor {
     /// Binds a UDS socket at the given [`SocketAddr`]
     ///
-    /// Starts a [`Processor`] which listens for incoming connections to accept.
+    /// Starts a [`Processor`] which listens for incoming connections to accept,
+    /// and returns its address along with the bound [`SocketAddr`].
     pub(crate) async fn start(
         ctx: &Context,
         router_handle: UdsRouterHandle,
         addr: SocketAddr,
-    ) -> Result<SocketAddr> {
+    ) -> Result<(SocketAddr, Address)> {
         let path = match addr.as_pathname() {
             Some(p) => p,
             None => {
@@ -47,12 +50,13 @@ impl UdsListenProcessor {
         let processor = Self {
             inner,
             router_handle,
+            path: path.to_path_buf(),
         };
 
-        ctx.start_processor(Address::random_tagged("UdsListenProcessor"), processor)
-            .await?;
+        let address = Address::random_tagged("UdsListenProcessor");
+        ctx.start_processor(address.clone(), processor).await?;
 
-        Ok(std_sock_addr)
+        Ok((std_sock_addr, address))
     }
 }
 
@@ -64,6 +68,14 @@ impl Processor for UdsListenProcessor {
         ctx.set_cluster(crate::CLUSTER_NAME).await
     }
 
+    /// Remove the socket file so that the path can be bound again
+    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            debug!("Failed to remove socket {}: {}", self.path.display(), e);
+        }
+        Ok(())
+    }
+
     /// Listen for and accept incoming UDS connections.
     ///
     /// Register the peers socket address, and create a worker to communicate with the peer.
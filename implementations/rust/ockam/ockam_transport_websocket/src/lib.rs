@@ -67,6 +67,9 @@
 //! }
 //! ```
 //!
+//! Connections can also be secured with TLS (`wss://`) and tunneled through an
+//! HTTP proxy, see [`WebSocketConnectionOptions`] and [`WebSocketListenerOptions`].
+//!
 #![deny(unsafe_code)]
 #![warn(
 // missing_docs,
@@ -89,11 +92,13 @@ use std::net::SocketAddr;
 
 use ockam_core::{Result, TransportType};
 use ockam_transport_core::TransportError;
+pub use options::*;
 pub use transport::*;
 
 use crate::router::{WebSocketRouter, WebSocketRouterHandle};
 
 mod error;
+mod options;
 mod router;
 mod transport;
 mod workers;
@@ -0,0 +1,236 @@
+use std::fmt;
+use std::io::BufReader;
+use std::sync::Arc;
+
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig};
+use serde::{Deserialize, Serialize};
+use tokio_rustls::TlsAcceptor;
+
+use ockam_core::Result;
+
+use crate::error::WebSocketError;
+
+/// Options applied to outgoing WebSocket connections.
+///
+/// ```rust
+/// use ockam_transport_websocket::{HttpProxy, WebSocketConnectionOptions};
+///
+/// let options = WebSocketConnectionOptions::new()
+///     .with_tls()
+///     .with_proxy(HttpProxy::new("proxy.internal:3128").with_basic_auth("user", "password"))
+///     .with_header("Authorization", "Bearer my-token");
+/// ```
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct WebSocketConnectionOptions {
+    pub(crate) tls: bool,
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+    pub(crate) proxy: Option<HttpProxy>,
+    pub(crate) headers: Vec<(String, String)>,
+}
+
+impl WebSocketConnectionOptions {
+    /// Plaintext `ws://` connections, without proxy nor additional headers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connect with `wss://`. Unless root certificates are given with
+    /// [`with_root_certificates`](Self::with_root_certificates), the server
+    /// certificate is verified against the platform's native roots.
+    pub fn with_tls(mut self) -> Self {
+        self.tls = true;
+        self
+    }
+
+    /// Connect with `wss://` and verify the server certificate against the
+    /// PEM-encoded certificates given here instead of the platform's native roots.
+    /// This can be called several times to trust several PEM bundles.
+    pub fn with_root_certificates(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.tls = true;
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Tunnel the connection through an HTTP proxy with the `CONNECT` method
+    pub fn with_proxy(mut self, proxy: HttpProxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Add a header to the HTTP upgrade request
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Return true if the connection uses `wss://`
+    pub fn is_tls(&self) -> bool {
+        self.tls
+    }
+
+    /// Build the rustls configuration used for `wss://` connections
+    pub(crate) fn client_config(&self) -> Result<Arc<ClientConfig>> {
+        let mut roots = RootCertStore::empty();
+        if self.root_certificates.is_empty() {
+            let certificates =
+                rustls_native_certs::load_native_certs().map_err(|_| WebSocketError::Tls)?;
+            for certificate in certificates {
+                // Ignore the platform certificates that rustls is unable to parse
+                let _ = roots.add(&Certificate(certificate.0));
+            }
+        } else {
+            for pem in &self.root_certificates {
+                for certificate in read_certificates(pem)? {
+                    roots.add(&certificate).map_err(|_| WebSocketError::Tls)?;
+                }
+            }
+        }
+
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Ok(Arc::new(config))
+    }
+}
+
+impl fmt::Debug for WebSocketConnectionOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Header values often carry credentials, only their names are displayed
+        let header_names: Vec<&str> = self.headers.iter().map(|(name, _)| name.as_str()).collect();
+        f.debug_struct("WebSocketConnectionOptions")
+            .field("tls", &self.tls)
+            .field("root_certificates", &self.root_certificates.len())
+            .field("proxy", &self.proxy)
+            .field("headers", &header_names)
+            .finish()
+    }
+}
+
+/// An HTTP proxy supporting the `CONNECT` method
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HttpProxy {
+    pub(crate) address: String,
+    pub(crate) basic_auth: Option<(String, String)>,
+}
+
+impl HttpProxy {
+    /// Proxy listening at the given `host:port` address
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+            basic_auth: None,
+        }
+    }
+
+    /// Authenticate to the proxy with the `Basic` scheme
+    pub fn with_basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.basic_auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Address of the proxy
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+}
+
+impl fmt::Debug for HttpProxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpProxy")
+            .field("address", &self.address)
+            .field(
+                "basic_auth",
+                &self
+                    .basic_auth
+                    .as_ref()
+                    .map(|(username, _)| (username, "<password omitted>")),
+            )
+            .finish()
+    }
+}
+
+/// Options applied to a WebSocket listener
+#[derive(Clone, Default)]
+pub struct WebSocketListenerOptions {
+    pub(crate) tls_certificate: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl WebSocketListenerOptions {
+    /// Plaintext `ws://` listener
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept `wss://` connections, presenting the given PEM-encoded
+    /// certificate chain and private key
+    pub fn with_tls_certificate(
+        mut self,
+        certificate_chain: impl Into<Vec<u8>>,
+        private_key: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.tls_certificate = Some((certificate_chain.into(), private_key.into()));
+        self
+    }
+
+    /// Build the TLS acceptor of the listener, if TLS is enabled
+    pub(crate) fn tls_acceptor(&self) -> Result<Option<TlsAcceptor>> {
+        let (certificate_chain, private_key) = match &self.tls_certificate {
+            Some(tls_certificate) => tls_certificate,
+            None => return Ok(None),
+        };
+
+        let certificates = read_certificates(certificate_chain)?;
+        let private_key = read_private_key(private_key)?;
+        let config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(certificates, private_key)
+            .map_err(|_| WebSocketError::Tls)?;
+        Ok(Some(TlsAcceptor::from(Arc::new(config))))
+    }
+}
+
+impl fmt::Debug for WebSocketListenerOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketListenerOptions")
+            .field(
+                "tls_certificate",
+                &self
+                    .tls_certificate
+                    .as_ref()
+                    .map(|_| "<certificate and private key omitted>"),
+            )
+            .finish()
+    }
+}
+
+fn read_certificates(pem: &[u8]) -> Result<Vec<Certificate>> {
+    let certificates = rustls_pemfile::certs(&mut BufReader::new(pem))
+        .map_err(|_| WebSocketError::Tls)?
+        .into_iter()
+        .map(Certificate)
+        .collect::<Vec<_>>();
+    if certificates.is_empty() {
+        return Err(WebSocketError::Tls.into());
+    }
+    Ok(certificates)
+}
+
+fn read_private_key(pem: &[u8]) -> Result<PrivateKey> {
+    let items =
+        rustls_pemfile::read_all(&mut BufReader::new(pem)).map_err(|_| WebSocketError::Tls)?;
+    for item in items {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => return Ok(PrivateKey(key)),
+            _ => continue,
+        }
+    }
+    Err(WebSocketError::Tls.into())
+}
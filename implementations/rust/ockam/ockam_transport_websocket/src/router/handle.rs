@@ -1,13 +1,13 @@
-use core::str::FromStr;
 use std::net::{SocketAddr, ToSocketAddrs};
 
 use ockam_core::{async_trait, Address, AsyncTryClone, DenyAll, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
+use tokio_rustls::TlsAcceptor;
 
 use crate::router::{WebSocketRouterRequest, WebSocketRouterResponse};
 use crate::workers::{WebSocketListenProcessor, WorkerPair};
-use crate::{parse_socket_addr, WebSocketAddress};
+use crate::{parse_socket_addr, WebSocketConnectionOptions, WS};
 
 /// A handle to connect to a WebSocketRouter.
 ///
@@ -44,8 +44,7 @@ impl WebSocketRouterHandle {
         accepts.extend(
             pair.hostnames()
                 .iter()
-                .filter_map(|x| WebSocketAddress::from_str(x).ok())
-                .map(|addr| addr.into()),
+                .map(|hostname| Address::new(WS, hostname.clone())),
         );
         let self_addr = pair.tx_addr();
         let response = self
@@ -56,15 +55,51 @@ impl WebSocketRouterHandle {
             )
             .await?;
 
-        let WebSocketRouterResponse::Register(res) = response;
-
-        res
+        match response {
+            WebSocketRouterResponse::Register(res) => res,
+            _ => Err(TransportError::InvalidRouterResponseType.into()),
+        }
     }
 
     /// Bind an incoming connection listener for this router.
-    pub(crate) async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<SocketAddr> {
+    ///
+    /// Returns the bound socket address and the address of the listener processor.
+    pub(crate) async fn bind(
+        &self,
+        addr: impl Into<SocketAddr>,
+        tls_acceptor: Option<TlsAcceptor>,
+    ) -> Result<(SocketAddr, Address)> {
         let socket_addr = addr.into();
-        WebSocketListenProcessor::start(&self.ctx, self.async_try_clone().await?, socket_addr).await
+        WebSocketListenProcessor::start(
+            &self.ctx,
+            self.async_try_clone().await?,
+            socket_addr,
+            tls_acceptor,
+        )
+        .await
+    }
+
+    /// Stop an incoming connection listener previously started with `bind`.
+    pub(crate) async fn unbind(&self, processor_address: Address) -> Result<()> {
+        self.ctx.stop_processor(processor_address).await
+    }
+
+    /// Close the connection to the given peer.
+    pub(crate) async fn disconnect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
+        let response = self
+            .ctx
+            .send_and_receive(
+                self.api_addr.clone(),
+                WebSocketRouterRequest::Disconnect {
+                    peer: peer.as_ref().to_string(),
+                },
+            )
+            .await?;
+
+        match response {
+            WebSocketRouterResponse::Disconnect(res) => res,
+            _ => Err(TransportError::InvalidRouterResponseType.into()),
+        }
     }
 
     /// Return the peer's `SocketAddr` and `hostnames` given a plain `String` address.
@@ -96,15 +131,28 @@ impl WebSocketRouterHandle {
     }
 
     /// Establish an outgoing WS connection on an existing transport.
-    pub(crate) async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
-        // Get peer address and connect to it.
-        let (peer_addr, hostnames) = Self::resolve_peer(peer.as_ref())?;
-
-        // Create a new `WorkerPair` for the given peer, initializing a new pair
-        // of sender worker and receiver processor.
-        let pair = WorkerPair::from_client(&self.ctx, peer_addr, hostnames).await?;
+    ///
+    /// Returns the address of the sender worker of the connection.
+    /// An existing connection to the same peer is reused.
+    pub(crate) async fn connect<S: AsRef<str>>(
+        &self,
+        peer: S,
+        options: WebSocketConnectionOptions,
+    ) -> Result<Address> {
+        let response = self
+            .ctx
+            .send_and_receive(
+                self.api_addr.clone(),
+                WebSocketRouterRequest::Connect {
+                    peer: peer.as_ref().to_string(),
+                    options,
+                },
+            )
+            .await?;
 
-        // Handle node's register request.
-        self.register(&pair).await
+        match response {
+            WebSocketRouterResponse::Connect(res) => res,
+            _ => Err(TransportError::InvalidRouterResponseType.into()),
+        }
     }
 }
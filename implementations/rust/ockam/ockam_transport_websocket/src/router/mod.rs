@@ -1,4 +1,3 @@
-use core::str::FromStr;
 use std::collections::BTreeMap;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -12,7 +11,7 @@ use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
 
 use crate::workers::WorkerPair;
-use crate::{WebSocketAddress, WS};
+use crate::{WebSocketAddress, WebSocketConnectionOptions, WS};
 use serde::{Deserialize, Serialize};
 
 mod handle;
@@ -26,11 +25,25 @@ pub enum WebSocketRouterRequest {
         /// The clients own worker bus address.
         self_addr: Address,
     },
+    /// Connect to a peer, unless a connection to it already exists.
+    Connect {
+        /// Address of the peer
+        peer: String,
+        /// Options used if a new connection is established
+        options: WebSocketConnectionOptions,
+    },
+    /// Close the connection to a peer and forget about it.
+    Disconnect {
+        /// Address of the peer, as given to `connect`.
+        peer: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Message)]
 pub enum WebSocketRouterResponse {
     Register(Result<()>),
+    Connect(Result<Address>),
+    Disconnect(Result<()>),
 }
 
 /// A WebSocket address router and connection listener.
@@ -47,11 +60,16 @@ pub(crate) struct WebSocketRouter {
     api_addr: Address,
     map: BTreeMap<Address, Address>,
     allow_auto_connection: bool,
+    /// Options of the connections established when routing to an unknown peer
+    default_connection_options: WebSocketConnectionOptions,
 }
 
 impl WebSocketRouter {
     /// Create and register a new WebSocket router with the node context.
-    pub(crate) async fn register(ctx: &Context) -> Result<WebSocketRouterHandle> {
+    pub(crate) async fn register(
+        ctx: &Context,
+        default_connection_options: WebSocketConnectionOptions,
+    ) -> Result<WebSocketRouterHandle> {
         let main_addr = Address::random_tagged("WebSocketRouter.main_addr");
         let api_addr = Address::random_tagged("WebSocketRouter.api_addr");
         debug!(
@@ -74,6 +92,7 @@ impl WebSocketRouter {
             api_addr: api_addr.clone(),
             map: BTreeMap::new(),
             allow_auto_connection: true,
+            default_connection_options,
         };
 
         let handle = router.create_self_handle(ctx).await?;
@@ -141,6 +160,28 @@ impl Worker for WebSocketRouter {
                     )
                     .await?;
                 }
+                WebSocketRouterRequest::Connect { peer, options } => {
+                    trace!("handle_message connect: {:?}", peer);
+                    let res = self.handle_connect(peer, options).await;
+
+                    ctx.send_from_address(
+                        return_route,
+                        WebSocketRouterResponse::Connect(res),
+                        self.api_addr.clone(),
+                    )
+                    .await?;
+                }
+                WebSocketRouterRequest::Disconnect { peer } => {
+                    trace!("handle_message disconnect: {:?}", peer);
+                    let res = self.handle_disconnect(ctx, peer).await;
+
+                    ctx.send_from_address(
+                        return_route,
+                        WebSocketRouterResponse::Disconnect(res),
+                        self.api_addr.clone(),
+                    )
+                    .await?;
+                }
             };
         } else {
             return Err(TransportError::InvalidAddress.into());
@@ -174,7 +215,8 @@ impl WebSocketRouter {
 
             // TODO: Check if this is the hostname and we have existing/pending connection to this IP
             if self.allow_auto_connection {
-                next = self.connect(peer_str).await?;
+                let options = self.default_connection_options.clone();
+                next = self.connect(peer_str, options).await?;
             } else {
                 return Err(TransportError::UnknownRoute.into());
             }
@@ -219,25 +261,117 @@ impl WebSocketRouter {
         Ok(())
     }
 
-    async fn connect(&mut self, peer: String) -> Result<Address> {
+    async fn handle_connect(
+        &mut self,
+        peer: String,
+        options: WebSocketConnectionOptions,
+    ) -> Result<Address> {
+        if let Some(self_addr) = self.map.get(&Address::new(WS, peer.clone())) {
+            return Ok(self_addr.clone());
+        }
+        let (peer, options) = Self::parse_scheme(peer, options);
+        let (peer_address, _, _) = Self::resolve(peer.clone(), &options)?;
+        if let Some(self_addr) = self.map.get(&peer_address) {
+            return Ok(self_addr.clone());
+        }
+
+        self.connect(peer, options).await
+    }
+
+    async fn handle_disconnect(&mut self, ctx: &Context, peer: String) -> Result<()> {
+        let self_addr = match self.map.get(&Address::new(WS, peer.clone())) {
+            Some(self_addr) => self_addr.clone(),
+            None => {
+                let (peer, options) =
+                    Self::parse_scheme(peer.clone(), self.default_connection_options.clone());
+                let (peer_address, _, _) = Self::resolve(peer, &options)?;
+                match self.map.get(&peer_address) {
+                    Some(self_addr) => self_addr.clone(),
+                    None => return Err(TransportError::PeerNotFound.into()),
+                }
+            }
+        };
+        trace!("WS disconnection request: {} => {}", peer, self_addr);
+
+        // Remove every hostname/address pair pointing to that connection
+        self.map.retain(|_, addr| addr != &self_addr);
+
+        // The sender may have already stopped itself after a failed write
+        if let Err(e) = ctx.stop_worker(self_addr.clone()).await {
+            debug!("Failed to stop WS sender {}: {}", self_addr, e);
+        }
+
+        Ok(())
+    }
+
+    async fn connect(
+        &mut self,
+        peer: String,
+        options: WebSocketConnectionOptions,
+    ) -> Result<Address> {
+        let original_peer = peer.clone();
+        let (peer, options) = Self::parse_scheme(peer, options);
+
         // Get peer address and connect to it.
-        let (peer_addr, hostnames) = WebSocketRouterHandle::resolve_peer(peer)?;
+        let (peer_address, target, hostnames) = Self::resolve(peer, &options)?;
 
         // Create a new `WorkerPair` for the given peer, initializing a new pair
         // of sender worker and receiver processor.
-        let pair = WorkerPair::from_client(&self.ctx, peer_addr, hostnames).await?;
+        let pair =
+            WorkerPair::from_client(&self.ctx, peer_address, target, hostnames, options).await?;
 
         // Handle node's register request.
         let mut accepts = vec![pair.peer()];
         accepts.extend(
             pair.hostnames()
                 .iter()
-                .filter_map(|x| WebSocketAddress::from_str(x).ok())
-                .map(|addr| addr.into()),
+                .map(|hostname| Address::new(WS, hostname.clone())),
         );
+        // Routes can also refer to the peer with its scheme, eg. `wss://example.com:443`
+        let original_address = Address::new(WS, original_peer);
+        if !accepts.contains(&original_address) {
+            accepts.push(original_address);
+        }
         let self_addr = pair.tx_addr();
         self.handle_register(accepts, self_addr.clone()).await?;
 
         Ok(self_addr)
     }
+
+    /// Remove the `ws://` or `wss://` scheme of a peer, if any. `wss://` enables TLS.
+    fn parse_scheme(
+        peer: String,
+        options: WebSocketConnectionOptions,
+    ) -> (String, WebSocketConnectionOptions) {
+        if let Some(peer) = peer.strip_prefix("wss://") {
+            (peer.trim_end_matches('/').to_string(), options.with_tls())
+        } else if let Some(peer) = peer.strip_prefix("ws://") {
+            (peer.trim_end_matches('/').to_string(), options)
+        } else {
+            (peer, options)
+        }
+    }
+
+    /// Return the address identifying the connection to a peer, the `host:port`
+    /// target to connect to, and the hostnames of the peer.
+    ///
+    /// When going through a proxy, a hostname which can't be resolved locally is
+    /// left for the proxy to resolve.
+    fn resolve(
+        peer: String,
+        options: &WebSocketConnectionOptions,
+    ) -> Result<(Address, String, Vec<String>)> {
+        match WebSocketRouterHandle::resolve_peer(peer.clone()) {
+            Ok((peer_addr, hostnames)) => {
+                // Prefer the hostname, which is needed to verify TLS certificates
+                let target = hostnames
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| peer_addr.to_string());
+                Ok((WebSocketAddress::from(peer_addr).into(), target, hostnames))
+            }
+            Err(_) if options.proxy.is_some() => Ok((Address::new(WS, peer.clone()), peer, vec![])),
+            Err(e) => Err(e),
+        }
+    }
 }
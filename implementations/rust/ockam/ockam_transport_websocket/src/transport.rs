@@ -2,10 +2,13 @@ use core::fmt;
 use std::net::SocketAddr;
 use std::str::FromStr;
 
-use ockam_core::{async_trait, Address, Result};
+use ockam_core::{async_trait, Address, AsyncTryClone, Result};
 use ockam_node::{Context, HasContext};
 
-use crate::{parse_socket_addr, WebSocketRouter, WebSocketRouterHandle, WS};
+use crate::{
+    parse_socket_addr, WebSocketConnectionOptions, WebSocketListenerOptions, WebSocketRouter,
+    WebSocketRouterHandle, WS,
+};
 
 /// High level management interface for WebSocket transports.
 ///
@@ -45,8 +48,33 @@ use crate::{parse_socket_addr, WebSocketRouter, WebSocketRouterHandle, WS};
 /// ws.listen("127.0.0.1:9000").await?; // Listen on port 9000
 /// # Ok(()) }
 /// ```
+///
+/// Connections can use TLS (`wss://`), go through an HTTP proxy and carry
+/// additional headers, see [`WebSocketConnectionOptions`]. Listeners can
+/// accept TLS connections, see [`WebSocketListenerOptions`].
+///
+/// ```rust
+/// use ockam_transport_websocket::{
+///     HttpProxy, WebSocketConnectionOptions, WebSocketListenerOptions, WebSocketTransport,
+/// };
+/// # use ockam_core::Result;
+/// # use ockam_node::Context;
+/// # async fn test(ctx: Context, certificate_chain: Vec<u8>, private_key: Vec<u8>) -> Result<()> {
+/// let ws = WebSocketTransport::create(&ctx).await?;
+/// let options = WebSocketListenerOptions::new().with_tls_certificate(certificate_chain, private_key);
+/// ws.start_listener_with_options("0.0.0.0:443", options).await?;
+///
+/// let options = WebSocketConnectionOptions::new()
+///     .with_tls()
+///     .with_proxy(HttpProxy::new("proxy.internal:3128"));
+/// ws.connect_with_options("example.com:443", options).await?;
+/// # Ok(()) }
+/// ```
+#[derive(AsyncTryClone)]
+#[async_try_clone(crate = "ockam_core")]
 pub struct WebSocketTransport {
     router_handle: WebSocketRouterHandle,
+    default_connection_options: WebSocketConnectionOptions,
 }
 
 impl WebSocketTransport {
@@ -61,12 +89,42 @@ impl WebSocketTransport {
     /// # Ok(()) }
     /// ```
     pub async fn create(ctx: &Context) -> Result<WebSocketTransport> {
-        let router_handle = WebSocketRouter::register(ctx).await?;
-        Ok(Self { router_handle })
+        Self::create_with_options(ctx, WebSocketConnectionOptions::new()).await
+    }
+
+    /// Create a new WebSocket transport and router for the current node.
+    ///
+    /// The given options are used by [`connect`](crate::WebSocketTransport::connect)
+    /// and by the connections which are established when a message is routed
+    /// to a peer this transport is not connected to yet.
+    ///
+    /// ```rust
+    /// use ockam_transport_websocket::{HttpProxy, WebSocketConnectionOptions, WebSocketTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let options = WebSocketConnectionOptions::new().with_proxy(HttpProxy::new("proxy.internal:3128"));
+    /// let ws = WebSocketTransport::create_with_options(&ctx, options).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_with_options(
+        ctx: &Context,
+        default_connection_options: WebSocketConnectionOptions,
+    ) -> Result<WebSocketTransport> {
+        let router_handle =
+            WebSocketRouter::register(ctx, default_connection_options.clone()).await?;
+        Ok(Self {
+            router_handle,
+            default_connection_options,
+        })
     }
 
     /// Establish an outgoing WebSocket connection on an existing transport.
     ///
+    /// Returns the address of the worker sending messages to the peer.
+    ///
+    /// The peer can be prefixed with `wss://` to connect with TLS.
+    ///
     /// ```rust
     /// use ockam_transport_websocket::WebSocketTransport;
     /// # use ockam_node::Context;
@@ -77,8 +135,20 @@ impl WebSocketTransport {
     /// ws.connect("127.0.0.1:5000").await?; // and connect to port 5000
     /// # Ok(()) }
     /// ```
-    pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
-        self.router_handle.connect(peer).await
+    pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
+        self.connect_with_options(peer, self.default_connection_options.clone())
+            .await
+    }
+
+    /// Establish an outgoing WebSocket connection with specific options.
+    ///
+    /// The options are ignored if a connection to the same peer already exists.
+    pub async fn connect_with_options<S: AsRef<str>>(
+        &self,
+        peer: S,
+        options: WebSocketConnectionOptions,
+    ) -> Result<Address> {
+        self.router_handle.connect(peer, options).await
     }
 
     /// Start listening to incoming connections on an existing transport.
@@ -97,8 +167,75 @@ impl WebSocketTransport {
     /// ws.listen("127.0.0.1:8000").await?;
     /// # Ok(()) }
     pub async fn listen<S: AsRef<str>>(&self, bind_addr: S) -> Result<SocketAddr> {
+        Ok(self.start_listener(bind_addr).await?.socket_address())
+    }
+
+    /// Start listening to incoming connections on an existing transport.
+    ///
+    /// Unlike [`listen`](crate::WebSocketTransport::listen), the returned
+    /// [`WebSocketListener`] can be used to stop the listener later on.
+    ///
+    /// ```rust
+    /// use ockam_transport_websocket::WebSocketTransport;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let ws = WebSocketTransport::create(&ctx).await?;
+    /// let listener = ws.start_listener("127.0.0.1:8000").await?;
+    /// ws.stop_listener(listener.processor_address()).await?;
+    /// # Ok(()) }
+    pub async fn start_listener<S: AsRef<str>>(&self, bind_addr: S) -> Result<WebSocketListener> {
+        self.start_listener_with_options(bind_addr, WebSocketListenerOptions::new())
+            .await
+    }
+
+    /// Start listening to incoming connections with specific options, for
+    /// example to accept `wss://` connections.
+    pub async fn start_listener_with_options<S: AsRef<str>>(
+        &self,
+        bind_addr: S,
+        options: WebSocketListenerOptions,
+    ) -> Result<WebSocketListener> {
         let bind_addr = parse_socket_addr(bind_addr)?;
-        self.router_handle.bind(bind_addr).await
+        let tls_acceptor = options.tls_acceptor()?;
+        let (socket_address, processor_address) =
+            self.router_handle.bind(bind_addr, tls_acceptor).await?;
+        Ok(WebSocketListener {
+            socket_address,
+            processor_address,
+        })
+    }
+
+    /// Stop a listener started with [`start_listener`](crate::WebSocketTransport::start_listener).
+    ///
+    /// Connections which were already accepted by the listener are not closed.
+    pub async fn stop_listener(&self, processor_address: &Address) -> Result<()> {
+        self.router_handle.unbind(processor_address.clone()).await
+    }
+
+    /// Close the connection to the given peer, which was either established with
+    /// [`connect`](crate::WebSocketTransport::connect) or accepted by a listener.
+    pub async fn disconnect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
+        self.router_handle.disconnect(peer).await
+    }
+}
+
+/// A listener started with [`WebSocketTransport::start_listener`].
+#[derive(Clone, Debug)]
+pub struct WebSocketListener {
+    socket_address: SocketAddr,
+    processor_address: Address,
+}
+
+impl WebSocketListener {
+    /// Local socket address the listener is bound to
+    pub fn socket_address(&self) -> SocketAddr {
+        self.socket_address
+    }
+
+    /// Address of the processor accepting incoming connections
+    pub fn processor_address(&self) -> &Address {
+        &self.processor_address
     }
 }
 
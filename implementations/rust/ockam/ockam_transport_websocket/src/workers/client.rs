@@ -0,0 +1,99 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::Connector;
+
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+
+use crate::error::WebSocketError;
+use crate::workers::{TcpClientStream, WebSocketStream};
+use crate::{HttpProxy, WebSocketConnectionOptions};
+
+/// Upper bound on the size of the response headers sent back by a proxy
+const MAX_PROXY_RESPONSE_LENGTH: usize = 8 * 1024;
+
+/// Open a WebSocket connection to `target`, a `host:port` string, applying
+/// the proxy, TLS and header settings of the given options.
+pub(crate) async fn connect(
+    target: &str,
+    options: &WebSocketConnectionOptions,
+) -> Result<WebSocketStream<TcpClientStream>> {
+    let tcp_stream = match &options.proxy {
+        Some(proxy) => connect_through_proxy(target, proxy).await?,
+        None => TcpStream::connect(target)
+            .await
+            .map_err(TransportError::from)?,
+    };
+
+    let scheme = if options.tls { "wss" } else { "ws" };
+    let mut request = format!("{scheme}://{target}")
+        .into_client_request()
+        .map_err(WebSocketError::from)?;
+    for (name, value) in &options.headers {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| WebSocketError::Http)?;
+        let value = HeaderValue::from_str(value).map_err(|_| WebSocketError::Http)?;
+        request.headers_mut().append(name, value);
+    }
+
+    let connector = if options.tls {
+        Connector::Rustls(options.client_config()?)
+    } else {
+        Connector::Plain
+    };
+    let (stream, _) =
+        tokio_tungstenite::client_async_tls_with_config(request, tcp_stream, None, Some(connector))
+            .await
+            .map_err(WebSocketError::from)?;
+    Ok(stream)
+}
+
+/// Open a TCP tunnel to `target` with the `CONNECT` method of an HTTP proxy
+async fn connect_through_proxy(target: &str, proxy: &HttpProxy) -> Result<TcpStream> {
+    debug!(
+        "Connecting to {} through the proxy {}",
+        target, proxy.address
+    );
+    let mut stream = TcpStream::connect(&proxy.address)
+        .await
+        .map_err(TransportError::from)?;
+
+    let mut request = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+    if let Some((username, password)) = &proxy.basic_auth {
+        let credentials = STANDARD.encode(format!("{username}:{password}"));
+        request.push_str(&format!("Proxy-Authorization: Basic {credentials}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(TransportError::from)?;
+
+    // Read the response one byte at a time, so that nothing sent after the
+    // headers by the remote peer is consumed
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_PROXY_RESPONSE_LENGTH {
+            return Err(WebSocketError::Http.into());
+        }
+        let byte = stream.read_u8().await.map_err(TransportError::from)?;
+        response.push(byte);
+    }
+
+    let status_line = String::from_utf8_lossy(&response);
+    let status = status_line.split_whitespace().nth(1);
+    if status != Some("200") {
+        warn!(
+            "The proxy {} refused to connect to {}: {}",
+            proxy.address,
+            target,
+            status_line.lines().next().unwrap_or_default()
+        );
+        return Err(WebSocketError::Http.into());
+    }
+
+    Ok(stream)
+}
@@ -1,13 +1,18 @@
 use std::net::SocketAddr;
+use std::time::Duration;
 
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsAcceptor;
 
-use ockam_core::{async_trait, Address, AllowAll, Processor, Result};
+use ockam_core::{async_trait, Address, AllowAll, AsyncTryClone, DenyAll, Processor, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 
 use crate::{error::WebSocketError, workers::WorkerPair, WebSocketRouterHandle};
 
+/// Maximum duration of the TLS and WebSocket handshakes of an incoming connection
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// A worker that runs in the background as a `Processor` waiting for incoming
 /// clients' connections.
 ///
@@ -16,6 +21,7 @@ use crate::{error::WebSocketError, workers::WorkerPair, WebSocketRouterHandle};
 pub(crate) struct WebSocketListenProcessor {
     inner: TcpListener,
     router_handle: WebSocketRouterHandle,
+    tls_acceptor: Option<TlsAcceptor>,
 }
 
 impl WebSocketListenProcessor {
@@ -24,7 +30,8 @@ impl WebSocketListenProcessor {
         ctx: &Context,
         router_handle: WebSocketRouterHandle,
         addr: SocketAddr,
-    ) -> Result<SocketAddr> {
+        tls_acceptor: Option<TlsAcceptor>,
+    ) -> Result<(SocketAddr, Address)> {
         debug!("Binding WebSocketListener to {}", addr);
         let inner = TcpListener::bind(addr)
             .await
@@ -33,14 +40,17 @@ impl WebSocketListenProcessor {
         let processor = Self {
             inner,
             router_handle,
+            tls_acceptor,
         };
         let waddr = Address::random_tagged("WebSocketListenProcessor");
         ctx.start_processor_with_access_control(
-            waddr, processor, AllowAll, // FIXME: @ac
+            waddr.clone(),
+            processor,
+            AllowAll, // FIXME: @ac
             AllowAll, // FIXME: @ac
         )
         .await?;
-        Ok(saddr)
+        Ok((saddr, waddr))
     }
 }
 
@@ -57,18 +67,66 @@ impl Processor for WebSocketListenProcessor {
 
         // Wait for an incoming connection
         let (tcp_stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
-        let ws_stream = tokio_tungstenite::accept_async(tcp_stream)
-            .await
-            .map_err(WebSocketError::from)?;
-        debug!("TCP connection accepted");
-
-        // Spawn a connection worker for it
-        let pair = WorkerPair::from_server(ctx, ws_stream, peer, vec![]).await?;
 
-        // Register the connection with the local TcpRouter
-        self.router_handle.register(&pair).await?;
-        debug!("TCP connection registered");
+        // The handshakes run in their own task, so that a slow client doesn't
+        // prevent other clients from connecting, and a failed handshake doesn't
+        // stop the listener
+        let ctx = ctx
+            .new_detached(
+                Address::random_tagged("WebSocketListenProcessor.handshake.detached"),
+                DenyAll,
+                DenyAll,
+            )
+            .await?;
+        let router_handle = self.router_handle.async_try_clone().await?;
+        let tls_acceptor = self.tls_acceptor.clone();
+        tokio::spawn(async move {
+            let result = tokio::time::timeout(
+                HANDSHAKE_TIMEOUT,
+                accept_connection(&ctx, &router_handle, tls_acceptor, tcp_stream, peer),
+            )
+            .await;
+            match result {
+                Ok(Ok(())) => debug!("WebSocket connection with {} registered", peer),
+                Ok(Err(e)) => warn!("WebSocket handshake with {} failed: {}", peer, e),
+                Err(_) => warn!("WebSocket handshake with {} timed out", peer),
+            }
+        });
 
         Ok(true)
     }
 }
+
+/// Run the TLS and WebSocket handshakes of an accepted connection, then spawn
+/// a connection worker for it and register it with the router
+async fn accept_connection(
+    ctx: &Context,
+    router_handle: &WebSocketRouterHandle,
+    tls_acceptor: Option<TlsAcceptor>,
+    tcp_stream: TcpStream,
+    peer: SocketAddr,
+) -> Result<()> {
+    let pair = match tls_acceptor {
+        Some(tls_acceptor) => {
+            let tls_stream = tls_acceptor.accept(tcp_stream).await.map_err(|e| {
+                debug!("TLS handshake with {} failed: {}", peer, e);
+                WebSocketError::Tls
+            })?;
+            let ws_stream = tokio_tungstenite::accept_async(tls_stream)
+                .await
+                .map_err(WebSocketError::from)?;
+            debug!("TLS connection accepted");
+            WorkerPair::from_server(ctx, ws_stream, peer, vec![]).await?
+        }
+        None => {
+            let ws_stream = tokio_tungstenite::accept_async(tcp_stream)
+                .await
+                .map_err(WebSocketError::from)?;
+            debug!("TCP connection accepted");
+            WorkerPair::from_server(ctx, ws_stream, peer, vec![]).await?
+        }
+    };
+
+    // Register the connection with the local router
+    router_handle.register(&pair).await
+}
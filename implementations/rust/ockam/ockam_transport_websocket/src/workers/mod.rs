@@ -3,6 +3,7 @@ pub(crate) use receiver::*;
 pub(crate) use sender::*;
 pub(crate) use stream::*;
 
+mod client;
 mod listener;
 mod receiver;
 mod sender;
@@ -6,7 +6,6 @@ use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
 use tokio_tungstenite::tungstenite::protocol::Message as WebSocketMessage;
 
-use crate::error::WebSocketError;
 use ockam_core::{
     async_trait, route, Address, AllowAll, Any, Decodable, Encodable, LocalMessage, Mailbox,
     Mailboxes, Result, Routed, TransportMessage, Worker,
@@ -15,9 +14,9 @@ use ockam_node::{Context, DelayedEvent, WorkerBuilder};
 use ockam_transport_core::TransportError;
 
 use crate::workers::{
-    AsyncStream, TcpClientStream, TcpServerStream, WebSocketRecvProcessor, WebSocketStream,
+    client, AsyncStream, ServerStream, TcpClientStream, WebSocketRecvProcessor, WebSocketStream,
 };
-use crate::WebSocketAddress;
+use crate::{WebSocketAddress, WebSocketConnectionOptions};
 
 /// Transmit and receive peers of a WebSocket connection.
 #[derive(Debug)]
@@ -41,17 +40,22 @@ impl WorkerPair {
     /// Spawn instances of `WebSocketSendWorker` and `WebSocketRecvProcessor` and
     /// returns a `WorkerPair` instance that will be registered by the `WebSocketRouter`.
     ///
-    /// The WebSocket stream is created when the `WebSocketSendWorker` is initialized.
+    /// The WebSocket stream is created when the `WebSocketSendWorker` is initialized,
+    /// by connecting to `target` with the given options.
     pub(crate) async fn from_client(
         ctx: &Context,
-        peer: SocketAddr,
+        peer: Address,
+        target: String,
         hostnames: Vec<String>,
+        options: WebSocketConnectionOptions,
     ) -> Result<WorkerPair> {
         trace!("Creating new WS worker pair");
 
         let internal_addr = Address::random_tagged("WebSocketSender.internal.from_client");
         let sender = WebSocketSendWorker::<TcpClientStream>::new(
-            peer,
+            peer.clone(),
+            target,
+            options,
             internal_addr.clone(),
             DelayedEvent::create(ctx, internal_addr.clone(), vec![]).await?,
         );
@@ -78,25 +82,29 @@ impl WorkerPair {
         // Return a handle to the worker pair
         Ok(WorkerPair {
             hostnames,
-            peer: WebSocketAddress::from(peer).into(),
+            peer,
             tx_addr,
         })
     }
 
     /// Spawn instances of `WebSocketSendWorker` and `WebSocketRecvProcessor` and
     /// returns a `WorkerPair` instance that will be registered by the `WebSocketRouter`.
-    pub(crate) async fn from_server(
+    pub(crate) async fn from_server<S>(
         ctx: &Context,
-        stream: WebSocketStream<TcpServerStream>,
+        stream: WebSocketStream<S>,
         peer: SocketAddr,
         hostnames: Vec<String>,
-    ) -> Result<WorkerPair> {
+    ) -> Result<WorkerPair>
+    where
+        S: ServerStream,
+    {
         trace!("Creating new WS worker pair");
 
+        let peer: Address = WebSocketAddress::from(peer).into();
         let internal_addr = Address::random_tagged("WebSocketSender.internal.from_server");
-        let sender = WebSocketSendWorker::<TcpServerStream>::new(
+        let sender = WebSocketSendWorker::<S>::from_stream(
             stream,
-            peer,
+            peer.clone(),
             internal_addr.clone(),
             DelayedEvent::create(ctx, internal_addr.clone(), vec![]).await?,
         );
@@ -122,7 +130,7 @@ impl WorkerPair {
         // Return a handle to the worker pair
         Ok(WorkerPair {
             hostnames,
-            peer: WebSocketAddress::from(peer).into(),
+            peer,
             tx_addr,
         })
     }
@@ -139,7 +147,10 @@ where
 {
     ws_stream: Option<SplitStream<WebSocketStream<S>>>,
     ws_sink: Option<SplitSink<WebSocketStream<S>, WebSocketMessage>>,
-    peer: SocketAddr,
+    peer: Address,
+    /// `host:port` to connect to, along with the connection options,
+    /// for the workers which initiate the connection
+    connect_to: Option<(String, WebSocketConnectionOptions)>,
     internal_addr: Address,
     heartbeat: DelayedEvent<Vec<u8>>,
     heartbeat_interval: Option<Duration>,
@@ -152,7 +163,7 @@ where
     async fn handle_initialize(&mut self, ctx: &mut Context) -> Result<()> {
         if let Some(ws_stream) = self.ws_stream.take() {
             let rx_addr = Address::random_tagged("WebSocketSendWorker.rx_addr");
-            let receiver = WebSocketRecvProcessor::new(ws_stream, self.peer);
+            let receiver = WebSocketRecvProcessor::new(ws_stream, self.peer.clone());
             ctx.start_processor_with_access_control(
                 rx_addr.clone(),
                 receiver,
@@ -226,10 +237,13 @@ where
     }
 }
 
-impl WebSocketSendWorker<TcpServerStream> {
-    fn new(
-        stream: WebSocketStream<TcpServerStream>,
-        peer: SocketAddr,
+impl<S> WebSocketSendWorker<S>
+where
+    S: ServerStream,
+{
+    fn from_stream(
+        stream: WebSocketStream<S>,
+        peer: Address,
         internal_addr: Address,
         heartbeat: DelayedEvent<Vec<u8>>,
     ) -> Self {
@@ -238,6 +252,7 @@ impl WebSocketSendWorker<TcpServerStream> {
             ws_sink: Some(ws_sink),
             ws_stream: Some(ws_stream),
             peer,
+            connect_to: None,
             internal_addr,
             heartbeat,
             heartbeat_interval: None,
@@ -246,11 +261,18 @@ impl WebSocketSendWorker<TcpServerStream> {
 }
 
 impl WebSocketSendWorker<TcpClientStream> {
-    fn new(peer: SocketAddr, internal_addr: Address, heartbeat: DelayedEvent<Vec<u8>>) -> Self {
+    fn new(
+        peer: Address,
+        target: String,
+        options: WebSocketConnectionOptions,
+        internal_addr: Address,
+        heartbeat: DelayedEvent<Vec<u8>>,
+    ) -> Self {
         Self {
             ws_stream: None,
             ws_sink: None,
             peer,
+            connect_to: Some((target, options)),
             internal_addr,
             heartbeat,
             heartbeat_interval: None,
@@ -259,10 +281,11 @@ impl WebSocketSendWorker<TcpClientStream> {
 
     async fn initialize_stream(&mut self) -> Result<()> {
         if self.ws_stream.is_none() {
-            let peer = WebSocketAddress::from(self.peer).to_string();
-            let (stream, _) = tokio_tungstenite::connect_async(peer)
-                .await
-                .map_err(WebSocketError::from)?;
+            let (target, options) = match &self.connect_to {
+                Some(connect_to) => connect_to,
+                None => return Err(TransportError::GenericIo.into()),
+            };
+            let stream = client::connect(target, options).await?;
             let (ws_sink, ws_stream) = stream.split();
             self.ws_sink = Some(ws_sink);
             self.ws_stream = Some(ws_stream);
@@ -272,7 +295,10 @@ impl WebSocketSendWorker<TcpClientStream> {
 }
 
 #[async_trait]
-impl Worker for WebSocketSendWorker<TcpServerStream> {
+impl<S> Worker for WebSocketSendWorker<S>
+where
+    S: ServerStream,
+{
     type Message = Any;
     type Context = Context;
 
@@ -4,6 +4,9 @@ pub(crate) type WebSocketStream<S> = tokio_tungstenite::WebSocketStream<S>;
 /// Stream created when a server accepts a new connection.
 pub(crate) type TcpServerStream = tokio::net::TcpStream;
 
+/// Stream created when a server with a TLS certificate accepts a new connection.
+pub(crate) type TlsServerStream = tokio_rustls::server::TlsStream<TcpServerStream>;
+
 /// Stream created when a client connects to a server.
 pub(crate) type TcpClientStream = tokio_tungstenite::MaybeTlsStream<TcpServerStream>;
 
@@ -20,3 +23,13 @@ pub(crate) trait AsyncStream:
 impl AsyncStream for TcpClientStream {}
 
 impl AsyncStream for TcpServerStream {}
+
+impl AsyncStream for TlsServerStream {}
+
+/// Marker for the streams accepted by a listener, which are already
+/// connected when the connection worker is created.
+pub(crate) trait ServerStream: AsyncStream {}
+
+impl ServerStream for TcpServerStream {}
+
+impl ServerStream for TlsServerStream {}
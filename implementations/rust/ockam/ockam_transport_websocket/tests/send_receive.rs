@@ -1,7 +1,25 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
 use ockam_core::compat::rand::{self, Rng};
-use ockam_core::{route, Result, Routed, Worker};
-use ockam_node::Context;
-use ockam_transport_websocket::{WebSocketTransport, WS};
+use ockam_core::{route, Address, Result, Routed, Worker};
+use ockam_node::{Context, MessageSendReceiveOptions};
+use ockam_transport_websocket::{
+    HttpProxy, WebSocketConnectionOptions, WebSocketListenerOptions, WebSocketTransport, WS,
+};
+
+/// Certificate of the authority which signed the certificate of `localhost`
+const CA_CERTIFICATE: &[u8] = include_bytes!("certs/ca.pem");
+const LOCALHOST_CERTIFICATE: &[u8] = include_bytes!("certs/localhost.pem");
+const LOCALHOST_PRIVATE_KEY: &[u8] = include_bytes!("certs/localhost.key");
+
+/// Base64 encoding of `user:password`
+const PROXY_CREDENTIALS: &str = "dXNlcjpwYXNzd29yZA==";
 
 #[ignore]
 #[ockam_macros::test]
@@ -30,6 +48,281 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+#[ignore]
+#[ockam_macros::test]
+async fn disconnect_and_stop_listener(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let listener = transport.start_listener("127.0.0.1:0").await?;
+    let peer = listener.socket_address().to_string();
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let r = route![(WS, peer.clone()), "echoer"];
+    let reply = ctx.send_and_receive::<String>(r, "Hello".into()).await?;
+    assert_eq!(reply, "Hello");
+
+    // The connection is known to the router until we disconnect it
+    transport.disconnect(&peer).await?;
+    assert!(transport.disconnect(&peer).await.is_err());
+
+    transport
+        .stop_listener(listener.processor_address())
+        .await?;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn tls_listener_requires_a_valid_certificate(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let options = WebSocketListenerOptions::new().with_tls_certificate("not a pem", "not a pem");
+    assert!(transport
+        .start_listener_with_options("127.0.0.1:0", options)
+        .await
+        .is_err());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn send_receive_with_tls(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let options = WebSocketListenerOptions::new()
+        .with_tls_certificate(LOCALHOST_CERTIFICATE, LOCALHOST_PRIVATE_KEY);
+    let listener = transport
+        .start_listener_with_options("127.0.0.1:0", options)
+        .await?;
+    let port = listener.socket_address().port();
+    ctx.start_worker("echoer", Echoer).await?;
+
+    // The certificate of the listener is not signed by a native root
+    let untrusted = transport
+        .connect_with_options(
+            format!("localhost:{port}"),
+            WebSocketConnectionOptions::new().with_tls(),
+        )
+        .await;
+    if let Ok(sender) = untrusted {
+        assert!(!echoes(ctx, sender).await);
+        transport.disconnect(format!("localhost:{port}")).await?;
+    }
+
+    let sender = transport
+        .connect_with_options(
+            format!("localhost:{port}"),
+            WebSocketConnectionOptions::new().with_root_certificates(CA_CERTIFICATE),
+        )
+        .await?;
+    assert!(echoes(ctx, sender).await);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn failed_and_slow_handshakes_dont_stop_the_listener(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let listener = transport.start_listener("127.0.0.1:0").await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    // A client which never sends its handshake
+    let _idle = TcpStream::connect(listener.socket_address()).await.unwrap();
+    // A client which sends an invalid handshake
+    let mut invalid = TcpStream::connect(listener.socket_address()).await.unwrap();
+    invalid.write_all(b"not a websocket\r\n\r\n").await.unwrap();
+    drop(invalid);
+
+    let sender = transport
+        .connect(listener.socket_address().to_string())
+        .await?;
+    assert!(echoes(ctx, sender).await);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn connect_through_a_proxy(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let listener = transport.start_listener("127.0.0.1:0").await?;
+    ctx.start_worker("echoer", Echoer).await?;
+    let proxy = start_proxy(None).await;
+
+    let options = WebSocketConnectionOptions::new().with_proxy(HttpProxy::new(proxy.to_string()));
+    let sender = transport
+        .connect_with_options(listener.socket_address().to_string(), options)
+        .await?;
+    assert!(echoes(ctx, sender).await);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn connect_through_a_proxy_with_basic_auth(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let listener = transport.start_listener("127.0.0.1:0").await?;
+    let peer = listener.socket_address().to_string();
+    ctx.start_worker("echoer", Echoer).await?;
+    let proxy = start_proxy(Some(PROXY_CREDENTIALS)).await;
+
+    // The proxy refuses the connection without valid credentials
+    for proxy in [
+        HttpProxy::new(proxy.to_string()),
+        HttpProxy::new(proxy.to_string()).with_basic_auth("user", "wrong"),
+    ] {
+        let options = WebSocketConnectionOptions::new().with_proxy(proxy);
+        if let Ok(sender) = transport.connect_with_options(&peer, options).await {
+            assert!(!echoes(ctx, sender).await);
+            transport.disconnect(&peer).await?;
+        }
+    }
+
+    let options = WebSocketConnectionOptions::new()
+        .with_proxy(HttpProxy::new(proxy.to_string()).with_basic_auth("user", "password"));
+    let sender = transport.connect_with_options(&peer, options).await?;
+    assert!(echoes(ctx, sender).await);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn custom_headers_are_sent_with_the_upgrade_request(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+
+    // A WebSocket server recording the headers of the upgrade request
+    let server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_address = server.local_addr().unwrap();
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let (stream, _) = server.accept().await.unwrap();
+        let record_headers = |request: &Request, response: Response| {
+            let _ = tx.send(request.headers().clone());
+            Ok(response)
+        };
+        let _ws_stream = tokio_tungstenite::accept_hdr_async(stream, record_headers).await;
+        // Keep the connection open until the end of the test
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    });
+
+    let options = WebSocketConnectionOptions::new()
+        .with_header("Authorization", "Bearer my-token")
+        .with_header("X-Ockam-Test", "1");
+    transport
+        .connect_with_options(server_address.to_string(), options)
+        .await?;
+
+    let headers = tokio::time::timeout(Duration::from_secs(5), rx)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(headers["authorization"], "Bearer my-token");
+    assert_eq!(headers["x-ockam-test"], "1");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[test]
+fn secrets_are_not_displayed_in_debug_output() {
+    let options = WebSocketConnectionOptions::new()
+        .with_proxy(HttpProxy::new("proxy.internal:3128").with_basic_auth("user", "password"))
+        .with_header("Authorization", "Bearer my-token");
+    let debug = format!("{:?}", options);
+    assert!(debug.contains("proxy.internal:3128"));
+    assert!(debug.contains("Authorization"));
+    assert!(!debug.contains("\"password\""));
+    assert!(!debug.contains("my-token"));
+
+    let options = WebSocketListenerOptions::new()
+        .with_tls_certificate(LOCALHOST_CERTIFICATE, LOCALHOST_PRIVATE_KEY);
+    let debug = format!("{:?}", options);
+    assert!(!debug.contains("PRIVATE KEY"));
+}
+
+/// Return true if a message sent through a connection is echoed back
+async fn echoes(ctx: &Context, sender: Address) -> bool {
+    let result = ctx
+        .send_and_receive_extended::<String>(
+            route![sender, "echoer"],
+            "Hello".to_string(),
+            MessageSendReceiveOptions::new().with_timeout(Duration::from_secs(2)),
+        )
+        .await;
+    matches!(result, Ok(reply) if reply.as_body() == "Hello")
+}
+
+/// Start an HTTP proxy accepting `CONNECT` requests, which requires the
+/// given `Basic` credentials if any
+async fn start_proxy(credentials: Option<&'static str>) -> SocketAddr {
+    let proxy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = proxy.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut client, _)) = proxy.accept().await {
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                while !request.ends_with(b"\r\n\r\n") {
+                    match client.read_u8().await {
+                        Ok(byte) => request.push(byte),
+                        Err(_) => return,
+                    }
+                }
+                let request = String::from_utf8_lossy(&request).to_string();
+                let target = request.split_whitespace().nth(1).unwrap_or_default();
+
+                if let Some(credentials) = credentials {
+                    let authorization = format!("Proxy-Authorization: Basic {credentials}\r\n");
+                    if !request.contains(&authorization) {
+                        let _ = client
+                            .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                            .await;
+                        return;
+                    }
+                }
+
+                let mut server = match TcpStream::connect(target).await {
+                    Ok(server) => server,
+                    Err(_) => {
+                        let _ = client.write_all(b"HTTP/1.1 502 Bad Gateway\r\n\r\n").await;
+                        return;
+                    }
+                };
+                if client
+                    .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                    .await
+                    .is_ok()
+                {
+                    let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+                }
+            });
+        }
+    });
+    address
+}
+
 pub struct Echoer;
 
 #[ockam_core::worker]
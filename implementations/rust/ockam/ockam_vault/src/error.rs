@@ -33,6 +33,10 @@ pub enum VaultError {
     InvalidSha256Len,
     /// Invalid Signature Size
     InvalidSignatureSize,
+    /// Invalid KEM Ciphertext length
+    InvalidCiphertextLength,
+    /// The operation is not supported by this vault
+    UnsupportedOperation,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -54,6 +58,8 @@ impl core::fmt::Display for VaultError {
             Self::KeyNotFound => write!(f, "key not found"),
             Self::InvalidSha256Len => write!(f, "invalid sha256 len"),
             Self::InvalidSignatureSize => write!(f, "invalid signature len"),
+            Self::InvalidCiphertextLength => write!(f, "invalid KEM ciphertext len"),
+            Self::UnsupportedOperation => write!(f, "operation not supported by this vault"),
         }
     }
 }
@@ -65,6 +71,7 @@ impl From<VaultError> for Error {
         let kind = match err {
             InvalidPublicKey | InvalidKeyType | InvalidHkdfOutputType => Kind::Misuse,
             UnknownEcdhKeyType => Kind::NotFound,
+            UnsupportedOperation => Kind::Unsupported,
             _ => Kind::Invalid,
         };
 
@@ -21,6 +21,21 @@ impl X25519SecretKey {
     }
 }
 
+/// ML-KEM-768 Decapsulation Key, encoded as specified by FIPS 203.
+#[derive(Eq, PartialEq, Clone, Zeroize, ZeroizeOnDrop)]
+pub struct KemSecretKey(Vec<u8>);
+
+impl KemSecretKey {
+    /// Constructor.
+    pub fn new(key: Vec<u8>) -> Self {
+        Self(key)
+    }
+
+    pub(crate) fn key(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
 /// Buffer with sensitive data, like HKDF output.
 #[derive(Eq, PartialEq, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct BufferSecret(Vec<u8>);
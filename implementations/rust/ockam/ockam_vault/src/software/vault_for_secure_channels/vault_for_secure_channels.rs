@@ -2,9 +2,9 @@ use super::aes::make_aes;
 
 use crate::{
     AeadSecret, AeadSecretKeyHandle, BufferSecret, HKDFNumberOfOutputs, HandleToSecret, HashOutput,
-    HkdfOutput, SecretBufferHandle, SoftwareVaultForVerifyingSignatures, VaultError,
-    VaultForSecureChannels, X25519PublicKey, X25519SecretKey, X25519SecretKeyHandle,
-    AEAD_SECRET_LENGTH,
+    HkdfOutput, KemSecretKey, KemSecretKeyHandle, SecretBufferHandle,
+    SoftwareVaultForVerifyingSignatures, VaultError, VaultForSecureChannels, X25519PublicKey,
+    X25519SecretKey, X25519SecretKeyHandle, AEAD_SECRET_LENGTH,
 };
 
 use ockam_core::compat::collections::BTreeMap;
@@ -15,13 +15,25 @@ use ockam_core::{async_trait, compat::boxed::Box, Result};
 use ockam_node::{InMemoryKeyValueStorage, KeyValueStorage};
 
 use crate::legacy::{KeyId, StoredSecret};
+#[cfg(feature = "pqc")]
+use crate::{KemCiphertext, KemPublicKey};
+#[cfg(feature = "pqc")]
+use ml_kem::kem::{Decapsulate, Encapsulate};
+#[cfg(feature = "pqc")]
+use ml_kem::{Ciphertext, EncodedSizeUser, KemCore, MlKem768};
 use sha2::{Digest, Sha256};
 
+#[cfg(feature = "pqc")]
+type KemDecapsulationKey = <MlKem768 as KemCore>::DecapsulationKey;
+#[cfg(feature = "pqc")]
+type KemEncapsulationKey = <MlKem768 as KemCore>::EncapsulationKey;
+
 /// [`SecureChannelVault`] implementation using software
 pub struct SoftwareVaultForSecureChannels {
     ephemeral_buffer_secrets: Arc<RwLock<BTreeMap<SecretBufferHandle, BufferSecret>>>,
     ephemeral_aead_secrets: Arc<RwLock<BTreeMap<AeadSecretKeyHandle, AeadSecret>>>,
     ephemeral_x25519_secrets: Arc<RwLock<BTreeMap<X25519SecretKeyHandle, X25519SecretKey>>>,
+    ephemeral_kem_secrets: Arc<RwLock<BTreeMap<KemSecretKeyHandle, KemSecretKey>>>,
     // Use String as a key for backwards compatibility
     static_x25519_secrets: Arc<dyn KeyValueStorage<KeyId, StoredSecret>>,
 }
@@ -33,6 +45,7 @@ impl SoftwareVaultForSecureChannels {
             ephemeral_buffer_secrets: Default::default(),
             ephemeral_aead_secrets: Default::default(),
             ephemeral_x25519_secrets: Default::default(),
+            ephemeral_kem_secrets: Default::default(),
             static_x25519_secrets: storage,
         }
     }
@@ -91,6 +104,11 @@ impl SoftwareVaultForSecureChannels {
         self.ephemeral_x25519_secrets.read().unwrap().len()
     }
 
+    /// Return the total number of ephemeral ML-KEM secrets present in the Vault
+    pub fn number_of_ephemeral_kem_secrets(&self) -> usize {
+        self.ephemeral_kem_secrets.read().unwrap().len()
+    }
+
     /// Return the total number of ephemeral buffer secrets present in the Vault
     pub fn number_of_ephemeral_buffer_secrets(&self) -> usize {
         self.ephemeral_buffer_secrets.read().unwrap().len()
@@ -160,6 +178,39 @@ impl SoftwareVaultForSecureChannels {
         X25519SecretKey::new(secret.to_bytes())
     }
 
+    #[cfg(feature = "pqc")]
+    fn import_kem_secret_key(secret: &KemSecretKey) -> Result<KemDecapsulationKey> {
+        let encoded = secret
+            .key()
+            .try_into()
+            .map_err(|_| VaultError::InvalidSecretLength)?;
+        Ok(KemDecapsulationKey::from_bytes(&encoded))
+    }
+
+    #[cfg(feature = "pqc")]
+    fn import_kem_public_key(public_key: &KemPublicKey) -> Result<KemEncapsulationKey> {
+        let encoded = public_key
+            .0
+            .as_slice()
+            .try_into()
+            .map_err(|_| VaultError::InvalidPublicLength)?;
+        Ok(KemEncapsulationKey::from_bytes(&encoded))
+    }
+
+    #[cfg(feature = "pqc")]
+    fn compute_handle_for_kem_public_key(public_key: &KemPublicKey) -> KemSecretKeyHandle {
+        let handle = Sha256::digest(&public_key.0);
+        KemSecretKeyHandle(HandleToSecret::new(handle.to_vec()))
+    }
+
+    #[cfg(feature = "pqc")]
+    fn get_kem_secret(&self, handle: &KemSecretKeyHandle) -> Result<KemSecretKey> {
+        match self.ephemeral_kem_secrets.read().unwrap().get(handle) {
+            Some(secret) => Ok(secret.clone()),
+            None => Err(VaultError::KeyNotFound.into()),
+        }
+    }
+
     fn import_buffer_secret_impl(&self, secret: BufferSecret) -> SecretBufferHandle {
         let handle = Self::generate_buffer_handle();
 
@@ -345,6 +396,83 @@ impl VaultForSecureChannels for SoftwareVaultForSecureChannels {
         Ok(Self::compute_handle_for_public_key(public_key))
     }
 
+    #[cfg(feature = "pqc")]
+    async fn generate_ephemeral_kem_secret_key(&self) -> Result<KemSecretKeyHandle> {
+        let (decapsulation_key, encapsulation_key) = MlKem768::generate(&mut thread_rng());
+        let public_key = KemPublicKey(encapsulation_key.as_bytes().to_vec());
+        let handle = Self::compute_handle_for_kem_public_key(&public_key);
+        let secret = KemSecretKey::new(decapsulation_key.as_bytes().to_vec());
+
+        self.ephemeral_kem_secrets
+            .write()
+            .unwrap()
+            .insert(handle.clone(), secret);
+
+        Ok(handle)
+    }
+
+    async fn delete_ephemeral_kem_secret_key(
+        &self,
+        secret_key_handle: KemSecretKeyHandle,
+    ) -> Result<bool> {
+        Ok(self
+            .ephemeral_kem_secrets
+            .write()
+            .unwrap()
+            .remove(&secret_key_handle)
+            .is_some())
+    }
+
+    #[cfg(feature = "pqc")]
+    async fn get_kem_public_key(
+        &self,
+        secret_key_handle: &KemSecretKeyHandle,
+    ) -> Result<KemPublicKey> {
+        let secret = self.get_kem_secret(secret_key_handle)?;
+        let decapsulation_key = Self::import_kem_secret_key(&secret)?;
+
+        Ok(KemPublicKey(
+            decapsulation_key.encapsulation_key().as_bytes().to_vec(),
+        ))
+    }
+
+    #[cfg(feature = "pqc")]
+    async fn kem_encapsulate(
+        &self,
+        peer_public_key: &KemPublicKey,
+    ) -> Result<(KemCiphertext, SecretBufferHandle)> {
+        let encapsulation_key = Self::import_kem_public_key(peer_public_key)?;
+        let (ciphertext, shared_secret) = encapsulation_key
+            .encapsulate(&mut thread_rng())
+            .map_err(|_| VaultError::InvalidPublicKey)?;
+        let shared_secret = BufferSecret::new(shared_secret.to_vec());
+
+        Ok((
+            KemCiphertext(ciphertext.to_vec()),
+            self.import_buffer_secret_impl(shared_secret),
+        ))
+    }
+
+    #[cfg(feature = "pqc")]
+    async fn kem_decapsulate(
+        &self,
+        secret_key_handle: &KemSecretKeyHandle,
+        ciphertext: &KemCiphertext,
+    ) -> Result<SecretBufferHandle> {
+        let secret = self.get_kem_secret(secret_key_handle)?;
+        let decapsulation_key = Self::import_kem_secret_key(&secret)?;
+        let ciphertext = Ciphertext::<MlKem768>::try_from(ciphertext.0.as_slice())
+            .map_err(|_| VaultError::InvalidCiphertextLength)?;
+        // ML-KEM uses an implicit rejection: an invalid ciphertext results in
+        // a shared secret which is different from the one of the peer
+        let shared_secret = decapsulation_key
+            .decapsulate(&ciphertext)
+            .map_err(|_| VaultError::InvalidCiphertextLength)?;
+        let shared_secret = BufferSecret::new(shared_secret.to_vec());
+
+        Ok(self.import_buffer_secret_impl(shared_secret))
+    }
+
     async fn import_secret_buffer(&self, buffer: Vec<u8>) -> Result<SecretBufferHandle> {
         Ok(self.import_buffer_secret_impl(BufferSecret::new(buffer)))
     }
@@ -400,3 +528,48 @@ impl VaultForSecureChannels for SoftwareVaultForSecureChannels {
             .is_some())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "pqc")]
+    #[tokio::test]
+    async fn test_kem_encapsulation() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create();
+        let secret_key = vault.generate_ephemeral_kem_secret_key().await?;
+        let public_key = vault.get_kem_public_key(&secret_key).await?;
+        assert_eq!(public_key.0.len(), crate::ML_KEM_768_PUBLIC_KEY_LENGTH);
+
+        let (ciphertext, sent_secret) = vault.kem_encapsulate(&public_key).await?;
+        assert_eq!(ciphertext.0.len(), crate::ML_KEM_768_CIPHERTEXT_LENGTH);
+        let received_secret = vault.kem_decapsulate(&secret_key, &ciphertext).await?;
+        assert_eq!(
+            vault.get_secret_buffer(&sent_secret),
+            vault.get_secret_buffer(&received_secret)
+        );
+
+        // a tampered ciphertext results in a different secret
+        let mut tampered = ciphertext.clone();
+        tampered.0[0] ^= 1;
+        let other_secret = vault.kem_decapsulate(&secret_key, &tampered).await?;
+        assert_ne!(
+            vault.get_secret_buffer(&sent_secret),
+            vault.get_secret_buffer(&other_secret)
+        );
+
+        // invalid lengths are rejected
+        assert!(vault
+            .kem_decapsulate(&secret_key, &KemCiphertext(vec![0; 10]))
+            .await
+            .is_err());
+        assert!(vault
+            .kem_encapsulate(&KemPublicKey(vec![0; 10]))
+            .await
+            .is_err());
+
+        assert!(vault.delete_ephemeral_kem_secret_key(secret_key).await?);
+        assert_eq!(vault.number_of_ephemeral_kem_secrets(), 0);
+        Ok(())
+    }
+}
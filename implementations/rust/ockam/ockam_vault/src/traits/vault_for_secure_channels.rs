@@ -1,6 +1,6 @@
 use crate::{
-    AeadSecretKeyHandle, HashOutput, HkdfOutput, SecretBufferHandle, X25519PublicKey,
-    X25519SecretKeyHandle,
+    AeadSecretKeyHandle, HashOutput, HkdfOutput, KemCiphertext, KemPublicKey, KemSecretKeyHandle,
+    SecretBufferHandle, VaultError, X25519PublicKey, X25519SecretKeyHandle,
 };
 
 use ockam_core::compat::vec::Vec;
@@ -88,6 +88,49 @@ pub trait VaultForSecureChannels: Send + Sync + 'static {
         public_key: &X25519PublicKey,
     ) -> Result<X25519SecretKeyHandle>;
 
+    /// Generate a fresh ephemeral (not persisted) ML-KEM-768 Decapsulation Key.
+    ///
+    /// The ML-KEM functions are optional: by default they return a
+    /// [`VaultError::UnsupportedOperation`] error, and secure channels created with
+    /// such a vault use a classical X25519 key exchange.
+    async fn generate_ephemeral_kem_secret_key(&self) -> Result<KemSecretKeyHandle> {
+        Err(VaultError::UnsupportedOperation.into())
+    }
+
+    /// Delete ephemeral ML-KEM-768 Decapsulation Key.
+    async fn delete_ephemeral_kem_secret_key(
+        &self,
+        _secret_key_handle: KemSecretKeyHandle,
+    ) -> Result<bool> {
+        Err(VaultError::UnsupportedOperation.into())
+    }
+
+    /// Get the [`KemPublicKey`] of the corresponding ML-KEM-768 Decapsulation Key given its Handle.
+    async fn get_kem_public_key(
+        &self,
+        _secret_key_handle: &KemSecretKeyHandle,
+    ) -> Result<KemPublicKey> {
+        Err(VaultError::UnsupportedOperation.into())
+    }
+
+    /// Perform ML-KEM-768 encapsulation: create a fresh shared secret for the owner of
+    /// the peer public key. Return the ciphertext to send to the peer and the shared secret.
+    async fn kem_encapsulate(
+        &self,
+        _peer_public_key: &KemPublicKey,
+    ) -> Result<(KemCiphertext, SecretBufferHandle)> {
+        Err(VaultError::UnsupportedOperation.into())
+    }
+
+    /// Perform ML-KEM-768 decapsulation: return the shared secret encapsulated in the ciphertext.
+    async fn kem_decapsulate(
+        &self,
+        _secret_key_handle: &KemSecretKeyHandle,
+        _ciphertext: &KemCiphertext,
+    ) -> Result<SecretBufferHandle> {
+        Err(VaultError::UnsupportedOperation.into())
+    }
+
     /// Import a Secret Buffer.
     async fn import_secret_buffer(&self, buffer: Vec<u8>) -> Result<SecretBufferHandle>;
 
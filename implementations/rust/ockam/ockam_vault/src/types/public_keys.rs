@@ -1,4 +1,5 @@
 use minicbor::{Decode, Encode};
+use ockam_core::compat::vec::Vec;
 
 /// X25519 public key length.
 pub const X25519_PUBLIC_KEY_LENGTH: usize = 32;
@@ -6,6 +7,12 @@ pub const X25519_PUBLIC_KEY_LENGTH: usize = 32;
 /// Ed25519 public key length.
 pub const EDDSA_CURVE25519_PUBLIC_KEY_LENGTH: usize = 32;
 
+/// ML-KEM-768 encapsulation key length.
+pub const ML_KEM_768_PUBLIC_KEY_LENGTH: usize = 1184;
+
+/// ML-KEM-768 ciphertext length.
+pub const ML_KEM_768_CIPHERTEXT_LENGTH: usize = 1088;
+
 /// NIST P256 public key length.
 pub const ECDSA_SHA256_CURVEP256_PUBLIC_KEY_LENGTH: usize = 65;
 
@@ -65,3 +72,17 @@ pub struct ECDSASHA256CurveP256PublicKey(
 pub struct X25519PublicKey(
     #[cbor(n(0), with = "minicbor::bytes")] pub [u8; X25519_PUBLIC_KEY_LENGTH],
 );
+
+/// ML-KEM-768 Encapsulation Key, used with an X25519 key for a hybrid post-quantum key exchange.
+///
+/// - ML-KEM as defined [here][1].
+///
+/// [1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+#[cbor(transparent)]
+pub struct KemPublicKey(#[cbor(n(0), with = "minicbor::bytes")] pub Vec<u8>);
+
+/// ML-KEM-768 Ciphertext, encapsulating a shared secret for the owner of a [`KemPublicKey`].
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+#[cbor(transparent)]
+pub struct KemCiphertext(#[cbor(n(0), with = "minicbor::bytes")] pub Vec<u8>);